@@ -98,7 +98,7 @@ impl Action {
 }
 
 pub mod actions {
-    pub(crate) const ALL: u8 = 0;
+    pub const ALL: u8 = 0;
     pub const SEARCH: u8 = 1;
     pub const DOCUMENTS_ADD: u8 = 2;
     pub const DOCUMENTS_GET: u8 = 3;
@@ -24,6 +24,8 @@ pub enum AuthControllerError {
     InvalidApiKeyName(Value),
     #[error("`uid` field value `{0}` is invalid. It should be a valid UUID v4 string or omitted.")]
     InvalidApiKeyUid(Value),
+    #[error("`{0}` field value `{1}` is invalid. It should be a positive integer or specified as a null value.")]
+    InvalidApiKeyQuota(&'static str, Value),
     #[error("API key `{0}` not found.")]
     ApiKeyNotFound(String),
     #[error("`uid` field value `{0}` is already an existing API key.")]
@@ -52,6 +54,7 @@ impl ErrorCode for AuthControllerError {
             Self::InvalidApiKeyName(_) => Code::InvalidApiKeyName,
             Self::ApiKeyNotFound(_) => Code::ApiKeyNotFound,
             Self::InvalidApiKeyUid(_) => Code::InvalidApiKeyUid,
+            Self::InvalidApiKeyQuota(_, _) => Code::InvalidApiKeyQuota,
             Self::ApiKeyAlreadyExists(_) => Code::ApiKeyAlreadyExists,
             Self::ImmutableField(_) => Code::ImmutableField,
             Self::Internal(_) => Code::Internal,
@@ -2,7 +2,7 @@ use crate::action::Action;
 use crate::error::{AuthControllerError, Result};
 use crate::store::KeyId;
 
-use meilisearch_types::index_uid::IndexUid;
+use meilisearch_types::index_uid::IndexUidPattern;
 use meilisearch_types::star_or::StarOr;
 use serde::{Deserialize, Serialize};
 use serde_json::{from_value, Value};
@@ -19,13 +19,24 @@ pub struct Key {
     pub name: Option<String>,
     pub uid: KeyId,
     pub actions: Vec<Action>,
-    pub indexes: Vec<StarOr<IndexUid>>,
+    pub indexes: Vec<StarOr<IndexUidPattern>>,
     #[serde(with = "time::serde::rfc3339::option")]
     pub expires_at: Option<OffsetDateTime>,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
     #[serde(with = "time::serde::rfc3339")]
     pub updated_at: OffsetDateTime,
+    #[serde(default)]
+    pub quota: Quota,
+}
+
+/// The limits applied to the writes made with a key, on top of the limits of the instance.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Quota {
+    /// The key can't create an index once the instance holds this number of indexes.
+    pub max_indexes: Option<u64>,
+    /// The key can't add documents to an index once they would exceed this number.
+    pub max_documents_per_index: Option<u64>,
 }
 
 impl Key {
@@ -73,6 +84,11 @@ impl Key {
             .map(parse_expiration_date)
             .ok_or(AuthControllerError::MissingParameter("expiresAt"))??;
 
+        let quota = Quota {
+            max_indexes: parse_quota(&value, "maxIndexes")?,
+            max_documents_per_index: parse_quota(&value, "maxDocumentsPerIndex")?,
+        };
+
         let created_at = OffsetDateTime::now_utc();
         let updated_at = created_at;
 
@@ -85,6 +101,7 @@ impl Key {
             expires_at,
             created_at,
             updated_at,
+            quota,
         })
     }
 
@@ -101,6 +118,14 @@ impl Key {
             self.name = des?;
         }
 
+        if value.get("maxIndexes").is_some() {
+            self.quota.max_indexes = parse_quota(&value, "maxIndexes")?;
+        }
+
+        if value.get("maxDocumentsPerIndex").is_some() {
+            self.quota.max_documents_per_index = parse_quota(&value, "maxDocumentsPerIndex")?;
+        }
+
         if value.get("uid").is_some() {
             return Err(AuthControllerError::ImmutableField("uid".to_string()));
         }
@@ -142,6 +167,7 @@ impl Key {
             expires_at: None,
             created_at: now,
             updated_at: now,
+            quota: Quota::default(),
         }
     }
 
@@ -157,10 +183,25 @@ impl Key {
             expires_at: None,
             created_at: now,
             updated_at: now,
+            quota: Quota::default(),
         }
     }
 }
 
+/// Parses the optional limit `field` of the key, which is either a positive integer or null.
+fn parse_quota(value: &Value, field: &'static str) -> Result<Option<u64>> {
+    match value.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(limit) => match limit.as_u64() {
+            Some(limit) if limit > 0 => Ok(Some(limit)),
+            _ => Err(AuthControllerError::InvalidApiKeyQuota(
+                field,
+                limit.clone(),
+            )),
+        },
+    }
+}
+
 fn parse_expiration_date(value: &Value) -> Result<Option<OffsetDateTime>> {
     match value {
         Value::String(string) => OffsetDateTime::parse(string, &Rfc3339)
@@ -10,18 +10,22 @@ use std::path::Path;
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
 pub use action::{actions, Action};
 use error::{AuthControllerError, Result};
-pub use key::Key;
+pub use key::{Key, Quota};
+use meilisearch_types::index_uid::{namespace, IndexUidPattern};
 use meilisearch_types::star_or::StarOr;
 use store::generate_key_as_base64;
 pub use store::open_auth_store_env;
 use store::HeedAuthStore;
 
+/// The reserved field of the documents listing the tags of the tenants allowed to see them.
+pub const ACL_FIELD: &str = "_acl";
+
 #[derive(Clone)]
 pub struct AuthController {
     store: Arc<HeedAuthStore>,
@@ -92,18 +96,7 @@ impl AuthController {
         if !key.indexes.iter().any(|i| i == &StarOr::Star) {
             filters.search_rules = match search_rules {
                 // Intersect search_rules with parent key authorized indexes.
-                Some(search_rules) => SearchRules::Map(
-                    key.indexes
-                        .into_iter()
-                        .filter_map(|index| {
-                            search_rules.get_index_search_rules(index.deref()).map(
-                                |index_search_rules| {
-                                    (String::from(index), Some(index_search_rules))
-                                },
-                            )
-                        })
-                        .collect(),
-                ),
+                Some(search_rules) => search_rules.restricted_to(&key.indexes),
                 None => SearchRules::Set(key.indexes.into_iter().map(String::from).collect()),
             };
         } else if let Some(search_rules) = search_rules {
@@ -114,6 +107,7 @@ impl AuthController {
             .actions
             .iter()
             .any(|&action| action == Action::IndexesAdd || action == Action::All);
+        filters.quota = key.quota;
 
         Ok(filters)
     }
@@ -155,10 +149,23 @@ impl AuthController {
             // check if the key has access to all indexes.
             .get_expiration_date(uid, action, None)?
             .or(match index {
-                // else check if the key has access to the requested index.
+                // else check if the key has access to the requested index,
                 Some(index) => {
-                    self.store
+                    match self
+                        .store
                         .get_expiration_date(uid, action, Some(index.as_bytes()))?
+                    {
+                        Some(expiration) => Some(expiration),
+                        // or to its namespace.
+                        None => match namespace_pattern(index) {
+                            Some(pattern) => self.store.get_expiration_date(
+                                uid,
+                                action,
+                                Some(pattern.as_bytes()),
+                            )?,
+                            None => None,
+                        },
+                    }
                 }
                 // or to any index if no index has been requested.
                 None => self.store.prefix_first_expiration_date(uid, action)?,
@@ -176,6 +183,9 @@ impl AuthController {
 pub struct AuthFilter {
     pub search_rules: SearchRules,
     pub allow_index_creation: bool,
+    pub quota: Quota,
+    /// The hash of the tenant token used to authenticate the request, if any.
+    pub tenant: Option<String>,
 }
 
 impl Default for AuthFilter {
@@ -183,6 +193,8 @@ impl Default for AuthFilter {
         Self {
             search_rules: SearchRules::default(),
             allow_index_creation: true,
+            quota: Quota::default(),
+            tenant: None,
         }
     }
 }
@@ -204,15 +216,23 @@ impl Default for SearchRules {
 impl SearchRules {
     pub fn is_index_authorized(&self, index: &str) -> bool {
         match self {
-            Self::Set(set) => set.contains("*") || set.contains(index),
-            Self::Map(map) => map.contains_key("*") || map.contains_key(index),
+            Self::Set(set) => {
+                set.contains("*")
+                    || set.contains(index)
+                    || namespace_pattern(index).map_or(false, |pattern| set.contains(&pattern))
+            }
+            Self::Map(map) => {
+                map.contains_key("*")
+                    || map.contains_key(index)
+                    || namespace_pattern(index).map_or(false, |pattern| map.contains_key(&pattern))
+            }
         }
     }
 
     pub fn get_index_search_rules(&self, index: &str) -> Option<IndexSearchRules> {
         match self {
-            Self::Set(set) => {
-                if set.contains("*") || set.contains(index) {
+            Self::Set(_) => {
+                if self.is_index_authorized(index) {
                     Some(IndexSearchRules::default())
                 } else {
                     None
@@ -220,10 +240,75 @@ impl SearchRules {
             }
             Self::Map(map) => map
                 .get(index)
+                .or_else(|| namespace_pattern(index).and_then(|pattern| map.get(&pattern)))
                 .or_else(|| map.get("*"))
                 .map(|isr| isr.clone().unwrap_or_default()),
         }
     }
+
+    /// Keeps the rules of the indexes authorized by `indexes`. The indexes authorized through a
+    /// namespace keep their own rules.
+    fn restricted_to(self, indexes: &[StarOr<IndexUidPattern>]) -> Self {
+        let mut rules: HashMap<_, _> = indexes
+            .iter()
+            .filter_map(|index| {
+                self.get_index_search_rules(index.deref())
+                    .map(|index_search_rules| (index.deref().to_string(), Some(index_search_rules)))
+            })
+            .collect();
+        for (index, index_search_rules) in self {
+            let authorized = indexes
+                .iter()
+                .any(|pattern| matches!(pattern, StarOr::Other(p) if p.matches(&index)));
+            if authorized {
+                rules.insert(index, Some(index_search_rules));
+            }
+        }
+        Self::Map(rules)
+    }
+
+    /// Restricts the searches of every index to the documents whose `_acl` field shares a tag with
+    /// `acl`, on top of the filters of the rules.
+    pub fn with_acl(self, acl: &[String]) -> Self {
+        let acl_filter = acl_filter(acl);
+        Self::Map(
+            self.into_iter()
+                .map(|(index, mut rules)| {
+                    let mut filter = match rules.filter.take() {
+                        Some(Value::Array(filter)) => filter,
+                        Some(filter) => vec![filter],
+                        None => Vec::new(),
+                    };
+                    filter.extend(acl_filter.iter().cloned());
+                    rules.filter = Some(Value::Array(filter));
+                    (index, Some(rules))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Returns the `<namespace>/*` pattern targeting every index of the namespace of `index`, if it
+/// belongs to one.
+fn namespace_pattern(index: &str) -> Option<String> {
+    namespace(index).map(|namespace| format!("{}/*", namespace))
+}
+
+/// The conditions, that must all hold, of the documents sharing a tag with `acl`.
+fn acl_filter(acl: &[String]) -> Vec<Value> {
+    if acl.is_empty() {
+        // no document has a tag both equal and not equal to a value
+        return vec![
+            json!(format!("{} = \"{}\"", ACL_FIELD, ACL_FIELD)),
+            json!(format!("{} != \"{}\"", ACL_FIELD, ACL_FIELD)),
+        ];
+    }
+
+    let tags = acl
+        .iter()
+        .map(|tag| json!(format!("{} = \"{}\"", ACL_FIELD, tag)))
+        .collect();
+    vec![Value::Array(tags)]
 }
 
 impl IntoIterator for SearchRules {
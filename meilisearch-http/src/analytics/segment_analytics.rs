@@ -365,10 +365,21 @@ pub struct SearchAggregator {
     // q
     // The maximum number of terms in a q request
     max_terms_number: usize,
+    attributes_to_search_on: bool,
+    attribute_weights: bool,
+    typo_tolerance: bool,
+    distinct: bool,
+    matching_strategy: HashMap<String, usize>,
+
+    // vector
+    // The maximum number of dimensions of a query vector
+    max_vector_size: usize,
+    with_hybrid: bool,
 
     // pagination
     max_limit: usize,
     max_offset: usize,
+    finite_pagination: usize,
 
     // formatting
     highlight_pre_tag: bool,
@@ -422,9 +433,26 @@ impl SearchAggregator {
         if let Some(ref q) = query.q {
             ret.max_terms_number = q.split_whitespace().count();
         }
+        ret.attributes_to_search_on = query.attributes_to_search_on.is_some();
+        ret.attribute_weights = query.attribute_weights.is_some();
+        ret.typo_tolerance = query.typo_tolerance.is_some();
+        ret.distinct = query.distinct.is_some();
+        ret.matching_strategy
+            .insert(format!("{:?}", query.matching_strategy), 1);
+
+        if let Some(ref vector) = query.vector {
+            ret.max_vector_size = vector.len();
+        }
+        ret.with_hybrid = query.hybrid.is_some();
 
-        ret.max_limit = query.limit;
-        ret.max_offset = query.offset.unwrap_or_default();
+        if query.is_finite_pagination() {
+            ret.max_limit = query.hits_per_page.unwrap_or(query.limit);
+            ret.max_offset = query.page.unwrap_or(1).saturating_sub(1) * ret.max_limit;
+            ret.finite_pagination = 1;
+        } else {
+            ret.max_limit = query.limit;
+            ret.max_offset = query.offset.unwrap_or_default();
+        }
 
         ret.highlight_pre_tag = query.highlight_pre_tag != DEFAULT_HIGHLIGHT_PRE_TAG();
         ret.highlight_post_tag = query.highlight_post_tag != DEFAULT_HIGHLIGHT_POST_TAG();
@@ -476,9 +504,23 @@ impl SearchAggregator {
         }
         // q
         self.max_terms_number = self.max_terms_number.max(other.max_terms_number);
+        self.attributes_to_search_on |= other.attributes_to_search_on;
+        self.attribute_weights |= other.attribute_weights;
+        self.typo_tolerance |= other.typo_tolerance;
+        self.distinct |= other.distinct;
+        for (key, value) in other.matching_strategy.into_iter() {
+            let matching_strategy = self.matching_strategy.entry(key).or_insert(0);
+            *matching_strategy = matching_strategy.saturating_add(value);
+        }
+        // vector
+        self.max_vector_size = self.max_vector_size.max(other.max_vector_size);
+        self.with_hybrid |= other.with_hybrid;
         // pagination
         self.max_limit = self.max_limit.max(other.max_limit);
         self.max_offset = self.max_offset.max(other.max_offset);
+        self.finite_pagination = self
+            .finite_pagination
+            .saturating_add(other.finite_pagination);
 
         self.highlight_pre_tag |= other.highlight_pre_tag;
         self.highlight_post_tag |= other.highlight_post_tag;
@@ -517,10 +559,20 @@ impl SearchAggregator {
                 },
                 "q": {
                    "max_terms_number": self.max_terms_number,
+                   "attributes_to_search_on": self.attributes_to_search_on,
+                   "attribute_weights": self.attribute_weights,
+                   "typo_tolerance": self.typo_tolerance,
+                   "distinct": self.distinct,
+                   "most_used_matching_strategy": self.matching_strategy.iter().max_by_key(|(_, v)| *v).map(|(k, _)| json!(k)).unwrap_or_else(|| json!(null)),
+                },
+                "vector": {
+                    "max_vector_size": self.max_vector_size,
+                    "with_hybrid": self.with_hybrid,
                 },
                 "pagination": {
                    "max_limit": self.max_limit,
                    "max_offset": self.max_offset,
+                   "most_used_navigation": if self.finite_pagination > (self.total_received / 2) { "exhaustive" } else { "estimated" },
                 },
                 "formatting": {
                     "highlight_pre_tag": self.highlight_pre_tag,
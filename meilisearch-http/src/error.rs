@@ -12,6 +12,24 @@ pub enum MeilisearchHttpError {
         .1.iter().map(|s| format!("`{}`", s)).collect::<Vec<_>>().join(", ")
     )]
     InvalidContentType(String, Vec<String>),
+    #[error("The synonyms file is malformed. {0}")]
+    MalformedSynonyms(String),
+    #[error("The csv delimiter must be a single ASCII character, found `{0}`.")]
+    InvalidCsvDelimiter(char),
+    #[error("The `csvDelimiter` parameter can only be used with the `text/csv` Content-Type.")]
+    CsvDelimiterWithoutCsv,
+    #[error(
+        "The `recordPath` parameter is required along with the `application/xml` Content-Type."
+    )]
+    MissingXmlRecordPath,
+    #[error("The record path `{0}` is invalid. It must be the names of the elements leading to a record from the root, e.g. `/products/product`.")]
+    InvalidXmlRecordPath(String),
+    #[error(
+        "The `recordPath` parameter can only be used with the `application/xml` Content-Type."
+    )]
+    XmlRecordPathWithoutXml,
+    #[error("The `mergeNested` parameter can only be used when updating documents.")]
+    MergeNestedWithoutUpdate,
 }
 
 impl ErrorCode for MeilisearchHttpError {
@@ -19,6 +37,13 @@ impl ErrorCode for MeilisearchHttpError {
         match self {
             MeilisearchHttpError::MissingContentType(_) => Code::MissingContentType,
             MeilisearchHttpError::InvalidContentType(_, _) => Code::InvalidContentType,
+            MeilisearchHttpError::MalformedSynonyms(_) => Code::MalformedPayload,
+            MeilisearchHttpError::InvalidCsvDelimiter(_)
+            | MeilisearchHttpError::CsvDelimiterWithoutCsv => Code::InvalidDocumentCsvDelimiter,
+            MeilisearchHttpError::MissingXmlRecordPath
+            | MeilisearchHttpError::InvalidXmlRecordPath(_)
+            | MeilisearchHttpError::XmlRecordPathWithoutXml => Code::InvalidDocumentXmlRecordPath,
+            MeilisearchHttpError::MergeNestedWithoutUpdate => Code::InvalidDocumentMergeNested,
         }
     }
 }
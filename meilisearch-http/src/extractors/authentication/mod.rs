@@ -1,4 +1,4 @@
-mod error;
+pub mod error;
 
 use std::marker::PhantomData;
 use std::ops::Deref;
@@ -10,6 +10,7 @@ use futures::future::err;
 use futures::Future;
 use meilisearch_auth::{AuthController, AuthFilter};
 use meilisearch_types::error::{Code, ResponseError};
+use serde::Deserialize;
 
 pub struct GuardedData<P, D> {
     data: D,
@@ -101,12 +102,18 @@ impl<P: Policy + 'static, D: 'static + Clone> FromRequest for GuardedData<P, D>
                 Some(mut type_token) => match type_token.next() {
                     Some("Bearer") => {
                         // TODO: find a less hardcoded way?
-                        let index = req.match_info().get("index_uid");
+                        // the uid is decoded like in the routes, the `/` of a namespaced uid
+                        // being sent as `%2F`.
+                        let index = req
+                            .match_info()
+                            .load::<IndexUidParam>()
+                            .ok()
+                            .map(|param| param.index_uid);
                         match type_token.next() {
                             Some(token) => Box::pin(Self::auth_bearer(
                                 auth,
                                 token.to_string(),
-                                index.map(String::from),
+                                index,
                                 req.app_data::<D>().cloned(),
                             )),
                             None => Box::pin(err(AuthenticationError::InvalidToken.into())),
@@ -123,6 +130,11 @@ impl<P: Policy + 'static, D: 'static + Clone> FromRequest for GuardedData<P, D>
     }
 }
 
+#[derive(Deserialize)]
+struct IndexUidParam {
+    index_uid: String,
+}
+
 pub trait Policy {
     fn authenticate(auth: AuthController, token: &str, index: Option<&str>) -> Option<AuthFilter>;
 }
@@ -130,6 +142,7 @@ pub trait Policy {
 pub mod policies {
     use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
     use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
     use time::OffsetDateTime;
     use uuid::Uuid;
 
@@ -163,6 +176,23 @@ pub mod policies {
         matches!(action, KEYS_GET | KEYS_CREATE | KEYS_UPDATE | KEYS_DELETE)
     }
 
+    /// Only authenticates the master key, for the administration routes that no API key, not even
+    /// one with all the actions, should be able to reach.
+    pub struct MasterPolicy;
+
+    impl Policy for MasterPolicy {
+        fn authenticate(
+            auth: AuthController,
+            token: &str,
+            _index: Option<&str>,
+        ) -> Option<AuthFilter> {
+            // if master key is None the instance is unprotected.
+            auth.get_master_key()
+                .map_or(true, |mk| mk == token)
+                .then(AuthFilter::default)
+        }
+    }
+
     pub struct ActionPolicy<const A: u8>;
 
     impl<const A: u8> Policy for ActionPolicy<A> {
@@ -198,6 +228,21 @@ pub mod policies {
         }
     }
 
+    /// Authenticates the keys allowed to perform both actions, for the routes that can do the
+    /// work of both. The filters are the ones of the action `A`.
+    pub struct ActionsPolicy<const A: u8, const B: u8>;
+
+    impl<const A: u8, const B: u8> Policy for ActionsPolicy<A, B> {
+        fn authenticate(
+            auth: AuthController,
+            token: &str,
+            index: Option<&str>,
+        ) -> Option<AuthFilter> {
+            ActionPolicy::<B>::authenticate(auth.clone(), token, index)?;
+            ActionPolicy::<A>::authenticate(auth, token, index)
+        }
+    }
+
     impl<const A: u8> ActionPolicy<A> {
         fn authenticate_tenant_token(
             auth: &AuthController,
@@ -235,9 +280,19 @@ pub mod policies {
                     }
                 }
 
-                return auth
+                let mut filters = auth
                     .get_key_filters(uid, Some(data.claims.search_rules))
-                    .ok();
+                    .ok()?;
+                if let Some(acl) = data.claims.acl {
+                    // the tags are quoted in the filter restricting the searches
+                    if acl.iter().any(|tag| tag.contains('"')) {
+                        return None;
+                    }
+                    filters.search_rules = filters.search_rules.with_acl(&acl);
+                }
+                filters.tenant = Some(format!("{:x}", Sha256::digest(token)));
+
+                return Some(filters);
             }
 
             None
@@ -250,5 +305,8 @@ pub mod policies {
         search_rules: SearchRules,
         exp: Option<i64>,
         api_key_uid: Uuid,
+        /// The tags of the tenant, the searches only return the documents whose `_acl` field has
+        /// one of them.
+        acl: Option<Vec<String>>,
     }
 }
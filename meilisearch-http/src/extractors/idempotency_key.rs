@@ -0,0 +1,47 @@
+use actix_web::{dev, FromRequest, HttpRequest};
+use futures::future::{ready, Ready};
+use meilisearch_types::error::{Code, ResponseError};
+
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// The keys are stored along with the uid of the index, whose length is limited by the maximum
+/// length of a key of the task database.
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 100;
+
+/// The `Idempotency-Key` header of a write request. A request retried with the key of a registered
+/// task returns that task instead of registering a new one.
+pub struct IdempotencyKey(Option<String>);
+
+impl IdempotencyKey {
+    pub fn into_inner(self) -> Option<String> {
+        self.0
+    }
+}
+
+impl FromRequest for IdempotencyKey {
+    type Error = ResponseError;
+
+    type Future = Ready<Result<IdempotencyKey, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut dev::Payload) -> Self::Future {
+        let key = match req.headers().get(IDEMPOTENCY_KEY_HEADER) {
+            Some(key) => match key.to_str() {
+                Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LENGTH => {
+                    Some(key.to_string())
+                }
+                _ => {
+                    return ready(Err(ResponseError::from_msg(
+                        format!(
+                            "The `{}` header must be made of 1 to {} visible ASCII characters.",
+                            IDEMPOTENCY_KEY_HEADER, MAX_IDEMPOTENCY_KEY_LENGTH
+                        ),
+                        Code::InvalidIdempotencyKey,
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        ready(Ok(IdempotencyKey(key)))
+    }
+}
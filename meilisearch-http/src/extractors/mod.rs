@@ -1,3 +1,4 @@
+pub mod idempotency_key;
 pub mod payload;
 #[macro_use]
 pub mod authentication;
@@ -47,7 +47,67 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<MeiliSearch> {
         // dump
         .set_ignore_missing_dump(opt.ignore_missing_dump)
         .set_ignore_dump_if_db_exists(opt.ignore_dump_if_db_exists)
-        .set_dump_dst(opt.dumps_dir.clone());
+        .set_dump_dst(opt.dumps_dir.clone())
+        .set_disable_auto_index_creation(opt.disable_auto_index_creation);
+
+    if let Some(ref path) = opt.task_db_path {
+        meilisearch.set_task_db_path(path.clone());
+    }
+
+    if let Some(size) = opt.task_db_size {
+        meilisearch.set_task_db_size(size.get_bytes() as usize);
+    }
+
+    if let Some(ref path) = opt.task_archive_dir {
+        meilisearch
+            .set_task_archive_dir(path.clone())
+            .set_task_archive_after(Duration::from_secs(opt.task_archive_after_sec));
+    }
+
+    if let Some(interval) = opt.documents_expiration_interval_sec {
+        meilisearch.set_documents_expiration_interval(Duration::from_secs(interval));
+    }
+
+    if opt.update_file_retention_sec != 0 {
+        meilisearch.set_update_files_retention(Duration::from_secs(opt.update_file_retention_sec));
+    }
+
+    if opt.upload_retention_sec != 0 {
+        meilisearch.set_upload_retention(Duration::from_secs(opt.upload_retention_sec));
+    }
+    // the chunks of an upload add up to a single payload
+    meilisearch.set_max_upload_size(opt.http_payload_size_limit.get_bytes() as u64);
+
+    if let Some(max) = opt.max_open_indexes {
+        meilisearch.set_max_open_indexes(max);
+    }
+
+    if let Some(max) = opt.max_indexes {
+        meilisearch.set_max_indexes(max);
+    }
+
+    if let Some(max) = opt.max_documents_per_index {
+        meilisearch.set_max_documents_per_index(max);
+    }
+
+    if opt.deleted_index_retention_sec != 0 {
+        meilisearch
+            .set_deleted_index_retention(Duration::from_secs(opt.deleted_index_retention_sec));
+    }
+
+    if let Some(size) = opt.search_log_size {
+        meilisearch.set_search_log_size(size);
+    }
+
+    if let Some(max) = opt.max_concurrent_searches {
+        meilisearch
+            .set_max_concurrent_searches(max)
+            .set_search_queue_size(opt.search_queue_size);
+    }
+
+    if let Some(size) = opt.search_cache_size {
+        meilisearch.set_search_cache_size(size);
+    }
 
     if let Some(ref path) = opt.import_snapshot {
         meilisearch.set_import_snapshot(path.clone());
@@ -9,6 +9,8 @@ use meilisearch_http::analytics;
 use meilisearch_http::analytics::Analytics;
 use meilisearch_http::{create_app, setup_meilisearch, Opt};
 use meilisearch_lib::MeiliSearch;
+#[cfg(unix)]
+use tokio::signal::unix::{signal, SignalKind};
 
 #[cfg(target_os = "linux")]
 #[global_allocator]
@@ -74,6 +76,7 @@ async fn run_http(
 ) -> anyhow::Result<()> {
     let _enable_dashboard = &opt.env == "development";
     let opt_clone = opt.clone();
+    let meilisearch = data.clone();
     let http_server = HttpServer::new(move || {
         create_app!(
             data,
@@ -87,14 +90,37 @@ async fn run_http(
     .disable_signals()
     .keep_alive(KeepAlive::Os);
 
-    if let Some(config) = opt.get_ssl_config()? {
-        http_server
-            .bind_rustls(opt.http_addr, config)?
-            .run()
-            .await?;
+    let server = if let Some(config) = opt.get_ssl_config()? {
+        http_server.bind_rustls(opt.http_addr, config)?.run()
     } else {
-        http_server.bind(&opt.http_addr)?.run().await?;
+        http_server.bind(&opt.http_addr)?.run()
+    };
+
+    // A SIGTERM triggers a graceful drain of the instance.
+    #[cfg(unix)]
+    {
+        let meilisearch = meilisearch.clone();
+        let mut sigterm = signal(SignalKind::terminate())?;
+        actix_web::rt::spawn(async move {
+            if sigterm.recv().await.is_some() {
+                log::info!("SIGTERM received, draining the instance");
+                if let Err(e) = meilisearch.drain().await {
+                    log::error!("error while draining the instance: {}", e);
+                }
+            }
+        });
     }
+
+    // Once the instance is drained, the server is stopped and the process exits.
+    let handle = server.handle();
+    actix_web::rt::spawn(async move {
+        meilisearch.wait_drained().await;
+        log::info!("instance drained, shutting down");
+        handle.stop(true).await;
+    });
+
+    server.await?;
+
     Ok(())
 }
 
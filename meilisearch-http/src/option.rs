@@ -51,10 +51,100 @@ pub struct Opt {
     #[clap(long, env = "MEILI_MAX_INDEX_SIZE", default_value = "100 GiB")]
     pub max_index_size: Byte,
 
+    /// The maximum number of indexes kept open at the same time. Past it, the least recently
+    /// used indexes are closed, and opened again when they're needed. The indexes used by a
+    /// search or a task are only closed once they're done. Every index is kept open if unset.
+    #[clap(long, env = "MEILI_MAX_OPEN_INDEXES")]
+    pub max_open_indexes: Option<usize>,
+
+    /// The maximum number of indexes of the instance. Past it, the index creations fail. The
+    /// API keys can set a lower limit on the indexes they create.
+    #[clap(long, env = "MEILI_MAX_INDEXES")]
+    pub max_indexes: Option<u64>,
+
+    /// The maximum number of documents of each index. Past it, the document additions fail. The
+    /// API keys can set a lower limit on the documents they add.
+    #[clap(long, env = "MEILI_MAX_DOCUMENTS_PER_INDEX")]
+    pub max_documents_per_index: Option<u64>,
+
     /// The maximum size, in bytes, of the update lmdb database directory
     #[clap(long, env = "MEILI_MAX_TASK_DB_SIZE", default_value = "100 GiB")]
     pub max_task_db_size: Byte,
 
+    /// The destination where the task database must be created, if it should live outside of
+    /// the main database directory.
+    #[clap(long, env = "MEILI_TASK_DB_PATH")]
+    pub task_db_path: Option<PathBuf>,
+
+    /// The maximum size, in bytes, of the task database when it lives in its own directory, see
+    /// `--task-db-path`. Defaults to `--max-task-db-size`.
+    #[clap(long, env = "MEILI_TASK_DB_SIZE")]
+    pub task_db_size: Option<Byte>,
+
+    /// The directory where the finished tasks are archived, as NDJSON files, once they are older
+    /// than `--task-archive-after-sec`. Tasks are never archived if unset.
+    #[clap(long, env = "MEILI_TASK_ARCHIVE_DIR")]
+    pub task_archive_dir: Option<PathBuf>,
+
+    /// Defines the time, in seconds, after which a finished task is archived.
+    #[clap(long, env = "MEILI_TASK_ARCHIVE_AFTER_SEC", default_value = "604800")] // 7d
+    pub task_archive_after_sec: u64,
+
+    /// Defines the interval, in seconds, at which the documents whose expiration date is over are
+    /// deleted. The documents never expire if unset. The expiration date must be a filterable
+    /// attribute, and only the documents of the open indexes are deleted.
+    #[clap(long, env = "MEILI_DOCUMENTS_EXPIRATION_INTERVAL_SEC")]
+    pub documents_expiration_interval_sec: Option<u64>,
+
+    /// Defines the time, in seconds, during which the update file of a document addition is kept
+    /// once its task is processed, to replay or debug the addition. The update files are deleted
+    /// as soon as their task is processed if set to 0, and when their task is archived at the
+    /// latest. The update files are checked at most once a minute, so they can outlive a shorter
+    /// retention by up to a minute.
+    #[clap(long, env = "MEILI_UPDATE_FILE_RETENTION_SEC", default_value = "0")]
+    pub update_file_retention_sec: u64,
+
+    /// Defines the time, in seconds, after which an upload of documents that received no chunk
+    /// is deleted. The abandoned uploads are never deleted if set to 0.
+    #[clap(long, env = "MEILI_UPLOAD_RETENTION_SEC", default_value = "86400")]
+    pub upload_retention_sec: u64,
+
+    /// Defines the time, in seconds, during which a deleted index is kept in the trash, from
+    /// which it can be restored with `POST /indexes/{uid}/restore`. The indexes are deleted for
+    /// good right away if set to 0.
+    #[clap(
+        long,
+        env = "MEILI_DELETED_INDEX_RETENTION_SEC",
+        default_value = "604800"
+    )]
+    pub deleted_index_retention_sec: u64,
+
+    /// Records the last searches performed on the instance, up to this number, so they can be
+    /// read from the `/analytics/searches` route. The searches aren't recorded if unset.
+    #[clap(long, env = "MEILI_SEARCH_LOG_SIZE")]
+    pub search_log_size: Option<usize>,
+
+    /// The maximum number of searches executed at the same time. The searches aren't limited if
+    /// unset.
+    #[clap(long, env = "MEILI_MAX_CONCURRENT_SEARCHES")]
+    pub max_concurrent_searches: Option<usize>,
+
+    /// The number of searches waiting for a slot once `--max-concurrent-searches` is reached. The
+    /// searches received while the queue is full are rejected with a `503`.
+    #[clap(long, env = "MEILI_SEARCH_QUEUE_SIZE", default_value = "1000")]
+    pub search_queue_size: usize,
+
+    /// Caches the results of the last searches, up to this number, until a task of their index
+    /// finishes. The search results aren't cached if unset.
+    #[clap(long, env = "MEILI_SEARCH_CACHE_SIZE")]
+    pub search_cache_size: Option<usize>,
+
+    /// Makes the document additions and settings updates targeting an index that doesn't exist
+    /// fail, instead of creating the index. The keys without the `indexes.create` action never
+    /// create an index implicitly.
+    #[clap(long, env = "MEILI_DISABLE_AUTO_INDEX_CREATION")]
+    pub disable_auto_index_creation: bool,
+
     /// The maximum size, in bytes, of accepted JSON payloads
     #[clap(long, env = "MEILI_HTTP_PAYLOAD_SIZE_LIMIT", default_value = "100 MB")]
     pub http_payload_size_limit: Byte,
@@ -0,0 +1,105 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::error::AuthenticationError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(SeqHandler(list_aliases))))
+        .service(
+            web::resource("/{alias}")
+                .route(web::get().to(SeqHandler(get_alias)))
+                .route(web::put().to(SeqHandler(set_alias)))
+                .route(web::delete().to(SeqHandler(delete_alias))),
+        );
+}
+
+pub async fn list_aliases(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let search_rules = &meilisearch.filters().search_rules;
+    let aliases: Vec<_> = meilisearch
+        .list_aliases()
+        .await?
+        .into_iter()
+        .filter(|alias| search_rules.is_index_authorized(&alias.alias))
+        .collect();
+
+    debug!("returns: {:?}", aliases);
+    Ok(HttpResponse::Ok().json(aliases))
+}
+
+pub async fn get_alias(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let alias = path.into_inner();
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(&alias)
+    {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    let alias = meilisearch.get_alias(alias).await?;
+
+    debug!("returns: {:?}", alias);
+    Ok(HttpResponse::Ok().json(alias))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SetAliasPayload {
+    index_uid: String,
+}
+
+/// Creates the alias, or retargets it to another index at once: the searches and documents
+/// requests on the alias are served by either the previous or the new index, never by none.
+pub async fn set_alias(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<SetAliasPayload>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let alias = path.into_inner();
+    let SetAliasPayload { index_uid } = body.into_inner();
+    debug!("called with params: {:?}, {:?}", alias, index_uid);
+
+    analytics.publish("Alias Set".to_string(), json!({}), Some(&req));
+
+    let search_rules = &meilisearch.filters().search_rules;
+    if !search_rules.is_index_authorized(&alias) || !search_rules.is_index_authorized(&index_uid) {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    let alias = meilisearch.set_alias(alias, index_uid).await?;
+
+    debug!("returns: {:?}", alias);
+    Ok(HttpResponse::Ok().json(alias))
+}
+
+pub async fn delete_alias(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let alias = path.into_inner();
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(&alias)
+    {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    meilisearch.delete_alias(alias).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,105 @@
+use actix_web::{web, HttpResponse};
+use log::debug;
+use meilisearch_lib::search_log::SearchLogFilter;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::{Code, ResponseError};
+use meilisearch_types::index_uid::IndexUid;
+use meilisearch_types::star_or::StarOr;
+use serde::Deserialize;
+use serde_cs::vec::CS;
+use serde_json::json;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::{fold_star_or, Pagination};
+
+const DEFAULT_LIMIT: fn() -> usize = || 20;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/searches").route(web::get().to(SeqHandler(get_searches))))
+        .service(web::resource("/top-searches").route(web::get().to(SeqHandler(get_top_searches))))
+        .service(
+            web::resource("/no-results").route(web::get().to(SeqHandler(get_no_result_searches))),
+        );
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SearchAnalyticsQuery {
+    from: Option<String>,
+    to: Option<String>,
+    index_uid: Option<CS<StarOr<IndexUid>>>,
+    #[serde(default = "DEFAULT_LIMIT")]
+    limit: usize,
+}
+
+fn parse_date_param(
+    name: &str,
+    value: Option<String>,
+) -> Result<Option<OffsetDateTime>, ResponseError> {
+    value
+        .map(|value| {
+            OffsetDateTime::parse(&value, &Rfc3339).map_err(|_| {
+                ResponseError::from_msg(
+                    format!(
+                        "`{}` is not a valid RFC 3339 date for the `{}` parameter.",
+                        value, name
+                    ),
+                    Code::BadRequest,
+                )
+            })
+        })
+        .transpose()
+}
+
+impl SearchAnalyticsQuery {
+    fn filter(self) -> Result<(SearchLogFilter, usize), ResponseError> {
+        let filter = SearchLogFilter {
+            from: parse_date_param("from", self.from)?,
+            to: parse_date_param("to", self.to)?,
+            index_uids: self
+                .index_uid
+                .and_then(fold_star_or)
+                .map(|uids: Vec<IndexUid>| uids.into_iter().map(IndexUid::into_inner).collect()),
+        };
+        Ok((filter, self.limit))
+    }
+}
+
+/// Returns the searches recorded by the search log, the most recent first.
+pub async fn get_searches(
+    meilisearch: GuardedData<ActionPolicy<{ actions::ALL }>, MeiliSearch>,
+    paginate: web::Query<Pagination>,
+) -> Result<HttpResponse, ResponseError> {
+    let searches = meilisearch.search_log()?.searches();
+    let searches = paginate.auto_paginate_sized(searches.into_iter());
+
+    debug!("returns: {:?}", searches);
+    Ok(HttpResponse::Ok().json(searches))
+}
+
+/// Returns the most searched queries of the search log.
+pub async fn get_top_searches(
+    meilisearch: GuardedData<ActionPolicy<{ actions::ALL }>, MeiliSearch>,
+    params: web::Query<SearchAnalyticsQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let (filter, limit) = params.into_inner().filter()?;
+    let results = meilisearch.search_log()?.top_searches(&filter, limit);
+
+    debug!("returns: {:?}", results);
+    Ok(HttpResponse::Ok().json(json!({ "results": results })))
+}
+
+/// Returns the most searched queries of the search log among the ones that returned no hits.
+pub async fn get_no_result_searches(
+    meilisearch: GuardedData<ActionPolicy<{ actions::ALL }>, MeiliSearch>,
+    params: web::Query<SearchAnalyticsQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let (filter, limit) = params.into_inner().filter()?;
+    let results = meilisearch.search_log()?.no_result_searches(&filter, limit);
+
+    debug!("returns: {:?}", results);
+    Ok(HttpResponse::Ok().json(json!({ "results": results })))
+}
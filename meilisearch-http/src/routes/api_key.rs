@@ -139,6 +139,8 @@ struct KeyView {
     created_at: OffsetDateTime,
     #[serde(serialize_with = "time::serde::rfc3339::serialize")]
     updated_at: OffsetDateTime,
+    max_indexes: Option<u64>,
+    max_documents_per_index: Option<u64>,
 }
 
 impl KeyView {
@@ -155,6 +157,8 @@ impl KeyView {
             expires_at: key.expires_at,
             created_at: key.created_at,
             updated_at: key.updated_at,
+            max_indexes: key.quota.max_indexes,
+            max_documents_per_index: key.quota.max_documents_per_index,
         }
     }
 }
@@ -0,0 +1,48 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(SeqHandler(get_drain_status)))
+            .route(web::post().to(SeqHandler(start_drain))),
+    );
+}
+
+pub async fn get_drain_status(
+    meilisearch: GuardedData<ActionPolicy<{ actions::ALL }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let res = json!({ "status": meilisearch.drain_status() });
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Ok().json(res))
+}
+
+/// Starts a graceful drain: the instance stops accepting new tasks, and exits once the batch being
+/// processed is finished and the task store is flushed.
+pub async fn start_drain(
+    meilisearch: GuardedData<ActionPolicy<{ actions::ALL }>, MeiliSearch>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Drain Started".to_string(), json!({}), Some(&req));
+
+    let meilisearch = MeiliSearch::clone(&meilisearch);
+    tokio::task::spawn_local(async move {
+        if let Err(e) = meilisearch.drain().await {
+            log::error!("error while draining the instance: {}", e);
+        }
+    });
+
+    let res = json!({ "status": "draining" });
+
+    debug!("returns: {:?}", res);
+    Ok(HttpResponse::Accepted().json(res))
+}
@@ -0,0 +1,88 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::error::AuthenticationError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(SeqHandler(list_templates))))
+        .service(
+            web::resource("/{pattern}")
+                .route(web::get().to(SeqHandler(get_template)))
+                .route(web::put().to(SeqHandler(set_template)))
+                .route(web::delete().to(SeqHandler(delete_template))),
+        );
+}
+
+pub async fn list_templates(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let templates = meilisearch.list_templates().await?;
+
+    debug!("returns: {:?}", templates);
+    Ok(HttpResponse::Ok().json(templates))
+}
+
+pub async fn get_template(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let template = meilisearch.get_template(path.into_inner()).await?;
+
+    debug!("returns: {:?}", template);
+    Ok(HttpResponse::Ok().json(template))
+}
+
+/// Creates or replaces the template. A key restricted to some indexes can only manage the templates
+/// whose pattern is one of them, a pattern with a wildcard requires a key authorized on all the
+/// indexes.
+pub async fn set_template(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<Settings<Unchecked>>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let pattern = path.into_inner();
+    let settings = body.into_inner();
+    debug!("called with params: {:?}, {:?}", pattern, settings);
+
+    analytics.publish("Index Template Set".to_string(), json!({}), Some(&req));
+
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(&pattern)
+    {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    let template = meilisearch.set_template(pattern, settings).await?;
+
+    debug!("returns: {:?}", template);
+    Ok(HttpResponse::Ok().json(template))
+}
+
+pub async fn delete_template(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let pattern = path.into_inner();
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(&pattern)
+    {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    meilisearch.delete_template(pattern).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
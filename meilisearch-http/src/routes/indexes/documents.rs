@@ -6,24 +6,31 @@ use actix_web::{web, HttpRequest, HttpResponse};
 use bstr::ByteSlice;
 use futures::{Stream, StreamExt};
 use log::debug;
-use meilisearch_lib::index_controller::{DocumentAdditionFormat, Update};
+use meilisearch_auth::AuthFilter;
+use meilisearch_lib::index::{
+    check_edition_function, OnDuplicate, SimilarQuery, DEFAULT_SEARCH_LIMIT,
+};
+use meilisearch_lib::index_controller::{self, DocumentAdditionFormat, Update};
 use meilisearch_lib::milli::update::IndexDocumentsMethod;
 use meilisearch_lib::MeiliSearch;
-use meilisearch_types::error::ResponseError;
+use meilisearch_types::error::{Code, ResponseError};
 use meilisearch_types::star_or::StarOr;
 use mime::Mime;
 use once_cell::sync::Lazy;
 use serde::Deserialize;
 use serde_cs::vec::CS;
-use serde_json::Value;
+use serde_json::{json, Value};
 use tokio::sync::mpsc;
+use uuid::Uuid;
 
 use crate::analytics::Analytics;
 use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::idempotency_key::IdempotencyKey;
 use crate::extractors::payload::Payload;
 use crate::extractors::sequential_extractor::SeqHandler;
-use crate::routes::{fold_star_or, PaginationView};
+use crate::routes::fold_star_or;
+use crate::routes::indexes::search::{add_search_rules, fix_sort_query_parameters};
 use crate::task::SummarizedTaskView;
 
 static ACCEPTED_CONTENT_TYPE: Lazy<Vec<String>> = Lazy::new(|| {
@@ -31,6 +38,13 @@ static ACCEPTED_CONTENT_TYPE: Lazy<Vec<String>> = Lazy::new(|| {
         "application/json".to_string(),
         "application/x-ndjson".to_string(),
         "text/csv".to_string(),
+        #[cfg(feature = "columnar-payloads")]
+        "application/vnd.apache.parquet".to_string(),
+        #[cfg(feature = "columnar-payloads")]
+        "application/vnd.apache.arrow.file".to_string(),
+        #[cfg(feature = "columnar-payloads")]
+        "application/vnd.apache.arrow.stream".to_string(),
+        "application/xml".to_string(),
     ]
 });
 
@@ -79,10 +93,31 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     )
     // this route needs to be before the /documents/{document_id} to match properly
     .service(web::resource("/delete-batch").route(web::post().to(SeqHandler(delete_documents))))
+    .service(web::resource("/delete").route(web::post().to(SeqHandler(delete_documents_by_filter))))
+    .service(web::resource("/fetch").route(web::post().to(SeqHandler(fetch_documents))))
+    .service(web::resource("/edit").route(web::post().to(SeqHandler(edit_documents_by_function))))
+    .service(web::resource("/export").route(web::get().to(SeqHandler(export_documents))))
+    .service(web::resource("/stats").route(web::get().to(SeqHandler(get_document_stats))))
+    .service(web::resource("/uploads").route(web::post().to(SeqHandler(create_upload))))
+    .service(
+        web::resource("/uploads/{upload_id}")
+            .route(web::get().to(SeqHandler(get_upload)))
+            .route(web::put().to(SeqHandler(upload_chunk)))
+            .route(web::delete().to(SeqHandler(delete_upload))),
+    )
+    .service(
+        web::resource("/uploads/{upload_id}/commit")
+            .route(web::post().to(SeqHandler(add_documents_from_upload)))
+            .route(web::put().to(SeqHandler(update_documents_from_upload))),
+    )
     .service(
         web::resource("/{document_id}")
             .route(web::get().to(SeqHandler(get_document)))
             .route(web::delete().to(SeqHandler(delete_document))),
+    )
+    .service(
+        web::resource("/{document_id}/similar")
+            .route(web::get().to(SeqHandler(get_similar_documents))),
     );
 }
 
@@ -109,16 +144,98 @@ pub async fn get_document(
     Ok(HttpResponse::Ok().json(document))
 }
 
+/// Returns the number of documents, and for each field the number of documents having it and an
+/// estimate of its number of distinct values.
+pub async fn get_document_stats(
+    meilisearch: GuardedData<ActionPolicy<{ actions::STATS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let stats = meilisearch.document_stats(path.into_inner()).await?;
+
+    debug!("returns: {:?}", stats);
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SimilarDocumentsQuery {
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "DEFAULT_SEARCH_LIMIT")]
+    limit: usize,
+    filter: Option<String>,
+    fields: Option<CS<StarOr<String>>>,
+}
+
+/// Returns the documents sharing the most terms with the searchable attributes of a document.
+pub async fn get_similar_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    path: web::Path<DocumentParam>,
+    params: web::Query<SimilarDocumentsQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let SimilarDocumentsQuery {
+        offset,
+        limit,
+        filter,
+        fields,
+    } = params.into_inner();
+    let DocumentParam {
+        index_uid,
+        document_id,
+    } = path.into_inner();
+
+    analytics.publish(
+        "Similar Documents Retrieved".to_string(),
+        json!({ "filter": filter.is_some(), "limit": limit, "offset": offset }),
+        Some(&req),
+    );
+
+    let mut filter = filter.map(|f| match serde_json::from_str(&f) {
+        Ok(v) => v,
+        _ => Value::String(f),
+    });
+    // Tenant token search_rules.
+    let mut rules_filter = None;
+    if let Some(search_rules) = meilisearch
+        .filters()
+        .search_rules
+        .get_index_search_rules(&index_uid)
+    {
+        rules_filter = search_rules.filter.clone();
+        add_search_rules(&mut filter, search_rules);
+    }
+
+    let query = SimilarQuery {
+        id: document_id,
+        offset,
+        limit,
+        filter,
+        rules_filter,
+        attributes_to_retrieve: fields.and_then(fold_star_or),
+    };
+    let result = meilisearch.similar_documents(index_uid, query).await?;
+
+    debug!("returns: {:?}", result);
+    Ok(HttpResponse::Ok().json(result))
+}
+
 pub async fn delete_document(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<DocumentParam>,
+    idempotency_key: IdempotencyKey,
 ) -> Result<HttpResponse, ResponseError> {
     let DocumentParam {
         document_id,
         index_uid,
     } = path.into_inner();
     let update = Update::DeleteDocuments(vec![document_id]);
-    let task: SummarizedTaskView = meilisearch.register_update(index_uid, update).await?.into();
+    let task: SummarizedTaskView = meilisearch
+        .register_idempotent_update(index_uid, update, idempotency_key.into_inner())
+        .await?
+        .into();
     debug!("returns: {:?}", task);
     Ok(HttpResponse::Accepted().json(task))
 }
@@ -131,6 +248,8 @@ pub struct BrowseQuery {
     #[serde(default = "crate::routes::PAGINATION_DEFAULT_LIMIT")]
     limit: usize,
     fields: Option<CS<StarOr<String>>>,
+    filter: Option<String>,
+    sort: Option<String>,
 }
 
 pub async fn get_all_documents(
@@ -143,23 +262,155 @@ pub async fn get_all_documents(
         limit,
         offset,
         fields,
+        filter,
+        sort,
     } = params.into_inner();
     let attributes_to_retrieve = fields.and_then(fold_star_or);
+    let filter = filter.map(|f| match serde_json::from_str(&f) {
+        Ok(v) => v,
+        _ => Value::String(f),
+    });
+    let sort = sort.map(|sort| fix_sort_query_parameters(&sort));
+
+    let documents = meilisearch
+        .documents(
+            path.into_inner(),
+            offset,
+            limit,
+            attributes_to_retrieve,
+            filter,
+            sort,
+        )
+        .await?;
+
+    // the page is serialized while it's sent, so large documents are never all held in memory
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .streaming(documents))
+}
 
-    let (total, documents) = meilisearch
-        .documents(path.into_inner(), offset, limit, attributes_to_retrieve)
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExportQuery {
+    fields: Option<CS<StarOr<String>>>,
+    filter: Option<String>,
+}
+
+/// Streams every document of the index, or the ones matching the filter, as NDJSON.
+pub async fn export_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Query<ExportQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let ExportQuery { fields, filter } = params.into_inner();
+    let attributes_to_retrieve = fields.and_then(fold_star_or);
+    let filter = filter.map(|f| match serde_json::from_str(&f) {
+        Ok(v) => v,
+        _ => Value::String(f),
+    });
+
+    let documents = meilisearch
+        .export_documents(path.into_inner(), attributes_to_retrieve, filter)
         .await?;
 
-    let ret = PaginationView::new(offset, limit, total as usize, documents);
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(documents))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FetchDocumentsQuery {
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "crate::routes::PAGINATION_DEFAULT_LIMIT")]
+    limit: usize,
+    fields: Option<Vec<String>>,
+    filter: Option<Value>,
+    sort: Option<Vec<String>>,
+    /// When set, the documents with these ids are returned in the order of the ids instead of a
+    /// page of documents, along with the ids matching no document.
+    ids: Option<Vec<Value>>,
+}
+
+/// Same as `get_all_documents`, with the filter and the sort in the syntax of the search body.
+pub async fn fetch_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Json<FetchDocumentsQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let FetchDocumentsQuery {
+        offset,
+        limit,
+        fields,
+        filter,
+        sort,
+        ids,
+    } = params.into_inner();
+    let attributes_to_retrieve = fields.filter(|fields| fields.iter().all(|f| f != "*"));
+
+    if let Some(ids) = ids {
+        if filter.is_some() || sort.is_some() {
+            return Err(ResponseError::from_msg(
+                "The `ids` parameter can't be used along with `filter` or `sort`.".to_string(),
+                Code::BadRequest,
+            ));
+        }
+
+        let ids = ids
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| v.to_string())
+            })
+            .collect();
+        let (documents, missing) = meilisearch
+            .documents_by_ids(path.into_inner(), ids, attributes_to_retrieve)
+            .await?;
+
+        let ret = json!({ "results": documents, "missing": missing });
+        debug!("returns: {:?}", ret);
+        return Ok(HttpResponse::Ok().json(ret));
+    }
 
-    debug!("returns: {:?}", ret);
-    Ok(HttpResponse::Ok().json(ret))
+    let documents = meilisearch
+        .documents(
+            path.into_inner(),
+            offset,
+            limit,
+            attributes_to_retrieve,
+            filter,
+            sort,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .streaming(documents))
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct UpdateDocumentsQuery {
     pub primary_key: Option<String>,
+    /// The character separating the values of a csv payload, a comma by default.
+    pub csv_delimiter: Option<char>,
+    /// The path of the elements of an xml payload that are documents, e.g. `/products/product`.
+    pub record_path: Option<String>,
+    /// Gives a UUIDv4 to the documents missing the primary key instead of failing them.
+    #[serde(default)]
+    pub auto_generate_primary_key: bool,
+    /// Merges the nested objects of the documents into the ones of the stored documents instead
+    /// of replacing them. Only available when updating documents.
+    #[serde(default)]
+    pub merge_nested: bool,
+    /// What happens to the documents whose id is used by a stored document: they replace it by
+    /// default, or are skipped, or fail.
+    #[serde(default)]
+    pub on_duplicate: OnDuplicate,
 }
 
 pub async fn add_documents(
@@ -167,6 +418,7 @@ pub async fn add_documents(
     path: web::Path<String>,
     params: web::Query<UpdateDocumentsQuery>,
     body: Payload,
+    idempotency_key: IdempotencyKey,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
@@ -180,15 +432,14 @@ pub async fn add_documents(
         &req,
     );
 
-    let allow_index_creation = meilisearch.filters().allow_index_creation;
     let task = document_addition(
         extract_mime_type(&req)?,
         meilisearch,
         index_uid,
-        params.primary_key,
+        params,
         body,
+        idempotency_key,
         IndexDocumentsMethod::ReplaceDocuments,
-        allow_index_creation,
     )
     .await?;
 
@@ -200,6 +451,7 @@ pub async fn update_documents(
     path: web::Path<String>,
     params: web::Query<UpdateDocumentsQuery>,
     body: Payload,
+    idempotency_key: IdempotencyKey,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
@@ -212,15 +464,14 @@ pub async fn update_documents(
         &req,
     );
 
-    let allow_index_creation = meilisearch.filters().allow_index_creation;
     let task = document_addition(
         extract_mime_type(&req)?,
         meilisearch,
         index_uid,
-        params.into_inner().primary_key,
+        params.into_inner(),
         body,
+        idempotency_key,
         IndexDocumentsMethod::UpdateDocuments,
-        allow_index_creation,
     )
     .await?;
 
@@ -231,18 +482,81 @@ async fn document_addition(
     mime_type: Option<Mime>,
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
     index_uid: String,
-    primary_key: Option<String>,
+    params: UpdateDocumentsQuery,
     body: Payload,
+    idempotency_key: IdempotencyKey,
     method: IndexDocumentsMethod,
-    allow_index_creation: bool,
 ) -> Result<SummarizedTaskView, ResponseError> {
-    let format = match mime_type
+    let update = document_addition_update(mime_type, params, method, meilisearch.filters())?;
+    let payload = Box::new(payload_to_stream(body));
+    let task = meilisearch
+        .register_idempotent_update(index_uid, update(payload), idempotency_key.into_inner())
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(task)
+}
+
+/// Checks the parameters of a document addition, and returns the addition of a payload.
+fn document_addition_update(
+    mime_type: Option<Mime>,
+    params: UpdateDocumentsQuery,
+    method: IndexDocumentsMethod,
+    filters: &AuthFilter,
+) -> Result<impl FnOnce(index_controller::Payload) -> Update, ResponseError> {
+    let allow_index_creation = filters.allow_index_creation;
+    let quota = filters.quota;
+    let UpdateDocumentsQuery {
+        primary_key,
+        csv_delimiter,
+        record_path,
+        auto_generate_primary_key,
+        merge_nested,
+        on_duplicate,
+    } = params;
+    if merge_nested && method != IndexDocumentsMethod::UpdateDocuments {
+        return Err(MeilisearchHttpError::MergeNestedWithoutUpdate.into());
+    }
+    let csv_delimiter = match csv_delimiter {
+        Some(delimiter) if delimiter.is_ascii() => Some(delimiter as u8),
+        Some(delimiter) => return Err(MeilisearchHttpError::InvalidCsvDelimiter(delimiter).into()),
+        None => None,
+    };
+    let record_path = record_path
+        .map(|path| match path.strip_prefix('/') {
+            Some(names) if names.split('/').all(|name| !name.is_empty()) => {
+                Ok(names.split('/').map(String::from).collect::<Vec<_>>())
+            }
+            _ => Err(MeilisearchHttpError::InvalidXmlRecordPath(path)),
+        })
+        .transpose()?;
+
+    let mime_type = mime_type
         .as_ref()
-        .map(|m| (m.type_().as_str(), m.subtype().as_str()))
-    {
+        .map(|m| (m.type_().as_str(), m.subtype().as_str()));
+    if csv_delimiter.is_some() && mime_type != Some(("text", "csv")) {
+        return Err(MeilisearchHttpError::CsvDelimiterWithoutCsv.into());
+    }
+    let is_xml = matches!(mime_type, Some(("application" | "text", "xml")));
+    if record_path.is_some() && !is_xml {
+        return Err(MeilisearchHttpError::XmlRecordPathWithoutXml.into());
+    }
+
+    let format = match mime_type {
         Some(("application", "json")) => DocumentAdditionFormat::Json,
         Some(("application", "x-ndjson")) => DocumentAdditionFormat::Ndjson,
-        Some(("text", "csv")) => DocumentAdditionFormat::Csv,
+        Some(("text", "csv")) => DocumentAdditionFormat::Csv(csv_delimiter.unwrap_or(b',')),
+        #[cfg(feature = "columnar-payloads")]
+        Some(("application", "vnd.apache.parquet")) => DocumentAdditionFormat::Parquet,
+        #[cfg(feature = "columnar-payloads")]
+        Some(("application", "vnd.apache.arrow.file")) => DocumentAdditionFormat::Arrow,
+        #[cfg(feature = "columnar-payloads")]
+        Some(("application", "vnd.apache.arrow.stream")) => DocumentAdditionFormat::ArrowStream,
+        Some(("application" | "text", "xml")) => match record_path {
+            Some(record_path) => DocumentAdditionFormat::Xml(record_path),
+            None => return Err(MeilisearchHttpError::MissingXmlRecordPath.into()),
+        },
         Some((type_, subtype)) => {
             return Err(MeilisearchHttpError::InvalidContentType(
                 format!("{}/{}", type_, subtype),
@@ -257,24 +571,174 @@ async fn document_addition(
         }
     };
 
-    let update = Update::DocumentAddition {
-        payload: Box::new(payload_to_stream(body)),
+    Ok(move |payload| Update::DocumentAddition {
+        payload,
         primary_key,
         method,
         format,
         allow_index_creation,
-    };
+        auto_generate_primary_key,
+        merge_nested,
+        on_duplicate,
+        quota,
+    })
+}
+
+#[derive(Deserialize)]
+pub struct UploadParam {
+    index_uid: String,
+    upload_id: String,
+}
+
+impl UploadParam {
+    fn upload_id(&self) -> Result<Uuid, ResponseError> {
+        Uuid::parse_str(&self.upload_id).map_err(|_| {
+            ResponseError::from_msg(
+                format!("Upload `{}` not found.", self.upload_id),
+                Code::UploadNotFound,
+            )
+        })
+    }
+}
+
+/// Creates an upload session, receiving the payload of a document addition in several chunks
+/// that are committed as a single addition once they're all uploaded.
+pub async fn create_upload(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let upload = meilisearch.create_upload(path.into_inner()).await?;
+
+    debug!("returns: {:?}", upload);
+    Ok(HttpResponse::Created().json(upload))
+}
+
+/// Returns the upload, whose size is the offset the next chunk must start at.
+pub async fn get_upload(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<UploadParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let upload_id = path.upload_id()?;
+    let upload = meilisearch
+        .get_upload(path.into_inner().index_uid, upload_id)
+        .await?;
+
+    debug!("returns: {:?}", upload);
+    Ok(HttpResponse::Ok().json(upload))
+}
 
-    let task = meilisearch.register_update(index_uid, update).await?.into();
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UploadChunkQuery {
+    offset: u64,
+}
+
+pub async fn upload_chunk(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<UploadParam>,
+    params: web::Query<UploadChunkQuery>,
+    body: Payload,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let upload_id = path.upload_id()?;
+    let upload = meilisearch
+        .upload_chunk(
+            path.into_inner().index_uid,
+            upload_id,
+            params.offset,
+            Box::new(payload_to_stream(body)),
+        )
+        .await?;
+
+    debug!("returns: {:?}", upload);
+    Ok(HttpResponse::Ok().json(upload))
+}
+
+pub async fn delete_upload(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<UploadParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let upload_id = path.upload_id()?;
+    meilisearch
+        .delete_upload(path.into_inner().index_uid, upload_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn add_documents_from_upload(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<UploadParam>,
+    params: web::Query<UpdateDocumentsQuery>,
+    idempotency_key: IdempotencyKey,
+    req: HttpRequest,
+) -> Result<HttpResponse, ResponseError> {
+    commit_upload(
+        meilisearch,
+        path.into_inner(),
+        params.into_inner(),
+        idempotency_key,
+        &req,
+        IndexDocumentsMethod::ReplaceDocuments,
+    )
+    .await
+}
+
+pub async fn update_documents_from_upload(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<UploadParam>,
+    params: web::Query<UpdateDocumentsQuery>,
+    idempotency_key: IdempotencyKey,
+    req: HttpRequest,
+) -> Result<HttpResponse, ResponseError> {
+    commit_upload(
+        meilisearch,
+        path.into_inner(),
+        params.into_inner(),
+        idempotency_key,
+        &req,
+        IndexDocumentsMethod::UpdateDocuments,
+    )
+    .await
+}
+
+/// Registers the assembled payload of the upload as a document addition, whose format is given
+/// by the content type of the commit request.
+async fn commit_upload(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: UploadParam,
+    params: UpdateDocumentsQuery,
+    idempotency_key: IdempotencyKey,
+    req: &HttpRequest,
+    method: IndexDocumentsMethod,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let upload_id = path.upload_id()?;
+    let update = document_addition_update(
+        extract_mime_type(req)?,
+        params,
+        method,
+        meilisearch.filters(),
+    )?;
+    let task: SummarizedTaskView = meilisearch
+        .commit_upload(
+            path.index_uid,
+            upload_id,
+            idempotency_key.into_inner(),
+            update,
+        )
+        .await?
+        .into();
 
     debug!("returns: {:?}", task);
-    Ok(task)
+    Ok(HttpResponse::Accepted().json(task))
 }
 
 pub async fn delete_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
     body: web::Json<Vec<Value>>,
+    idempotency_key: IdempotencyKey,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", body);
     let ids = body
@@ -288,7 +752,89 @@ pub async fn delete_documents(
 
     let update = Update::DeleteDocuments(ids);
     let task: SummarizedTaskView = meilisearch
-        .register_update(path.into_inner(), update)
+        .register_idempotent_update(path.into_inner(), update, idempotency_key.into_inner())
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentDeletionByFilter {
+    filter: Value,
+}
+
+pub async fn delete_documents_by_filter(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<DocumentDeletionByFilter>,
+    idempotency_key: IdempotencyKey,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let DocumentDeletionByFilter { filter } = body.into_inner();
+
+    // The filter itself is only parsed when the task is processed, but its shape must be the one
+    // of a search filter.
+    if !matches!(filter, Value::String(_) | Value::Array(_)) {
+        return Err(ResponseError::from_msg(
+            format!(
+                "Invalid syntax for the filter parameter: expected String or Array, found: {}.",
+                filter
+            ),
+            Code::Filter,
+        ));
+    }
+
+    let update = Update::DeleteDocumentsByFilter(filter);
+    let task: SummarizedTaskView = meilisearch
+        .register_idempotent_update(path.into_inner(), update, idempotency_key.into_inner())
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentEditionByFunction {
+    function: String,
+    filter: Option<Value>,
+}
+
+/// Runs a rhai function on the documents matching the filter, or on every document. The function
+/// edits the document bound to `doc`, or deletes it by setting it to `()`, so the key must be
+/// allowed to both add and delete documents.
+pub async fn edit_documents_by_function(
+    meilisearch: GuardedData<
+        ActionsPolicy<{ actions::DOCUMENTS_ADD }, { actions::DOCUMENTS_DELETE }>,
+        MeiliSearch,
+    >,
+    path: web::Path<String>,
+    body: web::Json<DocumentEditionByFunction>,
+    idempotency_key: IdempotencyKey,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let DocumentEditionByFunction { function, filter } = body.into_inner();
+
+    if let Some(ref filter) = filter {
+        if !matches!(filter, Value::String(_) | Value::Array(_)) {
+            return Err(ResponseError::from_msg(
+                format!(
+                    "Invalid syntax for the filter parameter: expected String or Array, found: {}.",
+                    filter
+                ),
+                Code::Filter,
+            ));
+        }
+    }
+    check_edition_function(&function)?;
+
+    let update = Update::EditDocumentsByFunction { function, filter };
+    let task: SummarizedTaskView = meilisearch
+        .register_idempotent_update(path.into_inner(), update, idempotency_key.into_inner())
         .await?
         .into();
 
@@ -299,10 +845,11 @@ pub async fn delete_documents(
 pub async fn clear_all_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
+    idempotency_key: IdempotencyKey,
 ) -> Result<HttpResponse, ResponseError> {
     let update = Update::ClearDocuments;
     let task: SummarizedTaskView = meilisearch
-        .register_update(path.into_inner(), update)
+        .register_idempotent_update(path.into_inner(), update, idempotency_key.into_inner())
         .await?
         .into();
 
@@ -0,0 +1,52 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::index::FacetSearchQuery;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::add_search_rules;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(search_facet_values))));
+}
+
+/// Searches the values of a facet among the documents matching the query and the filter.
+pub async fn search_facet_values(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+    params: web::Json<FacetSearchQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let mut query = params.into_inner();
+    debug!("facet search called with params: {:?}", query);
+
+    analytics.publish(
+        "Facet Searched".to_string(),
+        json!({
+            "facet_query": query.facet_query.is_some(),
+            "q": query.q.is_some(),
+            "filter": query.filter.is_some(),
+        }),
+        Some(&req),
+    );
+
+    let index_uid = index_uid.into_inner();
+    // Tenant token search_rules.
+    if let Some(search_rules) = meilisearch
+        .filters()
+        .search_rules
+        .get_index_search_rules(&index_uid)
+    {
+        add_search_rules(&mut query.filter, search_rules);
+    }
+
+    let result = meilisearch.facet_search(index_uid, query).await?;
+
+    debug!("returns: {:?}", result);
+    Ok(HttpResponse::Ok().json(result))
+}
@@ -3,20 +3,24 @@ use log::debug;
 use meilisearch_lib::index_controller::Update;
 use meilisearch_lib::MeiliSearch;
 use meilisearch_types::error::ResponseError;
+use meilisearch_types::index_uid;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use time::OffsetDateTime;
 
 use crate::analytics::Analytics;
+use crate::extractors::authentication::error::AuthenticationError;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
 use crate::task::SummarizedTaskView;
 
-use super::Pagination;
+use super::{Pagination, PAGINATION_DEFAULT_LIMIT};
 
 pub mod documents;
+pub mod facet_search;
 pub mod search;
 pub mod settings;
+pub mod suggest;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -33,25 +37,55 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                     .route(web::delete().to(SeqHandler(delete_index))),
             )
             .service(web::resource("/stats").route(web::get().to(SeqHandler(get_index_stats))))
+            .service(web::resource("/clone").route(web::post().to(SeqHandler(clone_index))))
+            .service(web::resource("/compact").route(web::post().to(SeqHandler(compact_index))))
+            .service(web::resource("/restore").route(web::post().to(SeqHandler(restore_index))))
+            .service(web::resource("/check").route(web::post().to(SeqHandler(check_index))))
+            .service(web::resource("/warmup").route(web::post().to(SeqHandler(warmup_index))))
             .service(web::scope("/documents").configure(documents::configure))
             .service(web::scope("/search").configure(search::configure))
+            .service(web::scope("/facet-search").configure(facet_search::configure))
+            .service(web::scope("/suggest").configure(suggest::configure))
+            .service(web::scope("/rules").service(settings::rules::resources()))
             .service(web::scope("/settings").configure(settings::configure)),
     );
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ListIndexesQuery {
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "PAGINATION_DEFAULT_LIMIT")]
+    limit: usize,
+    /// Only the indexes whose uid starts with the prefix are listed.
+    uid_prefix: Option<String>,
+    /// Only the indexes of the namespace are listed.
+    namespace: Option<String>,
+}
+
 pub async fn list_indexes(
     data: GuardedData<ActionPolicy<{ actions::INDEXES_GET }>, MeiliSearch>,
-    paginate: web::Query<Pagination>,
+    params: web::Query<ListIndexesQuery>,
 ) -> Result<HttpResponse, ResponseError> {
+    let ListIndexesQuery {
+        offset,
+        limit,
+        uid_prefix,
+        namespace,
+    } = params.into_inner();
     let search_rules = &data.filters().search_rules;
-    let indexes: Vec<_> = data.list_indexes().await?;
-    let nb_indexes = indexes.len();
-    let iter = indexes
-        .into_iter()
-        .filter(|i| search_rules.is_index_authorized(&i.uid));
-    let ret = paginate
-        .into_inner()
-        .auto_paginate_unsized(nb_indexes, iter);
+    let filter = |uid: &str| {
+        uid_prefix
+            .as_ref()
+            .map_or(true, |prefix| uid.starts_with(prefix.as_str()))
+            && namespace.as_ref().map_or(true, |namespace| {
+                index_uid::namespace(uid) == Some(namespace.as_str())
+            })
+            && search_rules.is_index_authorized(uid)
+    };
+    let (total, indexes) = data.list_indexes(filter, offset, limit).await?;
+    let ret = Pagination { offset, limit }.format_with(total, indexes);
 
     debug!("returns: {:?}", ret);
     Ok(HttpResponse::Ok().json(ret))
@@ -80,18 +114,134 @@ pub async fn create_index(
         Some(&req),
     );
 
-    let update = Update::CreateIndex { primary_key };
+    let quota = meilisearch.filters().quota;
+    let update = Update::CreateIndex { primary_key, quota };
     let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
 
     Ok(HttpResponse::Accepted().json(task))
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct IndexCloneRequest {
+    uid: String,
+}
+
+/// Creates the index `uid` as a copy of the documents and settings of the index, the copy being
+/// made from the files of the index rather than by indexing its documents again.
+pub async fn clone_index(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_CREATE }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<IndexCloneRequest>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let IndexCloneRequest { uid } = body.into_inner();
+    debug!("called with params: {:?}", uid);
+
+    analytics.publish("Index Cloned".to_string(), json!({}), Some(&req));
+
+    if !meilisearch.filters().search_rules.is_index_authorized(&uid) {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    let quota = meilisearch.filters().quota;
+    let task: SummarizedTaskView = meilisearch
+        .register_index_clone_task(path.into_inner(), uid, quota)
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// Rewrites the files of the index without the space left by the deleted documents, which is
+/// otherwise never given back to the filesystem.
+pub async fn compact_index(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
+    path: web::Path<String>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Index Compacted".to_string(), json!({}), Some(&req));
+
+    let task: SummarizedTaskView = meilisearch
+        .register_index_compaction_task(path.into_inner())
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// Moves a deleted index back from the trash, as long as it wasn't purged and no index was
+/// created with the same uid in the meantime.
+pub async fn restore_index(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_CREATE }>, MeiliSearch>,
+    path: web::Path<String>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Index Restored".to_string(), json!({}), Some(&req));
+
+    let quota = meilisearch.filters().quota;
+    let task: SummarizedTaskView = meilisearch
+        .register_index_restoration_task(path.into_inner(), quota)
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// Verifies the consistency of the databases of the index, the anomalies found are listed in the
+/// details of the task.
+pub async fn check_index(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
+    path: web::Path<String>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Index Checked".to_string(), json!({}), Some(&req));
+
+    let task: SummarizedTaskView = meilisearch
+        .register_index_integrity_check_task(path.into_inner())
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// Loads the databases used by the searches of the index in memory, so that the first searches
+/// after a restart aren't served from the disk.
+pub async fn warmup_index(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
+    path: web::Path<String>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Index Warmed Up".to_string(), json!({}), Some(&req));
+
+    let task: SummarizedTaskView = meilisearch
+        .register_index_warmup_task(path.into_inner())
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 #[allow(dead_code)]
 pub struct UpdateIndexRequest {
     uid: Option<String>,
     primary_key: Option<String>,
+    /// The size of the map of the index, in bytes, the size the index can't outgrow.
+    max_size: Option<usize>,
+    /// Whether the documents and settings of the index are protected from any modification.
+    frozen: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -127,12 +277,18 @@ pub async fn update_index(
     let body = body.into_inner();
     analytics.publish(
         "Index Updated".to_string(),
-        json!({ "primary_key": body.primary_key}),
+        json!({
+            "primary_key": body.primary_key,
+            "max_size": body.max_size,
+            "frozen": body.frozen,
+        }),
         Some(&req),
     );
 
     let update = Update::UpdateIndex {
         primary_key: body.primary_key,
+        max_size: body.max_size,
+        frozen: body.frozen,
     };
 
     let task: SummarizedTaskView = meilisearch
@@ -1,10 +1,11 @@
-use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse};
 use log::debug;
-use meilisearch_auth::IndexSearchRules;
+use meilisearch_auth::{AuthFilter, IndexSearchRules};
 use meilisearch_lib::index::{
-    SearchQuery, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG,
-    DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
+    MatchingStrategy, SearchQuery, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
+    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
 };
+use meilisearch_lib::search_log::LoggedSearch;
 use meilisearch_lib::MeiliSearch;
 use meilisearch_types::error::ResponseError;
 use serde::Deserialize;
@@ -30,6 +31,8 @@ pub struct SearchQueryGet {
     q: Option<String>,
     offset: Option<usize>,
     limit: Option<usize>,
+    page: Option<usize>,
+    hits_per_page: Option<usize>,
     attributes_to_retrieve: Option<CS<StarOr<String>>>,
     attributes_to_crop: Option<CS<StarOr<String>>>,
     #[serde(default = "DEFAULT_CROP_LENGTH")]
@@ -39,13 +42,22 @@ pub struct SearchQueryGet {
     sort: Option<String>,
     #[serde(default = "Default::default")]
     show_matches_position: bool,
-    facets: Option<CS<StarOr<String>>>,
+    facets: Option<CS<String>>,
+    #[serde(default)]
+    exhaustive_facet_count: bool,
+    attributes_to_search_on: Option<CS<String>>,
+    #[serde(default)]
+    matching_strategy: MatchingStrategy,
     #[serde(default = "DEFAULT_HIGHLIGHT_PRE_TAG")]
     highlight_pre_tag: String,
     #[serde(default = "DEFAULT_HIGHLIGHT_POST_TAG")]
     highlight_post_tag: String,
     #[serde(default = "DEFAULT_CROP_MARKER")]
     crop_marker: String,
+    #[serde(default)]
+    show_suggested_query: bool,
+    #[serde(default)]
+    debug: bool,
 }
 
 impl From<SearchQueryGet> for SearchQuery {
@@ -62,6 +74,8 @@ impl From<SearchQueryGet> for SearchQuery {
             q: other.q,
             offset: other.offset,
             limit: other.limit.unwrap_or_else(DEFAULT_SEARCH_LIMIT),
+            page: other.page,
+            hits_per_page: other.hits_per_page,
             attributes_to_retrieve: other.attributes_to_retrieve.and_then(fold_star_or),
             attributes_to_crop: other.attributes_to_crop.and_then(fold_star_or),
             crop_length: other.crop_length,
@@ -69,17 +83,74 @@ impl From<SearchQueryGet> for SearchQuery {
             filter,
             sort: other.sort.map(|attr| fix_sort_query_parameters(&attr)),
             show_matches_position: other.show_matches_position,
-            facets: other.facets.and_then(fold_star_or),
+            facets: other.facets.map(|facets| facets.into_iter().collect()),
+            exhaustive_facet_count: other.exhaustive_facet_count,
+            attributes_to_search_on: other
+                .attributes_to_search_on
+                .map(|attributes| attributes.into_iter().collect()),
+            attribute_weights: None,
+            typo_tolerance: None,
+            distinct: None,
+            boost_documents: None,
+            bury_documents: None,
+            matching_strategy: other.matching_strategy,
+            vector: None,
+            hybrid: None,
             highlight_pre_tag: other.highlight_pre_tag,
             highlight_post_tag: other.highlight_post_tag,
             crop_marker: other.crop_marker,
+            show_suggested_query: other.show_suggested_query,
+            debug: other.debug,
+            experiment_key: None,
+            ranking_score: false,
         }
     }
 }
 
-/// Incorporate search rules in search query
-fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
-    query.filter = match (query.filter.take(), rules.filter) {
+/// The header identifying the user of a client in the search log.
+const USER_TAG_HEADER: &str = "X-Meili-User-Tag";
+
+/// The `debug` option exposes how the documents of the index are matched and filtered, it's
+/// reserved to the master key and to the API keys having all the actions.
+pub async fn authorize_debug(query: &SearchQuery, req: &HttpRequest) -> Result<(), ResponseError> {
+    if query.debug {
+        GuardedData::<ActionPolicy<{ actions::ALL }>, MeiliSearch>::extract(req).await?;
+    }
+    Ok(())
+}
+
+/// Returns the key assigning the searches of a user to a variant of the ranking experiment of an
+/// index: the user tag of the client if any, or else the hash of the tenant token of the request.
+/// The API keys are shared by many users and are never used as a key.
+pub fn experiment_key(filters: &AuthFilter, req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(USER_TAG_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+        .or_else(|| filters.tenant.clone())
+}
+
+/// Returns the entry of the search log of a search, if the search log is enabled. The entry is
+/// created before the search rules of the tenant token are added to the query.
+pub fn logged_search(
+    meilisearch: &MeiliSearch,
+    index_uid: &str,
+    query: &SearchQuery,
+    req: &HttpRequest,
+) -> Option<LoggedSearch> {
+    meilisearch.is_search_log_enabled().then(|| {
+        let user_tag = req
+            .headers()
+            .get(USER_TAG_HEADER)
+            .and_then(|tag| tag.to_str().ok())
+            .map(String::from);
+        LoggedSearch::new(index_uid.to_string(), query, user_tag)
+    })
+}
+
+/// Incorporate search rules in the filter of a search query
+pub fn add_search_rules(filter: &mut Option<Value>, rules: IndexSearchRules) {
+    *filter = match (filter.take(), rules.filter) {
         (None, rules_filter) => rules_filter,
         (filter, None) => filter,
         (Some(filter), Some(rules_filter)) => {
@@ -100,7 +171,7 @@ fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
 // TODO: TAMO: split on :asc, and :desc, instead of doing some weird things
 
 /// Transform the sort query parameter into something that matches the post expected format.
-fn fix_sort_query_parameters(sort_query: &str) -> Vec<String> {
+pub(crate) fn fix_sort_query_parameters(sort_query: &str) -> Vec<String> {
     let mut sort_parameters = Vec::new();
     let mut merge = false;
     for current_sort in sort_query.trim_matches('"').split(',').map(|s| s.trim()) {
@@ -134,13 +205,16 @@ pub async fn search_with_url_query(
     let mut query: SearchQuery = params.into_inner().into();
 
     let index_uid = path.into_inner();
+    authorize_debug(&query, &req).await?;
+    query.experiment_key = experiment_key(meilisearch.filters(), &req);
+    let logged_search = logged_search(&meilisearch, &index_uid, &query, &req);
     // Tenant token search_rules.
     if let Some(search_rules) = meilisearch
         .filters()
         .search_rules
         .get_index_search_rules(&index_uid)
     {
-        add_search_rules(&mut query, search_rules);
+        add_search_rules(&mut query.filter, search_rules);
     }
 
     let mut aggregate = SearchAggregator::from_query(&query, &req);
@@ -148,6 +222,9 @@ pub async fn search_with_url_query(
     let search_result = meilisearch.search(index_uid, query).await;
     if let Ok(ref search_result) = search_result {
         aggregate.succeed(search_result);
+        if let Some(logged_search) = logged_search {
+            meilisearch.record_search(logged_search.with_result(search_result));
+        }
     }
     analytics.get_search(aggregate);
 
@@ -168,13 +245,16 @@ pub async fn search_with_post(
     debug!("search called with params: {:?}", query);
 
     let index_uid = path.into_inner();
+    authorize_debug(&query, &req).await?;
+    query.experiment_key = experiment_key(meilisearch.filters(), &req);
+    let logged_search = logged_search(&meilisearch, &index_uid, &query, &req);
     // Tenant token search_rules.
     if let Some(search_rules) = meilisearch
         .filters()
         .search_rules
         .get_index_search_rules(&index_uid)
     {
-        add_search_rules(&mut query, search_rules);
+        add_search_rules(&mut query.filter, search_rules);
     }
 
     let mut aggregate = SearchAggregator::from_query(&query, &req);
@@ -182,6 +262,9 @@ pub async fn search_with_post(
     let search_result = meilisearch.search(index_uid, query).await;
     if let Ok(ref search_result) = search_result {
         aggregate.succeed(search_result);
+        if let Some(logged_search) = logged_search {
+            meilisearch.record_search(logged_search.with_result(search_result));
+        }
     }
     analytics.post_search(aggregate);
 
@@ -1,14 +1,23 @@
+use std::collections::BTreeMap;
+
 use log::debug;
 
 use actix_web::{web, HttpRequest, HttpResponse};
+use futures::StreamExt;
 use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::index_controller::error::IndexControllerError;
 use meilisearch_lib::index_controller::Update;
+use meilisearch_lib::milli::update::Setting;
 use meilisearch_lib::MeiliSearch;
 use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
 use serde_json::json;
 
 use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::idempotency_key::IdempotencyKey;
+use crate::extractors::payload::Payload;
 use crate::task::SummarizedTaskView;
 
 #[macro_export]
@@ -24,12 +33,14 @@ macro_rules! make_setting_route {
             use meilisearch_types::error::ResponseError;
             use $crate::analytics::Analytics;
             use $crate::extractors::authentication::{policies::*, GuardedData};
+            use $crate::extractors::idempotency_key::IdempotencyKey;
             use $crate::extractors::sequential_extractor::SeqHandler;
             use $crate::task::SummarizedTaskView;
 
             pub async fn delete(
                 meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
                 index_uid: web::Path<String>,
+                idempotency_key: IdempotencyKey,
             ) -> Result<HttpResponse, ResponseError> {
                 let settings = Settings {
                     $attr: Setting::Reset,
@@ -37,13 +48,19 @@ macro_rules! make_setting_route {
                 };
 
                 let allow_index_creation = meilisearch.filters().allow_index_creation;
+                let quota = meilisearch.filters().quota;
                 let update = Update::Settings {
                     settings,
                     is_deletion: true,
                     allow_index_creation,
+                    quota,
                 };
                 let task: SummarizedTaskView = meilisearch
-                    .register_update(index_uid.into_inner(), update)
+                    .register_idempotent_update(
+                        index_uid.into_inner(),
+                        update,
+                        idempotency_key.into_inner(),
+                    )
                     .await?
                     .into();
 
@@ -55,6 +72,7 @@ macro_rules! make_setting_route {
                 meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
                 index_uid: actix_web::web::Path<String>,
                 body: actix_web::web::Json<Option<$type>>,
+                idempotency_key: IdempotencyKey,
                 req: HttpRequest,
                 $analytics_var: web::Data<dyn Analytics>,
             ) -> std::result::Result<HttpResponse, ResponseError> {
@@ -71,13 +89,19 @@ macro_rules! make_setting_route {
                 };
 
                 let allow_index_creation = meilisearch.filters().allow_index_creation;
+                let quota = meilisearch.filters().quota;
                 let update = Update::Settings {
                     settings,
                     is_deletion: false,
                     allow_index_creation,
+                    quota,
                 };
                 let task: SummarizedTaskView = meilisearch
-                    .register_update(index_uid.into_inner(), update)
+                    .register_idempotent_update(
+                        index_uid.into_inner(),
+                        update,
+                        idempotency_key.into_inner(),
+                    )
                     .await?
                     .into();
 
@@ -297,6 +321,11 @@ make_setting_route!(
             json!({
                 "faceting": {
                     "max_values_per_facet": setting.as_ref().and_then(|s| s.max_values_per_facet.set()),
+                    "sort_facet_values_by_star_count": setting.as_ref().and_then(|s| {
+                        s.sort_facet_values_by.as_ref().set().map(|sort_by| {
+                            sort_by.get("*") == Some(&meilisearch_lib::index::updates::FacetValuesSort::Count)
+                        })
+                    }),
                 },
             }),
             Some(req),
@@ -318,7 +347,37 @@ make_setting_route!(
             "Pagination Updated".to_string(),
             json!({
                 "pagination": {
-                    "limited_to": setting.as_ref().and_then(|s| s.limited_to.set()),
+                    "max_total_hits": setting.as_ref().and_then(|s| s.max_total_hits.set()),
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
+make_setting_route!(
+    "/search-cutoff-ms",
+    put,
+    u64,
+    search_cutoff_ms,
+    "searchCutoffMs"
+);
+
+make_setting_route!(
+    "/ranking-experiment",
+    put,
+    meilisearch_lib::index::updates::RankingExperiment,
+    ranking_experiment,
+    "rankingExperiment",
+    analytics,
+    |setting: &Option<meilisearch_lib::index::updates::RankingExperiment>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "RankingExperiment Updated".to_string(),
+            json!({
+                "ranking_experiment": {
+                    "variants": setting.as_ref().map(|experiment| experiment.variants.len()),
                 },
             }),
             Some(req),
@@ -326,6 +385,68 @@ make_setting_route!(
     }
 );
 
+make_setting_route!(
+    "",
+    put,
+    Vec<meilisearch_lib::index::rules::Rule>,
+    rules,
+    "rules",
+    analytics,
+    |setting: &Option<Vec<meilisearch_lib::index::rules::Rule>>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "Rules Updated".to_string(),
+            json!({
+                "rules": {
+                    "total": setting.as_ref().map(|rules| rules.len()),
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
+make_setting_route!(
+    "/expires-at-attribute",
+    put,
+    String,
+    expires_at_attribute,
+    "expiresAtAttribute"
+);
+
+make_setting_route!(
+    "/document-schema",
+    put,
+    serde_json::Value,
+    document_schema,
+    "documentSchema"
+);
+
+make_setting_route!(
+    "/ingestion-pipeline",
+    put,
+    Vec<meilisearch_lib::index::ingestion::IngestionStep>,
+    ingestion_pipeline,
+    "ingestionPipeline"
+);
+
+make_setting_route!(
+    "/nested-flattening",
+    put,
+    meilisearch_lib::index::flattening::NestedFlattening,
+    nested_flattening,
+    "nestedFlattening"
+);
+
+make_setting_route!(
+    "/attachments",
+    put,
+    meilisearch_lib::index::attachment::AttachmentSettings,
+    attachments,
+    "attachments"
+);
+
 macro_rules! generate_configure {
     ($($mod:ident),*) => {
         pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -333,8 +454,21 @@ macro_rules! generate_configure {
             cfg.service(
                 web::resource("")
                 .route(web::patch().to(SeqHandler(update_all)))
+                .route(web::put().to(SeqHandler(import_all)))
                 .route(web::get().to(SeqHandler(get_all)))
                 .route(web::delete().to(SeqHandler(delete_all))))
+                .service(
+                    web::resource("/synonyms/import")
+                    .route(web::post().to(SeqHandler(import_synonyms))))
+                .service(
+                    web::resource("/history")
+                    .route(web::get().to(SeqHandler(get_history))))
+                .service(
+                    web::resource("/rollback")
+                    .route(web::post().to(SeqHandler(rollback))))
+                .service(
+                    web::resource("/diff")
+                    .route(web::post().to(SeqHandler(diff))))
                 $(.service($mod::resources()))*;
         }
     };
@@ -349,13 +483,22 @@ generate_configure!(
     stop_words,
     synonyms,
     ranking_rules,
-    typo_tolerance
+    typo_tolerance,
+    pagination,
+    search_cutoff_ms,
+    ranking_experiment,
+    expires_at_attribute,
+    document_schema,
+    ingestion_pipeline,
+    nested_flattening,
+    attachments
 );
 
 pub async fn update_all(
     meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
     index_uid: web::Path<String>,
     body: web::Json<Settings<Unchecked>>,
+    idempotency_key: IdempotencyKey,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
@@ -414,13 +557,44 @@ pub async fn update_all(
     );
 
     let allow_index_creation = meilisearch.filters().allow_index_creation;
+    let quota = meilisearch.filters().quota;
     let update = Update::Settings {
         settings,
         is_deletion: false,
         allow_index_creation,
+        quota,
+    };
+    let task: SummarizedTaskView = meilisearch
+        .register_idempotent_update(index_uid.into_inner(), update, idempotency_key.into_inner())
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// Replaces all the settings of the index with the ones of the body, typically an export of the
+/// settings. The settings missing from the body are reset, in the same task as the others.
+pub async fn import_all(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+    body: web::Json<Settings<Unchecked>>,
+    idempotency_key: IdempotencyKey,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Settings Imported".to_string(), json!({}), Some(&req));
+
+    let allow_index_creation = meilisearch.filters().allow_index_creation;
+    let quota = meilisearch.filters().quota;
+    let update = Update::Settings {
+        settings: body.into_inner().reset_missing(),
+        is_deletion: false,
+        allow_index_creation,
+        quota,
     };
     let task: SummarizedTaskView = meilisearch
-        .register_update(index_uid.into_inner(), update)
+        .register_idempotent_update(index_uid.into_inner(), update, idempotency_key.into_inner())
         .await?
         .into();
 
@@ -428,11 +602,24 @@ pub async fn update_all(
     Ok(HttpResponse::Accepted().json(task))
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GetSettingsQuery {
+    /// Whether the settings reset to their default value are given that value, so the settings
+    /// can be imported as they are into another index.
+    #[serde(default)]
+    export: bool,
+}
+
 pub async fn get_all(
     data: GuardedData<ActionPolicy<{ actions::SETTINGS_GET }>, MeiliSearch>,
     index_uid: web::Path<String>,
+    params: web::Query<GetSettingsQuery>,
 ) -> Result<HttpResponse, ResponseError> {
-    let settings = data.settings(index_uid.into_inner()).await?;
+    let mut settings = data.settings(index_uid.into_inner()).await?;
+    if params.export {
+        settings = settings.with_explicit_defaults();
+    }
     debug!("returns: {:?}", settings);
     Ok(HttpResponse::Ok().json(settings))
 }
@@ -440,20 +627,222 @@ pub async fn get_all(
 pub async fn delete_all(
     data: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
     index_uid: web::Path<String>,
+    idempotency_key: IdempotencyKey,
 ) -> Result<HttpResponse, ResponseError> {
     let settings = Settings::cleared().into_unchecked();
 
     let allow_index_creation = data.filters().allow_index_creation;
+    let quota = data.filters().quota;
     let update = Update::Settings {
         settings,
         is_deletion: true,
         allow_index_creation,
+        quota,
     };
     let task: SummarizedTaskView = data
-        .register_update(index_uid.into_inner(), update)
+        .register_idempotent_update(index_uid.into_inner(), update, idempotency_key.into_inner())
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// Lists the last versions of the settings of the index, from the oldest to the newest.
+pub async fn get_history(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_GET }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+) -> Result<HttpResponse, ResponseError> {
+    let history = meilisearch.settings_history(index_uid.into_inner()).await?;
+    debug!("returns: {:?}", history);
+    Ok(HttpResponse::Ok().json(json!({ "results": history })))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RollbackQuery {
+    version: u64,
+}
+
+/// Enqueues a settings update restoring a version of the settings from the history.
+pub async fn rollback(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+    params: web::Query<RollbackQuery>,
+    idempotency_key: IdempotencyKey,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Settings Rolled Back".to_string(), json!({}), Some(&req));
+
+    let task: SummarizedTaskView = meilisearch
+        .register_settings_rollback(
+            index_uid.into_inner(),
+            params.version,
+            idempotency_key.into_inner(),
+        )
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// Lists the settings that the update of the body would change, and whether each change reindexes
+/// the documents of the index. Nothing is updated.
+pub async fn diff(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_GET }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+    body: web::Json<Settings<Unchecked>>,
+) -> Result<HttpResponse, ResponseError> {
+    let diff = meilisearch
+        .settings_diff(index_uid.into_inner(), body.into_inner())
+        .await?;
+    debug!("returns: {:?}", diff);
+    Ok(HttpResponse::Ok().json(diff))
+}
+
+/// Replaces the synonyms of an index with the ones of a synonyms file in the Solr format.
+pub async fn import_synonyms(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+    mut body: Payload,
+    idempotency_key: IdempotencyKey,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.next().await {
+        bytes.extend_from_slice(&chunk.map_err(IndexControllerError::from)?);
+    }
+    let text = String::from_utf8(bytes).map_err(|_| {
+        MeilisearchHttpError::MalformedSynonyms("It must be encoded in UTF-8.".to_string())
+    })?;
+    let synonyms = parse_solr_synonyms(&text)?;
+
+    analytics.publish(
+        "Synonyms Imported".to_string(),
+        json!({ "synonyms": { "total": synonyms.len() } }),
+        Some(&req),
+    );
+
+    let settings = Settings {
+        synonyms: Setting::Set(synonyms),
+        ..Default::default()
+    };
+
+    let allow_index_creation = meilisearch.filters().allow_index_creation;
+    let quota = meilisearch.filters().quota;
+    let update = Update::Settings {
+        settings,
+        is_deletion: false,
+        allow_index_creation,
+        quota,
+    };
+    let task: SummarizedTaskView = meilisearch
+        .register_idempotent_update(index_uid.into_inner(), update, idempotency_key.into_inner())
         .await?
         .into();
 
     debug!("returns: {:?}", task);
     Ok(HttpResponse::Accepted().json(task))
 }
+
+/// Parses a synonyms file in the Solr format. Each line is either a list of equivalent words,
+/// `couch, sofa, divan`, where each word is a synonym of the others, or an explicit mapping,
+/// `phone, cellphone => iphone`, where the words on the left have the words on the right as
+/// synonyms but not the reverse. The blank lines and the lines starting with `#` are ignored.
+fn parse_solr_synonyms(text: &str) -> Result<BTreeMap<String, Vec<String>>, MeilisearchHttpError> {
+    fn words(list: &str) -> Vec<String> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|word| !word.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    let mut synonyms: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut add = |word: &str, synonym: &str| {
+        let entry = synonyms.entry(word.to_string()).or_default();
+        if word != synonym && !entry.iter().any(|s| s == synonym) {
+            entry.push(synonym.to_string());
+        }
+    };
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let malformed = |reason: &str| {
+            MeilisearchHttpError::MalformedSynonyms(format!("Line {}: {}", number + 1, reason))
+        };
+        match line.split("=>").collect::<Vec<_>>()[..] {
+            [equivalent] => {
+                let words = words(equivalent);
+                if words.len() < 2 {
+                    return Err(malformed(
+                        "a list of synonyms must contain at least two words.",
+                    ));
+                }
+                for word in &words {
+                    for synonym in &words {
+                        add(word, synonym);
+                    }
+                }
+            }
+            [from, to] => {
+                let (from, to) = (words(from), words(to));
+                if from.is_empty() || to.is_empty() {
+                    return Err(malformed(
+                        "both sides of `=>` must contain at least one word.",
+                    ));
+                }
+                for word in &from {
+                    for synonym in &to {
+                        add(word, synonym);
+                    }
+                }
+            }
+            _ => return Err(malformed("a mapping can't contain more than one `=>`.")),
+        }
+    }
+
+    Ok(synonyms)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_solr_synonyms() {
+        let text = "
+            # equivalent words
+            couch, sofa
+            phone, cellphone => iphone
+            phone => smartphone, iphone
+        ";
+        let synonyms = parse_solr_synonyms(text).unwrap();
+        let expected: BTreeMap<String, Vec<String>> = [
+            ("cellphone", vec!["iphone"]),
+            ("couch", vec!["sofa"]),
+            ("phone", vec!["iphone", "smartphone"]),
+            ("sofa", vec!["couch"]),
+        ]
+        .into_iter()
+        .map(|(word, synonyms)| {
+            (
+                word.to_string(),
+                synonyms.into_iter().map(String::from).collect(),
+            )
+        })
+        .collect();
+        assert_eq!(synonyms, expected);
+
+        assert!(parse_solr_synonyms("couch").is_err());
+        assert!(parse_solr_synonyms("phone => ").is_err());
+        assert!(parse_solr_synonyms("a => b => c").is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::index::SuggestQuery;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::add_search_rules;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(suggest))));
+}
+
+/// Suggests completions of a query, from the queries searched on the index and from the words of
+/// its documents.
+pub async fn suggest(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    index_uid: web::Path<String>,
+    params: web::Json<SuggestQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let mut query = params.into_inner();
+    debug!("suggest called with params: {:?}", query);
+
+    analytics.publish(
+        "Suggestions Requested".to_string(),
+        json!({
+            "limit": query.limit,
+            "filter": query.filter.is_some(),
+        }),
+        Some(&req),
+    );
+
+    let index_uid = index_uid.into_inner();
+    // Tenant token search_rules.
+    if let Some(search_rules) = meilisearch
+        .filters()
+        .search_rules
+        .get_index_search_rules(&index_uid)
+    {
+        add_search_rules(&mut query.filter, search_rules);
+    }
+
+    let result = meilisearch.suggest(index_uid, query).await?;
+
+    debug!("returns: {:?}", result);
+    Ok(HttpResponse::Ok().json(result))
+}
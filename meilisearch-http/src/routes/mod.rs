@@ -11,19 +11,31 @@ use meilisearch_types::star_or::StarOr;
 
 use crate::extractors::authentication::{policies::*, GuardedData};
 
+mod aliases;
+mod analytics;
 mod api_key;
+mod drain;
 mod dump;
+mod index_templates;
 pub mod indexes;
+mod multi_search;
+mod swap_indexes;
 mod tasks;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(web::scope("/tasks").configure(tasks::configure))
+        .service(web::scope("/analytics").configure(analytics::configure))
         .service(web::resource("/health").route(web::get().to(get_health)))
         .service(web::scope("/keys").configure(api_key::configure))
         .service(web::scope("/dumps").configure(dump::configure))
+        .service(web::scope("/drain").configure(drain::configure))
         .service(web::resource("/stats").route(web::get().to(get_stats)))
         .service(web::resource("/version").route(web::get().to(get_version)))
-        .service(web::scope("/indexes").configure(indexes::configure));
+        .service(web::scope("/indexes").configure(indexes::configure))
+        .service(web::scope("/swap-indexes").configure(swap_indexes::configure))
+        .service(web::scope("/aliases").configure(aliases::configure))
+        .service(web::scope("/index-templates").configure(index_templates::configure))
+        .service(web::scope("/multi-search").configure(multi_search::configure));
 }
 
 /// Extracts the raw values from the `StarOr` types and
@@ -0,0 +1,234 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures::future::try_join_all;
+use log::debug;
+use meilisearch_auth::SearchRules;
+use meilisearch_lib::index::{FederatedQuery, SearchQuery, SearchResult, DEFAULT_SEARCH_LIMIT};
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::{Code, ResponseError};
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::analytics::{Analytics, SearchAggregator};
+use crate::extractors::authentication::error::AuthenticationError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::{
+    add_search_rules, authorize_debug, experiment_key, logged_search,
+};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(multi_search_with_post))))
+        .service(
+            web::resource("/federated")
+                .route(web::post().to(SeqHandler(federated_search_with_post))),
+        );
+}
+
+const DEFAULT_WEIGHT: f64 = 1.0;
+
+// The queries aren't deserialized with a flattened `SearchQuery`, since `deny_unknown_fields` is
+// ignored on a flattened struct: the search parameters are the fields left once the others are
+// taken out.
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "Map<String, Value>")]
+pub struct SearchQueryWithIndex {
+    index_uid: String,
+    query: SearchQuery,
+}
+
+impl TryFrom<Map<String, Value>> for SearchQueryWithIndex {
+    type Error = serde_json::Error;
+
+    fn try_from(mut fields: Map<String, Value>) -> Result<Self, Self::Error> {
+        let index_uid = take_field(&mut fields, "indexUid")?
+            .ok_or_else(|| serde_json::Error::missing_field("indexUid"))?;
+        let query = serde_json::from_value(Value::Object(fields))?;
+
+        Ok(Self { index_uid, query })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "Map<String, Value>")]
+pub struct FederatedSearchQueryWithIndex {
+    index_uid: String,
+    weight: f64,
+    query: SearchQuery,
+}
+
+impl TryFrom<Map<String, Value>> for FederatedSearchQueryWithIndex {
+    type Error = serde_json::Error;
+
+    fn try_from(mut fields: Map<String, Value>) -> Result<Self, Self::Error> {
+        let index_uid = take_field(&mut fields, "indexUid")?
+            .ok_or_else(|| serde_json::Error::missing_field("indexUid"))?;
+        let weight = take_field(&mut fields, "weight")?.unwrap_or(DEFAULT_WEIGHT);
+        let query = serde_json::from_value(Value::Object(fields))?;
+
+        Ok(Self {
+            index_uid,
+            weight,
+            query,
+        })
+    }
+}
+
+/// Removes the field `name` from the fields of a query and deserializes it.
+fn take_field<T: DeserializeOwned>(
+    fields: &mut Map<String, Value>,
+    name: &str,
+) -> Result<Option<T>, serde_json::Error> {
+    fields
+        .remove(name)
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| serde_json::Error::custom(format!("`{}`: {}", name, e)))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FederatedSearchPayload {
+    queries: Vec<FederatedSearchQueryWithIndex>,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "DEFAULT_SEARCH_LIMIT")]
+    limit: usize,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultWithIndex {
+    index_uid: String,
+    #[serde(flatten)]
+    result: SearchResult,
+}
+
+/// Performs several searches, possibly on different indexes, concurrently. The results are
+/// returned in the order of the queries.
+pub async fn multi_search_with_post(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    params: web::Json<Vec<SearchQueryWithIndex>>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let queries = params.into_inner();
+    debug!("multi-search called with params: {:?}", queries);
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let mut aggregates = Vec::with_capacity(queries.len());
+    let mut searches = Vec::with_capacity(queries.len());
+    for SearchQueryWithIndex {
+        index_uid,
+        mut query,
+    } in queries
+    {
+        authorize_debug(&query, &req).await?;
+        query.experiment_key = experiment_key(meilisearch.filters(), &req);
+        let logged_search = logged_search(&meilisearch, &index_uid, &query, &req);
+        authorize_query(search_rules, &index_uid, &mut query)?;
+
+        aggregates.push(SearchAggregator::from_query(&query, &req));
+
+        let meilisearch = &meilisearch;
+        searches.push(async move {
+            let result = meilisearch.search(index_uid.clone(), query).await?;
+            if let Some(logged_search) = logged_search {
+                meilisearch.record_search(logged_search.with_result(&result));
+            }
+            Ok::<_, ResponseError>(SearchResultWithIndex { index_uid, result })
+        });
+    }
+
+    let results = try_join_all(searches).await;
+    if let Ok(ref results) = results {
+        for (aggregate, result) in aggregates.iter_mut().zip(results) {
+            aggregate.succeed(&result.result);
+        }
+    }
+    for aggregate in aggregates {
+        analytics.post_search(aggregate);
+    }
+
+    let results = results?;
+
+    debug!("returns: {:?}", results);
+    Ok(HttpResponse::Ok().json(json!({ "results": results })))
+}
+
+/// Performs several searches, possibly on different indexes, concurrently, and merges their hits
+/// into a single list ordered by their ranking score, normalized across the indexes, multiplied by
+/// the weight of their query.
+pub async fn federated_search_with_post(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    params: web::Json<FederatedSearchPayload>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let FederatedSearchPayload {
+        queries,
+        offset,
+        limit,
+    } = params.into_inner();
+    debug!("federated search called with params: {:?}", queries);
+
+    analytics.publish(
+        "Federated Search Performed".to_string(),
+        json!({ "queries": queries.len() }),
+        Some(&req),
+    );
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let mut federated_queries = Vec::with_capacity(queries.len());
+    for FederatedSearchQueryWithIndex {
+        index_uid,
+        weight,
+        mut query,
+    } in queries
+    {
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(ResponseError::from_msg(
+                format!(
+                    "The weight of the queries on `{}` must be a positive number, found `{}`.",
+                    index_uid, weight
+                ),
+                Code::BadRequest,
+            ));
+        }
+
+        authorize_debug(&query, &req).await?;
+        query.experiment_key = experiment_key(meilisearch.filters(), &req);
+        authorize_query(search_rules, &index_uid, &mut query)?;
+
+        federated_queries.push(FederatedQuery {
+            index_uid,
+            query,
+            weight,
+        });
+    }
+
+    let result = meilisearch
+        .federated_search(federated_queries, offset, limit)
+        .await?;
+
+    debug!("returns: {:?}", result);
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// The routes aren't bound to an index, the access to the index of each query is checked here.
+fn authorize_query(
+    search_rules: &SearchRules,
+    index_uid: &str,
+    query: &mut SearchQuery,
+) -> Result<(), ResponseError> {
+    if !search_rules.is_index_authorized(index_uid) {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    // Tenant token search_rules.
+    if let Some(search_rules) = search_rules.get_index_search_rules(index_uid) {
+        add_search_rules(&mut query.filter, search_rules);
+    }
+
+    Ok(())
+}
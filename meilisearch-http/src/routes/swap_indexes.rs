@@ -0,0 +1,58 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_lib::MeiliSearch;
+use meilisearch_types::error::ResponseError;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::error::AuthenticationError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::task::SummarizedTaskView;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(swap_indexes))));
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SwapIndexesPayload {
+    indexes: (String, String),
+}
+
+/// Registers a task swapping the indexes of each pair, all at once. The tasks enqueued before the
+/// swap on these indexes are processed first, and the ones enqueued after it apply to the swapped
+/// indexes.
+pub async fn swap_indexes(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
+    body: web::Json<Vec<SwapIndexesPayload>>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let swaps: Vec<_> = body
+        .into_inner()
+        .into_iter()
+        .map(|payload| payload.indexes)
+        .collect();
+    debug!("called with params: {:?}", swaps);
+
+    analytics.publish(
+        "Indexes Swapped".to_string(),
+        json!({ "swap_operation_number": swaps.len() }),
+        Some(&req),
+    );
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let authorized = swaps.iter().all(|(lhs, rhs)| {
+        search_rules.is_index_authorized(lhs) && search_rules.is_index_authorized(rhs)
+    });
+    if !authorized {
+        return Err(AuthenticationError::InvalidToken.into());
+    }
+
+    let task: SummarizedTaskView = meilisearch.register_index_swap_task(swaps).await?.into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
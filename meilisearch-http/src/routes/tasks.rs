@@ -1,18 +1,32 @@
+use std::cmp::Reverse;
+
+use actix_web::error::PayloadError;
+use actix_web::web::Bytes;
 use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_auth::AuthFilter;
+use meilisearch_lib::index::{Document, OnDuplicate, Settings, Unchecked};
+use meilisearch_lib::index_controller::{DocumentAdditionFormat, Update};
+use meilisearch_lib::milli::update::IndexDocumentsMethod;
 use meilisearch_lib::tasks::task::{TaskContent, TaskEvent, TaskId};
-use meilisearch_lib::tasks::TaskFilter;
+use meilisearch_lib::tasks::{DetailsCondition, TaskFilter};
 use meilisearch_lib::MeiliSearch;
-use meilisearch_types::error::ResponseError;
-use meilisearch_types::index_uid::IndexUid;
+use meilisearch_types::error::{Code, ResponseError};
+use meilisearch_types::index_uid::IndexUidPattern;
 use meilisearch_types::star_or::StarOr;
 use serde::Deserialize;
 use serde_cs::vec::CS;
-use serde_json::json;
+use serde_json::{json, Value};
+use time::Duration;
 
 use crate::analytics::Analytics;
+use crate::extractors::authentication::error::AuthenticationError;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
-use crate::task::{TaskListView, TaskStatus, TaskType, TaskView};
+use crate::task::{
+    parse_duration, task_duration, SummarizedTaskView, TaskErrorSummaryView, TaskListView,
+    TaskStatus, TaskType, TaskView,
+};
 
 use super::fold_star_or;
 
@@ -20,7 +34,14 @@ const DEFAULT_LIMIT: fn() -> usize = || 20;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(web::resource("").route(web::get().to(SeqHandler(get_tasks))))
-        .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))));
+        .service(web::resource("/batch").route(web::post().to(SeqHandler(register_task_batch))))
+        .service(web::resource("/barrier").route(web::post().to(SeqHandler(register_barrier))))
+        .service(
+            web::resource("/errors/summary")
+                .route(web::get().to(SeqHandler(get_task_errors_summary))),
+        )
+        .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))))
+        .service(web::resource("/{task_id}/abort").route(web::post().to(SeqHandler(abort_task))));
 }
 
 #[derive(Deserialize, Debug)]
@@ -29,12 +50,227 @@ pub struct TaskFilterQuery {
     #[serde(rename = "type")]
     type_: Option<CS<StarOr<TaskType>>>,
     status: Option<CS<StarOr<TaskStatus>>>,
-    index_uid: Option<CS<StarOr<IndexUid>>>,
+    index_uid: Option<CS<StarOr<IndexUidPattern>>>,
+    min_duration: Option<String>,
+    max_duration: Option<String>,
+    details: Option<String>,
+    sort: Option<TaskSort>,
     #[serde(default = "DEFAULT_LIMIT")]
     limit: usize,
     from: Option<TaskId>,
 }
 
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub enum TaskSort {
+    #[serde(rename = "duration:asc")]
+    DurationAsc,
+    #[serde(rename = "duration:desc")]
+    DurationDesc,
+}
+
+fn parse_duration_param(
+    name: &str,
+    value: Option<String>,
+) -> Result<Option<Duration>, ResponseError> {
+    value
+        .map(|value| {
+            parse_duration(&value).ok_or_else(|| {
+                ResponseError::from_msg(
+                    format!(
+                        "`{}` is not a valid ISO 8601 duration for the `{}` parameter.",
+                        value, name
+                    ),
+                    Code::BadRequest,
+                )
+            })
+        })
+        .transpose()
+}
+
+/// Parses the comma separated conditions of the `details` parameter, which must all match.
+fn parse_details_param(
+    value: Option<String>,
+) -> Result<Option<Vec<DetailsCondition>>, ResponseError> {
+    value
+        .map(|value| {
+            value
+                .split(',')
+                .map(|condition| {
+                    DetailsCondition::parse(condition).ok_or_else(|| {
+                        ResponseError::from_msg(
+                            format!(
+                                "`{}` is not a valid condition for the `details` parameter. \
+                                Expected `<field><operator><value>`, with an operator among `=`, \
+                                `!=`, `>`, `>=`, `<` and `<=`, the last four only accepting numbers.",
+                                condition
+                            ),
+                            Code::BadRequest,
+                        )
+                    })
+                })
+                .collect()
+        })
+        .transpose()
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BarrierPayload {
+    index_uids: Option<Vec<String>>,
+}
+
+/// An operation of a batch registered through `POST /tasks/batch`.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "camelCase", deny_unknown_fields)]
+pub enum TaskOperation {
+    #[serde(rename_all = "camelCase")]
+    IndexCreation {
+        index_uid: String,
+        primary_key: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    IndexUpdate {
+        index_uid: String,
+        primary_key: Option<String>,
+        #[serde(default)]
+        max_size: Option<usize>,
+        #[serde(default)]
+        frozen: Option<bool>,
+    },
+    #[serde(rename_all = "camelCase")]
+    IndexDeletion { index_uid: String },
+    #[serde(rename_all = "camelCase")]
+    SettingsUpdate {
+        index_uid: String,
+        settings: Settings<Unchecked>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentAddition {
+        index_uid: String,
+        documents: Vec<Document>,
+        primary_key: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentUpdate {
+        index_uid: String,
+        documents: Vec<Document>,
+        primary_key: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentDeletion {
+        index_uid: String,
+        document_ids: Vec<Value>,
+    },
+}
+
+impl TaskOperation {
+    fn index_uid(&self) -> &str {
+        match self {
+            TaskOperation::IndexCreation { index_uid, .. }
+            | TaskOperation::IndexUpdate { index_uid, .. }
+            | TaskOperation::IndexDeletion { index_uid }
+            | TaskOperation::SettingsUpdate { index_uid, .. }
+            | TaskOperation::DocumentAddition { index_uid, .. }
+            | TaskOperation::DocumentUpdate { index_uid, .. }
+            | TaskOperation::DocumentDeletion { index_uid, .. } => index_uid,
+        }
+    }
+
+    fn into_update(self, filters: &AuthFilter) -> Result<(String, Update), ResponseError> {
+        let allow_index_creation = filters.allow_index_creation;
+        let quota = filters.quota;
+        let documents_update = |documents: Vec<Document>, primary_key, method| {
+            let payload = serde_json::to_vec(&documents)
+                .map_err(|e| ResponseError::from_msg(e.to_string(), Code::Internal))?;
+            let payload = futures::stream::iter(vec![Ok::<_, PayloadError>(Bytes::from(payload))]);
+            Ok::<_, ResponseError>(Update::DocumentAddition {
+                payload: Box::new(payload),
+                primary_key,
+                method,
+                format: DocumentAdditionFormat::Json,
+                allow_index_creation,
+                auto_generate_primary_key: false,
+                merge_nested: false,
+                on_duplicate: OnDuplicate::Replace,
+                quota,
+            })
+        };
+
+        let update = match self {
+            TaskOperation::IndexCreation {
+                index_uid,
+                primary_key,
+            } => (index_uid, Update::CreateIndex { primary_key, quota }),
+            TaskOperation::IndexUpdate {
+                index_uid,
+                primary_key,
+                max_size,
+                frozen,
+            } => (
+                index_uid,
+                Update::UpdateIndex {
+                    primary_key,
+                    max_size,
+                    frozen,
+                },
+            ),
+            TaskOperation::IndexDeletion { index_uid } => (index_uid, Update::DeleteIndex),
+            TaskOperation::SettingsUpdate {
+                index_uid,
+                settings,
+            } => (
+                index_uid,
+                Update::Settings {
+                    settings,
+                    is_deletion: false,
+                    allow_index_creation,
+                    quota,
+                },
+            ),
+            TaskOperation::DocumentAddition {
+                index_uid,
+                documents,
+                primary_key,
+            } => (
+                index_uid,
+                documents_update(
+                    documents,
+                    primary_key,
+                    IndexDocumentsMethod::ReplaceDocuments,
+                )?,
+            ),
+            TaskOperation::DocumentUpdate {
+                index_uid,
+                documents,
+                primary_key,
+            } => (
+                index_uid,
+                documents_update(
+                    documents,
+                    primary_key,
+                    IndexDocumentsMethod::UpdateDocuments,
+                )?,
+            ),
+            TaskOperation::DocumentDeletion {
+                index_uid,
+                document_ids,
+            } => {
+                let ids = document_ids
+                    .iter()
+                    .map(|v| {
+                        v.as_str()
+                            .map(String::from)
+                            .unwrap_or_else(|| v.to_string())
+                    })
+                    .collect();
+                (index_uid, Update::DeleteDocuments(ids))
+            }
+        };
+
+        Ok(update)
+    }
+}
+
 #[rustfmt::skip]
 fn task_type_matches_content(type_: &TaskType, content: &TaskContent) -> bool {
     matches!((type_, content),
@@ -43,7 +279,16 @@ fn task_type_matches_content(type_: &TaskType, content: &TaskContent) -> bool {
         | (TaskType::IndexDeletion, TaskContent::IndexDeletion { .. })
         | (TaskType::DocumentAdditionOrUpdate, TaskContent::DocumentAddition { .. })
         | (TaskType::DocumentDeletion, TaskContent::DocumentDeletion{ .. })
+        | (TaskType::DocumentDeletion, TaskContent::DocumentDeletionByFilter { .. })
+        | (TaskType::DocumentEdition, TaskContent::DocumentEdition { .. })
         | (TaskType::SettingsUpdate, TaskContent::SettingsUpdate { .. })
+        | (TaskType::Barrier, TaskContent::Barrier { .. })
+        | (TaskType::IndexSwap, TaskContent::IndexSwap { .. })
+        | (TaskType::IndexClone, TaskContent::IndexClone { .. })
+        | (TaskType::IndexCompaction, TaskContent::IndexCompaction { .. })
+        | (TaskType::IndexRestoration, TaskContent::IndexRestoration { .. })
+        | (TaskType::IndexIntegrityCheck, TaskContent::IndexIntegrityCheck { .. })
+        | (TaskType::IndexWarmup, TaskContent::IndexWarmup { .. })
     )
 }
 
@@ -75,10 +320,18 @@ async fn get_tasks(
         type_,
         status,
         index_uid,
+        min_duration,
+        max_duration,
+        details,
+        sort,
         limit,
         from,
     } = params.into_inner();
 
+    let min_duration = parse_duration_param("minDuration", min_duration)?;
+    let max_duration = parse_duration_param("maxDuration", max_duration)?;
+    let details = parse_details_param(details)?;
+
     let search_rules = &meilisearch.filters().search_rules;
 
     // We first transform a potential indexUid=* into a "not specified indexUid filter"
@@ -113,7 +366,12 @@ async fn get_tasks(
     };
 
     // Then we complete the task filter with other potential status and types filters.
-    let filters = if type_.is_some() || status.is_some() {
+    let filters = if type_.is_some()
+        || status.is_some()
+        || min_duration.is_some()
+        || max_duration.is_some()
+        || details.is_some()
+    {
         let mut filters = indexes_filters.unwrap_or_default();
         filters.filter_fn(move |task| {
             let matches_type = match &type_ {
@@ -130,22 +388,69 @@ async fn get_tasks(
                 None => true,
             };
 
-            matches_type && matches_status
+            // Only the finished tasks have a duration.
+            let matches_duration = match (min_duration, max_duration) {
+                (None, None) => true,
+                (min, max) => task_duration(&task.events).map_or(false, |duration| {
+                    min.map_or(true, |min| duration >= min)
+                        && max.map_or(true, |max| duration <= max)
+                }),
+            };
+
+            matches_type && matches_status && matches_duration
         });
+        for condition in details.into_iter().flatten() {
+            filters.filter_details(condition);
+        }
         Some(filters)
     } else {
         indexes_filters
     };
 
+    // Sorting on the duration requires all the matching tasks, the results can't be paginated: the
+    // cursor of the tasks is their uid, which doesn't follow their duration.
+    if let Some(sort) = sort {
+        if from.is_some() {
+            return Err(ResponseError::from_msg(
+                "The `from` parameter can't be used along with the `sort` parameter.".to_string(),
+                Code::BadRequest,
+            ));
+        }
+        let (mut tasks, counts) = meilisearch
+            .list_tasks_with_counts(filters, None, None)
+            .await?;
+        // The unfinished tasks, that have no duration, always come last.
+        match sort {
+            TaskSort::DurationAsc => tasks.sort_by_key(|t| {
+                let duration = task_duration(&t.events);
+                (duration.is_none(), duration)
+            }),
+            TaskSort::DurationDesc => tasks.sort_by_key(|t| {
+                let duration = task_duration(&t.events);
+                (duration.is_none(), Reverse(duration))
+            }),
+        }
+
+        let results: Vec<_> = tasks.into_iter().take(limit).map(TaskView::from).collect();
+        let tasks = TaskListView {
+            from: results.first().map(|t| t.uid),
+            results,
+            limit,
+            next: None,
+            total: counts.total,
+            enqueued: counts.enqueued,
+        };
+
+        return Ok(HttpResponse::Ok().json(tasks));
+    }
+
     // We +1 just to know if there is more after this "page" or not.
     let limit = limit.saturating_add(1);
 
-    let mut tasks_results: Vec<_> = meilisearch
-        .list_tasks(filters, Some(limit), from)
-        .await?
-        .into_iter()
-        .map(TaskView::from)
-        .collect();
+    let (tasks, counts) = meilisearch
+        .list_tasks_with_counts(filters, Some(limit), from)
+        .await?;
+    let mut tasks_results: Vec<_> = tasks.into_iter().map(TaskView::from).collect();
 
     // If we were able to fetch the number +1 tasks we asked
     // it means that there is more to come.
@@ -162,11 +467,125 @@ async fn get_tasks(
         limit: limit.saturating_sub(1),
         from,
         next,
+        total: counts.total,
+        enqueued: counts.enqueued,
     };
 
     Ok(HttpResponse::Ok().json(tasks))
 }
 
+async fn register_task_batch(
+    meilisearch: GuardedData<ActionPolicy<{ actions::ALL }>, MeiliSearch>,
+    body: web::Json<Vec<TaskOperation>>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let operations = body.into_inner();
+    debug!("called with params: {:?}", operations);
+
+    analytics.publish(
+        "Tasks Batch Registered".to_string(),
+        json!({ "operations": operations.len() }),
+        Some(&req),
+    );
+
+    let filters = meilisearch.filters();
+    let mut updates = Vec::with_capacity(operations.len());
+    for operation in operations {
+        if !filters
+            .search_rules
+            .is_index_authorized(operation.index_uid())
+        {
+            return Err(AuthenticationError::InvalidToken.into());
+        }
+        updates.push(operation.into_update(filters)?);
+    }
+
+    let tasks: Vec<SummarizedTaskView> = meilisearch
+        .register_updates(updates)
+        .await?
+        .into_iter()
+        .map(SummarizedTaskView::from)
+        .collect();
+
+    debug!("returns: {:?}", tasks);
+    Ok(HttpResponse::Accepted().json(tasks))
+}
+
+async fn register_barrier(
+    meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
+    body: web::Json<BarrierPayload>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let BarrierPayload { index_uids } = body.into_inner();
+    debug!("called with params: {:?}", index_uids);
+
+    analytics.publish(
+        "Barrier Created".to_string(),
+        json!({ "per_index_uid": index_uids.is_some() }),
+        Some(&req),
+    );
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let index_uids = match index_uids {
+        Some(index_uids) => {
+            if !index_uids
+                .iter()
+                .all(|index_uid| search_rules.is_index_authorized(index_uid))
+            {
+                return Err(AuthenticationError::InvalidToken.into());
+            }
+            Some(index_uids)
+        }
+        // A barrier without index restriction holds back the tasks of every index, which a key
+        // restricted to some indexes can't do.
+        None if !search_rules.is_index_authorized("*") => Some(
+            search_rules
+                .clone()
+                .into_iter()
+                .map(|(index, _policy)| index)
+                .collect(),
+        ),
+        None => None,
+    };
+
+    let task: SummarizedTaskView = meilisearch.register_barrier_task(index_uids).await?.into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+async fn get_task_errors_summary(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish(
+        "Tasks Errors Summary Seen".to_string(),
+        json!({}),
+        Some(&req),
+    );
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let mut filters = if search_rules.is_index_authorized("*") {
+        TaskFilter::default()
+    } else {
+        let mut filters = TaskFilter::default();
+        for (index, _policy) in search_rules.clone() {
+            filters.filter_index(index);
+        }
+        filters
+    };
+    filters.filter_fn(|task| task_status_matches_events(&TaskStatus::Failed, &task.events));
+
+    let tasks = meilisearch.list_tasks(Some(filters), None, None).await?;
+    let summary = TaskErrorSummaryView::from_failed_tasks(tasks);
+
+    debug!("returns: {:?}", summary);
+    Ok(HttpResponse::Ok().json(summary))
+}
+
 async fn get_task(
     meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
     task_id: web::Path<TaskId>,
@@ -197,3 +616,19 @@ async fn get_task(
 
     Ok(HttpResponse::Ok().json(task))
 }
+
+/// Aborts the processing of a task that got stuck. The writes of the task and the other tasks of
+/// its batch are rolled back, and they are marked as failed once their processing stops.
+async fn abort_task(
+    meilisearch: GuardedData<MasterPolicy, MeiliSearch>,
+    task_id: web::Path<TaskId>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish("Task Aborted".to_string(), json!({}), Some(&req));
+
+    let task: SummarizedTaskView = meilisearch.abort_task(task_id.into_inner()).await?.into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
@@ -1,15 +1,18 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{self, Write};
 use std::str::FromStr;
 use std::write;
 
-use meilisearch_lib::index::{Settings, Unchecked};
+use meilisearch_lib::index::{DocumentError, Settings, Unchecked};
 use meilisearch_lib::tasks::batch::BatchId;
 use meilisearch_lib::tasks::task::{
     DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskResult,
 };
 use meilisearch_types::error::ResponseError;
 use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
 use time::{Duration, OffsetDateTime};
 
 use crate::AUTOBATCHING_ENABLED;
@@ -22,8 +25,16 @@ pub enum TaskType {
     IndexDeletion,
     DocumentAdditionOrUpdate,
     DocumentDeletion,
+    DocumentEdition,
     SettingsUpdate,
     DumpCreation,
+    Barrier,
+    IndexSwap,
+    IndexClone,
+    IndexCompaction,
+    IndexRestoration,
+    IndexIntegrityCheck,
+    IndexWarmup,
 }
 
 impl From<TaskContent> for TaskType {
@@ -33,9 +44,19 @@ impl From<TaskContent> for TaskType {
             TaskContent::IndexUpdate { .. } => TaskType::IndexUpdate,
             TaskContent::IndexDeletion { .. } => TaskType::IndexDeletion,
             TaskContent::DocumentAddition { .. } => TaskType::DocumentAdditionOrUpdate,
-            TaskContent::DocumentDeletion { .. } => TaskType::DocumentDeletion,
+            TaskContent::DocumentDeletion { .. } | TaskContent::DocumentDeletionByFilter { .. } => {
+                TaskType::DocumentDeletion
+            }
+            TaskContent::DocumentEdition { .. } => TaskType::DocumentEdition,
             TaskContent::SettingsUpdate { .. } => TaskType::SettingsUpdate,
             TaskContent::Dump { .. } => TaskType::DumpCreation,
+            TaskContent::Barrier { .. } => TaskType::Barrier,
+            TaskContent::IndexSwap { .. } => TaskType::IndexSwap,
+            TaskContent::IndexClone { .. } => TaskType::IndexClone,
+            TaskContent::IndexCompaction { .. } => TaskType::IndexCompaction,
+            TaskContent::IndexRestoration { .. } => TaskType::IndexRestoration,
+            TaskContent::IndexIntegrityCheck { .. } => TaskType::IndexIntegrityCheck,
+            TaskContent::IndexWarmup { .. } => TaskType::IndexWarmup,
         }
     }
 }
@@ -51,7 +72,8 @@ impl fmt::Display for TaskTypeError {
             f,
             "invalid task type `{}`, expecting one of: \
             indexCreation, indexUpdate, indexDeletion, documentAdditionOrUpdate, \
-            documentDeletion, settingsUpdate, dumpCreation",
+            documentDeletion, documentEdition, settingsUpdate, dumpCreation, barrier, indexSwap, indexClone, \
+            indexCompaction, indexRestoration, indexIntegrityCheck, indexWarmup",
             self.invalid_type
         )
     }
@@ -73,10 +95,26 @@ impl FromStr for TaskType {
             Ok(TaskType::DocumentAdditionOrUpdate)
         } else if type_.eq_ignore_ascii_case("documentDeletion") {
             Ok(TaskType::DocumentDeletion)
+        } else if type_.eq_ignore_ascii_case("documentEdition") {
+            Ok(TaskType::DocumentEdition)
         } else if type_.eq_ignore_ascii_case("settingsUpdate") {
             Ok(TaskType::SettingsUpdate)
         } else if type_.eq_ignore_ascii_case("dumpCreation") {
             Ok(TaskType::DumpCreation)
+        } else if type_.eq_ignore_ascii_case("barrier") {
+            Ok(TaskType::Barrier)
+        } else if type_.eq_ignore_ascii_case("indexSwap") {
+            Ok(TaskType::IndexSwap)
+        } else if type_.eq_ignore_ascii_case("indexClone") {
+            Ok(TaskType::IndexClone)
+        } else if type_.eq_ignore_ascii_case("indexCompaction") {
+            Ok(TaskType::IndexCompaction)
+        } else if type_.eq_ignore_ascii_case("indexRestoration") {
+            Ok(TaskType::IndexRestoration)
+        } else if type_.eq_ignore_ascii_case("indexIntegrityCheck") {
+            Ok(TaskType::IndexIntegrityCheck)
+        } else if type_.eq_ignore_ascii_case("indexWarmup") {
+            Ok(TaskType::IndexWarmup)
         } else {
             Err(TaskTypeError {
                 invalid_type: type_.to_string(),
@@ -140,6 +178,8 @@ enum TaskDetails {
     DocumentAddition {
         received_documents: usize,
         indexed_documents: Option<u64>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        failed_documents: Vec<DocumentError>,
     },
     #[serde(rename_all = "camelCase")]
     Settings {
@@ -147,16 +187,50 @@ enum TaskDetails {
         settings: Settings<Unchecked>,
     },
     #[serde(rename_all = "camelCase")]
-    IndexInfo { primary_key: Option<String> },
+    IndexInfo {
+        primary_key: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_size: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        frozen: Option<bool>,
+    },
     #[serde(rename_all = "camelCase")]
     DocumentDeletion {
         received_document_ids: usize,
         deleted_documents: Option<u64>,
     },
     #[serde(rename_all = "camelCase")]
+    DocumentDeletionByFilter {
+        original_filter: Value,
+        deleted_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentEdition {
+        function: String,
+        original_filter: Option<Value>,
+        edited_documents: Option<u64>,
+        deleted_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
     ClearAll { deleted_documents: Option<u64> },
     #[serde(rename_all = "camelCase")]
     Dump { dump_uid: String },
+    #[serde(rename_all = "camelCase")]
+    Barrier { index_uids: Option<Vec<String>> },
+    #[serde(rename_all = "camelCase")]
+    IndexSwap { swaps: Vec<IndexSwapView> },
+    #[serde(rename_all = "camelCase")]
+    IndexClone { source_uid: String },
+    #[serde(rename_all = "camelCase")]
+    IntegrityCheck { anomalies: Option<Vec<String>> },
+    #[serde(rename_all = "camelCase")]
+    Warmup { warmed_bytes: Option<u64> },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSwapView {
+    indexes: (String, String),
 }
 
 /// Serialize a `time::Duration` as a best effort ISO 8601 while waiting for
@@ -210,6 +284,63 @@ fn serialize_duration<S: Serializer>(
     }
 }
 
+/// Parses the ISO 8601 durations produced by `serialize_duration`, e.g. `P1DT2H3M4.5S`. Years,
+/// months and weeks are not supported since their length in seconds is ambiguous.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let mut rest = s.strip_prefix('P')?;
+    let mut duration = Duration::ZERO;
+    let mut in_time = false;
+
+    if rest.is_empty() {
+        return None;
+    }
+
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('T') {
+            if in_time || r.is_empty() {
+                return None;
+            }
+            in_time = true;
+            rest = r;
+            continue;
+        }
+
+        let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let (value, unit) = rest.split_at(end);
+        let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+        let whole: i64 = whole.parse().ok()?;
+        duration += match (in_time, unit.chars().next()?) {
+            (false, 'D') if fraction.is_empty() => Duration::days(whole),
+            (true, 'H') if fraction.is_empty() => Duration::hours(whole),
+            (true, 'M') if fraction.is_empty() => Duration::minutes(whole),
+            // only the seconds can have a fractional part, up to the nanosecond
+            (true, 'S') if fraction.len() <= 9 && fraction.bytes().all(|b| b.is_ascii_digit()) => {
+                let nanos = format!("{:0<9}", fraction).parse().ok()?;
+                Duration::new(whole, nanos)
+            }
+            _ => return None,
+        };
+        rest = &unit[1..];
+    }
+
+    Some(duration)
+}
+
+/// Returns the time spent processing a finished task, computed from its events.
+pub fn task_duration(events: &[TaskEvent]) -> Option<Duration> {
+    let started_at = events.iter().find_map(|e| match e {
+        TaskEvent::Processing(ts) => Some(*ts),
+        _ => None,
+    })?;
+
+    match events.last()? {
+        TaskEvent::Succeeded { timestamp, .. } | TaskEvent::Failed { timestamp, .. } => {
+            Some(*timestamp - started_at)
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskView {
@@ -250,6 +381,7 @@ impl From<Task> for TaskView {
                 let details = TaskDetails::DocumentAddition {
                     received_documents: documents_count,
                     indexed_documents: None,
+                    failed_documents: Vec::new(),
                 };
 
                 (TaskType::DocumentAdditionOrUpdate, Some(details))
@@ -264,6 +396,24 @@ impl From<Task> for TaskView {
                     deleted_documents: None,
                 }),
             ),
+            TaskContent::DocumentDeletionByFilter { filter, .. } => (
+                TaskType::DocumentDeletion,
+                Some(TaskDetails::DocumentDeletionByFilter {
+                    original_filter: filter,
+                    deleted_documents: None,
+                }),
+            ),
+            TaskContent::DocumentEdition {
+                function, filter, ..
+            } => (
+                TaskType::DocumentEdition,
+                Some(TaskDetails::DocumentEdition {
+                    function,
+                    original_filter: filter,
+                    edited_documents: None,
+                    deleted_documents: None,
+                }),
+            ),
             TaskContent::DocumentDeletion {
                 deletion: DocumentDeletion::Clear,
                 ..
@@ -285,16 +435,62 @@ impl From<Task> for TaskView {
             ),
             TaskContent::IndexCreation { primary_key, .. } => (
                 TaskType::IndexCreation,
-                Some(TaskDetails::IndexInfo { primary_key }),
+                Some(TaskDetails::IndexInfo {
+                    primary_key,
+                    max_size: None,
+                    frozen: None,
+                }),
             ),
-            TaskContent::IndexUpdate { primary_key, .. } => (
+            TaskContent::IndexUpdate {
+                primary_key,
+                max_size,
+                frozen,
+                ..
+            } => (
                 TaskType::IndexUpdate,
-                Some(TaskDetails::IndexInfo { primary_key }),
+                Some(TaskDetails::IndexInfo {
+                    primary_key,
+                    max_size,
+                    frozen,
+                }),
             ),
             TaskContent::Dump { uid } => (
                 TaskType::DumpCreation,
                 Some(TaskDetails::Dump { dump_uid: uid }),
             ),
+            TaskContent::Barrier { index_uids } => (
+                TaskType::Barrier,
+                Some(TaskDetails::Barrier {
+                    index_uids: index_uids.map(|uids| uids.into_iter().map(String::from).collect()),
+                }),
+            ),
+            TaskContent::IndexSwap { swaps } => (
+                TaskType::IndexSwap,
+                Some(TaskDetails::IndexSwap {
+                    swaps: swaps
+                        .into_iter()
+                        .map(|(lhs, rhs)| IndexSwapView {
+                            indexes: (lhs.into_inner(), rhs.into_inner()),
+                        })
+                        .collect(),
+                }),
+            ),
+            TaskContent::IndexClone { source_uid, .. } => (
+                TaskType::IndexClone,
+                Some(TaskDetails::IndexClone {
+                    source_uid: source_uid.into_inner(),
+                }),
+            ),
+            TaskContent::IndexCompaction { .. } => (TaskType::IndexCompaction, None),
+            TaskContent::IndexRestoration { .. } => (TaskType::IndexRestoration, None),
+            TaskContent::IndexIntegrityCheck { .. } => (
+                TaskType::IndexIntegrityCheck,
+                Some(TaskDetails::IntegrityCheck { anomalies: None }),
+            ),
+            TaskContent::IndexWarmup { .. } => (
+                TaskType::IndexWarmup,
+                Some(TaskDetails::Warmup { warmed_bytes: None }),
+            ),
         };
 
         // An event always has at least one event: "Created"
@@ -307,14 +503,16 @@ impl From<Task> for TaskView {
                     (
                         TaskResult::DocumentAddition {
                             indexed_documents: num,
-                            ..
+                            failed_documents: failed,
                         },
                         Some(TaskDetails::DocumentAddition {
                             ref mut indexed_documents,
+                            ref mut failed_documents,
                             ..
                         }),
                     ) => {
                         indexed_documents.replace(*num);
+                        *failed_documents = failed.clone();
                     }
                     (
                         TaskResult::DocumentDeletion {
@@ -328,6 +526,31 @@ impl From<Task> for TaskView {
                     ) => {
                         deleted_documents.replace(*docs);
                     }
+                    (
+                        TaskResult::DocumentDeletion {
+                            deleted_documents: docs,
+                        },
+                        Some(TaskDetails::DocumentDeletionByFilter {
+                            ref mut deleted_documents,
+                            ..
+                        }),
+                    ) => {
+                        deleted_documents.replace(*docs);
+                    }
+                    (
+                        TaskResult::DocumentEdition {
+                            edited_documents: edited,
+                            deleted_documents: deleted,
+                        },
+                        Some(TaskDetails::DocumentEdition {
+                            ref mut edited_documents,
+                            ref mut deleted_documents,
+                            ..
+                        }),
+                    ) => {
+                        edited_documents.replace(*edited);
+                        deleted_documents.replace(*deleted);
+                    }
                     (
                         TaskResult::ClearAll {
                             deleted_documents: docs,
@@ -338,6 +561,22 @@ impl From<Task> for TaskView {
                     ) => {
                         deleted_documents.replace(*docs);
                     }
+                    (
+                        TaskResult::IntegrityCheck { anomalies: found },
+                        Some(TaskDetails::IntegrityCheck { ref mut anomalies }),
+                    ) => {
+                        anomalies.replace(found.clone());
+                    }
+                    (
+                        TaskResult::Warmup {
+                            warmed_bytes: bytes,
+                        },
+                        Some(TaskDetails::Warmup {
+                            ref mut warmed_bytes,
+                        }),
+                    ) => {
+                        warmed_bytes.replace(*bytes);
+                    }
                     _ => (),
                 }
                 (TaskStatus::Succeeded, None, Some(*timestamp))
@@ -350,6 +589,20 @@ impl From<Task> for TaskView {
                     }) => {
                         deleted_documents.replace(0);
                     }
+                    Some(TaskDetails::DocumentDeletionByFilter {
+                        ref mut deleted_documents,
+                        ..
+                    }) => {
+                        deleted_documents.replace(0);
+                    }
+                    Some(TaskDetails::DocumentEdition {
+                        ref mut edited_documents,
+                        ref mut deleted_documents,
+                        ..
+                    }) => {
+                        edited_documents.replace(0);
+                        deleted_documents.replace(0);
+                    }
                     Some(TaskDetails::ClearAll {
                         ref mut deleted_documents,
                         ..
@@ -378,7 +631,7 @@ impl From<Task> for TaskView {
             _ => None,
         });
 
-        let duration = finished_at.zip(started_at).map(|(tf, ts)| (tf - ts));
+        let duration = task_duration(&events);
 
         let batch_uid = if AUTOBATCHING_ENABLED.load(std::sync::atomic::Ordering::Relaxed) {
             let id = events.iter().find_map(|e| match e {
@@ -412,6 +665,10 @@ pub struct TaskListView {
     pub limit: usize,
     pub from: Option<TaskId>,
     pub next: Option<TaskId>,
+    /// The number of tasks matching the filters, on all the pages.
+    pub total: usize,
+    /// The number of tasks matching the filters that are still enqueued.
+    pub enqueued: usize,
 }
 
 #[derive(Debug, Serialize)]
@@ -448,3 +705,53 @@ impl From<Task> for SummarizedTaskView {
         }
     }
 }
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskErrorGroupView {
+    code: String,
+    index_uid: Option<String>,
+    count: usize,
+    latest: TaskView,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskErrorSummaryView {
+    pub results: Vec<TaskErrorGroupView>,
+}
+
+impl TaskErrorSummaryView {
+    /// Groups the failed tasks by error code and index uid. The tasks are expected to be sorted
+    /// from the most recent to the oldest, so that the first task of a group is its latest example.
+    pub fn from_failed_tasks(tasks: impl IntoIterator<Item = Task>) -> Self {
+        let mut positions: HashMap<(String, Option<String>), usize> = HashMap::new();
+        let mut results: Vec<TaskErrorGroupView> = Vec::new();
+
+        for task in tasks {
+            let code = match task.events.last() {
+                Some(TaskEvent::Failed { error, .. }) => error.error_code().to_string(),
+                _ => continue,
+            };
+            let index_uid = task.index_uid().map(String::from);
+
+            match positions.entry((code, index_uid)) {
+                Entry::Occupied(entry) => results[*entry.get()].count += 1,
+                Entry::Vacant(entry) => {
+                    let (code, index_uid) = entry.key().clone();
+                    entry.insert(results.len());
+                    results.push(TaskErrorGroupView {
+                        code,
+                        index_uid,
+                        count: 1,
+                        latest: task.into(),
+                    });
+                }
+            }
+        }
+
+        // The sort is stable: groups with the same count stay ordered by their latest failure.
+        results.sort_by(|a, b| b.count.cmp(&a.count));
+
+        Self { results }
+    }
+}
@@ -1458,3 +1458,123 @@ async fn error_access_api_key_routes_no_master_key_set() {
     assert_eq!(expected_code, code, "{:?}", &response);
     assert_eq!(response, expected_response);
 }
+
+#[actix_rt::test]
+async fn add_api_key_with_quota() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["*"],
+        "actions": ["indexes.create", "documents.add", "tasks.get"],
+        "expiresAt": null,
+        "maxIndexes": 1,
+        "maxDocumentsPerIndex": 2,
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(201, code, "{:?}", &response);
+    assert_eq!(response["maxIndexes"], json!(1));
+    assert_eq!(response["maxDocumentsPerIndex"], json!(2));
+
+    server.use_api_key(response["key"].as_str().unwrap());
+    let index = server.index("products");
+
+    let (response, code) = index
+        .add_documents(json!([{ "id": 1 }, { "id": 2 }, { "id": 3 }]), None)
+        .await;
+    assert_eq!(403, code, "{:?}", &response);
+    assert_eq!(response["code"], json!("document_quota_exceeded"));
+
+    let (response, code) = index
+        .add_documents(json!([{ "id": 1 }, { "id": 2 }]), None)
+        .await;
+    assert_eq!(202, code, "{:?}", &response);
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], json!("succeeded"), "{:?}", &response);
+
+    let (response, code) = server.index("users").create(None).await;
+    assert_eq!(403, code, "{:?}", &response);
+    assert_eq!(response["code"], json!("index_quota_exceeded"));
+}
+
+#[actix_rt::test]
+async fn api_key_quota_checked_when_tasks_are_processed() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["*"],
+        "actions": ["indexes.create", "indexes.get", "documents.add", "tasks.get", "stats.get"],
+        "expiresAt": null,
+        "maxIndexes": 1,
+        "maxDocumentsPerIndex": 2,
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(201, code, "{:?}", &response);
+    server.use_api_key(response["key"].as_str().unwrap());
+
+    // each request is within the quota when it's registered, unless the tasks enqueued before
+    // were already processed, so the quota must also be checked when the tasks are processed
+    let index = server.index("products");
+    let mut task_ids = Vec::new();
+    for id in 0..3 {
+        let (response, code) = index.add_documents(json!([{ "id": id }]), None).await;
+        if code == 202 {
+            task_ids.push(response["taskUid"].as_u64().unwrap());
+        } else {
+            assert_eq!(response["code"], json!("document_quota_exceeded"));
+        }
+    }
+    for uid in ["users", "orders"] {
+        let (response, code) = server.index(uid).create(None).await;
+        if code == 202 {
+            task_ids.push(response["taskUid"].as_u64().unwrap());
+        } else {
+            assert_eq!(response["code"], json!("index_quota_exceeded"));
+        }
+    }
+
+    for task_id in task_ids {
+        let response = index.wait_task(task_id).await;
+        if response["status"] == json!("failed") {
+            let code = response["error"]["code"].as_str().unwrap();
+            assert!(
+                ["document_quota_exceeded", "index_quota_exceeded"].contains(&code),
+                "{:?}",
+                &response
+            );
+        }
+    }
+
+    let (response, code) = index.stats().await;
+    assert_eq!(200, code, "{:?}", &response);
+    assert!(response["numberOfDocuments"].as_u64().unwrap() <= 2);
+    for uid in ["users", "orders"] {
+        let (_, code) = server.index(uid).get().await;
+        assert_eq!(404, code);
+    }
+}
+
+#[actix_rt::test]
+async fn error_add_api_key_invalid_parameters_quota() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["products"],
+        "actions": ["documents.add"],
+        "expiresAt": null,
+        "maxIndexes": 0,
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(400, code, "{:?}", &response);
+
+    let expected_response = json!({
+        "message": "`maxIndexes` field value `0` is invalid. It should be a positive integer or specified as a null value.",
+        "code": "invalid_api_key_quota",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_api_key_quota"
+    });
+
+    assert_eq!(response, expected_response);
+}
@@ -11,22 +11,61 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
         hashmap! {
             ("POST",    "/indexes/products/search") =>                         hashset!{"search", "*"},
             ("GET",     "/indexes/products/search") =>                         hashset!{"search", "*"},
+            ("POST",    "/indexes/products/facet-search") =>                   hashset!{"search", "*"},
+            ("POST",    "/indexes/products/suggest") =>                        hashset!{"search", "*"},
+            ("POST",    "/multi-search") =>                                    hashset!{"search", "*"},
+            ("POST",    "/multi-search/federated") =>                          hashset!{"search", "*"},
             ("POST",    "/indexes/products/documents") =>                      hashset!{"documents.add", "*"},
             ("GET",     "/indexes/products/documents") =>                      hashset!{"documents.get", "*"},
             ("GET",     "/indexes/products/documents/0") =>                    hashset!{"documents.get", "*"},
+            ("POST",    "/indexes/products/documents/fetch") =>                hashset!{"documents.get", "*"},
+            ("GET",     "/indexes/products/documents/0/similar") =>            hashset!{"search", "*"},
             ("DELETE",  "/indexes/products/documents/0") =>                    hashset!{"documents.delete", "*"},
+            ("POST",    "/indexes/products/documents/delete") =>               hashset!{"documents.delete", "*"},
+            ("GET",     "/indexes/products/documents/export") =>               hashset!{"documents.get", "*"},
+            ("GET",     "/indexes/products/documents/stats") =>                hashset!{"stats.get", "*"},
+            ("POST",    "/indexes/products/documents/uploads") =>              hashset!{"documents.add", "*"},
+            ("GET",     "/indexes/products/documents/uploads/0") =>            hashset!{"documents.add", "*"},
+            ("PUT",     "/indexes/products/documents/uploads/0") =>            hashset!{"documents.add", "*"},
+            ("DELETE",  "/indexes/products/documents/uploads/0") =>            hashset!{"documents.add", "*"},
+            ("POST",    "/indexes/products/documents/uploads/0/commit") =>     hashset!{"documents.add", "*"},
+            ("PUT",     "/indexes/products/documents/uploads/0/commit") =>     hashset!{"documents.add", "*"},
             ("GET",     "/tasks") =>                                           hashset!{"tasks.get", "*"},
             ("GET",     "/tasks?indexUid=products") =>                         hashset!{"tasks.get", "*"},
             ("GET",     "/tasks/0") =>                                         hashset!{"tasks.get", "*"},
+            ("GET",     "/tasks/errors/summary") =>                            hashset!{"tasks.get", "*"},
+            ("POST",    "/tasks/batch") =>                                     hashset!{"*"},
+            ("POST",    "/tasks/barrier") =>                                   hashset!{"indexes.update", "*"},
+            ("POST",    "/swap-indexes") =>                                    hashset!{"indexes.update", "*"},
+            ("GET",     "/aliases") =>                                         hashset!{"indexes.get", "*"},
+            ("GET",     "/aliases/products") =>                                hashset!{"indexes.get", "*"},
+            ("PUT",     "/aliases/products") =>                                hashset!{"indexes.update", "*"},
+            ("DELETE",  "/aliases/products") =>                                hashset!{"indexes.update", "*"},
+            ("GET",     "/index-templates") =>                                 hashset!{"settings.get", "*"},
+            ("GET",     "/index-templates/products") =>                        hashset!{"settings.get", "*"},
+            ("PUT",     "/index-templates/products") =>                        hashset!{"settings.update", "*"},
+            ("DELETE",  "/index-templates/products") =>                        hashset!{"settings.update", "*"},
             ("PATCH",   "/indexes/products/") =>                               hashset!{"indexes.update", "*"},
+            ("POST",    "/indexes/products/clone") =>                          hashset!{"indexes.create", "*"},
+            ("POST",    "/indexes/products/compact") =>                        hashset!{"indexes.update", "*"},
+            ("POST",    "/indexes/products/restore") =>                        hashset!{"indexes.create", "*"},
+            ("POST",    "/indexes/products/check") =>                          hashset!{"indexes.update", "*"},
+            ("POST",    "/indexes/products/warmup") =>                         hashset!{"indexes.update", "*"},
             ("GET",     "/indexes/products/") =>                               hashset!{"indexes.get", "*"},
             ("DELETE",  "/indexes/products/") =>                               hashset!{"indexes.delete", "*"},
             ("POST",    "/indexes") =>                                         hashset!{"indexes.create", "*"},
             ("GET",     "/indexes") =>                                         hashset!{"indexes.get", "*"},
             ("GET",     "/indexes/products/settings") =>                       hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/attachments") =>           hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/displayed-attributes") =>  hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/distinct-attribute") =>    hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/document-schema") =>       hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/expires-at-attribute") =>  hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/filterable-attributes") => hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/ingestion-pipeline") =>    hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/nested-flattening") =>     hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/pagination") =>            hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/ranking-experiment") =>    hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/ranking-rules") =>         hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/searchable-attributes") => hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.get", "*"},
@@ -34,18 +73,38 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("GET",     "/indexes/products/settings/synonyms") =>              hashset!{"settings.get", "*"},
             ("DELETE",  "/indexes/products/settings") =>                       hashset!{"settings.update", "*"},
             ("PATCH",   "/indexes/products/settings") =>                       hashset!{"settings.update", "*"},
+            ("PUT",     "/indexes/products/settings") =>                       hashset!{"settings.update", "*"},
+            ("PATCH",   "/indexes/products/settings/pagination") =>            hashset!{"settings.update", "*"},
             ("PATCH",   "/indexes/products/settings/typo-tolerance") =>        hashset!{"settings.update", "*"},
+            ("POST",    "/indexes/products/settings/synonyms/import") =>       hashset!{"settings.update", "*"},
+            ("GET",     "/indexes/products/settings/history") =>               hashset!{"settings.get", "*"},
+            ("POST",    "/indexes/products/settings/rollback?version=1") =>    hashset!{"settings.update", "*"},
+            ("POST",    "/indexes/products/settings/diff") =>                  hashset!{"settings.get", "*"},
+            ("PUT",     "/indexes/products/settings/attachments") =>           hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/displayed-attributes") =>  hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/distinct-attribute") =>    hashset!{"settings.update", "*"},
+            ("PUT",     "/indexes/products/settings/document-schema") =>       hashset!{"settings.update", "*"},
+            ("PUT",     "/indexes/products/settings/expires-at-attribute") =>  hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/filterable-attributes") => hashset!{"settings.update", "*"},
+            ("PUT",     "/indexes/products/settings/ingestion-pipeline") =>    hashset!{"settings.update", "*"},
+            ("PUT",     "/indexes/products/settings/nested-flattening") =>     hashset!{"settings.update", "*"},
+            ("PUT",     "/indexes/products/settings/ranking-experiment") =>    hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/ranking-rules") =>         hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/searchable-attributes") => hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/stop-words") =>            hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/synonyms") =>              hashset!{"settings.update", "*"},
+            ("GET",     "/indexes/products/rules") =>                          hashset!{"settings.get", "*"},
+            ("PUT",     "/indexes/products/rules") =>                          hashset!{"settings.update", "*"},
+            ("DELETE",  "/indexes/products/rules") =>                          hashset!{"settings.update", "*"},
             ("GET",     "/indexes/products/stats") =>                          hashset!{"stats.get", "*"},
             ("GET",     "/stats") =>                                           hashset!{"stats.get", "*"},
             ("POST",    "/dumps") =>                                           hashset!{"dumps.create", "*"},
+            ("GET",     "/drain") =>                                           hashset!{"*"},
+            ("POST",    "/drain") =>                                           hashset!{"*"},
+            ("GET",     "/analytics/searches") =>                              hashset!{"*"},
+            ("GET",     "/analytics/top-searches") =>                          hashset!{"*"},
+            ("GET",     "/analytics/no-results") =>                            hashset!{"*"},
             ("GET",     "/version") =>                                         hashset!{"version", "*"},
             ("PATCH",   "/keys/mykey/") =>                                     hashset!{"keys.update", "*"},
             ("GET",     "/keys/mykey/") =>                                     hashset!{"keys.get", "*"},
@@ -180,6 +239,34 @@ async fn error_access_unauthorized_action() {
     }
 }
 
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn error_access_master_key_only_route() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let content = json!({
+        "indexes": ["*"],
+        "actions": ["*"],
+        "expiresAt": (OffsetDateTime::now_utc() + Duration::hours(1)).format(&Rfc3339).unwrap(),
+    });
+
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(201, code, "{:?}", &response);
+    let key = response["key"].as_str().unwrap();
+    server.use_api_key(&key);
+
+    // even a key with all the actions can't abort a task.
+    let (response, code) = server.dummy_request("POST", "/tasks/0/abort").await;
+    assert_eq!(response, INVALID_RESPONSE.clone());
+    assert_eq!(403, code, "{:?}", &response);
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server.dummy_request("POST", "/tasks/0/abort").await;
+    assert_ne!(response, INVALID_RESPONSE.clone());
+    assert_ne!(code, 403);
+}
+
 #[actix_rt::test]
 #[cfg_attr(target_os = "windows", ignore)]
 async fn access_authorized_master_key() {
@@ -478,6 +565,56 @@ async fn list_authorized_tasks_restricted_index() {
     assert!(!response.iter().any(|task| task["indexUid"] == "test"));
 }
 
+#[actix_rt::test]
+async fn access_authorized_namespace() {
+    let mut server = Server::new_auth().await;
+    server.use_admin_key("MASTER_KEY").await;
+
+    for (i, uid) in ["tenant-a/products", "tenant-b/products", "products"]
+        .iter()
+        .enumerate()
+    {
+        let (response, code) = server.index(uid).create(None).await;
+        assert_eq!(202, code, "{:?}", &response);
+        server.index(uid).wait_task(i as u64).await;
+    }
+
+    // create key with access on the indexes of the `tenant-a` namespace only.
+    let content = json!({
+        "indexes": ["tenant-a/*"],
+        "actions": ["indexes.get", "tasks.get"],
+        "expiresAt": (OffsetDateTime::now_utc() + Duration::hours(1)).format(&Rfc3339).unwrap(),
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(201, code, "{:?}", &response);
+    assert_eq!(response["indexes"], json!(["tenant-a/*"]));
+
+    // use created key.
+    let key = response["key"].as_str().unwrap();
+    server.use_api_key(&key);
+
+    let (response, code) = server.index("tenant-a/products").get().await;
+    assert_eq!(200, code, "{:?}", &response);
+
+    let (response, code) = server.index("tenant-b/products").get().await;
+    assert_eq!(403, code, "{:?}", &response);
+
+    let (response, code) = server.index("products").get().await;
+    assert_eq!(403, code, "{:?}", &response);
+
+    let (response, code) = server.list_indexes(None, None).await;
+    assert_eq!(200, code, "{:?}", &response);
+    assert_eq!(response["total"], json!(1));
+    assert_eq!(response["results"][0]["uid"], json!("tenant-a/products"));
+
+    let (response, code) = server.service.get("/tasks").await;
+    assert_eq!(200, code, "{:?}", &response);
+    let response = response["results"].as_array().unwrap();
+    assert!(response
+        .iter()
+        .all(|task| task["indexUid"] == "tenant-a/products"));
+}
+
 #[actix_rt::test]
 async fn list_authorized_tasks_no_index_restriction() {
     let mut server = Server::new_auth().await;
@@ -650,3 +787,75 @@ async fn lazy_create_index() {
     assert_eq!(200, code, "{:?}", &response);
     assert_eq!(response["status"], "succeeded");
 }
+
+#[actix_rt::test]
+async fn search_debug_requires_all_actions() {
+    let mut server = Server::new_auth().await;
+    server.use_api_key("MASTER_KEY");
+
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(0).await;
+
+    let content = json!({
+        "indexes": ["test"],
+        "actions": ["search"],
+        "expiresAt": "2050-11-13T00:00:00Z"
+    });
+    let (response, code) = server.add_api_key(content).await;
+    assert_eq!(201, code, "{:?}", &response);
+    let search_key = response["key"].as_str().unwrap().to_string();
+
+    let (response, code) = server
+        .index("test")
+        .search_post(json!({ "debug": true }))
+        .await;
+    assert_eq!(200, code, "{:?}", &response);
+    assert!(response["debug"].is_object());
+
+    server.use_api_key(&search_key);
+    let index = server.index("test");
+    let (response, code) = index.search_post(json!({})).await;
+    assert_eq!(200, code, "{:?}", &response);
+    let (response, code) = index.search_post(json!({ "debug": true })).await;
+    assert_eq!(403, code, "{:?}", &response);
+}
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn access_edit_documents_requires_add_and_delete() {
+    let mut server = Server::new_auth().await;
+
+    // the function of an edition can delete documents
+    for (actions, authorized) in [
+        (vec!["documents.add"], false),
+        (vec!["documents.delete"], false),
+        (vec!["documents.add", "documents.delete"], true),
+        (vec!["*"], true),
+    ] {
+        server.use_api_key("MASTER_KEY");
+        let content = json!({
+            "indexes": ["products"],
+            "actions": &actions,
+            "expiresAt": null,
+        });
+        let (response, code) = server.add_api_key(content).await;
+        assert_eq!(201, code, "{:?}", &response);
+        server.use_api_key(response["key"].as_str().unwrap());
+
+        let (response, code) = server
+            .dummy_request("POST", "/indexes/products/documents/edit")
+            .await;
+        if authorized {
+            assert_ne!(code, 403, "{:?} with actions: {:?}", &response, actions);
+        } else {
+            assert_eq!(
+                response,
+                INVALID_RESPONSE.clone(),
+                "with actions: {:?}",
+                actions
+            );
+            assert_eq!(code, 403);
+        }
+    }
+}
@@ -582,3 +582,182 @@ async fn error_access_modified_token() {
     assert_eq!(response, INVALID_RESPONSE.clone());
     assert_eq!(code, 403);
 }
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn search_restricted_by_acl_claim() {
+    let mut server = Server::new_auth().await;
+    server.use_admin_key("MASTER_KEY").await;
+    let index = server.index("sales");
+    let documents = json!([
+        { "id": 1, "title": "Shazam!", "_acl": ["team:1"] },
+        { "id": 2, "title": "Captain Marvel", "_acl": ["team:1", "team:2"] },
+        { "id": 3, "title": "Escape Room", "_acl": ["team:2"] },
+        { "id": 4, "title": "Glass" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({"filterableAttributes": ["_acl"]}))
+        .await;
+    index.wait_task(1).await;
+    drop(index);
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server
+        .add_api_key(json!({ "indexes": ["*"], "actions": ["search"], "expiresAt": null }))
+        .await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap();
+    let uid = response["uid"].as_str().unwrap();
+
+    let cases: [(Value, Value, Vec<u64>); 4] = [
+        (json!(["team:1"]), Value::Null, vec![1, 2]),
+        (json!(["team:2", "team:3"]), Value::Null, vec![2, 3]),
+        // the filter of the search can only narrow the documents of the tags
+        (json!(["team:1"]), json!("_acl = \"team:2\""), vec![2]),
+        (json!([]), Value::Null, vec![]),
+    ];
+    for (acl, filter, expected) in cases {
+        let tenant_token = hashmap! {
+            "searchRules" => json!(["*"]),
+            "acl" => acl.clone(),
+        };
+        let web_token = generate_tenant_token(&uid, &key, tenant_token);
+        server.use_api_key(&web_token);
+        let (response, code) = server
+            .index("sales")
+            .search_post(json!({ "filter": filter }))
+            .await;
+        assert_eq!(code, 200, "{} using acl: {}", response, acl);
+        let mut ids: Vec<_> = response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, expected, "using acl: {}", acl);
+    }
+
+    // the tags can't contain a double quote
+    let tenant_token = hashmap! {
+        "searchRules" => json!(["*"]),
+        "acl" => json!(["team:1\" OR id EXISTS"]),
+    };
+    let web_token = generate_tenant_token(&uid, &key, tenant_token);
+    server.use_api_key(&web_token);
+    let (response, code) = server.index("sales").search_post(json!({})).await;
+    assert_eq!(response, INVALID_RESPONSE.clone());
+    assert_eq!(code, 403);
+}
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn similar_documents_hides_documents_out_of_the_rules() {
+    let mut server = Server::new_auth().await;
+    server.use_admin_key("MASTER_KEY").await;
+    let index = server.index("sales");
+    let documents = json!([
+        { "id": 1, "title": "red leather shoes", "_acl": ["team:1"] },
+        { "id": 2, "title": "red running shoes", "_acl": ["team:2"] },
+        { "id": 3, "title": "red leather shoes with laces", "_acl": ["team:2"] },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({"filterableAttributes": ["_acl"]}))
+        .await;
+    index.wait_task(1).await;
+    drop(index);
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server
+        .add_api_key(json!({ "indexes": ["*"], "actions": ["search"], "expiresAt": null }))
+        .await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap();
+    let uid = response["uid"].as_str().unwrap();
+
+    let tenant_token = hashmap! {
+        "searchRules" => json!(["*"]),
+        "acl" => json!(["team:2"]),
+    };
+    let web_token = generate_tenant_token(&uid, &key, tenant_token);
+    server.use_api_key(&web_token);
+
+    // the terms of a document the tenant can't see must not leak through its similar documents
+    let (response, code) = server
+        .service
+        .get("/indexes/sales/documents/1/similar")
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "document_not_found");
+
+    // and it isn't among the similar documents of a document the tenant can see
+    let (response, code) = server
+        .service
+        .get("/indexes/sales/documents/2/similar")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![3]);
+}
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn multi_search_applies_the_rules_of_each_index() {
+    let mut server = Server::new_auth().await;
+    server.use_admin_key("MASTER_KEY").await;
+    for (task_id, uid) in ["sales", "products"].into_iter().enumerate() {
+        let index = server.index(uid);
+        index.add_documents(DOCUMENTS.clone(), None).await;
+        index.wait_task(task_id as u64 * 2).await;
+        index
+            .update_settings(json!({"filterableAttributes": ["color"]}))
+            .await;
+        index.wait_task(task_id as u64 * 2 + 1).await;
+    }
+
+    server.use_api_key("MASTER_KEY");
+    let (response, code) = server
+        .add_api_key(json!({ "indexes": ["*"], "actions": ["search"], "expiresAt": null }))
+        .await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap();
+    let uid = response["uid"].as_str().unwrap();
+
+    let tenant_token = hashmap! {
+        "searchRules" => json!({
+            "sales": { "filter": "color = blue" },
+            "products": { "filter": "color = red" },
+        }),
+    };
+    let web_token = generate_tenant_token(&uid, &key, tenant_token);
+    server.use_api_key(&web_token);
+
+    let (response, code) = server
+        .service
+        .post(
+            "/multi-search",
+            json!([
+                { "indexUid": "sales" },
+                { "indexUid": "products" },
+                { "indexUid": "products", "filter": "color = green" },
+            ]),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let counts: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| result["hits"].as_array().unwrap().len())
+        .collect();
+    assert_eq!(counts, [3, 3, 1]);
+}
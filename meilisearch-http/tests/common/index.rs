@@ -175,6 +175,16 @@ impl Index<'_> {
             .await
     }
 
+    pub async fn delete_by_filter(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/delete", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
+    pub async fn edit_documents(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/edit", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
     pub async fn settings(&self) -> (Value, StatusCode) {
         let url = format!("/indexes/{}/settings", encode(self.uid.as_ref()));
         self.service.get(url).await
@@ -1,8 +1,14 @@
-use crate::common::{GetAllDocumentsOptions, Server};
+use std::io::Write;
+use std::time::Duration;
+
+use crate::common::{default_settings, GetAllDocumentsOptions, Server};
 use actix_web::test;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use meilisearch_http::{analytics, create_app};
 use serde_json::{json, Value};
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tokio::time::sleep;
 
 /// This is the basic usage of our API and every other tests uses the content-type application/json
 #[actix_rt::test]
@@ -51,6 +57,164 @@ async fn add_documents_test_json_content_types() {
     assert_eq!(response["taskUid"], 1);
 }
 
+#[actix_rt::test]
+async fn add_compressed_documents() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(b"{ \"id\": 1, \"content\": \"Bouvier Bernois\" }\n{ \"id\": 2, \"content\": \"Leonberg\" }\n")
+        .unwrap();
+    let payload = encoder.finish().unwrap();
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/dog/documents")
+        .set_payload(payload)
+        .insert_header(("content-type", "application/x-ndjson"))
+        .insert_header(("content-encoding", "gzip"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    let index = server.index("dog");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+}
+
+#[actix_rt::test]
+async fn add_csv_documents_with_delimiter_and_types() {
+    let document = "id;title;price:number;tags:string[];available:boolean\n1;Alice;9.5;kids,fantasy;true\n2;Dune;12;;false\n";
+
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/books/documents?csvDelimiter=%3B")
+        .set_payload(document.to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    let index = server.index("books");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": "1", "title": "Alice", "price": 9.5, "tags": ["kids", "fantasy"], "available": true })
+    );
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": "2", "title": "Dune", "price": 12, "tags": [], "available": false })
+    );
+
+    // the delimiter must be a single ASCII character
+    let req = test::TestRequest::post()
+        .uri("/indexes/books/documents?csvDelimiter=%C3%A9")
+        .set_payload(document.to_string())
+        .insert_header(("content-type", "text/csv"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 400);
+    let response: Value = serde_json::from_slice(&test::read_body(res).await).unwrap();
+    assert_eq!(response["code"], "invalid_document_csv_delimiter");
+
+    // and it's only accepted along with a csv payload
+    let req = test::TestRequest::post()
+        .uri("/indexes/books/documents?csvDelimiter=%3B")
+        .set_payload(r#"[{ "id": 3 }]"#)
+        .insert_header(("content-type", "application/json"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 400);
+    let response: Value = serde_json::from_slice(&test::read_body(res).await).unwrap();
+    assert_eq!(response["code"], "invalid_document_csv_delimiter");
+}
+
+#[actix_rt::test]
+async fn add_xml_documents_with_record_path() {
+    let document = r#"<?xml version="1.0"?>
+        <feed>
+            <products>
+                <product id="1"><title>Alice</title><tag>kids</tag><tag>fantasy</tag></product>
+                <product id="2"><title>Dune &amp; co</title></product>
+            </products>
+        </feed>"#;
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let req = test::TestRequest::post()
+        .uri("/indexes/books/documents?recordPath=/feed/products/product")
+        .set_payload(document)
+        .insert_header(("content-type", "application/xml"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    let index = server.index("books");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": "1", "title": "Alice", "tag": ["kids", "fantasy"] })
+    );
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!({ "id": "2", "title": "Dune & co" }));
+
+    // the record path is required along with an xml payload
+    let req = test::TestRequest::post()
+        .uri("/indexes/books/documents")
+        .set_payload(document)
+        .insert_header(("content-type", "application/xml"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 400);
+    let response: Value = serde_json::from_slice(&test::read_body(res).await).unwrap();
+    assert_eq!(response["code"], "invalid_document_xml_record_path");
+
+    // and it's only accepted along with an xml payload
+    let req = test::TestRequest::post()
+        .uri("/indexes/books/documents?recordPath=/feed/products/product")
+        .set_payload(r#"[{ "id": 3 }]"#)
+        .insert_header(("content-type", "application/json"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 400);
+    let response: Value = serde_json::from_slice(&test::read_body(res).await).unwrap();
+    assert_eq!(response["code"], "invalid_document_xml_record_path");
+}
+
 /// any other content-type is must be refused
 #[actix_rt::test]
 async fn error_add_documents_test_bad_content_types() {
@@ -84,7 +248,7 @@ async fn error_add_documents_test_bad_content_types() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `application/vnd.apache.parquet`, `application/vnd.apache.arrow.file`, `application/vnd.apache.arrow.stream`, `application/xml`"#
         )
     );
     assert_eq!(response["code"], "invalid_content_type");
@@ -108,7 +272,7 @@ async fn error_add_documents_test_bad_content_types() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"The Content-Type `text/plain` is invalid. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `application/vnd.apache.parquet`, `application/vnd.apache.arrow.file`, `application/vnd.apache.arrow.stream`, `application/xml`"#
         )
     );
     assert_eq!(response["code"], "invalid_content_type");
@@ -151,7 +315,7 @@ async fn error_add_documents_test_no_content_type() {
     assert_eq!(
         response["message"],
         json!(
-            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `application/vnd.apache.parquet`, `application/vnd.apache.arrow.file`, `application/vnd.apache.arrow.stream`, `application/xml`"#
         )
     );
     assert_eq!(response["code"], "missing_content_type");
@@ -174,7 +338,7 @@ async fn error_add_documents_test_no_content_type() {
     assert_eq!(
         response["message"],
         json!(
-            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`"#
+            r#"A Content-Type header is missing. Accepted values for the Content-Type header are: `application/json`, `application/x-ndjson`, `text/csv`, `application/vnd.apache.parquet`, `application/vnd.apache.arrow.file`, `application/vnd.apache.arrow.stream`, `application/xml`"#
         )
     );
     assert_eq!(response["code"], "missing_content_type");
@@ -388,7 +552,7 @@ async fn error_add_malformed_ndjson_documents() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 1 column 2`."#
+            r#"The `ndjson` payload provided is malformed at line 2. `Couldn't serialize document value: key must be a string at column 2`."#
         )
     );
     assert_eq!(response["code"], json!("malformed_payload"));
@@ -412,7 +576,7 @@ async fn error_add_malformed_ndjson_documents() {
     assert_eq!(
         response["message"],
         json!(
-            r#"The `ndjson` payload provided is malformed. `Couldn't serialize document value: key must be a string at line 1 column 2`."#
+            r#"The `ndjson` payload provided is malformed at line 2. `Couldn't serialize document value: key must be a string at column 2`."#
         )
     );
     assert_eq!(response["code"], json!("malformed_payload"));
@@ -638,7 +802,7 @@ async fn error_document_add_create_index_bad_uid() {
     let (response, code) = index.add_documents(json!([{"id": 1}]), None).await;
 
     let expected_response = json!({
-        "message": "invalid index uid `883  fj!`, the uid must be an integer or a string containing only alphanumeric characters a-z A-Z 0-9, hyphens - and underscores _.",
+        "message": "invalid index uid `883  fj!`, the uid must be an integer or a string containing only alphanumeric characters a-z A-Z 0-9, hyphens - and underscores _, optionally prefixed by a namespace and a slash /",
         "code": "invalid_index_uid",
         "type": "invalid_request",
         "link": "https://docs.meilisearch.com/errors#invalid_index_uid"
@@ -655,7 +819,7 @@ async fn error_document_update_create_index_bad_uid() {
     let (response, code) = index.update_documents(json!([{"id": 1}]), None).await;
 
     let expected_response = json!({
-        "message": "invalid index uid `883  fj!`, the uid must be an integer or a string containing only alphanumeric characters a-z A-Z 0-9, hyphens - and underscores _.",
+        "message": "invalid index uid `883  fj!`, the uid must be an integer or a string containing only alphanumeric characters a-z A-Z 0-9, hyphens - and underscores _, optionally prefixed by a namespace and a slash /",
         "code": "invalid_index_uid",
         "type": "invalid_request",
         "link": "https://docs.meilisearch.com/errors#invalid_index_uid"
@@ -867,18 +1031,15 @@ async fn error_add_documents_bad_document_id() {
     index.wait_task(1).await;
     let (response, code) = index.get_task(1).await;
     assert_eq!(code, 200);
-    assert_eq!(response["status"], json!("failed"));
-    assert_eq!(
-        response["error"]["message"],
-        json!(
-            r#"Document identifier `"foo & bar"` is invalid. A document identifier can be of type integer or string, only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and underscores (_)."#
-        )
-    );
-    assert_eq!(response["error"]["code"], json!("invalid_document_id"));
-    assert_eq!(response["error"]["type"], json!("invalid_request"));
+    assert_eq!(response["status"], json!("succeeded"));
+    assert_eq!(response["details"]["indexedDocuments"], 0);
     assert_eq!(
-        response["error"]["link"],
-        json!("https://docs.meilisearch.com/errors#invalid_document_id")
+        response["details"]["failedDocuments"],
+        json!([{
+            "index": 0,
+            "code": "invalid_document_id",
+            "message": r#"Document identifier `"foo & bar"` is invalid. A document identifier can be of type integer or string, only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and underscores (_)."#
+        }])
     );
 }
 
@@ -895,18 +1056,11 @@ async fn error_update_documents_bad_document_id() {
     ]);
     index.update_documents(documents, None).await;
     let response = index.wait_task(1).await;
-    assert_eq!(response["status"], json!("failed"));
-    assert_eq!(
-        response["error"]["message"],
-        json!(
-            r#"Document identifier `"foo & bar"` is invalid. A document identifier can be of type integer or string, only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and underscores (_)."#
-        )
-    );
-    assert_eq!(response["error"]["code"], json!("invalid_document_id"));
-    assert_eq!(response["error"]["type"], json!("invalid_request"));
+    assert_eq!(response["status"], json!("succeeded"));
+    assert_eq!(response["details"]["indexedDocuments"], 0);
     assert_eq!(
-        response["error"]["link"],
-        json!("https://docs.meilisearch.com/errors#invalid_document_id")
+        response["details"]["failedDocuments"][0]["code"],
+        json!("invalid_document_id")
     );
 }
 
@@ -925,17 +1079,341 @@ async fn error_add_documents_missing_document_id() {
     index.wait_task(1).await;
     let (response, code) = index.get_task(1).await;
     assert_eq!(code, 200);
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["indexedDocuments"], 0);
+    assert_eq!(
+        response["details"]["failedDocuments"],
+        json!([{
+            "index": 0,
+            "code": "missing_document_id",
+            "message": r#"Document doesn't have a `docid` attribute: `{"id":"11","content":"foobar"}`."#
+        }])
+    );
+}
+
+#[actix_rt::test]
+async fn add_documents_auto_generate_primary_key() {
+    let server = Server::new().await;
+    let index = server.index("logs");
+    let documents = json!([
+        { "level": "info", "message": "started" },
+        { "id": "crash-1", "level": "error", "message": "crashed" },
+    ]);
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/logs/documents?autoGeneratePrimaryKey=true",
+            documents,
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+    assert!(response["details"]["failedDocuments"].is_null());
+
+    let (response, code) = index.get().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["primaryKey"], "id");
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    let ids: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|document| document["id"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&"crash-1".to_string()));
+    assert!(ids
+        .iter()
+        .any(|id| id != "crash-1" && uuid::Uuid::parse_str(id).is_ok()));
+}
+
+#[actix_rt::test]
+async fn update_documents_merge_nested() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    index
+        .add_documents(
+            json!([{ "id": 1, "attrs": { "color": "blue", "size": 42 }, "tags": ["shoe"] }]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .put(
+            "/indexes/products/documents?mergeNested=true",
+            json!([{ "id": 1, "attrs": { "color": "red" }, "tags": ["sale"] }]),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": 1, "attrs": { "color": "red", "size": 42 }, "tags": ["sale"] })
+    );
+
+    // the nested objects are only merged when updating documents
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/products/documents?mergeNested=true",
+            json!([{ "id": 1, "attrs": { "color": "green" } }]),
+        )
+        .await;
+    assert_eq!(code, 400);
+    assert_eq!(response["code"], "invalid_document_merge_nested");
+}
+
+#[actix_rt::test]
+async fn update_documents_with_operators() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "stock": 10, "price": 2.5 },
+                { "id": 2, "stock": "many" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    index
+        .update_documents(
+            json!([
+                { "id": 1, "stock": { "_inc": -1 }, "price": { "_dec": 0.5 } },
+                { "id": 1, "stock": { "_inc": -1 } },
+                { "id": 2, "stock": { "_inc": 1 } },
+                { "id": 3, "stock": { "_inc": 5 } },
+            ]),
+            None,
+        )
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(
+        response["details"]["failedDocuments"],
+        json!([{
+            "index": 2,
+            "documentId": "2",
+            "code": "invalid_document_operator",
+            "message": "The `_inc` operator of the `stock` field can't be applied: `\"many\"` and `1` must be numbers."
+        }])
+    );
+
+    let (response, _) = index.get_document(1, None).await;
+    assert_eq!(response, json!({ "id": 1, "stock": 8, "price": 2.0 }));
+    let (response, _) = index.get_document(3, None).await;
+    assert_eq!(response, json!({ "id": 3, "stock": 5 }));
+}
+
+#[actix_rt::test]
+async fn add_documents_with_document_schema() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    index
+        .update_settings(json!({
+            "documentSchema": {
+                "type": "object",
+                "properties": { "price": { "type": "number" } },
+                "required": ["price"]
+            }
+        }))
+        .await;
+    index.wait_task(0).await;
+
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "price": 9.5 },
+                { "id": 2, "price": "cheap" },
+                { "id": 3 },
+            ]),
+            None,
+        )
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 1);
+    let failed = response["details"]["failedDocuments"].as_array().unwrap();
+    assert_eq!(failed.len(), 2);
+    assert_eq!(failed[0]["index"], 1);
+    assert_eq!(failed[0]["documentId"], "2");
+    assert_eq!(failed[0]["code"], "document_schema_violation");
+    assert_eq!(failed[1]["index"], 2);
+    assert_eq!(failed[1]["code"], "document_schema_violation");
+
+    // the schema must be a valid JSON Schema
+    index
+        .update_settings(json!({ "documentSchema": { "type": "price" } }))
+        .await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_document_schema");
+}
+
+#[actix_rt::test]
+async fn add_documents_with_ingestion_pipeline() {
+    let server = Server::new().await;
+    let index = server.index("books");
+    index
+        .update_settings(json!({
+            "ingestionPipeline": [
+                { "rename": { "from": "name", "to": "title" } },
+                { "drop": { "fields": ["internal"] } },
+                { "parseDate": { "field": "published" } },
+                { "split": { "field": "tags", "separator": "," } },
+            ]
+        }))
+        .await;
+    index.wait_task(0).await;
+
+    index
+        .add_documents(
+            json!([{
+                "id": 1,
+                "name": "Hamlet",
+                "internal": "do not index",
+                "published": "2022-06-01T12:00:00Z",
+                "tags": "drama, tragedy",
+            }]),
+            None,
+        )
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({
+            "id": 1,
+            "title": "Hamlet",
+            "published": 1654084800,
+            "tags": ["drama", "tragedy"],
+        })
+    );
+
+    // the separator of a split can't be empty
+    index
+        .update_settings(json!({
+            "ingestionPipeline": [{ "split": { "field": "tags", "separator": "" } }]
+        }))
+        .await;
+    let response = index.wait_task(2).await;
     assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_ingestion_pipeline");
+}
+
+#[actix_rt::test]
+async fn add_documents_with_nested_flattening() {
+    let server = Server::new().await;
+    let index = server.index("books");
+    index
+        .update_settings(json!({
+            "nestedFlattening": { "maxDepth": 2, "arraysOfObjects": "collapse" }
+        }))
+        .await;
+    index.wait_task(0).await;
+
+    index
+        .add_documents(
+            json!([{
+                "id": 1,
+                "author": { "name": "Carol", "address": { "city": "Paris" } },
+                "reviews": [{ "stars": 4 }],
+            }]),
+            None,
+        )
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
     assert_eq!(
-        response["error"]["message"],
-        json!(r#"Document doesn't have a `docid` attribute: `{"id":"11","content":"foobar"}`."#)
+        response,
+        json!({
+            "id": 1,
+            "author": { "name": "Carol", "address": "{\"city\":\"Paris\"}" },
+            "reviews": ["{\"stars\":4}"],
+        })
     );
-    assert_eq!(response["error"]["code"], json!("missing_document_id"));
-    assert_eq!(response["error"]["type"], json!("invalid_request"));
+
+    // the collapsed objects are searchable as a single value
+    let (response, code) = index
+        .search_post(json!({ "q": "paris", "attributesToRetrieve": ["id"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": 1 }]));
+
+    index
+        .update_settings(json!({ "nestedFlattening": { "maxDepth": 0 } }))
+        .await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_nested_flattening");
+}
+
+#[actix_rt::test]
+async fn add_documents_with_attachments() {
+    let server = Server::new().await;
+    let index = server.index("books");
+    index
+        .update_settings(json!({
+            "attachments": {
+                "fields": { "file": "fileContent" },
+                "allowedUrlPrefixes": ["https://books.example.com/"],
+            }
+        }))
+        .await;
+    index.wait_task(0).await;
+
+    // `<h1>Hamlet</h1><p>To be, or not to be</p>`
+    let file = "data:text/html;base64,PGgxPkhhbWxldDwvaDE+PHA+VG8gYmUsIG9yIG5vdCB0byBiZTwvcD4=";
+    let (response, code) = index
+        .add_documents(json!([{ "id": 1, "file": file }]), None)
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    // the embedded attachment is replaced by its text
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
     assert_eq!(
-        response["error"]["link"],
-        json!("https://docs.meilisearch.com/errors#missing_document_id")
+        response,
+        json!({ "id": 1, "fileContent": "Hamlet To be, or not to be" })
     );
+
+    let (response, code) = index
+        .add_documents(
+            json!([{ "id": 2, "file": "https://evil.example.com/hamlet.pdf" }]),
+            None,
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "attachment_extraction_failed");
+
+    index
+        .update_settings(json!({ "attachments": { "fields": { "file": "file" } } }))
+        .await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_attachments");
 }
 
 #[actix_rt::test]
@@ -951,16 +1429,11 @@ async fn error_update_documents_missing_document_id() {
     ]);
     index.update_documents(documents, None).await;
     let response = index.wait_task(1).await;
-    assert_eq!(response["status"], "failed");
-    assert_eq!(
-        response["error"]["message"],
-        r#"Document doesn't have a `docid` attribute: `{"id":"11","content":"foobar"}`."#
-    );
-    assert_eq!(response["error"]["code"], "missing_document_id");
-    assert_eq!(response["error"]["type"], "invalid_request");
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["indexedDocuments"], 0);
     assert_eq!(
-        response["error"]["link"],
-        "https://docs.meilisearch.com/errors#missing_document_id"
+        response["details"]["failedDocuments"][0]["code"],
+        "missing_document_id"
     );
 }
 
@@ -1000,6 +1473,27 @@ async fn error_document_field_limit_reached() {
     assert_eq!(response["error"], expected_error);
 }
 
+#[actix_rt::test]
+async fn add_documents_invalid_acl_field() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "_acl": ["team:1", "team:2"] },
+        { "id": 2, "_acl": "team:1" },
+        { "id": 3, "_acl": [1] },
+    ]);
+    index.add_documents(documents, None).await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 1);
+    let failed_documents = &response["details"]["failedDocuments"];
+    assert_eq!(failed_documents[0]["documentId"], "2");
+    assert_eq!(failed_documents[0]["code"], "invalid_document_acl");
+    assert_eq!(failed_documents[1]["documentId"], "3");
+    assert_eq!(failed_documents[1]["code"], "invalid_document_acl");
+}
+
 #[actix_rt::test]
 async fn add_documents_invalid_geo_field() {
     let server = Server::new().await;
@@ -1013,6 +1507,13 @@ async fn add_documents_invalid_geo_field() {
         {
             "id": "11",
             "_geo": "foobar"
+        },
+        {
+            "id": "12",
+            "_geo": { "lat": 50.6, "lng": 3.06 }
+        },
+        {
+            "title": "no id"
         }
     ]);
 
@@ -1020,7 +1521,22 @@ async fn add_documents_invalid_geo_field() {
     index.wait_task(2).await;
     let (response, code) = index.get_task(2).await;
     assert_eq!(code, 200);
+    // The valid documents are indexed, the other ones are reported.
     assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["receivedDocuments"], 3);
+    assert_eq!(response["details"]["indexedDocuments"], 1);
+    let failed_documents = &response["details"]["failedDocuments"];
+    assert_eq!(failed_documents[0]["index"], 0);
+    assert_eq!(failed_documents[0]["documentId"], "11");
+    assert_eq!(failed_documents[0]["code"], "invalid_geo_field");
+    assert_eq!(failed_documents[1]["index"], 2);
+    assert_eq!(failed_documents[1].get("documentId"), None);
+    assert_eq!(failed_documents[1]["code"], "missing_document_id");
+
+    let (_, code) = index.get_document(12, None).await;
+    assert_eq!(code, 200);
+    let (_, code) = index.get_document(11, None).await;
+    assert_eq!(code, 404);
 }
 
 #[actix_rt::test]
@@ -1099,3 +1615,286 @@ async fn add_documents_with_primary_key_twice() {
     let (response, _code) = index.get_task(1).await;
     assert_eq!(response["status"], "succeeded");
 }
+
+#[actix_rt::test]
+async fn retain_update_files() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        update_file_retention_sec: 2,
+        ..default_settings(temp.path())
+    };
+    let update_files_path = options.db_path.join("updates/updates_files");
+    let update_files_count = || std::fs::read_dir(&update_files_path).unwrap().count();
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(update_files_count(), 1);
+
+    // Leaves the time for the retention to be over and for the update file to be deleted.
+    sleep(Duration::from_secs(5)).await;
+    assert_eq!(update_files_count(), 0);
+}
+
+#[actix_rt::test]
+async fn add_documents_with_resumable_upload() {
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    let (response, code) = server
+        .service
+        .post("/indexes/books/documents/uploads", json!({}))
+        .await;
+    assert_eq!(code, 201, "{}", response);
+    assert_eq!(response["indexUid"], "books");
+    assert_eq!(response["size"], 0);
+    let upload_id = response["uploadId"].as_str().unwrap().to_string();
+    let url = format!("/indexes/books/documents/uploads/{}", upload_id);
+
+    let chunks = [
+        r#"{"id": 1, "title": "Hamlet"}"#,
+        "\n",
+        r#"{"id": 2, "title": "Macbeth"}"#,
+    ];
+    let mut offset = 0;
+    for chunk in chunks {
+        let req = test::TestRequest::put()
+            .uri(&format!("{}?offset={}", url, offset))
+            .set_payload(chunk)
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), 200);
+        offset += chunk.len();
+    }
+
+    // a chunk sent again, after the response to the first attempt was lost, is rejected
+    let req = test::TestRequest::put()
+        .uri(&format!("{}?offset=0", url))
+        .set_payload(chunks[0])
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 409);
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(response["code"], "invalid_upload_offset");
+
+    // the size of the upload is where the next chunk starts
+    let (response, code) = server.service.get(&url).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["size"], offset);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("{}/commit", url))
+        .insert_header(("content-type", "application/x-ndjson"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 202);
+
+    let index = server.index("books");
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 2);
+
+    // the upload is deleted once committed
+    let (response, code) = server.service.get(&url).await;
+    assert_eq!(code, 404);
+    assert_eq!(response["code"], "upload_not_found");
+
+    let (response, code) = server
+        .service
+        .get("/indexes/books/documents/uploads/not-an-upload")
+        .await;
+    assert_eq!(code, 404);
+    assert_eq!(response["code"], "upload_not_found");
+}
+
+#[actix_rt::test]
+async fn add_documents_with_idempotency_key() {
+    let server = Server::new().await;
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    let add_documents = |uri: &'static str, key: &'static str| {
+        test::TestRequest::post()
+            .uri(uri)
+            .set_json(&json!([{ "id": 1, "title": "Hamlet" }]))
+            .insert_header(("Idempotency-Key", key))
+            .to_request()
+    };
+
+    let res = test::call_service(&app, add_documents("/indexes/books/documents", "k1")).await;
+    assert_eq!(res.status(), 202);
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(response["taskUid"], 0);
+
+    // the retried request returns the task of the first one
+    let res = test::call_service(&app, add_documents("/indexes/books/documents", "k1")).await;
+    assert_eq!(res.status(), 202);
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(response["taskUid"], 0);
+
+    // the keys are scoped to an index
+    let res = test::call_service(&app, add_documents("/indexes/movies/documents", "k1")).await;
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(response["taskUid"], 1);
+
+    let res = test::call_service(&app, add_documents("/indexes/books/documents", "k2")).await;
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(response["taskUid"], 2);
+
+    let res = test::call_service(&app, add_documents("/indexes/books/documents", "")).await;
+    assert_eq!(res.status(), 400);
+    let body = test::read_body(res).await;
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(response["code"], "invalid_idempotency_key");
+
+    let (response, code) = server.tasks().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["results"].as_array().unwrap().len(), 3);
+}
+
+#[actix_rt::test]
+async fn add_documents_on_duplicate() {
+    let server = Server::new().await;
+    let index = server.index("events");
+    index
+        .add_documents(json!([{ "id": 1, "title": "curated" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let documents = json!([
+        { "id": 1, "title": "overwritten" },
+        { "id": 2, "title": "new" },
+    ]);
+    let (_, code) = server
+        .service
+        .post(
+            "/indexes/events/documents?onDuplicate=skip",
+            documents.clone(),
+        )
+        .await;
+    assert_eq!(code, 202);
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["indexedDocuments"], 1);
+    assert!(response["details"]["failedDocuments"].is_null());
+
+    let (response, _) = index.get_document(1, None).await;
+    assert_eq!(response, json!({ "id": 1, "title": "curated" }));
+    let (response, _) = index.get_document(2, None).await;
+    assert_eq!(response, json!({ "id": 2, "title": "new" }));
+
+    let (_, code) = server
+        .service
+        .put("/indexes/events/documents?onDuplicate=fail", documents)
+        .await;
+    assert_eq!(code, 202);
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(
+        response["details"]["failedDocuments"],
+        json!([
+            {
+                "index": 0,
+                "documentId": "1",
+                "code": "document_already_exists",
+                "message": "A document with the id `1` already exists."
+            },
+            {
+                "index": 1,
+                "documentId": "2",
+                "code": "document_already_exists",
+                "message": "A document with the id `2` already exists."
+            },
+        ])
+    );
+    let (response, _) = index.get_document(1, None).await;
+    assert_eq!(response, json!({ "id": 1, "title": "curated" }));
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/events/documents?onDuplicate=merge",
+            json!([{ "id": 3 }]),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn add_documents_with_auto_index_creation_disabled() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        disable_auto_index_creation: true,
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let index = server.index("prodcuts");
+    let (response, code) = index.add_documents(json!([{ "id": 1 }]), None).await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_not_found");
+    let (_, code) = index.get().await;
+    assert_eq!(code, 404);
+
+    // the indexes created explicitly still receive documents
+    let index = server.index("products");
+    index.create(None).await;
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+}
+
+#[actix_rt::test]
+async fn error_add_documents_past_max_documents_per_index() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        max_documents_per_index: Some(2),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1 }, { "id": 2 }]), None)
+        .await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    // nothing is indexed when the addition would exceed the limit
+    index.add_documents(json!([{ "id": 3 }]), None).await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "document_quota_exceeded");
+
+    // replacing stored documents doesn't exceed it
+    index
+        .add_documents(json!([{ "id": 2, "title": "kept" }]), None)
+        .await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, _) = index.stats().await;
+    assert_eq!(response["numberOfDocuments"], 2);
+}
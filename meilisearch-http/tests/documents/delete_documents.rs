@@ -1,6 +1,9 @@
+use std::time::Duration;
+
 use serde_json::json;
+use tokio::time::sleep;
 
-use crate::common::{GetAllDocumentsOptions, Server};
+use crate::common::{default_settings, GetAllDocumentsOptions, Server};
 
 #[actix_rt::test]
 async fn delete_one_document_unexisting_index() {
@@ -145,3 +148,131 @@ async fn delete_no_document_batch() {
     assert_eq!(code, 200);
     assert_eq!(response["results"].as_array().unwrap().len(), 3);
 }
+
+#[actix_rt::test]
+async fn delete_documents_by_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["status", "updatedAt"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "status": "archived", "updatedAt": 1640000000 },
+                { "id": 1, "status": "archived", "updatedAt": 1660000000 },
+                { "id": 2, "status": "published", "updatedAt": 1640000000 },
+                { "id": 3, "status": "archived", "updatedAt": 1600000000 },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .delete_by_filter(json!({ "filter": "status = archived AND updatedAt < 1650000000" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["type"], "documentDeletion");
+    assert_eq!(response["details"]["deletedDocuments"], 2);
+    assert_eq!(
+        response["details"]["originalFilter"],
+        "status = archived AND updatedAt < 1650000000"
+    );
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    let ids: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![1, 2]);
+}
+
+#[actix_rt::test]
+async fn delete_documents_by_filter_on_non_filterable_attribute() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 0, "status": "archived" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .delete_by_filter(json!({ "filter": "status = archived" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "invalid_filter");
+    assert_eq!(response["details"]["deletedDocuments"], 0);
+}
+
+#[actix_rt::test]
+async fn delete_documents_by_filter_invalid_filter_type() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, code) = index.delete_by_filter(json!({ "filter": 42 })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_filter");
+}
+
+#[actix_rt::test]
+async fn delete_expired_documents() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        documents_expiration_interval_sec: Some(1),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "expiresAtAttribute": "deadline",
+            "filterableAttributes": ["deadline"],
+        }))
+        .await;
+    index.wait_task(0).await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "deadline": 0 },
+                { "id": 2, "deadline": "2999-01-01T00:00:00Z" },
+                { "id": 3, "deadline": "2001-01-01T00:00:00+02:00" },
+                { "id": 4, "_expiresAt": 0 },
+                { "id": 5 },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    // Leaves the time for the deletion of the expired documents to be enqueued.
+    sleep(Duration::from_secs(3)).await;
+    let response = index.wait_task(2).await;
+    assert_eq!(response["type"], "documentDeletion");
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["deletedDocuments"], 2);
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    let ids: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|document| document["id"].clone())
+        .collect();
+    assert_eq!(ids, [json!(2), json!(4), json!(5)]);
+}
@@ -0,0 +1,98 @@
+use serde_json::json;
+
+use crate::common::{GetAllDocumentsOptions, Server};
+
+#[actix_rt::test]
+async fn edit_documents_by_function() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["brand"] }))
+        .await;
+    let documents = json!([
+        { "id": 1, "brand": "acme", "price": 10 },
+        { "id": 2, "brand": "acme", "price": 20, "discontinued": true },
+        { "id": 3, "brand": "other", "price": 30 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .edit_documents(json!({
+            "function": "if doc.discontinued == true { doc = () } else { doc.price = doc.price * 2 }",
+            "filter": "brand = acme"
+        }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["type"], "documentEdition");
+    assert_eq!(response["details"]["editedDocuments"], 1);
+    assert_eq!(response["details"]["deletedDocuments"], 1);
+    assert_eq!(response["details"]["originalFilter"], "brand = acme");
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response["results"],
+        json!([
+            { "id": 1, "brand": "acme", "price": 20 },
+            { "id": 3, "brand": "other", "price": 30 },
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn edit_documents_invalid_function() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .edit_documents(json!({ "function": "doc.price = " }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_document_edition_function");
+}
+
+#[actix_rt::test]
+async fn edit_documents_primary_key_change() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "a" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .edit_documents(json!({ "function": "doc.id = 2" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "document_edition_failed");
+    assert_eq!(response["details"]["editedDocuments"], 0);
+}
+
+#[actix_rt::test]
+async fn edit_documents_growing_string() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "a" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    // the function fails once the string exceeds the limit, long before exhausting the memory
+    let (response, code) = index
+        .edit_documents(json!({ "function": "loop { doc.title += doc.title; }" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "document_edition_failed");
+}
@@ -1,6 +1,8 @@
 use crate::common::{GetAllDocumentsOptions, GetDocumentOptions, Server};
 
-use serde_json::json;
+use actix_web::test;
+use meilisearch_http::{analytics, create_app};
+use serde_json::{json, Value};
 
 // TODO: partial test since we are testing error, amd error is not yet fully implemented in
 // transplant
@@ -400,3 +402,200 @@ async fn get_documents_displayed_attributes_is_ignored() {
     assert_eq!(response.as_object().unwrap().keys().count(), 16);
     assert!(response.as_object().unwrap().get("gender").is_some());
 }
+
+#[actix_rt::test]
+async fn get_documents_with_filter_and_sort() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["stock"],
+            "sortableAttributes": ["price"]
+        }))
+        .await;
+    let documents = json!([
+        { "id": 1, "stock": 0, "price": 30 },
+        { "id": 2, "stock": 5, "price": 10 },
+        { "id": 3, "stock": 0, "price": 20 },
+        { "id": 4, "stock": 0, "price": 10 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .service
+        .get("/indexes/test/documents?filter=stock%20%3D%200&fields=id&limit=2")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([{ "id": 1 }, { "id": 3 }]));
+    assert_eq!(response["total"], json!(3));
+
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/documents/fetch",
+            json!({ "filter": "stock = 0", "sort": ["price:asc"], "fields": ["id"], "offset": 1 }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([{ "id": 3 }, { "id": 1 }]));
+    assert_eq!(response["total"], json!(3));
+}
+
+#[actix_rt::test]
+async fn get_documents_page_larger_than_a_chunk() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["stock"] }))
+        .await;
+    // the page is sent in several chunks
+    let documents: Vec<_> = (0..1000)
+        .map(|id| json!({ "id": id, "stock": id % 2, "title": "book ".repeat(40) }))
+        .collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .service
+        .get("/indexes/test/documents?limit=1000&offset=10")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 990);
+    assert_eq!(response["results"][0]["id"], json!(10));
+    assert_eq!(response["offset"], json!(10));
+    assert_eq!(response["limit"], json!(1000));
+    assert_eq!(response["total"], json!(1000));
+
+    // the errors are reported before the page is sent
+    let (response, code) = index
+        .service
+        .get("/indexes/test/documents?filter=title%20%3D%20a")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_filter");
+}
+
+#[actix_rt::test]
+async fn fetch_documents_by_ids() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let documents = json!([
+        { "id": 1, "title": "a" },
+        { "id": 2, "title": "b" },
+        { "id": 3, "title": "c" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/documents/fetch",
+            json!({ "ids": [3, "42", "1"], "fields": ["id"] }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "results": [{ "id": 3 }, { "id": 1 }], "missing": ["42"] })
+    );
+
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/documents/fetch",
+            json!({ "ids": [1], "filter": "id = 1" }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn export_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["stock"] }))
+        .await;
+    // more documents than a page of search results
+    let documents: Vec<_> = (0..1500)
+        .map(|id| json!({ "id": id, "stock": id % 2, "title": format!("book {}", id) }))
+        .collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(1).await;
+
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+    let app = &app;
+    let export = move |url: &'static str| {
+        let req = test::TestRequest::get().uri(url).to_request();
+        test::call_service(app, req)
+    };
+
+    let res = export("/indexes/test/documents/export").await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "application/x-ndjson");
+    let body = test::read_body(res).await;
+    let lines: Vec<Value> = body
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_slice(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1500);
+    assert_eq!(lines[0], json!({ "id": 0, "stock": 0, "title": "book 0" }));
+
+    let res = export("/indexes/test/documents/export?filter=stock%20%3D%201&fields=id").await;
+    assert_eq!(res.status(), 200);
+    let body = test::read_body(res).await;
+    let lines: Vec<Value> = body
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_slice(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 750);
+    assert_eq!(lines[0], json!({ "id": 1 }));
+
+    // the errors are reported before the export starts
+    let res = export("/indexes/test/documents/export?filter=title%20%3D%20a").await;
+    assert_eq!(res.status(), 400);
+    let response: Value = serde_json::from_slice(&test::read_body(res).await).unwrap();
+    assert_eq!(response["code"], "invalid_filter");
+}
+
+#[actix_rt::test]
+async fn get_document_stats() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let documents: Vec<_> = (0..100)
+        .map(|id| match id % 2 {
+            0 => json!({ "id": id, "color": ["red", "blue"][id % 4 / 2], "title": format!("book {}", id) }),
+            _ => json!({ "id": id, "title": format!("book {}", id) }),
+        })
+        .collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.get("/indexes/test/documents/stats").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["numberOfDocuments"], 100);
+    let fields = &response["fields"];
+    assert_eq!(fields["color"]["documents"], 50);
+    assert_eq!(fields["color"]["approximateDistinctValues"], 2);
+    for field in ["id", "title"] {
+        assert_eq!(fields[field]["documents"], 100);
+        let distinct = fields[field]["approximateDistinctValues"].as_u64().unwrap();
+        assert!((95..=100).contains(&distinct), "{}", response);
+    }
+
+    let (response, code) = server.service.get("/indexes/other/documents/stats").await;
+    assert_eq!(code, 404);
+    assert_eq!(response["code"], "index_not_found");
+}
@@ -1,3 +1,6 @@
 mod add_documents;
 mod delete_documents;
+#[cfg(feature = "document-edition")]
+mod edit_documents;
 mod get_documents;
+mod similar_documents;
@@ -0,0 +1,68 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn similar_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(
+            json!({ "searchableAttributes": ["title"], "filterableAttributes": ["color"] }),
+        )
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "red leather shoes", "color": "red" },
+                { "id": 2, "title": "red running shoes", "color": "blue" },
+                { "id": 3, "title": "red leather shoes with laces", "color": "red" },
+                { "id": 4, "title": "woolen hat", "color": "red" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.service.get("/indexes/test/documents/1/similar").await;
+    assert_eq!(code, 200, "{}", response);
+    // The document itself and the documents without common terms are left out.
+    let ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![3, 2]);
+    assert_eq!(response["id"], "1");
+    assert_eq!(response["estimatedTotalHits"], 2);
+
+    let (response, code) = index
+        .service
+        .get("/indexes/test/documents/1/similar?filter=color%20%3D%20blue&fields=title")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "title": "red running shoes" }]));
+
+    let (response, code) = index
+        .service
+        .get("/indexes/test/documents/1/similar?limit=1&offset=1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"],
+        json!([{ "id": 2, "title": "red running shoes", "color": "blue" }])
+    );
+}
+
+#[actix_rt::test]
+async fn similar_documents_unexisting_document() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.service.get("/indexes/test/documents/2/similar").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "document_not_found");
+}
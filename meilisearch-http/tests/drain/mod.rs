@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::time::sleep;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn drain_refuses_new_tasks() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.get("/drain").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["status"], "running");
+
+    let (response, code) = server.service.post("/drain", json!(null)).await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["status"], "draining");
+
+    let (response, code) = index.add_documents(json!([{ "id": 1 }]), None).await;
+    assert_eq!(code, 503, "{}", response);
+    assert_eq!(response["code"], "instance_draining");
+
+    let (response, code) = server.service.post("/dumps", json!(null)).await;
+    assert_eq!(code, 503, "{}", response);
+    assert_eq!(response["code"], "instance_draining");
+
+    // The read routes are still available.
+    let (response, code) = index.get().await;
+    assert_eq!(code, 200, "{}", response);
+
+    for _ in 0..100 {
+        let (response, code) = server.service.get("/drain").await;
+        assert_eq!(code, 200, "{}", response);
+        if response["status"] == "drained" {
+            return;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    panic!("Timeout waiting for the instance to be drained");
+}
@@ -0,0 +1,154 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn search_and_retarget_alias() {
+    let server = Server::new().await;
+    let blue = server.index("products_blue");
+    let green = server.index("products_green");
+    blue.add_documents(json!([{ "id": 1, "color": "blue" }]), None)
+        .await;
+    green
+        .add_documents(json!([{ "id": 1, "color": "green" }]), None)
+        .await;
+    blue.wait_task(0).await;
+    green.wait_task(1).await;
+
+    let (response, code) = server
+        .service
+        .put("/aliases/products", json!({ "indexUid": "products_blue" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "alias": "products", "indexUid": "products_blue" })
+    );
+
+    let products = server.index("products");
+    products
+        .search(json!({ "q": "" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([{ "id": 1, "color": "blue" }]));
+        })
+        .await;
+
+    // the documents added to the alias are added to the index it targets
+    let (response, code) = products
+        .add_documents(json!([{ "id": 2, "color": "blue" }]), None)
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["indexUid"], "products_blue");
+    blue.wait_task(2).await;
+    let (response, _) = blue.stats().await;
+    assert_eq!(response["numberOfDocuments"], 2);
+
+    let (response, code) = server
+        .service
+        .put("/aliases/products", json!({ "indexUid": "products_green" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    products
+        .search(json!({ "q": "" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([{ "id": 1, "color": "green" }]));
+        })
+        .await;
+    let (response, code) = products.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["color"], "green");
+
+    let (response, code) = server.service.get("/aliases").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!([{ "alias": "products", "indexUid": "products_green" }])
+    );
+
+    let (_, code) = server.service.delete("/aliases/products").await;
+    assert_eq!(code, 204);
+    let (response, code) = server.service.get("/aliases/products").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "alias_not_found");
+    let (response, code) = products.get_document(1, None).await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn alias_named_after_index() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    index.create(None).await;
+    index.wait_task(0).await;
+    server.index("products_v2").create(None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server
+        .service
+        .put("/aliases/products", json!({ "indexUid": "products_v2" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_alias");
+
+    let (response, code) = server
+        .service
+        .put("/aliases/catalog", json!({ "indexUid": "missing" }))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+
+    // an index can't be created with the name of an alias either
+    server
+        .service
+        .put("/aliases/catalog", json!({ "indexUid": "products" }))
+        .await;
+    let catalog = server.index("catalog");
+    catalog.create(None).await;
+    let response = catalog.wait_task(2).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_already_exists");
+}
+
+#[actix_rt::test]
+async fn update_settings_through_alias_with_search_cache() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        search_cache_size: Some(10),
+        ..crate::common::default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("products_v1");
+    index
+        .add_documents(
+            json!([{ "id": 1, "title": "shoes", "color": "blue" }]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+    server
+        .service
+        .put("/aliases/products", json!({ "indexUid": "products_v1" }))
+        .await;
+
+    let products = server.index("products");
+    let (response, code) = products.search_post(json!({ "q": "blue" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+
+    // the settings update is registered on the index, whose task invalidates the cached searches
+    let (response, code) = products
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["indexUid"], "products_v1");
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = products.search_post(json!({ "q": "blue" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 0);
+    let (response, code) = products.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["searchableAttributes"], json!(["title"]));
+}
@@ -0,0 +1,47 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn check_healthy_index() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    index
+        .update_settings(json!({ "filterableAttributes": ["price", "color"] }))
+        .await;
+    let documents = json!([
+        { "id": 1, "price": 10, "color": "red" },
+        { "id": "two", "price": 20.5, "color": "blue" },
+        { "id": 3 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.delete_document(3).await;
+    index.wait_task(2).await;
+
+    let (response, code) = server
+        .service
+        .post("/indexes/products/check", json!(null))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["taskUid"], 3);
+    assert_eq!(response["type"], "indexIntegrityCheck");
+
+    let response = index.wait_task(3).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["anomalies"], json!([]));
+}
+
+#[actix_rt::test]
+async fn check_unexisting_index() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post("/indexes/products/check", json!(null))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = server.index("products").wait_task(0).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_not_found");
+    assert_eq!(response["details"]["anomalies"], json!(null));
+}
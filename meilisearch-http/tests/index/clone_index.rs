@@ -0,0 +1,89 @@
+use crate::common::{GetAllDocumentsOptions, Server};
+use serde_json::json;
+
+#[actix_rt::test]
+async fn clone_index() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    index
+        .add_documents(json!([{ "id": 1, "name": "kefir" }]), None)
+        .await;
+    index
+        .update_settings(json!({ "searchableAttributes": ["name"] }))
+        .await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/products/clone",
+            json!({ "uid": "products_staging" }),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["taskUid"], 2);
+    assert_eq!(response["indexUid"], "products_staging");
+    assert_eq!(response["type"], "indexClone");
+
+    // the documents added after the clone was enqueued aren't copied
+    index.add_documents(json!([{ "id": 2 }]), None).await;
+
+    let staging = server.index("products_staging");
+    let response = staging.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["sourceUid"], "products");
+
+    let (response, code) = staging
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([{ "id": 1, "name": "kefir" }]));
+    let (response, _) = staging.settings().await;
+    assert_eq!(response["searchableAttributes"], json!(["name"]));
+
+    // the copy is an index of its own
+    staging.add_documents(json!([{ "id": 3 }]), None).await;
+    index.wait_task(3).await;
+    staging.wait_task(4).await;
+    let (response, _) = index.stats().await;
+    assert_eq!(response["numberOfDocuments"], 2);
+    let (response, _) = staging.stats().await;
+    assert_eq!(response["numberOfDocuments"], 2);
+}
+
+#[actix_rt::test]
+async fn clone_index_already_exists() {
+    let server = Server::new().await;
+    server.index("products").create(None).await;
+    server.index("products_staging").create(None).await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/products/clone",
+            json!({ "uid": "products_staging" }),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = server.index("products").wait_task(2).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_already_exists");
+}
+
+#[actix_rt::test]
+async fn clone_unexisting_index() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/products/clone",
+            json!({ "uid": "products_staging" }),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = server.index("products").wait_task(0).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_not_found");
+}
@@ -0,0 +1,55 @@
+use crate::common::Server;
+use serde_json::{json, Value};
+
+#[actix_rt::test]
+async fn compact_index() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    let documents: Vec<Value> = (0..1000)
+        .map(|id| json!({ "id": id, "name": format!("product {}", id) }))
+        .collect();
+    index.add_documents(json!(documents), None).await;
+    index.clear_all_documents().await;
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.wait_task(2).await;
+    let (response, _) = index.stats().await;
+    let size = response["databaseSize"].as_u64().unwrap();
+
+    let (response, code) = server
+        .service
+        .post("/indexes/products/compact", json!(null))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["taskUid"], 3);
+    assert_eq!(response["indexUid"], "products");
+    assert_eq!(response["type"], "indexCompaction");
+
+    let response = index.wait_task(3).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.stats().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["numberOfDocuments"], 1);
+    assert!(response["databaseSize"].as_u64().unwrap() < size);
+
+    // the compacted index is the one receiving the next tasks
+    index.add_documents(json!([{ "id": 2 }]), None).await;
+    index.wait_task(4).await;
+    let (response, _) = index.stats().await;
+    assert_eq!(response["numberOfDocuments"], 2);
+}
+
+#[actix_rt::test]
+async fn compact_unexisting_index() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post("/indexes/products/compact", json!(null))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = server.index("products").wait_task(0).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_not_found");
+}
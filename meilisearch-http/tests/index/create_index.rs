@@ -1,4 +1,4 @@
-use crate::common::Server;
+use crate::common::{default_settings, Server};
 use serde_json::{json, Value};
 
 #[actix_rt::test]
@@ -102,7 +102,7 @@ async fn error_create_with_invalid_index_uid() {
     let (response, code) = index.create(None).await;
 
     let expected_response = json!({
-        "message": "invalid index uid `test test#!`, the uid must be an integer or a string containing only alphanumeric characters a-z A-Z 0-9, hyphens - and underscores _.",
+        "message": "invalid index uid `test test#!`, the uid must be an integer or a string containing only alphanumeric characters a-z A-Z 0-9, hyphens - and underscores _, optionally prefixed by a namespace and a slash /",
         "code": "invalid_index_uid",
         "type": "invalid_request",
         "link": "https://docs.meilisearch.com/errors#invalid_index_uid"
@@ -111,3 +111,24 @@ async fn error_create_with_invalid_index_uid() {
     assert_eq!(response, expected_response);
     assert_eq!(code, 400);
 }
+
+#[actix_rt::test]
+async fn error_create_index_past_max_indexes() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        max_indexes: Some(1),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let (_, code) = server.index("products").create(None).await;
+    assert_eq!(code, 202);
+    let response = server.index("products").wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (_, code) = server.index("users").create(None).await;
+    assert_eq!(code, 202);
+    let response = server.index("users").wait_task(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_quota_exceeded");
+}
@@ -1,4 +1,4 @@
-use crate::common::Server;
+use crate::common::{default_settings, Server};
 use serde_json::json;
 use serde_json::Value;
 
@@ -20,7 +20,8 @@ async fn create_and_get_index() {
     assert!(response.get("updatedAt").is_some());
     assert_eq!(response["createdAt"], response["updatedAt"]);
     assert_eq!(response["primaryKey"], Value::Null);
-    assert_eq!(response.as_object().unwrap().len(), 4);
+    assert_eq!(response["frozen"], false);
+    assert_eq!(response.as_object().unwrap().len(), 5);
 }
 
 #[actix_rt::test]
@@ -183,6 +184,94 @@ async fn get_and_paginate_indexes() {
         .all(|(expected, entry)| entry["uid"] == expected));
 }
 
+#[actix_rt::test]
+async fn list_indexes_with_uid_prefix() {
+    let server = Server::new().await;
+    for (i, uid) in ["tenant1_products", "tenant1_users", "tenant2_products"]
+        .iter()
+        .enumerate()
+    {
+        server.index(uid).create(None).await;
+        server.index(uid).wait_task(i as u64).await;
+    }
+
+    let (response, code) = server
+        .service
+        .get("/indexes?uidPrefix=tenant1_&limit=1&offset=1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["total"], json!(2));
+    assert_eq!(response["limit"], json!(1));
+    assert_eq!(response["offset"], json!(1));
+    let uids: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|index| index["uid"].clone())
+        .collect();
+    assert_eq!(uids, [json!("tenant1_users")]);
+
+    let (response, code) = server.service.get("/indexes?uidPrefix=tenant3_").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["total"], json!(0));
+    assert_eq!(response["results"], json!([]));
+}
+
+#[actix_rt::test]
+async fn list_indexes_of_namespace() {
+    let server = Server::new().await;
+    for (i, uid) in [
+        "tenant-a/products",
+        "tenant-a/users",
+        "tenant-b/products",
+        "products",
+    ]
+    .iter()
+    .enumerate()
+    {
+        server.index(uid).create(None).await;
+        server.index(uid).wait_task(i as u64).await;
+    }
+
+    let (response, code) = server.service.get("/indexes?namespace=tenant-a").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["total"], json!(2));
+    let uids: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|index| index["uid"].clone())
+        .collect();
+    assert_eq!(uids, [json!("tenant-a/products"), json!("tenant-a/users")]);
+
+    let (response, code) = server.index("tenant-a/products").get().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["uid"], json!("tenant-a/products"));
+}
+
+#[actix_rt::test]
+async fn get_indexes_past_max_open_indexes() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        max_open_indexes: Some(2),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+
+    for (i, uid) in ["products", "users", "orders"].iter().enumerate() {
+        let index = server.index(uid);
+        index.add_documents(json!([{ "id": i }]), None).await;
+        index.wait_task(i as u64).await;
+    }
+
+    // the evicted indexes are opened again
+    for uid in ["products", "users", "orders", "products"] {
+        let (response, code) = server.index(uid).stats().await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["numberOfDocuments"], 1);
+    }
+}
+
 #[actix_rt::test]
 async fn get_invalid_index_uid() {
     let server = Server::new().await;
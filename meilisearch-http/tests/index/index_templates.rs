@@ -0,0 +1,100 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn auto_created_index_gets_template_settings() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .put(
+            "/index-templates/logs-*",
+            json!({ "searchableAttributes": ["message"], "filterableAttributes": ["level"] }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["pattern"], "logs-*");
+    assert_eq!(
+        response["settings"]["filterableAttributes"],
+        json!(["level"])
+    );
+
+    // the most specific template prevails
+    server
+        .service
+        .put(
+            "/index-templates/logs-eu-*",
+            json!({ "filterableAttributes": ["region"] }),
+        )
+        .await;
+
+    let index = server.index("logs-2022");
+    index
+        .add_documents(
+            json!([{ "id": 1, "message": "started", "level": "info" }]),
+            None,
+        )
+        .await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    let (response, _) = index.settings().await;
+    assert_eq!(response["searchableAttributes"], json!(["message"]));
+    assert_eq!(response["filterableAttributes"], json!(["level"]));
+
+    let index = server.index("logs-eu-2022");
+    index.create(None).await;
+    index.wait_task(1).await;
+    let (response, _) = index.settings().await;
+    assert_eq!(response["searchableAttributes"], json!(["*"]));
+    assert_eq!(response["filterableAttributes"], json!(["region"]));
+
+    let index = server.index("products");
+    index.create(None).await;
+    index.wait_task(2).await;
+    let (response, _) = index.settings().await;
+    assert_eq!(response["filterableAttributes"], json!([]));
+
+    let (response, code) = server.service.get("/index-templates").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response.as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn delete_template() {
+    let server = Server::new().await;
+    server
+        .service
+        .put(
+            "/index-templates/logs-*",
+            json!({ "filterableAttributes": ["level"] }),
+        )
+        .await;
+
+    let (_, code) = server.service.delete("/index-templates/logs-*").await;
+    assert_eq!(code, 204);
+
+    let (response, code) = server.service.get("/index-templates/logs-*").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_template_not_found");
+
+    let index = server.index("logs-2022");
+    index.create(None).await;
+    index.wait_task(0).await;
+    let (response, _) = index.settings().await;
+    assert_eq!(response["filterableAttributes"], json!([]));
+}
+
+#[actix_rt::test]
+async fn invalid_template_pattern() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .put(
+            "/index-templates/logs.*",
+            json!({ "filterableAttributes": ["level"] }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_index_template");
+}
@@ -1,5 +1,13 @@
+mod aliases;
+mod check_index;
+mod clone_index;
+mod compact_index;
 mod create_index;
 mod delete_index;
 mod get_index;
+mod index_templates;
+mod restore_index;
 mod stats;
+mod swap_indexes;
 mod update_index;
+mod warmup_index;
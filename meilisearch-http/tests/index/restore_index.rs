@@ -0,0 +1,105 @@
+use crate::common::{default_settings, Server};
+use serde_json::json;
+
+#[actix_rt::test]
+async fn restore_deleted_index() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.delete().await;
+    index.wait_task(1).await;
+
+    let (_, code) = index.get().await;
+    assert_eq!(code, 404);
+
+    let (response, code) = server
+        .service
+        .post("/indexes/products/restore", json!(null))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["type"], "indexRestoration");
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.stats().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["numberOfDocuments"], 1);
+}
+
+#[actix_rt::test]
+async fn restore_index_recreated_since_deletion() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    index.create(None).await;
+    index.delete().await;
+    index.create(None).await;
+
+    server
+        .service
+        .post("/indexes/products/restore", json!(null))
+        .await;
+
+    let response = index.wait_task(3).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_already_exists");
+}
+
+#[actix_rt::test]
+async fn restore_unexisting_index() {
+    let server = Server::new().await;
+
+    server
+        .service
+        .post("/indexes/products/restore", json!(null))
+        .await;
+
+    let response = server.index("products").wait_task(0).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn restore_index_without_retention() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        deleted_index_retention_sec: 0,
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("products");
+    index.create(None).await;
+    index.delete().await;
+
+    server
+        .service
+        .post("/indexes/products/restore", json!(null))
+        .await;
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn error_restore_index_past_max_indexes() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        max_indexes: Some(1),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("products");
+    index.create(None).await;
+    index.delete().await;
+    server.index("users").create(None).await;
+
+    server
+        .service
+        .post("/indexes/products/restore", json!(null))
+        .await;
+
+    let response = index.wait_task(3).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_quota_exceeded");
+}
@@ -49,6 +49,45 @@ async fn stats() {
     assert_eq!(response["fieldDistribution"]["age"], 1);
 }
 
+#[actix_rt::test]
+async fn stats_with_nested_fields() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        {
+            "id": 1,
+            "author": { "name": "Carol", "address": { "city": "Paris" } },
+            "reviews": [{ "stars": 4 }, { "stars": 5 }],
+        },
+        {
+            "id": 2,
+            "author": { "name": "Dan" },
+        }
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.stats().await;
+
+    assert_eq!(code, 200);
+    assert!(response["databaseSize"].as_u64().unwrap() > 0);
+    assert_eq!(response["numberOfPendingTasks"], 0);
+    assert!(response["lastUpdate"].is_string());
+    assert_eq!(
+        response["fieldDistribution"],
+        json!({
+            "author": 2,
+            "author.address": 1,
+            "author.address.city": 1,
+            "author.name": 2,
+            "id": 2,
+            "reviews": 1,
+            "reviews.stars": 1,
+        })
+    );
+}
+
 #[actix_rt::test]
 async fn error_get_stats_unexisting_index() {
     let server = Server::new().await;
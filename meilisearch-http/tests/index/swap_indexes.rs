@@ -0,0 +1,79 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn swap_indexes() {
+    let server = Server::new().await;
+    let products = server.index("products");
+    let products_new = server.index("products_new");
+    products
+        .add_documents(json!([{ "id": 1, "name": "old" }]), None)
+        .await;
+    products_new
+        .add_documents(json!([{ "id": 1, "name": "new" }]), None)
+        .await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/swap-indexes",
+            json!([{ "indexes": ["products", "products_new"] }]),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["taskUid"], 2);
+    assert_eq!(response["type"], "indexSwap");
+
+    let response = products.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(
+        response["details"]["swaps"],
+        json!([{ "indexes": ["products", "products_new"] }])
+    );
+
+    let (response, code) = products.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["name"], "new");
+    let (response, code) = products_new.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["name"], "old");
+}
+
+#[actix_rt::test]
+async fn swap_indexes_duplicate_index() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/swap-indexes",
+            json!([{ "indexes": ["a", "b"] }, { "indexes": ["b", "c"] }]),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_swap_indexes");
+}
+
+#[actix_rt::test]
+async fn swap_indexes_unexisting_index() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    index.create(None).await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/swap-indexes",
+            json!([{ "indexes": ["products", "missing"] }]),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_not_found");
+
+    // the failed swap leaves the index as is
+    let (response, code) = index.get().await;
+    assert_eq!(code, 200, "{}", response);
+}
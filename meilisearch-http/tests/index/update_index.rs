@@ -103,3 +103,85 @@ async fn error_update_unexisting_index() {
 
     assert_eq!(response["error"], expected_response);
 }
+
+#[actix_rt::test]
+async fn update_max_size() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+
+    let (_, code) = server
+        .service
+        .patch("/indexes/test", json!({ "maxSize": 2097152 }))
+        .await;
+    assert_eq!(code, 202);
+
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["maxSize"], 2097152);
+
+    let (response, code) = index.stats().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["maxSize"], 2097152);
+
+    // the documents don't fit in the index anymore
+    let documents: Vec<_> = (0..2000)
+        .map(|id| json!({ "id": id, "content": format!("{} {}", "lorem ipsum".repeat(100), id) }))
+        .collect();
+    index.add_documents(json!(documents), None).await;
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "index_full");
+}
+
+#[actix_rt::test]
+async fn freeze_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "Hamlet" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (_, code) = server
+        .service
+        .patch("/indexes/test", json!({ "frozen": true }))
+        .await;
+    assert_eq!(code, 202);
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded");
+    assert_eq!(response["details"]["frozen"], true);
+
+    let (response, _) = index.get().await;
+    assert_eq!(response["frozen"], true);
+
+    index
+        .add_documents(json!([{ "id": 2, "title": "Macbeth" }]), None)
+        .await;
+    index
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    index.delete().await;
+    for uid in 2..5 {
+        let response = index.wait_task(uid).await;
+        assert_eq!(response["status"], "failed");
+        assert_eq!(response["error"]["code"], "index_frozen");
+    }
+
+    // a frozen index can still be searched
+    let (response, code) = index.search_post(json!({ "q": "hamlet" })).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+
+    server
+        .service
+        .patch("/indexes/test", json!({ "frozen": false }))
+        .await;
+    index.wait_task(5).await;
+    index
+        .add_documents(json!([{ "id": 2, "title": "Macbeth" }]), None)
+        .await;
+    let response = index.wait_task(6).await;
+    assert_eq!(response["status"], "succeeded");
+}
@@ -0,0 +1,39 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn warmup_index() {
+    let server = Server::new().await;
+    let index = server.index("products");
+    index
+        .add_documents(json!([{ "id": 1, "name": "red shoes" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post("/indexes/products/warmup", json!(null))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["taskUid"], 1);
+    assert_eq!(response["type"], "indexWarmup");
+
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert!(response["details"]["warmedBytes"].as_u64().unwrap() > 0);
+}
+
+#[actix_rt::test]
+async fn warmup_unexisting_index() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post("/indexes/products/warmup", json!(null))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = server.index("products").wait_task(0).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "index_not_found");
+}
@@ -2,9 +2,11 @@ mod auth;
 mod common;
 mod dashboard;
 mod documents;
+mod drain;
 mod dumps;
 mod index;
 mod search;
+mod search_log;
 mod settings;
 mod snapshot;
 mod stats;
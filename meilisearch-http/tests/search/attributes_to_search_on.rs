@@ -0,0 +1,122 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn search_restricted_to_attributes() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "running shoes", "description": "light" },
+                { "id": 2, "title": "light jacket", "description": "goes with running shoes" },
+                { "id": 3, "title": "hat", "description": "warm" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    index
+        .search(
+            json!({ "q": "shoes", "attributesToSearchOn": ["title"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert_eq!(response["hits"][0]["id"], 1);
+                assert_eq!(response["estimatedTotalHits"], 1);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "q": "shoes", "attributesToSearchOn": ["description"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert_eq!(response["hits"][0]["id"], 2);
+            },
+        )
+        .await;
+
+    // Without terms to match, the attributes don't restrict anything.
+    index
+        .search(
+            json!({ "attributesToSearchOn": ["title"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 3);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_attribute_weights() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "searchableAttributes": ["title", "description"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "shoes", "description": "light" },
+                { "id": 2, "title": "light", "description": "shoes" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.search_post(json!({ "q": "shoes" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0]["id"], 1);
+
+    // The weights are only supported by the POST route.
+    let (response, code) = index
+        .search_post(json!({ "q": "shoes", "attributeWeights": { "description": 2.0 } }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0]["id"], 2);
+    assert_eq!(response["hits"][1]["id"], 1);
+}
+
+#[actix_rt::test]
+async fn search_on_invalid_attributes() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "searchableAttributes": ["title"] }))
+        .await;
+    index
+        .add_documents(json!([{ "id": 1, "title": "shoes", "color": "red" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let expected_response = json!({
+        "message": "Attribute `color` is not searchable. Available searchable attributes are: `title`.",
+        "code": "invalid_search_attributes_to_search_on",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_search_attributes_to_search_on"
+    });
+    index
+        .search(
+            json!({ "q": "shoes", "attributesToSearchOn": ["color"] }),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response, expected_response);
+            },
+        )
+        .await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "shoes", "attributeWeights": { "title": -1.0 } }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_attribute_weights");
+}
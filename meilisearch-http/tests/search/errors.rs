@@ -1,4 +1,4 @@
-use crate::common::Server;
+use crate::common::{default_settings, Server};
 use serde_json::json;
 
 use super::DOCUMENTS;
@@ -85,7 +85,8 @@ async fn filter_invalid_syntax_object() {
         "message": "Was expecting an operation `=`, `!=`, `>=`, `>`, `<=`, `<`, `TO` or `_geoRadius` at `title & Glass`.\n1:14 title & Glass",
         "code": "invalid_filter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+        "link": "https://docs.meilisearch.com/errors#invalid_filter",
+        "details": { "start": 0, "end": 13 }
     });
     index
         .search(json!({"filter": "title & Glass"}), |response, code| {
@@ -112,7 +113,8 @@ async fn filter_invalid_syntax_array() {
         "message": "Was expecting an operation `=`, `!=`, `>=`, `>`, `<=`, `<`, `TO` or `_geoRadius` at `title & Glass`.\n1:14 title & Glass",
         "code": "invalid_filter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+        "link": "https://docs.meilisearch.com/errors#invalid_filter",
+        "details": { "start": 0, "end": 13 }
     });
     index
         .search(json!({"filter": [["title & Glass"]]}), |response, code| {
@@ -139,7 +141,8 @@ async fn filter_invalid_syntax_string() {
         "message": "Found unexpected characters at the end of the filter: `XOR title = Glass`. You probably forgot an `OR` or an `AND` rule.\n15:32 title = Glass XOR title = Glass",
         "code": "invalid_filter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+        "link": "https://docs.meilisearch.com/errors#invalid_filter",
+        "details": { "start": 14, "end": 31 }
     });
     index
         .search(
@@ -169,7 +172,8 @@ async fn filter_invalid_attribute_array() {
         "message": "Attribute `many` is not filterable. Available filterable attributes are: `title`.\n1:5 many = Glass",
         "code": "invalid_filter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+        "link": "https://docs.meilisearch.com/errors#invalid_filter",
+        "details": { "start": 0, "end": 4 }
     });
     index
         .search(json!({"filter": [["many = Glass"]]}), |response, code| {
@@ -196,7 +200,8 @@ async fn filter_invalid_attribute_string() {
         "message": "Attribute `many` is not filterable. Available filterable attributes are: `title`.\n1:5 many = Glass",
         "code": "invalid_filter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+        "link": "https://docs.meilisearch.com/errors#invalid_filter",
+        "details": { "start": 0, "end": 4 }
     });
     index
         .search(json!({"filter": "many = Glass"}), |response, code| {
@@ -206,6 +211,34 @@ async fn filter_invalid_attribute_string() {
         .await;
 }
 
+#[actix_rt::test]
+async fn filter_misspelled_attribute() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["title", "id"]}))
+        .await;
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({"filter": "id > 2 AND titel = Glass"}),
+            |response, code| {
+                assert_eq!(code, 400);
+                assert_eq!(response["code"], "invalid_filter");
+                assert_eq!(
+                    response["details"],
+                    json!({ "start": 11, "end": 16, "suggestion": "title" })
+                );
+            },
+        )
+        .await;
+}
+
 #[actix_rt::test]
 async fn filter_reserved_geo_attribute_array() {
     let server = Server::new().await;
@@ -223,7 +256,8 @@ async fn filter_reserved_geo_attribute_array() {
         "message": "`_geo` is a reserved keyword and thus can't be used as a filter expression. Use the _geoRadius(latitude, longitude, distance) built-in rule to filter on _geo field coordinates.\n1:5 _geo = Glass",
         "code": "invalid_filter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+        "link": "https://docs.meilisearch.com/errors#invalid_filter",
+        "details": { "start": 0, "end": 4 }
     });
     index
         .search(json!({"filter": [["_geo = Glass"]]}), |response, code| {
@@ -250,7 +284,8 @@ async fn filter_reserved_geo_attribute_string() {
         "message": "`_geo` is a reserved keyword and thus can't be used as a filter expression. Use the _geoRadius(latitude, longitude, distance) built-in rule to filter on _geo field coordinates.\n1:5 _geo = Glass",
         "code": "invalid_filter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+        "link": "https://docs.meilisearch.com/errors#invalid_filter",
+        "details": { "start": 0, "end": 4 }
     });
     index
         .search(json!({"filter": "_geo = Glass"}), |response, code| {
@@ -277,7 +312,8 @@ async fn filter_reserved_attribute_array() {
         "message": "`_geoDistance` is a reserved keyword and thus can't be used as a filter expression.\n1:13 _geoDistance = Glass",
         "code": "invalid_filter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+        "link": "https://docs.meilisearch.com/errors#invalid_filter",
+        "details": { "start": 0, "end": 12 }
     });
     index
         .search(
@@ -307,7 +343,8 @@ async fn filter_reserved_attribute_string() {
         "message": "`_geoDistance` is a reserved keyword and thus can't be used as a filter expression.\n1:13 _geoDistance = Glass",
         "code": "invalid_filter",
         "type": "invalid_request",
-        "link": "https://docs.meilisearch.com/errors#invalid_filter"
+        "link": "https://docs.meilisearch.com/errors#invalid_filter",
+        "details": { "start": 0, "end": 12 }
     });
     index
         .search(
@@ -481,3 +518,35 @@ async fn sort_unset_ranking_rule() {
         )
         .await;
 }
+
+#[actix_rt::test]
+async fn search_rejected_when_the_search_queue_is_full() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        max_concurrent_searches: Some(0),
+        search_queue_size: 0,
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let expected_response = json!({
+        "message": "Too many search requests are being processed, retry later.",
+        "code": "too_many_search_requests",
+        "type": "internal",
+        "link": "https://docs.meilisearch.com/errors#too_many_search_requests"
+    });
+    index
+        .search(json!({"q": "hello"}), |response, code| {
+            assert_eq!(response, expected_response);
+            assert_eq!(code, 503);
+        })
+        .await;
+
+    // The tasks aren't limited.
+    let (response, code) = index.get().await;
+    assert_eq!(code, 200, "{}", response);
+}
@@ -0,0 +1,83 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn facet_search() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["brand", "color"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "brand": "Apple", "color": "red" },
+                { "id": 2, "brand": "Apple", "color": "blue" },
+                { "id": 3, "brand": "Applied Science", "color": "red" },
+                { "id": 4, "brand": "Pineapple", "color": "red" },
+                { "id": 5, "brand": "Samsung", "color": "red" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/facet-search",
+            json!({ "facetName": "brand", "facetQuery": "app" }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([
+            { "value": "Apple", "count": 2 },
+            { "value": "Applied Science", "count": 1 },
+        ])
+    );
+    assert_eq!(response["facetQuery"], "app");
+
+    // The counts only cover the documents matching the filter.
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/facet-search",
+            json!({ "facetName": "brand", "facetQuery": "app", "filter": "color = red" }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetHits"],
+        json!([
+            { "value": "Apple", "count": 1 },
+            { "value": "Applied Science", "count": 1 },
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn facet_search_not_filterable() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["color"] }))
+        .await;
+    index
+        .add_documents(json!([{ "id": 1, "brand": "Apple" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/facet-search",
+            json!({ "facetName": "brand" }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_facet_search_facet_name");
+}
@@ -257,6 +257,92 @@ async fn format_nested() {
     );
 }
 
+#[actix_rt::test]
+async fn matches_position_in_bytes() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([{ "id": 1, "title": "café crème tarte" }]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    // The positions are byte offsets in the original string, `é` and `è` are two bytes long.
+    let (response, code) = index
+        .search_post(json!({ "q": "tarte", "showMatchesPosition": true }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"][0]["_matchesPosition"],
+        json!({ "title": [{ "start": 13, "length": 5 }] })
+    );
+}
+
+#[actix_rt::test]
+async fn format_nested_displayed_attributes() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = NESTED_DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    // The attribute to highlight is nested in a displayed attribute.
+    index
+        .update_settings(json!({ "displayedAttributes": ["id", "doggos"] }))
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "bobby", "attributesToRetrieve": ["id"], "attributesToHighlight": ["doggos.name"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"][0],
+        json!({
+            "id": 852,
+            "_formatted": {
+                "id": "852",
+                "doggos": [
+                    {
+                        "name": "<em>bobby</em>",
+                    },
+                    {
+                        "name": "buddy",
+                    },
+                ],
+            },
+        })
+    );
+
+    // Only the displayed parts of the attribute to highlight are formatted.
+    index
+        .update_settings(json!({ "displayedAttributes": ["id", "doggos.name"] }))
+        .await;
+    index.wait_task(2).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "bobby", "attributesToRetrieve": ["id"], "attributesToHighlight": ["doggos"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"][0],
+        json!({
+            "id": 852,
+            "_formatted": {
+                "id": "852",
+                "doggos": [
+                    {
+                        "name": "<em>bobby</em>",
+                    },
+                    {
+                        "name": "buddy",
+                    },
+                ],
+            },
+        })
+    );
+}
+
 #[actix_rt::test]
 async fn displayedattr_2_smol() {
     let server = Server::new().await;
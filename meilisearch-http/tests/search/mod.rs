@@ -1,8 +1,17 @@
 // This modules contains all the test concerning search. Each particular feture of the search
 // should be tested in its own module to isolate tests and keep the tests readable.
 
+mod attributes_to_search_on;
 mod errors;
+mod facet_search;
 mod formatted;
+mod multi;
+mod pagination;
+mod rules;
+mod suggest;
+mod vector;
+
+use std::collections::HashSet;
 
 use crate::common::Server;
 use once_cell::sync::Lazy;
@@ -510,6 +519,59 @@ async fn search_facet_distribution() {
         .await;
 }
 
+#[actix_rt::test]
+async fn search_facet_stats() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["id", "father"]}))
+        .await;
+
+    let documents = NESTED_DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({
+                "facets": ["id", "father"]
+            }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                // only the numeric facets have stats
+                assert_eq!(
+                    response["facetStats"],
+                    json!({ "id": { "min": 654.0, "max": 951.0 } })
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({
+                "facets": ["id"],
+                "filter": "id < 900"
+            }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["facetStats"],
+                    json!({ "id": { "min": 654.0, "max": 852.0 } })
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(json!({}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("facetStats").is_none());
+        })
+        .await;
+}
+
 #[actix_rt::test]
 async fn displayed_attributes() {
     let server = Server::new().await;
@@ -567,7 +629,7 @@ async fn placeholder_search_is_hard_limited() {
         .await;
 
     index
-        .update_settings(json!({ "pagination": { "limitedTo": 10_000 } }))
+        .update_settings(json!({ "pagination": { "maxTotalHits": 10_000 } }))
         .await;
     index.wait_task(1).await;
 
@@ -636,7 +698,7 @@ async fn search_is_hard_limited() {
         .await;
 
     index
-        .update_settings(json!({ "pagination": { "limitedTo": 10_000 } }))
+        .update_settings(json!({ "pagination": { "maxTotalHits": 10_000 } }))
         .await;
     index.wait_task(1).await;
 
@@ -716,3 +778,888 @@ async fn faceting_max_values_per_facet() {
         )
         .await;
 }
+
+#[actix_rt::test]
+async fn faceting_sort_facet_values_by() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["color", "size"] }))
+        .await;
+
+    let documents = json!([
+        { "id": 1, "color": "red", "size": "xl" },
+        { "id": 2, "color": "red", "size": "xl" },
+        { "id": 3, "color": "red", "size": "m" },
+        { "id": 4, "color": "green", "size": "s" },
+        { "id": 5, "color": "green" },
+        { "id": 6, "color": "blue" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let values = |response: &Value, facet: &str| -> Vec<String> {
+        response["facetDistribution"][facet]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect()
+    };
+
+    index
+        .search(json!({ "facets": ["color", "size"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(values(&response, "color"), ["blue", "green", "red"]);
+            assert_eq!(values(&response, "size"), ["m", "s", "xl"]);
+        })
+        .await;
+
+    index
+        .update_settings(json!({ "faceting": {
+            "maxValuesPerFacet": 2,
+            "sortFacetValuesBy": { "*": "count", "size": "alpha" }
+        } }))
+        .await;
+    index.wait_task(2).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["faceting"],
+        json!({ "maxValuesPerFacet": 2, "sortFacetValuesBy": { "*": "count", "size": "alpha" } })
+    );
+
+    // The most frequent values are kept when the values sorted by count are truncated.
+    index
+        .search(json!({ "facets": ["color", "size"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(values(&response, "color"), ["red", "green"]);
+            assert_eq!(response["facetDistribution"]["color"]["red"], json!(3));
+            assert_eq!(values(&response, "size"), ["m", "s"]);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_exhaustive_facet_count() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["color"],
+            "faceting": { "maxValuesPerFacet": 1 }
+        }))
+        .await;
+
+    let documents = json!([
+        { "id": 1, "color": ["red", "blue"] },
+        { "id": 2, "color": "red" },
+        { "id": 3, "color": "blue" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({ "facets": ["color"], "exhaustiveFacetCount": true }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["facetDistribution"],
+                    json!({ "color": { "blue": 2 } })
+                );
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_facet_patterns() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["dim_color", "dim_size", "brand"]}))
+        .await;
+
+    let documents = json!([
+        { "id": 1, "dim_color": "red", "dim_size": "xl", "brand": "nike" },
+        { "id": 2, "dim_color": "blue", "dim_size": "xl", "brand": "puma" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({ "facets": ["dim_*"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(
+                response["facetDistribution"],
+                json!({
+                    "dim_color": { "blue": 1, "red": 1 },
+                    "dim_size": { "xl": 2 }
+                })
+            );
+        })
+        .await;
+
+    index
+        .search(json!({ "facets": ["*"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            let distribution = response["facetDistribution"].as_object().unwrap();
+            assert_eq!(distribution.len(), 3);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_cutoff_degraded() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["title"]}))
+        .await;
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({ "facets": ["title"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("degraded").is_none());
+            assert!(response.get("facetDistribution").is_some());
+        })
+        .await;
+
+    // With no budget at all, the facets are left out but the hits are still returned.
+    index.update_settings(json!({"searchCutoffMs": 0})).await;
+    index.wait_task(2).await;
+
+    index
+        .search(json!({ "facets": ["title"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["degraded"], json!(true));
+            assert!(response.get("facetDistribution").is_none());
+            assert_eq!(response["hits"].as_array().unwrap().len(), 5);
+        })
+        .await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["searchCutoffMs"], json!(0));
+}
+
+#[actix_rt::test]
+async fn search_with_ranking_experiment() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"sortableAttributes": ["price"]}))
+        .await;
+    let documents = json!([
+        { "id": 1, "title": "red shirt", "price": 30 },
+        { "id": 2, "title": "blue shirt", "price": 10 },
+        { "id": 3, "title": "green shirt", "price": 20 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    // A variant without traffic never serves a search.
+    index
+        .update_settings(json!({
+            "rankingExperiment": {
+                "variants": {
+                    "control": { "traffic": 0 },
+                    "cheapest": { "traffic": 1, "sort": ["price:asc"] },
+                }
+            }
+        }))
+        .await;
+    index.wait_task(2).await;
+
+    index
+        .search(json!({ "q": "shirt" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["rankingVariant"], json!("cheapest"));
+            let ids: Vec<_> = response["hits"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|hit| hit["id"].as_u64().unwrap())
+                .collect();
+            assert_eq!(ids, [2, 3, 1]);
+        })
+        .await;
+
+    // The sort of the search takes precedence over the one of the variant.
+    index
+        .search(
+            json!({ "q": "shirt", "sort": ["price:desc"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["rankingVariant"], json!("cheapest"));
+                assert_eq!(response["hits"][0]["id"], json!(1));
+            },
+        )
+        .await;
+
+    index
+        .update_settings(json!({
+            "rankingExperiment": { "variants": { "control": { "traffic": 0 } } }
+        }))
+        .await;
+    let response = index.wait_task(3).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_ranking_experiment");
+
+    index
+        .update_settings(json!({ "rankingExperiment": null }))
+        .await;
+    index.wait_task(4).await;
+
+    index
+        .search(json!({ "q": "shirt" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("rankingVariant").is_none());
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_debug() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["color"] }))
+        .await;
+    let documents = json!([
+        { "id": 1, "title": "red shirt", "color": "red" },
+        { "id": 2, "title": "red shrt", "color": "blue" },
+        { "id": 3, "title": "green hat", "color": "red" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "red shirt", "filter": ["color = red"], "debug": true }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["debug"]["candidatesWithoutFilters"], json!(2));
+    assert_eq!(
+        response["debug"]["filters"],
+        json!([{ "filter": "color = red", "candidates": 1, "pruned": 1 }])
+    );
+    assert!(!response["debug"]["rankingRules"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    let hit = &response["hits"][0];
+    assert_eq!(hit["id"], json!(1));
+    assert_eq!(hit["_debug"]["matchedQueryWords"], json!(2));
+    assert_eq!(hit["_debug"]["typos"], json!(0));
+
+    let (response, code) = index
+        .search_post(json!({ "q": "red shirt", "filter": "color = blue", "debug": true }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let hit = &response["hits"][0];
+    assert_eq!(hit["id"], json!(2));
+    assert_eq!(hit["_debug"]["typos"], json!(1));
+    assert!(hit["_debug"]["matchedWords"]
+        .as_array()
+        .unwrap()
+        .contains(&json!({
+            "attribute": "title",
+            "queryWord": "shirt",
+            "documentWord": "shrt",
+            "typos": 1,
+        })));
+
+    let (response, code) = index.search_post(json!({ "q": "red shirt" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("debug").is_none());
+    assert!(response["hits"][0].get("_debug").is_none());
+}
+
+#[actix_rt::test]
+async fn search_with_matching_strategy() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "red shirt" },
+        { "id": 2, "title": "red pants" },
+        { "id": 3, "title": "red hat" },
+        { "id": 4, "title": "blue shirt" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let ids = |response: &Value| {
+        let mut ids: Vec<_> = response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    index
+        .search(json!({ "q": "red blue" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(ids(&response), [1, 2, 3]);
+        })
+        .await;
+
+    index
+        .search(
+            json!({ "q": "red blue", "matchingStrategy": "all" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert!(ids(&response).is_empty());
+            },
+        )
+        .await;
+
+    // `red` is the most frequent term, it's dropped first.
+    index
+        .search(
+            json!({ "q": "red blue", "matchingStrategy": "frequency" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), [4]);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "q": "red shirt", "matchingStrategy": "frequency" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), [1]);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_suggested_query() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "bluetooth headphones" },
+        { "id": 2, "title": "bluetooth speaker" },
+        { "id": 3, "title": "wired headphones" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(
+            json!({ "q": "bluetoht", "showSuggestedQuery": true }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["estimatedTotalHits"], json!(0));
+                assert_eq!(response["suggestedQuery"], json!("bluetooth"));
+            },
+        )
+        .await;
+
+    // Nothing is suggested without the parameter, or when the query is spelled right.
+    index
+        .search(json!({ "q": "bluetoht" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("suggestedQuery").is_none(), "{}", response);
+        })
+        .await;
+    index
+        .search(
+            json!({ "q": "wired", "showSuggestedQuery": true }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert!(response.get("suggestedQuery").is_none(), "{}", response);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_distinct_groups() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "shirt", "product_id": "a", "color": "red" },
+        { "id": 2, "title": "shirt", "product_id": "b", "color": "red" },
+        { "id": 3, "title": "shirt", "product_id": "a", "color": "blue" },
+        { "id": 4, "title": "shirt", "product_id": "a", "color": "green" },
+        { "id": 5, "title": "shirt", "color": "white" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "shirt",
+            "distinct": { "attribute": "product_id", "hitsPerGroup": 2 }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect();
+    // The hits of a product follow each other, the ones without a product are all kept.
+    assert_eq!(ids, [1, 3, 2, 5]);
+    assert_eq!(response["estimatedTotalHits"], json!(4));
+
+    let (response, code) = index
+        .search_post(json!({ "q": "shirt", "distinct": { "attribute": "product_id" } }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 3);
+
+    let (response, code) = index
+        .search_post(json!({
+            "distinct": { "attribute": "product_id", "hitsPerGroup": 0 }
+        }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_distinct");
+}
+
+#[actix_rt::test]
+async fn search_with_negative_terms() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "jaguar car" },
+        { "id": 2, "title": "jaguar cat" },
+        { "id": 3, "title": "jaguar sports car" },
+        { "id": 4, "title": "jaguar cartoon" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({ "q": "jaguar -car" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            // The negative terms have no typos nor prefixes.
+            let mut ids: Vec<_> = response["hits"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|hit| hit["id"].as_u64().unwrap())
+                .collect();
+            ids.sort_unstable();
+            assert_eq!(ids, vec![2, 4]);
+            assert_eq!(response["estimatedTotalHits"], 2);
+        })
+        .await;
+
+    index
+        .search(
+            json!({ "q": "jaguar -\"sports car\"" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 3);
+                assert!(response["hits"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .all(|hit| hit["id"] != 3));
+            },
+        )
+        .await;
+
+    // Without positive terms, all the documents but the excluded ones are returned.
+    index
+        .search(json!({ "q": "-jaguar" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 0);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_string_filter_operators() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["sku"] }))
+        .await;
+    let documents = json!([
+        { "id": 1, "sku": "EU-1234" },
+        { "id": 2, "sku": "eu-5678" },
+        { "id": 3, "sku": "US-EU-12" },
+        { "id": 4, "sku": "US-9876" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let ids = |response: &Value| {
+        let mut ids: Vec<_> = response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    index
+        .search(
+            json!({ "filter": "sku STARTS WITH \"EU-\"" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![1, 2]);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "filter": ["sku CONTAINS 12", "NOT sku STARTS WITH US"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![1]);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "filter": "sku CONTAINS \"CN-\"" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), Vec::<u64>::new());
+            },
+        )
+        .await;
+
+    index
+        .search(json!({ "filter": "id CONTAINS 1" }), |response, code| {
+            assert_eq!(code, 400, "{}", response);
+            assert_eq!(response["code"], "invalid_filter");
+        })
+        .await;
+
+    // A matching value that can't be quoted can't be filtered on.
+    index
+        .add_documents(json!([{ "id": 5, "sku": "EU-\"it's\"" }]), None)
+        .await;
+    index.wait_task(2).await;
+    index
+        .search(
+            json!({ "filter": "sku STARTS WITH \"EU-\"" }),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_filter");
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_geo_bounding_box_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["_geo", "kind"] }))
+        .await;
+    let documents = json!([
+        { "id": 1, "kind": "bakery", "_geo": { "lat": 45.4685, "lng": 9.1824 } },
+        { "id": 2, "kind": "bakery", "_geo": { "lat": 45.4685, "lng": 9.1824 } },
+        { "id": 3, "kind": "museum", "_geo": { "lat": 45.4408, "lng": 9.1505 } },
+        { "id": 4, "kind": "bakery", "_geo": { "lat": 48.8566, "lng": 2.3522 } },
+        { "id": 5, "kind": "bakery" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let ids = |response: &Value| {
+        let mut ids: Vec<_> = response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect();
+        ids.sort_unstable();
+        ids
+    };
+
+    index
+        .search(
+            json!({ "filter": "_geoBoundingBox([45.5, 9.2], [45.4, 9.1])" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![1, 2, 3]);
+            },
+        )
+        .await;
+
+    // Like with `_geoRadius`, the documents without a location are never in the negation.
+    index
+        .search(
+            json!({ "filter": ["kind = bakery", "NOT _geoBoundingBox([45.5, 9.2], [45.4, 9.1])"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![4]);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "filter": "_geoBoundingBox([10, 10], [0, 0]) OR kind = museum" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), vec![3]);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "filter": "_geoBoundingBox([45.4, 9.2], [45.5, 9.1])" }),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_filter");
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_object_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["genre", "year"] }))
+        .await;
+    let documents = json!([
+        { "id": 1, "genre": "horror", "year": 1980 },
+        { "id": 2, "genre": "horror", "year": 2005 },
+        { "id": 3, "genre": "it's \"comedy\"", "year": 2010 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "filter": {
+                "or": [
+                    { "and": [
+                        { "attribute": "genre", "operator": "=", "value": "horror" },
+                        { "attribute": "year", "operator": ">", "value": 2000 },
+                    ]},
+                    { "attribute": "genre", "operator": "STARTS WITH", "value": "it's" },
+                ]
+            }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let mut ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![2, 3]);
+
+    // The objects can be mixed with the array form.
+    let (response, code) = index
+        .search_post(json!({
+            "filter": [{ "not": { "attribute": "year", "operator": "TO", "value": [1990, 2007] } }, "genre = horror"]
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(response["hits"][0]["id"], 1);
+
+    let (response, code) = index
+        .search_post(json!({
+            "filter": { "attribute": "genre", "operator": "LIKE", "value": "horror" }
+        }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_filter");
+}
+
+#[actix_rt::test]
+async fn search_with_typo_tolerance_override() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "running shoes" },
+        { "id": 2, "title": "red jacket" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.search_post(json!({ "q": "shoos" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+
+    // The typo tolerance is only supported by the POST route.
+    for typo_tolerance in [
+        json!({ "enabled": false }),
+        json!({ "disableOnWords": ["SHOOS"] }),
+        json!({ "minWordSizeForTypos": { "oneTypo": 6 } }),
+    ] {
+        let (response, code) = index
+            .search_post(json!({ "q": "shoos", "typoTolerance": typo_tolerance }))
+            .await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(
+            response["hits"].as_array().unwrap().len(),
+            0,
+            "{}",
+            typo_tolerance
+        );
+    }
+
+    // The index settings are left untouched.
+    let (response, _) = index.settings().await;
+    assert_eq!(response["typoTolerance"]["enabled"], true);
+}
+
+#[actix_rt::test]
+async fn search_cache_invalidated_by_tasks() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        search_cache_size: Some(10),
+        ..crate::common::default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "title": "blue shoes" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    for _ in 0..2 {
+        let (response, code) = index.search_post(json!({ "q": "shoes" })).await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    }
+
+    // The cached result is dropped once the new document is indexed.
+    index
+        .add_documents(json!([{ "id": 2, "title": "red shoes" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.search_post(json!({ "q": "shoes" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn search_cache_with_random_ranking_variants() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        search_cache_size: Some(10),
+        ..crate::common::default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "title": "blue shoes" }]), None)
+        .await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({
+            "rankingExperiment": {
+                "variants": {
+                    "control": { "traffic": 1 },
+                    "strict": { "traffic": 1, "typoTolerance": false },
+                }
+            }
+        }))
+        .await;
+    index.wait_task(1).await;
+
+    // The searches without an experiment key are spread over the variants, even when a result of
+    // the same search was computed before.
+    let mut variants = HashSet::new();
+    for _ in 0..40 {
+        let (response, code) = index.search_post(json!({ "q": "shoes" })).await;
+        assert_eq!(code, 200, "{}", response);
+        variants.insert(response["rankingVariant"].as_str().unwrap().to_string());
+    }
+    assert_eq!(variants.len(), 2, "{:?}", variants);
+}
+
+#[actix_rt::test]
+async fn search_with_boosted_and_buried_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["color"] }))
+        .await;
+
+    let documents = json!([
+        { "id": 1, "title": "shoes", "color": "red" },
+        { "id": 2, "title": "shoes", "color": "red" },
+        { "id": 3, "title": "shoes", "color": "red" },
+        { "id": 4, "title": "shoes", "color": "blue" },
+        { "id": 5, "title": "jacket", "color": "red" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let ids = |response: &Value| -> Vec<u64> {
+        response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect()
+    };
+
+    // The documents not matching the search and the unknown ids are ignored.
+    let (response, code) = index
+        .search_post(json!({
+            "q": "shoes",
+            "filter": "color = red",
+            "boostDocuments": [3, "5", "unknown"],
+            "buryDocuments": ["1", 4],
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), [3, 2, 1]);
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "shoes",
+            "boostDocuments": [4, 2],
+            "offset": 1,
+            "limit": 2,
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), [2, 1]);
+}
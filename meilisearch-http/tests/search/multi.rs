@@ -0,0 +1,169 @@
+use super::*;
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn multi_search() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let index = server.index("nested");
+    index.add_documents(NESTED_DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/multi-search",
+            json!([
+                { "indexUid": "test", "q": "glass" },
+                { "indexUid": "nested", "q": "pesti", "limit": 1 },
+            ]),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["indexUid"], "test");
+    assert_eq!(results[0]["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(results[1]["indexUid"], "nested");
+    assert_eq!(results[1]["limit"], 1);
+    assert_eq!(results[1]["hits"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn multi_search_unknown_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/multi-search",
+            json!([
+                { "indexUid": "test", "q": "glass" },
+                { "indexUid": "unknown", "q": "glass" },
+            ]),
+        )
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn federated_search() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let index = server.index("nested");
+    index.add_documents(NESTED_DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/multi-search/federated",
+            json!({
+                "queries": [
+                    { "indexUid": "test" },
+                    { "indexUid": "nested", "weight": 2.0 },
+                ],
+                "limit": 3,
+            }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["estimatedTotalHits"], 9);
+    assert_eq!(response["limit"], 3);
+
+    // All the hits of a placeholder search are as relevant, the weight of the `nested` query puts
+    // its hits first.
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 3);
+    for hit in hits {
+        assert_eq!(hit["_federation"]["indexUid"], "nested");
+        assert_eq!(hit["_federation"]["queriesPosition"], 1);
+        assert_eq!(hit["_federation"]["weightedRankingScore"], 2.0);
+    }
+
+    // The hits matching all the words of their query are as relevant, whatever their index.
+    let (response, code) = server
+        .service
+        .post(
+            "/multi-search/federated",
+            json!({
+                "queries": [
+                    { "indexUid": "test", "q": "glass" },
+                    { "indexUid": "nested", "q": "pesti" },
+                ],
+            }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits[0]["_federation"]["indexUid"], "test");
+    assert_eq!(hits[0]["_federation"]["weightedRankingScore"], 1.0);
+    assert_eq!(hits[1]["_federation"]["indexUid"], "nested");
+    assert_eq!(hits[1]["_federation"]["weightedRankingScore"], 1.0);
+}
+
+#[actix_rt::test]
+async fn federated_search_invalid_weight() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/multi-search/federated",
+            json!({ "queries": [{ "indexUid": "test", "weight": -1.0 }] }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn multi_search_unknown_field() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/multi-search",
+            json!([{ "indexUid": "test", "q": "glass", "limitt": 1 }]),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert!(
+        response["message"]
+            .as_str()
+            .unwrap()
+            .contains("unknown field `limitt`"),
+        "{}",
+        response
+    );
+
+    let (response, code) = server
+        .service
+        .post(
+            "/multi-search/federated",
+            json!({ "queries": [{ "indexUid": "test", "weigth": 2.0 }] }),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert!(
+        response["message"]
+            .as_str()
+            .unwrap()
+            .contains("unknown field `weigth`"),
+        "{}",
+        response
+    );
+}
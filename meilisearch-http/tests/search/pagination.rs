@@ -0,0 +1,178 @@
+use crate::common::Server;
+use serde_json::{json, Value};
+
+#[actix_rt::test]
+async fn search_with_page_and_hits_per_page() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents: Vec<_> = (0..45)
+        .map(|i| json!({ "id": i, "text": "hello" }))
+        .collect();
+    index.add_documents(documents.into(), None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({ "page": 2, "hitsPerPage": 20 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 20);
+            assert_eq!(response["hits"][0]["id"], 20);
+            assert_eq!(response["page"], 2);
+            assert_eq!(response["hitsPerPage"], 20);
+            assert_eq!(response["totalHits"], 45);
+            assert_eq!(response["totalPages"], 3);
+            assert!(response.get("estimatedTotalHits").is_none());
+            assert!(response.get("offset").is_none());
+            assert!(response.get("limit").is_none());
+        })
+        .await;
+
+    // The last page is partial, and the pages past it are empty.
+    index
+        .search(json!({ "page": 3, "hitsPerPage": 20 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 5);
+        })
+        .await;
+    index
+        .search(json!({ "page": 4, "hitsPerPage": 20 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 0);
+            assert_eq!(response["totalPages"], 3);
+        })
+        .await;
+
+    // Only one of the parameters is enough, the other one has its default value.
+    index
+        .search(json!({ "page": 1 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hitsPerPage"], 20);
+            assert_eq!(response["totalPages"], 3);
+        })
+        .await;
+    index
+        .search(json!({ "hitsPerPage": 0 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 0);
+            assert_eq!(response["page"], 1);
+            assert_eq!(response["totalHits"], 45);
+            assert_eq!(response["totalPages"], 0);
+        })
+        .await;
+    index
+        .search(json!({ "page": 0 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 0);
+            assert_eq!(response["totalPages"], 3);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_page_is_limited_by_pagination_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "pagination": { "maxTotalHits": 30 } }))
+        .await;
+    let documents: Vec<_> = (0..45)
+        .map(|i| json!({ "id": i, "text": "hello" }))
+        .collect();
+    index.add_documents(documents.into(), None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({ "page": 2, "hitsPerPage": 20 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 10);
+            assert_eq!(response["totalHits"], 30);
+            assert_eq!(response["totalPages"], 2);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn browse_filtered_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["even"] }))
+        .await;
+    let documents: Vec<_> = (0..100)
+        .map(|i| json!({ "id": i, "even": i % 2 == 0 }))
+        .collect();
+    index.add_documents(documents.into(), None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({ "filter": "even = true", "offset": 40, "limit": 3 }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"], json!([{ "id": 80, "even": true }, { "id": 82, "even": true }, { "id": 84, "even": true }]));
+                assert_eq!(response["estimatedTotalHits"], 50);
+            },
+        )
+        .await;
+
+    // A browse sorted by a single attribute puts the numbers first, then the strings, then the
+    // documents without a value.
+    index
+        .update_settings(json!({ "sortableAttributes": ["rank"] }))
+        .await;
+    index.wait_task(2).await;
+    let documents = json!([
+        { "id": 100, "even": true, "rank": "b" },
+        { "id": 102, "even": true, "rank": 3 },
+        { "id": 104, "even": true, "rank": "a" },
+        { "id": 106, "even": true, "rank": 1 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(3).await;
+
+    let ids = |response: &Value| -> Vec<u64> {
+        response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect()
+    };
+    index
+        .search(
+            json!({ "filter": "even = true", "sort": ["rank:asc"], "limit": 6 }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), [106, 102, 104, 100, 0, 2]);
+            },
+        )
+        .await;
+    index
+        .search(
+            json!({ "filter": "even = true", "sort": ["rank:desc"], "offset": 1, "limit": 4 }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(ids(&response), [106, 100, 104, 0]);
+                assert_eq!(response["estimatedTotalHits"], 54);
+            },
+        )
+        .await;
+
+    // The ranking rules sorting by an attribute are still applied.
+    index
+        .update_settings(json!({ "sortableAttributes": ["id"], "rankingRules": ["id:desc"] }))
+        .await;
+    index.wait_task(4).await;
+
+    index
+        .search(
+            json!({ "filter": "even = true", "offset": 40, "limit": 1 }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"], json!([{ "id": 26, "even": true }]));
+            },
+        )
+        .await;
+}
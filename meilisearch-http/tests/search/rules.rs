@@ -0,0 +1,102 @@
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+fn ids(response: &Value) -> Vec<u64> {
+    response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn search_with_rules() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["brand"] }))
+        .await;
+
+    let documents = json!([
+        { "id": 1, "title": "phone case", "brand": "acme" },
+        { "id": 2, "title": "phone charger", "brand": "acme" },
+        { "id": 3, "title": "phone stand", "brand": "globex" },
+        { "id": 4, "title": "mobile holder", "brand": "globex" },
+        { "id": 5, "title": "tablet sleeve", "brand": "acme" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    let rules = json!([
+        {
+            "id": "promote-globex",
+            "conditions": [{ "pattern": "phone", "anchoring": "contains" }],
+            "consequence": {
+                "pin": ["5"],
+                "hide": ["1"],
+                "boost": "brand = globex",
+                "synonyms": { "phone": ["mobile"] }
+            }
+        },
+        {
+            "id": "acme-deals",
+            "conditions": [{ "filter": "brand = acme" }],
+            "consequence": { "pin": ["2"] }
+        }
+    ]);
+    let url = format!("/indexes/{}/rules", index.uid);
+    let (response, code) = index.service.put(&url, rules.clone()).await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(2).await;
+
+    let (response, code) = index.service.get(&url).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, rules);
+
+    // The hidden document is removed and the documents of the boosted brand are ranked first, the
+    // pinned document doesn't match the search.
+    let (response, code) = index.search_post(json!({ "q": "phone" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), [3, 4, 2]);
+    assert_eq!(response["appliedRules"], json!(["promote-globex"]));
+
+    let (response, code) = index
+        .search_post(json!({ "q": "charger", "filter": "brand = acme" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), [2]);
+    assert_eq!(response["appliedRules"], json!(["acme-deals"]));
+
+    let (response, code) = index.search_post(json!({ "q": "tablet" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), [5]);
+    assert!(response.get("appliedRules").is_none(), "{}", response);
+
+    let (response, code) = index.service.delete(&url).await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(3).await;
+
+    let (response, code) = index.search_post(json!({ "q": "phone" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), [1, 2, 3]);
+}
+
+#[actix_rt::test]
+async fn rules_with_duplicated_ids() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let url = format!("/indexes/{}/rules", index.uid);
+    let (response, code) = index
+        .service
+        .put(&url, json!([{ "id": "sale" }, { "id": "sale" }]))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_rules");
+}
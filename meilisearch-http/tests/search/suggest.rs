@@ -0,0 +1,102 @@
+use serde_json::json;
+
+use crate::common::{default_settings, Server};
+
+#[actix_rt::test]
+async fn suggest() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        search_log_size: Some(10),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "laptop stand" },
+                { "id": 2, "title": "laptop bag" },
+                { "id": 3, "title": "lamp" },
+                { "id": 4, "title": "gaming laptop" },
+                { "id": 5, "title": "lapel pin" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    index.search_post(json!({ "q": "laptop bag" })).await;
+    index.search_post(json!({ "q": "Laptop bag " })).await;
+    index.search_post(json!({ "q": "laptop stand" })).await;
+
+    // The popular queries come first, then the words of the documents, the most frequent first.
+    let (response, code) = index
+        .service
+        .post("/indexes/test/suggest", json!({ "q": "lap" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["suggestions"],
+        json!([
+            { "suggestion": "laptop bag", "source": "query", "count": 2 },
+            { "suggestion": "laptop stand", "source": "query", "count": 1 },
+            { "suggestion": "laptop", "source": "term", "count": 3 },
+            { "suggestion": "lapel", "source": "term", "count": 1 },
+        ])
+    );
+
+    // The last word is completed, the beginning of the query is kept.
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/suggest",
+            json!({ "q": "gaming la", "limit": 1 }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["suggestions"],
+        json!([{ "suggestion": "gaming laptop", "source": "term", "count": 3 }])
+    );
+
+    // A typo is tolerated on queries of five characters or more.
+    let (response, code) = index
+        .service
+        .post(
+            "/indexes/test/suggest",
+            json!({ "q": "laptpo", "limit": 3 }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["suggestions"],
+        json!([
+            { "suggestion": "laptop bag", "source": "query", "count": 2 },
+            { "suggestion": "laptop stand", "source": "query", "count": 1 },
+            { "suggestion": "laptop", "source": "term", "count": 3 },
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn suggest_without_search_log() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "title": "laptop stand" }]), None)
+        .await;
+    index.wait_task(0).await;
+    index.search_post(json!({ "q": "laptop stand" })).await;
+
+    let (response, code) = index
+        .service
+        .post("/indexes/test/suggest", json!({ "q": "lapt" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["suggestions"],
+        json!([{ "suggestion": "laptop", "source": "term", "count": 1 }])
+    );
+}
@@ -0,0 +1,200 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn vector_search() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["color"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "color": "red", "_vectors": [1.0, 0.0] },
+                { "id": 2, "color": "blue", "_vectors": [0.0, 1.0] },
+                { "id": 3, "color": "red", "_vectors": [[0.0, -1.0], [0.7, 0.7]] },
+                { "id": 4, "color": "red", "_vectors": [1.0, 0.0, 0.0] },
+                { "id": 5, "color": "red" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    // The vector is only supported by the POST route.
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.1], "attributesToRetrieve": ["id"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    // The documents without a vector of the same dimensions are left out.
+    let ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![1, 3, 2]);
+    assert_eq!(response["estimatedTotalHits"], 3);
+    assert!(response["hits"][0]["_semanticScore"].as_f64().unwrap() > 0.99);
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.1], "filter": "color = red", "limit": 1 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(response["hits"][0]["id"], 1);
+    assert_eq!(response["estimatedTotalHits"], 2);
+
+    let (response, code) = index.search_post(json!({})).await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["hits"][0].get("_semanticScore").is_none());
+}
+
+#[actix_rt::test]
+async fn vector_search_follows_document_updates() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "_vectors": [1.0, 0.0] },
+                { "id": 2, "_vectors": [0.0, 1.0] },
+                { "id": 3, "_vectors": [0.7, 0.7] },
+            ]),
+            None,
+        )
+        .await;
+    index
+        .add_documents(json!([{ "id": 2, "_vectors": [1.0, 0.1] }]), None)
+        .await;
+    // a partial update keeps the vectors of the document
+    index
+        .update_documents(json!([{ "id": 3, "color": "red" }]), None)
+        .await;
+    index.delete_document(1).await;
+    index.wait_task(3).await;
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.0], "attributesToRetrieve": ["id"] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![2, 3]);
+}
+
+#[actix_rt::test]
+async fn vector_search_invalid_vector() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "_vectors": [1.0, 0.0] }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let expected_response = json!({
+        "message": "The `vector` parameter must contain at least one dimension.",
+        "code": "invalid_search_vector",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_search_vector"
+    });
+    let (response, code) = index.search_post(json!({ "vector": [] })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response, expected_response);
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.0], "sort": ["id:asc"] }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_vector");
+}
+
+#[actix_rt::test]
+async fn hybrid_search() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(
+            json!([
+                { "id": 1, "title": "red shoes", "_vectors": [0.0, 1.0] },
+                { "id": 2, "title": "blue shoes", "_vectors": [1.0, 0.0] },
+                { "id": 3, "title": "red hat" },
+                { "id": 4, "title": "sneakers", "_vectors": [1.0, 0.1] },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_task(0).await;
+
+    let ids = |response: &serde_json::Value| -> Vec<u64> {
+        response["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].as_u64().unwrap())
+            .collect()
+    };
+
+    // Only the keyword ranking is used, the keyword matches come first.
+    let (response, code) = index
+        .search_post(
+            json!({ "q": "shoes", "vector": [1.0, 0.0], "hybrid": { "semanticRatio": 0.0 } }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response)[..2], [1, 2]);
+
+    // Only the similarity is used, the documents without keyword match are included.
+    let (response, code) = index
+        .search_post(
+            json!({ "q": "shoes", "vector": [1.0, 0.0], "hybrid": { "semanticRatio": 1.0 } }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![2, 4, 1]);
+    assert_eq!(response["estimatedTotalHits"], 3);
+
+    let (response, code) = index
+        .search_post(json!({ "q": "shoes", "vector": [1.0, 0.0], "hybrid": {} }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response)[0], 2);
+    assert!(response["hits"][0]["_hybridScore"].as_f64().is_some());
+    assert_eq!(response["hits"][0]["_semanticScore"], 1.0);
+}
+
+#[actix_rt::test]
+async fn hybrid_search_invalid_parameters() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "_vectors": [1.0, 0.0] }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .search_post(json!({ "hybrid": { "semanticRatio": 0.5 } }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_search_vector");
+
+    let expected_response = json!({
+        "message": "The `semanticRatio` must be a number between 0.0 and 1.0, found `1.5`.",
+        "code": "invalid_search_semantic_ratio",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_search_semantic_ratio"
+    });
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.0], "hybrid": { "semanticRatio": 1.5 } }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response, expected_response);
+}
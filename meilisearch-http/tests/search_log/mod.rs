@@ -0,0 +1,107 @@
+use serde_json::json;
+
+use crate::common::{default_settings, Server};
+
+#[actix_rt::test]
+async fn search_log_records_searches() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        search_log_size: Some(2),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "title": "shoes" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    index.search_post(json!({ "q": "jacket" })).await;
+    index.search_get(json!({ "q": "shoes" })).await;
+    let (response, code) = server
+        .service
+        .post(
+            "/multi-search",
+            json!([{ "indexUid": "test", "q": "shoes", "filter": "id = 1" }]),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    // Only the last two searches are kept, the most recent first.
+    let (response, code) = server.service.get("/analytics/searches").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["total"], 2);
+    let searches = response["results"].as_array().unwrap();
+    assert_eq!(searches[0]["indexUid"], "test");
+    assert_eq!(searches[0]["q"], "shoes");
+    assert_eq!(searches[0]["filter"], "id = 1");
+    assert_eq!(searches[0]["nbHits"], 1);
+    assert_eq!(searches[1]["q"], "shoes");
+    assert_eq!(searches[1]["filter"], json!(null));
+
+    let (response, code) = server.service.get("/analytics/searches?limit=1").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn search_log_disabled() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/analytics/searches").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "search_log_disabled");
+}
+
+#[actix_rt::test]
+async fn top_searches_and_no_results() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = meilisearch_http::Opt {
+        search_log_size: Some(20),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await.unwrap();
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "title": "shoes" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    for q in ["shoes", "Shoes ", "jacket", "shoes", "hat", "jacket"] {
+        index.search_post(json!({ "q": q })).await;
+    }
+    index.search_post(json!({ "q": "" })).await;
+
+    let (response, code) = server
+        .service
+        .get("/analytics/top-searches?indexUid=test&limit=2")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["results"],
+        json!([{ "q": "shoes", "count": 3 }, { "q": "jacket", "count": 2 }])
+    );
+
+    let (response, code) = server.service.get("/analytics/no-results").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["results"],
+        json!([{ "q": "jacket", "count": 2 }, { "q": "hat", "count": 1 }])
+    );
+
+    let (response, code) = server
+        .service
+        .get("/analytics/top-searches?indexUid=movies")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([]));
+
+    let (response, code) = server
+        .service
+        .get("/analytics/no-results?from=yesterday")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
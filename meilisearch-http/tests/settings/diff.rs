@@ -0,0 +1,86 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn diff_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "rankingRules": ["words", "typo"], "stopWords": ["the"] }))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/test/settings/diff",
+            json!({
+                "rankingRules": ["typo", "words"],
+                "filterableAttributes": ["genre"],
+                "stopWords": ["the"],
+            }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "changes": [
+                {
+                    "setting": "filterableAttributes",
+                    "current": [],
+                    "proposed": ["genre"],
+                    "reindex": true,
+                },
+                {
+                    "setting": "rankingRules",
+                    "current": ["words", "typo"],
+                    "proposed": ["typo", "words"],
+                    "reindex": false,
+                },
+            ],
+            "reindex": true,
+        })
+    );
+
+    // nothing was updated
+    let (response, _) = index.settings().await;
+    assert_eq!(response["rankingRules"], json!(["words", "typo"]));
+    assert_eq!(response["filterableAttributes"], json!([]));
+}
+
+#[actix_rt::test]
+async fn diff_settings_without_changes() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/test/settings/diff",
+            json!({ "stopWords": null, "distinctAttribute": null }),
+        )
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "changes": [], "reindex": false }));
+}
+
+#[actix_rt::test]
+async fn diff_settings_unexisting_index() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/test/settings/diff",
+            json!({ "stopWords": ["the"] }),
+        )
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
@@ -0,0 +1,74 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn export_and_import_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "rankingRules": ["typo", "words"],
+            "stopWords": ["the"],
+            "typoTolerance": { "enabled": false },
+        }))
+        .await;
+    index.wait_task(0).await;
+
+    let (export, code) = server
+        .service
+        .get("/indexes/test/settings?export=true")
+        .await;
+    assert_eq!(code, 200, "{}", export);
+    // the defaults are explicit
+    assert_eq!(export["expiresAtAttribute"], json!("_expiresAt"));
+    assert_eq!(export["displayedAttributes"], json!(["*"]));
+
+    let copy = server.index("copy");
+    let (response, code) = server
+        .service
+        .put("/indexes/copy/settings", export.clone())
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = copy.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = server
+        .service
+        .get("/indexes/copy/settings?export=true")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, export);
+}
+
+#[actix_rt::test]
+async fn import_resets_missing_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "stopWords": ["the"],
+            "typoTolerance": { "enabled": false, "disableOnWords": ["iphone"] },
+        }))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .put(
+            "/indexes/test/settings",
+            json!({ "typoTolerance": { "disableOnWords": ["ipad"] } }),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["stopWords"], json!([]));
+    assert_eq!(response["typoTolerance"]["enabled"], json!(true));
+    assert_eq!(response["typoTolerance"]["disableOnWords"], json!(["ipad"]));
+}
@@ -24,6 +24,23 @@ static DEFAULT_SETTINGS_VALUES: Lazy<HashMap<&'static str, Value>> = Lazy::new(|
     );
     map.insert("stop_words", json!([]));
     map.insert("synonyms", json!({}));
+    map.insert("search_cutoff_ms", json!(null));
+    map.insert("ranking_experiment", json!(null));
+    map.insert("expires_at_attribute", json!(null));
+    map.insert("document_schema", json!(null));
+    map.insert("ingestion_pipeline", json!([]));
+    map.insert(
+        "nested_flattening",
+        json!({
+            "maxDepth": null,
+            "arraysOfObjects": "flatten",
+            "jsonLeavesAsKeywords": true,
+        }),
+    );
+    map.insert(
+        "attachments",
+        json!({ "fields": {}, "allowedUrlPrefixes": [] }),
+    );
     map.insert(
         "faceting",
         json!({
@@ -49,7 +66,7 @@ async fn get_settings() {
     let (response, code) = index.settings().await;
     assert_eq!(code, 200);
     let settings = response.as_object().unwrap();
-    assert_eq!(settings.keys().len(), 11);
+    assert_eq!(settings.keys().len(), 19);
     assert_eq!(settings["displayedAttributes"], json!(["*"]));
     assert_eq!(settings["searchableAttributes"], json!(["*"]));
     assert_eq!(settings["filterableAttributes"], json!([]));
@@ -71,14 +88,29 @@ async fn get_settings() {
         settings["faceting"],
         json!({
             "maxValuesPerFacet": 100,
+            "sortFacetValuesBy": { "*": "alpha" },
         })
     );
     assert_eq!(
         settings["pagination"],
         json!({
-            "limitedTo": 1000,
+            "maxTotalHits": 1000,
         })
     );
+    assert_eq!(settings["searchCutoffMs"], json!(null));
+    assert_eq!(settings["rankingExperiment"], json!(null));
+    assert_eq!(settings["rules"], json!([]));
+    assert_eq!(settings["expiresAtAttribute"], json!(null));
+    assert_eq!(settings["documentSchema"], json!(null));
+    assert_eq!(settings["ingestionPipeline"], json!([]));
+    assert_eq!(
+        settings["nestedFlattening"],
+        json!({ "maxDepth": null, "arraysOfObjects": "flatten", "jsonLeavesAsKeywords": true })
+    );
+    assert_eq!(
+        settings["attachments"],
+        json!({ "fields": {}, "allowedUrlPrefixes": [] })
+    );
 }
 
 #[actix_rt::test]
@@ -174,6 +206,32 @@ async fn reset_all_settings() {
     assert!(response.as_object().unwrap().get("age").is_some());
 }
 
+#[actix_rt::test]
+async fn set_stop_words_preset() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "stopWords": ["preset:en", "foo"] }))
+        .await;
+    let response = index.wait_task(0).await;
+    assert_eq!(response["status"], "succeeded");
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200);
+    let stop_words = response["stopWords"].as_array().unwrap();
+    assert!(stop_words.contains(&json!("the")));
+    assert!(stop_words.contains(&json!("foo")));
+    assert!(!stop_words.contains(&json!("preset:en")));
+
+    index
+        .update_settings(json!({ "stopWords": ["preset:klingon"] }))
+        .await;
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["error"]["code"], "invalid_stop_words_preset");
+}
+
 #[actix_rt::test]
 async fn update_setting_unexisting_index() {
     let server = Server::new().await;
@@ -197,7 +255,7 @@ async fn error_update_setting_unexisting_index_invalid_uid() {
     assert_eq!(code, 400);
 
     let expected = json!({
-        "message": "invalid index uid `test##!  `, the uid must be an integer or a string containing only alphanumeric characters a-z A-Z 0-9, hyphens - and underscores _.",
+        "message": "invalid index uid `test##!  `, the uid must be an integer or a string containing only alphanumeric characters a-z A-Z 0-9, hyphens - and underscores _, optionally prefixed by a namespace and a slash /",
         "code": "invalid_index_uid",
         "type": "invalid_request",
         "link": "https://docs.meilisearch.com/errors#invalid_index_uid"});
@@ -282,7 +340,14 @@ test_setting_routes!(
     distinct_attribute,
     stop_words,
     ranking_rules,
-    synonyms
+    synonyms,
+    search_cutoff_ms,
+    ranking_experiment,
+    expires_at_attribute,
+    document_schema,
+    ingestion_pipeline,
+    nested_flattening,
+    attachments
 );
 
 #[actix_rt::test]
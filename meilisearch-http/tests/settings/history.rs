@@ -0,0 +1,66 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn rollback_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "rankingRules": ["words", "typo"] }))
+        .await;
+    index.wait_task(0).await;
+    index
+        .update_settings(json!({ "rankingRules": ["typo"], "stopWords": ["the"] }))
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = server.service.get("/indexes/test/settings/history").await;
+    assert_eq!(code, 200, "{}", response);
+    let history = response["results"].as_array().unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["version"], 1);
+    assert_eq!(
+        history[0]["settings"]["rankingRules"],
+        json!(["words", "typo"])
+    );
+    assert_eq!(history[1]["version"], 2);
+    assert_eq!(history[1]["settings"]["stopWords"], json!(["the"]));
+
+    let (response, code) = server
+        .service
+        .post("/indexes/test/settings/rollback?version=1", json!(null))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["type"], "settingsUpdate");
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["rankingRules"], json!(["words", "typo"]));
+    assert_eq!(response["stopWords"], json!([]));
+
+    // the rollback is itself a new version
+    let (response, _) = server.service.get("/indexes/test/settings/history").await;
+    assert_eq!(response["results"][2]["version"], 3);
+}
+
+#[actix_rt::test]
+async fn rollback_to_unknown_version() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "rankingRules": ["words"] }))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post("/indexes/test/settings/rollback?version=7", json!(null))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "settings_version_not_found");
+}
@@ -1,2 +1,7 @@
+mod diff;
 mod distinct;
+mod export;
 mod get_settings;
+mod history;
+mod pagination;
+mod synonyms;
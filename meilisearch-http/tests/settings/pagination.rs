@@ -0,0 +1,61 @@
+use crate::common::Server;
+use serde_json::json;
+
+#[actix_rt::test]
+async fn set_and_reset_max_total_hits_with_dedicated_route() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents: Vec<_> = (0..20).map(|i| json!({ "id": i })).collect();
+    index.add_documents(documents.into(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .patch(
+            "/indexes/test/settings/pagination",
+            json!({ "maxTotalHits": 5 }),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(1).await;
+
+    let (response, code) = server
+        .service
+        .get("/indexes/test/settings/pagination")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "maxTotalHits": 5 }));
+
+    // The value is enforced in the search.
+    index
+        .search(json!({ "limit": 10 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 5);
+        })
+        .await;
+
+    let (response, code) = server
+        .service
+        .delete("/indexes/test/settings/pagination")
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(2).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["pagination"], json!({ "maxTotalHits": 1000 }));
+}
+
+#[actix_rt::test]
+async fn set_max_total_hits_with_previous_name() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "pagination": { "limitedTo": 10 } }))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, _) = index.settings().await;
+    assert_eq!(response["pagination"], json!({ "maxTotalHits": 10 }));
+}
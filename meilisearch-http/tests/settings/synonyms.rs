@@ -0,0 +1,61 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn import_solr_synonyms() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "notebook sleeve" },
+        { "id": 2, "title": "laptop stand" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    let file = "# managed by the linguists\ncouch, sofa\nlaptop => notebook\n";
+    let (response, code) = server
+        .service
+        .post_str("/indexes/test/settings/synonyms/import", file)
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(1).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["synonyms"],
+        json!({ "couch": ["sofa"], "laptop": ["notebook"], "sofa": ["couch"] })
+    );
+
+    // The mapping is one way: `laptop` matches `notebook`, but not the reverse.
+    index
+        .search(json!({ "q": "laptop" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+        })
+        .await;
+    index
+        .search(json!({ "q": "notebook" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn import_malformed_synonyms() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post_str(
+            "/indexes/test/settings/synonyms/import",
+            "couch, sofa\nphone => \n",
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "malformed_payload");
+    assert!(response["message"].as_str().unwrap().contains("Line 2"));
+}
@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use byte_unit::{Byte, ByteUnit};
+
 use crate::common::server::default_settings;
 use crate::common::GetAllDocumentsOptions;
 use crate::common::Server;
@@ -81,3 +83,51 @@ async fn perform_snapshot() {
         server.index("test1").settings(),
     );
 }
+
+#[actix_rt::test]
+async fn perform_snapshot_with_separate_task_db() {
+    let temp = tempfile::tempdir().unwrap();
+    let task_db = tempfile::tempdir().unwrap();
+    let snapshot_dir = tempfile::tempdir().unwrap();
+
+    let options = Opt {
+        snapshot_dir: snapshot_dir.path().to_owned(),
+        snapshot_interval_sec: 1,
+        schedule_snapshot: true,
+        task_db_path: Some(task_db.path().to_owned()),
+        task_db_size: Some(Byte::from_unit(50.0, ByteUnit::MiB).unwrap()),
+        ..default_settings(temp.path())
+    };
+
+    let server = Server::new_with_options(options).await.unwrap();
+
+    let index = server.index("test");
+    index.load_test_set().await;
+    server.index("test1").create(Some("prim")).await;
+
+    index.wait_task(1).await;
+
+    assert!(task_db.path().join("data.mdb").exists());
+
+    sleep(Duration::from_secs(2)).await;
+
+    let temp = tempfile::tempdir().unwrap();
+    let task_db = tempfile::tempdir().unwrap();
+
+    let snapshot_path = snapshot_dir.path().to_owned().join("db.snapshot");
+
+    let options = Opt {
+        import_snapshot: Some(snapshot_path),
+        task_db_path: Some(task_db.path().to_owned()),
+        task_db_size: Some(Byte::from_unit(50.0, ByteUnit::MiB).unwrap()),
+        ..default_settings(temp.path())
+    };
+
+    let snapshot_server = Server::new_with_options(options).await.unwrap();
+
+    verify_snapshot!(server, snapshot_server, |server| =>
+        server.list_indexes(None, None),
+        server.tasks(),
+        server.index("test").get_all_documents(GetAllDocumentsOptions::default()),
+    );
+}
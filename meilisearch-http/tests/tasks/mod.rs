@@ -59,6 +59,55 @@ async fn list_tasks() {
     assert_eq!(response["results"].as_array().unwrap().len(), 2);
 }
 
+#[actix_rt::test]
+async fn list_tasks_counts() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+    index.add_documents(json!([{ "id": 2 }]), None).await;
+    index.wait_task(2).await;
+
+    // The counts cover all the pages.
+    let (response, code) = server.service.get("/tasks?limit=1").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["total"], 3);
+    assert_eq!(response["enqueued"], 0);
+
+    let (response, code) = server
+        .service
+        .get("/tasks?type=documentAdditionOrUpdate&limit=1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["total"], 2);
+}
+
+#[actix_rt::test]
+async fn list_tasks_of_namespace() {
+    let server = Server::new().await;
+    for (i, uid) in ["tenant-a/products", "tenant-a/users", "tenant-b/products"]
+        .iter()
+        .enumerate()
+    {
+        server.index(uid).create(None).await;
+        server.index(uid).wait_task(i as u64).await;
+    }
+
+    let (response, code) = server.service.get("/tasks?indexUid=tenant-a/*").await;
+    assert_eq!(code, 200, "{}", response);
+    let uids: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| task["indexUid"].clone())
+        .collect();
+    assert_eq!(uids, [json!("tenant-a/users"), json!("tenant-a/products")]);
+
+    let (response, code) = server.service.get("/tasks?indexUid=tenant-*").await;
+    assert_eq!(code, 400, "{}", response);
+}
+
 #[actix_rt::test]
 async fn list_tasks_with_star_filters() {
     let server = Server::new().await;
@@ -189,6 +238,245 @@ async fn list_tasks_status_and_type_filtered() {
     assert_eq!(response["results"].as_array().unwrap().len(), 2);
 }
 
+#[actix_rt::test]
+async fn list_tasks_duration_sorted_and_filtered() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index
+        .add_documents(
+            serde_json::from_str(include_str!("../assets/test_set.json")).unwrap(),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = server.service.get("/tasks?sort=duration:desc").await;
+    assert_eq!(code, 200, "{}", response);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(response["next"], json!(null));
+
+    // The sorted tasks can't be paginated.
+    let (response, code) = server.service.get("/tasks?sort=duration:desc&from=1").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+
+    // The longest duration is a valid lower bound for itself.
+    let longest = results[0]["duration"].as_str().unwrap();
+    let (response, code) = server
+        .service
+        .get(format!("/tasks?minDuration={}", longest))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let results = response["results"].as_array().unwrap();
+    assert!(results.iter().all(|t| t["duration"].is_string()));
+    assert!(!results.is_empty());
+
+    let (response, code) = server.service.get("/tasks?maxDuration=P1D").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+
+    let (response, code) = server.service.get("/tasks?minDuration=1s").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn list_tasks_details_filtered() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(Some("sku")).await;
+    index
+        .add_documents(json!([{ "sku": 1 }, { "sku": 2 }, { "sku": 3 }]), None)
+        .await;
+    index.add_documents(json!([{ "sku": 4 }]), None).await;
+    index.wait_task(2).await;
+
+    let uids = |response: &serde_json::Value| -> Vec<u64> {
+        response["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["uid"].as_u64().unwrap())
+            .collect()
+    };
+
+    // `>`, `<` and `=` are percent-encoded as `%3E`, `%3C` and `%3D`.
+    let (response, code) = server
+        .service
+        .get("/tasks?details=receivedDocuments%3E1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(uids(&response), vec![1]);
+
+    let (response, code) = server.service.get("/tasks?details=primaryKey%3Dsku").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(uids(&response), vec![0]);
+
+    let (response, code) = server
+        .service
+        .get("/tasks?details=receivedDocuments%3E%3D1,receivedDocuments%3C%3D1")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(uids(&response), vec![2]);
+
+    let (response, code) = server.service.get("/tasks?details=primaryKey%3Esku").await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn tasks_errors_summary() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.create(None).await;
+    index.create(None).await;
+    let other = server.index("other");
+    other.delete().await;
+    other.wait_task(3).await;
+
+    let (response, code) = server.service.get("/tasks/errors/summary").await;
+    assert_eq!(code, 200, "{}", response);
+
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2, "{}", response);
+
+    assert_eq!(results[0]["code"], "index_already_exists");
+    assert_eq!(results[0]["indexUid"], "test");
+    assert_eq!(results[0]["count"], 2);
+    assert_eq!(results[0]["latest"]["uid"], 2);
+
+    assert_eq!(results[1]["code"], "index_not_found");
+    assert_eq!(results[1]["indexUid"], "other");
+    assert_eq!(results[1]["count"], 1);
+    assert_eq!(results[1]["latest"]["uid"], 3);
+}
+
+#[actix_rt::test]
+async fn register_task_batch() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/tasks/batch",
+            json!([
+                { "type": "indexCreation", "indexUid": "test", "primaryKey": "id" },
+                { "type": "settingsUpdate", "indexUid": "test", "settings": { "filterableAttributes": ["color"] } },
+                { "type": "documentAddition", "indexUid": "test", "documents": [{ "id": 1, "color": "blue" }] },
+            ]),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let tasks = response.as_array().unwrap();
+    assert_eq!(tasks.len(), 3);
+    assert_eq!(tasks[0]["taskUid"], 0);
+    assert_eq!(tasks[0]["type"], "indexCreation");
+    assert_eq!(tasks[1]["taskUid"], 1);
+    assert_eq!(tasks[1]["type"], "settingsUpdate");
+    assert_eq!(tasks[2]["taskUid"], 2);
+    assert_eq!(tasks[2]["type"], "documentAdditionOrUpdate");
+
+    let index = server.index("test");
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["color"], "blue");
+}
+
+#[actix_rt::test]
+async fn register_task_batch_is_atomic() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/tasks/batch",
+            json!([
+                { "type": "indexCreation", "indexUid": "test" },
+                { "type": "indexCreation", "indexUid": "bad index uid" },
+            ]),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_index_uid");
+
+    let (response, code) = server.service.get("/tasks").await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["results"].as_array().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn register_barrier() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.add_documents(json!([{ "id": 1 }]), None).await;
+
+    let (response, code) = server
+        .service
+        .post("/tasks/barrier", json!({ "indexUids": ["test"] }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["taskUid"], 2);
+    assert_eq!(response["type"], "barrier");
+
+    index.add_documents(json!([{ "id": 2 }]), None).await;
+
+    let response = index.wait_task(3).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    // The barrier is processed once the tasks enqueued before it are finished, and before the
+    // tasks enqueued after it are started.
+    let date = |response: &serde_json::Value, field: &str| {
+        OffsetDateTime::parse(response[field].as_str().unwrap(), &Rfc3339).unwrap()
+    };
+    let (first_addition, _) = index.get_task(1).await;
+    let (barrier, _) = index.get_task(2).await;
+    let (second_addition, _) = index.get_task(3).await;
+    assert_eq!(barrier["status"], "succeeded", "{}", barrier);
+    assert_eq!(barrier["details"]["indexUids"], json!(["test"]));
+    assert!(date(&barrier, "startedAt") >= date(&first_addition, "finishedAt"));
+    assert!(date(&second_addition, "startedAt") >= date(&barrier, "finishedAt"));
+
+    let (response, code) = server.service.get("/tasks?type=barrier").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn register_barrier_bad_index_uid() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post("/tasks/barrier", json!({ "indexUids": ["bad index uid"] }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_index_uid");
+}
+
+#[actix_rt::test]
+async fn abort_task_not_processing() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.post("/tasks/0/abort", json!(null)).await;
+    assert_eq!(code, 409, "{}", response);
+    assert_eq!(response["code"], "task_not_processing");
+
+    let (response, code) = server.service.post("/tasks/1/abort", json!(null)).await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "task_not_found");
+}
+
 macro_rules! assert_valid_summarized_task {
     ($response:expr, $task_type:literal, $index:literal) => {{
         assert_eq!($response.as_object().unwrap().len(), 5);
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{error, info};
+use meilisearch_types::index_uid::IndexUid;
+use time::OffsetDateTime;
+use tokio::sync::{watch, RwLock};
+use tokio::task::spawn_blocking;
+use tokio::time::sleep;
+
+use crate::index_controller::DrainStatus;
+use crate::index_resolver::HardStateIndexResolver;
+use crate::tasks::task::{DocumentDeletion, TaskContent, TaskId};
+use crate::tasks::{Scheduler, TaskStore};
+
+/// Periodically enqueues the deletion of the documents whose expiration date is over. Only the
+/// open indexes are looked at, so that the indexes in use aren't closed to open the others: the
+/// expired documents of an index are deleted once it's opened again.
+pub struct DocumentExpirationService {
+    pub(crate) index_resolver: Arc<HardStateIndexResolver>,
+    pub(crate) task_store: TaskStore,
+    pub(crate) scheduler: Arc<RwLock<Scheduler>>,
+    pub(crate) drain_status: watch::Receiver<DrainStatus>,
+    pub(crate) interval: Duration,
+}
+
+impl DocumentExpirationService {
+    pub async fn run(self) {
+        info!(
+            "Expired documents will be deleted every {}s.",
+            self.interval.as_secs()
+        );
+        // The deletion task enqueued for each index, so the same documents aren't deleted twice
+        // while the task waits in the queue.
+        let mut pending = HashMap::new();
+        loop {
+            match self.expire_documents(&mut pending).await {
+                Ok(0) => (),
+                Ok(count) => info!("Enqueued the deletion of {} expired documents.", count),
+                Err(e) => error!("Error while deleting the expired documents: {}", e),
+            }
+            sleep(self.interval).await;
+        }
+    }
+
+    /// Enqueues a deletion task for each index having expired documents, and returns the number of
+    /// documents to delete.
+    async fn expire_documents(
+        &self,
+        pending: &mut HashMap<String, TaskId>,
+    ) -> anyhow::Result<usize> {
+        let now = OffsetDateTime::now_utc();
+        let mut count = 0;
+        for (uid, index) in self.index_resolver.list_open().await? {
+            if let Some(&task_id) = pending.get(&uid) {
+                match self.task_store.get_task(task_id, None).await {
+                    Ok(task) if !task.is_finished() => continue,
+                    // The task is finished, or was archived.
+                    _ => {
+                        pending.remove(&uid);
+                    }
+                }
+            }
+
+            // the documents of a frozen index don't expire
+            let ids = spawn_blocking(move || {
+                if index.is_frozen()? {
+                    return Ok(Vec::new());
+                }
+                index.expired_documents(now)
+            })
+            .await??;
+            if ids.is_empty() {
+                continue;
+            }
+            // no task is registered once the instance is drained
+            if *self.drain_status.borrow() != DrainStatus::Running {
+                break;
+            }
+
+            count += ids.len();
+            let content = TaskContent::DocumentDeletion {
+                index_uid: IndexUid::new_unchecked(&uid),
+                deletion: DocumentDeletion::Ids(ids),
+            };
+            let task = self.task_store.register(content).await?;
+            self.scheduler.read().await.notify();
+            pending.insert(uid, task.id);
+        }
+
+        Ok(count)
+    }
+}
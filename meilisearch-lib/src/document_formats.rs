@@ -1,18 +1,41 @@
 use std::borrow::Borrow;
 use std::fmt::{self, Debug, Display};
-use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Seek, Write};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
 
+#[cfg(feature = "columnar-payloads")]
+use arrow::error::Result as ArrowResult;
+#[cfg(feature = "columnar-payloads")]
+use arrow::ipc::reader::{FileReader, StreamReader};
+#[cfg(feature = "columnar-payloads")]
+use arrow::json::LineDelimitedWriter;
+#[cfg(feature = "columnar-payloads")]
+use arrow::record_batch::RecordBatch;
 use meilisearch_types::error::{Code, ErrorCode};
 use meilisearch_types::internal_error;
 use milli::documents::DocumentBatchBuilder;
+#[cfg(feature = "columnar-payloads")]
+use parquet::arrow::{ArrowReader, ParquetFileArrowReader};
+use quick_xml::events::{BytesStart, Event};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
 
 type Result<T> = std::result::Result<T, DocumentFormatError>;
 
-#[derive(Debug)]
+/// The number of rows of a columnar payload converted to documents at once.
+#[cfg(feature = "columnar-payloads")]
+const RECORD_BATCH_SIZE: usize = 1024;
+
+#[derive(Debug, Clone, Copy)]
 pub enum PayloadType {
     Ndjson,
     Json,
     Csv,
+    Parquet,
+    Arrow,
+    ArrowStream,
+    Xml,
 }
 
 impl fmt::Display for PayloadType {
@@ -21,6 +44,10 @@ impl fmt::Display for PayloadType {
             PayloadType::Ndjson => write!(f, "ndjson"),
             PayloadType::Json => write!(f, "json"),
             PayloadType::Csv => write!(f, "csv"),
+            PayloadType::Parquet => write!(f, "parquet"),
+            PayloadType::Arrow => write!(f, "arrow"),
+            PayloadType::ArrowStream => write!(f, "arrow stream"),
+            PayloadType::Xml => write!(f, "xml"),
         }
     }
 }
@@ -29,6 +56,22 @@ impl fmt::Display for PayloadType {
 pub enum DocumentFormatError {
     Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
     MalformedPayload(Box<milli::documents::Error>, PayloadType),
+    MalformedCsv(String),
+    /// A record of a ndjson payload is malformed, at the given line.
+    MalformedNdjson(usize, Box<milli::documents::Error>),
+    /// A parquet or arrow payload is malformed, or has a column that can't be converted to json.
+    MalformedColumnar(PayloadType, String),
+    MalformedXml(String),
+}
+
+/// https://github.com/meilisearch/meilisearch/issues/2107
+/// The user input maybe insanely long. We need to truncate it.
+fn truncate_serde_message(mut serde_msg: String) -> String {
+    let ellipsis = "...";
+    if serde_msg.len() > 100 + ellipsis.len() {
+        serde_msg.replace_range(50..serde_msg.len() - 85, ellipsis);
+    }
+    serde_msg
 }
 
 impl Display for DocumentFormatError {
@@ -37,22 +80,55 @@ impl Display for DocumentFormatError {
             Self::Internal(e) => write!(f, "An internal error has occurred: `{}`.", e),
             Self::MalformedPayload(me, b) => match me.borrow() {
                 milli::documents::Error::JsonError(se) => {
-                    // https://github.com/meilisearch/meilisearch/issues/2107
-                    // The user input maybe insanely long. We need to truncate it.
-                    let mut serde_msg = se.to_string();
-                    let ellipsis = "...";
-                    if serde_msg.len() > 100 + ellipsis.len() {
-                        serde_msg.replace_range(50..serde_msg.len() - 85, ellipsis);
-                    }
-
                     write!(
                         f,
                         "The `{}` payload provided is malformed. `Couldn't serialize document value: {}`.",
-                        b, serde_msg
+                        b, truncate_serde_message(se.to_string())
                 )
                 }
                 _ => write!(f, "The `{}` payload provided is malformed: `{}`.", b, me),
             },
+            Self::MalformedColumnar(ty, e) => {
+                write!(f, "The `{}` payload provided is malformed: `{}`.", ty, e)
+            }
+            Self::MalformedCsv(e) => write!(
+                f,
+                "The `{}` payload provided is malformed: `{}`.",
+                PayloadType::Csv,
+                e
+            ),
+            Self::MalformedXml(e) => write!(
+                f,
+                "The `{}` payload provided is malformed: `{}`.",
+                PayloadType::Xml,
+                e
+            ),
+            Self::MalformedNdjson(line, me) => match me.borrow() {
+                milli::documents::Error::JsonError(se) => {
+                    // The position reported by serde is relative to the record, only its column
+                    // is kept.
+                    let serde_msg = se.to_string();
+                    let position = format!(" at line {} column {}", se.line(), se.column());
+                    let serde_msg = match serde_msg.strip_suffix(&position) {
+                        Some(msg) => format!("{} at column {}", msg, se.column()),
+                        None => serde_msg,
+                    };
+                    write!(
+                        f,
+                        "The `{}` payload provided is malformed at line {}. `Couldn't serialize document value: {}`.",
+                        PayloadType::Ndjson,
+                        line,
+                        truncate_serde_message(serde_msg)
+                    )
+                }
+                _ => write!(
+                    f,
+                    "The `{}` payload provided is malformed at line {}: `{}`.",
+                    PayloadType::Ndjson,
+                    line,
+                    me
+                ),
+            },
         }
     }
 }
@@ -72,18 +148,145 @@ impl ErrorCode for DocumentFormatError {
     fn error_code(&self) -> Code {
         match self {
             DocumentFormatError::Internal(_) => Code::Internal,
-            DocumentFormatError::MalformedPayload(_, _) => Code::MalformedPayload,
+            DocumentFormatError::MalformedPayload(_, _)
+            | DocumentFormatError::MalformedCsv(_)
+            | DocumentFormatError::MalformedNdjson(_, _)
+            | DocumentFormatError::MalformedColumnar(_, _)
+            | DocumentFormatError::MalformedXml(_) => Code::MalformedPayload,
+        }
+    }
+}
+
+internal_error!(DocumentFormatError: io::Error, serde_json::Error);
+
+impl From<csv::Error> for DocumentFormatError {
+    fn from(error: csv::Error) -> Self {
+        match error.kind() {
+            csv::ErrorKind::Io(_) => Self::Internal(Box::new(error)),
+            _ => Self::MalformedCsv(error.to_string()),
         }
     }
 }
 
-internal_error!(DocumentFormatError: io::Error);
+/// The type of the values of a CSV column, annotated in its header after a colon, e.g.
+/// `price:number` or `tags:string[]`. The values of an array column are separated by commas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CsvType {
+    String,
+    Number,
+    Boolean,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CsvColumn {
+    name: String,
+    type_: CsvType,
+    is_array: bool,
+}
+
+impl CsvColumn {
+    /// The columns without a known type annotation are strings, named after their whole header.
+    fn from_header(header: &str) -> Self {
+        let column = |name: &str, type_, is_array| CsvColumn {
+            name: name.to_string(),
+            type_,
+            is_array,
+        };
 
-/// reads csv from input and write an obkv batch to writer.
-pub fn read_csv(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+        match header.rsplit_once(':') {
+            Some((name, annotation)) => {
+                let (annotation, is_array) = match annotation.strip_suffix("[]") {
+                    Some(annotation) => (annotation, true),
+                    None => (annotation, false),
+                };
+                match annotation {
+                    "string" => column(name, CsvType::String, is_array),
+                    "number" => column(name, CsvType::Number, is_array),
+                    "boolean" => column(name, CsvType::Boolean, is_array),
+                    _ => column(header, CsvType::String, false),
+                }
+            }
+            None => column(header, CsvType::String, false),
+        }
+    }
+
+    /// Converts a value of the column, an empty value being `null`.
+    fn parse(&self, value: &str) -> std::result::Result<Value, String> {
+        if self.is_array {
+            if value.trim().is_empty() {
+                return Ok(Value::Array(Vec::new()));
+            }
+            return value
+                .split(',')
+                .map(|value| self.parse_single(value.trim()))
+                .collect::<std::result::Result<_, _>>()
+                .map(Value::Array);
+        }
+
+        if value.is_empty() {
+            return Ok(Value::Null);
+        }
+        self.parse_single(value)
+    }
+
+    fn parse_single(&self, value: &str) -> std::result::Result<Value, String> {
+        let invalid = |type_| format!("`{}` is not a valid {}", value, type_);
+        match self.type_ {
+            CsvType::String => Ok(Value::String(value.to_string())),
+            CsvType::Number => {
+                let trimmed = value.trim();
+                if let Ok(number) = trimmed.parse::<i64>() {
+                    return Ok(Value::Number(number.into()));
+                }
+                trimmed
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(Number::from_f64)
+                    .map(Value::Number)
+                    .ok_or_else(|| invalid("number"))
+            }
+            CsvType::Boolean => match value.trim() {
+                v if v.eq_ignore_ascii_case("true") => Ok(Value::Bool(true)),
+                v if v.eq_ignore_ascii_case("false") => Ok(Value::Bool(false)),
+                _ => Err(invalid("boolean")),
+            },
+        }
+    }
+}
+
+/// reads csv from input, its values separated by the delimiter, and write an obkv batch to
+/// writer.
+pub fn read_csv(input: impl Read, writer: impl Write + Seek, delimiter: u8) -> Result<usize> {
     let writer = BufWriter::new(writer);
-    let builder =
-        DocumentBatchBuilder::from_csv(input, writer).map_err(|e| (PayloadType::Csv, e))?;
+    let mut builder = DocumentBatchBuilder::new(writer).map_err(|e| (PayloadType::Csv, e))?;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(input);
+    let columns: Vec<_> = reader
+        .headers()?
+        .iter()
+        .map(CsvColumn::from_header)
+        .collect();
+
+    let mut record = csv::StringRecord::new();
+    while reader.read_record(&mut record)? {
+        let mut document = Map::new();
+        for (column, value) in columns.iter().zip(record.iter()) {
+            let value = column.parse(value).map_err(|e| {
+                let line = record.position().map_or(0, |p| p.line());
+                DocumentFormatError::MalformedCsv(format!(
+                    "line {}, column `{}`: {}",
+                    line, column.name, e
+                ))
+            })?;
+            document.insert(column.name.clone(), value);
+        }
+
+        builder
+            .extend_from_json(Cursor::new(serde_json::to_vec(&document)?))
+            .map_err(|e| (PayloadType::Csv, e))?;
+    }
 
     let count = builder.finish().map_err(|e| (PayloadType::Csv, e))?;
 
@@ -97,8 +300,10 @@ pub fn read_ndjson(input: impl Read, writer: impl Write + Seek) -> Result<usize>
 
     let mut builder = DocumentBatchBuilder::new(writer).map_err(|e| (PayloadType::Ndjson, e))?;
     let mut buf = String::new();
+    let mut line = 0;
 
     while reader.read_line(&mut buf)? > 0 {
+        line += 1;
         // skip empty lines
         if buf == "\n" {
             buf.clear();
@@ -106,7 +311,10 @@ pub fn read_ndjson(input: impl Read, writer: impl Write + Seek) -> Result<usize>
         }
         builder
             .extend_from_json(Cursor::new(&buf.as_bytes()))
-            .map_err(|e| (PayloadType::Ndjson, e))?;
+            .map_err(|e| match e {
+                milli::documents::Error::Io(e) => DocumentFormatError::Internal(Box::new(e)),
+                e => DocumentFormatError::MalformedNdjson(line, Box::new(e)),
+            })?;
         buf.clear();
     }
 
@@ -127,3 +335,556 @@ pub fn read_json(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
 
     Ok(count)
 }
+
+/// reads json, or jsonl when `ndjson` is set, from input and write an obkv batch to writer. The
+/// documents are transformed by `transform`, along with their position, as they're read, without
+/// buffering the payload.
+pub fn read_json_transformed<E: From<DocumentFormatError>>(
+    input: impl Read,
+    writer: impl Write + Seek,
+    ndjson: bool,
+    mut transform: impl FnMut(usize, &mut Map<String, Value>) -> std::result::Result<(), E>,
+) -> std::result::Result<usize, E> {
+    let payload_type = if ndjson {
+        PayloadType::Ndjson
+    } else {
+        PayloadType::Json
+    };
+    let malformed = |e| DocumentFormatError::from((payload_type, e));
+    let writer = BufWriter::new(writer);
+    let mut builder = DocumentBatchBuilder::new(writer).map_err(malformed)?;
+    let mut position = 0;
+    let mut write = |mut document: Map<String, Value>| -> std::result::Result<(), E> {
+        transform(position, &mut document)?;
+        position += 1;
+        let document = serde_json::to_vec(&document).map_err(DocumentFormatError::from)?;
+        builder
+            .extend_from_json(Cursor::new(document))
+            .map_err(malformed)?;
+        Ok(())
+    };
+
+    if ndjson {
+        let mut reader = BufReader::new(input);
+        let mut buf = String::new();
+        let mut line = 0;
+        while reader
+            .read_line(&mut buf)
+            .map_err(DocumentFormatError::from)?
+            > 0
+        {
+            line += 1;
+            if !buf.trim().is_empty() {
+                let document = serde_json::from_str(&buf).map_err(|e| {
+                    let e = milli::documents::Error::JsonError(e);
+                    DocumentFormatError::MalformedNdjson(line, Box::new(e))
+                })?;
+                write(document)?;
+            }
+            buf.clear();
+        }
+    } else {
+        let mut deserializer = serde_json::Deserializer::from_reader(input);
+        let mut failure = None;
+        let visitor = DocumentsVisitor {
+            write: &mut write,
+            failure: &mut failure,
+        };
+        let result = deserializer
+            .deserialize_any(visitor)
+            .and_then(|()| deserializer.end());
+        // the error of the transformation prevails over the deserialization error it caused
+        if let Some(error) = failure {
+            return Err(error);
+        }
+        result.map_err(|e| malformed(milli::documents::Error::JsonError(e)))?;
+    }
+
+    let count = builder.finish().map_err(malformed)?;
+
+    Ok(count)
+}
+
+/// Writes the documents of a json payload, which is a document or an array of documents, as
+/// they're deserialized.
+struct DocumentsVisitor<'a, F, E> {
+    write: &'a mut F,
+    failure: &'a mut Option<E>,
+}
+
+impl<F, E> DocumentsVisitor<'_, F, E>
+where
+    F: FnMut(Map<String, Value>) -> std::result::Result<(), E>,
+{
+    fn write_document<Er: de::Error>(
+        &mut self,
+        document: Map<String, Value>,
+    ) -> std::result::Result<(), Er> {
+        (self.write)(document).map_err(|e| {
+            *self.failure = Some(e);
+            Er::custom("the document couldn't be written")
+        })
+    }
+}
+
+impl<'de, F, E> Visitor<'de> for DocumentsVisitor<'_, F, E>
+where
+    F: FnMut(Map<String, Value>) -> std::result::Result<(), E>,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a document or an array of documents")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(document) = seq.next_element()? {
+            self.write_document(document)?;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, map: A) -> std::result::Result<(), A::Error> {
+        let document = Map::deserialize(MapAccessDeserializer::new(map))?;
+        self.write_document(document)
+    }
+}
+
+/// reads parquet from input and write an obkv batch to writer. The payload is buffered in a
+/// temporary file first, since the metadata of a parquet file is at its end.
+#[cfg(feature = "columnar-payloads")]
+pub fn read_parquet(mut input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+    let mut file = tempfile::tempfile()?;
+    io::copy(&mut input, &mut file)?;
+
+    let malformed = |e: parquet::errors::ParquetError| {
+        DocumentFormatError::MalformedColumnar(PayloadType::Parquet, e.to_string())
+    };
+    let mut reader = ParquetFileArrowReader::try_new(file).map_err(malformed)?;
+    let batches = reader
+        .get_record_reader(RECORD_BATCH_SIZE)
+        .map_err(malformed)?;
+
+    write_record_batches(PayloadType::Parquet, batches, writer)
+}
+
+/// reads an arrow IPC file from input and write an obkv batch to writer. The payload is buffered
+/// in a temporary file first, since the footer of an arrow file is read first.
+#[cfg(feature = "columnar-payloads")]
+pub fn read_arrow(mut input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+    let mut file = tempfile::tempfile()?;
+    io::copy(&mut input, &mut file)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    let reader = FileReader::try_new(file, None)
+        .map_err(|e| DocumentFormatError::MalformedColumnar(PayloadType::Arrow, e.to_string()))?;
+
+    write_record_batches(PayloadType::Arrow, reader, writer)
+}
+
+/// reads an arrow IPC stream from input and write an obkv batch to writer.
+#[cfg(feature = "columnar-payloads")]
+pub fn read_arrow_stream(input: impl Read, writer: impl Write + Seek) -> Result<usize> {
+    let reader = StreamReader::try_new(input).map_err(|e| {
+        DocumentFormatError::MalformedColumnar(PayloadType::ArrowStream, e.to_string())
+    })?;
+
+    write_record_batches(PayloadType::ArrowStream, reader, writer)
+}
+
+/// reads xml from input and write an obkv batch to writer. Each element at the record path is a
+/// document, whose attributes and child elements are its fields. The values are strings: an
+/// element holding only text is the string of its text, the text of an element also having
+/// attributes or children is its `#text` field, and the children sharing a name are an array.
+pub fn read_xml(
+    input: impl Read,
+    writer: impl Write + Seek,
+    record_path: &[String],
+) -> Result<usize> {
+    let writer = BufWriter::new(writer);
+    let mut builder = DocumentBatchBuilder::new(writer).map_err(|e| (PayloadType::Xml, e))?;
+    let mut reader = quick_xml::Reader::from_reader(BufReader::new(input));
+    reader.trim_text(true);
+
+    // the names of the open elements outside of a record, and the open elements of the record
+    let mut path = Vec::new();
+    let mut elements: Vec<XmlElement> = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        let position = reader.buffer_position();
+        let event = reader
+            .read_event(&mut buf)
+            .map_err(|e| malformed_xml(e, position))?;
+        match event {
+            Event::Start(start) => {
+                let element = XmlElement::new(&reader, &start, position)?;
+                if elements.is_empty() {
+                    path.push(element.name.clone());
+                    if path != record_path {
+                        continue;
+                    }
+                }
+                elements.push(element);
+            }
+            Event::Empty(start) => {
+                let element = XmlElement::new(&reader, &start, position)?;
+                if let Some(parent) = elements.last_mut() {
+                    parent.insert(element);
+                } else if path.len() + 1 == record_path.len()
+                    && record_path.starts_with(&path)
+                    && record_path.last() == Some(&element.name)
+                {
+                    write_xml_record(&mut builder, element)?;
+                }
+            }
+            Event::End(_) => match elements.pop() {
+                Some(element) => match elements.last_mut() {
+                    Some(parent) => parent.insert(element),
+                    None => {
+                        path.pop();
+                        write_xml_record(&mut builder, element)?;
+                    }
+                },
+                None => {
+                    path.pop();
+                }
+            },
+            Event::Text(text) => {
+                if let Some(element) = elements.last_mut() {
+                    let text = text
+                        .unescape_and_decode(&reader)
+                        .map_err(|e| malformed_xml(e, position))?;
+                    element.push_text(&text);
+                }
+            }
+            Event::CData(cdata) => {
+                if let Some(element) = elements.last_mut() {
+                    let text = String::from_utf8(cdata.into_inner().into_owned())
+                        .map_err(|e| malformed_xml(e, position))?;
+                    element.push_text(&text);
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+
+    if !path.is_empty() {
+        return Err(malformed_xml(
+            format!("The element `{}` isn't closed", path.join("/")),
+            reader.buffer_position(),
+        ));
+    }
+
+    let count = builder.finish().map_err(|e| (PayloadType::Xml, e))?;
+
+    Ok(count)
+}
+
+fn malformed_xml(error: impl Display, position: usize) -> DocumentFormatError {
+    DocumentFormatError::MalformedXml(format!("{} at byte {}", error, position))
+}
+
+fn write_xml_record(
+    builder: &mut DocumentBatchBuilder<impl Write + Seek>,
+    element: XmlElement,
+) -> Result<()> {
+    let document = match element.into_value() {
+        Value::Object(document) => document,
+        // a record without attributes nor children is an empty document
+        _ => Map::new(),
+    };
+    builder
+        .extend_from_json(Cursor::new(serde_json::to_vec(&document)?))
+        .map_err(|e| (PayloadType::Xml, e))?;
+
+    Ok(())
+}
+
+/// An element of an xml record being read.
+struct XmlElement {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+impl XmlElement {
+    fn new<R: BufRead>(
+        reader: &quick_xml::Reader<R>,
+        start: &BytesStart,
+        position: usize,
+    ) -> Result<Self> {
+        let mut element = XmlElement {
+            name: String::from_utf8_lossy(start.name()).into_owned(),
+            fields: Map::new(),
+            text: String::new(),
+        };
+        for attribute in start.attributes() {
+            let attribute = attribute.map_err(|e| malformed_xml(e, position))?;
+            let value = attribute
+                .unescape_and_decode_value(reader)
+                .map_err(|e| malformed_xml(e, position))?;
+            let name = String::from_utf8_lossy(attribute.key).into_owned();
+            element.insert_field(name, Value::String(value));
+        }
+
+        Ok(element)
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if !self.text.is_empty() {
+            self.text.push(' ');
+        }
+        self.text.push_str(text);
+    }
+
+    fn insert(&mut self, child: XmlElement) {
+        let name = child.name.clone();
+        self.insert_field(name, child.into_value());
+    }
+
+    /// The values of a field are never arrays, so a field already holding an array is repeated.
+    fn insert_field(&mut self, name: String, value: Value) {
+        match self.fields.get_mut(&name) {
+            Some(Value::Array(values)) => values.push(value),
+            Some(previous) => *previous = Value::Array(vec![previous.take(), value]),
+            None => {
+                self.fields.insert(name, value);
+            }
+        }
+    }
+
+    fn into_value(mut self) -> Value {
+        if self.fields.is_empty() {
+            return Value::String(self.text);
+        }
+        if !self.text.is_empty() {
+            self.fields
+                .insert("#text".to_string(), Value::String(self.text));
+        }
+
+        Value::Object(self.fields)
+    }
+}
+
+/// Converts each row of the record batches to a document, the null values being left out.
+#[cfg(feature = "columnar-payloads")]
+fn write_record_batches(
+    ty: PayloadType,
+    batches: impl Iterator<Item = ArrowResult<RecordBatch>>,
+    writer: impl Write + Seek,
+) -> Result<usize> {
+    let writer = BufWriter::new(writer);
+    let mut builder = DocumentBatchBuilder::new(writer).map_err(|e| (ty, e))?;
+    let malformed =
+        |e: arrow::error::ArrowError| DocumentFormatError::MalformedColumnar(ty, e.to_string());
+
+    let mut rows = Vec::new();
+    for batch in batches {
+        let batch = batch.map_err(malformed)?;
+
+        rows.clear();
+        let mut json_writer = LineDelimitedWriter::new(&mut rows);
+        json_writer.write_batches(&[batch]).map_err(malformed)?;
+        json_writer.finish().map_err(malformed)?;
+
+        for row in rows.split(|b| *b == b'\n').filter(|row| !row.is_empty()) {
+            builder
+                .extend_from_json(Cursor::new(row))
+                .map_err(|e| (ty, e))?;
+        }
+    }
+
+    let count = builder.finish().map_err(|e| (ty, e))?;
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+    #[cfg(feature = "columnar-payloads")]
+    use std::sync::Arc;
+
+    #[cfg(feature = "columnar-payloads")]
+    use arrow::array::{Float64Array, Int64Array, StringArray};
+    #[cfg(feature = "columnar-payloads")]
+    use arrow::datatypes::{DataType, Field, Schema};
+    #[cfg(feature = "columnar-payloads")]
+    use arrow::ipc::writer::StreamWriter;
+    use milli::documents::DocumentBatchReader;
+    #[cfg(feature = "columnar-payloads")]
+    use parquet::arrow::ArrowWriter;
+    use serde_json::json;
+
+    use super::*;
+
+    #[cfg(feature = "columnar-payloads")]
+    fn record_batch() -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("title", DataType::Utf8, true),
+            Field::new("price", DataType::Float64, true),
+        ]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(StringArray::from(vec![Some("Hamlet"), None])),
+                Arc::new(Float64Array::from(vec![Some(9.5), Some(12.0)])),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn documents(file: &mut File) -> Vec<Value> {
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut reader = DocumentBatchReader::from_reader(&*file).unwrap();
+        let mut documents = Vec::new();
+        while let Some((index, obkv)) = reader.next_document_with_index().unwrap() {
+            let mut document = Map::new();
+            for (field_id, content) in obkv.iter() {
+                let value = serde_json::from_slice(content).unwrap();
+                document.insert(index.name(field_id).unwrap().to_string(), value);
+            }
+            documents.push(Value::Object(document));
+        }
+        documents
+    }
+
+    #[test]
+    #[cfg(feature = "columnar-payloads")]
+    fn test_read_columnar_payloads() {
+        let batch = record_batch();
+        let expected = vec![
+            json!({ "id": 1, "title": "Hamlet", "price": 9.5 }),
+            json!({ "id": 2, "price": 12.0 }),
+        ];
+
+        let mut parquet = tempfile::tempfile().unwrap();
+        let mut writer =
+            ArrowWriter::try_new(parquet.try_clone().unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        parquet.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut file = tempfile::tempfile().unwrap();
+        assert_eq!(read_parquet(parquet, &mut file).unwrap(), 2);
+        assert_eq!(documents(&mut file), expected);
+
+        let mut stream = Vec::new();
+        let mut writer = StreamWriter::try_new(&mut stream, &batch.schema()).unwrap();
+        writer.write(&batch).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        let mut file = tempfile::tempfile().unwrap();
+        assert_eq!(
+            read_arrow_stream(Cursor::new(&stream), &mut file).unwrap(),
+            2
+        );
+        assert_eq!(documents(&mut file), expected);
+
+        let mut file = tempfile::tempfile().unwrap();
+        let error = read_parquet(Cursor::new(stream), &mut file).unwrap_err();
+        assert!(matches!(
+            error,
+            DocumentFormatError::MalformedColumnar(PayloadType::Parquet, _)
+        ));
+    }
+
+    #[test]
+    fn test_read_json_transformed() {
+        let mark = |position: usize, document: &mut Map<String, Value>| {
+            document.insert("position".to_string(), json!(position));
+            Ok::<_, DocumentFormatError>(())
+        };
+
+        let json = r#"[{ "id": 1 }, { "id": 2 }]"#;
+        let mut file = tempfile::tempfile().unwrap();
+        assert_eq!(
+            read_json_transformed(json.as_bytes(), &mut file, false, mark).unwrap(),
+            2
+        );
+        assert_eq!(
+            documents(&mut file),
+            vec![
+                json!({ "id": 1, "position": 0 }),
+                json!({ "id": 2, "position": 1 })
+            ]
+        );
+
+        let ndjson = "{ \"id\": 1 }\n\n{ \"id\": 2 }\n";
+        let mut file = tempfile::tempfile().unwrap();
+        assert_eq!(
+            read_json_transformed(ndjson.as_bytes(), &mut file, true, mark).unwrap(),
+            2
+        );
+        assert_eq!(documents(&mut file)[1], json!({ "id": 2, "position": 1 }));
+
+        // the error of the transformation is returned rather than a malformed payload
+        let fail = |position: usize, _: &mut Map<String, Value>| match position {
+            0 => Ok(()),
+            _ => Err(DocumentFormatError::MalformedCsv(
+                "transformation".to_string(),
+            )),
+        };
+        let mut file = tempfile::tempfile().unwrap();
+        assert!(matches!(
+            read_json_transformed(json.as_bytes(), &mut file, false, fail),
+            Err(DocumentFormatError::MalformedCsv(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_xml() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <catalog>
+                <products>
+                    <product id="1">
+                        <title>Hamlet &amp; Othello</title>
+                        <tag>drama</tag>
+                        <tag>classic</tag>
+                        <price currency="EUR">9.5</price>
+                        <summary><![CDATA[<b>Two</b> plays]]></summary>
+                    </product>
+                    <product id="2"/>
+                </products>
+                <product id="3"/>
+            </catalog>"#;
+        let record_path = [
+            "catalog".to_string(),
+            "products".to_string(),
+            "product".to_string(),
+        ];
+
+        let mut file = tempfile::tempfile().unwrap();
+        assert_eq!(
+            read_xml(xml.as_bytes(), &mut file, &record_path).unwrap(),
+            2
+        );
+        assert_eq!(
+            documents(&mut file),
+            vec![
+                json!({
+                    "id": "1",
+                    "title": "Hamlet & Othello",
+                    "tag": ["drama", "classic"],
+                    "price": { "currency": "EUR", "#text": "9.5" },
+                    "summary": "<b>Two</b> plays",
+                }),
+                json!({ "id": "2" }),
+            ]
+        );
+
+        let mut file = tempfile::tempfile().unwrap();
+        let error = read_xml(
+            &b"<catalog><products></catalog>"[..],
+            &mut file,
+            &record_path,
+        )
+        .unwrap_err();
+        assert!(matches!(error, DocumentFormatError::MalformedXml(_)));
+    }
+}
@@ -1,3 +1,4 @@
+use meilisearch_auth::Quota;
 use meilisearch_types::error::ResponseError;
 use meilisearch_types::index_uid::IndexUid;
 use milli::update::IndexDocumentsMethod;
@@ -5,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::index::{Settings, Unchecked};
+use crate::index::{OnDuplicate, Settings, Unchecked};
 use crate::tasks::batch::BatchId;
 use crate::tasks::task::{
     DocumentDeletion, TaskContent as NewTaskContent, TaskEvent as NewTaskEvent, TaskId, TaskResult,
@@ -115,6 +116,10 @@ impl From<(IndexUid, TaskContent)> for NewTaskContent {
                 primary_key,
                 documents_count,
                 allow_index_creation,
+                quota: Quota::default(),
+                auto_generate_primary_key: false,
+                merge_nested: false,
+                on_duplicate: OnDuplicate::Replace,
             },
             TaskContent::DocumentDeletion(deletion) => NewTaskContent::DocumentDeletion {
                 index_uid,
@@ -129,15 +134,19 @@ impl From<(IndexUid, TaskContent)> for NewTaskContent {
                 settings,
                 is_deletion,
                 allow_index_creation,
+                quota: Quota::default(),
             },
             TaskContent::IndexDeletion => NewTaskContent::IndexDeletion { index_uid },
             TaskContent::IndexCreation { primary_key } => NewTaskContent::IndexCreation {
                 index_uid,
                 primary_key,
+                quota: Quota::default(),
             },
             TaskContent::IndexUpdate { primary_key } => NewTaskContent::IndexUpdate {
                 index_uid,
                 primary_key,
+                max_size: None,
+                frozen: None,
             },
             TaskContent::Dump { uid } => NewTaskContent::Dump { uid },
         }
@@ -1,8 +1,12 @@
 use std::error::Error;
 use std::fmt;
 
-use meilisearch_types::error::{Code, ErrorCode};
+use meilisearch_types::error::{Code, ErrorCode, ErrorDetails};
 use milli::UserError;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::index::edit_distance;
 
 #[derive(Debug)]
 pub struct MilliError<'a>(pub &'a milli::Error);
@@ -48,4 +52,79 @@ impl ErrorCode for MilliError<'_> {
             }
         }
     }
+
+    fn error_details(&self) -> Option<ErrorDetails> {
+        match self.0 {
+            milli::Error::UserError(UserError::InvalidFilter(ref message)) => {
+                filter_error_details(message)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The filter errors of milli end with the 1-based span of the erroneous part, the end excluded,
+/// followed by the filter expression the span refers to, e.g. `1:5 many = Glass`. With the array
+/// syntax, the expression is the element of the array holding the error.
+fn filter_error_details(message: &str) -> Option<ErrorDetails> {
+    static NOT_FILTERABLE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"^Attribute `(.*)` is not filterable\. Available filterable attributes are: `(.*)`\.$",
+        )
+        .unwrap()
+    });
+
+    let (description, location) = message.rsplit_once('\n')?;
+    let (span, _expression) = location.split_once(' ')?;
+    let (start, end) = span.split_once(':')?;
+    let start = start.parse::<usize>().ok()?.checked_sub(1)?;
+    let end = end.parse::<usize>().ok()?.checked_sub(1)?;
+
+    let suggestion = NOT_FILTERABLE.captures(description).and_then(|captures| {
+        let attribute = &captures[1];
+        closest_attribute(attribute, captures[2].split(", ").filter(|a| !a.is_empty()))
+    });
+
+    Some(ErrorDetails {
+        start,
+        end,
+        suggestion,
+    })
+}
+
+/// The attribute with the closest name, when it's likely to be a misspelling of the name.
+fn closest_attribute<'a>(
+    attribute: &str,
+    attributes: impl Iterator<Item = &'a str>,
+) -> Option<String> {
+    let max_distance = (attribute.chars().count() / 3).max(1);
+    attributes
+        .map(|candidate| (edit_distance(attribute, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_filter_error_details() {
+        let details = filter_error_details(
+            "Attribute `prices` is not filterable. Available filterable attributes are: `brand, price`.\n15:21 brand = a AND prices > 2",
+        )
+        .unwrap();
+        assert_eq!((details.start, details.end), (14, 20));
+        assert_eq!(details.suggestion.as_deref(), Some("price"));
+
+        let details = filter_error_details(
+            "Attribute `many` is not filterable. Available filterable attributes are: `title`.\n1:5 many = Glass",
+        )
+        .unwrap();
+        assert_eq!((details.start, details.end), (0, 4));
+        assert!(details.suggestion.is_none());
+
+        assert!(filter_error_details("Invalid filter.").is_none());
+    }
 }
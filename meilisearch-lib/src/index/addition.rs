@@ -0,0 +1,685 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{Cursor, Seek, SeekFrom};
+
+use jsonschema::JSONSchema;
+use meilisearch_auth::ACL_FIELD;
+use meilisearch_types::error::Code;
+use milli::documents::{DocumentBatchBuilder, DocumentBatchReader};
+use milli::heed::RoTxn;
+use milli::{obkv_to_json, ExternalDocumentsIds, FieldId, FieldsIdsMap};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use uuid::Uuid;
+
+use super::error::Result;
+
+/// The maximum number of document errors reported for the payload of a document addition, the
+/// documents failing after it are left out without being reported.
+pub const MAX_DOCUMENT_ERRORS: usize = 100;
+
+/// The operators of the fields of a document update, adding or subtracting their operand to the
+/// stored value of the field.
+const INCREMENT_OPERATOR: &str = "_inc";
+const DECREMENT_OPERATOR: &str = "_dec";
+
+/// The maximum length, in bytes, of a document id.
+const MAX_DOCUMENT_ID_LENGTH: usize = 512;
+
+/// A document of the payload of a document addition that couldn't be indexed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentError {
+    /// The position of the document in the payload.
+    pub index: usize,
+    /// The id of the document, when it has a valid one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_id: Option<String>,
+    pub code: String,
+    pub message: String,
+}
+
+impl DocumentError {
+    fn new(index: usize, document_id: Option<String>, message: String, code: Code) -> Self {
+        Self {
+            index,
+            document_id,
+            code: code.name(),
+            message,
+        }
+    }
+}
+
+/// What happens to a document of an addition whose id is already used by a stored document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub enum OnDuplicate {
+    /// The document replaces, or updates, the stored document.
+    Replace,
+    /// The document is left out, and the stored document is kept.
+    Skip,
+    /// The document is left out and reported as failed, and the stored document is kept.
+    Fail,
+}
+
+impl Default for OnDuplicate {
+    fn default() -> Self {
+        Self::Replace
+    }
+}
+
+/// A payload of a document addition, along with its options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DocumentPayload {
+    pub content_uuid: Uuid,
+    /// Whether the documents missing the primary key are given a generated one.
+    pub auto_generate_primary_key: bool,
+    /// Whether the nested objects of the documents are merged into the ones of the stored
+    /// documents, rather than replacing them.
+    pub merge_nested: bool,
+    pub on_duplicate: OnDuplicate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentAdditionOutcome {
+    pub indexed_documents: u64,
+    /// The errors of the documents left out of the addition, for each payload, in the order of
+    /// the payloads.
+    pub failed_documents: Vec<Vec<DocumentError>>,
+}
+
+/// The primary key milli infers for a payload: the first of its fields containing `id`.
+pub fn infer_primary_key(file: File) -> Result<Option<String>> {
+    let mut reader = DocumentBatchReader::from_reader(file)?;
+    let primary_key = match reader.next_document_with_index()? {
+        Some((index, _)) => {
+            let mut fields: Vec<_> = index.iter().collect();
+            fields.sort_unstable_by_key(|(id, _)| **id);
+            fields
+                .into_iter()
+                .map(|(_, name)| name)
+                .find(|name| name.to_lowercase().contains("id"))
+                .cloned()
+        }
+        None => None,
+    };
+    Ok(primary_key)
+}
+
+/// Checks the primary key of each document of the payload, and its `_geo` field when
+/// `check_geo` is set. The documents missing the primary key are given a UUIDv4 one when
+/// `generate_ids` is set. Returns the payload without the documents failing the checks, along
+/// with their errors, or `None` when every document passes them and none was given an id.
+pub fn remove_invalid_documents(
+    file: &mut File,
+    primary_key: &str,
+    check_geo: bool,
+    generate_ids: bool,
+) -> Result<Option<(File, Vec<DocumentError>)>> {
+    let mut invalid = BTreeSet::new();
+    let mut missing_id = BTreeSet::new();
+    let mut errors = Vec::new();
+    for_each_document(file, |position, mut document| {
+        let generate_id = generate_ids && !document.contains_key(primary_key);
+        if generate_id {
+            missing_id.insert(position);
+            document.insert(primary_key.to_string(), generated_id());
+        }
+        if let Some(mut error) = check_document(position, &document, primary_key, check_geo) {
+            // The id the document was given for the checks is never stored.
+            if generate_id {
+                error.document_id = None;
+            }
+            invalid.insert(position);
+            if errors.len() < MAX_DOCUMENT_ERRORS {
+                errors.push(error);
+            }
+        }
+        Ok(())
+    })?;
+
+    if invalid.is_empty() && missing_id.is_empty() {
+        file.seek(SeekFrom::Start(0))?;
+        return Ok(None);
+    }
+
+    let mut valid_file = tempfile::tempfile()?;
+    let mut builder = DocumentBatchBuilder::new(&mut valid_file)?;
+    for_each_document(file, |position, mut document| {
+        if missing_id.contains(&position) {
+            document.insert(primary_key.to_string(), generated_id());
+        }
+        if !invalid.contains(&position) {
+            builder.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+        }
+        Ok(())
+    })?;
+    builder.finish()?;
+    valid_file.seek(SeekFrom::Start(0))?;
+
+    Ok(Some((valid_file, errors)))
+}
+
+/// Validates each document of the payload against the document schema of the index. Returns the
+/// payload without the documents violating it, along with their errors, or `None` when every
+/// document matches it.
+pub fn remove_schema_violations(
+    file: &mut File,
+    schema: &JSONSchema,
+    primary_key: &str,
+) -> Result<Option<(File, Vec<DocumentError>)>> {
+    let mut invalid = BTreeSet::new();
+    let mut errors = Vec::new();
+    for_each_document(file, |position, document| {
+        let document = Value::Object(document);
+        let message = match schema.validate(&document) {
+            Ok(()) => return Ok(()),
+            Err(mut violations) => match violations.next() {
+                Some(violation) if violation.instance_path.to_string().is_empty() => format!(
+                    "The document doesn't match the schema of the index: {}.",
+                    violation
+                ),
+                Some(violation) => format!(
+                    "The document doesn't match the schema of the index at `{}`: {}.",
+                    violation.instance_path, violation
+                ),
+                None => return Ok(()),
+            },
+        };
+
+        invalid.insert(position);
+        if errors.len() < MAX_DOCUMENT_ERRORS {
+            errors.push(DocumentError::new(
+                position,
+                document.get(primary_key).and_then(valid_document_id),
+                message,
+                Code::DocumentSchemaViolation,
+            ));
+        }
+        Ok(())
+    })?;
+
+    if invalid.is_empty() {
+        file.seek(SeekFrom::Start(0))?;
+        return Ok(None);
+    }
+
+    let mut valid_file = tempfile::tempfile()?;
+    let mut builder = DocumentBatchBuilder::new(&mut valid_file)?;
+    for_each_document(file, |position, document| {
+        if !invalid.contains(&position) {
+            builder.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+        }
+        Ok(())
+    })?;
+    builder.finish()?;
+    valid_file.seek(SeekFrom::Start(0))?;
+
+    Ok(Some((valid_file, errors)))
+}
+
+/// Leaves out the documents of the payload whose id is used by a stored document, reporting them as
+/// failed when `on_duplicate` is `Fail`. Returns the payload without them, along with their
+/// errors, or `None` when no document is left out.
+pub fn remove_duplicate_documents(
+    index: &milli::Index,
+    txn: &RoTxn,
+    file: &mut File,
+    primary_key: &str,
+    on_duplicate: OnDuplicate,
+) -> Result<Option<(File, Vec<DocumentError>)>> {
+    let external_documents_ids = index.external_documents_ids(txn)?;
+    let mut duplicates = BTreeSet::new();
+    let mut errors = Vec::new();
+    for_each_document(file, |position, document| {
+        // The documents were checked beforehand, they all have a valid id.
+        let document_id = match document.get(primary_key).and_then(valid_document_id) {
+            Some(document_id) => document_id,
+            None => return Ok(()),
+        };
+        if external_documents_ids.get(document_id.as_bytes()).is_none() {
+            return Ok(());
+        }
+
+        duplicates.insert(position);
+        if on_duplicate == OnDuplicate::Fail && errors.len() < MAX_DOCUMENT_ERRORS {
+            errors.push(DocumentError::new(
+                position,
+                Some(document_id.clone()),
+                format!("A document with the id `{}` already exists.", document_id),
+                Code::DocumentAlreadyExists,
+            ));
+        }
+        Ok(())
+    })?;
+
+    if duplicates.is_empty() {
+        file.seek(SeekFrom::Start(0))?;
+        return Ok(None);
+    }
+
+    let mut kept_file = tempfile::tempfile()?;
+    let mut builder = DocumentBatchBuilder::new(&mut kept_file)?;
+    for_each_document(file, |position, document| {
+        if !duplicates.contains(&position) {
+            builder.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+        }
+        Ok(())
+    })?;
+    builder.finish()?;
+    kept_file.seek(SeekFrom::Start(0))?;
+
+    Ok(Some((kept_file, errors)))
+}
+
+/// Merges the nested objects of each document of the payload into the ones of the stored document
+/// with the same id, so updating a nested object doesn't drop the keys it doesn't set.
+pub fn merge_nested_documents(
+    index: &milli::Index,
+    txn: &RoTxn,
+    file: &mut File,
+    primary_key: &str,
+) -> Result<File> {
+    let stored_documents = StoredDocuments::new(index, txn)?;
+
+    let mut merged_file = tempfile::tempfile()?;
+    let mut builder = DocumentBatchBuilder::new(&mut merged_file)?;
+    for_each_document(file, |_, mut document| {
+        let stored = match document.get(primary_key).and_then(valid_document_id) {
+            Some(document_id) => stored_documents.get(txn, &document_id)?,
+            None => None,
+        };
+        if let Some(stored) = stored {
+            merge_nested(&mut document, stored);
+        }
+        builder.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+        Ok(())
+    })?;
+    builder.finish()?;
+    merged_file.seek(SeekFrom::Start(0))?;
+
+    Ok(merged_file)
+}
+
+/// Merges the objects of `document` into the ones of `stored`, recursively. The other values of
+/// `document` replace the ones of `stored`.
+fn merge_nested(document: &mut Map<String, Value>, stored: Map<String, Value>) {
+    for (key, stored_value) in stored {
+        match (document.get_mut(&key), stored_value) {
+            (Some(Value::Object(object)), Value::Object(stored_object)) => {
+                merge_nested(object, stored_object)
+            }
+            (Some(_), _) => (),
+            (None, stored_value) => {
+                document.insert(key, stored_value);
+            }
+        }
+    }
+}
+
+/// Whether a document of the payload uses an update operator.
+pub fn has_update_operators(file: &mut File) -> Result<bool> {
+    let mut has_operators = false;
+    for_each_document(file, |_, document| {
+        has_operators |= document.values().any(|value| operator(value).is_some());
+        Ok(())
+    })?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(has_operators)
+}
+
+/// Applies the `_inc` and `_dec` operators of the fields of the documents of the payload, e.g.
+/// `{ "stock": { "_inc": -1 } }`, to the values of the stored documents, or of the previous
+/// documents of the payload with the same id, a missing value counting as 0. Returns the payload
+/// with the operators replaced by their result, without the documents whose operators couldn't
+/// be applied, along with their errors.
+pub fn apply_update_operators(
+    index: &milli::Index,
+    txn: &RoTxn,
+    file: &mut File,
+    primary_key: &str,
+) -> Result<(File, Vec<DocumentError>)> {
+    let stored_documents = StoredDocuments::new(index, txn)?;
+    // The fields set by the previous documents of the payload, by document id.
+    let mut updated: HashMap<String, Map<String, Value>> = HashMap::new();
+    let mut errors = Vec::new();
+    let mut applied_file = tempfile::tempfile()?;
+    let mut builder = DocumentBatchBuilder::new(&mut applied_file)?;
+    for_each_document(file, |position, mut document| {
+        // The documents were checked beforehand, they all have a valid id.
+        let document_id = document
+            .get(primary_key)
+            .and_then(valid_document_id)
+            .unwrap_or_default();
+        let operations: Vec<_> = document
+            .iter()
+            .filter_map(|(field, value)| Some((field.clone(), operator(value)?)))
+            .map(|(field, (name, operand))| (field, name.to_string(), operand.clone()))
+            .collect();
+
+        let stored = if operations.is_empty() {
+            None
+        } else {
+            stored_documents.get(txn, &document_id)?
+        };
+        let previous = updated.entry(document_id.clone()).or_default();
+
+        let mut results = Vec::with_capacity(operations.len());
+        for (field, name, operand) in operations {
+            let current = previous
+                .get(&field)
+                .or_else(|| stored.as_ref()?.get(&field))
+                .cloned()
+                .unwrap_or_else(|| Value::from(0));
+            match apply_operator(&name, &current, &operand) {
+                Some(result) => results.push((field, result)),
+                None => {
+                    if errors.len() < MAX_DOCUMENT_ERRORS {
+                        errors.push(DocumentError::new(
+                            position,
+                            Some(document_id),
+                            format!(
+                                "The `{}` operator of the `{}` field can't be applied: `{}` and `{}` must be numbers.",
+                                name, field, current, operand
+                            ),
+                            Code::InvalidDocumentOperator,
+                        ));
+                    }
+                    return Ok(());
+                }
+            }
+        }
+
+        for (field, result) in results {
+            document.insert(field, result);
+        }
+        previous.extend(document.clone());
+        builder.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+        Ok(())
+    })?;
+    builder.finish()?;
+    applied_file.seek(SeekFrom::Start(0))?;
+
+    Ok((applied_file, errors))
+}
+
+/// The name and the operand of the operator the value is made of, if it is one.
+fn operator(value: &Value) -> Option<(&str, &Value)> {
+    match value {
+        Value::Object(object) if object.len() == 1 => {
+            let (name, operand) = object.iter().next()?;
+            [INCREMENT_OPERATOR, DECREMENT_OPERATOR]
+                .contains(&name.as_str())
+                .then(|| (name.as_str(), operand))
+        }
+        _ => None,
+    }
+}
+
+fn apply_operator(name: &str, current: &Value, operand: &Value) -> Option<Value> {
+    let (current, operand) = match (current, operand) {
+        (Value::Number(current), Value::Number(operand)) => (current, operand),
+        _ => return None,
+    };
+    let result = match (current.as_i64(), operand.as_i64()) {
+        (Some(current), Some(operand)) if name == INCREMENT_OPERATOR => {
+            Number::from(current.checked_add(operand)?)
+        }
+        (Some(current), Some(operand)) => Number::from(current.checked_sub(operand)?),
+        _ if name == INCREMENT_OPERATOR => Number::from_f64(current.as_f64()? + operand.as_f64()?)?,
+        _ => Number::from_f64(current.as_f64()? - operand.as_f64()?)?,
+    };
+    Some(Value::Number(result))
+}
+
+/// Reads the stored documents of an index by id.
+struct StoredDocuments<'t> {
+    index: &'t milli::Index,
+    fields_ids_map: FieldsIdsMap,
+    all_fields: Vec<FieldId>,
+    external_documents_ids: ExternalDocumentsIds<'t>,
+}
+
+impl<'t> StoredDocuments<'t> {
+    fn new(index: &'t milli::Index, txn: &'t RoTxn) -> Result<Self> {
+        let fields_ids_map = index.fields_ids_map(txn)?;
+        let all_fields = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let external_documents_ids = index.external_documents_ids(txn)?;
+        Ok(Self {
+            index,
+            fields_ids_map,
+            all_fields,
+            external_documents_ids,
+        })
+    }
+
+    fn get(&self, txn: &RoTxn, document_id: &str) -> Result<Option<Map<String, Value>>> {
+        let internal_id = match self.external_documents_ids.get(document_id.as_bytes()) {
+            Some(internal_id) => internal_id,
+            None => return Ok(None),
+        };
+        match self
+            .index
+            .documents(txn, std::iter::once(internal_id))?
+            .into_iter()
+            .next()
+        {
+            Some((_, obkv)) => Ok(Some(obkv_to_json(
+                &self.all_fields,
+                &self.fields_ids_map,
+                obkv,
+            )?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn generated_id() -> Value {
+    Value::String(Uuid::new_v4().to_string())
+}
+
+/// Returns the ids of the documents of the payload, without decoding their other fields.
+pub(super) fn payload_document_ids(file: &mut File, primary_key: &str) -> Result<Vec<String>> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = DocumentBatchReader::from_reader(&*file)?;
+
+    let mut ids = Vec::new();
+    while let Some((index, obkv)) = reader.next_document_with_index()? {
+        let primary_key_id = index
+            .iter()
+            .find(|(_, name)| name.as_str() == primary_key)
+            .map(|(id, _)| *id);
+        if let Some(value) = primary_key_id.and_then(|id| obkv.get(id)) {
+            ids.extend(valid_document_id(&serde_json::from_slice(value)?));
+        }
+    }
+
+    file.seek(SeekFrom::Start(0))?;
+    Ok(ids)
+}
+
+/// Calls `f` with the position and the content of each document of the payload.
+pub(super) fn for_each_document(
+    file: &mut File,
+    mut f: impl FnMut(usize, Map<String, Value>) -> Result<()>,
+) -> Result<()> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = DocumentBatchReader::from_reader(&*file)?;
+
+    let mut position = 0;
+    while let Some((index, obkv)) = reader.next_document_with_index()? {
+        let mut document = Map::new();
+        for (field_id, content) in obkv.iter() {
+            if let Some(field_name) = index.name(field_id) {
+                document.insert(field_name.to_string(), serde_json::from_slice(content)?);
+            }
+        }
+        f(position, document)?;
+        position += 1;
+    }
+
+    Ok(())
+}
+
+fn check_document(
+    position: usize,
+    document: &Map<String, Value>,
+    primary_key: &str,
+    check_geo: bool,
+) -> Option<DocumentError> {
+    let document_id = match document.get(primary_key) {
+        Some(value) => match valid_document_id(value) {
+            Some(document_id) => document_id,
+            None => {
+                return Some(DocumentError::new(
+                    position,
+                    None,
+                    format!(
+                        "Document identifier `{}` is invalid. A document identifier can be of type integer or string, only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and underscores (_).",
+                        value
+                    ),
+                    Code::InvalidDocumentId,
+                ))
+            }
+        },
+        None => {
+            return Some(DocumentError::new(
+                position,
+                None,
+                format!(
+                    "Document doesn't have a `{}` attribute: `{}`.",
+                    primary_key,
+                    Value::Object(document.clone())
+                ),
+                Code::MissingDocumentId,
+            ))
+        }
+    };
+
+    if check_geo {
+        if let Some(geo) = document.get("_geo") {
+            if geo["lat"].as_f64().zip(geo["lng"].as_f64()).is_none() {
+                return Some(DocumentError::new(
+                    position,
+                    Some(document_id),
+                    format!(
+                        "The document's `_geo` field is invalid, it must be an object with the `lat` and `lng` numbers, found `{}`.",
+                        geo
+                    ),
+                    Code::InvalidGeoField,
+                ));
+            }
+        }
+    }
+
+    if let Some(acl) = document.get(ACL_FIELD) {
+        let is_valid = acl
+            .as_array()
+            .map_or(false, |tags| tags.iter().all(Value::is_string));
+        if !is_valid {
+            return Some(DocumentError::new(
+                position,
+                Some(document_id),
+                format!(
+                    "The document's `{}` field is invalid, it must be an array of strings, found `{}`.",
+                    ACL_FIELD, acl
+                ),
+                Code::InvalidDocumentAcl,
+            ));
+        }
+    }
+
+    None
+}
+
+fn valid_document_id(value: &Value) -> Option<String> {
+    let document_id = match value {
+        Value::String(id) => id.clone(),
+        Value::Number(id) => id.to_string(),
+        _ => return None,
+    };
+
+    let is_valid = !document_id.is_empty()
+        && document_id.len() <= MAX_DOCUMENT_ID_LENGTH
+        && document_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    is_valid.then(|| document_id)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_check_document() {
+        let check = |document: Value, check_geo| {
+            let document = document.as_object().unwrap().clone();
+            check_document(0, &document, "id", check_geo).map(|error| error.code)
+        };
+
+        assert_eq!(check(json!({ "id": 1 }), true), None);
+        assert_eq!(check(json!({ "id": "a-b_c" }), true), None);
+        assert_eq!(
+            check(json!({ "title": "Carol" }), true).as_deref(),
+            Some("missing_document_id")
+        );
+        assert_eq!(
+            check(json!({ "id": "a b" }), true).as_deref(),
+            Some("invalid_document_id")
+        );
+        assert_eq!(
+            check(json!({ "id": 1.5 }), true).as_deref(),
+            Some("invalid_document_id")
+        );
+
+        let geo = json!({ "id": 1, "_geo": { "lat": "north", "lng": 2.3 } });
+        assert_eq!(
+            check(geo.clone(), true).as_deref(),
+            Some("invalid_geo_field")
+        );
+        assert_eq!(check(geo, false), None);
+    }
+
+    #[test]
+    fn test_update_operators() {
+        assert_eq!(operator(&json!({ "_inc": 2 })), Some(("_inc", &json!(2))));
+        assert_eq!(operator(&json!({ "_inc": 2, "_dec": 1 })), None);
+        assert_eq!(operator(&json!({ "inc": 2 })), None);
+
+        assert_eq!(
+            apply_operator("_inc", &json!(5), &json!(-1)),
+            Some(json!(4))
+        );
+        assert_eq!(apply_operator("_dec", &json!(5), &json!(2)), Some(json!(3)));
+        assert_eq!(
+            apply_operator("_inc", &json!(1.5), &json!(1)),
+            Some(json!(2.5))
+        );
+        assert_eq!(apply_operator("_inc", &json!("5"), &json!(1)), None);
+        assert_eq!(apply_operator("_inc", &json!(i64::MAX), &json!(1)), None);
+    }
+
+    #[test]
+    fn test_merge_nested() {
+        let mut document =
+            json!({ "id": 1, "attrs": { "color": "red", "size": { "eu": 42 } }, "tags": ["new"] })
+                .as_object()
+                .unwrap()
+                .clone();
+        let stored = json!({ "id": 1, "title": "Shoe", "attrs": { "color": "blue", "brand": "Acme", "size": { "us": 9 } }, "tags": ["old"] })
+            .as_object()
+            .unwrap()
+            .clone();
+        merge_nested(&mut document, stored);
+
+        assert_eq!(
+            Value::Object(document),
+            json!({ "id": 1, "title": "Shoe", "attrs": { "color": "red", "brand": "Acme", "size": { "eu": 42, "us": 9 } }, "tags": ["new"] })
+        );
+    }
+}
@@ -0,0 +1,546 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+#[cfg(feature = "pdf-attachments")]
+use std::panic::catch_unwind;
+use std::time::{Duration, Instant};
+
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::redirect::Policy;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::error::{IndexError, Result};
+use super::index::Index;
+use super::updates::attachments;
+
+/// The maximum size, in bytes, of an attachment fetched from a URL.
+const MAX_ATTACHMENT_SIZE: u64 = 50 * 1024 * 1024;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 10;
+/// The maximum number of attachments fetched from a URL for a single payload.
+const MAX_FETCHES: usize = 1000;
+/// The maximum time spent fetching the attachments of a single payload.
+const MAX_FETCH_TIME: Duration = Duration::from_secs(10 * 60);
+
+/// The fields of the documents holding an attachment, a PDF, HTML or text file, whose text is
+/// extracted into another field while the payload of a document addition is received.
+///
+/// An attachment is either a `data:` URL, a base64 string, or an `http(s)` URL starting with one
+/// of the allowed prefixes. The embedded attachments are removed from the documents once their
+/// text is extracted, the URLs are kept.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentSettings {
+    /// The field storing the extracted text, for each field holding an attachment.
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+    /// The prefixes of the URLs the attachments are fetched from, no URL is fetched when empty. A
+    /// URL is allowed when it has the scheme, host and port of a prefix, and its path starts with
+    /// the path segments of the prefix.
+    #[serde(default)]
+    pub allowed_url_prefixes: Vec<String>,
+}
+
+impl Index {
+    pub fn attachments(&self) -> Result<AttachmentSettings> {
+        let txn = self.read_txn()?;
+        attachments(self, &txn)
+    }
+}
+
+/// Checks that the attachments and the extracted texts are stored in distinct, named fields.
+pub fn check_attachments(settings: &AttachmentSettings) -> Result<()> {
+    let invalid = |message: String| Err(IndexError::InvalidAttachments(message));
+    for (field, content_field) in &settings.fields {
+        if field.is_empty() || content_field.is_empty() {
+            return invalid("The fields of the attachments can't be empty.".to_string());
+        }
+        if settings.fields.contains_key(content_field) {
+            return invalid(format!(
+                "The text of the attachment `{}` can't be stored in the attachment field `{}`.",
+                field, content_field
+            ));
+        }
+    }
+    for prefix in &settings.allowed_url_prefixes {
+        match Url::parse(prefix) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => (),
+            _ => {
+                return invalid(format!(
+                    "The allowed URL prefix `{}` must be an http(s) URL.",
+                    prefix
+                ))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttachmentFormat {
+    Pdf,
+    Html,
+    Text,
+}
+
+pub struct AttachmentExtractor<'a> {
+    settings: &'a AttachmentSettings,
+    client: Option<Client>,
+    /// The number of attachments fetched so far, and the time it took.
+    fetches: usize,
+    fetch_time: Duration,
+}
+
+impl<'a> AttachmentExtractor<'a> {
+    pub fn new(settings: &'a AttachmentSettings) -> Self {
+        Self {
+            settings,
+            client: None,
+            fetches: 0,
+            fetch_time: Duration::ZERO,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.settings.fields.is_empty()
+    }
+
+    /// Stores the text of the attachments of the document at `position` of the payload in their
+    /// content field. The documents without attachment are left as is.
+    pub fn extract(&mut self, position: usize, document: &mut Map<String, Value>) -> Result<()> {
+        let settings = self.settings;
+        for (field, content_field) in &settings.fields {
+            let attachment = match document.get(field) {
+                Some(Value::String(attachment)) => attachment,
+                _ => continue,
+            };
+            let is_url = attachment.starts_with("http://") || attachment.starts_with("https://");
+            let text = self.extract_text(attachment).map_err(|reason| {
+                IndexError::AttachmentExtractionFailed(field.clone(), position, reason)
+            })?;
+            if !is_url {
+                document.remove(field);
+            }
+            document.insert(content_field.clone(), Value::String(text));
+        }
+
+        Ok(())
+    }
+
+    fn extract_text(&mut self, attachment: &str) -> std::result::Result<String, String> {
+        let (content_type, bytes) =
+            if attachment.starts_with("http://") || attachment.starts_with("https://") {
+                self.fetch(attachment)?
+            } else if let Some(data_url) = attachment.strip_prefix("data:") {
+                let (media_type, data) = data_url
+                    .split_once(";base64,")
+                    .ok_or("A `data:` URL attachment must be base64 encoded.")?;
+                let bytes = base64::decode(data).map_err(|e| e.to_string())?;
+                (Some(media_type.to_string()), bytes)
+            } else {
+                let bytes = base64::decode(attachment).map_err(|e| e.to_string())?;
+                (None, bytes)
+            };
+
+        let text = match attachment_format(content_type.as_deref(), &bytes) {
+            // pdf-extract panics on some malformed files
+            #[cfg(feature = "pdf-attachments")]
+            Some(AttachmentFormat::Pdf) => {
+                catch_unwind(|| pdf_extract::extract_text_from_mem(&bytes))
+                    .map_err(|_| "The PDF file is malformed.".to_string())?
+                    .map_err(|e| e.to_string())?
+            }
+            #[cfg(not(feature = "pdf-attachments"))]
+            Some(AttachmentFormat::Pdf) => {
+                return Err(
+                    "The PDF attachments require Meilisearch to be built with the \
+                    `pdf-attachments` feature."
+                        .to_string(),
+                )
+            }
+            Some(AttachmentFormat::Html) => html_to_text(&String::from_utf8_lossy(&bytes)),
+            Some(AttachmentFormat::Text) => String::from_utf8_lossy(&bytes).into_owned(),
+            None => {
+                return Err(format!(
+                "Unsupported content type `{}`, the attachments must be PDF, HTML or text files.",
+                content_type.unwrap_or_default()
+            ))
+            }
+        };
+
+        Ok(text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    /// Returns the content type and the content of the attachment at `url`. The redirections are
+    /// only followed to the URLs starting with an allowed prefix. The number of fetches and the
+    /// time spent fetching are limited for the whole payload.
+    fn fetch(&mut self, url: &str) -> std::result::Result<(Option<String>, Vec<u8>), String> {
+        let prefixes = &self.settings.allowed_url_prefixes;
+        if !is_allowed_url(prefixes, url) {
+            return Err(format!(
+                "The URL `{}` doesn't start with an allowed prefix of the index.",
+                url
+            ));
+        }
+        if self.fetches >= MAX_FETCHES {
+            return Err(format!(
+                "A payload can't have more than {} attachments fetched from a URL.",
+                MAX_FETCHES
+            ));
+        }
+        let remaining_time = match MAX_FETCH_TIME.checked_sub(self.fetch_time) {
+            Some(remaining_time) if !remaining_time.is_zero() => remaining_time,
+            _ => {
+                return Err(format!(
+                    "The attachments of a payload can't be fetched for more than {}s.",
+                    MAX_FETCH_TIME.as_secs()
+                ))
+            }
+        };
+        self.fetches += 1;
+        let start = Instant::now();
+        let fetched = self.fetch_within(url, remaining_time.min(FETCH_TIMEOUT));
+        self.fetch_time += start.elapsed();
+        fetched
+    }
+
+    fn fetch_within(
+        &mut self,
+        url: &str,
+        timeout: Duration,
+    ) -> std::result::Result<(Option<String>, Vec<u8>), String> {
+        let prefixes = &self.settings.allowed_url_prefixes;
+
+        // the client is only built when the payload has a URL
+        if self.client.is_none() {
+            let prefixes = prefixes.clone();
+            let redirect = Policy::custom(move |attempt| {
+                if attempt.previous().len() > MAX_REDIRECTS {
+                    attempt.error("too many redirections")
+                } else if is_allowed_url(&prefixes, attempt.url().as_str()) {
+                    attempt.follow()
+                } else {
+                    let error = format!(
+                        "The URL `{}` redirects to `{}`, which doesn't start with an allowed prefix of the index.",
+                        attempt.previous()[0],
+                        attempt.url()
+                    );
+                    attempt.error(error)
+                }
+            });
+            let client = Client::builder()
+                .redirect(redirect)
+                .build()
+                .map_err(|e| e.to_string())?;
+            self.client = Some(client);
+        }
+        let response = self
+            .client
+            .as_ref()
+            .unwrap()
+            .get(url)
+            .timeout(timeout)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| match std::error::Error::source(&e) {
+                // the refused redirections are explained by the error of the policy
+                Some(source) if e.is_redirect() => source.to_string(),
+                _ => e.to_string(),
+            })?;
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        let mut bytes = Vec::new();
+        response
+            .take(MAX_ATTACHMENT_SIZE + 1)
+            .read_to_end(&mut bytes)
+            .map_err(|e| e.to_string())?;
+        if bytes.len() as u64 > MAX_ATTACHMENT_SIZE {
+            return Err(format!(
+                "The attachment is larger than {} bytes.",
+                MAX_ATTACHMENT_SIZE
+            ));
+        }
+
+        Ok((content_type, bytes))
+    }
+}
+
+/// Whether the URL has the scheme, host and port of one of the prefixes, and its path starts with
+/// the path segments of the prefix. The URLs holding credentials are never allowed.
+fn is_allowed_url(prefixes: &[String], url: &str) -> bool {
+    let url = match Url::parse(url) {
+        Ok(url) if url.username().is_empty() && url.password().is_none() => url,
+        _ => return false,
+    };
+    prefixes
+        .iter()
+        .filter_map(|prefix| Url::parse(prefix).ok())
+        .any(|prefix| {
+            url.scheme() == prefix.scheme()
+                && url.host_str() == prefix.host_str()
+                && url.port_or_known_default() == prefix.port_or_known_default()
+                && path_segments(&url).starts_with(&path_segments(&prefix))
+        })
+}
+
+/// The non-empty segments of the path of the URL, its dot segments being already resolved.
+fn path_segments(url: &Url) -> Vec<&str> {
+    url.path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// The format of an attachment, from its content type or, when it's unknown, its content.
+fn attachment_format(content_type: Option<&str>, bytes: &[u8]) -> Option<AttachmentFormat> {
+    let essence = content_type
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|essence| essence.trim().to_ascii_lowercase());
+    match essence.as_deref() {
+        Some("application/pdf") => Some(AttachmentFormat::Pdf),
+        Some("text/html" | "application/xhtml+xml") => Some(AttachmentFormat::Html),
+        Some("text/plain") => Some(AttachmentFormat::Text),
+        Some("" | "application/octet-stream") | None => {
+            if bytes.starts_with(b"%PDF-") {
+                Some(AttachmentFormat::Pdf)
+            } else if String::from_utf8_lossy(bytes).trim_start().starts_with('<') {
+                Some(AttachmentFormat::Html)
+            } else {
+                None
+            }
+        }
+        Some(_) => None,
+    }
+}
+
+/// Returns the text of the HTML document, without its tags, scripts and styles.
+fn html_to_text(html: &str) -> String {
+    // the ASCII lowercase keeps the byte offsets of the HTML
+    let lowercase = html.to_ascii_lowercase();
+    let mut text = String::with_capacity(html.len());
+    let mut offset = 0;
+    while let Some(start) = lowercase[offset..].find('<').map(|start| offset + start) {
+        text.push_str(&decode_entities(&html[offset..start]));
+        text.push(' ');
+
+        let mut end = start;
+        for element in ["script", "style"] {
+            let tag = &lowercase[start + 1..];
+            if tag.starts_with(element) {
+                let closing = format!("</{}", element);
+                end = lowercase[start..]
+                    .find(&closing)
+                    .map_or(lowercase.len(), |closing| start + closing);
+            }
+        }
+        offset = match lowercase[end..].find('>') {
+            Some(tag_end) => end + tag_end + 1,
+            None => lowercase.len(),
+        };
+    }
+    text.push_str(&decode_entities(&html[offset..]));
+
+    text
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';').map(|end| &rest[1..end]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => {
+                let code = match entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => entity.strip_prefix('#')?.parse().ok(),
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_html_to_text() {
+        let html = r#"<html><head><style>p { color: red; }</style></head>
+            <body><h1>Hamlet</h1><SCRIPT>alert("<p>")</SCRIPT>
+            <p>To be, or not to be &amp; &#233;t&#xE9;</p></body></html>"#;
+        let text = html_to_text(html);
+        assert_eq!(
+            text.split_whitespace().collect::<Vec<_>>(),
+            ["Hamlet", "To", "be,", "or", "not", "to", "be", "&", "été"]
+        );
+    }
+
+    #[test]
+    fn test_is_allowed_url() {
+        let prefixes = vec!["https://example.com/files/".to_string()];
+        assert!(is_allowed_url(
+            &prefixes,
+            "https://example.com/files/hamlet.pdf"
+        ));
+        assert!(is_allowed_url(
+            &prefixes,
+            "https://EXAMPLE.com:443/files/a/b"
+        ));
+        assert!(!is_allowed_url(
+            &prefixes,
+            "https://example.com.evil.org/files/a"
+        ));
+        assert!(!is_allowed_url(
+            &prefixes,
+            "https://example.com:8443/files/a"
+        ));
+        assert!(!is_allowed_url(&prefixes, "http://example.com/files/a"));
+        assert!(!is_allowed_url(
+            &prefixes,
+            "https://example.com/files-private/a"
+        ));
+        assert!(!is_allowed_url(
+            &prefixes,
+            "https://example.com/files/../secret"
+        ));
+        assert!(!is_allowed_url(
+            &prefixes,
+            "https://user@example.com/files/a"
+        ));
+        assert!(!is_allowed_url(&prefixes, "not a url"));
+    }
+
+    #[test]
+    fn test_extract_embedded_attachments() {
+        let settings = AttachmentSettings {
+            fields: BTreeMap::from([("file".to_string(), "content".to_string())]),
+            allowed_url_prefixes: Vec::new(),
+        };
+        let mut extractor = AttachmentExtractor::new(&settings);
+
+        let html = base64::encode("<p>Hello <b>world</b></p>");
+        let mut document = json!({ "id": 1, "file": format!("data:text/html;base64,{}", html) })
+            .as_object()
+            .unwrap()
+            .clone();
+        extractor.extract(0, &mut document).unwrap();
+        assert_eq!(
+            Value::Object(document),
+            json!({ "id": 1, "content": "Hello world" })
+        );
+
+        // the format of a base64 attachment is guessed from its content
+        let mut document = json!({ "id": 2, "file": html })
+            .as_object()
+            .unwrap()
+            .clone();
+        extractor.extract(0, &mut document).unwrap();
+        assert_eq!(document["content"], json!("Hello world"));
+
+        let mut document = json!({ "id": 3, "file": "https://example.com/hamlet.pdf" })
+            .as_object()
+            .unwrap()
+            .clone();
+        assert!(matches!(
+            extractor.extract(3, &mut document),
+            Err(IndexError::AttachmentExtractionFailed(_, 3, _))
+        ));
+    }
+
+    #[test]
+    fn test_fetch_follows_allowed_redirections_only() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request_line).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+
+                let response = match request_line.split_whitespace().nth(1) {
+                    Some("/inside") => {
+                        "HTTP/1.1 302 Found\r\nLocation: /hamlet.txt\r\n".to_string()
+                    }
+                    Some("/outside") => format!(
+                        "HTTP/1.1 302 Found\r\nLocation: http://localhost:{}/hamlet.txt\r\n",
+                        port
+                    ),
+                    _ => "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n".to_string(),
+                };
+                let body = "To be, or not to be";
+                write!(
+                    stream,
+                    "{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        let settings = AttachmentSettings {
+            fields: BTreeMap::from([("file".to_string(), "content".to_string())]),
+            allowed_url_prefixes: vec![format!("http://127.0.0.1:{}/", port)],
+        };
+        let mut extractor = AttachmentExtractor::new(&settings);
+
+        let (_, bytes) = extractor
+            .fetch(&format!("http://127.0.0.1:{}/inside", port))
+            .unwrap();
+        assert_eq!(bytes, b"To be, or not to be");
+
+        // the redirection leaves the allowed prefixes
+        let error = extractor
+            .fetch(&format!("http://127.0.0.1:{}/outside", port))
+            .unwrap_err();
+        assert!(
+            error.contains("doesn't start with an allowed prefix"),
+            "{}",
+            error
+        );
+    }
+}
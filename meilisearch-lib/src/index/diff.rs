@@ -0,0 +1,160 @@
+use milli::update::Setting;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::error::Result;
+use super::index::Index;
+use super::updates::{expand_stop_words_presets, Checked, Settings};
+
+/// The settings whose update reindexes all the documents of the index, with the fields
+/// concerned when the setting is an object.
+const REINDEXING_SETTINGS: &[(&str, Option<&str>)] = &[
+    ("searchableAttributes", None),
+    ("filterableAttributes", None),
+    ("sortableAttributes", None),
+    ("stopWords", None),
+    ("synonyms", None),
+    ("typoTolerance", Some("disableOnAttributes")),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsDiff {
+    pub changes: Vec<SettingsChange>,
+    /// Whether at least one of the changes reindexes all the documents.
+    pub reindex: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChange {
+    pub setting: String,
+    pub current: Value,
+    /// `null` when the setting is reset to its default value.
+    pub proposed: Value,
+    pub reindex: bool,
+}
+
+impl Index {
+    /// Compares the settings of the index with the ones they would be after the update of
+    /// `proposed`, without applying it.
+    pub fn settings_diff(&self, proposed: Settings<Checked>) -> Result<SettingsDiff> {
+        let proposed = match proposed.stop_words {
+            Setting::Set(ref stop_words) => Settings {
+                stop_words: Setting::Set(expand_stop_words_presets(stop_words)?),
+                ..proposed
+            },
+            _ => proposed,
+        };
+
+        let current = to_object(serde_json::to_value(self.settings()?)?);
+        let proposed = to_object(serde_json::to_value(proposed)?);
+        let changes = diff_settings(&current, proposed);
+        let reindex = changes.iter().any(|change| change.reindex);
+
+        Ok(SettingsDiff { changes, reindex })
+    }
+}
+
+fn to_object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(object) => object,
+        _ => Map::new(),
+    }
+}
+
+/// Lists the settings of `proposed` changing the `current` ones. The fields of a nested setting
+/// missing from the proposal are kept, and a reset setting whose current value is empty is
+/// considered unchanged, since its default value is empty.
+fn diff_settings(
+    current: &Map<String, Value>,
+    proposed: Map<String, Value>,
+) -> Vec<SettingsChange> {
+    let mut changes = Vec::new();
+    for (setting, proposed) in proposed {
+        let current = current.get(&setting).cloned().unwrap_or(Value::Null);
+        let proposed = match (&current, proposed) {
+            (Value::Object(current), Value::Object(fields)) => {
+                let mut merged = current.clone();
+                merged.extend(fields);
+                Value::Object(merged)
+            }
+            (_, proposed) => proposed,
+        };
+
+        let is_empty = match &current {
+            Value::Array(values) => values.is_empty(),
+            Value::Object(fields) => fields.is_empty(),
+            value => value.is_null(),
+        };
+        if proposed == current || (proposed.is_null() && is_empty) {
+            continue;
+        }
+
+        let reindex = REINDEXING_SETTINGS.iter().any(|(name, field)| {
+            *name == setting
+                && match field {
+                    Some(field) => current.get(field) != proposed.get(field),
+                    None => true,
+                }
+        });
+        changes.push(SettingsChange {
+            setting,
+            current,
+            proposed,
+            reindex,
+        });
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_settings() {
+        let current = to_object(json!({
+            "displayedAttributes": ["*"],
+            "rankingRules": ["words", "typo"],
+            "stopWords": [],
+            "distinctAttribute": null,
+            "typoTolerance": { "enabled": true, "disableOnAttributes": [] },
+        }));
+        let proposed = to_object(json!({
+            "displayedAttributes": ["*"],
+            "rankingRules": ["typo", "words"],
+            "stopWords": null,
+            "distinctAttribute": "sku",
+            "typoTolerance": { "enabled": false },
+        }));
+
+        let changes = diff_settings(&current, proposed);
+        let changed: Vec<_> = changes
+            .iter()
+            .map(|change| (change.setting.as_str(), change.reindex))
+            .collect();
+        assert_eq!(
+            changed,
+            [
+                ("rankingRules", false),
+                ("distinctAttribute", false),
+                ("typoTolerance", false)
+            ]
+        );
+        assert_eq!(
+            changes[2].proposed,
+            json!({ "enabled": false, "disableOnAttributes": [] })
+        );
+
+        let proposed = to_object(json!({
+            "typoTolerance": { "disableOnAttributes": ["sku"] },
+            "stopWords": ["the"],
+        }));
+        let changes = diff_settings(&current, proposed);
+        assert!(changes.iter().all(|change| change.reindex));
+    }
+}
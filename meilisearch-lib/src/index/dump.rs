@@ -10,7 +10,11 @@ use milli::update::{IndexDocumentsConfig, IndexerConfig};
 use serde::{Deserialize, Serialize};
 
 use crate::document_formats::read_ndjson;
-use crate::index::updates::apply_settings_to_builder;
+use crate::index::updates::{
+    apply_attachments, apply_document_schema, apply_expires_at_attribute, apply_frozen,
+    apply_ingestion_pipeline, apply_nested_flattening, apply_ranking_experiment, apply_rules,
+    apply_search_cutoff_ms, apply_settings_to_builder, apply_sort_facet_values_by, is_frozen,
+};
 
 use super::error::Result;
 use super::{index::Index, Settings, Unchecked};
@@ -19,6 +23,11 @@ use super::{index::Index, Settings, Unchecked};
 struct DumpMeta {
     settings: Settings<Unchecked>,
     primary_key: Option<String>,
+    /// The size of the map set for the index, if any.
+    #[serde(default)]
+    map_size: Option<usize>,
+    #[serde(default)]
+    frozen: bool,
 }
 
 const META_FILE_NAME: &str = "meta.json";
@@ -74,6 +83,8 @@ impl Index {
         let meta = DumpMeta {
             settings,
             primary_key,
+            map_size: self.has_custom_map_size.then(|| self.map_size),
+            frozen: is_frozen(self, txn)?,
         };
 
         serde_json::to_writer(&mut meta_file, &meta)?;
@@ -100,11 +111,16 @@ impl Index {
         let DumpMeta {
             settings,
             primary_key,
+            map_size,
+            frozen,
         } = serde_json::from_reader(meta_file)?;
         let settings = settings.check();
 
+        if let Some(map_size) = map_size {
+            Index::write_map_size(&dst_dir_path, map_size)?;
+        }
         let mut options = EnvOpenOptions::new();
-        options.map_size(size);
+        options.map_size(map_size.unwrap_or(size));
         let index = milli::Index::new(options, &dst_dir_path)?;
 
         let mut txn = index.write_txn()?;
@@ -120,6 +136,17 @@ impl Index {
 
         builder.execute(|_| ())?;
 
+        apply_search_cutoff_ms(&index, &mut txn, &settings.search_cutoff_ms)?;
+        apply_ranking_experiment(&index, &mut txn, &settings.ranking_experiment)?;
+        apply_sort_facet_values_by(&index, &mut txn, &settings.faceting)?;
+        apply_rules(&index, &mut txn, &settings.rules)?;
+        apply_expires_at_attribute(&index, &mut txn, &settings.expires_at_attribute)?;
+        apply_document_schema(&index, &mut txn, &settings.document_schema)?;
+        apply_ingestion_pipeline(&index, &mut txn, &settings.ingestion_pipeline)?;
+        apply_nested_flattening(&index, &mut txn, &settings.nested_flattening)?;
+        apply_attachments(&index, &mut txn, &settings.attachments)?;
+        apply_frozen(&index, &mut txn, frozen)?;
+
         let document_file_path = src.as_ref().join(DATA_FILE_NAME);
         let reader = BufReader::new(File::open(&document_file_path)?);
 
@@ -0,0 +1,241 @@
+use std::io::{Cursor, Seek, SeekFrom};
+
+use log::{debug, info};
+use milli::documents::{DocumentBatchBuilder, DocumentBatchReader};
+use milli::heed::RwTxn;
+use milli::obkv_to_json;
+use milli::update::{IndexDocumentsConfig, IndexDocumentsMethod};
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use self::function::EditionFunction;
+use super::error::{IndexError, Result};
+use super::index::{Document, Index};
+use super::search::parse_filter;
+use super::vectors::sync_vectors;
+
+/// The number of documents edited at once, so the edited documents aren't all held in memory.
+const EDITION_CHUNK_SIZE: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DocumentEditionResult {
+    pub edited_documents: u64,
+    pub deleted_documents: u64,
+}
+
+#[cfg(feature = "document-edition")]
+mod function {
+    use log::debug;
+    use rhai::{Dynamic, Engine, Scope, AST};
+
+    use super::super::error::{IndexError, Result};
+    use super::super::index::Document;
+
+    /// The maximum number of operations the function of an edition can perform on a single
+    /// document, so a function looping forever fails instead of blocking the task queue.
+    const MAX_OPERATIONS_PER_DOCUMENT: u64 = 1_000_000;
+    /// The limits of the values built by the function, so a function growing a value fails
+    /// before exhausting the memory.
+    const MAX_STRING_SIZE: usize = 1024 * 1024;
+    const MAX_ARRAY_SIZE: usize = 100_000;
+    const MAX_MAP_SIZE: usize = 10_000;
+    const MAX_CALL_LEVELS: usize = 32;
+
+    /// A compiled rhai function editing the documents.
+    pub struct EditionFunction {
+        engine: Engine,
+        ast: AST,
+    }
+
+    impl EditionFunction {
+        pub fn compile(function: &str) -> Result<Self> {
+            let mut engine = Engine::new();
+            engine.set_max_operations(MAX_OPERATIONS_PER_DOCUMENT);
+            engine.set_max_string_size(MAX_STRING_SIZE);
+            engine.set_max_array_size(MAX_ARRAY_SIZE);
+            engine.set_max_map_size(MAX_MAP_SIZE);
+            engine.set_max_call_levels(MAX_CALL_LEVELS);
+            // the output of the functions goes to the logs rather than to the standard output
+            engine.on_print(|text| debug!("document edition: {}", text));
+            engine
+                .on_debug(|text, _, position| debug!("document edition at {}: {}", position, text));
+
+            let ast = engine
+                .compile(function)
+                .map_err(|e| IndexError::InvalidDocumentEditionFunction(e.to_string()))?;
+
+            Ok(Self { engine, ast })
+        }
+
+        /// Runs the function on the document bound to `doc`, the document is deleted when the
+        /// function sets it to `()`.
+        pub fn run(&self, document: &Document) -> std::result::Result<Option<Document>, String> {
+            let mut scope = Scope::new();
+            scope.push(
+                "doc",
+                rhai::serde::to_dynamic(document).map_err(|e| e.to_string())?,
+            );
+            self.engine
+                .run_ast_with_scope(&mut scope, &self.ast)
+                .map_err(|e| e.to_string())?;
+
+            let new_document = scope.get_value::<Dynamic>("doc").unwrap_or(Dynamic::UNIT);
+            if new_document.is_unit() {
+                return Ok(None);
+            }
+
+            rhai::serde::from_dynamic(&new_document)
+                .map(Some)
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(not(feature = "document-edition"))]
+mod function {
+    use super::super::error::{IndexError, Result};
+    use super::super::index::Document;
+
+    /// Without the `document-edition` feature, no function can be compiled.
+    pub enum EditionFunction {}
+
+    impl EditionFunction {
+        pub fn compile(_function: &str) -> Result<Self> {
+            Err(IndexError::DocumentEditionDisabled)
+        }
+
+        pub fn run(&self, _document: &Document) -> std::result::Result<Option<Document>, String> {
+            match *self {}
+        }
+    }
+}
+
+/// Checks that the function of an edition compiles, so the mistakes are reported when the edition
+/// is registered rather than when its task is processed.
+pub fn check_edition_function(function: &str) -> Result<()> {
+    EditionFunction::compile(function).map(drop)
+}
+
+impl Index {
+    /// Runs the function on each document matching the filter, or on every document when there's
+    /// no filter. The function is a rhai script modifying the document bound to `doc`; the
+    /// documents the function sets to `()` are deleted.
+    pub fn edit_documents_by_function(
+        &self,
+        filter: Option<&Value>,
+        function: &str,
+    ) -> Result<DocumentEditionResult> {
+        let function = EditionFunction::compile(function)?;
+
+        let mut txn = self.write_txn()?;
+        let before = self.documents_ids(&txn)?;
+
+        let candidates = match filter {
+            Some(filter) => match parse_filter(&self.prepare_filter(&txn, filter)?)? {
+                Some(filter) => filter.evaluate(&txn, self)?,
+                // An empty filter matches no document.
+                None => RoaringBitmap::new(),
+            },
+            None => self.documents_ids(&txn)?,
+        };
+
+        let primary_key = match self.primary_key(&txn)? {
+            Some(primary_key) => primary_key.to_string(),
+            // An index without a primary key has no document.
+            None => {
+                return Ok(DocumentEditionResult {
+                    edited_documents: 0,
+                    deleted_documents: 0,
+                })
+            }
+        };
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+
+        let mut edited_documents = 0;
+        let mut edited_ids = RoaringBitmap::new();
+        let mut deleted = RoaringBitmap::new();
+        let candidates: Vec<_> = candidates.into_iter().collect();
+        for chunk in candidates.chunks(EDITION_CHUNK_SIZE) {
+            let mut edited = Vec::new();
+            for (id, obkv) in self.documents(&txn, chunk.iter().copied())? {
+                let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+                let document_id = match document.get(&primary_key) {
+                    Some(Value::String(id)) => id.clone(),
+                    Some(id) => id.to_string(),
+                    None => String::new(),
+                };
+                let failed =
+                    |error: String| IndexError::DocumentEditionFailed(document_id.clone(), error);
+
+                let new_document = match function.run(&document).map_err(failed)? {
+                    Some(new_document) => new_document,
+                    None => {
+                        deleted.insert(id);
+                        continue;
+                    }
+                };
+                if new_document.get(&primary_key) != document.get(&primary_key) {
+                    return Err(failed(format!(
+                        "the primary key `{}` can't be modified.",
+                        primary_key
+                    )));
+                }
+                if new_document != document {
+                    edited.push(new_document);
+                    edited_ids.insert(id);
+                }
+            }
+
+            // the edited documents keep their internal id, so the next chunks are unchanged
+            edited_documents += edited.len() as u64;
+            self.replace_edited_documents(&mut txn, &edited)?;
+        }
+
+        let deleted_documents = deleted.len();
+        if !deleted.is_empty() {
+            let mut builder = milli::update::DeleteDocuments::new(&mut txn, self)?;
+            builder.delete_documents(&deleted);
+            builder.execute()?;
+        }
+        sync_vectors(self, &mut txn, &before, edited_ids)?;
+
+        self.check_aborted()?;
+        txn.commit()?;
+
+        let result = DocumentEditionResult {
+            edited_documents,
+            deleted_documents,
+        };
+        info!("document edition done: {:?}", result);
+
+        Ok(result)
+    }
+    fn replace_edited_documents(&self, txn: &mut RwTxn, edited: &[Document]) -> Result<()> {
+        if edited.is_empty() {
+            return Ok(());
+        }
+
+        let mut file = tempfile::tempfile()?;
+        let mut documents = DocumentBatchBuilder::new(&mut file)?;
+        documents.extend_from_json(Cursor::new(serde_json::to_vec(edited)?))?;
+        documents.finish()?;
+        file.seek(SeekFrom::Start(0))?;
+
+        let config = IndexDocumentsConfig {
+            update_method: IndexDocumentsMethod::ReplaceDocuments,
+            ..Default::default()
+        };
+        let mut builder = milli::update::IndexDocuments::new(
+            txn,
+            self,
+            self.indexer_config.as_ref(),
+            config,
+            |indexing_step| debug!("update: {:?}", indexing_step),
+        )?;
+        builder.add_documents(DocumentBatchReader::from_reader(file)?)?;
+        builder.execute()?;
+
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use std::error::Error;
 
-use meilisearch_types::error::{Code, ErrorCode};
+use meilisearch_types::error::{Code, ErrorCode, ErrorDetails};
 use meilisearch_types::internal_error;
 use serde_json::Value;
 
@@ -14,8 +14,60 @@ pub enum IndexError {
     Internal(Box<dyn Error + Send + Sync + 'static>),
     #[error("Document `{0}` not found.")]
     DocumentNotFound(String),
+    #[error("Version `{0}` of the settings not found.")]
+    SettingsVersionNotFound(u64),
+    #[error("The write was rolled back since the processing of its task was aborted.")]
+    WriteAborted,
+    #[error("The index can't hold more than {0} documents.")]
+    DocumentQuotaExceeded(u64),
     #[error("{0}")]
     Facet(#[from] FacetError),
+    #[error("The `vector` parameter must contain at least one dimension.")]
+    EmptySearchVector,
+    #[error("The `vector` parameter can't be used along with the `sort` parameter.")]
+    VectorWithSort,
+    #[error("The `hybrid` parameter requires a `vector` to be provided.")]
+    HybridWithoutVector,
+    #[error("The `semanticRatio` must be a number between 0.0 and 1.0, found `{0}`.")]
+    InvalidSemanticRatio(f32),
+    #[error(
+        "Attribute `{0}` is not searchable. Available searchable attributes are: `{}`.",
+        .1.join(", ")
+    )]
+    NotSearchable(String, Vec<String>),
+    #[error("The weight of the attribute `{0}` must be a positive number, found `{1}`.")]
+    InvalidAttributeWeight(String, f32),
+    #[error("The `hitsPerGroup` of the `distinct` parameter must be greater than 0.")]
+    InvalidHitsPerGroup,
+    #[error("The traffic of at least one variant of the ranking experiment must be positive.")]
+    InvalidRankingExperiment,
+    #[error("Invalid rule: {0}")]
+    InvalidRule(String),
+    #[error("Invalid document schema: {0}")]
+    InvalidDocumentSchema(String),
+    #[error("Invalid ingestion pipeline: {0}")]
+    InvalidIngestionPipeline(String),
+    #[error("Invalid nested flattening: {0}")]
+    InvalidNestedFlattening(String),
+    #[error("Invalid attachments: {0}")]
+    InvalidAttachments(String),
+    #[error(
+        "The attachment of the field `{0}` of the document at position {1} can't be extracted: {2}"
+    )]
+    AttachmentExtractionFailed(String, usize, String),
+    #[error(
+        "The stop words preset `{0}` doesn't exist. Available presets are: `{}`.",
+        .1.join(", ")
+    )]
+    UnknownStopWordsPreset(String, Vec<String>),
+    #[error("Invalid document edition function: {0}")]
+    InvalidDocumentEditionFunction(String),
+    #[error("The edition of the document `{0}` failed: {1}")]
+    DocumentEditionFailed(String, String),
+    #[error(
+        "The document edition requires Meilisearch to be built with the `document-edition` feature."
+    )]
+    DocumentEditionDisabled,
     #[error("{0}")]
     Milli(#[from] milli::Error),
 }
@@ -34,22 +86,84 @@ impl ErrorCode for IndexError {
         match self {
             IndexError::Internal(_) => Code::Internal,
             IndexError::DocumentNotFound(_) => Code::DocumentNotFound,
+            IndexError::SettingsVersionNotFound(_) => Code::SettingsVersionNotFound,
+            IndexError::DocumentQuotaExceeded(_) => Code::DocumentQuotaExceeded,
+            IndexError::WriteAborted => Code::TaskProcessingAborted,
             IndexError::Facet(e) => e.error_code(),
+            IndexError::EmptySearchVector
+            | IndexError::VectorWithSort
+            | IndexError::HybridWithoutVector => Code::InvalidSearchVector,
+            IndexError::InvalidSemanticRatio(_) => Code::InvalidSearchSemanticRatio,
+            IndexError::NotSearchable(_, _) => Code::InvalidSearchAttributesToSearchOn,
+            IndexError::InvalidAttributeWeight(_, _) => Code::InvalidSearchAttributeWeights,
+            IndexError::InvalidHitsPerGroup => Code::InvalidSearchDistinct,
+            IndexError::InvalidRankingExperiment => Code::InvalidRankingExperiment,
+            IndexError::InvalidRule(_) => Code::InvalidRules,
+            IndexError::InvalidDocumentSchema(_) => Code::InvalidDocumentSchema,
+            IndexError::InvalidIngestionPipeline(_) => Code::InvalidIngestionPipeline,
+            IndexError::InvalidNestedFlattening(_) => Code::InvalidNestedFlattening,
+            IndexError::InvalidAttachments(_) => Code::InvalidAttachments,
+            IndexError::AttachmentExtractionFailed(_, _, _) => Code::AttachmentExtractionFailed,
+            IndexError::UnknownStopWordsPreset(_, _) => Code::InvalidStopWordsPreset,
+            IndexError::InvalidDocumentEditionFunction(_) => Code::InvalidDocumentEditionFunction,
+            IndexError::DocumentEditionFailed(_, _) => Code::DocumentEditionFailed,
+            IndexError::DocumentEditionDisabled => Code::DocumentEditionDisabled,
             IndexError::Milli(e) => MilliError(e).error_code(),
         }
     }
+
+    fn error_details(&self) -> Option<ErrorDetails> {
+        match self {
+            IndexError::Milli(e) => MilliError(e).error_details(),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum FacetError {
     #[error("Invalid syntax for the filter parameter: `expected {}, found: {1}`.", .0.join(", "))]
     InvalidExpression(&'static [&'static str], Value),
+    #[error(
+        "Attribute `{0}` is not filterable. Available filterable attributes are: `{}`.",
+        .1.join(", ")
+    )]
+    NotFilterable(String, Vec<String>),
+    #[error(
+        "Attribute `{0}` is not filterable, it can't be used with the `{1}` operator. Available filterable attributes are: `{}`.",
+        .2.join(", ")
+    )]
+    StringOperatorOnNotFilterable(String, &'static str, Vec<String>),
+    #[error("The `{1}` condition on the attribute `{0}` matches more than {2} values.")]
+    TooManyStringOperatorValues(String, &'static str, usize),
+    #[error(
+        "The `{1}` condition on the attribute `{0}` matches the value `{2}` which contains both kinds of quotes and can't be filtered on."
+    )]
+    UnquotableStringOperatorValue(String, &'static str, String),
+    #[error(
+        "Attribute `_geo` is not filterable, it can't be used with the `_geoBoundingBox` condition. Available filterable attributes are: `{}`.",
+        .0.join(", ")
+    )]
+    GeoBoundingBoxOnNotFilterable(Vec<String>),
+    #[error(
+        "Invalid syntax for the `_geoBoundingBox` condition: expected `_geoBoundingBox([latitude, longitude], [latitude, longitude])` with the top right corner of the box first, found `_geoBoundingBox({0})`."
+    )]
+    InvalidGeoBoundingBox(String),
+    #[error("The `_geoBoundingBox` condition matches documents at more than {0} locations.")]
+    TooManyGeoBoundingBoxPoints(usize),
 }
 
 impl ErrorCode for FacetError {
     fn error_code(&self) -> Code {
         match self {
             FacetError::InvalidExpression(_, _) => Code::Filter,
+            FacetError::NotFilterable(_, _) => Code::InvalidFacetSearchFacetName,
+            FacetError::StringOperatorOnNotFilterable(_, _, _)
+            | FacetError::TooManyStringOperatorValues(_, _, _)
+            | FacetError::UnquotableStringOperatorValue(_, _, _)
+            | FacetError::GeoBoundingBoxOnNotFilterable(_)
+            | FacetError::InvalidGeoBoundingBox(_)
+            | FacetError::TooManyGeoBoundingBoxPoints(_) => Code::Filter,
         }
     }
 }
@@ -0,0 +1,103 @@
+use milli::Filter;
+use serde_json::Value;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use super::error::Result;
+use super::index::Index;
+use super::updates::{expires_at_attribute, DEFAULT_EXPIRES_AT_ATTRIBUTE};
+
+impl Index {
+    /// Returns the ids of the documents whose expiration date is before `now`. The expiration date
+    /// of a document is read from the attribute named by the index, `_expiresAt` by default, and
+    /// is either a unix timestamp in seconds or an RFC 3339 date. The documents without a valid
+    /// expiration date never expire.
+    ///
+    /// The expired documents are found through the facets of the attribute, so the documents
+    /// only expire if it's filterable.
+    pub fn expired_documents(&self, now: OffsetDateTime) -> Result<Vec<String>> {
+        let txn = self.read_txn()?;
+
+        let attribute = expires_at_attribute(self, &txn)?
+            .unwrap_or_else(|| DEFAULT_EXPIRES_AT_ATTRIBUTE.to_string());
+        if !self.filterable_fields(&txn)?.contains(&attribute) {
+            return Ok(Vec::new());
+        }
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let primary_key = self.primary_key(&txn)?;
+        let (expires_at_id, primary_key_id) = match (
+            fields_ids_map.id(&attribute),
+            primary_key.and_then(|pk| fields_ids_map.id(pk)),
+        ) {
+            (Some(expires_at_id), Some(primary_key_id)) => (expires_at_id, primary_key_id),
+            // No document of the index has an expiration date.
+            _ => return Ok(Vec::new()),
+        };
+
+        // The timestamps are compared through the number facets. The string facets are
+        // normalized and can't be compared as dates, so only the documents having one are read.
+        let quote = if attribute.contains('"') { '\'' } else { '"' };
+        let expression = format!(
+            "{quote}{}{quote} <= {}",
+            attribute,
+            now.unix_timestamp(),
+            quote = quote
+        );
+        let mut candidates = match Filter::from_str(&expression)? {
+            Some(filter) => filter.evaluate(&txn, self)?,
+            None => Default::default(),
+        };
+        let dated = self.string_faceted_documents_ids(&txn, expires_at_id)?;
+        for (id, obkv) in self.documents(&txn, dated.iter())? {
+            let expires_at = match obkv.get(expires_at_id) {
+                Some(value) => serde_json::from_slice(value)?,
+                None => continue,
+            };
+            if expiration_date(&expires_at).map_or(false, |date| date <= now) {
+                candidates.insert(id);
+            }
+        }
+
+        let mut expired = Vec::with_capacity(candidates.len() as usize);
+        for (_id, obkv) in self.documents(&txn, candidates.iter())? {
+            if let Some(value) = obkv.get(primary_key_id) {
+                match serde_json::from_slice(value)? {
+                    Value::String(id) => expired.push(id),
+                    id => expired.push(id.to_string()),
+                }
+            }
+        }
+
+        Ok(expired)
+    }
+}
+
+fn expiration_date(value: &Value) -> Option<OffsetDateTime> {
+    match value {
+        Value::Number(timestamp) => {
+            OffsetDateTime::from_unix_timestamp(timestamp.as_f64()? as i64).ok()
+        }
+        Value::String(date) => OffsetDateTime::parse(date, &Rfc3339).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn test_expiration_date() {
+        let date = datetime!(2022-06-01 12:00 UTC);
+        assert_eq!(expiration_date(&json!(1654084800)), Some(date));
+        assert_eq!(
+            expiration_date(&json!("2022-06-01T14:00:00+02:00")),
+            Some(date)
+        );
+        assert_eq!(expiration_date(&json!("June 1st")), None);
+        assert_eq!(expiration_date(&json!(null)), None);
+    }
+}
@@ -0,0 +1,197 @@
+use std::fs::File;
+use std::io::{Cursor, Seek, SeekFrom};
+
+use milli::documents::DocumentBatchBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::addition::for_each_document;
+use super::error::{IndexError, Result};
+
+/// The field of the geolocation of the documents, whose object is never collapsed.
+const GEO_FIELD: &str = "_geo";
+
+/// How the nested objects of the documents are flattened into fields. milli turns each key of a
+/// nested object into a field, `a.b.c`; the objects collapsed by the settings are replaced by
+/// their JSON text, a single value of the field holding them, or removed from the documents.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct NestedFlattening {
+    /// The depth of the deepest fields, the top-level fields being at depth 1. The objects held by
+    /// the fields at this depth are collapsed. There is no limit when `None`.
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+    #[serde(default)]
+    pub arrays_of_objects: ArraysOfObjects,
+    /// Whether the collapsed objects are kept as their JSON text, rather than removed.
+    #[serde(default = "default_json_leaves_as_keywords")]
+    pub json_leaves_as_keywords: bool,
+}
+
+impl Default for NestedFlattening {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            arrays_of_objects: ArraysOfObjects::default(),
+            json_leaves_as_keywords: default_json_leaves_as_keywords(),
+        }
+    }
+}
+
+fn default_json_leaves_as_keywords() -> bool {
+    true
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ArraysOfObjects {
+    /// The keys of the objects of an array are fields, like the ones of a nested object.
+    Flatten,
+    /// The objects of an array are collapsed.
+    Collapse,
+}
+
+impl Default for ArraysOfObjects {
+    fn default() -> Self {
+        Self::Flatten
+    }
+}
+
+impl NestedFlattening {
+    /// Whether the documents are flattened by milli as they are, without collapsing any object.
+    pub fn is_default_flattening(&self) -> bool {
+        self.max_depth.is_none() && self.arrays_of_objects == ArraysOfObjects::Flatten
+    }
+
+    fn collapse(&self, object: Map<String, Value>) -> Option<Value> {
+        self.json_leaves_as_keywords
+            .then(|| Value::String(Value::Object(object).to_string()))
+    }
+
+    /// Returns the value of a field at `depth` with the objects it holds collapsed, or `None` when
+    /// the value is a collapsed object that's removed.
+    fn flatten_value(&self, value: Value, depth: usize, in_array: bool) -> Option<Value> {
+        match value {
+            Value::Object(object) => {
+                let too_deep = self.max_depth.map_or(false, |max_depth| depth >= max_depth);
+                if too_deep || (in_array && self.arrays_of_objects == ArraysOfObjects::Collapse) {
+                    return self.collapse(object);
+                }
+                let object = object
+                    .into_iter()
+                    .filter_map(|(key, value)| {
+                        let value = self.flatten_value(value, depth + 1, false)?;
+                        Some((key, value))
+                    })
+                    .collect();
+                Some(Value::Object(object))
+            }
+            // the values of an array are at the depth of its field
+            Value::Array(values) => Some(Value::Array(
+                values
+                    .into_iter()
+                    .filter_map(|value| self.flatten_value(value, depth, true))
+                    .collect(),
+            )),
+            value => Some(value),
+        }
+    }
+
+    fn flatten_document(&self, document: Map<String, Value>) -> Map<String, Value> {
+        document
+            .into_iter()
+            .filter_map(|(field, value)| {
+                if field == GEO_FIELD {
+                    return Some((field, value));
+                }
+                let value = self.flatten_value(value, 1, false)?;
+                Some((field, value))
+            })
+            .collect()
+    }
+}
+
+pub fn check_nested_flattening(flattening: &NestedFlattening) -> Result<()> {
+    if flattening.max_depth == Some(0) {
+        return Err(IndexError::InvalidNestedFlattening(
+            "The `maxDepth` must be at least 1, the depth of the top-level fields.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns the payload with the objects of its documents collapsed by the flattening settings.
+pub fn flatten_documents(file: &mut File, flattening: &NestedFlattening) -> Result<File> {
+    let mut flattened_file = tempfile::tempfile()?;
+    let mut builder = DocumentBatchBuilder::new(&mut flattened_file)?;
+    for_each_document(file, |_, document| {
+        let document = flattening.flatten_document(document);
+        builder.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+        Ok(())
+    })?;
+    builder.finish()?;
+    flattened_file.seek(SeekFrom::Start(0))?;
+
+    Ok(flattened_file)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn flatten(flattening: Value, document: Value) -> Value {
+        let flattening: NestedFlattening = serde_json::from_value(flattening).unwrap();
+        let document = document.as_object().unwrap().clone();
+        Value::Object(flattening.flatten_document(document))
+    }
+
+    #[test]
+    fn test_flatten_document() {
+        let document = json!({
+            "id": 1,
+            "_geo": { "lat": 45.0, "lng": 2.0 },
+            "author": { "name": "Carol", "address": { "city": "Paris" } },
+            "reviews": [{ "stars": 4 }, 5],
+        });
+
+        assert_eq!(flatten(json!({}), document.clone()), document);
+
+        assert_eq!(
+            flatten(json!({ "maxDepth": 2 }), document.clone()),
+            json!({
+                "id": 1,
+                "_geo": { "lat": 45.0, "lng": 2.0 },
+                "author": { "name": "Carol", "address": "{\"city\":\"Paris\"}" },
+                "reviews": [{ "stars": 4 }, 5],
+            })
+        );
+
+        assert_eq!(
+            flatten(
+                json!({ "maxDepth": 1, "jsonLeavesAsKeywords": false }),
+                document.clone()
+            ),
+            json!({
+                "id": 1,
+                "_geo": { "lat": 45.0, "lng": 2.0 },
+                "reviews": [5],
+            })
+        );
+
+        assert_eq!(
+            flatten(json!({ "arraysOfObjects": "collapse" }), document),
+            json!({
+                "id": 1,
+                "_geo": { "lat": 45.0, "lng": 2.0 },
+                "author": { "name": "Carol", "address": { "city": "Paris" } },
+                "reviews": ["{\"stars\":4}", 5],
+            })
+        );
+    }
+}
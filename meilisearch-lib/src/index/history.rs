@@ -0,0 +1,72 @@
+use milli::heed::types::{SerdeJson, Str};
+use milli::heed::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use super::error::Result;
+use super::index::Index;
+use super::updates::{Settings, Unchecked};
+
+/// The key of the history of the settings in the main database of milli.
+const SETTINGS_HISTORY_KEY: &str = "settings-history";
+
+/// The number of versions kept in the history, the oldest ones are dropped first.
+const MAX_SETTINGS_VERSIONS: usize = 20;
+
+/// The settings of an index as they were after one of their updates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsVersion {
+    /// Starts at 1 and is incremented by each update of the settings, it isn't reused once the
+    /// version is dropped from the history.
+    pub version: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    pub settings: Settings<Unchecked>,
+}
+
+impl Index {
+    /// Returns the last versions of the settings of the index, from the oldest to the newest.
+    pub fn settings_history(&self) -> Result<Vec<SettingsVersion>> {
+        let txn = self.read_txn()?;
+        settings_history(self, &txn)
+    }
+
+    /// Returns the version `version` of the settings, if it's still in the history.
+    pub fn settings_version(&self, version: u64) -> Result<Option<SettingsVersion>> {
+        Ok(self
+            .settings_history()?
+            .into_iter()
+            .find(|entry| entry.version == version))
+    }
+}
+
+fn settings_history(index: &milli::Index, txn: &RoTxn) -> Result<Vec<SettingsVersion>> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<Vec<SettingsVersion>>>(txn, SETTINGS_HISTORY_KEY)?
+        .unwrap_or_default())
+}
+
+/// Appends the settings of the index, as updated by the transaction, to its history.
+pub fn record_settings_version(index: &Index, txn: &mut RwTxn) -> Result<()> {
+    let settings = index.settings_txn(txn)?.into_unchecked();
+    let mut history = settings_history(index, txn)?;
+    let version = history.last().map_or(1, |last| last.version + 1);
+    history.push(SettingsVersion {
+        version,
+        updated_at: OffsetDateTime::now_utc(),
+        settings,
+    });
+    if history.len() > MAX_SETTINGS_VERSIONS {
+        history.drain(..history.len() - MAX_SETTINGS_VERSIONS);
+    }
+
+    index.main.put::<_, Str, SerdeJson<Vec<SettingsVersion>>>(
+        txn,
+        SETTINGS_HISTORY_KEY,
+        &history,
+    )?;
+
+    Ok(())
+}
@@ -1,29 +1,41 @@
 use std::collections::BTreeSet;
-use std::fs::create_dir_all;
+use std::fs::{self, create_dir_all};
+use std::io::Write;
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use fst::IntoStreamer;
-use milli::heed::{EnvOpenOptions, RoTxn};
+use milli::heed::{EnvClosingEvent, EnvOpenOptions, RoTxn};
 use milli::update::{IndexerConfig, Setting};
-use milli::{obkv_to_json, FieldDistribution, DEFAULT_VALUES_PER_FACET};
+use milli::{obkv_to_json, AscDesc, FieldDistribution, SortError, DEFAULT_VALUES_PER_FACET};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::index::search::DEFAULT_PAGINATION_LIMITED_TO;
+use crate::index::search::{parse_filter, DEFAULT_PAGINATION_MAX_TOTAL_HITS};
 use crate::EnvSizer;
 
 use super::error::IndexError;
 use super::error::Result;
-use super::updates::{FacetingSettings, MinWordSizeTyposSetting, PaginationSettings, TypoSettings};
+use super::updates::{
+    attachments, document_schema, expires_at_attribute, ingestion_pipeline, is_frozen,
+    nested_flattening, ranking_experiment, rules, search_cutoff_ms, sort_facet_values_by,
+    FacetingSettings, MinWordSizeTyposSetting, PaginationSettings, TypoSettings,
+};
+use super::vectors::store_missing_vectors;
 use super::{Checked, Settings};
 
 pub type Document = Map<String, Value>;
 
+/// The file of the index directory storing the size of its map, in bytes, when it was set for the
+/// index rather than taken from the options.
+const MAP_SIZE_FILE_NAME: &str = "map_size";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IndexMeta {
@@ -32,6 +44,8 @@ pub struct IndexMeta {
     #[serde(with = "time::serde::rfc3339")]
     pub updated_at: OffsetDateTime,
     pub primary_key: Option<String>,
+    #[serde(default)]
+    pub frozen: bool,
 }
 
 impl IndexMeta {
@@ -44,10 +58,12 @@ impl IndexMeta {
         let created_at = index.created_at(txn)?;
         let updated_at = index.updated_at(txn)?;
         let primary_key = index.primary_key(txn)?.map(String::from);
+        let frozen = is_frozen(index, txn)?;
         Ok(Self {
             created_at,
             updated_at,
             primary_key,
+            frozen,
         })
     }
 }
@@ -73,6 +89,13 @@ pub struct Index {
     pub inner: Arc<milli::Index>,
     #[derivative(Debug = "ignore")]
     pub indexer_config: Arc<IndexerConfig>,
+    /// The size of the map of the environment, the size the index can't outgrow.
+    pub(super) map_size: usize,
+    pub(super) has_custom_map_size: bool,
+    /// Set when the task writing to this instance of the index is aborted, its writes are then
+    /// rolled back rather than committed.
+    #[derivative(Debug = "ignore")]
+    aborted: Arc<AtomicBool>,
 }
 
 impl Deref for Index {
@@ -92,21 +115,88 @@ impl Index {
     ) -> Result<Self> {
         log::debug!("opening index in {}", path.as_ref().display());
         create_dir_all(&path)?;
+        let custom_map_size = Self::read_map_size(&path)?;
+        let map_size = custom_map_size.unwrap_or(size);
         let mut options = EnvOpenOptions::new();
-        options.map_size(size);
+        options.map_size(map_size);
         let inner = Arc::new(milli::Index::new(options, &path)?);
+
+        // The vectors of the indexes created before the vectors were stored are stored once.
+        let mut txn = inner.write_txn()?;
+        store_missing_vectors(&inner, &mut txn)?;
+        txn.commit()?;
+
         Ok(Index {
             inner,
             uuid,
             indexer_config: update_handler,
+            map_size,
+            has_custom_map_size: custom_map_size.is_some(),
+            aborted: Arc::default(),
         })
     }
 
+    /// Returns an instance of the index whose writes fail with `IndexError::WriteAborted`, rather
+    /// than being committed, once `aborted` is set.
+    pub fn abortable(self, aborted: Arc<AtomicBool>) -> Self {
+        Self { aborted, ..self }
+    }
+
+    /// Fails if the writes of this instance were aborted, to be checked before each commit.
+    pub(super) fn check_aborted(&self) -> Result<()> {
+        if self.aborted.load(Ordering::Relaxed) {
+            return Err(IndexError::WriteAborted);
+        }
+        Ok(())
+    }
+
     /// Asynchronously close the underlying index
     pub fn close(self) {
         self.inner.as_ref().clone().prepare_for_closing();
     }
 
+    /// Asynchronously close the underlying index, returning the event signaled once its
+    /// environment is closed. It can't be opened again before.
+    pub fn prepare_for_closing(self) -> EnvClosingEvent {
+        self.inner.as_ref().clone().prepare_for_closing()
+    }
+
+    /// Whether other instances of the index are alive, e.g. held by a search or a task.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.inner) > 1
+    }
+
+    /// Closes the underlying index and waits for its environment to be closed, which only
+    /// happens once every other instance of the index is dropped.
+    pub fn close_and_wait(self) {
+        let closing = self.inner.as_ref().clone().prepare_for_closing();
+        drop(self);
+        closing.wait();
+    }
+
+    pub fn map_size(&self) -> usize {
+        self.map_size
+    }
+
+    /// Sets the size of the map of the index stored in `path`, used the next time it's opened.
+    pub fn write_map_size(path: impl AsRef<Path>, size: usize) -> Result<()> {
+        fs::write(path.as_ref().join(MAP_SIZE_FILE_NAME), size.to_string())?;
+        Ok(())
+    }
+
+    fn read_map_size(path: impl AsRef<Path>) -> Result<Option<usize>> {
+        let path = path.as_ref().join(MAP_SIZE_FILE_NAME);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let size = fs::read_to_string(path)?;
+        let size = size
+            .trim()
+            .parse()
+            .map_err(|e| IndexError::Internal(Box::new(e)))?;
+        Ok(Some(size))
+    }
+
     pub fn stats(&self) -> Result<IndexStats> {
         let rtxn = self.read_txn()?;
 
@@ -199,12 +289,13 @@ impl Index {
                 self.max_values_per_facet(txn)?
                     .unwrap_or(DEFAULT_VALUES_PER_FACET),
             ),
+            sort_facet_values_by: Setting::Set(sort_facet_values_by(self, txn)?),
         };
 
         let pagination = PaginationSettings {
-            limited_to: Setting::Set(
+            max_total_hits: Setting::Set(
                 self.pagination_limited_to(txn)?
-                    .unwrap_or(DEFAULT_PAGINATION_LIMITED_TO),
+                    .unwrap_or(DEFAULT_PAGINATION_MAX_TOTAL_HITS),
             ),
         };
 
@@ -229,28 +320,211 @@ impl Index {
             typo_tolerance: Setting::Set(typo_tolerance),
             faceting: Setting::Set(faceting),
             pagination: Setting::Set(pagination),
+            search_cutoff_ms: match search_cutoff_ms(self, txn)? {
+                Some(cutoff) => Setting::Set(cutoff),
+                None => Setting::Reset,
+            },
+            ranking_experiment: match ranking_experiment(self, txn)? {
+                Some(experiment) => Setting::Set(experiment),
+                None => Setting::Reset,
+            },
+            rules: Setting::Set(rules(self, txn)?),
+            expires_at_attribute: match expires_at_attribute(self, txn)? {
+                Some(attribute) => Setting::Set(attribute),
+                None => Setting::Reset,
+            },
+            document_schema: match document_schema(self, txn)? {
+                Some(schema) => Setting::Set(schema),
+                None => Setting::Reset,
+            },
+            ingestion_pipeline: Setting::Set(ingestion_pipeline(self, txn)?),
+            nested_flattening: Setting::Set(nested_flattening(self, txn)?),
+            attachments: Setting::Set(attachments(self, txn)?),
             _kind: PhantomData,
         })
     }
 
-    /// Return the total number of documents contained in the index + the selected documents.
-    pub fn retrieve_documents<S: AsRef<str>>(
+    /// Writes a page of the documents matching the filter as a JSON object holding the documents
+    /// in its `results`, along with the `offset`, the `limit` and the `total` number of documents
+    /// matching the filter. Returns the total. The documents are in the order of the sort, which
+    /// requires the `sort` ranking rule, or in the order they are stored when there's no sort.
+    ///
+    /// Nothing is written before the filter and the sort are checked, and the documents are
+    /// serialized one at a time, so the page is never held in memory.
+    pub fn write_documents<S: AsRef<str>>(
         &self,
         offset: usize,
         limit: usize,
         attributes_to_retrieve: Option<Vec<S>>,
-    ) -> Result<(u64, Vec<Document>)> {
+        filter: Option<&Value>,
+        sort: Option<&[String]>,
+        mut writer: impl Write,
+    ) -> Result<u64> {
+        let txn = self.read_txn()?;
+
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+
+        let filter = match filter {
+            Some(filter) => parse_filter(&self.prepare_filter(&txn, filter)?)?,
+            None => None,
+        };
+
+        let (number_of_documents, obkvs) = match (filter, sort) {
+            (None, None) => {
+                let mut obkvs = Vec::new();
+                for entry in self.documents.range(&txn, &(..))?.skip(offset).take(limit) {
+                    let (_id, obkv) = entry?;
+                    obkvs.push(obkv);
+                }
+                (self.number_of_documents(&txn)?, obkvs)
+            }
+            (Some(filter), None) => {
+                let candidates = filter.evaluate(&txn, self)?;
+                let page = candidates.iter().skip(offset).take(limit);
+                let obkvs = self.documents(&txn, page)?.into_iter();
+                (candidates.len(), obkvs.map(|(_id, obkv)| obkv).collect())
+            }
+            (filter, Some(sort)) => {
+                let sort = match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
+                    Ok(sort) => sort,
+                    Err(asc_desc_error) => {
+                        return Err(IndexError::Milli(SortError::from(asc_desc_error).into()))
+                    }
+                };
+
+                let mut search = self.search(&txn);
+                search.offset(offset);
+                search.limit(limit);
+                search.sort_criteria(sort);
+                if let Some(filter) = filter {
+                    search.filter(filter);
+                }
+                let milli::SearchResult {
+                    documents_ids,
+                    candidates,
+                    ..
+                } = search.execute()?;
+
+                let obkvs = self.documents(&txn, documents_ids)?.into_iter();
+                (candidates.len(), obkvs.map(|(_id, obkv)| obkv).collect())
+            }
+        };
+
+        writer.write_all(b"{\"results\":[")?;
+        for (i, obkv) in obkvs.into_iter().enumerate() {
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+            let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+            let document = match &attributes_to_retrieve {
+                Some(attributes_to_retrieve) => permissive_json_pointer::select_values(
+                    &document,
+                    attributes_to_retrieve.iter().map(|s| s.as_ref()),
+                ),
+                None => document,
+            };
+            serde_json::to_writer(&mut writer, &document)?;
+        }
+        write!(
+            writer,
+            "],\"offset\":{},\"limit\":{},\"total\":{}}}",
+            offset, limit, number_of_documents
+        )?;
+        writer.flush()?;
+
+        Ok(number_of_documents)
+    }
+
+    /// Writes the documents matching the filter, or every document, as NDJSON. Returns the number
+    /// of documents written.
+    pub fn export_documents<S: AsRef<str>>(
+        &self,
+        attributes_to_retrieve: Option<Vec<S>>,
+        filter: Option<&Value>,
+        mut writer: impl Write,
+    ) -> Result<u64> {
         let txn = self.read_txn()?;
 
         let fields_ids_map = self.fields_ids_map(&txn)?;
         let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
 
-        let iter = self.documents.range(&txn, &(..))?.skip(offset).take(limit);
+        let filter = match filter {
+            Some(filter) => parse_filter(&self.prepare_filter(&txn, filter)?)?,
+            None => None,
+        };
+
+        let mut count = 0;
+        let mut write_document = |obkv| -> Result<()> {
+            let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+            let document = match &attributes_to_retrieve {
+                Some(attributes_to_retrieve) => permissive_json_pointer::select_values(
+                    &document,
+                    attributes_to_retrieve.iter().map(|s| s.as_ref()),
+                ),
+                None => document,
+            };
+            serde_json::to_writer(&mut writer, &document)?;
+            writer.write_all(b"\n")?;
+            count += 1;
+            Ok(())
+        };
+
+        match filter {
+            Some(filter) => {
+                let candidates = filter.evaluate(&txn, self)?;
+                for id in candidates {
+                    for (_id, obkv) in self.documents(&txn, std::iter::once(id))? {
+                        write_document(obkv)?;
+                    }
+                }
+            }
+            None => {
+                for entry in self.documents.range(&txn, &(..))? {
+                    let (_id, obkv) = entry?;
+                    write_document(obkv)?;
+                }
+            }
+        }
+
+        writer.flush()?;
+
+        Ok(count)
+    }
+
+    /// Returns the documents with the given ids, in the order of the ids, along with the ids
+    /// matching no document.
+    pub fn retrieve_documents_by_ids<S: AsRef<str>>(
+        &self,
+        ids: &[String],
+        attributes_to_retrieve: Option<Vec<S>>,
+    ) -> Result<(Vec<Document>, Vec<String>)> {
+        let txn = self.read_txn()?;
+
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let external_documents_ids = self.external_documents_ids(&txn)?;
 
         let mut documents = Vec::new();
+        let mut missing = Vec::new();
+
+        for id in ids {
+            let obkv = match external_documents_ids.get(id.as_bytes()) {
+                Some(internal_id) => self
+                    .documents(&txn, std::iter::once(internal_id))?
+                    .into_iter()
+                    .next()
+                    .map(|(_, obkv)| obkv),
+                None => None,
+            };
+            let obkv = match obkv {
+                Some(obkv) => obkv,
+                None => {
+                    missing.push(id.clone());
+                    continue;
+                }
+            };
 
-        for entry in iter {
-            let (_id, obkv) = entry?;
             let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
             let document = match &attributes_to_retrieve {
                 Some(attributes_to_retrieve) => permissive_json_pointer::select_values(
@@ -262,9 +536,7 @@ impl Index {
             documents.push(document);
         }
 
-        let number_of_documents = self.number_of_documents(&txn)?;
-
-        Ok((number_of_documents, documents))
+        Ok((documents, missing))
     }
 
     pub fn retrieve_document<S: AsRef<str>>(
@@ -306,13 +578,21 @@ impl Index {
     }
 
     pub fn snapshot(&self, path: impl AsRef<Path>) -> Result<()> {
-        let mut dst = path.as_ref().join(format!("indexes/{}/", self.uuid));
-        create_dir_all(&dst)?;
-        dst.push("data.mdb");
+        self.copy_to(path.as_ref().join(format!("indexes/{}/", self.uuid)))
+    }
+
+    /// Copies the environment of the index into the directory `path`, from which the copy is
+    /// opened as an index.
+    pub fn copy_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        create_dir_all(&path)?;
+        let dst = path.as_ref().join("data.mdb");
         let _txn = self.write_txn()?;
         self.inner
             .env
             .copy_to_path(dst, milli::heed::CompactionOption::Enabled)?;
+        if self.has_custom_map_size {
+            Self::write_map_size(&path, self.map_size)?;
+        }
         Ok(())
     }
 }
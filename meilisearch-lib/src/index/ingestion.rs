@@ -0,0 +1,175 @@
+use std::fs::File;
+use std::io::{Cursor, Seek, Write};
+
+use milli::documents::DocumentBatchBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime};
+
+use super::addition::for_each_document;
+use super::error::{IndexError, Result};
+use super::index::Index;
+use super::updates::ingestion_pipeline;
+
+/// A transformation of the top-level fields of the documents, applied while the payload of a
+/// document addition is received. The steps of the pipeline of an index are applied in order.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub enum IngestionStep {
+    /// Moves the value of the field `from` to the field `to`, replacing its value.
+    Rename { from: String, to: String },
+    /// Removes the fields.
+    Drop { fields: Vec<String> },
+    /// Replaces an RFC 3339 date, or a `YYYY-MM-DD` date, by its unix timestamp in seconds. The
+    /// values that aren't dates are left as is.
+    ParseDate { field: String },
+    /// Replaces a string by the array of its non-empty parts around the separator, trimmed.
+    Split { field: String, separator: String },
+}
+
+impl Index {
+    pub fn ingestion_pipeline(&self) -> Result<Vec<IngestionStep>> {
+        let txn = self.read_txn()?;
+        ingestion_pipeline(self, &txn)
+    }
+}
+
+/// Checks that the steps of a pipeline name their fields, and that the strings are split around
+/// a non-empty separator.
+pub fn check_ingestion_pipeline(steps: &[IngestionStep]) -> Result<()> {
+    let invalid = |message: &str| Err(IndexError::InvalidIngestionPipeline(message.to_string()));
+    for step in steps {
+        match step {
+            IngestionStep::Rename { from, to } if from.is_empty() || to.is_empty() => {
+                return invalid("The fields of a `rename` step can't be empty.")
+            }
+            IngestionStep::Drop { fields } if fields.iter().any(String::is_empty) => {
+                return invalid("The fields of a `drop` step can't be empty.")
+            }
+            IngestionStep::ParseDate { field } if field.is_empty() => {
+                return invalid("The field of a `parseDate` step can't be empty.")
+            }
+            IngestionStep::Split { field, separator }
+                if field.is_empty() || separator.is_empty() =>
+            {
+                return invalid("The field and the separator of a `split` step can't be empty.")
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the documents of the payload, transformed by `transform` along with their position, to
+/// the writer. Returns the number of documents.
+pub fn transform_documents(
+    file: &mut File,
+    writer: impl Write + Seek,
+    mut transform: impl FnMut(usize, &mut Map<String, Value>) -> Result<()>,
+) -> Result<usize> {
+    let mut builder = DocumentBatchBuilder::new(writer)?;
+    let mut count = 0;
+    for_each_document(file, |position, mut document| {
+        transform(position, &mut document)?;
+        builder.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+        count += 1;
+        Ok(())
+    })?;
+    builder.finish()?;
+
+    Ok(count)
+}
+
+pub fn apply_ingestion_pipeline(steps: &[IngestionStep], document: &mut Map<String, Value>) {
+    for step in steps {
+        match step {
+            IngestionStep::Rename { from, to } => {
+                if let Some(value) = document.remove(from) {
+                    document.insert(to.clone(), value);
+                }
+            }
+            IngestionStep::Drop { fields } => {
+                for field in fields {
+                    document.remove(field);
+                }
+            }
+            IngestionStep::ParseDate { field } => {
+                if let Some(value) = document.get_mut(field) {
+                    if let Some(timestamp) = value.as_str().and_then(parse_date) {
+                        *value = Value::from(timestamp);
+                    }
+                }
+            }
+            IngestionStep::Split { field, separator } => {
+                if let Some(value) = document.get_mut(field) {
+                    if let Some(string) = value.as_str() {
+                        let parts = string
+                            .split(separator.as_str())
+                            .map(str::trim)
+                            .filter(|part| !part.is_empty())
+                            .map(|part| Value::String(part.to_string()))
+                            .collect();
+                        *value = Value::Array(parts);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn parse_date(date: &str) -> Option<i64> {
+    if let Ok(date) = OffsetDateTime::parse(date, &Rfc3339) {
+        return Some(date.unix_timestamp());
+    }
+    let date = Date::parse(date, format_description!("[year]-[month]-[day]")).ok()?;
+    Some(date.midnight().assume_utc().unix_timestamp())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_apply_ingestion_pipeline() {
+        let steps: Vec<IngestionStep> = serde_json::from_value(json!([
+            { "rename": { "from": "name", "to": "title" } },
+            { "drop": { "fields": ["internal", "missing"] } },
+            { "parseDate": { "field": "published" } },
+            { "parseDate": { "field": "updated" } },
+            { "parseDate": { "field": "title" } },
+            { "split": { "field": "tags", "separator": "," } },
+        ]))
+        .unwrap();
+
+        let mut document = json!({
+            "id": 1,
+            "name": "Carol",
+            "internal": true,
+            "published": "2022-06-01T14:00:00+02:00",
+            "updated": "2022-06-01",
+            "tags": "fantasy, ,adventure ",
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        apply_ingestion_pipeline(&steps, &mut document);
+
+        assert_eq!(
+            Value::Object(document),
+            json!({
+                "id": 1,
+                "title": "Carol",
+                "published": 1654084800,
+                "updated": 1654041600,
+                "tags": ["fantasy", "adventure"],
+            })
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use fst::Streamer;
+use milli::heed::types::ByteSlice;
+use milli::FieldId;
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use super::error::Result;
+use super::index::Index;
+
+/// The number of ids listed in the description of an anomaly, the others are only counted.
+const MAX_SAMPLES: usize = 10;
+
+impl Index {
+    /// Verifies that the databases of the index are consistent with each other, and returns a
+    /// description of each anomaly found: the documents and the mapping of their external ids to
+    /// the internal ones, the field id map, and the facet databases. The index is only read.
+    pub fn check_integrity(&self) -> Result<Vec<String>> {
+        let txn = self.read_txn()?;
+        let mut anomalies = Vec::new();
+
+        let documents_ids = self.documents_ids(&txn)?;
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+        let primary_key_id = self
+            .primary_key(&txn)?
+            .and_then(|primary_key| fields_ids_map.id(primary_key));
+
+        // external ids to internal ids
+        let external_documents_ids = self.external_documents_ids(&txn)?;
+        let external_ids = external_documents_ids.to_fst()?;
+        let mut mapped_ids = RoaringBitmap::new();
+        let mut dangling = Vec::new();
+        let mut shared = Vec::new();
+        let mut stream = external_ids.stream();
+        while let Some((external_id, internal_id)) = stream.next() {
+            let internal_id = internal_id as u32;
+            if !documents_ids.contains(internal_id) {
+                dangling.push(String::from_utf8_lossy(external_id).into_owned());
+            }
+            if !mapped_ids.insert(internal_id) {
+                shared.push(internal_id);
+            }
+        }
+        if !dangling.is_empty() {
+            anomalies.push(format!(
+                "{} external ids point to a missing document: {}.",
+                dangling.len(),
+                samples(&dangling)
+            ));
+        }
+        if !shared.is_empty() {
+            anomalies.push(format!(
+                "{} internal ids are shared by several external ids: {}.",
+                shared.len(),
+                samples(&shared)
+            ));
+        }
+        let unmapped = &documents_ids - &mapped_ids;
+        if !unmapped.is_empty() {
+            anomalies.push(format!(
+                "{} documents have no external id: {}.",
+                unmapped.len(),
+                samples(&unmapped)
+            ));
+        }
+
+        // stored documents
+        let mut stored_ids = RoaringBitmap::new();
+        let mut mismatched = Vec::new();
+        let mut unknown_fields = BTreeSet::new();
+        for entry in self.documents.range(&txn, &(..))? {
+            let (id, obkv) = entry?;
+            let id = id.get();
+            stored_ids.insert(id);
+            for (field_id, _) in obkv.iter() {
+                if fields_ids_map.name(field_id).is_none() {
+                    unknown_fields.insert(field_id);
+                }
+            }
+            if let Some(value) = primary_key_id.and_then(|primary_key_id| obkv.get(primary_key_id))
+            {
+                let external_id = match serde_json::from_slice(value)? {
+                    Value::String(id) => id,
+                    id => id.to_string(),
+                };
+                if external_documents_ids.get(external_id.as_bytes()) != Some(id) {
+                    mismatched.push(external_id);
+                }
+            }
+        }
+        let unstored = &documents_ids - &stored_ids;
+        if !unstored.is_empty() {
+            anomalies.push(format!(
+                "{} documents are listed but not stored: {}.",
+                unstored.len(),
+                samples(&unstored)
+            ));
+        }
+        let unlisted = &stored_ids - &documents_ids;
+        if !unlisted.is_empty() {
+            anomalies.push(format!(
+                "{} documents are stored but not listed: {}.",
+                unlisted.len(),
+                samples(&unlisted)
+            ));
+        }
+        if !mismatched.is_empty() {
+            anomalies.push(format!(
+                "{} documents aren't found from the value of their primary key: {}.",
+                mismatched.len(),
+                samples(&mismatched)
+            ));
+        }
+
+        // field id map
+        if !unknown_fields.is_empty() {
+            anomalies.push(format!(
+                "The documents have fields missing from the field id map: {}.",
+                samples(&unknown_fields)
+            ));
+        }
+        let unknown_names: Vec<_> = self
+            .field_distribution(&txn)?
+            .into_keys()
+            .filter(|name| fields_ids_map.id(name).is_none())
+            .collect();
+        if !unknown_names.is_empty() {
+            anomalies.push(format!(
+                "The field distribution has fields missing from the field id map: {}.",
+                samples(&unknown_names)
+            ));
+        }
+
+        // facet databases
+        for field_id in self.faceted_fields_ids(&txn)? {
+            let faceted = self.number_faceted_documents_ids(&txn, field_id)?
+                | self.string_faceted_documents_ids(&txn, field_id)?;
+            let missing = &faceted - &documents_ids;
+            if !missing.is_empty() {
+                anomalies.push(format!(
+                    "The facets of the field `{}` refer to {} missing documents: {}.",
+                    fields_ids_map.name(field_id).unwrap_or_default(),
+                    missing.len(),
+                    samples(&missing)
+                ));
+            }
+        }
+        let mut missing_numbers = RoaringBitmap::new();
+        let mut unknown_facets = BTreeSet::new();
+        for entry in self
+            .facet_id_f64_docids
+            .remap_key_type::<ByteSlice>()
+            .iter(&txn)?
+        {
+            let (key, docids) = entry?;
+            // the keys start with the big endian field id
+            if let [first, second, ..] = key {
+                let field_id = FieldId::from_be_bytes([*first, *second]);
+                if fields_ids_map.name(field_id).is_none() {
+                    unknown_facets.insert(field_id);
+                }
+            }
+            missing_numbers |= docids - &documents_ids;
+        }
+        if !missing_numbers.is_empty() {
+            anomalies.push(format!(
+                "The numeric facet database refers to {} missing documents: {}.",
+                missing_numbers.len(),
+                samples(&missing_numbers)
+            ));
+        }
+        if !unknown_facets.is_empty() {
+            anomalies.push(format!(
+                "The numeric facet database has fields missing from the field id map: {}.",
+                samples(&unknown_facets)
+            ));
+        }
+
+        Ok(anomalies)
+    }
+}
+
+/// Lists the first `MAX_SAMPLES` values, followed by an ellipsis if there are more.
+fn samples<T: Display>(values: impl IntoIterator<Item = T>) -> String {
+    let mut values = values.into_iter();
+    let mut listed: Vec<_> = values
+        .by_ref()
+        .take(MAX_SAMPLES)
+        .map(|value| format!("`{}`", value))
+        .collect();
+    if values.next().is_some() {
+        listed.push("...".to_string());
+    }
+    listed.join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_samples() {
+        assert_eq!(samples(&[1, 2, 3]), "`1`, `2`, `3`");
+        assert_eq!(
+            samples(0..20),
+            "`0`, `1`, `2`, `3`, `4`, `5`, `6`, `7`, `8`, `9`, ..."
+        );
+    }
+}
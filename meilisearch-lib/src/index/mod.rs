@@ -1,13 +1,37 @@
 pub use search::{
-    SearchQuery, SearchResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
+    merge_federated_results, FacetSearchQuery, FacetSearchResult, FederatedQuery,
+    FederatedSearchResult, HitsInfo, MatchingStrategy, SearchQuery, SearchResult, SimilarQuery,
+    SimilarResult, SuggestQuery, SuggestResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
     DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
 };
 pub use updates::{apply_settings_to_builder, Checked, Facets, Settings, Unchecked};
 
+pub use addition::{
+    DocumentAdditionOutcome, DocumentError, DocumentPayload, OnDuplicate, MAX_DOCUMENT_ERRORS,
+};
+pub use diff::{SettingsChange, SettingsDiff};
+pub use edition::{check_edition_function, DocumentEditionResult};
+pub use history::SettingsVersion;
+pub(crate) use search::edit_distance;
+pub use stats::{DocumentStats, FieldStats};
+
+mod addition;
+pub mod attachment;
+mod diff;
 mod dump;
+mod edition;
 pub mod error;
+mod expiration;
+pub mod flattening;
+mod history;
+pub mod ingestion;
+mod integrity;
+pub mod rules;
 mod search;
+mod stats;
 pub mod updates;
+mod vectors;
+mod warmup;
 
 #[allow(clippy::module_inception)]
 mod index;
@@ -26,17 +50,29 @@ pub use test::MockIndex as Index;
 pub mod test {
     use std::path::Path;
     use std::path::PathBuf;
+    use std::sync::atomic::AtomicBool;
     use std::sync::Arc;
 
+    use milli::heed::EnvClosingEvent;
     use milli::update::IndexerConfig;
-    use milli::update::{DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsMethod};
+    use milli::update::{DocumentDeletionResult, IndexDocumentsMethod};
+    use milli::FieldDistribution;
     use nelson::Mocker;
+    use serde_json::Value;
+    use time::OffsetDateTime;
     use uuid::Uuid;
 
+    use super::attachment::AttachmentSettings;
     use super::error::Result;
     use super::index::Index;
+    use super::ingestion::IngestionStep;
     use super::Document;
-    use super::{Checked, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings};
+    use super::{
+        Checked, DocumentAdditionOutcome, DocumentPayload, DocumentStats, FacetSearchQuery,
+        FacetSearchResult, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings,
+        SettingsDiff, SettingsVersion, SimilarQuery, SimilarResult, SuggestQuery, SuggestResult,
+    };
+    use crate::search_log::QueryCount;
     use crate::update_file_store::UpdateFileStore;
 
     #[derive(Clone)]
@@ -76,6 +112,13 @@ pub mod test {
             }
         }
 
+        pub fn abortable(self, aborted: Arc<AtomicBool>) -> Self {
+            match self {
+                MockIndex::Real(index) => MockIndex::Real(index.abortable(aborted)),
+                mock => mock,
+            }
+        }
+
         pub fn stats(&self) -> Result<IndexStats> {
             match self {
                 MockIndex::Real(index) => index.stats(),
@@ -83,6 +126,34 @@ pub mod test {
             }
         }
 
+        pub fn document_stats(&self) -> Result<DocumentStats> {
+            match self {
+                MockIndex::Real(index) => index.document_stats(),
+                MockIndex::Mock(m) => unsafe { m.get("document_stats").call(()) },
+            }
+        }
+
+        pub fn check_integrity(&self) -> Result<Vec<String>> {
+            match self {
+                MockIndex::Real(index) => index.check_integrity(),
+                MockIndex::Mock(m) => unsafe { m.get("check_integrity").call(()) },
+            }
+        }
+
+        pub fn warmup(&self) -> Result<u64> {
+            match self {
+                MockIndex::Real(index) => index.warmup(),
+                MockIndex::Mock(m) => unsafe { m.get("warmup").call(()) },
+            }
+        }
+
+        pub fn nested_field_distribution(&self) -> Result<FieldDistribution> {
+            match self {
+                MockIndex::Real(index) => index.nested_field_distribution(),
+                MockIndex::Mock(m) => unsafe { m.get("nested_field_distribution").call(()) },
+            }
+        }
+
         pub fn meta(&self) -> Result<IndexMeta> {
             match self {
                 MockIndex::Real(index) => index.meta(),
@@ -96,15 +167,71 @@ pub mod test {
             }
         }
 
-        pub fn retrieve_documents<S: AsRef<str>>(
+        pub fn settings_history(&self) -> Result<Vec<SettingsVersion>> {
+            match self {
+                MockIndex::Real(index) => index.settings_history(),
+                MockIndex::Mock(m) => unsafe { m.get("settings_history").call(()) },
+            }
+        }
+
+        pub fn settings_version(&self, version: u64) -> Result<Option<SettingsVersion>> {
+            match self {
+                MockIndex::Real(index) => index.settings_version(version),
+                MockIndex::Mock(m) => unsafe { m.get("settings_version").call(version) },
+            }
+        }
+
+        pub fn settings_diff(&self, proposed: Settings<Checked>) -> Result<SettingsDiff> {
+            match self {
+                MockIndex::Real(index) => index.settings_diff(proposed),
+                MockIndex::Mock(m) => unsafe { m.get("settings_diff").call(proposed) },
+            }
+        }
+
+        pub fn write_documents<S: AsRef<str>>(
             &self,
             offset: usize,
             limit: usize,
             attributes_to_retrieve: Option<Vec<S>>,
-        ) -> Result<(u64, Vec<Document>)> {
+            filter: Option<&Value>,
+            sort: Option<&[String]>,
+            writer: impl std::io::Write,
+        ) -> Result<u64> {
+            match self {
+                MockIndex::Real(index) => index.write_documents(
+                    offset,
+                    limit,
+                    attributes_to_retrieve,
+                    filter,
+                    sort,
+                    writer,
+                ),
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn export_documents<S: AsRef<str>>(
+            &self,
+            attributes_to_retrieve: Option<Vec<S>>,
+            filter: Option<&Value>,
+            writer: impl std::io::Write,
+        ) -> Result<u64> {
             match self {
                 MockIndex::Real(index) => {
-                    index.retrieve_documents(offset, limit, attributes_to_retrieve)
+                    index.export_documents(attributes_to_retrieve, filter, writer)
+                }
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn retrieve_documents_by_ids<S: AsRef<str>>(
+            &self,
+            ids: &[String],
+            attributes_to_retrieve: Option<Vec<S>>,
+        ) -> Result<(Vec<Document>, Vec<String>)> {
+            match self {
+                MockIndex::Real(index) => {
+                    index.retrieve_documents_by_ids(ids, attributes_to_retrieve)
                 }
                 MockIndex::Mock(_) => todo!(),
             }
@@ -135,6 +262,13 @@ pub mod test {
             }
         }
 
+        pub fn copy_to(&self, path: impl AsRef<Path>) -> Result<()> {
+            match self {
+                MockIndex::Real(index) => index.copy_to(path),
+                MockIndex::Mock(m) => unsafe { m.get("copy_to").call(path.as_ref()) },
+            }
+        }
+
         pub fn close(self) {
             match self {
                 MockIndex::Real(index) => index.close(),
@@ -142,6 +276,52 @@ pub mod test {
             }
         }
 
+        pub fn prepare_for_closing(self) -> EnvClosingEvent {
+            match self {
+                MockIndex::Real(index) => index.prepare_for_closing(),
+                MockIndex::Mock(m) => unsafe { m.get("prepare_for_closing").call(()) },
+            }
+        }
+
+        pub fn is_shared(&self) -> bool {
+            match self {
+                MockIndex::Real(index) => index.is_shared(),
+                MockIndex::Mock(m) => unsafe { m.get("is_shared").call(()) },
+            }
+        }
+
+        pub fn is_frozen(&self) -> Result<bool> {
+            match self {
+                MockIndex::Real(index) => index.is_frozen(),
+                MockIndex::Mock(m) => unsafe { m.get("is_frozen").call(()) },
+            }
+        }
+
+        pub fn set_frozen(&self, frozen: bool) -> Result<()> {
+            match self {
+                MockIndex::Real(index) => index.set_frozen(frozen),
+                MockIndex::Mock(m) => unsafe { m.get("set_frozen").call(frozen) },
+            }
+        }
+
+        pub fn close_and_wait(self) {
+            match self {
+                MockIndex::Real(index) => index.close_and_wait(),
+                MockIndex::Mock(m) => unsafe { m.get("close_and_wait").call(()) },
+            }
+        }
+
+        pub fn map_size(&self) -> usize {
+            match self {
+                MockIndex::Real(index) => index.map_size(),
+                MockIndex::Mock(m) => unsafe { m.get("map_size").call(()) },
+            }
+        }
+
+        pub fn write_map_size(path: impl AsRef<Path>, size: usize) -> Result<()> {
+            Index::write_map_size(path, size)
+        }
+
         pub fn perform_search(&self, query: SearchQuery) -> Result<SearchResult> {
             match self {
                 MockIndex::Real(index) => index.perform_search(query),
@@ -149,6 +329,33 @@ pub mod test {
             }
         }
 
+        pub fn perform_facet_search(&self, query: FacetSearchQuery) -> Result<FacetSearchResult> {
+            match self {
+                MockIndex::Real(index) => index.perform_facet_search(query),
+                MockIndex::Mock(m) => unsafe { m.get("perform_facet_search").call(query) },
+            }
+        }
+
+        pub fn perform_similar(&self, query: SimilarQuery) -> Result<SimilarResult> {
+            match self {
+                MockIndex::Real(index) => index.perform_similar(query),
+                MockIndex::Mock(m) => unsafe { m.get("perform_similar").call(query) },
+            }
+        }
+
+        pub fn perform_suggest(
+            &self,
+            query: SuggestQuery,
+            popular_queries: Vec<QueryCount>,
+        ) -> Result<SuggestResult> {
+            match self {
+                MockIndex::Real(index) => index.perform_suggest(query, popular_queries),
+                MockIndex::Mock(m) => unsafe {
+                    m.get("perform_suggest").call((query, popular_queries))
+                },
+            }
+        }
+
         pub fn dump(&self, path: impl AsRef<Path>) -> Result<()> {
             match self {
                 MockIndex::Real(index) => index.dump(path),
@@ -160,17 +367,22 @@ pub mod test {
             &self,
             method: IndexDocumentsMethod,
             primary_key: Option<String>,
+            max_documents: Option<u64>,
             file_store: UpdateFileStore,
-            contents: impl Iterator<Item = Uuid>,
-        ) -> Result<DocumentAdditionResult> {
+            contents: impl Iterator<Item = DocumentPayload>,
+        ) -> Result<DocumentAdditionOutcome> {
             match self {
                 MockIndex::Real(index) => {
-                    index.update_documents(method, primary_key, file_store, contents)
+                    index.update_documents(method, primary_key, max_documents, file_store, contents)
                 }
                 MockIndex::Mock(mocker) => unsafe {
-                    mocker
-                        .get("update_documents")
-                        .call((method, primary_key, file_store, contents))
+                    mocker.get("update_documents").call((
+                        method,
+                        primary_key,
+                        max_documents,
+                        file_store,
+                        contents,
+                    ))
                 },
             }
         }
@@ -196,6 +408,47 @@ pub mod test {
             }
         }
 
+        pub fn delete_documents_by_filter(&self, filter: &Value) -> Result<DocumentDeletionResult> {
+            match self {
+                MockIndex::Real(index) => index.delete_documents_by_filter(filter),
+                MockIndex::Mock(m) => unsafe { m.get("delete_documents_by_filter").call(filter) },
+            }
+        }
+
+        pub fn edit_documents_by_function(
+            &self,
+            filter: Option<&Value>,
+            function: &str,
+        ) -> Result<DocumentEditionResult> {
+            match self {
+                MockIndex::Real(index) => index.edit_documents_by_function(filter, function),
+                MockIndex::Mock(m) => unsafe {
+                    m.get("edit_documents_by_function").call((filter, function))
+                },
+            }
+        }
+
+        pub fn ingestion_pipeline(&self) -> Result<Vec<IngestionStep>> {
+            match self {
+                MockIndex::Real(index) => index.ingestion_pipeline(),
+                MockIndex::Mock(m) => unsafe { m.get("ingestion_pipeline").call(()) },
+            }
+        }
+
+        pub fn attachments(&self) -> Result<AttachmentSettings> {
+            match self {
+                MockIndex::Real(index) => index.attachments(),
+                MockIndex::Mock(m) => unsafe { m.get("attachments").call(()) },
+            }
+        }
+
+        pub fn expired_documents(&self, now: OffsetDateTime) -> Result<Vec<String>> {
+            match self {
+                MockIndex::Real(index) => index.expired_documents(now),
+                MockIndex::Mock(m) => unsafe { m.get("expired_documents").call(now) },
+            }
+        }
+
         pub fn clear_documents(&self) -> Result<()> {
             match self {
                 MockIndex::Real(index) => index.clear_documents(),
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::search::SearchQuery;
+
+/// A merchandising rule of an index. The consequence of the rule is applied to the searches
+/// matching at least one of its conditions, or to every search when it has no condition.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub id: String,
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
+    #[serde(default)]
+    pub consequence: RuleConsequence,
+}
+
+/// Matches the searches whose query matches the pattern and whose filter contains the filter
+/// condition, when they are set. The query and the filter are compared ignoring the case and
+/// the extra spaces.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleCondition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub anchoring: Anchoring,
+    /// One of the conditions joined with `AND` at the top level of the filter of the search, e.g.
+    /// `brand = apple`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+}
+
+/// Where the words of the pattern must be found in the query.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Anchoring {
+    Is,
+    StartsWith,
+    EndsWith,
+    Contains,
+}
+
+impl Default for Anchoring {
+    fn default() -> Self {
+        Self::Is
+    }
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleConsequence {
+    /// The ids of the documents placed, in this order, on top of the hits when they match the
+    /// search.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pin: Vec<String>,
+    /// The ids of the documents removed from the hits.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hide: Vec<String>,
+    /// A filter, the documents matching it are ranked before the other hits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<String>,
+    /// The hits of the query with a word replaced by one of its synonyms are returned after the
+    /// hits of the query. The synonyms of the index still apply.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub synonyms: BTreeMap<String, Vec<String>>,
+}
+
+impl Rule {
+    pub fn matches(&self, query: &SearchQuery) -> bool {
+        self.conditions.is_empty()
+            || self
+                .conditions
+                .iter()
+                .any(|condition| condition.matches(query))
+    }
+}
+
+impl RuleCondition {
+    fn matches(&self, query: &SearchQuery) -> bool {
+        let pattern_matches = match self.pattern {
+            Some(ref pattern) => {
+                let pattern = normalized_words(pattern);
+                let words = normalized_words(query.q.as_deref().unwrap_or_default());
+                match self.anchoring {
+                    Anchoring::Is => words == pattern,
+                    Anchoring::StartsWith => words.starts_with(&pattern),
+                    Anchoring::EndsWith => words.ends_with(&pattern),
+                    Anchoring::Contains => find_words(&words, &pattern).is_some(),
+                }
+            }
+            None => true,
+        };
+
+        let filter_matches = match self.filter {
+            Some(ref filter) => {
+                let filter = normalized_words(filter).join(" ");
+                query
+                    .filter
+                    .as_ref()
+                    .map_or(false, |f| filter_conditions(f).contains(&filter))
+            }
+            None => true,
+        };
+
+        pattern_matches && filter_matches
+    }
+}
+
+impl RuleConsequence {
+    /// The queries obtained by replacing a word of the query by one of its synonyms.
+    pub fn alternative_queries(&self, query: &str) -> Vec<String> {
+        let words = normalized_words(query);
+        let mut queries = Vec::new();
+        for (word, synonyms) in &self.synonyms {
+            let word = normalized_words(word);
+            if let Some(position) = find_words(&words, &word) {
+                for synonym in synonyms {
+                    let alternative: Vec<_> = words[..position]
+                        .iter()
+                        .cloned()
+                        .chain(normalized_words(synonym))
+                        .chain(words[position + word.len()..].iter().cloned())
+                        .collect();
+                    queries.push(alternative.join(" "));
+                }
+            }
+        }
+        queries
+    }
+}
+
+/// The rules applying to the search, in the order of the rules of the index.
+pub fn matching_rules<'a>(rules: &'a [Rule], query: &SearchQuery) -> Vec<&'a Rule> {
+    rules.iter().filter(|rule| rule.matches(query)).collect()
+}
+
+fn normalized_words(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_lowercase).collect()
+}
+
+/// The position of the first occurrence of the words in `words`.
+fn find_words(words: &[String], pattern: &[String]) -> Option<usize> {
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    words
+        .windows(pattern.len())
+        .position(|window| window == pattern)
+}
+
+/// The conditions joined with `AND` at the top level of a search filter, normalized. The
+/// conditions of the array syntax are the elements of the array, an element holding several
+/// conditions joined with `OR`.
+fn filter_conditions(filter: &Value) -> Vec<String> {
+    static AND: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\s+AND\s+").unwrap());
+
+    let normalize = |condition: &str| normalized_words(condition).join(" ");
+    match filter {
+        Value::String(filter) => AND.split(filter).map(normalize).collect(),
+        Value::Array(conditions) => conditions
+            .iter()
+            .map(|condition| match condition {
+                Value::String(condition) => normalize(condition),
+                Value::Array(alternatives) => alternatives
+                    .iter()
+                    .map(|alternative| match alternative {
+                        Value::String(alternative) => normalize(alternative),
+                        other => normalize(&other.to_string()),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" or "),
+                other => normalize(&other.to_string()),
+            })
+            .collect(),
+        other => vec![normalize(&other.to_string())],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn query(q: &str, filter: Option<Value>) -> SearchQuery {
+        serde_json::from_value(json!({ "q": q, "filter": filter })).unwrap()
+    }
+
+    #[test]
+    fn test_rule_conditions() {
+        let condition = |pattern: &str, anchoring: Anchoring| RuleCondition {
+            pattern: Some(pattern.to_string()),
+            anchoring,
+            filter: None,
+        };
+
+        let q = query("Cheap  Gaming laptop", None);
+        assert!(condition("cheap gaming laptop", Anchoring::Is).matches(&q));
+        assert!(!condition("gaming laptop", Anchoring::Is).matches(&q));
+        assert!(condition("cheap gaming", Anchoring::StartsWith).matches(&q));
+        assert!(condition("laptop", Anchoring::EndsWith).matches(&q));
+        assert!(condition("gaming", Anchoring::Contains).matches(&q));
+        assert!(!condition("game", Anchoring::Contains).matches(&q));
+
+        let filter = RuleCondition {
+            pattern: None,
+            anchoring: Anchoring::Is,
+            filter: Some("brand = Apple".to_string()),
+        };
+        assert!(filter.matches(&query("", Some(json!("price < 10 and BRAND = apple")))));
+        assert!(filter.matches(&query("", Some(json!(["price < 10", "brand = apple"])))));
+        assert!(!filter.matches(&query("", Some(json!("brand = apple OR price < 10")))));
+        assert!(!filter.matches(&query("", None)));
+    }
+
+    #[test]
+    fn test_alternative_queries() {
+        let consequence = RuleConsequence {
+            synonyms: BTreeMap::from([(
+                "tv".to_string(),
+                vec!["television".to_string(), "flat screen".to_string()],
+            )]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            consequence.alternative_queries("Samsung TV 55"),
+            ["samsung television 55", "samsung flat screen 55"]
+        );
+        assert!(consequence.alternative_queries("samsung phone").is_empty());
+    }
+}
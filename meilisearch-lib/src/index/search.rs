@@ -1,22 +1,36 @@
-use std::cmp::min;
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::borrow::Cow;
+use std::cmp::{min, Ordering};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::str::FromStr;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use either::Either;
+use fst::{Automaton, IntoStreamer, Streamer};
+use indexmap::IndexMap;
+use milli::heed::types::ByteSlice;
+use milli::heed::{BytesEncode, RoTxn};
+use milli::heed_codec::facet::FacetStringLevelZeroCodec;
 use milli::tokenizer::TokenizerBuilder;
 use milli::{
-    AscDesc, FieldId, FieldsIdsMap, Filter, FormatOptions, MatchBounds, MatcherBuilder, SortError,
-    DEFAULT_VALUES_PER_FACET,
+    AscDesc, Criterion, DocumentId, FieldId, FieldsIdsMap, Filter, FormatOptions, MatchBounds,
+    MatcherBuilder, Member, SortError, DEFAULT_VALUES_PER_FACET,
 };
 use regex::Regex;
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::index::error::FacetError;
+use crate::search_log::QueryCount;
 
 use super::error::{IndexError, Result};
 use super::index::Index;
+use super::rules::{matching_rules, Rule};
+use super::updates::{
+    ranking_experiment, rules, search_cutoff_ms, sort_facet_values_by, FacetValuesSort,
+    RankingVariant,
+};
+use super::vectors::document_vectors;
 
 pub type Document = serde_json::Map<String, Value>;
 type MatchesPosition = BTreeMap<String, Vec<MatchBounds>>;
@@ -26,10 +40,16 @@ pub const DEFAULT_CROP_LENGTH: fn() -> usize = || 10;
 pub const DEFAULT_CROP_MARKER: fn() -> String = || "…".to_string();
 pub const DEFAULT_HIGHLIGHT_PRE_TAG: fn() -> String = || "<em>".to_string();
 pub const DEFAULT_HIGHLIGHT_POST_TAG: fn() -> String = || "</em>".to_string();
+pub const DEFAULT_SEMANTIC_RATIO: fn() -> f32 = || 0.5;
+pub const DEFAULT_HITS_PER_GROUP: fn() -> usize = || 1;
+
+/// The number of documents whose vectors are compared to the query vector between two checks of
+/// the search budget.
+const SIMILARITY_CHUNK_SIZE: usize = 1000;
 
 /// The maximimum number of results that the engine
 /// will be able to return in one search call.
-pub const DEFAULT_PAGINATION_LIMITED_TO: usize = 1000;
+pub const DEFAULT_PAGINATION_MAX_TOTAL_HITS: usize = 1000;
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
@@ -38,6 +58,8 @@ pub struct SearchQuery {
     pub offset: Option<usize>,
     #[serde(default = "DEFAULT_SEARCH_LIMIT")]
     pub limit: usize,
+    pub page: Option<usize>,
+    pub hits_per_page: Option<usize>,
     pub attributes_to_retrieve: Option<BTreeSet<String>>,
     pub attributes_to_crop: Option<Vec<String>>,
     #[serde(default = "DEFAULT_CROP_LENGTH")]
@@ -49,12 +71,118 @@ pub struct SearchQuery {
     pub filter: Option<Value>,
     pub sort: Option<Vec<String>>,
     pub facets: Option<Vec<String>>,
+    /// Counts every value of the facets in all the matching documents. The counts are otherwise
+    /// only exact on the matching documents read until `maxValuesPerFacet` values were found,
+    /// which is slower on the facets having many distinct values.
+    #[serde(default)]
+    pub exhaustive_facet_count: bool,
+    pub attributes_to_search_on: Option<Vec<String>>,
+    pub attribute_weights: Option<BTreeMap<String, f32>>,
+    pub typo_tolerance: Option<TypoToleranceQuery>,
+    pub distinct: Option<DistinctQuery>,
+    /// The documents pinned, in this order, on top of the hits when they match the search.
+    pub boost_documents: Option<Vec<Value>>,
+    /// The documents moved to the end of the hits when they match the search.
+    pub bury_documents: Option<Vec<Value>>,
+    #[serde(default)]
+    pub matching_strategy: MatchingStrategy,
+    /// Ranks the candidates by the similarity of their `_vectors` with this vector. There is no
+    /// approximate nearest neighbours index: the vectors of every candidate are decoded and
+    /// compared, so the cost of the search grows linearly with the number of candidates.
+    pub vector: Option<Vec<f32>>,
+    pub hybrid: Option<HybridQuery>,
     #[serde(default = "DEFAULT_HIGHLIGHT_PRE_TAG")]
     pub highlight_pre_tag: String,
     #[serde(default = "DEFAULT_HIGHLIGHT_POST_TAG")]
     pub highlight_post_tag: String,
     #[serde(default = "DEFAULT_CROP_MARKER")]
     pub crop_marker: String,
+    /// Suggests a corrected query when the search returns few hits.
+    #[serde(default)]
+    pub show_suggested_query: bool,
+    /// Explains how the hits were matched and how the candidates were filtered.
+    #[serde(default)]
+    pub debug: bool,
+    /// Identifies the user the search is performed for, the searches sharing a key are served by
+    /// the same variant of the ranking experiment of the index. It's set by the search routes.
+    #[serde(skip)]
+    pub experiment_key: Option<String>,
+    /// Computes the ranking score of the hits, so they can be merged with the hits of other
+    /// indexes. It's set by the federated search.
+    #[serde(skip)]
+    pub ranking_score: bool,
+}
+
+/// Blends the keyword ranking with the similarity of the documents with the query vector. A
+/// `semanticRatio` of 0 only uses the keyword ranking, a ratio of 1 only uses the similarity.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HybridQuery {
+    #[serde(default = "DEFAULT_SEMANTIC_RATIO")]
+    pub semantic_ratio: f32,
+}
+
+/// How the terms of the query are dropped when no document contains all of them.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchingStrategy {
+    /// Drops the last terms of the query first.
+    Last,
+    /// Only returns the documents containing all the terms.
+    All,
+    /// Drops the terms contained by the most documents first. milli doesn't know this strategy,
+    /// the terms are dropped before the search.
+    Frequency,
+}
+
+impl Default for MatchingStrategy {
+    fn default() -> Self {
+        Self::Last
+    }
+}
+
+/// Keeps at most `hitsPerGroup` hits sharing the same value of an attribute, the hits of a group
+/// following each other. milli only knows the distinct attribute of the index, this one is applied
+/// on the results of milli.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DistinctQuery {
+    pub attribute: String,
+    #[serde(default = "DEFAULT_HITS_PER_GROUP")]
+    pub hits_per_group: usize,
+}
+
+/// Overrides the typo tolerance of the index for a search. milli only lets a search disable the
+/// typos, the words of the query that must not have typos are searched as phrases.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TypoToleranceQuery {
+    pub enabled: Option<bool>,
+    pub disable_on_words: Option<BTreeSet<String>>,
+    pub min_word_size_for_typos: Option<MinWordSizeForTyposQuery>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MinWordSizeForTyposQuery {
+    /// The words shorter than this size are searched without typos.
+    pub one_typo: Option<u8>,
+}
+
+impl TypoToleranceQuery {
+    /// Returns whether a word of the query must be searched without typos.
+    fn is_exact(&self, word: &str) -> bool {
+        let too_short = self
+            .min_word_size_for_typos
+            .and_then(|size| size.one_typo)
+            .map_or(false, |one_typo| word.chars().count() < one_typo as usize);
+        let disabled = self.disable_on_words.as_ref().map_or(false, |words| {
+            words
+                .iter()
+                .any(|w| w.to_lowercase() == word.to_lowercase())
+        });
+        too_short || disabled
+    }
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
@@ -65,196 +193,2454 @@ pub struct SearchHit {
     pub formatted: Document,
     #[serde(rename = "_matchesPosition", skip_serializing_if = "Option::is_none")]
     pub matches_position: Option<MatchesPosition>,
+    #[serde(rename = "_semanticScore", skip_serializing_if = "Option::is_none")]
+    pub semantic_score: Option<f32>,
+    #[serde(rename = "_hybridScore", skip_serializing_if = "Option::is_none")]
+    pub hybrid_score: Option<f32>,
+    #[serde(rename = "_debug", skip_serializing_if = "Option::is_none")]
+    pub debug: Option<HitDebug>,
+    /// The relevancy of the hit between 0 and 1, computed when the query asks for it.
+    #[serde(skip)]
+    pub ranking_score: Option<f64>,
+}
+
+/// Explains which words of a hit matched the words of the query, returned with `debug`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HitDebug {
+    pub matched_words: Vec<MatchedWord>,
+    /// The number of distinct words of the query found in the hit.
+    pub matched_query_words: usize,
+    /// The sum, over the words of the query found in the hit, of the typos of their best match.
+    pub typos: usize,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchedWord {
+    pub attribute: String,
+    pub query_word: String,
+    pub document_word: String,
+    pub typos: usize,
+}
+
+/// Explains how the candidates of a search were computed, returned with `debug`. milli doesn't
+/// expose which ranking rule ordered two hits, only the rules of the index are listed.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchDebug {
+    pub ranking_rules: Vec<String>,
+    /// The number of documents matching the keywords, before the filters are applied.
+    pub candidates_without_filters: u64,
+    /// The number of documents matching the keywords but removed by the negative terms.
+    pub excluded_by_negative_terms: u64,
+    pub filters: Vec<FilterDebug>,
+}
+
+/// The number of documents matching the keywords that a filter of the search keeps on its own.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterDebug {
+    pub filter: Value,
+    pub candidates: u64,
+    pub pruned: u64,
+}
+
+/// The scores of a hit ranked with a query vector.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct VectorScores {
+    semantic: Option<f32>,
+    hybrid: Option<f32>,
+}
+
+impl SearchQuery {
+    /// The keyword ranking is restricted to some attributes or boosted by attribute when the query
+    /// has terms to match.
+    fn is_restricted_to_attributes(&self) -> bool {
+        let has_terms = self
+            .q
+            .as_ref()
+            .map_or(false, |q| !split_negative_terms(q).0.trim().is_empty());
+        has_terms && (self.attributes_to_search_on.is_some() || self.attribute_weights.is_some())
+    }
+
+    /// Whether the hits are paginated with `page` and `hitsPerPage` rather than with `offset` and
+    /// `limit`.
+    pub fn is_finite_pagination(&self) -> bool {
+        self.page.is_some() || self.hits_per_page.is_some()
+    }
+
+    /// Applies the ranking parameters of a variant of a ranking experiment. The parameters set by
+    /// the search itself take precedence, and the sort is ignored along with a `vector`.
+    fn apply_ranking_variant(&mut self, variant: &RankingVariant) {
+        if self.sort.is_none() && self.vector.is_none() {
+            self.sort = variant.sort.clone();
+        }
+        if self.attribute_weights.is_none() {
+            self.attribute_weights = variant.attribute_weights.clone();
+        }
+        if self.typo_tolerance.is_none() && variant.typo_tolerance == Some(false) {
+            self.typo_tolerance = Some(TypoToleranceQuery {
+                enabled: Some(false),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/// How the hits of a search are paginated. With `page` and `hitsPerPage`, the total number of
+/// hits is exhaustive up to the pagination limit of the index.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum HitsInfo {
+    #[serde(rename_all = "camelCase")]
+    Pagination {
+        hits_per_page: usize,
+        page: usize,
+        total_pages: usize,
+        total_hits: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    OffsetLimit {
+        limit: usize,
+        offset: usize,
+        estimated_total_hits: u64,
+    },
+}
+
+impl HitsInfo {
+    pub fn total_hits(&self) -> u64 {
+        match self {
+            HitsInfo::Pagination { total_hits, .. } => *total_hits,
+            HitsInfo::OffsetLimit {
+                estimated_total_hits,
+                ..
+            } => *estimated_total_hits,
+        }
+    }
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchResult {
     pub hits: Vec<SearchHit>,
-    pub estimated_total_hits: u64,
     pub query: String,
-    pub limit: usize,
-    pub offset: usize,
     pub processing_time_ms: u128,
+    #[serde(flatten)]
+    pub hits_info: HitsInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facet_distribution: Option<BTreeMap<String, IndexMap<String, u64>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub facet_distribution: Option<BTreeMap<String, BTreeMap<String, u64>>>,
+    pub facet_stats: Option<BTreeMap<String, FacetStats>>,
+    /// Whether the search exceeded the search cutoff of the index, and returned what it computed
+    /// until then.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
+    /// The variant of the ranking experiment of the index that served the search, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ranking_variant: Option<String>,
+    /// The query with its misspelled words corrected, when it returns more hits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_query: Option<String>,
+    /// The ids of the rules of the index applied to the search.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub applied_rules: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug: Option<SearchDebug>,
 }
 
-impl Index {
-    pub fn perform_search(&self, query: SearchQuery) -> Result<SearchResult> {
-        let before_search = Instant::now();
-        let rtxn = self.read_txn()?;
-
-        let mut search = self.search(&rtxn);
+/// The time budget of a search, set by the search cutoff of the index.
+#[derive(Debug, Clone, Copy)]
+struct SearchBudget {
+    deadline: Option<Instant>,
+}
 
-        if let Some(ref query) = query.q {
-            search.query(query);
+impl SearchBudget {
+    fn new(start: Instant, cutoff_ms: Option<u64>) -> Self {
+        Self {
+            deadline: cutoff_ms.map(|ms| start + Duration::from_millis(ms)),
         }
+    }
 
-        let pagination_limited_to = self
-            .pagination_limited_to(&rtxn)?
-            .unwrap_or(DEFAULT_PAGINATION_LIMITED_TO);
+    fn exceeded(&self) -> bool {
+        self.deadline
+            .map_or(false, |deadline| Instant::now() >= deadline)
+    }
+}
 
-        // Make sure that a user can't get more documents than the hard limit,
-        // we align that on the offset too.
-        let offset = min(query.offset.unwrap_or(0), pagination_limited_to);
-        let limit = min(query.limit, pagination_limited_to.saturating_sub(offset));
+/// The smallest and biggest numeric values of a facet among the documents matching a search.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FacetStats {
+    pub min: f64,
+    pub max: f64,
+}
 
-        search.offset(offset);
-        search.limit(limit);
+/// Orders the values of each facet of the distribution. The values sorted by count are taken from
+/// all the values of the facet, so the most frequent ones are kept when they are truncated.
+fn sort_facet_values(
+    distribution: BTreeMap<String, BTreeMap<String, u64>>,
+    mut all_values: BTreeMap<String, BTreeMap<String, u64>>,
+    sort_by: &BTreeMap<String, FacetValuesSort>,
+    max_values_per_facet: usize,
+) -> BTreeMap<String, IndexMap<String, u64>> {
+    distribution
+        .into_iter()
+        .map(|(facet, values)| {
+            let order = sort_by
+                .get(&facet)
+                .or_else(|| sort_by.get("*"))
+                .copied()
+                .unwrap_or_default();
+            let values = match order {
+                FacetValuesSort::Alpha => values.into_iter().collect(),
+                FacetValuesSort::Count => {
+                    let mut values: Vec<_> = all_values
+                        .remove(&facet)
+                        .unwrap_or(values)
+                        .into_iter()
+                        .collect();
+                    values.sort_by(|(a, a_count), (b, b_count)| {
+                        b_count.cmp(a_count).then_with(|| a.cmp(b))
+                    });
+                    values.truncate(max_values_per_facet);
+                    values.into_iter().collect()
+                }
+            };
+            (facet, values)
+        })
+        .collect()
+}
 
-        if let Some(ref filter) = query.filter {
-            if let Some(facets) = parse_filter(filter)? {
-                search.filter(facets);
+/// Keeps the first `max_values_per_facet` values of each facet, in alphabetical order.
+fn truncate_facet_values(
+    distribution: &BTreeMap<String, BTreeMap<String, u64>>,
+    max_values_per_facet: usize,
+) -> BTreeMap<String, BTreeMap<String, u64>> {
+    distribution
+        .iter()
+        .map(|(facet, values)| {
+            let values = values
+                .iter()
+                .take(max_values_per_facet)
+                .map(|(value, count)| (value.clone(), *count))
+                .collect();
+            (facet.clone(), values)
+        })
+        .collect()
+}
+
+/// The facets to compute the distribution of, `None` standing for all the filterable attributes.
+/// A facet ending with `*`, e.g. `dim_*`, stands for the filterable attributes starting with the
+/// rest of its name.
+fn expand_facets(facets: &[String], filterable_fields: &HashSet<String>) -> Option<Vec<String>> {
+    if facets.iter().any(|facet| facet == "*") {
+        return None;
+    }
+
+    let mut expanded = Vec::new();
+    for facet in facets {
+        match facet.strip_suffix('*') {
+            Some(prefix) => {
+                let mut matching: Vec<_> = filterable_fields
+                    .iter()
+                    .filter(|field| field.starts_with(prefix) && !expanded.contains(*field))
+                    .cloned()
+                    .collect();
+                matching.sort_unstable();
+                expanded.extend(matching);
             }
+            None if !expanded.contains(facet) => expanded.push(facet.clone()),
+            None => (),
         }
+    }
+    Some(expanded)
+}
 
-        if let Some(ref sort) = query.sort {
-            let sort = match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
-                Ok(sorts) => sorts,
-                Err(asc_desc_error) => {
-                    return Err(IndexError::Milli(SortError::from(asc_desc_error).into()))
-                }
-            };
+/// Computes the stats of the facets having at least one numeric value in the distribution.
+fn compute_facet_stats(
+    distribution: &BTreeMap<String, BTreeMap<String, u64>>,
+) -> BTreeMap<String, FacetStats> {
+    distribution
+        .iter()
+        .filter_map(|(facet, values)| {
+            let mut numbers = values.keys().filter_map(|v| v.parse::<f64>().ok());
+            let first = numbers.next()?;
+            let stats = numbers.fold(
+                FacetStats {
+                    min: first,
+                    max: first,
+                },
+                |stats, n| FacetStats {
+                    min: stats.min.min(n),
+                    max: stats.max.max(n),
+                },
+            );
+            Some((facet.clone(), stats))
+        })
+        .collect()
+}
 
-            search.sort_criteria(sort);
-        }
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FacetSearchQuery {
+    pub facet_name: String,
+    pub facet_query: Option<String>,
+    pub q: Option<String>,
+    pub filter: Option<Value>,
+}
 
-        let milli::SearchResult {
-            documents_ids,
-            matching_words,
-            candidates,
-            ..
-        } = search.execute()?;
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FacetValueHit {
+    pub value: String,
+    pub count: u64,
+}
 
-        let fields_ids_map = self.fields_ids_map(&rtxn).unwrap();
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FacetSearchResult {
+    pub facet_hits: Vec<FacetValueHit>,
+    pub facet_query: Option<String>,
+    pub processing_time_ms: u128,
+}
 
-        let displayed_ids = self
-            .displayed_fields_ids(&rtxn)?
-            .map(|fields| fields.into_iter().collect::<BTreeSet<_>>())
-            .unwrap_or_else(|| fields_ids_map.iter().map(|(id, _)| id).collect());
+/// Returns whether a word of the facet value starts with the query, ignoring the case.
+fn facet_value_matches(value: &str, query: &str) -> bool {
+    let value = value.to_lowercase();
+    let query = query.to_lowercase();
+    value.starts_with(&query)
+        || value
+            .split_whitespace()
+            .any(|word| word.starts_with(&query))
+}
 
-        let fids = |attrs: &BTreeSet<String>| {
-            let mut ids = BTreeSet::new();
-            for attr in attrs {
-                if attr == "*" {
-                    ids = displayed_ids.clone();
-                    break;
-                }
+/// Returns the cosine similarity of two vectors of the same dimensions, or 0 when one of them is
+/// null.
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(a, b)| a * b).sum();
+    let norm_a = a.iter().map(|a| a * a).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|b| b * b).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
 
-                if let Some(id) = fields_ids_map.id(attr) {
-                    ids.insert(id);
-                }
-            }
-            ids
-        };
+/// Returns the best similarity between the query vector and the vectors of a document. The
+/// vectors that don't have the dimensions of the query vector are ignored.
+fn document_similarity(vectors: &[Vec<f32>], query: &[f32]) -> Option<f32> {
+    vectors
+        .iter()
+        .filter(|vector| vector.len() == query.len())
+        .map(|vector| cosine_similarity(vector, query))
+        .max_by(|a, b| a.total_cmp(b))
+}
 
-        // The attributes to retrieve are the ones explicitly marked as to retrieve (all by default),
-        // but these attributes must be also be present
-        // - in the fields_ids_map
-        // - in the the displayed attributes
-        let to_retrieve_ids: BTreeSet<_> = query
-            .attributes_to_retrieve
-            .as_ref()
-            .map(fids)
-            .unwrap_or_else(|| displayed_ids.clone())
-            .intersection(&displayed_ids)
-            .cloned()
+/// Merges the documents ranked by milli with the documents ranked by their similarity with the
+/// query vector. The keyword score of a document is derived from its position in the keyword
+/// ranking, and the documents missing from one of the rankings get a score of 0 for it.
+fn blend_rankings(
+    keyword_ranking: &[DocumentId],
+    semantic_ranking: Vec<(DocumentId, f32)>,
+    semantic_ratio: f32,
+) -> Vec<(DocumentId, VectorScores)> {
+    let keyword_scores: HashMap<_, _> = keyword_ranking
+        .iter()
+        .enumerate()
+        .map(|(position, id)| (*id, 1.0 / (1 + position) as f32))
+        .collect();
+    let similarities: HashMap<_, _> = semantic_ranking.iter().copied().collect();
+
+    let mut ranked: Vec<_> = keyword_ranking
+        .iter()
+        .copied()
+        .chain(
+            semantic_ranking
+                .iter()
+                .map(|(id, _)| *id)
+                .filter(|id| !keyword_scores.contains_key(id)),
+        )
+        .map(|id| {
+            let similarity = similarities.get(&id).copied();
+            let keyword_score = keyword_scores.get(&id).copied().unwrap_or_default();
+            let score = (1.0 - semantic_ratio) * keyword_score
+                + semantic_ratio * similarity.unwrap_or_default();
+            (id, score, similarity)
+        })
+        .collect();
+
+    // The sort is stable: on equal scores, the keyword ranking wins.
+    ranked.sort_by(|(_, a, _), (_, b, _)| b.total_cmp(a));
+
+    ranked
+        .into_iter()
+        .map(|(id, score, similarity)| {
+            let scores = VectorScores {
+                semantic: similarity,
+                hybrid: Some(score),
+            };
+            (id, scores)
+        })
+        .collect()
+}
+
+/// The documents moved on top or to the end of the hits of a search.
+struct PinnedDocuments {
+    boosted: Vec<DocumentId>,
+    /// The documents ranked before the other hits, in their order, by the rules of the index.
+    promoted: RoaringBitmap,
+    buried: RoaringBitmap,
+}
+
+impl PinnedDocuments {
+    /// Places the boosted documents matching the search first, even the ones ranked too low to be
+    /// in `ranked`, then the promoted documents, and the buried documents last. A document both
+    /// boosted and buried is boosted.
+    fn apply(&self, ranked: Vec<DocumentId>, candidates: &RoaringBitmap) -> Vec<DocumentId> {
+        let boosted: Vec<_> = self
+            .boosted
+            .iter()
+            .copied()
+            .filter(|id| candidates.contains(*id))
             .collect();
+        let boosted_ids: RoaringBitmap = boosted.iter().copied().collect();
+        let (buried, others): (Vec<_>, Vec<_>) = ranked
+            .into_iter()
+            .filter(|id| !boosted_ids.contains(*id))
+            .partition(|id| self.buried.contains(*id));
+        let (promoted, others): (Vec<_>, Vec<_>) = others
+            .into_iter()
+            .partition(|id| self.promoted.contains(*id));
+
+        boosted
+            .into_iter()
+            .chain(promoted)
+            .chain(others)
+            .chain(buried)
+            .collect()
+    }
+}
 
-        let attr_to_highlight = query.attributes_to_highlight.unwrap_or_default();
+/// The maximum number of terms of a document used to find the documents similar to it.
+const MAX_SIMILAR_TERMS: usize = 10;
 
-        let attr_to_crop = query.attributes_to_crop.unwrap_or_default();
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarQuery {
+    pub id: String,
+    pub offset: usize,
+    pub limit: usize,
+    pub filter: Option<Value>,
+    /// The filter of the tenant token rules only, the reference document must match it.
+    pub rules_filter: Option<Value>,
+    pub attributes_to_retrieve: Option<Vec<String>>,
+}
 
-        // Attributes in `formatted_options` correspond to the attributes that will be in `_formatted`
-        // These attributes are:
-        // - the attributes asked to be highlighted or cropped (with `attributesToCrop` or `attributesToHighlight`)
-        // - the attributes asked to be retrieved: these attributes will not be highlighted/cropped
-        // But these attributes must be also present in displayed attributes
-        let formatted_options = compute_formatted_options(
-            &attr_to_highlight,
-            &attr_to_crop,
-            query.crop_length,
-            &to_retrieve_ids,
-            &fields_ids_map,
-            &displayed_ids,
-        );
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarResult {
+    pub hits: Vec<Document>,
+    pub id: String,
+    pub offset: usize,
+    pub limit: usize,
+    pub estimated_total_hits: u64,
+    pub processing_time_ms: u128,
+}
 
-        let tokenizer = TokenizerBuilder::default().build();
+pub const DEFAULT_SUGGEST_LIMIT: fn() -> usize = || 10;
 
-        let mut formatter_builder = MatcherBuilder::new(matching_words, tokenizer);
-        formatter_builder.crop_marker(query.crop_marker);
-        formatter_builder.highlight_prefix(query.highlight_pre_tag);
-        formatter_builder.highlight_suffix(query.highlight_post_tag);
+/// Below this number of hits, a search asked to suggest a query looks for a corrected one.
+const SUGGESTED_QUERY_MAX_HITS: u64 = 3;
 
-        let mut documents = Vec::new();
+/// The maximum number of words of the index considered to complete the last word of a query.
+const SUGGEST_MAX_SCANNED_WORDS: usize = 1000;
 
-        let documents_iter = self.documents(&rtxn, documents_ids)?;
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SuggestQuery {
+    #[serde(default)]
+    pub q: String,
+    #[serde(default = "DEFAULT_SUGGEST_LIMIT")]
+    pub limit: usize,
+    pub filter: Option<Value>,
+}
 
-        for (_id, obkv) in documents_iter {
-            // First generate a document with all the displayed fields
-            let displayed_document = make_document(&displayed_ids, &fields_ids_map, obkv)?;
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SuggestionSource {
+    /// A query searched on the index, the count is its number of searches.
+    Query,
+    /// A word of the documents, the count is the number of documents containing it.
+    Term,
+}
 
-            // select the attributes to retrieve
-            let attributes_to_retrieve = to_retrieve_ids
-                .iter()
-                .map(|&fid| fields_ids_map.name(fid).expect("Missing field name"));
-            let mut document =
-                permissive_json_pointer::select_values(&displayed_document, attributes_to_retrieve);
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub suggestion: String,
+    pub source: SuggestionSource,
+    pub count: u64,
+}
 
-            let (matches_position, formatted) = format_fields(
-                &displayed_document,
-                &fields_ids_map,
-                &formatter_builder,
-                &formatted_options,
-                query.show_matches_position,
-                &displayed_ids,
-            )?;
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestResult {
+    pub suggestions: Vec<Suggestion>,
+    pub query: String,
+    pub processing_time_ms: u128,
+}
 
-            if let Some(sort) = query.sort.as_ref() {
-                insert_geo_distance(sort, &mut document);
-            }
+/// Returns the number of typos between the query and the beginning of the text when it's at most
+/// `max_typos`. The beginnings a few characters shorter or longer than the query are compared
+/// too, so a missing or an extra character is a single typo.
+fn prefix_typos(query: &str, text: &str, max_typos: u8) -> Option<u8> {
+    let query_len = query.chars().count();
+    let text_len = text.chars().count();
+    let min_len = query_len.saturating_sub(max_typos as usize);
+    let max_len = min(query_len + max_typos as usize, text_len);
+    (min_len..=max_len)
+        .map(|len| {
+            let prefix: String = text.chars().take(len).collect();
+            edit_distance(query, &prefix)
+        })
+        .min()
+        .filter(|&typos| typos <= max_typos as usize)
+        .map(|typos| typos as u8)
+}
 
-            let hit = SearchHit {
-                document,
-                formatted,
-                matches_position,
+/// Splits the negative terms, `-word` or `-"a phrase"`, out of a query. The query without them is
+/// returned along with the terms the documents must not contain. A `-` inside a word or a phrase
+/// isn't an operator.
+fn split_negative_terms(query: &str) -> (String, Vec<String>) {
+    let mut positive = String::with_capacity(query.len());
+    let mut negative = Vec::new();
+    let mut chars = query.chars().peekable();
+    let mut in_phrase = false;
+    let mut word_start = true;
+
+    while let Some(c) = chars.next() {
+        if c == '-' && word_start && !in_phrase {
+            let term: Option<String> = match chars.peek() {
+                Some('"') => {
+                    chars.next();
+                    Some(chars.by_ref().take_while(|&c| c != '"').collect())
+                }
+                Some(next) if !next.is_whitespace() => {
+                    Some(chars.by_ref().take_while(|c| !c.is_whitespace()).collect())
+                }
+                _ => None,
             };
-            documents.push(hit);
+            if let Some(term) = term {
+                let term = term.trim();
+                if !term.is_empty() {
+                    negative.push(term.to_string());
+                }
+                positive.push(' ');
+                continue;
+            }
+        }
+
+        if c == '"' {
+            in_phrase = !in_phrase;
         }
+        word_start = c.is_whitespace();
+        positive.push(c);
+    }
+
+    (positive, negative)
+}
+
+/// Quotes the words of a query that must be searched without typos, a phrase has no typos. The
+/// phrases of the query are kept as is.
+fn quote_exact_words(query: &str, typo_tolerance: &TypoToleranceQuery) -> String {
+    let mut quoted = String::with_capacity(query.len());
+    let mut in_phrase = false;
+    for (i, part) in query.split('"').enumerate() {
+        if i > 0 {
+            quoted.push('"');
+            in_phrase = !in_phrase;
+        }
+        if in_phrase {
+            quoted.push_str(part);
+            continue;
+        }
+
+        let mut words = part.split(' ').peekable();
+        while let Some(word) = words.next() {
+            if !word.is_empty() && typo_tolerance.is_exact(word) {
+                quoted.push('"');
+                quoted.push_str(word);
+                quoted.push('"');
+            } else {
+                quoted.push_str(word);
+            }
+            if words.peek().is_some() {
+                quoted.push(' ');
+            }
+        }
+    }
+    quoted
+}
+
+/// Pushes the strings of a value, nested in arrays and objects, to `strings`.
+fn collect_strings<'a>(value: &'a Value, strings: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => strings.push(s),
+        Value::Array(values) => values.iter().for_each(|v| collect_strings(v, strings)),
+        Value::Object(object) => object.values().for_each(|v| collect_strings(v, strings)),
+        _ => (),
+    }
+}
+
+/// Returns the most frequent terms of the strings of the values, the first ones to appear first on
+/// equal frequencies. The terms shorter than 3 characters are ignored.
+fn most_frequent_terms(values: &[Value], max: usize) -> Vec<String> {
+    let mut strings = Vec::new();
+    values.iter().for_each(|v| collect_strings(v, &mut strings));
+
+    let mut terms: Vec<(String, usize)> = Vec::new();
+    let words = strings
+        .into_iter()
+        .flat_map(|s| s.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| word.chars().count() >= 3);
+    for word in words {
+        let word = word.to_lowercase();
+        match terms.iter_mut().find(|(term, _)| *term == word) {
+            Some((_, count)) => *count += 1,
+            None => terms.push((word, 1)),
+        }
+    }
+
+    // The sort is stable: on equal counts, the terms keep their order of appearance.
+    terms.sort_by(|(_, a), (_, b)| b.cmp(a));
+    terms.into_iter().take(max).map(|(term, _)| term).collect()
+}
+
+/// A query of a federated search, whose hits are merged with the hits of the other queries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FederatedQuery {
+    pub index_uid: String,
+    pub query: SearchQuery,
+    /// Multiplies the ranking score of the hits of this query.
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FederationInfo {
+    pub index_uid: String,
+    pub queries_position: usize,
+    /// The ranking score of the hit multiplied by the weight of its query.
+    pub weighted_ranking_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FederatedHit {
+    #[serde(flatten)]
+    pub hit: SearchHit,
+    #[serde(rename = "_federation")]
+    pub federation: FederationInfo,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FederatedSearchResult {
+    pub hits: Vec<FederatedHit>,
+    pub estimated_total_hits: u64,
+    pub limit: usize,
+    pub offset: usize,
+    pub processing_time_ms: u128,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
+}
+
+/// Merges the results of the queries of a federated search into a single list of hits ordered by
+/// their ranking score multiplied by the weight of their query.
+///
+/// milli doesn't expose the ranking score of a hit, the queries compute one between 0 and 1 that
+/// can be compared across indexes: the similarity of the hit with the query vector for a semantic
+/// or hybrid search, and the share of the words of the query found in the hit otherwise. On equal
+/// scores, the hits ranked first by their query come first. Each result must hold the first
+/// `offset + limit` hits of its query, with their ranking score.
+pub fn merge_federated_results(
+    results: Vec<(FederatedQuery, SearchResult)>,
+    offset: usize,
+    limit: usize,
+) -> FederatedSearchResult {
+    let mut estimated_total_hits = 0;
+    let mut processing_time_ms = 0;
+    let mut degraded = false;
+    let mut hits = Vec::new();
+
+    for (queries_position, (query, result)) in results.into_iter().enumerate() {
+        estimated_total_hits += result.hits_info.total_hits();
+        processing_time_ms = processing_time_ms.max(result.processing_time_ms);
+        degraded |= result.degraded;
+
+        hits.extend(result.hits.into_iter().enumerate().map(|(position, hit)| {
+            let ranking_score = hit.ranking_score.unwrap_or_default();
+            let hit = FederatedHit {
+                hit,
+                federation: FederationInfo {
+                    index_uid: query.index_uid.clone(),
+                    queries_position,
+                    weighted_ranking_score: query.weight * ranking_score,
+                },
+            };
+            (position, hit)
+        }));
+    }
+
+    // The sort is stable: on equal scores and positions, the hits of the first queries come first.
+    hits.sort_by(|(a_position, a), (b_position, b)| {
+        b.federation
+            .weighted_ranking_score
+            .total_cmp(&a.federation.weighted_ranking_score)
+            .then(a_position.cmp(b_position))
+    });
+
+    FederatedSearchResult {
+        hits: hits
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(_, hit)| hit)
+            .collect(),
+        estimated_total_hits,
+        limit,
+        offset,
+        processing_time_ms,
+        degraded,
+    }
+}
+
+impl Index {
+    pub fn perform_search(&self, mut query: SearchQuery) -> Result<SearchResult> {
+        let before_search = Instant::now();
+        let rtxn = self.read_txn()?;
+        // milli can't be interrupted, the budget is only checked by the steps computed here.
+        let budget = SearchBudget::new(before_search, search_cutoff_ms(self, &rtxn)?);
+        let mut degraded = false;
+
+        let ranking_variant = match ranking_experiment(self, &rtxn)? {
+            Some(experiment) => {
+                experiment
+                    .assign(query.experiment_key.as_deref())
+                    .map(|(name, variant)| {
+                        query.apply_ranking_variant(variant);
+                        name.to_string()
+                    })
+            }
+            None => None,
+        };
+
+        let rules = rules(self, &rtxn)?;
+        let rules = matching_rules(&rules, &query);
+        let alternative_queries: Vec<_> = match query.q {
+            Some(ref q) => {
+                let (positive, _) = split_negative_terms(q);
+                rules
+                    .iter()
+                    .flat_map(|rule| rule.consequence.alternative_queries(&positive))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        let filter = query
+            .filter
+            .as_ref()
+            .map(|filter| self.prepare_filter(&rtxn, filter))
+            .transpose()?;
+        let mut search = self.search(&rtxn);
+
+        // The documents containing a negative term are removed from the results after the search.
+        let (keywords, excluded) = match query.q {
+            Some(ref q) => {
+                let (positive, negative) = split_negative_terms(q);
+                let keywords = match query.typo_tolerance {
+                    Some(ref typo_tolerance) => quote_exact_words(&positive, typo_tolerance),
+                    None => positive,
+                };
+                (Some(keywords), self.documents_containing(&rtxn, &negative)?)
+            }
+            None => (None, RoaringBitmap::new()),
+        };
+        let hidden = rules
+            .iter()
+            .flat_map(|rule| rule.consequence.hide.iter().cloned());
+        let excluded = excluded
+            | self
+                .internal_ids(&rtxn, hidden)?
+                .into_iter()
+                .collect::<RoaringBitmap>();
+        let authorize_typos = query
+            .typo_tolerance
+            .as_ref()
+            .map_or(true, |typo_tolerance| typo_tolerance.enabled != Some(false));
+        let keywords = match keywords {
+            Some(keywords) if query.matching_strategy == MatchingStrategy::Frequency => Some(
+                self.drop_frequent_terms(&rtxn, &keywords, authorize_typos, filter.as_ref())?,
+            ),
+            keywords => keywords,
+        };
+        if let Some(ref keywords) = keywords {
+            search.query(keywords);
+        }
+        search.authorize_typos(authorize_typos);
+        search.optional_words(query.matching_strategy == MatchingStrategy::Last);
+
+        let pagination_limited_to = self
+            .pagination_limited_to(&rtxn)?
+            .unwrap_or(DEFAULT_PAGINATION_MAX_TOTAL_HITS);
+
+        let hits_per_page = query.hits_per_page.unwrap_or_else(DEFAULT_SEARCH_LIMIT);
+        let page = query.page.unwrap_or(1);
+        let (offset, limit) = if query.is_finite_pagination() {
+            // There is no page 0, it holds no hits.
+            let limit = if page == 0 { 0 } else { hits_per_page };
+            (page.saturating_sub(1).saturating_mul(hits_per_page), limit)
+        } else {
+            (query.offset.unwrap_or(0), query.limit)
+        };
+
+        // Make sure that a user can't get more documents than the hard limit,
+        // we align that on the offset too.
+        let offset = min(offset, pagination_limited_to);
+        let limit = min(limit, pagination_limited_to.saturating_sub(offset));
+
+        let restricted = query.is_restricted_to_attributes();
+        if restricted {
+            self.check_attributes_to_search_on(&rtxn, &query)?;
+        }
+        if let Some(ref distinct) = query.distinct {
+            if distinct.hits_per_group == 0 {
+                return Err(IndexError::InvalidHitsPerGroup);
+            }
+        }
+        let pinned = self.pinned_documents(&rtxn, &query, &rules)?;
+        let ranked_after_search = restricted
+            || !excluded.is_empty()
+            || query.distinct.is_some()
+            || pinned.is_some()
+            || !alternative_queries.is_empty();
+
+        let sort_criteria = match query.sort {
+            Some(ref sort) => match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
+                Ok(sorts) => Some(sorts),
+                Err(asc_desc_error) => {
+                    return Err(IndexError::Milli(SortError::from(asc_desc_error).into()))
+                }
+            },
+            None => None,
+        };
+
+        // A placeholder search, browsing the documents matching a filter, doesn't need the ranking
+        // of milli when the hits are the candidates in the order of their ids, or sorted by a
+        // single attribute whose facet values are walked in order.
+        let browse = keywords.as_deref().map_or(true, |k| k.trim().is_empty())
+            && sort_criteria
+                .as_deref()
+                .map_or(true, |sort| browse_sort(sort).is_some())
+            && query.vector.is_none()
+            && !ranked_after_search
+            && self.browses_in_id_order(&rtxn)?;
+
+        // The documents are ranked by their similarity with the query vector after the search,
+        // milli must return all the candidates.
+        if let Some(ref vector) = query.vector {
+            if vector.is_empty() {
+                return Err(IndexError::EmptySearchVector);
+            }
+            if query.sort.is_some() {
+                return Err(IndexError::VectorWithSort);
+            }
+            match query.hybrid {
+                Some(ref hybrid) => {
+                    if !(0.0..=1.0).contains(&hybrid.semantic_ratio) {
+                        return Err(IndexError::InvalidSemanticRatio(hybrid.semantic_ratio));
+                    }
+                    // The keyword ranking is blended with the similarities, the best documents
+                    // reachable with the pagination are needed.
+                    search.limit(pagination_limited_to);
+                }
+                // The similarity is only computed for the documents matching the keywords in
+                // the restricted attributes.
+                None if restricted => {
+                    search.limit(pagination_limited_to);
+                }
+                None => {
+                    search.limit(0);
+                }
+            }
+        } else if query.hybrid.is_some() {
+            return Err(IndexError::HybridWithoutVector);
+        } else if ranked_after_search {
+            // The documents are filtered, grouped, or reordered by attribute, after the search,
+            // milli must return the best documents reachable with the pagination.
+            search.limit(pagination_limited_to);
+        } else if browse {
+            search.limit(0);
+        } else {
+            search.offset(offset);
+            search.limit(limit);
+        }
+
+        if let Some(ref filter) = filter {
+            if let Some(facets) = parse_filter(filter)? {
+                search.filter(facets);
+            }
+        }
+
+        if let Some(ref sort) = sort_criteria {
+            search.sort_criteria(sort.clone());
+        }
+
+        let milli::SearchResult {
+            documents_ids,
+            matching_words,
+            candidates,
+            ..
+        } = search.execute()?;
+
+        let browse_sort = sort_criteria.as_deref().and_then(browse_sort);
+        let documents_ids = match browse_sort {
+            Some((field, ascending)) if browse => {
+                match self.fields_ids_map(&rtxn)?.id(field) {
+                    Some(field_id) => {
+                        self.sorted_browse(&rtxn, &candidates, field_id, ascending, offset, limit)?
+                    }
+                    // No document has a value to sort on.
+                    None => candidates.iter().skip(offset).take(limit).collect(),
+                }
+            }
+            _ if browse => candidates.iter().skip(offset).take(limit).collect(),
+            _ => documents_ids,
+        };
+        let mut documents_ids: Vec<_> = documents_ids
+            .into_iter()
+            .filter(|id| !excluded.contains(*id))
+            .collect();
+        let mut candidates = candidates - &excluded;
+
+        // The documents only matching the queries rewritten with the synonyms of the rules are
+        // ranked after the documents matching the query.
+        for alternative in &alternative_queries {
+            let mut search = self.search(&rtxn);
+            search.query(alternative);
+            search.authorize_typos(authorize_typos);
+            search.optional_words(query.matching_strategy == MatchingStrategy::Last);
+            search.limit(pagination_limited_to);
+            if let Some(ref filter) = filter {
+                if let Some(facets) = parse_filter(filter)? {
+                    search.filter(facets);
+                }
+            }
+            if let Some(ref sort) = sort_criteria {
+                search.sort_criteria(sort.clone());
+            }
+            let result = search.execute()?;
+
+            let added = result.candidates - &excluded - &candidates;
+            documents_ids.extend(
+                result
+                    .documents_ids
+                    .into_iter()
+                    .filter(|id| added.contains(*id)),
+            );
+            candidates |= added;
+        }
+
+        let search_debug = if query.debug {
+            Some(self.search_debug(
+                &rtxn,
+                keywords.as_deref(),
+                authorize_typos,
+                query.filter.as_ref(),
+                &excluded,
+            )?)
+        } else {
+            None
+        };
+        let query_words = keywords
+            .as_deref()
+            .map(split_query_words)
+            .unwrap_or_default();
+        let searchable_fields = self.searchable_fields(&rtxn)?;
+
+        let fields_ids_map = self.fields_ids_map(&rtxn).unwrap();
+
+        let tokenizer = TokenizerBuilder::default().build();
+
+        let mut formatter_builder = MatcherBuilder::new(matching_words, tokenizer);
+        formatter_builder.crop_marker(query.crop_marker);
+        formatter_builder.highlight_prefix(query.highlight_pre_tag);
+        formatter_builder.highlight_suffix(query.highlight_post_tag);
+
+        // Only the documents reachable with the pagination are ranked by attribute, the number
+        // of hits is capped accordingly.
+        let (documents_ids, candidates) = if restricted {
+            let documents_ids = self.rank_by_attributes(
+                &rtxn,
+                &fields_ids_map,
+                &formatter_builder,
+                documents_ids,
+                query.attributes_to_search_on.as_deref(),
+                query.attribute_weights.as_ref(),
+            )?;
+            let candidates = documents_ids.iter().copied().collect();
+            (documents_ids, candidates)
+        } else {
+            (documents_ids, candidates)
+        };
+
+        // With a query vector, the documents are grouped once ranked by similarity.
+        let (documents_ids, candidates) = match query.distinct {
+            Some(ref distinct) if query.vector.is_none() => {
+                let documents_ids =
+                    self.group_by_distinct(&rtxn, &fields_ids_map, documents_ids, distinct)?;
+                let candidates = documents_ids.iter().copied().collect();
+                (documents_ids, candidates)
+            }
+            _ => (documents_ids, candidates),
+        };
+
+        let documents_ids = match pinned {
+            Some(ref pinned) if query.vector.is_none() => pinned.apply(documents_ids, &candidates),
+            _ => documents_ids,
+        };
+
+        let (documents_ids, candidates, vector_scores) = match query.vector {
+            Some(ref vector) => {
+                let (ranked, candidates) = match query.hybrid {
+                    // The semantic side isn't restricted to the documents matching the keywords,
+                    // and the documents matching the keywords without a vector are kept.
+                    Some(ref hybrid) => {
+                        let filtered =
+                            self.filtered_candidates(&rtxn, query.filter.as_ref())? - &excluded;
+                        let (similarities, exceeded) =
+                            self.rank_by_similarity(&rtxn, vector, &filtered, budget)?;
+                        degraded |= exceeded;
+                        let candidates = similarities
+                            .iter()
+                            .map(|(id, _)| *id)
+                            .collect::<RoaringBitmap>()
+                            | candidates;
+                        let ranked =
+                            blend_rankings(&documents_ids, similarities, hybrid.semantic_ratio);
+                        (ranked, candidates)
+                    }
+                    None => {
+                        let (similarities, exceeded) =
+                            self.rank_by_similarity(&rtxn, vector, &candidates, budget)?;
+                        degraded |= exceeded;
+                        let candidates: RoaringBitmap =
+                            similarities.iter().map(|(id, _)| *id).collect();
+                        let ranked = similarities
+                            .into_iter()
+                            .map(|(id, similarity)| {
+                                let scores = VectorScores {
+                                    semantic: Some(similarity),
+                                    hybrid: None,
+                                };
+                                (id, scores)
+                            })
+                            .collect();
+                        (ranked, candidates)
+                    }
+                };
+                let (ranked, candidates) = match query.distinct {
+                    Some(ref distinct) => {
+                        let ids = ranked.iter().map(|(id, _)| *id).collect();
+                        let scores: HashMap<_, _> = ranked.into_iter().collect();
+                        let ids = self.group_by_distinct(&rtxn, &fields_ids_map, ids, distinct)?;
+                        let candidates = ids.iter().copied().collect();
+                        let ranked = ids.into_iter().map(|id| (id, scores[&id])).collect();
+                        (ranked, candidates)
+                    }
+                    None => (ranked, candidates),
+                };
+                let ranked = match pinned {
+                    Some(ref pinned) => {
+                        let ids: Vec<_> = ranked.iter().map(|(id, _)| *id).collect();
+                        let ranked_ids = ids.iter().copied().collect();
+                        let scores: HashMap<_, _> = ranked.into_iter().collect();
+                        pinned
+                            .apply(ids, &ranked_ids)
+                            .into_iter()
+                            .map(|id| (id, scores[&id]))
+                            .collect()
+                    }
+                    None => ranked,
+                };
+                let (documents_ids, scores): (Vec<_>, Vec<_>) =
+                    ranked.into_iter().skip(offset).take(limit).unzip();
+                (documents_ids, candidates, Some(scores))
+            }
+            None if ranked_after_search => {
+                let documents_ids = documents_ids.into_iter().skip(offset).take(limit).collect();
+                (documents_ids, candidates, None)
+            }
+            None => (documents_ids, candidates, None),
+        };
+        let mut vector_scores = vector_scores.map(Vec::into_iter);
+
+        let displayed_ids = self
+            .displayed_fields_ids(&rtxn)?
+            .map(|fields| fields.into_iter().collect::<BTreeSet<_>>())
+            .unwrap_or_else(|| fields_ids_map.iter().map(|(id, _)| id).collect());
+
+        let fids = |attrs: &BTreeSet<String>| {
+            let mut ids = BTreeSet::new();
+            for attr in attrs {
+                if attr == "*" {
+                    ids = displayed_ids.clone();
+                    break;
+                }
+
+                if let Some(id) = fields_ids_map.id(attr) {
+                    ids.insert(id);
+                }
+            }
+            ids
+        };
+
+        // The attributes to retrieve are the ones explicitly marked as to retrieve (all by default),
+        // but these attributes must be also be present
+        // - in the fields_ids_map
+        // - in the the displayed attributes
+        let to_retrieve_ids: BTreeSet<_> = query
+            .attributes_to_retrieve
+            .as_ref()
+            .map(fids)
+            .unwrap_or_else(|| displayed_ids.clone())
+            .intersection(&displayed_ids)
+            .cloned()
+            .collect();
+
+        let attr_to_highlight = query.attributes_to_highlight.unwrap_or_default();
+
+        let attr_to_crop = query.attributes_to_crop.unwrap_or_default();
+
+        // Attributes in `formatted_options` correspond to the attributes that will be in `_formatted`
+        // These attributes are:
+        // - the attributes asked to be highlighted or cropped (with `attributesToCrop` or `attributesToHighlight`)
+        // - the attributes asked to be retrieved: these attributes will not be highlighted/cropped
+        // But these attributes must be also present in displayed attributes
+        let formatted_options = compute_formatted_options(
+            &attr_to_highlight,
+            &attr_to_crop,
+            query.crop_length,
+            &to_retrieve_ids,
+            &fields_ids_map,
+            &displayed_ids,
+        );
+
+        let mut documents = Vec::new();
+
+        let documents_iter = self.documents(&rtxn, documents_ids)?;
+
+        for (_id, obkv) in documents_iter {
+            // First generate a document with all the displayed fields
+            let displayed_document = make_document(&displayed_ids, &fields_ids_map, obkv)?;
+
+            // select the attributes to retrieve
+            let attributes_to_retrieve = to_retrieve_ids
+                .iter()
+                .map(|&fid| fields_ids_map.name(fid).expect("Missing field name"));
+            let mut document =
+                permissive_json_pointer::select_values(&displayed_document, attributes_to_retrieve);
+
+            let (matches_position, formatted) = format_fields(
+                &displayed_document,
+                &fields_ids_map,
+                &formatter_builder,
+                &formatted_options,
+                query.show_matches_position,
+                &displayed_ids,
+            )?;
+
+            if let Some(sort) = query.sort.as_ref() {
+                insert_geo_distance(sort, &mut document);
+            }
+
+            let scores = vector_scores
+                .as_mut()
+                .and_then(Iterator::next)
+                .unwrap_or_default();
+
+            let matched = (query.debug || query.ranking_score).then(|| {
+                hit_debug(
+                    &displayed_document,
+                    searchable_fields.as_deref(),
+                    &formatter_builder,
+                    &query_words,
+                )
+            });
+            let ranking_score = match (scores.hybrid.or(scores.semantic), &matched) {
+                _ if !query.ranking_score => None,
+                (Some(score), _) => Some(score as f64),
+                (None, Some(matched)) => Some(keyword_ranking_score(matched, &query_words)),
+                (None, None) => None,
+            };
+
+            let hit = SearchHit {
+                document,
+                formatted,
+                matches_position,
+                semantic_score: scores.semantic,
+                hybrid_score: scores.hybrid,
+                debug: matched.filter(|_| query.debug),
+                ranking_score,
+            };
+            documents.push(hit);
+        }
+
+        let estimated_total_hits = candidates.len();
+
+        let suggested_query = match keywords {
+            Some(ref keywords)
+                if query.show_suggested_query
+                    && estimated_total_hits < SUGGESTED_QUERY_MAX_HITS =>
+            {
+                self.suggest_query(&rtxn, keywords, filter.as_ref(), estimated_total_hits)?
+            }
+            _ => None,
+        };
+
+        let (facet_distribution, facet_stats) = match query.facets {
+            // The facets are left out rather than computed over a part of the candidates.
+            Some(_) if budget.exceeded() => {
+                degraded = true;
+                (None, None)
+            }
+            Some(ref fields) => {
+                let max_values_by_facet = self
+                    .max_values_per_facet(&rtxn)?
+                    .unwrap_or(DEFAULT_VALUES_PER_FACET);
+
+                // The distribution is truncated to `maxValuesPerFacet`, the stats and the values
+                // sorted by count must be computed over all the values of the facets.
+                let fields = expand_facets(fields, &self.filterable_fields(&rtxn)?);
+                let mut all_values = self.facets_distribution(&rtxn);
+                all_values.max_values_per_facet(usize::MAX);
+                if let Some(ref fields) = fields {
+                    all_values.facets(fields);
+                }
+                let all_values = all_values.candidates(candidates.clone()).execute()?;
+                let stats = compute_facet_stats(&all_values);
+
+                let distribution = if query.exhaustive_facet_count {
+                    truncate_facet_values(&all_values, max_values_by_facet)
+                } else {
+                    let mut facet_distribution = self.facets_distribution(&rtxn);
+                    facet_distribution.max_values_per_facet(max_values_by_facet);
+                    if let Some(ref fields) = fields {
+                        facet_distribution.facets(fields);
+                    }
+                    facet_distribution.candidates(candidates).execute()?
+                };
+                let distribution = sort_facet_values(
+                    distribution,
+                    all_values,
+                    &sort_facet_values_by(self, &rtxn)?,
+                    max_values_by_facet,
+                );
+
+                (Some(distribution), Some(stats))
+            }
+            None => (None, None),
+        };
+
+        let hits_info = if query.is_finite_pagination() {
+            let total_hits = min(estimated_total_hits, pagination_limited_to as u64);
+            let total_pages = match hits_per_page {
+                0 => 0,
+                hits_per_page => (total_hits as usize + hits_per_page - 1) / hits_per_page,
+            };
+            HitsInfo::Pagination {
+                hits_per_page,
+                page,
+                total_pages,
+                total_hits,
+            }
+        } else {
+            HitsInfo::OffsetLimit {
+                limit: query.limit,
+                offset: query.offset.unwrap_or_default(),
+                estimated_total_hits,
+            }
+        };
+
+        let result = SearchResult {
+            hits: documents,
+            query: query.q.clone().unwrap_or_default(),
+            hits_info,
+            processing_time_ms: before_search.elapsed().as_millis(),
+            facet_distribution,
+            facet_stats,
+            degraded,
+            ranking_variant,
+            suggested_query,
+            applied_rules: rules.iter().map(|rule| rule.id.clone()).collect(),
+            debug: search_debug,
+        };
+        Ok(result)
+    }
+
+    /// Replaces the words of the keywords missing from the index by the closest words of the
+    /// index, the most frequent first on equal distances. The corrected query is only returned
+    /// when it matches more documents than the `hits` of the original one.
+    fn suggest_query(
+        &self,
+        rtxn: &RoTxn,
+        keywords: &str,
+        filter: Option<&Value>,
+        hits: u64,
+    ) -> Result<Option<String>> {
+        let words_fst = self.words_fst(rtxn)?;
+        let mut corrected = false;
+        let mut words = Vec::new();
+
+        for word in split_query_words(keywords) {
+            let len = word.chars().count();
+            if len < 3 || words_fst.contains(&word) {
+                words.push(word);
+                continue;
+            }
+
+            let automaton = Levenshtein {
+                query: word.as_bytes(),
+                max_distance: if len < 5 { 1 } else { 2 },
+            };
+            let mut stream = words_fst.search(automaton).into_stream();
+            let mut best: Option<(usize, u64, String)> = None;
+            while let Some(candidate) = stream.next() {
+                let candidate = match std::str::from_utf8(candidate) {
+                    Ok(candidate) => candidate,
+                    Err(_) => continue,
+                };
+                let distance = edit_distance(&word, candidate);
+                let count = self
+                    .word_docids
+                    .get(rtxn, candidate)?
+                    .map_or(0, |docids| docids.len());
+                let is_better = best
+                    .as_ref()
+                    .map_or(true, |(best_distance, best_count, _)| {
+                        distance < *best_distance
+                            || (distance == *best_distance && count > *best_count)
+                    });
+                if is_better {
+                    best = Some((distance, count, candidate.to_string()));
+                }
+            }
+
+            match best {
+                Some((_, _, correction)) => {
+                    corrected = true;
+                    words.push(correction);
+                }
+                None => words.push(word),
+            }
+        }
+
+        if !corrected {
+            return Ok(None);
+        }
+
+        let suggested_query = words.join(" ");
+        let mut search = self.search(rtxn);
+        search.query(&suggested_query);
+        search.limit(0);
+        if let Some(filter) = filter {
+            if let Some(facets) = parse_filter(filter)? {
+                search.filter(facets);
+            }
+        }
+        let candidates = search.execute()?.candidates;
+
+        if candidates.len() > hits {
+            Ok(Some(suggested_query))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns the documents sharing the most terms with the searchable attributes of a document,
+    /// the document itself excluded.
+    pub fn perform_similar(&self, query: SimilarQuery) -> Result<SimilarResult> {
+        let before_search = Instant::now();
+        let rtxn = self.read_txn()?;
+
+        let internal_id = self
+            .external_documents_ids(&rtxn)?
+            .get(query.id.as_bytes())
+            .ok_or_else(|| IndexError::DocumentNotFound(query.id.clone()))?;
+
+        // A document the tenant token isn't allowed to see is reported missing, like a document
+        // that doesn't exist.
+        if let Some(ref rules_filter) = query.rules_filter {
+            let rules_filter = self.prepare_filter(&rtxn, rules_filter)?;
+            if let Some(rules) = parse_filter(&rules_filter)? {
+                if !rules.evaluate(&rtxn, self)?.contains(internal_id) {
+                    return Err(IndexError::DocumentNotFound(query.id.clone()));
+                }
+            }
+        }
+
+        let (_, obkv) = self
+            .documents(&rtxn, std::iter::once(internal_id))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| IndexError::DocumentNotFound(query.id.clone()))?;
+
+        let fields_ids_map = self.fields_ids_map(&rtxn)?;
+        let searchable_ids = self
+            .searchable_fields_ids(&rtxn)?
+            .unwrap_or_else(|| fields_ids_map.iter().map(|(id, _)| id).collect());
+        let values = searchable_ids
+            .into_iter()
+            .filter_map(|fid| obkv.get(fid))
+            .map(serde_json::from_slice)
+            .collect::<std::result::Result<Vec<Value>, _>>()?;
+        let terms = most_frequent_terms(&values, MAX_SIMILAR_TERMS);
+
+        let pagination_limited_to = self
+            .pagination_limited_to(&rtxn)?
+            .unwrap_or(DEFAULT_PAGINATION_MAX_TOTAL_HITS);
+        let offset = min(query.offset, pagination_limited_to);
+        let limit = min(query.limit, pagination_limited_to.saturating_sub(offset));
+
+        // Without terms, milli would return all the documents.
+        let (documents_ids, estimated_total_hits) = if terms.is_empty() {
+            (Vec::new(), 0)
+        } else {
+            let filter = query
+                .filter
+                .as_ref()
+                .map(|filter| self.prepare_filter(&rtxn, filter))
+                .transpose()?;
+            let mut search = self.search(&rtxn);
+            search.query(terms.join(" "));
+            // The document itself is most likely among the results, it's removed before
+            // applying the offset.
+            search.limit(offset + limit + 1);
+            if let Some(ref filter) = filter {
+                if let Some(facets) = parse_filter(filter)? {
+                    search.filter(facets);
+                }
+            }
+
+            let milli::SearchResult {
+                documents_ids,
+                mut candidates,
+                ..
+            } = search.execute()?;
+            candidates.remove(internal_id);
+
+            let documents_ids: Vec<_> = documents_ids
+                .into_iter()
+                .filter(|id| *id != internal_id)
+                .skip(offset)
+                .take(limit)
+                .collect();
+            (documents_ids, candidates.len())
+        };
+
+        let displayed_ids = self
+            .displayed_fields_ids(&rtxn)?
+            .map(|fields| fields.into_iter().collect::<BTreeSet<_>>())
+            .unwrap_or_else(|| fields_ids_map.iter().map(|(id, _)| id).collect());
+
+        let mut hits = Vec::new();
+        for (_id, obkv) in self.documents(&rtxn, documents_ids)? {
+            let document = make_document(&displayed_ids, &fields_ids_map, obkv)?;
+            let document = match query.attributes_to_retrieve {
+                Some(ref attributes_to_retrieve) => permissive_json_pointer::select_values(
+                    &document,
+                    attributes_to_retrieve.iter().map(String::as_str),
+                ),
+                None => document,
+            };
+            hits.push(document);
+        }
+
+        Ok(SimilarResult {
+            hits,
+            id: query.id,
+            offset,
+            limit,
+            estimated_total_hits,
+            processing_time_ms: before_search.elapsed().as_millis(),
+        })
+    }
+
+    /// Returns completions of the query: the popular queries starting with it first, then the
+    /// words of the documents completing its last word. Both tolerate typos on the part of the
+    /// query they complete, as the searches do. With a filter, the words are only taken from the
+    /// documents matching it and the popular queries, which can't be filtered, aren't suggested.
+    pub fn perform_suggest(
+        &self,
+        query: SuggestQuery,
+        popular_queries: Vec<QueryCount>,
+    ) -> Result<SuggestResult> {
+        let before_search = Instant::now();
+        let rtxn = self.read_txn()?;
+
+        let q = query.q.trim().to_lowercase();
+        let authorize_typos = self.authorize_typos(&rtxn)?;
+        let one_typo = self.min_word_len_one_typo(&rtxn)? as usize;
+        let two_typos = self.min_word_len_two_typos(&rtxn)? as usize;
+        let max_typos = |text: &str| match text.chars().count() {
+            _ if !authorize_typos => 0,
+            len if len >= two_typos => 2,
+            len if len >= one_typo => 1,
+            _ => 0,
+        };
+
+        let mut suggestions = Vec::new();
+
+        if query.filter.is_none() {
+            let mut queries: Vec<_> = popular_queries
+                .into_iter()
+                .filter(|popular| popular.q != q)
+                .filter_map(|popular| {
+                    let typos = prefix_typos(&q, &popular.q, max_typos(&q))?;
+                    Some((typos, popular))
+                })
+                .collect();
+            queries.sort_by(|(a_typos, a), (b_typos, b)| {
+                a_typos.cmp(b_typos).then_with(|| b.count.cmp(&a.count))
+            });
+            suggestions.extend(queries.into_iter().map(|(_, popular)| Suggestion {
+                suggestion: popular.q,
+                source: SuggestionSource::Query,
+                count: popular.count,
+            }));
+        }
+
+        let (prefix, last_word) = match q.rsplit_once(char::is_whitespace) {
+            Some((prefix, last_word)) => (prefix.trim_end(), last_word),
+            None => ("", q.as_str()),
+        };
+        if !last_word.is_empty() {
+            let candidates = query
+                .filter
+                .as_ref()
+                .map(|filter| self.filtered_candidates(&rtxn, Some(filter)))
+                .transpose()?;
+            let typos = max_typos(last_word);
+            // With typos, the first character of the words must still match.
+            let scanned_prefix: String = if typos == 0 {
+                last_word.to_string()
+            } else {
+                last_word.chars().take(1).collect()
+            };
+
+            let words_fst = self.words_fst(&rtxn)?;
+            let mut stream = words_fst
+                .search(fst::automaton::Str::new(&scanned_prefix).starts_with())
+                .into_stream();
+            let mut words = Vec::new();
+            while let Some(word) = stream.next() {
+                if words.len() == SUGGEST_MAX_SCANNED_WORDS {
+                    break;
+                }
+                let word = match std::str::from_utf8(word) {
+                    Ok(word) if word != last_word => word,
+                    _ => continue,
+                };
+                if let Some(typos) = prefix_typos(last_word, word, typos) {
+                    words.push((typos, word.to_string()));
+                }
+            }
+
+            let mut terms = Vec::with_capacity(words.len());
+            for (typos, word) in words {
+                let docids = self.word_docids.get(&rtxn, &word)?.unwrap_or_default();
+                let count = match candidates {
+                    Some(ref candidates) => docids.intersection_len(candidates),
+                    None => docids.len(),
+                };
+                if count > 0 {
+                    terms.push((typos, word, count));
+                }
+            }
+            terms.sort_by(|(a_typos, a, a_count), (b_typos, b, b_count)| {
+                a_typos
+                    .cmp(b_typos)
+                    .then_with(|| b_count.cmp(a_count))
+                    .then_with(|| a.cmp(b))
+            });
+
+            for (_, word, count) in terms {
+                let suggestion = if prefix.is_empty() {
+                    word
+                } else {
+                    format!("{} {}", prefix, word)
+                };
+                if suggestions
+                    .iter()
+                    .all(|s: &Suggestion| s.suggestion != suggestion)
+                {
+                    suggestions.push(Suggestion {
+                        suggestion,
+                        source: SuggestionSource::Term,
+                        count,
+                    });
+                }
+            }
+        }
+
+        suggestions.truncate(query.limit);
+
+        Ok(SuggestResult {
+            suggestions,
+            query: query.q,
+            processing_time_ms: before_search.elapsed().as_millis(),
+        })
+    }
+
+    /// Returns the values of a facet matching the facet query, with the number of documents
+    /// matching both the value and the search query and filter. At most `maxValuesPerFacet`
+    /// values are returned, the most frequent first.
+    pub fn perform_facet_search(&self, query: FacetSearchQuery) -> Result<FacetSearchResult> {
+        let before_search = Instant::now();
+        let rtxn = self.read_txn()?;
+
+        let filterable_fields = self.filterable_fields(&rtxn)?;
+        let is_filterable = filterable_fields.iter().any(|field| {
+            query.facet_name == *field || query.facet_name.starts_with(&format!("{}.", field))
+        });
+        if !is_filterable {
+            let mut filterable_fields: Vec<_> = filterable_fields.into_iter().collect();
+            filterable_fields.sort();
+            return Err(FacetError::NotFilterable(query.facet_name, filterable_fields).into());
+        }
+
+        let filter = query
+            .filter
+            .as_ref()
+            .map(|filter| self.prepare_filter(&rtxn, filter))
+            .transpose()?;
+        let mut search = self.search(&rtxn);
+        search.limit(0);
+        if let Some(ref q) = query.q {
+            search.query(q);
+        }
+        if let Some(ref filter) = filter {
+            if let Some(facets) = parse_filter(filter)? {
+                search.filter(facets);
+            }
+        }
+        let candidates = search.execute()?.candidates;
+
+        // All the values are needed to find the matching ones, the limit is applied afterward.
+        let mut facet_distribution = self.facets_distribution(&rtxn);
+        facet_distribution
+            .facets(std::iter::once(&query.facet_name))
+            .max_values_per_facet(usize::MAX)
+            .candidates(candidates);
+        let mut distribution = facet_distribution.execute()?;
+
+        let mut facet_hits: Vec<_> = distribution
+            .remove(&query.facet_name)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(value, _)| {
+                query
+                    .facet_query
+                    .as_ref()
+                    .map_or(true, |facet_query| facet_value_matches(value, facet_query))
+            })
+            .map(|(value, count)| FacetValueHit { value, count })
+            .collect();
+
+        facet_hits.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        let max_values_per_facet = self
+            .max_values_per_facet(&rtxn)?
+            .unwrap_or(DEFAULT_VALUES_PER_FACET);
+        facet_hits.truncate(max_values_per_facet);
+
+        Ok(FacetSearchResult {
+            facet_hits,
+            facet_query: query.facet_query,
+            processing_time_ms: before_search.elapsed().as_millis(),
+        })
+    }
+
+    /// Checks that the attributes the query is restricted to, or boosted on, are searchable and
+    /// that their weights are positive numbers.
+    fn check_attributes_to_search_on(&self, rtxn: &RoTxn, query: &SearchQuery) -> Result<()> {
+        let searchable_fields = self.searchable_fields(rtxn)?;
+        let is_searchable = |attribute: &str| match searchable_fields {
+            Some(ref fields) => fields
+                .iter()
+                .any(|field| milli::is_faceted_by(attribute, field)),
+            None => true,
+        };
+
+        let attributes = query.attributes_to_search_on.iter().flatten();
+        let weighted = query.attribute_weights.iter().flat_map(BTreeMap::keys);
+        let not_searchable = attributes
+            .chain(weighted)
+            .find(|a| !is_searchable(a.as_str()));
+        if let Some(attribute) = not_searchable {
+            let available = searchable_fields
+                .map(|fields| fields.into_iter().map(String::from).collect())
+                .unwrap_or_default();
+            return Err(IndexError::NotSearchable(attribute.clone(), available));
+        }
+
+        if let Some((attribute, weight)) = query
+            .attribute_weights
+            .iter()
+            .flatten()
+            .find(|(_, weight)| !weight.is_finite() || **weight < 0.0)
+        {
+            return Err(IndexError::InvalidAttributeWeight(
+                attribute.clone(),
+                *weight,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Reorders the documents by the weight of the best attribute matching the query, the order
+    /// of milli breaking the ties. The weight of an attribute is 1 when it isn't given. When the
+    /// query is restricted to some attributes, the documents matching none of them are left out,
+    /// otherwise they are ranked last.
+    fn rank_by_attributes<'a, A: AsRef<[u8]>>(
+        &self,
+        rtxn: &RoTxn,
+        fields_ids_map: &FieldsIdsMap,
+        builder: &MatcherBuilder<'a, A>,
+        documents_ids: Vec<DocumentId>,
+        attributes_to_search_on: Option<&[String]>,
+        attribute_weights: Option<&BTreeMap<String, f32>>,
+    ) -> Result<Vec<DocumentId>> {
+        let all_ids: BTreeSet<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let attributes: Vec<&str> = match attributes_to_search_on {
+            Some(attributes) => attributes.iter().map(String::as_str).collect(),
+            None => match self.searchable_fields(rtxn)? {
+                Some(fields) => fields,
+                None => fields_ids_map.iter().map(|(_, name)| name).collect(),
+            },
+        };
+        let weight_of = |attribute: &str| {
+            attribute_weights
+                .and_then(|weights| weights.get(attribute))
+                .copied()
+                .unwrap_or(1.0)
+        };
+
+        let mut ranked = Vec::with_capacity(documents_ids.len());
+        for (id, obkv) in self.documents(rtxn, documents_ids)? {
+            let document = make_document(&all_ids, fields_ids_map, obkv)?;
+            let best_weight = attributes
+                .iter()
+                .filter(|attribute| {
+                    let selected = permissive_json_pointer::select_values(&document, [**attribute]);
+                    selected
+                        .values()
+                        .any(|value| value_matches_query(value, builder))
+                })
+                .map(|attribute| weight_of(*attribute))
+                .reduce(f32::max);
+
+            match best_weight {
+                Some(weight) => ranked.push((id, weight)),
+                None if attributes_to_search_on.is_none() => ranked.push((id, f32::NEG_INFINITY)),
+                None => (),
+            }
+        }
+
+        // The sort is stable: on equal weights, the documents keep the order of milli.
+        ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        Ok(ranked.into_iter().map(|(id, _)| id).collect())
+    }
+
+    /// Keeps at most `hitsPerGroup` documents per value of the distinct attribute, in the order of
+    /// their best document. The documents without the attribute are all kept, each in its own
+    /// group.
+    fn group_by_distinct(
+        &self,
+        rtxn: &RoTxn,
+        fields_ids_map: &FieldsIdsMap,
+        documents_ids: Vec<DocumentId>,
+        distinct: &DistinctQuery,
+    ) -> Result<Vec<DocumentId>> {
+        let all_ids: BTreeSet<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let mut groups: Vec<Vec<DocumentId>> = Vec::new();
+        let mut group_of_value: HashMap<String, usize> = HashMap::new();
+
+        for (id, obkv) in self.documents(rtxn, documents_ids)? {
+            let document = make_document(&all_ids, fields_ids_map, obkv)?;
+            let selected =
+                permissive_json_pointer::select_values(&document, [distinct.attribute.as_str()]);
+            if selected.is_empty() {
+                groups.push(vec![id]);
+                continue;
+            }
+
+            let value = serde_json::to_string(&selected)?;
+            match group_of_value.get(&value) {
+                Some(&group) => {
+                    if groups[group].len() < distinct.hits_per_group {
+                        groups[group].push(id);
+                    }
+                }
+                None => {
+                    group_of_value.insert(value, groups.len());
+                    groups.push(vec![id]);
+                }
+            }
+        }
+
+        Ok(groups.into_iter().flatten().collect())
+    }
+
+    /// The documents to boost and bury by the search and the rules applying to it. The documents
+    /// pinned by the rules are placed before the ones boosted by the search.
+    fn pinned_documents(
+        &self,
+        rtxn: &RoTxn,
+        query: &SearchQuery,
+        rules: &[&Rule],
+    ) -> Result<Option<PinnedDocuments>> {
+        let has_consequence = rules
+            .iter()
+            .any(|rule| !rule.consequence.pin.is_empty() || rule.consequence.boost.is_some());
+        if query.boost_documents.is_none() && query.bury_documents.is_none() && !has_consequence {
+            return Ok(None);
+        }
+
+        let ids = |ids: &Option<Vec<Value>>| -> Vec<String> {
+            ids.iter()
+                .flatten()
+                .map(|id| {
+                    id.as_str()
+                        .map(String::from)
+                        .unwrap_or_else(|| id.to_string())
+                })
+                .collect()
+        };
+
+        let pinned = rules
+            .iter()
+            .flat_map(|rule| rule.consequence.pin.iter().cloned());
+        let mut boosted = self.internal_ids(rtxn, pinned.chain(ids(&query.boost_documents)))?;
+        let mut seen = RoaringBitmap::new();
+        boosted.retain(|id| seen.insert(*id));
+
+        let mut promoted = RoaringBitmap::new();
+        for boost in rules
+            .iter()
+            .filter_map(|rule| rule.consequence.boost.as_ref())
+        {
+            promoted |= self.filtered_candidates(rtxn, Some(&Value::String(boost.clone())))?;
+        }
+
+        Ok(Some(PinnedDocuments {
+            boosted,
+            promoted,
+            buried: self
+                .internal_ids(rtxn, ids(&query.bury_documents))?
+                .into_iter()
+                .collect(),
+        }))
+    }
+
+    /// The internal ids of the documents, the unknown ids are ignored.
+    fn internal_ids(
+        &self,
+        rtxn: &RoTxn,
+        ids: impl IntoIterator<Item = String>,
+    ) -> Result<Vec<DocumentId>> {
+        let external_ids = self.external_documents_ids(rtxn)?;
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| external_ids.get(id.as_bytes()))
+            .collect())
+    }
+
+    /// Drops the terms of the keywords contained by the most documents, one at a time, until some
+    /// documents matching the filter contain all the remaining terms. The last term is never
+    /// dropped.
+    fn drop_frequent_terms(
+        &self,
+        rtxn: &RoTxn,
+        keywords: &str,
+        authorize_typos: bool,
+        filter: Option<&Value>,
+    ) -> Result<String> {
+        let count_candidates = |query: &str| -> Result<u64> {
+            let mut search = self.search(rtxn);
+            search.query(query);
+            search.authorize_typos(authorize_typos);
+            search.optional_words(false);
+            search.limit(0);
+            if let Some(filter) = filter {
+                if let Some(facets) = parse_filter(filter)? {
+                    search.filter(facets);
+                }
+            }
+            Ok(search.execute()?.candidates.len())
+        };
+
+        let mut terms = split_query_terms(keywords);
+        let mut frequencies = terms
+            .iter()
+            .map(|term| count_candidates(term))
+            .collect::<Result<Vec<_>>>()?;
+
+        while terms.len() > 1 && count_candidates(&terms.join(" "))? == 0 {
+            // On equal frequencies, the last term is dropped first.
+            let most_frequent = frequencies
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, frequency)| **frequency)
+                .map(|(i, _)| i)
+                .unwrap_or_default();
+            terms.remove(most_frequent);
+            frequencies.remove(most_frequent);
+        }
+
+        Ok(terms.join(" "))
+    }
+
+    /// Returns the documents containing one of the terms, without typos nor prefixes. A term made
+    /// of several words matches the documents containing them as a phrase.
+    fn documents_containing(&self, rtxn: &RoTxn, terms: &[String]) -> Result<RoaringBitmap> {
+        let mut documents = RoaringBitmap::new();
+        for term in terms {
+            let mut search = self.search(rtxn);
+            search.query(format!("\"{}\"", term));
+            search.authorize_typos(false);
+            search.optional_words(false);
+            search.limit(0);
+            documents |= search.execute()?.candidates;
+        }
+        Ok(documents)
+    }
+
+    /// Converts the object form of the filters into expressions, and rewrites the `CONTAINS` and
+    /// `STARTS WITH` conditions, which milli doesn't support, into equalities with the values of
+    /// the attribute matching the condition.
+    pub(crate) fn prepare_filter(&self, rtxn: &RoTxn, filter: &Value) -> Result<Value> {
+        let filterable_fields = self.filterable_fields(rtxn)?;
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        let mut expand =
+            |attribute: &str, operator: StringOperator, pattern: &str| -> Result<Vec<String>> {
+                let is_filterable = filterable_fields
+                    .iter()
+                    .any(|field| milli::is_faceted_by(attribute, field));
+                if !is_filterable {
+                    let mut filterable_fields: Vec<_> = filterable_fields.iter().cloned().collect();
+                    filterable_fields.sort();
+                    return Err(IndexError::from(FacetError::StringOperatorOnNotFilterable(
+                        attribute.to_string(),
+                        operator.name(),
+                        filterable_fields,
+                    )));
+                }
+
+                match fields_ids_map.id(attribute) {
+                    Some(field_id) => self
+                        .string_facet_values_matching(rtxn, attribute, field_id, operator, pattern),
+                    // No document has a value for the attribute.
+                    None => Ok(Vec::new()),
+                }
+            };
+
+        let mut locate = |bounding_box: &GeoBoundingBox| -> Result<Vec<[f64; 2]>> {
+            if !filterable_fields.contains("_geo") {
+                let mut filterable_fields: Vec<_> = filterable_fields.iter().cloned().collect();
+                filterable_fields.sort();
+                return Err(FacetError::GeoBoundingBoxOnNotFilterable(filterable_fields).into());
+            }
+            self.geo_points_in_bounding_box(rtxn, bounding_box)
+        };
+
+        let mut expand_expression = |expr: &str| -> Result<Value> {
+            let expanded = expand_string_conditions(expr, &mut expand)?;
+            Ok(Value::String(
+                expand_geo_bounding_boxes(&expanded, &mut locate)?.into_owned(),
+            ))
+        };
+
+        let mut expand_value = |value: &Value| match value {
+            Value::String(expr) => expand_expression(expr),
+            Value::Object(_) => expand_expression(&filter_object_to_expression(value)?),
+            value => Ok(value.clone()),
+        };
+
+        let expanded = match filter {
+            Value::Array(values) => {
+                let mut expanded = Vec::with_capacity(values.len());
+                for value in values {
+                    expanded.push(match value {
+                        Value::Array(ors) => {
+                            Value::Array(ors.iter().map(&mut expand_value).collect::<Result<_>>()?)
+                        }
+                        value => expand_value(value)?,
+                    });
+                }
+                Value::Array(expanded)
+            }
+            value => expand_value(value)?,
+        };
+
+        Ok(expanded)
+    }
+
+    /// Returns the values of the string facet of a field matching a string operator. The values
+    /// are read from the facet database, only the ones sharing the prefix for `STARTS WITH`, and
+    /// there can't be more than `MAX_STRING_OPERATOR_VALUES` of them.
+    fn string_facet_values_matching(
+        &self,
+        rtxn: &RoTxn,
+        attribute: &str,
+        field_id: FieldId,
+        operator: StringOperator,
+        pattern: &str,
+    ) -> Result<Vec<String>> {
+        // The keys hold the lowercased values, a `CONTAINS` has to go through all of them.
+        let prefix = match operator {
+            StringOperator::StartsWith => pattern.to_lowercase(),
+            StringOperator::Contains => String::new(),
+        };
+        let prefix = FacetStringLevelZeroCodec::bytes_encode(&(field_id, prefix.as_str()))
+            .ok_or(milli::heed::Error::Encoding)?;
+
+        let mut values = Vec::new();
+        let database = self.facet_id_string_docids.remap_key_type::<ByteSlice>();
+        for result in database.prefix_iter(rtxn, &prefix)? {
+            let (_, (value, _)) = result?;
+            if operator.matches(value, pattern) {
+                if values.len() == MAX_STRING_OPERATOR_VALUES {
+                    return Err(FacetError::TooManyStringOperatorValues(
+                        attribute.to_string(),
+                        operator.name(),
+                        MAX_STRING_OPERATOR_VALUES,
+                    )
+                    .into());
+                }
+                values.push(value.to_string());
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Returns the distinct locations of the documents within the bounding box, read from the geo
+    /// index. There can't be more than `MAX_GEO_BOUNDING_BOX_POINTS` of them.
+    fn geo_points_in_bounding_box(
+        &self,
+        rtxn: &RoTxn,
+        bounding_box: &GeoBoundingBox,
+    ) -> Result<Vec<[f64; 2]>> {
+        let rtree = match self.geo_rtree(rtxn)? {
+            Some(rtree) => rtree,
+            // No document has a location.
+            None => return Ok(Vec::new()),
+        };
+
+        let mut points: Vec<_> = rtree
+            .iter()
+            .map(|point| point.data.1)
+            .filter(|&point| bounding_box.contains(point))
+            .collect();
+        points.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        points.dedup();
+        if points.len() > MAX_GEO_BOUNDING_BOX_POINTS {
+            return Err(
+                FacetError::TooManyGeoBoundingBoxPoints(MAX_GEO_BOUNDING_BOX_POINTS).into(),
+            );
+        }
+
+        Ok(points)
+    }
+
+    /// Counts the documents matching the keywords of a search, and the ones kept by each of its
+    /// filters on their own.
+    fn search_debug(
+        &self,
+        rtxn: &RoTxn,
+        keywords: Option<&str>,
+        authorize_typos: bool,
+        filter: Option<&Value>,
+        excluded: &RoaringBitmap,
+    ) -> Result<SearchDebug> {
+        let mut search = self.search(rtxn);
+        if let Some(keywords) = keywords {
+            search.query(keywords);
+        }
+        search.authorize_typos(authorize_typos);
+        search.limit(0);
+        let matching = search.execute()?.candidates;
+        let excluded_by_negative_terms = (&matching & excluded).len();
+        let matching = matching - excluded;
+
+        let filters = match filter {
+            Some(Value::Array(filters)) => filters.clone(),
+            Some(filter) => vec![filter.clone()],
+            None => Vec::new(),
+        };
+        let filters = filters
+            .into_iter()
+            .map(|filter| {
+                let kept = self.filtered_candidates(rtxn, Some(&filter))? & &matching;
+                Ok(FilterDebug {
+                    filter,
+                    candidates: kept.len(),
+                    pruned: matching.len() - kept.len(),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(SearchDebug {
+            ranking_rules: self
+                .criteria(rtxn)?
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            candidates_without_filters: matching.len(),
+            excluded_by_negative_terms,
+            filters,
+        })
+    }
+
+    /// Pages through the candidates of a placeholder search sorted by a single attribute, by
+    /// walking its facet databases in order instead of ranking them. Like milli does, a document
+    /// comes at its smallest value in the ascending order and at its biggest in the descending
+    /// order, the numbers before the strings, and the documents without a value last. The
+    /// documents sharing a value are in the order of their ids.
+    fn sorted_browse(
+        &self,
+        rtxn: &RoTxn,
+        candidates: &RoaringBitmap,
+        field_id: FieldId,
+        ascending: bool,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<DocumentId>> {
+        // The level 0 of the number facets holds a key per value, ordered like the values.
+        let mut number_prefix = field_id.to_be_bytes().to_vec();
+        number_prefix.push(0);
+        let numbers = self.facet_id_f64_docids.remap_key_type::<ByteSlice>();
+        let numbers: Box<dyn Iterator<Item = milli::heed::Result<RoaringBitmap>> + '_> =
+            if ascending {
+                let iter = numbers.prefix_iter(rtxn, &number_prefix)?;
+                Box::new(iter.map(|r| r.map(|(_, d)| d)))
+            } else {
+                let iter = numbers.rev_prefix_iter(rtxn, &number_prefix)?;
+                Box::new(iter.map(|r| r.map(|(_, d)| d)))
+            };
+
+        let string_prefix = FacetStringLevelZeroCodec::bytes_encode(&(field_id, ""))
+            .ok_or(milli::heed::Error::Encoding)?;
+        let strings = self.facet_id_string_docids.remap_key_type::<ByteSlice>();
+        let strings: Box<dyn Iterator<Item = milli::heed::Result<RoaringBitmap>> + '_> =
+            if ascending {
+                let iter = strings.prefix_iter(rtxn, &string_prefix)?;
+                Box::new(iter.map(|r| r.map(|(_, (_, d))| d)))
+            } else {
+                let iter = strings.rev_prefix_iter(rtxn, &string_prefix)?;
+                Box::new(iter.map(|r| r.map(|(_, (_, d))| d)))
+            };
+
+        let buckets = numbers
+            .chain(strings)
+            // the documents without a value come last
+            .chain(std::iter::once(Ok(candidates.clone())));
+
+        let mut remaining = candidates.clone();
+        let mut skip = offset as u64;
+        let mut page = Vec::with_capacity(limit);
+        for bucket in buckets {
+            let bucket = bucket? & &remaining;
+            remaining -= &bucket;
+            if skip >= bucket.len() {
+                skip -= bucket.len();
+                continue;
+            }
+            let missing = limit - page.len();
+            page.extend(bucket.iter().skip(skip as usize).take(missing));
+            skip = 0;
+            if page.len() >= limit {
+                break;
+            }
+        }
+
+        Ok(page)
+    }
+
+    /// Whether the hits of a placeholder search without sort, or sorted by a single attribute,
+    /// are its candidates in the order of their ids or of the values of the attribute, which isn't
+    /// the case when the ranking rules sort them by an attribute or when the index has a distinct
+    /// attribute. Only then can the search page through its candidates without ranking them.
+    fn browses_in_id_order(&self, rtxn: &RoTxn) -> Result<bool> {
+        let sorted = self
+            .criteria(rtxn)?
+            .iter()
+            .any(|criterion| matches!(criterion, Criterion::Asc(_) | Criterion::Desc(_)));
+        Ok(!sorted && self.distinct_field(rtxn)?.is_none())
+    }
+
+    /// Returns the documents matching the filter, whatever the keywords of the query.
+    fn filtered_candidates(&self, rtxn: &RoTxn, filter: Option<&Value>) -> Result<RoaringBitmap> {
+        let filter = filter
+            .map(|filter| self.prepare_filter(rtxn, filter))
+            .transpose()?;
+        let mut search = self.search(rtxn);
+        search.limit(0);
+        if let Some(ref filter) = filter {
+            if let Some(facets) = parse_filter(filter)? {
+                search.filter(facets);
+            }
+        }
+        Ok(search.execute()?.candidates)
+    }
+
+    /// Ranks the candidates by the similarity of their `_vectors` with the query vector, the most
+    /// similar first. All the candidates are scanned, the ones without a vector of the dimensions
+    /// of the query vector are left out. When the budget is exceeded, the scan stops and only the
+    /// candidates scanned so far are ranked, which is signaled by the returned boolean.
+    ///
+    /// The vectors are read from the vectors stored along with the documents when they're
+    /// indexed, not from the documents. There is no approximate nearest neighbours index, every
+    /// candidate is compared to the query vector, filters are the way to keep their number low.
+    fn rank_by_similarity(
+        &self,
+        rtxn: &RoTxn,
+        vector: &[f32],
+        candidates: &RoaringBitmap,
+        budget: SearchBudget,
+    ) -> Result<(Vec<(DocumentId, f32)>, bool)> {
+        let mut ranked = Vec::new();
+        let mut exceeded = false;
+        let ids: Vec<_> = candidates.iter().collect();
+        for chunk in ids.chunks(SIMILARITY_CHUNK_SIZE) {
+            if budget.exceeded() {
+                exceeded = true;
+                break;
+            }
+
+            for &id in chunk {
+                let vectors = document_vectors(self, rtxn, id)?;
+                if let Some(similarity) = document_similarity(&vectors, vector) {
+                    ranked.push((id, similarity));
+                }
+            }
+        }
+
+        // The sort is stable: on equal similarities, the documents keep the order of their ids.
+        ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        Ok((ranked, exceeded))
+    }
+}
+
+/// Returns whether a string, or a number, of the value contains a word of the query.
+fn value_matches_query<'a, A: AsRef<[u8]>>(value: &Value, builder: &MatcherBuilder<'a, A>) -> bool {
+    match value {
+        Value::String(s) => !builder.build(s).matches().is_empty(),
+        Value::Number(number) => !builder.build(&number.to_string()).matches().is_empty(),
+        Value::Array(values) => values.iter().any(|v| value_matches_query(v, builder)),
+        Value::Object(object) => object.values().any(|v| value_matches_query(v, builder)),
+        _ => false,
+    }
+}
+
+/// Returns the distinct words of the keywords of a search, lowercased.
+fn split_query_words(keywords: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for word in keywords.split(|c: char| !c.is_alphanumeric()) {
+        let word = word.to_lowercase();
+        if !word.is_empty() && !words.contains(&word) {
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// Splits the keywords of a search on whitespaces, the phrases are kept whole with their quotes.
+fn split_query_terms(keywords: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut term = String::new();
+    let mut in_phrase = false;
+    for c in keywords.chars() {
+        if c == '"' {
+            in_phrase = !in_phrase;
+        }
+        if c.is_whitespace() && !in_phrase {
+            if !term.is_empty() {
+                terms.push(std::mem::take(&mut term));
+            }
+        } else {
+            term.push(c);
+        }
+    }
+    if !term.is_empty() {
+        terms.push(term);
+    }
+    terms
+}
+
+/// Matches the words at most `max_distance` single byte insertions, deletions or substitutions
+/// away from the query, so the dictionary of the index is searched without being scanned whole.
+struct Levenshtein<'a> {
+    query: &'a [u8],
+    max_distance: usize,
+}
+
+impl Automaton for Levenshtein<'_> {
+    /// The distances between the query prefixes and the bytes accepted so far, `None` once all of
+    /// them exceed the max distance.
+    type State = Option<Vec<usize>>;
+
+    fn start(&self) -> Self::State {
+        Some((0..=self.query.len()).collect())
+    }
 
-        let estimated_total_hits = candidates.len();
+    fn is_match(&self, state: &Self::State) -> bool {
+        state
+            .as_ref()
+            .and_then(|row| row.last())
+            .map_or(false, |&distance| distance <= self.max_distance)
+    }
 
-        let facet_distribution = match query.facets {
-            Some(ref fields) => {
-                let mut facet_distribution = self.facets_distribution(&rtxn);
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.is_some()
+    }
 
-                let max_values_by_facet = self
-                    .max_values_per_facet(&rtxn)?
-                    .unwrap_or(DEFAULT_VALUES_PER_FACET);
-                facet_distribution.max_values_per_facet(max_values_by_facet);
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        let row = state.as_ref()?;
+        let mut next = Vec::with_capacity(row.len());
+        next.push(row[0] + 1);
+        for (i, &b) in self.query.iter().enumerate() {
+            let substitution = row[i] + usize::from(b != byte);
+            next.push(substitution.min(row[i + 1] + 1).min(next[i] + 1));
+        }
+        if next.iter().any(|&distance| distance <= self.max_distance) {
+            Some(next)
+        } else {
+            None
+        }
+    }
+}
 
-                if fields.iter().all(|f| f != "*") {
-                    facet_distribution.facets(fields);
-                }
-                let distribution = facet_distribution.candidates(candidates).execute()?;
+/// The number of single character insertions, deletions or substitutions turning a word into
+/// another.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
 
-                Some(distribution)
+/// Lists the words of the searchable attributes of a document matching the words of the query,
+/// each one with the query word it's the closest to.
+fn hit_debug<'a, A: AsRef<[u8]>>(
+    document: &Document,
+    searchable_fields: Option<&[&str]>,
+    builder: &MatcherBuilder<'a, A>,
+    query_words: &[String],
+) -> HitDebug {
+    let mut matched_words: Vec<MatchedWord> = Vec::new();
+    for (attribute, value) in document {
+        if searchable_fields.map_or(false, |fields| !fields.contains(&attribute.as_str())) {
+            continue;
+        }
+
+        let mut texts = Vec::new();
+        collect_strings(value, &mut texts);
+        for text in texts {
+            for MatchBounds { start, length } in builder.build(text).matches() {
+                let document_word = text[start..start + length].to_lowercase();
+                let closest = query_words
+                    .iter()
+                    .map(|word| (word, edit_distance(word, &document_word)))
+                    .min_by_key(|(_, typos)| *typos);
+                if let Some((query_word, typos)) = closest {
+                    let matched = MatchedWord {
+                        attribute: attribute.clone(),
+                        query_word: query_word.clone(),
+                        document_word,
+                        typos,
+                    };
+                    if !matched_words.contains(&matched) {
+                        matched_words.push(matched);
+                    }
+                }
             }
-            None => None,
-        };
+        }
+    }
 
-        let result = SearchResult {
-            hits: documents,
-            estimated_total_hits,
-            query: query.q.clone().unwrap_or_default(),
-            limit: query.limit,
-            offset: query.offset.unwrap_or_default(),
-            processing_time_ms: before_search.elapsed().as_millis(),
-            facet_distribution,
-        };
-        Ok(result)
+    let best_typos: Vec<usize> = query_words
+        .iter()
+        .filter_map(|word| {
+            matched_words
+                .iter()
+                .filter(|matched| &matched.query_word == word)
+                .map(|matched| matched.typos)
+                .min()
+        })
+        .collect();
+
+    HitDebug {
+        matched_words,
+        matched_query_words: best_typos.len(),
+        typos: best_typos.iter().sum(),
+    }
+}
+
+/// The attribute a placeholder search sorts by, and whether it's in the ascending order, when it
+/// sorts by a single attribute, which the search can page through without ranking.
+fn browse_sort(sort: &[AscDesc]) -> Option<(&str, bool)> {
+    match sort {
+        [AscDesc::Asc(Member::Field(field))] => Some((field, true)),
+        [AscDesc::Desc(Member::Field(field))] => Some((field, false)),
+        _ => None,
+    }
+}
+
+/// Scores the words of the query found in a hit, each one counting less with the typos of its best
+/// match, like the `words` and `typo` ranking rules do. A search without words scores 1.
+fn keyword_ranking_score(matched: &HitDebug, query_words: &[String]) -> f64 {
+    if query_words.is_empty() {
+        return 1.0;
     }
+
+    let found: f64 = query_words
+        .iter()
+        .filter_map(|word| {
+            matched
+                .matched_words
+                .iter()
+                .filter(|matched| &matched.query_word == word)
+                // a prefix match isn't a typo
+                .map(
+                    |matched| match matched.document_word.starts_with(word.as_str()) {
+                        true => 0,
+                        false => matched.typos,
+                    },
+                )
+                .min()
+        })
+        .map(|typos| 1.0 / (1 + typos) as f64)
+        .sum();
+    found / query_words.len() as f64
 }
 
 fn insert_geo_distance(sorts: &[String], document: &mut Document) {
@@ -328,10 +2714,8 @@ fn add_highlight_to_formatted_options(
             break;
         }
 
-        if let Some(id) = fields_ids_map.id(attr) {
-            if displayed_ids.contains(&id) {
-                formatted_options.insert(id, new_format);
-            }
+        for id in displayed_fields_of(attr, fields_ids_map, displayed_ids) {
+            formatted_options.insert(id, new_format);
         }
     }
 }
@@ -365,18 +2749,44 @@ fn add_crop_to_formatted_options(
             }
         }
 
-        if let Some(id) = fields_ids_map.id(attr_name) {
-            if displayed_ids.contains(&id) {
-                formatted_options
-                    .entry(id)
-                    .and_modify(|f| f.crop = Some(attr_len))
-                    .or_insert(FormatOptions {
-                        highlight: false,
-                        crop: Some(attr_len),
-                    });
-            }
+        for id in displayed_fields_of(attr_name, fields_ids_map, displayed_ids) {
+            formatted_options
+                .entry(id)
+                .and_modify(|f| f.crop = Some(attr_len))
+                .or_insert(FormatOptions {
+                    highlight: false,
+                    crop: Some(attr_len),
+                });
+        }
+    }
+}
+
+/// Returns the fields to format for an attribute given in dot-notation. An attribute nested in a
+/// displayed attribute is formatted on its own, an attribute holding displayed attributes is
+/// formatted through them, so only its displayed parts end up in `_formatted`.
+fn displayed_fields_of(
+    attr: &str,
+    fields_ids_map: &FieldsIdsMap,
+    displayed_ids: &BTreeSet<FieldId>,
+) -> Vec<FieldId> {
+    let displayed_names = || {
+        displayed_ids
+            .iter()
+            .filter_map(|&id| fields_ids_map.name(id).map(|name| (id, name)))
+    };
+
+    if let Some(id) = fields_ids_map.id(attr) {
+        if displayed_ids.contains(&id)
+            || displayed_names().any(|(_, name)| milli::is_faceted_by(attr, name))
+        {
+            return vec![id];
         }
     }
+
+    displayed_names()
+        .filter(|(_, name)| milli::is_faceted_by(name, attr))
+        .map(|(id, _)| id)
+        .collect()
 }
 
 fn add_non_formatted_ids_to_formatted_options(
@@ -554,7 +2964,400 @@ fn format_value<'a, A: AsRef<[u8]>>(
     }
 }
 
-fn parse_filter(facets: &Value) -> Result<Option<Filter>> {
+/// The maximum number of values a `CONTAINS` or `STARTS WITH` condition can be rewritten into.
+const MAX_STRING_OPERATOR_VALUES: usize = 1000;
+
+/// The maximum number of distinct locations a `_geoBoundingBox` condition can be rewritten into.
+const MAX_GEO_BOUNDING_BOX_POINTS: usize = 1000;
+
+/// The radius, in meters, of the `_geoRadius` conditions matching the documents at a location. The
+/// distance between a document and its own location is zero, any positive radius only matches it.
+const GEO_POINT_RADIUS: f64 = 0.000001;
+
+/// A `_geoBoundingBox([top, right], [bottom, left])` condition, which milli's filters don't
+/// support, computed from the locations of the documents. The box crosses the antimeridian when
+/// its left side is east of its right side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct GeoBoundingBox {
+    top: f64,
+    right: f64,
+    bottom: f64,
+    left: f64,
+}
+
+impl GeoBoundingBox {
+    /// Parses the arguments of the condition, the latitude and longitude of the top right corner,
+    /// then of the bottom left corner: `[lat, lng], [lat, lng]`.
+    fn parse(args: &str) -> Option<Self> {
+        let args: String = args.chars().filter(|c| !c.is_whitespace()).collect();
+        let (top_right, bottom_left) = args
+            .strip_prefix('[')?
+            .strip_suffix(']')?
+            .split_once("],[")?;
+        let point = |point: &str| -> Option<(f64, f64)> {
+            let (lat, lng) = point.split_once(',')?;
+            Some((lat.parse().ok()?, lng.parse().ok()?))
+        };
+        let (top, right) = point(top_right)?;
+        let (bottom, left) = point(bottom_left)?;
+
+        let is_latitude = |lat: f64| (-90.0..=90.0).contains(&lat);
+        let is_longitude = |lng: f64| (-180.0..=180.0).contains(&lng);
+        let valid = is_latitude(top)
+            && is_latitude(bottom)
+            && top >= bottom
+            && is_longitude(right)
+            && is_longitude(left);
+        valid.then(|| Self {
+            top,
+            right,
+            bottom,
+            left,
+        })
+    }
+
+    fn contains(&self, [lat, lng]: [f64; 2]) -> bool {
+        let within_longitudes = if self.left <= self.right {
+            (self.left..=self.right).contains(&lng)
+        } else {
+            lng >= self.left || lng <= self.right
+        };
+        (self.bottom..=self.top).contains(&lat) && within_longitudes
+    }
+}
+
+/// The string operators milli's filters don't support, computed from the values of the attribute.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StringOperator {
+    Contains,
+    StartsWith,
+}
+
+impl StringOperator {
+    fn name(&self) -> &'static str {
+        match self {
+            StringOperator::Contains => "CONTAINS",
+            StringOperator::StartsWith => "STARTS WITH",
+        }
+    }
+
+    /// Like the equalities of milli, the operators are case insensitive.
+    fn matches(&self, value: &str, pattern: &str) -> bool {
+        let value = value.to_lowercase();
+        let pattern = pattern.to_lowercase();
+        match self {
+            StringOperator::Contains => value.contains(&pattern),
+            StringOperator::StartsWith => value.starts_with(&pattern),
+        }
+    }
+}
+
+/// Splits a filter expression into its parentheses, its quoted values, quotes included, its
+/// words, and the whitespaces between them.
+fn tokenize_filter(expr: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let end = match c {
+            '(' | ')' => start + c.len_utf8(),
+            '"' | '\'' => match chars.find(|&(_, next)| next == c) {
+                Some((i, quote)) => i + quote.len_utf8(),
+                None => expr.len(),
+            },
+            c if c.is_whitespace() => {
+                while chars.next_if(|(_, next)| next.is_whitespace()).is_some() {}
+                chars.peek().map_or(expr.len(), |&(i, _)| i)
+            }
+            _ => {
+                let is_word = |next: char| !(next.is_whitespace() || "()\"'".contains(next));
+                while chars.next_if(|&(_, next)| is_word(next)).is_some() {}
+                chars.peek().map_or(expr.len(), |&(i, _)| i)
+            }
+        };
+        tokens.push(&expr[start..end]);
+    }
+    tokens
+}
+
+/// Removes the quotes around a quoted value of a filter.
+fn unquote(token: &str) -> &str {
+    let quoted = token.len() >= 2
+        && (token.starts_with('"') && token.ends_with('"')
+            || token.starts_with('\'') && token.ends_with('\''));
+    if quoted {
+        &token[1..token.len() - 1]
+    } else {
+        token
+    }
+}
+
+/// Rewrites the `attribute CONTAINS value` and `attribute STARTS WITH value` conditions of a
+/// filter expression into a disjunction of equalities with the values returned by `expand`. The
+/// disjunction is balanced to keep the expression shallow whatever the number of values. The
+/// expression is returned as is when it has no such conditions.
+fn expand_string_conditions<'a>(
+    expr: &'a str,
+    mut expand: impl FnMut(&str, StringOperator, &str) -> Result<Vec<String>>,
+) -> Result<Cow<'a, str>> {
+    let tokens = tokenize_filter(expr);
+    let significant: Vec<_> = (0..tokens.len())
+        .filter(|&i| !tokens[i].trim().is_empty())
+        .collect();
+    // The parentheses, the other operators and the keywords can't be an attribute nor a value.
+    let is_operand = |i: usize| {
+        let keywords = [
+            "AND", "OR", "NOT", "TO", "(", ")", "=", "!=", ">", ">=", "<", "<=",
+        ];
+        !keywords.iter().any(|k| tokens[i].eq_ignore_ascii_case(k))
+    };
+
+    // The conditions found, as the range of tokens they span and their rewriting.
+    let mut rewritten = Vec::new();
+    let mut j = 1;
+    while j < significant.len() {
+        let token = tokens[significant[j]];
+        let operator = if token.eq_ignore_ascii_case("CONTAINS") {
+            Some((StringOperator::Contains, 1))
+        } else if token.eq_ignore_ascii_case("STARTS")
+            && significant
+                .get(j + 1)
+                .map_or(false, |&i| tokens[i].eq_ignore_ascii_case("WITH"))
+        {
+            Some((StringOperator::StartsWith, 2))
+        } else {
+            None
+        };
+
+        match operator {
+            Some((operator, len)) => {
+                let attribute = significant[j - 1];
+                let value = match significant.get(j + len) {
+                    Some(&value) if is_operand(attribute) && is_operand(value) => value,
+                    _ => {
+                        j += 1;
+                        continue;
+                    }
+                };
+                let values = expand(unquote(tokens[attribute]), operator, unquote(tokens[value]))?;
+                let equalities = values
+                    .iter()
+                    .map(|v| match quote(v) {
+                        Some(v) => Ok(format!("{} = {}", tokens[attribute], v)),
+                        None => Err(IndexError::from(FacetError::UnquotableStringOperatorValue(
+                            unquote(tokens[attribute]).to_string(),
+                            operator.name(),
+                            v.clone(),
+                        ))),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let condition = match equalities.as_slice() {
+                    // A condition that can't be satisfied.
+                    [] => format!("({0} = \"\" AND {0} != \"\")", tokens[attribute]),
+                    equalities => format!("({})", balanced_disjunction(equalities)),
+                };
+                rewritten.push((attribute, value, condition));
+                j += len + 2;
+            }
+            None => j += 1,
+        }
+    }
+
+    if rewritten.is_empty() {
+        return Ok(Cow::Borrowed(expr));
+    }
+
+    let mut expanded = String::with_capacity(expr.len());
+    let mut rewritten = rewritten.into_iter().peekable();
+    let mut i = 0;
+    while i < tokens.len() {
+        match rewritten.next_if(|(start, _, _)| *start == i) {
+            Some((_, end, condition)) => {
+                expanded.push_str(&condition);
+                i = end + 1;
+            }
+            None => {
+                expanded.push_str(tokens[i]);
+                i += 1;
+            }
+        }
+    }
+
+    Ok(Cow::Owned(expanded))
+}
+
+/// Rewrites the `_geoBoundingBox([lat, lng], [lat, lng])` conditions of a filter expression into a
+/// disjunction of `_geoRadius` conditions, one for each of the locations returned by `locate`. The
+/// expression is returned as is when it has no such conditions.
+fn expand_geo_bounding_boxes<'a>(
+    expr: &'a str,
+    mut locate: impl FnMut(&GeoBoundingBox) -> Result<Vec<[f64; 2]>>,
+) -> Result<Cow<'a, str>> {
+    let tokens = tokenize_filter(expr);
+    let next_significant = |i: usize| (i..tokens.len()).find(|&i| !tokens[i].trim().is_empty());
+
+    let mut expanded = String::with_capacity(expr.len());
+    let mut rewritten = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let open = next_significant(i + 1).filter(|&open| tokens[open] == "(");
+        let open = match open {
+            Some(open) if tokens[i] == "_geoBoundingBox" => open,
+            _ => {
+                expanded.push_str(tokens[i]);
+                i += 1;
+                continue;
+            }
+        };
+
+        let close = (open..tokens.len()).find(|&close| tokens[close] == ")");
+        let args = tokens[open + 1..close.unwrap_or(tokens.len())].concat();
+        let (close, bounding_box) = match close.zip(GeoBoundingBox::parse(&args)) {
+            Some(found) => found,
+            None => return Err(FacetError::InvalidGeoBoundingBox(args.trim().to_string()).into()),
+        };
+
+        let conditions: Vec<_> = locate(&bounding_box)?
+            .into_iter()
+            .map(|[lat, lng]| format!("_geoRadius({}, {}, {})", lat, lng, GEO_POINT_RADIUS))
+            .collect();
+        let condition = match conditions.as_slice() {
+            // A condition that can't be satisfied.
+            [] => format!(
+                "(_geoRadius(0, 0, {0}) AND NOT _geoRadius(0, 0, {0}))",
+                GEO_POINT_RADIUS
+            ),
+            conditions => format!("({})", balanced_disjunction(conditions)),
+        };
+        expanded.push_str(&condition);
+        rewritten = true;
+        i = close + 1;
+    }
+
+    if !rewritten {
+        return Ok(Cow::Borrowed(expr));
+    }
+
+    Ok(Cow::Owned(expanded))
+}
+
+/// Quotes a value of a filter, `None` when it contains both kinds of quotes which milli's filters
+/// can't escape.
+fn quote(value: &str) -> Option<String> {
+    ['"', '\'']
+        .into_iter()
+        .find(|q| !value.contains(*q))
+        .map(|q| format!("{}{}{}", q, value, q))
+}
+
+/// Converts the object form of a filter into an expression. A condition is written
+/// `{ "attribute": "year", "operator": ">", "value": 2000 }`, and the conditions are combined with
+/// `{ "and": [...] }`, `{ "or": [...] }` and `{ "not": ... }`. The values of the conditions are
+/// quoted, an expression can still be given as a string.
+fn filter_object_to_expression(filter: &Value) -> Result<String> {
+    const OPERATORS: &[&str] = &[
+        "=",
+        "!=",
+        ">",
+        ">=",
+        "<",
+        "<=",
+        "TO",
+        "CONTAINS",
+        "STARTS WITH",
+    ];
+    let invalid = |expected: &'static [&'static str], value: &Value| {
+        IndexError::from(FacetError::InvalidExpression(expected, value.clone()))
+    };
+    let quote_value = |value: &Value| match value {
+        Value::String(s) => {
+            quote(s).ok_or_else(|| invalid(&["a value without both quotes"], value))
+        }
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        value => Err(invalid(&["String", "Number", "Boolean"], value)),
+    };
+
+    let object = match filter {
+        Value::String(expr) => return Ok(format!("({})", expr)),
+        Value::Object(object) => object,
+        value => return Err(invalid(&["String", "Object"], value)),
+    };
+
+    let combine = |operands: &Value, operator: &str| match operands {
+        Value::Array(operands) if !operands.is_empty() => Ok(format!(
+            "({})",
+            operands
+                .iter()
+                .map(filter_object_to_expression)
+                .collect::<Result<Vec<_>>>()?
+                .join(operator)
+        )),
+        value => Err(invalid(&["a non-empty Array"], value)),
+    };
+
+    match (object.get("and"), object.get("or"), object.get("not")) {
+        (Some(operands), None, None) if object.len() == 1 => combine(operands, " AND "),
+        (None, Some(operands), None) if object.len() == 1 => combine(operands, " OR "),
+        (None, None, Some(operand)) if object.len() == 1 => {
+            Ok(format!("(NOT {})", filter_object_to_expression(operand)?))
+        }
+        _ => {
+            // The attributes are only quoted when they aren't a single word.
+            let attribute = match object.get("attribute") {
+                Some(Value::String(attribute))
+                    if !attribute.is_empty()
+                        && attribute
+                            .chars()
+                            .all(|c| c.is_alphanumeric() || "_-.".contains(c)) =>
+                {
+                    attribute.clone()
+                }
+                Some(attribute @ Value::String(_)) => quote_value(attribute)?,
+                _ => return Err(invalid(&["`and`", "`or`", "`not`", "`attribute`"], filter)),
+            };
+            let operator = match object.get("operator") {
+                Some(Value::String(op)) if OPERATORS.iter().any(|o| o.eq_ignore_ascii_case(op)) => {
+                    op.to_uppercase()
+                }
+                _ => return Err(invalid(OPERATORS, filter)),
+            };
+            let value = match (operator.as_str(), object.get("value")) {
+                ("TO", Some(Value::Array(range))) if range.len() == 2 => {
+                    format!("{} TO {}", quote_value(&range[0])?, quote_value(&range[1])?)
+                }
+                ("TO", value) => {
+                    return Err(invalid(
+                        &["an Array of two values"],
+                        value.unwrap_or(&Value::Null),
+                    ))
+                }
+                (_, Some(value)) => quote_value(value)?,
+                (_, None) => return Err(invalid(&["`value`"], filter)),
+            };
+            match operator.as_str() {
+                "TO" => Ok(format!("({} {})", attribute, value)),
+                operator => Ok(format!("({} {} {})", attribute, operator, value)),
+            }
+        }
+    }
+}
+
+/// Joins the conditions with `OR`, as a balanced tree of parenthesized disjunctions.
+fn balanced_disjunction(conditions: &[String]) -> String {
+    match conditions {
+        [condition] => condition.clone(),
+        conditions => {
+            let (left, right) = conditions.split_at(conditions.len() / 2);
+            format!(
+                "({} OR {})",
+                balanced_disjunction(left),
+                balanced_disjunction(right)
+            )
+        }
+    }
+}
+
+pub(crate) fn parse_filter(facets: &Value) -> Result<Option<Filter>> {
     match facets {
         Value::String(expr) => {
             let condition = Filter::from_str(expr)?;
@@ -595,8 +3398,385 @@ fn parse_filter_array(arr: &[Value]) -> Result<Option<Filter>> {
 
 #[cfg(test)]
 mod test {
+    use super::super::vectors::parse_vectors;
     use super::*;
 
+    fn search_result(ids: &[&str]) -> SearchResult {
+        SearchResult {
+            hits: ids
+                .iter()
+                .map(|id| SearchHit {
+                    document: json!({ "id": id }).as_object().unwrap().clone(),
+                    formatted: Document::new(),
+                    matches_position: None,
+                    semantic_score: None,
+                    hybrid_score: None,
+                    debug: None,
+                    ranking_score: None,
+                })
+                .collect(),
+            query: String::new(),
+            processing_time_ms: 0,
+            hits_info: HitsInfo::OffsetLimit {
+                limit: ids.len(),
+                offset: 0,
+                estimated_total_hits: ids.len() as u64,
+            },
+            facet_distribution: None,
+            facet_stats: None,
+            degraded: false,
+            ranking_variant: None,
+            suggested_query: None,
+            applied_rules: Vec::new(),
+            debug: None,
+        }
+    }
+
+    fn scored_result(hits: &[(&str, f64)]) -> SearchResult {
+        let ids: Vec<_> = hits.iter().map(|(id, _)| *id).collect();
+        let mut result = search_result(&ids);
+        for (hit, (_, score)) in result.hits.iter_mut().zip(hits) {
+            hit.ranking_score = Some(*score);
+        }
+        result
+    }
+
+    fn federated_query(index_uid: &str, weight: f64) -> FederatedQuery {
+        FederatedQuery {
+            index_uid: index_uid.to_string(),
+            query: serde_json::from_value(json!({})).unwrap(),
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_facet_value_matches() {
+        assert!(facet_value_matches("Apple", "app"));
+        assert!(facet_value_matches("Golden Apple", "APP"));
+        assert!(!facet_value_matches("Pineapple", "app"));
+        assert!(facet_value_matches("Pineapple", ""));
+    }
+
+    #[test]
+    fn test_split_negative_terms() {
+        let (positive, negative) = split_negative_terms("jaguar -car");
+        assert_eq!(positive.trim(), "jaguar");
+        assert_eq!(negative, vec!["car"]);
+
+        let (positive, negative) = split_negative_terms(r#"-"sports car" jaguar -"" -"#);
+        assert_eq!(
+            positive.split_whitespace().collect::<Vec<_>>(),
+            ["jaguar", "-"]
+        );
+        assert_eq!(negative, vec!["sports car"]);
+
+        // A dash inside a word or a phrase isn't an operator.
+        let (positive, negative) = split_negative_terms(r#"t-shirt "big -red" dog"#);
+        assert_eq!(positive, r#"t-shirt "big -red" dog"#);
+        assert!(negative.is_empty());
+    }
+
+    #[test]
+    fn test_expand_string_conditions() {
+        let expand = |attribute: &str, operator: StringOperator, pattern: &str| {
+            assert_eq!(attribute, "sku");
+            let values = ["EU-1", "eu-2", "US-EU", "it's \"quoted\""];
+            Ok(values
+                .iter()
+                .filter(|value| operator.matches(value, pattern))
+                .map(|value| value.to_string())
+                .collect())
+        };
+
+        let expanded = expand_string_conditions(r#"sku STARTS WITH "EU-" AND id = 1"#, expand);
+        assert_eq!(
+            expanded.unwrap(),
+            r#"((sku = "EU-1" OR sku = "eu-2")) AND id = 1"#
+        );
+
+        let expanded = expand_string_conditions("NOT (sku contains eu)", expand);
+        assert_eq!(
+            expanded.unwrap(),
+            r#"NOT (((sku = "EU-1" OR (sku = "eu-2" OR sku = "US-EU"))))"#
+        );
+
+        // The values that can't be quoted can't be matched.
+        let expanded = expand_string_conditions(r#"sku CONTAINS 'quote' OR id = 1"#, expand);
+        assert!(matches!(
+            expanded,
+            Err(IndexError::Facet(
+                FacetError::UnquotableStringOperatorValue(..)
+            ))
+        ));
+
+        let expanded = expand_string_conditions(r#"sku CONTAINS 'us'"#, expand);
+        assert_eq!(expanded.unwrap(), r#"((sku = "US-EU"))"#);
+
+        // The values of the other conditions aren't operators.
+        let expr = r#"sku = "STARTS WITH" OR contains = 1"#;
+        assert!(
+            matches!(expand_string_conditions(expr, expand), Ok(Cow::Borrowed(e)) if e == expr)
+        );
+    }
+
+    #[test]
+    fn test_expand_geo_bounding_boxes() {
+        let locations = [[45.47, 9.19], [45.44, 9.15], [45.44, 9.3], [48.85, 2.35]];
+        let locate = |bounding_box: &GeoBoundingBox| {
+            Ok(locations
+                .iter()
+                .copied()
+                .filter(|&location| bounding_box.contains(location))
+                .collect())
+        };
+
+        let expanded = expand_geo_bounding_boxes(
+            "_geoBoundingBox([45.5, 9.2], [45.4, 9.1]) AND id = 1",
+            locate,
+        );
+        assert_eq!(
+            expanded.unwrap(),
+            "((_geoRadius(45.47, 9.19, 0.000001) OR _geoRadius(45.44, 9.15, 0.000001))) AND id = 1"
+        );
+
+        let expanded = expand_geo_bounding_boxes("NOT _geoBoundingBox ([10, 10],[0, 0])", locate);
+        assert_eq!(
+            expanded.unwrap(),
+            "NOT (_geoRadius(0, 0, 0.000001) AND NOT _geoRadius(0, 0, 0.000001))"
+        );
+
+        // A box whose left side is east of its right side crosses the antimeridian.
+        let bounding_box = GeoBoundingBox::parse("[10, -170], [-10, 170]").unwrap();
+        assert!(bounding_box.contains([0.0, 179.0]));
+        assert!(bounding_box.contains([0.0, -179.0]));
+        assert!(!bounding_box.contains([0.0, 0.0]));
+
+        for expr in [
+            "_geoBoundingBox([45.4, 9.2], [45.5, 9.1])",
+            "_geoBoundingBox([91, 9.2], [45.5, 9.1])",
+            "_geoBoundingBox([45.5, 9.2])",
+            "_geoBoundingBox([45.5, 9.2], [45.4, 9.1]",
+        ] {
+            assert!(matches!(
+                expand_geo_bounding_boxes(expr, locate),
+                Err(IndexError::Facet(FacetError::InvalidGeoBoundingBox(..)))
+            ));
+        }
+
+        let expr = r#"name = "_geoBoundingBox" OR _geoRadius(45.5, 9.2, 100)"#;
+        assert!(
+            matches!(expand_geo_bounding_boxes(expr, locate), Ok(Cow::Borrowed(e)) if e == expr)
+        );
+    }
+
+    #[test]
+    fn test_filter_object_to_expression() {
+        let filter = json!({
+            "and": [
+                { "attribute": "genre", "operator": "=", "value": "horror \"classics\"" },
+                { "or": [
+                    { "attribute": "year", "operator": "to", "value": [1980, 1989] },
+                    { "not": "rating < 3" },
+                ]},
+            ]
+        });
+        assert_eq!(
+            filter_object_to_expression(&filter).unwrap(),
+            r#"((genre = 'horror "classics"') AND ((year 1980 TO 1989) OR (NOT (rating < 3))))"#
+        );
+
+        let filter = json!({ "attribute": "genre", "operator": "LIKE", "value": "horror" });
+        assert!(filter_object_to_expression(&filter).is_err());
+        assert!(filter_object_to_expression(&json!({ "and": [] })).is_err());
+        let filter = json!({ "attribute": "year", "operator": "TO", "value": 1980 });
+        assert!(filter_object_to_expression(&filter).is_err());
+    }
+
+    #[test]
+    fn test_quote_exact_words() {
+        let typo_tolerance: TypoToleranceQuery = serde_json::from_value(json!({
+            "disableOnWords": ["SKU"],
+            "minWordSizeForTypos": { "oneTypo": 4 }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            quote_exact_words("red sku shoes", &typo_tolerance),
+            r#""red" "sku" shoes"#
+        );
+        // The phrases are kept as is.
+        assert_eq!(
+            quote_exact_words(r#"shoes "red sku" dog"#, &typo_tolerance),
+            r#"shoes "red sku" "dog""#
+        );
+        assert_eq!(
+            quote_exact_words("shoes", &TypoToleranceQuery::default()),
+            "shoes"
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("hello", "hello"), 0);
+        assert_eq!(edit_distance("hello", "helo"), 1);
+        assert_eq!(edit_distance("hello", "hallo"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(
+            split_query_words("The \"red\" shirt, the RED"),
+            ["the", "red", "shirt"]
+        );
+    }
+
+    #[test]
+    fn test_levenshtein_automaton() {
+        let words = fst::Set::from_iter(["blue", "bluetooth", "glue", "red"]).unwrap();
+        let search = |query: &str, max_distance| {
+            let automaton = Levenshtein {
+                query: query.as_bytes(),
+                max_distance,
+            };
+            words.search(automaton).into_stream().into_strs().unwrap()
+        };
+        assert_eq!(search("blu", 1), ["blue"]);
+        assert_eq!(search("blu", 2), ["blue", "glue"]);
+        assert_eq!(search("bluetoht", 2), ["bluetooth"]);
+        assert!(search("green", 2).is_empty());
+    }
+
+    #[test]
+    fn test_prefix_typos() {
+        assert_eq!(prefix_typos("lapt", "laptop", 0), Some(0));
+        assert_eq!(prefix_typos("lpatop", "laptop", 0), None);
+        assert_eq!(prefix_typos("lpatop", "laptops", 1), None);
+        assert_eq!(prefix_typos("lpatop", "laptops", 2), Some(2));
+        assert_eq!(prefix_typos("labtop", "laptops", 1), Some(1));
+        assert_eq!(prefix_typos("laaptop", "laptop", 1), Some(1));
+        assert_eq!(prefix_typos("", "laptop", 1), Some(0));
+    }
+
+    #[test]
+    fn test_split_query_terms() {
+        assert_eq!(
+            split_query_terms("  red \"cotton  shirt\" xl"),
+            ["red", "\"cotton  shirt\"", "xl"]
+        );
+        assert!(split_query_terms("   ").is_empty());
+    }
+
+    #[test]
+    fn test_document_similarity() {
+        let query = [1.0, 0.0];
+        assert_eq!(
+            document_similarity(&parse_vectors(&json!([2.0, 0.0])), &query),
+            Some(1.0)
+        );
+        assert_eq!(
+            document_similarity(&parse_vectors(&json!([0.0, 0.0])), &query),
+            Some(0.0)
+        );
+        // the best of the vectors of the document is used
+        assert_eq!(
+            document_similarity(&parse_vectors(&json!([[0.0, 1.0], [3.0, 0.0]])), &query),
+            Some(1.0)
+        );
+        // the vectors of other dimensions are ignored
+        assert_eq!(
+            document_similarity(
+                &parse_vectors(&json!([[1.0, 0.0, 0.0], [0.0, 1.0]])),
+                &query
+            ),
+            Some(0.0)
+        );
+        assert_eq!(
+            document_similarity(&parse_vectors(&json!([1.0, 0.0, 0.0])), &query),
+            None
+        );
+        assert_eq!(
+            document_similarity(&parse_vectors(&json!(["a", "b"])), &query),
+            None
+        );
+        assert_eq!(
+            document_similarity(&parse_vectors(&json!("hello")), &query),
+            None
+        );
+    }
+
+    #[test]
+    fn test_most_frequent_terms() {
+        let values = [
+            json!("The red shoes, red as a rose"),
+            json!({ "tags": ["Shoes", "leather"], "size": 42 }),
+        ];
+        assert_eq!(
+            most_frequent_terms(&values, 3),
+            vec!["red".to_string(), "shoes".to_string(), "the".to_string()]
+        );
+        assert!(most_frequent_terms(&[json!("a b"), json!(12)], 3).is_empty());
+    }
+
+    #[test]
+    fn test_blend_rankings() {
+        let keyword_ranking = [1, 2, 3];
+        let semantic_ranking = vec![(4, 1.0), (3, 0.5), (1, 0.0)];
+
+        let ids = |ranked: Vec<(DocumentId, VectorScores)>| {
+            ranked.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(blend_rankings(
+                &keyword_ranking,
+                semantic_ranking.clone(),
+                0.0
+            )),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            ids(blend_rankings(
+                &keyword_ranking,
+                semantic_ranking.clone(),
+                1.0
+            )),
+            vec![4, 3, 1, 2]
+        );
+        // 1: 0.5, 2: 0.25, 3: 0.4167, 4: 0.5
+        let ranked = blend_rankings(&keyword_ranking, semantic_ranking, 0.5);
+        assert_eq!(ranked[0].1.hybrid, Some(0.5));
+        assert_eq!(ranked[2].1.semantic, Some(0.5));
+        assert_eq!(ranked[3].1.semantic, None);
+        assert_eq!(ids(ranked), vec![1, 4, 3, 2]);
+    }
+
+    #[test]
+    fn test_merge_federated_results() {
+        let results = vec![
+            (
+                federated_query("movies", 1.0),
+                scored_result(&[("m1", 1.0), ("m2", 0.5)]),
+            ),
+            (
+                federated_query("books", 3.0),
+                scored_result(&[("b1", 0.5), ("b2", 0.2), ("b3", 0.1)]),
+            ),
+        ];
+
+        let merged = merge_federated_results(results, 1, 3);
+
+        let ids: Vec<_> = merged
+            .hits
+            .iter()
+            .map(|hit| hit.hit.document["id"].as_str().unwrap())
+            .collect();
+        // b1: 1.5, m1: 1, b2: 0.6, m2: 0.5, b3: 0.3
+        assert_eq!(ids, ["m1", "b2", "m2"]);
+        assert_eq!(merged.hits[0].federation.index_uid, "movies");
+        assert_eq!(merged.hits[0].federation.queries_position, 0);
+        assert_eq!(merged.hits[0].federation.weighted_ranking_score, 1.0);
+        assert_eq!(merged.estimated_total_hits, 5);
+        assert_eq!(merged.offset, 1);
+        assert_eq!(merged.limit, 3);
+    }
+
     #[test]
     fn test_insert_geo_distance() {
         let value: Document = serde_json::from_str(
@@ -0,0 +1,225 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::Hasher;
+
+use milli::FieldDistribution;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::error::Result;
+use super::index::Index;
+
+/// The number of bits of a hash picking the register of a `HyperLogLog`, which has `2^p`
+/// registers; the standard error of the estimate is about `1.04 / sqrt(2^p)`, 1.6% here.
+const HYPERLOGLOG_PRECISION: u32 = 12;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentStats {
+    pub number_of_documents: u64,
+    pub fields: BTreeMap<String, FieldStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldStats {
+    /// The number of documents having the field.
+    pub documents: u64,
+    /// An estimate of the number of distinct values of the field. The values are compared as a
+    /// whole, two arrays are distinct unless they're equal.
+    pub approximate_distinct_values: u64,
+}
+
+impl Index {
+    /// Returns the exact number of documents, the number of documents having each field, and an
+    /// estimate of the number of distinct values of each field. The estimate requires reading
+    /// every document, but uses a fixed amount of memory per field.
+    pub fn document_stats(&self) -> Result<DocumentStats> {
+        let txn = self.read_txn()?;
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+
+        let mut distinct_values: HashMap<_, HyperLogLog> = HashMap::new();
+        for entry in self.documents.range(&txn, &(..))? {
+            let (_id, obkv) = entry?;
+            for (field_id, value) in obkv.iter() {
+                let mut hasher = DefaultHasher::new();
+                hasher.write(value);
+                distinct_values
+                    .entry(field_id)
+                    .or_default()
+                    .insert(hasher.finish());
+            }
+        }
+
+        let fields = self
+            .field_distribution(&txn)?
+            .into_iter()
+            .map(|(field, documents)| {
+                let estimate = fields_ids_map
+                    .id(&field)
+                    .and_then(|id| distinct_values.get(&id))
+                    .map_or(0, HyperLogLog::estimate);
+                let stats = FieldStats {
+                    documents,
+                    // there can't be more values than documents having the field
+                    approximate_distinct_values: estimate.min(documents),
+                };
+                (field, stats)
+            })
+            .collect();
+
+        Ok(DocumentStats {
+            number_of_documents: self.number_of_documents(&txn)?,
+            fields,
+        })
+    }
+
+    /// Returns the number of documents having each nested path, `author.name`, of the objects
+    /// held by the fields, including the objects of arrays. The field distribution of milli only
+    /// counts the top-level fields.
+    pub fn nested_field_distribution(&self) -> Result<FieldDistribution> {
+        let txn = self.read_txn()?;
+        let fields_ids_map = self.fields_ids_map(&txn)?;
+
+        let mut distribution = FieldDistribution::new();
+        let mut paths = BTreeSet::new();
+        for entry in self.documents.range(&txn, &(..))? {
+            let (_id, obkv) = entry?;
+            for (field_id, value) in obkv.iter() {
+                // only the objects and the arrays can hold nested fields
+                if !value.starts_with(b"{") && !value.starts_with(b"[") {
+                    continue;
+                }
+                if let Some(field) = fields_ids_map.name(field_id) {
+                    let value: Value = serde_json::from_slice(value)?;
+                    nested_paths(field, &value, &mut paths);
+                }
+            }
+            // a document having a path several times, in an array, counts once
+            for path in std::mem::take(&mut paths) {
+                *distribution.entry(path).or_default() += 1;
+            }
+        }
+
+        Ok(distribution)
+    }
+}
+
+fn nested_paths(path: &str, value: &Value, paths: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let path = format!("{}.{}", path, key);
+                nested_paths(&path, value, paths);
+                paths.insert(path);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                nested_paths(path, value, paths);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Estimates the number of distinct hashes inserted, from the longest runs of leading zeros of
+/// the hashes falling into each register.
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; 1 << HYPERLOGLOG_PRECISION],
+        }
+    }
+}
+
+impl HyperLogLog {
+    fn insert(&mut self, hash: u64) {
+        let register = (hash >> (64 - HYPERLOGLOG_PRECISION)) as usize;
+        // the bit set at the end bounds the rank when the rest of the hash is zero
+        let rest = (hash << HYPERLOGLOG_PRECISION) | (1 << (HYPERLOGLOG_PRECISION - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        self.registers[register] = self.registers[register].max(rank);
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&rank| 2f64.powi(-(rank as i32)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+
+        let empty_registers = self.registers.iter().filter(|&&rank| rank == 0).count();
+        // the raw estimate is biased for small cardinalities, which are counted from the empty
+        // registers instead
+        let estimate = if estimate <= 2.5 * m && empty_registers > 0 {
+            m * (m / empty_registers as f64).ln()
+        } else {
+            estimate
+        };
+
+        estimate.round() as u64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::hash::Hash;
+
+    use super::*;
+
+    fn estimate(values: impl Iterator<Item = u64>) -> u64 {
+        let mut hll = HyperLogLog::default();
+        for value in values {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hll.insert(hasher.finish());
+        }
+        hll.estimate()
+    }
+
+    #[test]
+    fn test_nested_paths() {
+        let value = serde_json::json!({
+            "name": "Carol",
+            "address": { "city": "Paris" },
+            "tags": ["a"],
+        });
+        let mut paths = BTreeSet::new();
+        nested_paths("author", &value, &mut paths);
+        nested_paths(
+            "reviews",
+            &serde_json::json!([{ "stars": 4 }, 5]),
+            &mut paths,
+        );
+        assert_eq!(
+            paths.into_iter().collect::<Vec<_>>(),
+            [
+                "author.address",
+                "author.address.city",
+                "author.name",
+                "author.tags",
+                "reviews.stars"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hyperloglog_estimate() {
+        assert_eq!(estimate(std::iter::empty()), 0);
+        assert_eq!(estimate((0..10).chain(0..10)), 10);
+
+        for count in [1_000, 100_000] {
+            let estimate = estimate((0..count).chain(0..count / 2)) as f64;
+            let error = (estimate - count as f64).abs() / count as f64;
+            assert!(error < 0.05, "{} estimated for {}", estimate, count);
+        }
+    }
+}
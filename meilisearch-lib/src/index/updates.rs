@@ -1,20 +1,78 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
 
+use jsonschema::JSONSchema;
 use log::{debug, info, trace};
 use milli::documents::DocumentBatchReader;
-use milli::update::{
-    DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsConfig, IndexDocumentsMethod,
-    Setting,
-};
+use milli::heed::types::{OwnedType, SerdeJson, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::update::{DocumentDeletionResult, IndexDocumentsConfig, IndexDocumentsMethod, Setting};
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize, Serializer};
-use uuid::Uuid;
-
-use super::error::Result;
+use serde_json::Value;
+use siphasher::sip::SipHasher13;
+
+use super::addition::{
+    apply_update_operators, has_update_operators, infer_primary_key, merge_nested_documents,
+    payload_document_ids, remove_duplicate_documents, remove_invalid_documents,
+    remove_schema_violations, DocumentAdditionOutcome, DocumentPayload, OnDuplicate,
+    MAX_DOCUMENT_ERRORS,
+};
+use super::attachment::{check_attachments, AttachmentSettings};
+use super::error::{IndexError, Result};
+use super::flattening::{check_nested_flattening, flatten_documents, NestedFlattening};
+use super::history::record_settings_version;
 use super::index::{Index, IndexMeta};
+use super::ingestion::{check_ingestion_pipeline, IngestionStep};
+use super::rules::Rule;
+use super::search::parse_filter;
+use super::vectors::sync_vectors;
 use crate::update_file_store::UpdateFileStore;
 
+/// The key of the search cutoff in the main database of milli. The cutoff isn't a milli setting,
+/// it's stored next to them.
+const SEARCH_CUTOFF_MS_KEY: &str = "search-cutoff-ms";
+/// The key of the ranking experiment in the main database of milli.
+const RANKING_EXPERIMENT_KEY: &str = "ranking-experiment";
+/// The key of the order of the facet values in the main database of milli.
+const SORT_FACET_VALUES_BY_KEY: &str = "sort-facet-values-by";
+/// The key of the merchandising rules in the main database of milli.
+const RULES_KEY: &str = "rules";
+/// The key of the attribute holding the expiration date of the documents in the main database
+/// of milli.
+const EXPIRES_AT_ATTRIBUTE_KEY: &str = "expires-at-attribute";
+/// The key of the JSON Schema of the documents in the main database of milli.
+const DOCUMENT_SCHEMA_KEY: &str = "document-schema";
+/// The key of the ingestion pipeline in the main database of milli.
+const INGESTION_PIPELINE_KEY: &str = "ingestion-pipeline";
+/// The key of the flattening of the nested objects in the main database of milli.
+const NESTED_FLATTENING_KEY: &str = "nested-flattening";
+/// The key of the attachment fields in the main database of milli.
+const ATTACHMENTS_KEY: &str = "attachments";
+
+/// The key of the flag freezing the index in the main database of milli.
+const FROZEN_KEY: &str = "frozen";
+
+/// The attribute holding the expiration date of the documents when the index doesn't name one.
+pub const DEFAULT_EXPIRES_AT_ATTRIBUTE: &str = "_expiresAt";
+
+/// The primary key of an index whose first documents are all given a generated id.
+const DEFAULT_PRIMARY_KEY: &str = "id";
+
+/// The prefix of the stop words expanded to the stop words list of a language, e.g. `preset:en`.
+const STOP_WORDS_PRESET_PREFIX: &str = "preset:";
+/// The stop words lists that can be set with a preset, one word per line.
+const STOP_WORDS_PRESETS: &[(&str, &str)] = &[
+    ("de", include_str!("stop_words/de.txt")),
+    ("en", include_str!("stop_words/en.txt")),
+    ("es", include_str!("stop_words/es.txt")),
+    ("fr", include_str!("stop_words/fr.txt")),
+    ("it", include_str!("stop_words/it.txt")),
+];
+
 fn serialize_with_wildcard<S>(
     field: &Setting<Vec<String>>,
     s: S,
@@ -77,6 +135,27 @@ pub struct FacetingSettings {
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     pub max_values_per_facet: Setting<usize>,
+    /// The order of the values of each facet in the `facetDistribution` of the searches. The `*`
+    /// key applies to the facets without their own order.
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub sort_facet_values_by: Setting<BTreeMap<String, FacetValuesSort>>,
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FacetValuesSort {
+    /// The values are sorted in alphanumeric order.
+    Alpha,
+    /// The values contained by the most documents come first.
+    Count,
+}
+
+impl Default for FacetValuesSort {
+    fn default() -> Self {
+        Self::Alpha
+    }
 }
 
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
@@ -84,9 +163,74 @@ pub struct FacetingSettings {
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "camelCase")]
 pub struct PaginationSettings {
+    /// The maximum number of hits a search can return, whatever its offset and limit. The
+    /// `limitedTo` name is still accepted, it was used by the previous versions and their dumps.
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
-    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
-    pub limited_to: Setting<usize>,
+    #[serde(
+        default,
+        alias = "limitedTo",
+        skip_serializing_if = "Setting::is_not_set"
+    )]
+    pub max_total_hits: Setting<usize>,
+}
+
+/// Splits the searches on an index between several variants of its ranking. milli reads the
+/// ranking rules of the index while searching, so a variant overrides the ranking parameters of
+/// the searches it serves instead.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RankingExperiment {
+    pub variants: BTreeMap<String, RankingVariant>,
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RankingVariant {
+    /// The share of the searches served by the variant, relative to the traffic of the others.
+    pub traffic: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribute_weights: Option<BTreeMap<String, f32>>,
+    /// The typos are disabled for the searches served by the variant when `false`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typo_tolerance: Option<bool>,
+}
+
+impl RankingExperiment {
+    /// Picks the variant serving a search. The searches sharing a key are served by the same
+    /// variant as long as the experiment doesn't change, the ones without a key are spread
+    /// randomly.
+    pub fn assign(&self, key: Option<&str>) -> Option<(&str, &RankingVariant)> {
+        let total: u64 = self.variants.values().map(|v| v.traffic as u64).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut bucket = match key {
+            Some(key) => {
+                // The keys of the hasher are fixed so that the assignment survives a restart.
+                let mut hasher = SipHasher13::new();
+                key.hash(&mut hasher);
+                hasher.finish() % total
+            }
+            None => rand::random::<u64>() % total,
+        };
+
+        for (name, variant) in &self.variants {
+            let traffic = variant.traffic as u64;
+            if bucket < traffic {
+                return Some((name, variant));
+            }
+            bucket -= traffic;
+        }
+
+        None
+    }
 }
 
 /// Holds all the settings for an index. `T` can either be `Checked` if they represents settings
@@ -141,6 +285,35 @@ pub struct Settings<T> {
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     pub pagination: Setting<PaginationSettings>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub search_cutoff_ms: Setting<u64>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub ranking_experiment: Setting<RankingExperiment>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub rules: Setting<Vec<Rule>>,
+    /// The attribute holding the expiration date of the documents, `_expiresAt` when unset.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub expires_at_attribute: Setting<String>,
+    /// A JSON Schema the documents added to the index must match.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::document_schema_strategy()"))]
+    pub document_schema: Setting<Value>,
+    /// The transformations applied to the documents while their payload is received.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub ingestion_pipeline: Setting<Vec<IngestionStep>>,
+    /// How the nested objects of the added documents are flattened into fields.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub nested_flattening: Setting<NestedFlattening>,
+    /// The fields holding an attachment whose text is extracted while the payload is received.
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub attachments: Setting<AttachmentSettings>,
 
     #[serde(skip)]
     pub _kind: PhantomData<T>,
@@ -160,10 +333,32 @@ impl Settings<Checked> {
             typo_tolerance: Setting::Reset,
             faceting: Setting::Reset,
             pagination: Setting::Reset,
+            search_cutoff_ms: Setting::Reset,
+            ranking_experiment: Setting::Reset,
+            rules: Setting::Reset,
+            expires_at_attribute: Setting::Reset,
+            document_schema: Setting::Reset,
+            ingestion_pipeline: Setting::Reset,
+            nested_flattening: Setting::Reset,
+            attachments: Setting::Reset,
             _kind: PhantomData,
         }
     }
 
+    /// Replaces the settings reset to their default value with that value, so they're explicit
+    /// in an export of the settings.
+    pub fn with_explicit_defaults(self) -> Settings<Checked> {
+        let expires_at_attribute = match self.expires_at_attribute {
+            Setting::Set(attribute) => Setting::Set(attribute),
+            _ => Setting::Set(DEFAULT_EXPIRES_AT_ATTRIBUTE.to_string()),
+        };
+
+        Settings {
+            expires_at_attribute,
+            ..self
+        }
+    }
+
     pub fn into_unchecked(self) -> Settings<Unchecked> {
         let Self {
             displayed_attributes,
@@ -177,6 +372,14 @@ impl Settings<Checked> {
             typo_tolerance,
             faceting,
             pagination,
+            search_cutoff_ms,
+            ranking_experiment,
+            rules,
+            expires_at_attribute,
+            document_schema,
+            ingestion_pipeline,
+            nested_flattening,
+            attachments,
             ..
         } = self;
 
@@ -192,6 +395,14 @@ impl Settings<Checked> {
             typo_tolerance,
             faceting,
             pagination,
+            search_cutoff_ms,
+            ranking_experiment,
+            rules,
+            expires_at_attribute,
+            document_schema,
+            ingestion_pipeline,
+            nested_flattening,
+            attachments,
             _kind: PhantomData,
         }
     }
@@ -233,9 +444,80 @@ impl Settings<Unchecked> {
             typo_tolerance: self.typo_tolerance,
             faceting: self.faceting,
             pagination: self.pagination,
+            search_cutoff_ms: self.search_cutoff_ms,
+            ranking_experiment: self.ranking_experiment,
+            rules: self.rules,
+            expires_at_attribute: self.expires_at_attribute,
+            document_schema: self.document_schema,
+            ingestion_pipeline: self.ingestion_pipeline,
+            nested_flattening: self.nested_flattening,
+            attachments: self.attachments,
             _kind: PhantomData,
         }
     }
+
+    /// Resets the settings missing from `self`, the fields missing from its nested settings
+    /// included, so the update replaces all the settings of the index.
+    pub fn reset_missing(self) -> Settings<Unchecked> {
+        let typo_tolerance = match self.typo_tolerance {
+            Setting::Set(typo) => Setting::Set(TypoSettings {
+                enabled: or_reset(typo.enabled),
+                min_word_size_for_typos: match typo.min_word_size_for_typos {
+                    Setting::Set(min_word_size) => Setting::Set(MinWordSizeTyposSetting {
+                        one_typo: or_reset(min_word_size.one_typo),
+                        two_typos: or_reset(min_word_size.two_typos),
+                    }),
+                    _ => Setting::Reset,
+                },
+                disable_on_words: or_reset(typo.disable_on_words),
+                disable_on_attributes: or_reset(typo.disable_on_attributes),
+            }),
+            _ => Setting::Reset,
+        };
+        let faceting = match self.faceting {
+            Setting::Set(faceting) => Setting::Set(FacetingSettings {
+                max_values_per_facet: or_reset(faceting.max_values_per_facet),
+                sort_facet_values_by: or_reset(faceting.sort_facet_values_by),
+            }),
+            _ => Setting::Reset,
+        };
+        let pagination = match self.pagination {
+            Setting::Set(pagination) => Setting::Set(PaginationSettings {
+                max_total_hits: or_reset(pagination.max_total_hits),
+            }),
+            _ => Setting::Reset,
+        };
+
+        Settings {
+            displayed_attributes: or_reset(self.displayed_attributes),
+            searchable_attributes: or_reset(self.searchable_attributes),
+            filterable_attributes: or_reset(self.filterable_attributes),
+            sortable_attributes: or_reset(self.sortable_attributes),
+            ranking_rules: or_reset(self.ranking_rules),
+            stop_words: or_reset(self.stop_words),
+            synonyms: or_reset(self.synonyms),
+            distinct_attribute: or_reset(self.distinct_attribute),
+            typo_tolerance,
+            faceting,
+            pagination,
+            search_cutoff_ms: or_reset(self.search_cutoff_ms),
+            ranking_experiment: or_reset(self.ranking_experiment),
+            rules: or_reset(self.rules),
+            expires_at_attribute: or_reset(self.expires_at_attribute),
+            document_schema: or_reset(self.document_schema),
+            ingestion_pipeline: or_reset(self.ingestion_pipeline),
+            nested_flattening: or_reset(self.nested_flattening),
+            attachments: or_reset(self.attachments),
+            _kind: PhantomData,
+        }
+    }
+}
+
+fn or_reset<T>(setting: Setting<T>) -> Setting<T> {
+    match setting {
+        Setting::NotSet => Setting::Reset,
+        setting => setting,
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -263,14 +545,31 @@ impl Index {
     pub fn update_primary_key(&self, primary_key: String) -> Result<IndexMeta> {
         let mut txn = self.write_txn()?;
         let res = self.update_primary_key_txn(&mut txn, primary_key)?;
+        self.check_aborted()?;
         txn.commit()?;
 
         Ok(res)
     }
 
+    /// Whether the index is frozen: its documents and settings can't be modified, it can only be
+    /// searched.
+    pub fn is_frozen(&self) -> Result<bool> {
+        let txn = self.read_txn()?;
+        is_frozen(self, &txn)
+    }
+
+    pub fn set_frozen(&self, frozen: bool) -> Result<()> {
+        let mut txn = self.write_txn()?;
+        apply_frozen(self, &mut txn, frozen)?;
+        txn.commit()?;
+
+        Ok(())
+    }
+
     /// Deletes `ids` from the index, and returns how many documents were deleted.
     pub fn delete_documents(&self, ids: &[String]) -> Result<DocumentDeletionResult> {
         let mut txn = self.write_txn()?;
+        let before = self.documents_ids(&txn)?;
         let mut builder = milli::update::DeleteDocuments::new(&mut txn, self)?;
 
         // We ignore unexisting document ids
@@ -279,7 +578,33 @@ impl Index {
         });
 
         let deleted = builder.execute()?;
+        sync_vectors(self, &mut txn, &before, RoaringBitmap::new())?;
 
+        self.check_aborted()?;
+        txn.commit()?;
+
+        Ok(deleted)
+    }
+
+    /// Deletes all the documents matching the filter. The filter has the same syntax as the
+    /// `filter` of a search.
+    pub fn delete_documents_by_filter(&self, filter: &Value) -> Result<DocumentDeletionResult> {
+        let mut txn = self.write_txn()?;
+
+        let filter = self.prepare_filter(&txn, filter)?;
+        let candidates = match parse_filter(&filter)? {
+            Some(filter) => filter.evaluate(&txn, self)?,
+            // An empty filter matches no document.
+            None => RoaringBitmap::new(),
+        };
+
+        let before = self.documents_ids(&txn)?;
+        let mut builder = milli::update::DeleteDocuments::new(&mut txn, self)?;
+        builder.delete_documents(&candidates);
+        let deleted = builder.execute()?;
+        sync_vectors(self, &mut txn, &before, RoaringBitmap::new())?;
+
+        self.check_aborted()?;
         txn.commit()?;
 
         Ok(deleted)
@@ -287,27 +612,166 @@ impl Index {
 
     pub fn clear_documents(&self) -> Result<()> {
         let mut txn = self.write_txn()?;
+        let before = self.documents_ids(&txn)?;
         milli::update::ClearDocuments::new(&mut txn, self).execute()?;
+        sync_vectors(self, &mut txn, &before, RoaringBitmap::new())?;
+        self.check_aborted()?;
         txn.commit()?;
 
         Ok(())
     }
 
+    /// Indexes the payloads. Nothing is indexed when the index would hold more than
+    /// `max_documents` documents once they're indexed.
     pub fn update_documents(
         &self,
         method: IndexDocumentsMethod,
         primary_key: Option<String>,
+        max_documents: Option<u64>,
         file_store: UpdateFileStore,
-        contents: impl IntoIterator<Item = Uuid>,
-    ) -> Result<DocumentAdditionResult> {
+        contents: impl IntoIterator<Item = DocumentPayload>,
+    ) -> Result<DocumentAdditionOutcome> {
         trace!("performing document addition");
         let mut txn = self.write_txn()?;
-
-        if let Some(primary_key) = primary_key {
-            if self.primary_key(&txn)?.is_none() {
+        let contents: Vec<_> = contents.into_iter().collect();
+
+        if self.primary_key(&txn)?.is_none() {
+            // The primary key is inferred here rather than by milli, so the documents can be
+            // checked against it.
+            let primary_key = match (primary_key, contents.first()) {
+                (Some(primary_key), _) => Some(primary_key),
+                (None, Some(payload)) => {
+                    match infer_primary_key(file_store.get_update(payload.content_uuid)?)? {
+                        Some(primary_key) => Some(primary_key),
+                        // The documents are given an `id` rather than failing the addition.
+                        None if payload.auto_generate_primary_key => {
+                            Some(DEFAULT_PRIMARY_KEY.to_string())
+                        }
+                        None => None,
+                    }
+                }
+                (None, None) => None,
+            };
+            if let Some(primary_key) = primary_key {
                 self.update_primary_key_txn(&mut txn, primary_key)?;
             }
         }
+        // Without a primary key the whole addition fails, there's no document to check.
+        let primary_key = self.primary_key(&txn)?.map(String::from);
+        let check_geo = self.filterable_fields(&txn)?.contains("_geo")
+            || self.sortable_fields(&txn)?.contains("_geo");
+        let schema = document_schema(self, &txn)?
+            .map(|schema| compile_document_schema(&schema))
+            .transpose()?;
+        let flattening = nested_flattening(self, &txn)?;
+
+        // The payloads are indexed together, except when a payload reads the stored documents:
+        // the payloads preceding it are indexed first, so it reads their documents.
+        let mut indexed_documents = 0;
+        let mut pending_files = Vec::with_capacity(contents.len());
+        let mut failed_documents = Vec::with_capacity(contents.len());
+        for payload in contents {
+            let mut content_file = file_store.get_update(payload.content_uuid)?;
+            let mut errors = Vec::new();
+            if let Some(ref primary_key) = primary_key {
+                if let Some((valid_file, document_errors)) = remove_invalid_documents(
+                    &mut content_file,
+                    primary_key,
+                    check_geo,
+                    payload.auto_generate_primary_key,
+                )? {
+                    content_file = valid_file;
+                    errors = document_errors;
+                }
+
+                let apply_operators = method == IndexDocumentsMethod::UpdateDocuments
+                    && has_update_operators(&mut content_file)?;
+                let check_duplicates = payload.on_duplicate != OnDuplicate::Replace;
+                if (apply_operators || payload.merge_nested || check_duplicates)
+                    && !pending_files.is_empty()
+                {
+                    indexed_documents +=
+                        self.index_files(&mut txn, method, pending_files.drain(..))?;
+                }
+                if check_duplicates {
+                    if let Some((kept_file, duplicate_errors)) = remove_duplicate_documents(
+                        self,
+                        &txn,
+                        &mut content_file,
+                        primary_key,
+                        payload.on_duplicate,
+                    )? {
+                        content_file = kept_file;
+                        errors.extend(duplicate_errors);
+                    }
+                }
+                if apply_operators {
+                    let (applied_file, operator_errors) =
+                        apply_update_operators(self, &txn, &mut content_file, primary_key)?;
+                    content_file = applied_file;
+                    errors.extend(operator_errors);
+                }
+                if payload.merge_nested {
+                    content_file =
+                        merge_nested_documents(self, &txn, &mut content_file, primary_key)?;
+                }
+                if let Some(ref schema) = schema {
+                    if let Some((valid_file, violations)) =
+                        remove_schema_violations(&mut content_file, schema, primary_key)?
+                    {
+                        content_file = valid_file;
+                        errors.extend(violations);
+                    }
+                }
+                // the documents are checked against the schema before their objects are collapsed
+                if !flattening.is_default_flattening() {
+                    content_file = flatten_documents(&mut content_file, &flattening)?;
+                }
+                errors.sort_unstable_by_key(|error| error.index);
+                errors.truncate(MAX_DOCUMENT_ERRORS);
+            }
+            pending_files.push(content_file);
+            failed_documents.push(errors);
+        }
+        indexed_documents += self.index_files(&mut txn, method, pending_files)?;
+
+        if let Some(max_documents) = max_documents {
+            if self.number_of_documents(&txn)? > max_documents {
+                return Err(IndexError::DocumentQuotaExceeded(max_documents));
+            }
+        }
+
+        self.check_aborted()?;
+        txn.commit()?;
+
+        info!(
+            "document addition done: {} documents indexed",
+            indexed_documents
+        );
+
+        Ok(DocumentAdditionOutcome {
+            indexed_documents,
+            failed_documents,
+        })
+    }
+
+    /// Indexes the payloads, and stores the vectors of their documents.
+    fn index_files(
+        &self,
+        txn: &mut RwTxn,
+        method: IndexDocumentsMethod,
+        files: impl IntoIterator<Item = File>,
+    ) -> Result<u64> {
+        let mut files: Vec<_> = files.into_iter().collect();
+        // The replaced documents keep their internal id, they are found by their external id.
+        // Without a primary key the index is empty, all the documents are new.
+        let before = self.documents_ids(txn)?;
+        let mut replaced_ids = Vec::new();
+        if let Some(primary_key) = self.primary_key(txn)? {
+            for file in &mut files {
+                replaced_ids.extend(payload_document_ids(file, primary_key)?);
+            }
+        }
 
         let config = IndexDocumentsConfig {
             update_method: method,
@@ -316,26 +780,31 @@ impl Index {
 
         let indexing_callback = |indexing_step| debug!("update: {:?}", indexing_step);
         let mut builder = milli::update::IndexDocuments::new(
-            &mut txn,
+            txn,
             self,
             self.indexer_config.as_ref(),
             config,
             indexing_callback,
         )?;
 
-        for content_uuid in contents.into_iter() {
-            let content_file = file_store.get_update(content_uuid)?;
-            let reader = DocumentBatchReader::from_reader(content_file)?;
+        for file in files {
+            let reader = DocumentBatchReader::from_reader(file)?;
             builder.add_documents(reader)?;
         }
 
         let addition = builder.execute()?;
+        debug!("indexed payloads: {:?}", addition);
+
+        let replaced: RoaringBitmap = {
+            let external_documents_ids = self.external_documents_ids(txn)?;
+            replaced_ids
+                .iter()
+                .filter_map(|id| external_documents_ids.get(id.as_bytes()))
+                .collect()
+        };
+        sync_vectors(self, txn, &before, replaced)?;
 
-        txn.commit()?;
-
-        info!("document addition done: {:?}", addition);
-
-        Ok(addition)
+        Ok(addition.indexed_documents)
     }
 
     pub fn update_settings(&self, settings: &Settings<Checked>) -> Result<()> {
@@ -344,16 +813,398 @@ impl Index {
         let mut builder =
             milli::update::Settings::new(&mut txn, self, self.indexer_config.as_ref());
 
-        apply_settings_to_builder(settings, &mut builder);
+        let settings = match settings.stop_words {
+            Setting::Set(ref stop_words) => Settings {
+                stop_words: Setting::Set(expand_stop_words_presets(stop_words)?),
+                ..settings.clone()
+            },
+            _ => settings.clone(),
+        };
+        apply_settings_to_builder(&settings, &mut builder);
 
         builder.execute(|indexing_step| debug!("update: {:?}", indexing_step))?;
 
+        apply_search_cutoff_ms(self, &mut txn, &settings.search_cutoff_ms)?;
+        apply_ranking_experiment(self, &mut txn, &settings.ranking_experiment)?;
+        apply_sort_facet_values_by(self, &mut txn, &settings.faceting)?;
+        apply_rules(self, &mut txn, &settings.rules)?;
+        apply_expires_at_attribute(self, &mut txn, &settings.expires_at_attribute)?;
+        apply_document_schema(self, &mut txn, &settings.document_schema)?;
+        apply_ingestion_pipeline(self, &mut txn, &settings.ingestion_pipeline)?;
+        apply_nested_flattening(self, &mut txn, &settings.nested_flattening)?;
+        apply_attachments(self, &mut txn, &settings.attachments)?;
+        record_settings_version(self, &mut txn)?;
+
+        self.check_aborted()?;
         txn.commit()?;
 
         Ok(())
     }
 }
 
+pub fn is_frozen(index: &milli::Index, txn: &RoTxn) -> Result<bool> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<bool>>(txn, FROZEN_KEY)?
+        .unwrap_or(false))
+}
+
+pub fn apply_frozen(index: &milli::Index, txn: &mut RwTxn, frozen: bool) -> Result<()> {
+    if frozen {
+        index
+            .main
+            .put::<_, Str, SerdeJson<bool>>(txn, FROZEN_KEY, &true)?;
+    } else {
+        index.main.delete::<_, Str>(txn, FROZEN_KEY)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the time budget of the searches on the index, if any.
+pub fn search_cutoff_ms(index: &milli::Index, txn: &RoTxn) -> Result<Option<u64>> {
+    Ok(index
+        .main
+        .get::<_, Str, OwnedType<u64>>(txn, SEARCH_CUTOFF_MS_KEY)?)
+}
+
+/// Stores the search cutoff of the index, which can't be applied through the settings builder of
+/// milli.
+pub fn apply_search_cutoff_ms(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    setting: &Setting<u64>,
+) -> Result<()> {
+    match setting {
+        Setting::Set(cutoff) => {
+            index
+                .main
+                .put::<_, Str, OwnedType<u64>>(txn, SEARCH_CUTOFF_MS_KEY, cutoff)?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, SEARCH_CUTOFF_MS_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
+/// Returns the attribute holding the expiration date of the documents, if the index names one.
+pub fn expires_at_attribute(index: &milli::Index, txn: &RoTxn) -> Result<Option<String>> {
+    Ok(index
+        .main
+        .get::<_, Str, Str>(txn, EXPIRES_AT_ATTRIBUTE_KEY)?
+        .map(String::from))
+}
+
+/// Stores the attribute holding the expiration date of the documents, which isn't a milli
+/// setting.
+pub fn apply_expires_at_attribute(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    setting: &Setting<String>,
+) -> Result<()> {
+    match setting {
+        Setting::Set(attribute) => {
+            index
+                .main
+                .put::<_, Str, Str>(txn, EXPIRES_AT_ATTRIBUTE_KEY, attribute)?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, EXPIRES_AT_ATTRIBUTE_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
+/// Returns the JSON Schema the documents added to the index must match, if there's one.
+pub fn document_schema(index: &milli::Index, txn: &RoTxn) -> Result<Option<Value>> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<Value>>(txn, DOCUMENT_SCHEMA_KEY)?)
+}
+
+pub fn compile_document_schema(schema: &Value) -> Result<JSONSchema> {
+    JSONSchema::compile(schema).map_err(|e| IndexError::InvalidDocumentSchema(e.to_string()))
+}
+
+/// Stores the JSON Schema of the documents, once it's checked to compile. The documents already
+/// in the index aren't validated against it.
+pub fn apply_document_schema(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    setting: &Setting<Value>,
+) -> Result<()> {
+    match setting {
+        Setting::Set(schema) => {
+            compile_document_schema(schema)?;
+            index
+                .main
+                .put::<_, Str, SerdeJson<Value>>(txn, DOCUMENT_SCHEMA_KEY, schema)?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, DOCUMENT_SCHEMA_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
+/// Returns the order of the values of each facet, the `*` key applying to the facets without
+/// their own order.
+pub fn sort_facet_values_by(
+    index: &milli::Index,
+    txn: &RoTxn,
+) -> Result<BTreeMap<String, FacetValuesSort>> {
+    let sort_by = index
+        .main
+        .get::<_, Str, SerdeJson<BTreeMap<String, FacetValuesSort>>>(
+            txn,
+            SORT_FACET_VALUES_BY_KEY,
+        )?;
+    Ok(sort_by.unwrap_or_else(|| BTreeMap::from([("*".to_string(), FacetValuesSort::default())])))
+}
+
+/// Stores the order of the facet values, which can't be applied through the settings builder of
+/// milli.
+pub fn apply_sort_facet_values_by(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    setting: &Setting<FacetingSettings>,
+) -> Result<()> {
+    let sort_by = match setting {
+        Setting::Set(faceting) => &faceting.sort_facet_values_by,
+        Setting::Reset => &Setting::Reset,
+        Setting::NotSet => &Setting::NotSet,
+    };
+
+    match sort_by {
+        Setting::Set(sort_by) => {
+            index
+                .main
+                .put::<_, Str, SerdeJson<BTreeMap<String, FacetValuesSort>>>(
+                    txn,
+                    SORT_FACET_VALUES_BY_KEY,
+                    sort_by,
+                )?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, SORT_FACET_VALUES_BY_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
+/// Replaces the stop words presets, like `preset:en`, with the stop words list of their language.
+pub fn expand_stop_words_presets(stop_words: &BTreeSet<String>) -> Result<BTreeSet<String>> {
+    let mut expanded = BTreeSet::new();
+    for word in stop_words {
+        match word.strip_prefix(STOP_WORDS_PRESET_PREFIX) {
+            Some(language) => {
+                let (_, list) = STOP_WORDS_PRESETS
+                    .iter()
+                    .find(|(name, _)| *name == language)
+                    .ok_or_else(|| {
+                        let available = STOP_WORDS_PRESETS
+                            .iter()
+                            .map(|(name, _)| format!("{}{}", STOP_WORDS_PRESET_PREFIX, name))
+                            .collect();
+                        IndexError::UnknownStopWordsPreset(word.clone(), available)
+                    })?;
+                expanded.extend(list.lines().map(String::from));
+            }
+            None => {
+                expanded.insert(word.clone());
+            }
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Returns the ranking experiment running on the index, if any.
+pub fn ranking_experiment(index: &milli::Index, txn: &RoTxn) -> Result<Option<RankingExperiment>> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<RankingExperiment>>(txn, RANKING_EXPERIMENT_KEY)?)
+}
+
+/// Stores the ranking experiment of the index, which can't be applied through the settings
+/// builder of milli.
+pub fn apply_ranking_experiment(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    setting: &Setting<RankingExperiment>,
+) -> Result<()> {
+    match setting {
+        Setting::Set(experiment) => {
+            if experiment.variants.values().all(|v| v.traffic == 0) {
+                return Err(IndexError::InvalidRankingExperiment);
+            }
+            index.main.put::<_, Str, SerdeJson<RankingExperiment>>(
+                txn,
+                RANKING_EXPERIMENT_KEY,
+                experiment,
+            )?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, RANKING_EXPERIMENT_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
+/// Returns the merchandising rules of the index, in the order they were set.
+pub fn rules(index: &milli::Index, txn: &RoTxn) -> Result<Vec<Rule>> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<Vec<Rule>>>(txn, RULES_KEY)?
+        .unwrap_or_default())
+}
+
+/// Stores the merchandising rules of the index, once their ids and boost filters are checked.
+pub fn apply_rules(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    setting: &Setting<Vec<Rule>>,
+) -> Result<()> {
+    match setting {
+        Setting::Set(rules) => {
+            let mut ids = BTreeSet::new();
+            for rule in rules {
+                if rule.id.is_empty() {
+                    return Err(IndexError::InvalidRule(
+                        "The id of a rule can't be empty.".to_string(),
+                    ));
+                }
+                if !ids.insert(rule.id.as_str()) {
+                    return Err(IndexError::InvalidRule(format!(
+                        "The id `{}` is used by several rules.",
+                        rule.id
+                    )));
+                }
+                if let Some(ref boost) = rule.consequence.boost {
+                    parse_filter(&Value::String(boost.clone()))?;
+                }
+            }
+            index
+                .main
+                .put::<_, Str, SerdeJson<Vec<Rule>>>(txn, RULES_KEY, rules)?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, RULES_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
+/// Returns the ingestion pipeline of the index, in the order its steps are applied.
+pub fn ingestion_pipeline(index: &milli::Index, txn: &RoTxn) -> Result<Vec<IngestionStep>> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<Vec<IngestionStep>>>(txn, INGESTION_PIPELINE_KEY)?
+        .unwrap_or_default())
+}
+
+/// Stores the ingestion pipeline of the index, once its steps are checked.
+pub fn apply_ingestion_pipeline(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    setting: &Setting<Vec<IngestionStep>>,
+) -> Result<()> {
+    match setting {
+        Setting::Set(steps) => {
+            check_ingestion_pipeline(steps)?;
+            index.main.put::<_, Str, SerdeJson<Vec<IngestionStep>>>(
+                txn,
+                INGESTION_PIPELINE_KEY,
+                steps,
+            )?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, INGESTION_PIPELINE_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
+/// Returns how the nested objects of the added documents are flattened into fields.
+pub fn nested_flattening(index: &milli::Index, txn: &RoTxn) -> Result<NestedFlattening> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<NestedFlattening>>(txn, NESTED_FLATTENING_KEY)?
+        .unwrap_or_default())
+}
+
+/// Stores the flattening of the nested objects of the index, once it's checked. The documents
+/// already indexed aren't flattened again.
+pub fn apply_nested_flattening(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    setting: &Setting<NestedFlattening>,
+) -> Result<()> {
+    match setting {
+        Setting::Set(flattening) => {
+            check_nested_flattening(flattening)?;
+            index.main.put::<_, Str, SerdeJson<NestedFlattening>>(
+                txn,
+                NESTED_FLATTENING_KEY,
+                flattening,
+            )?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, NESTED_FLATTENING_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
+/// Returns the attachment fields of the index, and the URLs their attachments are fetched from.
+pub fn attachments(index: &milli::Index, txn: &RoTxn) -> Result<AttachmentSettings> {
+    Ok(index
+        .main
+        .get::<_, Str, SerdeJson<AttachmentSettings>>(txn, ATTACHMENTS_KEY)?
+        .unwrap_or_default())
+}
+
+/// Stores the attachment fields of the index, once they're checked.
+pub fn apply_attachments(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    setting: &Setting<AttachmentSettings>,
+) -> Result<()> {
+    match setting {
+        Setting::Set(settings) => {
+            check_attachments(settings)?;
+            index.main.put::<_, Str, SerdeJson<AttachmentSettings>>(
+                txn,
+                ATTACHMENTS_KEY,
+                settings,
+            )?;
+        }
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, ATTACHMENTS_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
 pub fn apply_settings_to_builder(
     settings: &Settings<Checked>,
     builder: &mut milli::update::Settings,
@@ -474,7 +1325,7 @@ pub fn apply_settings_to_builder(
     }
 
     match settings.pagination {
-        Setting::Set(ref value) => match value.limited_to {
+        Setting::Set(ref value) => match value.max_total_hits {
             Setting::Set(val) => builder.set_pagination_limited_to(val),
             Setting::Reset => builder.reset_pagination_limited_to(),
             Setting::NotSet => (),
@@ -498,6 +1349,14 @@ pub(crate) mod test {
         ]
     }
 
+    pub(super) fn document_schema_strategy() -> impl Strategy<Value = Setting<Value>> {
+        prop_oneof![
+            Just(Setting::NotSet),
+            Just(Setting::Reset),
+            Just(Setting::Set(serde_json::json!({ "type": "object" })))
+        ]
+    }
+
     #[test]
     fn test_setting_check() {
         // test no changes
@@ -513,6 +1372,14 @@ pub(crate) mod test {
             typo_tolerance: Setting::NotSet,
             faceting: Setting::NotSet,
             pagination: Setting::NotSet,
+            search_cutoff_ms: Setting::NotSet,
+            ranking_experiment: Setting::NotSet,
+            rules: Setting::NotSet,
+            expires_at_attribute: Setting::NotSet,
+            document_schema: Setting::NotSet,
+            ingestion_pipeline: Setting::NotSet,
+            nested_flattening: Setting::NotSet,
+            attachments: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -537,6 +1404,14 @@ pub(crate) mod test {
             typo_tolerance: Setting::NotSet,
             faceting: Setting::NotSet,
             pagination: Setting::NotSet,
+            search_cutoff_ms: Setting::NotSet,
+            ranking_experiment: Setting::NotSet,
+            rules: Setting::NotSet,
+            expires_at_attribute: Setting::NotSet,
+            document_schema: Setting::NotSet,
+            ingestion_pipeline: Setting::NotSet,
+            nested_flattening: Setting::NotSet,
+            attachments: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -544,4 +1419,68 @@ pub(crate) mod test {
         assert_eq!(checked.displayed_attributes, Setting::Reset);
         assert_eq!(checked.searchable_attributes, Setting::Reset);
     }
+
+    #[test]
+    fn test_setting_reset_missing() {
+        let settings = Settings {
+            stop_words: Setting::Set(BTreeSet::from(["the".to_string()])),
+            typo_tolerance: Setting::Set(TypoSettings {
+                enabled: Setting::Set(false),
+                ..Default::default()
+            }),
+            ..Settings::<Unchecked>::default()
+        }
+        .reset_missing();
+
+        assert_eq!(
+            settings.stop_words,
+            Setting::Set(BTreeSet::from(["the".to_string()]))
+        );
+        assert_eq!(settings.ranking_rules, Setting::Reset);
+        assert_eq!(settings.attachments, Setting::Reset);
+        let typo_tolerance = settings.typo_tolerance.set().unwrap();
+        assert_eq!(typo_tolerance.enabled, Setting::Set(false));
+        assert_eq!(typo_tolerance.min_word_size_for_typos, Setting::Reset);
+        assert_eq!(typo_tolerance.disable_on_words, Setting::Reset);
+    }
+
+    #[test]
+    fn test_ranking_experiment_assign() {
+        let variant = |traffic| RankingVariant {
+            traffic,
+            ..Default::default()
+        };
+        let experiment = RankingExperiment {
+            variants: BTreeMap::from([
+                ("control".to_string(), variant(1)),
+                ("price".to_string(), variant(1)),
+                ("disabled".to_string(), variant(0)),
+            ]),
+        };
+
+        for key in ["alice", "bob", "carol", "dave"] {
+            let (name, _) = experiment.assign(Some(key)).unwrap();
+            assert_ne!(name, "disabled");
+            // The assignment is sticky.
+            assert_eq!(experiment.assign(Some(key)).unwrap().0, name);
+        }
+        assert_ne!(experiment.assign(None).unwrap().0, "disabled");
+
+        let experiment = RankingExperiment {
+            variants: BTreeMap::from([("disabled".to_string(), variant(0))]),
+        };
+        assert!(experiment.assign(Some("alice")).is_none());
+    }
+
+    #[test]
+    fn test_expand_stop_words_presets() {
+        let stop_words = BTreeSet::from(["preset:en".to_string(), "foo".to_string()]);
+        let expanded = expand_stop_words_presets(&stop_words).unwrap();
+        assert!(expanded.contains("the"));
+        assert!(expanded.contains("foo"));
+        assert!(!expanded.contains("preset:en"));
+
+        let stop_words = BTreeSet::from(["preset:klingon".to_string()]);
+        assert!(expand_stop_words_presets(&stop_words).is_err());
+    }
 }
@@ -0,0 +1,186 @@
+use milli::heed::types::{ByteSlice, SerdeJson, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::DocumentId;
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use super::error::Result;
+
+/// The field of the documents holding their embeddings, either a single vector or an array of
+/// vectors.
+pub const VECTORS_FIELD: &str = "_vectors";
+
+/// The prefix of the keys of the vectors of the documents in the main database, followed by the
+/// internal id of the document in big endian.
+const VECTORS_KEY_PREFIX: &[u8] = b"vectors\0";
+/// Set once the vectors of every document are stored, the indexes created before the vectors
+/// were stored don't have it.
+const VECTORS_STORED_KEY: &str = "vectors-stored";
+
+/// The number of documents read at once to store their vectors.
+const VECTORS_CHUNK_SIZE: usize = 1000;
+
+fn vectors_key(id: DocumentId) -> Vec<u8> {
+    let mut key = VECTORS_KEY_PREFIX.to_vec();
+    key.extend_from_slice(&id.to_be_bytes());
+    key
+}
+
+/// Returns the vectors of a document, as stored when it was indexed, without decoding the
+/// document.
+pub fn document_vectors(
+    index: &milli::Index,
+    txn: &RoTxn,
+    id: DocumentId,
+) -> Result<Vec<Vec<f32>>> {
+    let vectors = index
+        .main
+        .get::<_, ByteSlice, ByteSlice>(txn, &vectors_key(id))?
+        .map(decode_vectors)
+        .unwrap_or_default();
+    Ok(vectors)
+}
+
+/// Keeps the vectors of the documents in sync with the documents after a write. The vectors of
+/// the documents deleted since `before` are removed, and the vectors of the documents added since
+/// then and of the `updated` documents are read again from the documents.
+pub fn sync_vectors(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    before: &RoaringBitmap,
+    updated: RoaringBitmap,
+) -> Result<()> {
+    let after = index.documents_ids(txn)?;
+    for id in before - &after {
+        index.main.delete::<_, ByteSlice>(txn, &vectors_key(id))?;
+    }
+
+    let vectors_fid = match index.fields_ids_map(txn)?.id(VECTORS_FIELD) {
+        Some(fid) => fid,
+        // No document ever had vectors.
+        None => return Ok(()),
+    };
+
+    let updated = (updated | (&after - before)) & &after;
+    let ids: Vec<_> = updated.iter().collect();
+    for chunk in ids.chunks(VECTORS_CHUNK_SIZE) {
+        // the documents borrow the transaction, their vectors are decoded before being written
+        let mut vectors = Vec::with_capacity(chunk.len());
+        for (id, obkv) in index.documents(txn, chunk.iter().copied())? {
+            let document_vectors = match obkv.get(vectors_fid) {
+                Some(value) => parse_vectors(&serde_json::from_slice(value)?),
+                None => Vec::new(),
+            };
+            vectors.push((id, document_vectors));
+        }
+
+        for (id, document_vectors) in vectors {
+            if document_vectors.is_empty() {
+                index.main.delete::<_, ByteSlice>(txn, &vectors_key(id))?;
+            } else {
+                index.main.put::<_, ByteSlice, ByteSlice>(
+                    txn,
+                    &vectors_key(id),
+                    &encode_vectors(&document_vectors),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Stores the vectors of all the documents of an index created before the vectors were stored.
+pub fn store_missing_vectors(index: &milli::Index, txn: &mut RwTxn) -> Result<()> {
+    let stored = index
+        .main
+        .get::<_, Str, SerdeJson<bool>>(txn, VECTORS_STORED_KEY)?
+        .unwrap_or(false);
+    if stored {
+        return Ok(());
+    }
+
+    sync_vectors(index, txn, &RoaringBitmap::new(), RoaringBitmap::new())?;
+    index
+        .main
+        .put::<_, Str, SerdeJson<bool>>(txn, VECTORS_STORED_KEY, &true)?;
+
+    Ok(())
+}
+
+/// Parses the `_vectors` of a document, either a single vector or an array of vectors. The
+/// vectors that aren't made of numbers are ignored.
+pub fn parse_vectors(vectors: &Value) -> Vec<Vec<f32>> {
+    let parse = |vector: &Value| -> Option<Vec<f32>> {
+        vector
+            .as_array()?
+            .iter()
+            .map(|n| n.as_f64().map(|n| n as f32))
+            .collect()
+    };
+
+    match vectors.as_array() {
+        Some(values) if values.iter().all(Value::is_array) => {
+            values.iter().filter_map(parse).collect()
+        }
+        _ => parse(vectors).into_iter().collect(),
+    }
+}
+
+/// Encodes each vector as its number of dimensions followed by its components, in big endian.
+fn encode_vectors(vectors: &[Vec<f32>]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for vector in vectors {
+        bytes.extend_from_slice(&(vector.len() as u32).to_be_bytes());
+        for n in vector {
+            bytes.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+    bytes
+}
+
+fn decode_vectors(bytes: &[u8]) -> Vec<Vec<f32>> {
+    let mut words = bytes
+        .chunks_exact(4)
+        .map(|word| [word[0], word[1], word[2], word[3]]);
+
+    let mut vectors = Vec::new();
+    while let Some(len) = words.next() {
+        let vector = words
+            .by_ref()
+            .take(u32::from_be_bytes(len) as usize)
+            .map(f32::from_be_bytes)
+            .collect();
+        vectors.push(vector);
+    }
+    vectors
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_vectors() {
+        assert_eq!(parse_vectors(&json!([2.0, 0.5])), vec![vec![2.0, 0.5]]);
+        assert_eq!(
+            parse_vectors(&json!([[0.0, 1.0], [3.0, 0.0, 1.0]])),
+            vec![vec![0.0, 1.0], vec![3.0, 0.0, 1.0]]
+        );
+        assert_eq!(
+            parse_vectors(&json!([[0.0, 1.0], ["a", "b"]])),
+            vec![vec![0.0, 1.0]]
+        );
+        assert!(parse_vectors(&json!(["a", "b"])).is_empty());
+        assert!(parse_vectors(&json!("hello")).is_empty());
+    }
+
+    #[test]
+    fn test_encode_decode_vectors() {
+        let vectors = vec![vec![0.0, -1.5], vec![], vec![3.0, 0.25, 1e-3]];
+        assert_eq!(decode_vectors(&encode_vectors(&vectors)), vectors);
+        assert!(decode_vectors(&[]).is_empty());
+    }
+}
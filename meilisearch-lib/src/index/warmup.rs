@@ -0,0 +1,50 @@
+use milli::heed::types::ByteSlice;
+use milli::heed::{Database, RoTxn};
+
+use super::error::Result;
+use super::index::Index;
+
+/// The size of the pages of the memory map, a single byte is read from each of them.
+const PAGE_SIZE: usize = 4096;
+
+impl Index {
+    /// Reads the databases used by the searches, the words and prefixes with their documents
+    /// and proximities, and the documents themselves, so that their pages are loaded in memory
+    /// before the searches need them. Returns the number of bytes read.
+    pub fn warmup(&self) -> Result<u64> {
+        let txn = self.read_txn()?;
+        let databases = [
+            self.main.remap_types::<ByteSlice, ByteSlice>(),
+            self.word_docids.remap_types(),
+            self.word_prefix_docids.remap_types(),
+            self.word_pair_proximity_docids.remap_types(),
+            self.word_prefix_pair_proximity_docids.remap_types(),
+            self.documents.remap_types(),
+        ];
+
+        let mut bytes = 0;
+        for database in databases {
+            bytes += warm_database(&txn, database)?;
+        }
+
+        Ok(bytes)
+    }
+}
+
+fn warm_database(txn: &RoTxn, database: Database<ByteSlice, ByteSlice>) -> Result<u64> {
+    let mut bytes = 0;
+    for entry in database.iter(txn)? {
+        let (key, value) = entry?;
+        bytes += touch(key) + touch(value);
+    }
+    Ok(bytes)
+}
+
+/// Reads a byte of each page of `bytes`, which points into the memory map, and returns its length.
+fn touch(bytes: &[u8]) -> u64 {
+    for byte in bytes.iter().step_by(PAGE_SIZE) {
+        // a volatile read isn't optimized away, unlike a read whose value is unused
+        unsafe { std::ptr::read_volatile(byte) };
+    }
+    bytes.len() as u64
+}
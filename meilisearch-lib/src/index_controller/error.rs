@@ -1,6 +1,6 @@
 use std::error::Error;
 
-use meilisearch_types::error::{Code, ErrorCode};
+use meilisearch_types::error::{Code, ErrorCode, ErrorDetails};
 use meilisearch_types::index_uid::IndexUidFormatError;
 use meilisearch_types::internal_error;
 use tokio::task::JoinError;
@@ -11,6 +11,7 @@ use crate::dump::error::DumpError;
 use crate::index::error::IndexError;
 use crate::tasks::error::TaskError;
 use crate::update_file_store::UpdateFileStoreError;
+use crate::upload_store::UploadStoreError;
 
 use crate::index_resolver::error::IndexResolverError;
 
@@ -32,10 +33,22 @@ pub enum IndexControllerError {
     DumpError(#[from] DumpError),
     #[error("{0}")]
     DocumentFormatError(#[from] DocumentFormatError),
+    #[error("{0}")]
+    UploadStore(#[from] UploadStoreError),
     #[error("A {0} payload is missing.")]
     MissingPayload(DocumentAdditionFormat),
     #[error("The provided payload reached the size limit.")]
     PayloadTooLarge,
+    #[error("Meilisearch is draining and doesn't accept new tasks.")]
+    Draining,
+    #[error("The search log is disabled, it's enabled with the `--search-log-size` option.")]
+    SearchLogDisabled,
+    #[error("Too many search requests are being processed, retry later.")]
+    TooManySearchRequests,
+    #[error(
+        "Indexes must be declared only once during a swap. `{0}` was specified several times."
+    )]
+    DuplicateIndexInSwap(String),
 }
 
 internal_error!(IndexControllerError: JoinError, UpdateFileStoreError);
@@ -58,9 +71,22 @@ impl ErrorCode for IndexControllerError {
             IndexControllerError::Internal(_) => Code::Internal,
             IndexControllerError::TaskError(e) => e.error_code(),
             IndexControllerError::DocumentFormatError(e) => e.error_code(),
+            IndexControllerError::UploadStore(e) => e.error_code(),
             IndexControllerError::MissingPayload(_) => Code::MissingPayload,
             IndexControllerError::PayloadTooLarge => Code::PayloadTooLarge,
             IndexControllerError::DumpError(e) => e.error_code(),
+            IndexControllerError::Draining => Code::InstanceDraining,
+            IndexControllerError::SearchLogDisabled => Code::SearchLogDisabled,
+            IndexControllerError::TooManySearchRequests => Code::TooManySearchRequests,
+            IndexControllerError::DuplicateIndexInSwap(_) => Code::InvalidSwapIndexes,
+        }
+    }
+
+    fn error_details(&self) -> Option<ErrorDetails> {
+        match self {
+            IndexControllerError::IndexResolver(e) => e.error_details(),
+            IndexControllerError::IndexError(e) => e.error_details(),
+            _ => None,
         }
     }
 }
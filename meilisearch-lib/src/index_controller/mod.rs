@@ -1,7 +1,7 @@
-use meilisearch_auth::SearchRules;
-use std::collections::BTreeMap;
+use meilisearch_auth::{Quota, SearchRules};
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
-use std::io::Cursor;
+use std::io::{self, BufRead, BufWriter, Read, Seek, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
@@ -15,23 +15,41 @@ use meilisearch_types::index_uid::IndexUid;
 use milli::update::IndexDocumentsMethod;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, watch, RwLock};
 use tokio::task::spawn_blocking;
 use tokio::time::sleep;
 use uuid::Uuid;
 
-use crate::document_formats::{read_csv, read_json, read_ndjson};
+use crate::document_expiration::DocumentExpirationService;
+#[cfg(feature = "columnar-payloads")]
+use crate::document_formats::{read_arrow, read_arrow_stream, read_parquet};
+use crate::document_formats::{
+    read_csv, read_json, read_json_transformed, read_ndjson, read_xml, DocumentFormatError,
+};
 use crate::dump::{self, load_dump, DumpHandler};
+use crate::index::attachment::{AttachmentExtractor, AttachmentSettings};
+use crate::index::error::IndexError;
+use crate::index::ingestion::{apply_ingestion_pipeline, transform_documents, IngestionStep};
 use crate::index::{
-    Checked, Document, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings, Unchecked,
+    merge_federated_results, Checked, Document, DocumentStats, FacetSearchQuery, FacetSearchResult,
+    FederatedQuery, FederatedSearchResult, Index, IndexMeta, IndexStats, OnDuplicate, SearchQuery,
+    SearchResult, Settings, SettingsDiff, SettingsVersion, SimilarQuery, SimilarResult,
+    SuggestQuery, SuggestResult, Unchecked,
 };
 use crate::index_resolver::error::IndexResolverError;
+use crate::index_trash::IndexTrashService;
 use crate::options::{IndexerOpts, SchedulerConfig};
-use crate::snapshot::{load_snapshot, SnapshotService};
+use crate::search_cache::{cache_key, SearchCache};
+use crate::search_limiter::{SearchLimiter, SearchPermit};
+use crate::search_log::{LoggedSearch, SearchLog, SearchLogFilter};
+use crate::snapshot::{load_snapshot, SnapshotService, TASKS_SNAPSHOT_DIR};
+use crate::tasks::archive::TaskArchiveService;
 use crate::tasks::error::TaskError;
 use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskId};
+use crate::tasks::update_file_retention::UpdateFileRetentionService;
 use crate::tasks::{
-    BatchHandler, EmptyBatchHandler, Scheduler, SnapshotHandler, TaskFilter, TaskStore,
+    idempotency_key_on_index, BarrierHandler, BatchHandler, EmptyBatchHandler, Scheduler,
+    SearchCacheHandler, SnapshotHandler, TaskCounts, TaskFilter, TaskStore,
 };
 use error::Result;
 
@@ -39,7 +57,8 @@ use self::error::IndexControllerError;
 use crate::index_resolver::index_store::{IndexStore, MapIndexStore};
 use crate::index_resolver::meta_store::{HeedMetaStore, IndexMetaStore};
 use crate::index_resolver::{create_index_resolver, IndexResolver};
-use crate::update_file_store::UpdateFileStore;
+use crate::update_file_store::{UpdateFile, UpdateFileStore};
+use crate::upload_store::{Upload, UploadRetentionService, UploadStore};
 
 pub mod error;
 pub mod versioning;
@@ -47,10 +66,186 @@ pub mod versioning;
 /// Concrete implementation of the IndexController, exposed by meilisearch-lib
 pub type MeiliSearch = IndexController<HeedMetaStore, MapIndexStore>;
 
+/// How often a drain checks whether the batch being processed is finished.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 pub type Payload = Box<
     dyn Stream<Item = std::result::Result<Bytes, PayloadError>> + Send + Sync + 'static + Unpin,
 >;
 
+/// The number of chunks of a payload received ahead of the thread parsing them.
+const PAYLOAD_CHANNEL_CAPACITY: usize = 16;
+
+/// Reads, from a blocking thread, the chunks of a payload as they're received, so the payload is
+/// parsed without being buffered first.
+struct PayloadReader {
+    receiver: mpsc::Receiver<std::result::Result<Bytes, PayloadError>>,
+    chunk: Bytes,
+    /// The error of the payload stream, it ends the payload.
+    error: Option<PayloadError>,
+}
+
+impl PayloadReader {
+    fn new(receiver: mpsc::Receiver<std::result::Result<Bytes, PayloadError>>) -> Self {
+        Self {
+            receiver,
+            chunk: Bytes::new(),
+            error: None,
+        }
+    }
+}
+
+impl Read for PayloadReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for PayloadReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.chunk.is_empty() && self.error.is_none() {
+            match self.receiver.blocking_recv() {
+                Some(Ok(chunk)) => self.chunk = chunk,
+                Some(Err(error)) => self.error = Some(error),
+                None => break,
+            }
+        }
+        match self.error {
+            Some(_) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "the payload couldn't be received",
+            )),
+            None => Ok(&self.chunk),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.chunk = self.chunk.slice(amt..);
+    }
+}
+
+/// The size of the chunks of the exports of documents.
+const EXPORT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Sends what's written to it, from a blocking thread, through a channel. The writes fail once
+/// the receiver is dropped.
+struct ChannelWriter {
+    sender: mpsc::Sender<Result<Bytes>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sender
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the receiver was dropped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Streams the chunks written by `write`, which runs on a blocking thread. The error of `write`
+/// is returned instead of the stream when nothing was written before it.
+async fn stream_written<T, F>(write: F) -> Result<impl Stream<Item = Result<Bytes>>>
+where
+    F: FnOnce(BufWriter<ChannelWriter>) -> std::result::Result<T, IndexError> + Send + 'static,
+{
+    let (sender, mut receiver) = mpsc::channel(PAYLOAD_CHANNEL_CAPACITY);
+    spawn_blocking(move || {
+        let writer = BufWriter::with_capacity(
+            EXPORT_CHUNK_SIZE,
+            ChannelWriter {
+                sender: sender.clone(),
+            },
+        );
+        if let Err(e) = write(writer) {
+            let _ = sender.blocking_send(Err(e.into()));
+        }
+    });
+
+    // The writers check their parameters before writing anything, so the first message tells
+    // whether the writing started.
+    let first = match receiver.recv().await {
+        Some(Err(e)) => return Err(e),
+        first => first,
+    };
+    let rest = futures::stream::poll_fn(move |cx| receiver.poll_recv(cx));
+    Ok(futures::stream::iter(first).chain(rest))
+}
+
+/// Parses the payload into the update file, returning the number of documents. The documents are
+/// transformed by the ingestion pipeline of the index on the way, and then the text of their
+/// attachments is extracted.
+fn read_payload(
+    reader: &mut PayloadReader,
+    format: DocumentAdditionFormat,
+    ingestion_pipeline: &[IngestionStep],
+    attachments: &AttachmentSettings,
+    update_file: &mut UpdateFile,
+) -> Result<usize> {
+    // check if the payload is empty, and return an error
+    if reader
+        .fill_buf()
+        .map_err(DocumentFormatError::from)?
+        .is_empty()
+    {
+        return Err(IndexControllerError::MissingPayload(format));
+    }
+
+    let mut extractor = AttachmentExtractor::new(attachments);
+    if ingestion_pipeline.is_empty() && extractor.is_empty() {
+        return parse_payload(reader, format, &mut **update_file);
+    }
+    let mut transform =
+        |position: usize, document: &mut serde_json::Map<String, serde_json::Value>| {
+            apply_ingestion_pipeline(ingestion_pipeline, document);
+            extractor.extract(position, document)
+        };
+
+    // The json documents are transformed as they're parsed. The documents of the other formats
+    // are parsed first, so they're transformed the same way whatever their format.
+    let ndjson = match format {
+        DocumentAdditionFormat::Json => false,
+        DocumentAdditionFormat::Ndjson => true,
+        format => {
+            let mut documents = tempfile::tempfile().map_err(IndexError::from)?;
+            parse_payload(reader, format, &mut documents)?;
+            let count = transform_documents(&mut documents, &mut **update_file, transform)?;
+            return Ok(count);
+        }
+    };
+    read_json_transformed(reader, &mut **update_file, ndjson, |position, document| {
+        transform(position, document).map_err(IndexControllerError::from)
+    })
+}
+
+fn parse_payload(
+    reader: &mut PayloadReader,
+    format: DocumentAdditionFormat,
+    writer: impl Write + Seek,
+) -> Result<usize> {
+    let count = match format {
+        DocumentAdditionFormat::Json => read_json(reader, writer)?,
+        DocumentAdditionFormat::Csv(delimiter) => read_csv(reader, writer, delimiter)?,
+        DocumentAdditionFormat::Ndjson => read_ndjson(reader, writer)?,
+        #[cfg(feature = "columnar-payloads")]
+        DocumentAdditionFormat::Parquet => read_parquet(reader, writer)?,
+        #[cfg(feature = "columnar-payloads")]
+        DocumentAdditionFormat::Arrow => read_arrow(reader, writer)?,
+        #[cfg(feature = "columnar-payloads")]
+        DocumentAdditionFormat::ArrowStream => read_arrow_stream(reader, writer)?,
+        DocumentAdditionFormat::Xml(record_path) => read_xml(reader, writer, &record_path)?,
+    };
+
+    Ok(count)
+}
+
 pub fn open_meta_env(path: &Path, size: usize) -> milli::heed::Result<milli::heed::Env> {
     let mut options = milli::heed::EnvOpenOptions::new();
     options.map_size(size);
@@ -68,17 +263,53 @@ pub struct IndexMetadata {
     pub meta: IndexMeta,
 }
 
+/// A name resolved to the index it targets by the search and documents routes.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexAlias {
+    pub alias: String,
+    pub index_uid: String,
+}
+
+/// The settings applied to the indexes created with a uid matching the pattern.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexTemplate {
+    pub pattern: String,
+    pub settings: Settings<Unchecked>,
+}
+
 #[derive(Clone, Debug)]
 pub struct IndexSettings {
     pub uid: Option<String>,
     pub primary_key: Option<String>,
 }
 
+/// The state of the instance regarding a graceful drain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DrainStatus {
+    /// New tasks are accepted and processed.
+    Running,
+    /// New tasks are refused, and the batch being processed, if any, is finishing.
+    Draining,
+    /// No task is processed anymore, and the task store is flushed to disk.
+    Drained,
+}
+
 pub struct IndexController<U, I> {
     pub index_resolver: Arc<IndexResolver<U, I>>,
     scheduler: Arc<RwLock<Scheduler>>,
     task_store: TaskStore,
     pub update_file_store: UpdateFileStore,
+    upload_store: UploadStore,
+    drain_status: Arc<watch::Sender<DrainStatus>>,
+    search_log: Option<SearchLog>,
+    search_limiter: Option<SearchLimiter>,
+    search_cache: Option<SearchCache>,
+    /// Whether the document additions and settings updates create their index when it doesn't
+    /// exist, for the keys allowed to create indexes.
+    auto_index_creation: bool,
 }
 
 /// Need a custom implementation for clone because deriving require that U and I are clone.
@@ -88,7 +319,13 @@ impl<U, I> Clone for IndexController<U, I> {
             index_resolver: self.index_resolver.clone(),
             scheduler: self.scheduler.clone(),
             update_file_store: self.update_file_store.clone(),
+            upload_store: self.upload_store.clone(),
             task_store: self.task_store.clone(),
+            drain_status: self.drain_status.clone(),
+            search_log: self.search_log.clone(),
+            search_limiter: self.search_limiter.clone(),
+            search_cache: self.search_cache.clone(),
+            auto_index_creation: self.auto_index_creation,
         }
     }
 }
@@ -96,8 +333,20 @@ impl<U, I> Clone for IndexController<U, I> {
 #[derive(Debug)]
 pub enum DocumentAdditionFormat {
     Json,
-    Csv,
+    /// The values of the csv payload are separated by the delimiter.
+    Csv(u8),
     Ndjson,
+    #[cfg(feature = "columnar-payloads")]
+    Parquet,
+    /// An arrow IPC file.
+    #[cfg(feature = "columnar-payloads")]
+    Arrow,
+    /// An arrow IPC stream.
+    #[cfg(feature = "columnar-payloads")]
+    ArrowStream,
+    /// Each element of the xml payload at the record path, given as the names of the elements
+    /// from the root, is a document.
+    Xml(Vec<String>),
 }
 
 impl fmt::Display for DocumentAdditionFormat {
@@ -105,7 +354,14 @@ impl fmt::Display for DocumentAdditionFormat {
         match self {
             DocumentAdditionFormat::Json => write!(f, "json"),
             DocumentAdditionFormat::Ndjson => write!(f, "ndjson"),
-            DocumentAdditionFormat::Csv => write!(f, "csv"),
+            DocumentAdditionFormat::Csv(_) => write!(f, "csv"),
+            #[cfg(feature = "columnar-payloads")]
+            DocumentAdditionFormat::Parquet => write!(f, "parquet"),
+            #[cfg(feature = "columnar-payloads")]
+            DocumentAdditionFormat::Arrow => write!(f, "arrow"),
+            #[cfg(feature = "columnar-payloads")]
+            DocumentAdditionFormat::ArrowStream => write!(f, "arrow stream"),
+            DocumentAdditionFormat::Xml(_) => write!(f, "xml"),
         }
     }
 }
@@ -119,17 +375,43 @@ pub struct Stats {
     pub indexes: BTreeMap<String, IndexStats>,
 }
 
+/// The stats of a single index, along with the ones too costly to compute for all the indexes of
+/// the instance at once.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexDetailedStats {
+    #[serde(flatten)]
+    pub stats: IndexStats,
+    pub database_size: u64,
+    /// The size the index can't outgrow.
+    pub max_size: usize,
+    /// The number of tasks of the index that are enqueued or being processed.
+    pub number_of_pending_tasks: usize,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_update: OffsetDateTime,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(derivative::Derivative)]
 #[derivative(Debug)]
 pub enum Update {
     DeleteDocuments(Vec<String>),
+    /// Deletes the documents matching a filter expression.
+    DeleteDocumentsByFilter(serde_json::Value),
+    /// Runs a function on the documents matching a filter expression, or on every document.
+    EditDocumentsByFunction {
+        function: String,
+        filter: Option<serde_json::Value>,
+    },
     ClearDocuments,
     Settings {
         settings: Settings<Unchecked>,
         /// Indicates whether the update was a deletion
         is_deletion: bool,
         allow_index_creation: bool,
+        /// The limits of the key registering the update, checked when it's registered and when
+        /// its task is processed.
+        quota: Quota,
     },
     DocumentAddition {
         #[derivative(Debug = "ignore")]
@@ -138,20 +420,39 @@ pub enum Update {
         method: IndexDocumentsMethod,
         format: DocumentAdditionFormat,
         allow_index_creation: bool,
+        auto_generate_primary_key: bool,
+        merge_nested: bool,
+        on_duplicate: OnDuplicate,
+        quota: Quota,
     },
     DeleteIndex,
     CreateIndex {
         primary_key: Option<String>,
+        quota: Quota,
     },
     UpdateIndex {
         primary_key: Option<String>,
+        max_size: Option<usize>,
+        frozen: Option<bool>,
     },
 }
 
 #[derive(Default, Debug)]
 pub struct IndexControllerBuilder {
     max_index_size: Option<usize>,
+    max_open_indexes: Option<usize>,
+    max_indexes: Option<u64>,
+    max_documents_per_index: Option<u64>,
     max_task_store_size: Option<usize>,
+    task_db_path: Option<PathBuf>,
+    task_db_size: Option<usize>,
+    task_archive_dir: Option<PathBuf>,
+    task_archive_after: Option<Duration>,
+    documents_expiration_interval: Option<Duration>,
+    update_files_retention: Option<Duration>,
+    upload_retention: Option<Duration>,
+    max_upload_size: Option<u64>,
+    deleted_index_retention: Option<Duration>,
     snapshot_dir: Option<PathBuf>,
     import_snapshot: Option<PathBuf>,
     snapshot_interval: Option<Duration>,
@@ -162,6 +463,11 @@ pub struct IndexControllerBuilder {
     dump_dst: Option<PathBuf>,
     ignore_dump_if_db_exists: bool,
     ignore_missing_dump: bool,
+    search_log_size: Option<usize>,
+    max_concurrent_searches: Option<usize>,
+    search_queue_size: usize,
+    search_cache_size: Option<usize>,
+    disable_auto_index_creation: bool,
 }
 
 impl IndexControllerBuilder {
@@ -177,6 +483,7 @@ impl IndexControllerBuilder {
         let task_store_size = self
             .max_task_store_size
             .ok_or_else(|| anyhow::anyhow!("Missing update database size"))?;
+        let task_db_size = self.task_db_size.unwrap_or(task_store_size);
 
         if let Some(ref path) = self.import_snapshot {
             log::info!("Loading from snapshot {:?}", path);
@@ -207,18 +514,45 @@ impl IndexControllerBuilder {
         std::fs::create_dir_all(db_path.as_ref())?;
 
         let meta_env = Arc::new(open_meta_env(db_path.as_ref(), task_store_size)?);
+        let task_env = match self.task_db_path {
+            Some(ref path) => {
+                std::fs::create_dir_all(path)?;
+                let task_env = Arc::new(open_meta_env(path, task_db_size)?);
+                // The tasks of an existing database or of an imported dump are stored in the main
+                // environment, they are moved to the task environment on the first start.
+                TaskStore::migrate(meta_env.clone(), task_env.clone())?;
+                task_env
+            }
+            None => meta_env.clone(),
+        };
 
-        let update_file_store = UpdateFileStore::new(&db_path)?;
+        // Snapshots taken while the task store was in its own environment carry it separately.
+        let tasks_snapshot_path = db_path.as_ref().join(TASKS_SNAPSHOT_DIR);
+        if tasks_snapshot_path.exists() {
+            let snapshot_env = Arc::new(open_meta_env(&tasks_snapshot_path, task_db_size)?);
+            TaskStore::migrate(snapshot_env, task_env.clone())?;
+            std::fs::remove_dir_all(&tasks_snapshot_path)?;
+        }
+
+        let mut update_file_store = UpdateFileStore::new(&db_path)?;
+        update_file_store.set_retain_processed(self.update_files_retention.is_some());
         // Create or overwrite the version file for this DB
         versioning::create_version_file(db_path.as_ref())?;
 
-        let index_resolver = Arc::new(create_index_resolver(
+        let mut index_resolver = create_index_resolver(
             &db_path,
             index_size,
+            self.max_open_indexes,
             &indexer_options,
             meta_env.clone(),
             update_file_store.clone(),
-        )?);
+        )?;
+        index_resolver.set_trash_deleted_indexes(self.deleted_index_retention.is_some());
+        index_resolver.set_quota(Quota {
+            max_indexes: self.max_indexes,
+            max_documents_per_index: self.max_documents_per_index,
+        });
+        let index_resolver = Arc::new(index_resolver);
 
         let dump_path = self
             .dump_dst
@@ -230,20 +564,32 @@ impl IndexControllerBuilder {
             update_file_store.clone(),
             task_store_size,
             index_size,
-            meta_env.clone(),
+            task_env.clone(),
             index_resolver.clone(),
         ));
-        let task_store = TaskStore::new(meta_env)?;
+        let task_store = TaskStore::new(task_env)?;
+
+        let search_cache = self.search_cache_size.map(SearchCache::new);
+        // the cached search results of an index are dropped once its tasks are processed.
+        let index_handler: Arc<dyn BatchHandler + Sync + Send + 'static> = match search_cache {
+            Some(ref cache) => Arc::new(SearchCacheHandler::new(
+                index_resolver.clone(),
+                cache.clone(),
+            )),
+            None => index_resolver.clone(),
+        };
 
         // register all the batch handlers for use with the scheduler.
         let handlers: Vec<Arc<dyn BatchHandler + Sync + Send + 'static>> = vec![
-            index_resolver.clone(),
+            index_handler,
             dump_handler,
             Arc::new(SnapshotHandler),
+            Arc::new(BarrierHandler),
             // dummy handler to catch all empty batches
             Arc::new(EmptyBatchHandler),
         ];
         let scheduler = Scheduler::new(task_store.clone(), handlers, scheduler_config)?;
+        let drain_status = Arc::new(watch::channel(DrainStatus::Running).0);
 
         if self.schedule_snapshot {
             let snapshot_period = self
@@ -259,31 +605,219 @@ impl IndexControllerBuilder {
                 snapshot_path,
                 index_size,
                 meta_env_size: task_store_size,
+                task_db_path: self.task_db_path.clone(),
+                task_db_size,
                 scheduler: scheduler.clone(),
             };
 
             tokio::task::spawn_local(snapshot_service.run());
         }
 
+        if let Some(archive_dir) = self.task_archive_dir {
+            let archive_after = self
+                .task_archive_after
+                .ok_or_else(|| anyhow::anyhow!("Task archival delay not provided."))?;
+
+            let archive_service = TaskArchiveService {
+                task_store: task_store.clone(),
+                update_file_store: update_file_store.clone(),
+                archive_dir,
+                archive_after,
+            };
+
+            tokio::task::spawn_local(archive_service.run());
+        }
+
+        if let Some(retention) = self.update_files_retention {
+            let retention_service = UpdateFileRetentionService {
+                task_store: task_store.clone(),
+                update_file_store: update_file_store.clone(),
+                retention,
+            };
+
+            tokio::task::spawn_local(retention_service.run());
+        }
+
+        if let Some(interval) = self.documents_expiration_interval {
+            let expiration_service = DocumentExpirationService {
+                index_resolver: index_resolver.clone(),
+                task_store: task_store.clone(),
+                scheduler: scheduler.clone(),
+                drain_status: drain_status.subscribe(),
+                interval,
+            };
+
+            tokio::task::spawn_local(expiration_service.run());
+        }
+
+        if let Some(retention) = self.deleted_index_retention {
+            let trash_service = IndexTrashService {
+                index_resolver: index_resolver.clone(),
+                retention,
+            };
+
+            tokio::task::spawn_local(trash_service.run());
+        }
+
+        let mut upload_store = UploadStore::new(&db_path);
+        if let Some(max_size) = self.max_upload_size {
+            upload_store.set_max_size(max_size);
+        }
+        if let Some(retention) = self.upload_retention {
+            let retention_service = UploadRetentionService {
+                upload_store: upload_store.clone(),
+                retention,
+            };
+
+            tokio::task::spawn_local(retention_service.run());
+        }
+
         Ok(IndexController {
             index_resolver,
             scheduler,
             update_file_store,
+            upload_store,
             task_store,
+            drain_status,
+            search_log: self.search_log_size.map(SearchLog::new),
+            search_limiter: self
+                .max_concurrent_searches
+                .map(|max| SearchLimiter::new(max, self.search_queue_size)),
+            search_cache,
+            auto_index_creation: !self.disable_auto_index_creation,
         })
     }
 
+    /// Set the index controller builder's search log size. The searches aren't recorded when
+    /// unset.
+    pub fn set_search_log_size(&mut self, search_log_size: usize) -> &mut Self {
+        self.search_log_size.replace(search_log_size);
+        self
+    }
+
+    /// Set the index controller builder's max number of concurrent searches. The searches aren't
+    /// limited when unset.
+    pub fn set_max_concurrent_searches(&mut self, max_concurrent_searches: usize) -> &mut Self {
+        self.max_concurrent_searches
+            .replace(max_concurrent_searches);
+        self
+    }
+
+    /// Set the index controller builder's number of searches waiting for a slot once the max
+    /// number of concurrent searches is reached.
+    pub fn set_search_queue_size(&mut self, search_queue_size: usize) -> &mut Self {
+        self.search_queue_size = search_queue_size;
+        self
+    }
+
+    /// Set the index controller builder's number of cached search results. The search results
+    /// aren't cached when unset.
+    pub fn set_search_cache_size(&mut self, search_cache_size: usize) -> &mut Self {
+        self.search_cache_size.replace(search_cache_size);
+        self
+    }
+
+    /// Set whether the document additions and settings updates targeting an index that doesn't
+    /// exist fail rather than create it.
+    pub fn set_disable_auto_index_creation(
+        &mut self,
+        disable_auto_index_creation: bool,
+    ) -> &mut Self {
+        self.disable_auto_index_creation = disable_auto_index_creation;
+        self
+    }
+
     /// Set the index controller builder's max update store size.
     pub fn set_max_task_store_size(&mut self, max_update_store_size: usize) -> &mut Self {
         self.max_task_store_size.replace(max_update_store_size);
         self
     }
 
+    /// Set the index controller builder's task database path. When unset, the task store shares
+    /// the environment of the main database.
+    pub fn set_task_db_path(&mut self, task_db_path: PathBuf) -> &mut Self {
+        self.task_db_path.replace(task_db_path);
+        self
+    }
+
+    /// Set the index controller builder's task database size, the size of the map of the task
+    /// database when it has its own path. Defaults to the maximum task store size.
+    pub fn set_task_db_size(&mut self, task_db_size: usize) -> &mut Self {
+        self.task_db_size.replace(task_db_size);
+        self
+    }
+
+    /// Set the index controller builder's task archive directory. When set, the finished tasks
+    /// older than the archival delay are periodically moved there.
+    pub fn set_task_archive_dir(&mut self, task_archive_dir: PathBuf) -> &mut Self {
+        self.task_archive_dir.replace(task_archive_dir);
+        self
+    }
+
+    /// Set the index controller builder's task archival delay.
+    pub fn set_task_archive_after(&mut self, task_archive_after: Duration) -> &mut Self {
+        self.task_archive_after.replace(task_archive_after);
+        self
+    }
+
+    /// Set the index controller builder's documents expiration interval. When set, the deletion
+    /// of the expired documents is enqueued at this interval.
+    pub fn set_documents_expiration_interval(&mut self, interval: Duration) -> &mut Self {
+        self.documents_expiration_interval.replace(interval);
+        self
+    }
+
+    /// Set the index controller builder's update files retention. When set, the update files are
+    /// kept for this duration after their task is processed, instead of being deleted right away.
+    pub fn set_update_files_retention(&mut self, retention: Duration) -> &mut Self {
+        self.update_files_retention.replace(retention);
+        self
+    }
+
+    /// Set the index controller builder's upload retention. When set, the uploads that received no
+    /// chunk for this duration are deleted.
+    pub fn set_upload_retention(&mut self, retention: Duration) -> &mut Self {
+        self.upload_retention.replace(retention);
+        self
+    }
+
+    /// Set the size the payload of an upload can't outgrow, all its chunks included.
+    pub fn set_max_upload_size(&mut self, size: u64) -> &mut Self {
+        self.max_upload_size.replace(size);
+        self
+    }
+
+    /// Set the index controller builder's deleted index retention. When set, the deleted indexes
+    /// are moved to a trash, from which they can be restored for this duration.
+    pub fn set_deleted_index_retention(&mut self, retention: Duration) -> &mut Self {
+        self.deleted_index_retention.replace(retention);
+        self
+    }
+
     pub fn set_max_index_size(&mut self, size: usize) -> &mut Self {
         self.max_index_size.replace(size);
         self
     }
 
+    /// Set the index controller builder's maximum number of open indexes. Past it, the least
+    /// recently used indexes are closed.
+    pub fn set_max_open_indexes(&mut self, max: usize) -> &mut Self {
+        self.max_open_indexes.replace(max);
+        self
+    }
+
+    /// Set the index controller builder's maximum number of indexes.
+    pub fn set_max_indexes(&mut self, max: u64) -> &mut Self {
+        self.max_indexes.replace(max);
+        self
+    }
+
+    /// Set the index controller builder's maximum number of documents of each index.
+    pub fn set_max_documents_per_index(&mut self, max: u64) -> &mut Self {
+        self.max_documents_per_index.replace(max);
+        self
+    }
+
     /// Set the index controller builder's snapshot path.
     pub fn set_snapshot_dir(&mut self, snapshot_dir: PathBuf) -> &mut Self {
         self.snapshot_dir.replace(snapshot_dir);
@@ -358,12 +892,199 @@ where
     }
 
     pub async fn register_update(&self, uid: String, update: Update) -> Result<Task> {
+        self.register_idempotent_update(uid, update, None).await
+    }
+
+    /// Registers the update, unless a task was already registered on the index with the
+    /// idempotency key, in which case that task is returned and the update is dropped.
+    pub async fn register_idempotent_update(
+        &self,
+        uid: String,
+        update: Update,
+        idempotency_key: Option<String>,
+    ) -> Result<Task> {
+        self.ensure_not_draining()?;
+        let idempotency_key = match idempotency_key {
+            Some(key) => idempotency_key_on_index(&uid, &key),
+            None => {
+                let content = self.update_to_task_content(uid, update).await?;
+                let task = self.task_store.register(content).await?;
+                self.scheduler.read().await.notify();
+                return Ok(task);
+            }
+        };
+
+        // checked before the update is read, so the payload of a retried addition isn't stored
+        // again
+        if let Some(task) = self
+            .task_store
+            .get_idempotent_task(idempotency_key.clone())
+            .await?
+        {
+            return Ok(task);
+        }
+
+        let content = self.update_to_task_content(uid, update).await?;
+        let content_uuid = match content {
+            TaskContent::DocumentAddition { content_uuid, .. } => Some(content_uuid),
+            _ => None,
+        };
+        let (task, registered) = self
+            .task_store
+            .register_idempotent(content, idempotency_key)
+            .await?;
+        if registered {
+            self.scheduler.read().await.notify();
+        } else {
+            // a concurrent request with the same key was registered first
+            self.delete_content_files(content_uuid.into_iter().collect())
+                .await;
+        }
+
+        Ok(task)
+    }
+
+    /// Creates an upload of the payload of a document addition to the index, receiving the payload
+    /// in several chunks.
+    pub async fn create_upload(&self, uid: String) -> Result<Upload> {
+        self.ensure_not_draining()?;
+        let index_uid = IndexUid::from_str(&uid).map_err(IndexResolverError::from)?;
+        Ok(self.upload_store.create(index_uid).await?)
+    }
+
+    pub async fn get_upload(&self, uid: String, upload_id: Uuid) -> Result<Upload> {
+        Ok(self.upload_store.get(&uid, upload_id).await?)
+    }
+
+    /// Appends a chunk, starting at `offset`, to the payload of the upload.
+    pub async fn upload_chunk(
+        &self,
+        uid: String,
+        upload_id: Uuid,
+        offset: u64,
+        chunk: Payload,
+    ) -> Result<Upload> {
+        Ok(self
+            .upload_store
+            .append(&uid, upload_id, offset, chunk)
+            .await?)
+    }
+
+    pub async fn delete_upload(&self, uid: String, upload_id: Uuid) -> Result<()> {
+        Ok(self.upload_store.delete(&uid, upload_id).await?)
+    }
+
+    /// Registers the document addition built from the assembled payload of the upload, then
+    /// deletes the upload. The upload is kept when the addition can't be registered, so the
+    /// commit can be retried.
+    pub async fn commit_upload(
+        &self,
+        uid: String,
+        upload_id: Uuid,
+        idempotency_key: Option<String>,
+        make_update: impl FnOnce(Payload) -> Update,
+    ) -> Result<Task> {
+        // the upload of a retried commit was deleted along with the first commit
+        if let Some(key) = &idempotency_key {
+            let key = idempotency_key_on_index(&uid, key);
+            if let Some(task) = self.task_store.get_idempotent_task(key).await? {
+                return Ok(task);
+            }
+        }
+
+        let payload = self.upload_store.payload(&uid, upload_id).await?;
+        let task = self
+            .register_idempotent_update(uid.clone(), make_update(payload), idempotency_key)
+            .await?;
+        if let Err(e) = self.upload_store.delete(&uid, upload_id).await {
+            log::error!("error deleting upload {}: {}", upload_id, e);
+        }
+
+        Ok(task)
+    }
+
+    /// Registers all the updates at once: either every update is registered, with contiguous
+    /// task ids, or none of them are.
+    pub async fn register_updates(&self, updates: Vec<(String, Update)>) -> Result<Vec<Task>> {
+        self.ensure_not_draining()?;
+        let mut contents = Vec::with_capacity(updates.len());
+        let mut content_uuids = Vec::new();
+        for (uid, update) in updates {
+            match self.update_to_task_content(uid, update).await {
+                Ok(content) => {
+                    if let TaskContent::DocumentAddition { content_uuid, .. } = content {
+                        content_uuids.push(content_uuid);
+                    }
+                    contents.push(content);
+                }
+                Err(e) => {
+                    self.delete_content_files(content_uuids).await;
+                    return Err(e);
+                }
+            }
+        }
+
+        let tasks = match self.task_store.register_batch(contents).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                self.delete_content_files(content_uuids).await;
+                return Err(e.into());
+            }
+        };
+        self.scheduler.read().await.notify();
+
+        Ok(tasks)
+    }
+
+    /// Removes the update files of updates that could not be registered.
+    async fn delete_content_files(&self, content_uuids: Vec<Uuid>) {
+        for content_uuid in content_uuids {
+            if let Err(e) = self.update_file_store.delete(content_uuid).await {
+                log::error!("error deleting update file {}: {}", content_uuid, e);
+            }
+        }
+    }
+
+    /// Fails when the index `uid` would be created past the number of indexes allowed by the
+    /// quota, so the request is rejected right away. The quota is checked again along with the
+    /// limits of the instance when the task is processed, since the tasks enqueued before may
+    /// create indexes.
+    async fn check_index_quota(&self, uid: &str, quota: &Quota) -> Result<()> {
+        if let Some(max_indexes) = quota.max_indexes {
+            let uids = self.index_resolver.list_uids().await?;
+            if !uids.iter().any(|index_uid| index_uid == uid) && uids.len() as u64 >= max_indexes {
+                return Err(IndexResolverError::IndexQuotaExceeded(max_indexes).into());
+            }
+        }
+        Ok(())
+    }
+
+    async fn update_to_task_content(&self, uid: String, update: Update) -> Result<TaskContent> {
+        // the documents and settings updates of an alias apply to the index it targets when
+        // they're registered
+        let uid = match update {
+            Update::DeleteDocuments(_)
+            | Update::DeleteDocumentsByFilter(_)
+            | Update::EditDocumentsByFunction { .. }
+            | Update::ClearDocuments
+            | Update::DocumentAddition { .. }
+            | Update::Settings { .. } => self.index_resolver.resolve_alias(uid).await?,
+            Update::DeleteIndex | Update::CreateIndex { .. } | Update::UpdateIndex { .. } => uid,
+        };
         let index_uid = IndexUid::from_str(&uid).map_err(IndexResolverError::from)?;
         let content = match update {
             Update::DeleteDocuments(ids) => TaskContent::DocumentDeletion {
                 index_uid,
                 deletion: DocumentDeletion::Ids(ids),
             },
+            Update::DeleteDocumentsByFilter(filter) => {
+                TaskContent::DocumentDeletionByFilter { index_uid, filter }
+            }
+            Update::EditDocumentsByFunction { function, filter } => TaskContent::DocumentEdition {
+                index_uid,
+                function,
+                filter,
+            },
             Update::ClearDocuments => TaskContent::DocumentDeletion {
                 index_uid,
                 deletion: DocumentDeletion::Clear,
@@ -372,83 +1093,305 @@ where
                 settings,
                 is_deletion,
                 allow_index_creation,
-            } => TaskContent::SettingsUpdate {
-                settings,
-                is_deletion,
-                allow_index_creation,
-                index_uid,
-            },
+                quota,
+            } => {
+                let allow_index_creation = allow_index_creation && self.auto_index_creation;
+                if allow_index_creation {
+                    self.check_index_quota(&uid, &quota).await?;
+                }
+                TaskContent::SettingsUpdate {
+                    settings,
+                    is_deletion,
+                    allow_index_creation,
+                    quota,
+                    index_uid,
+                }
+            }
             Update::DocumentAddition {
                 mut payload,
                 primary_key,
                 format,
                 method,
                 allow_index_creation,
+                auto_generate_primary_key,
+                merge_nested,
+                on_duplicate,
+                quota,
             } => {
-                let mut buffer = Vec::new();
-                while let Some(bytes) = payload.next().await {
-                    let bytes = bytes?;
-                    buffer.extend_from_slice(&bytes);
-                }
+                let (ingestion_pipeline, attachments, stored_documents) =
+                    match self.index_resolver.get_index(uid.clone()).await {
+                        Ok(index) => {
+                            spawn_blocking(move || -> Result<_> {
+                                Ok((
+                                    index.ingestion_pipeline()?,
+                                    index.attachments()?,
+                                    index.stats()?.number_of_documents,
+                                ))
+                            })
+                            .await??
+                        }
+                        // the index is created by the task, without ingestion pipeline nor
+                        // attachments
+                        Err(IndexResolverError::UnexistingIndex(_)) => {
+                            if allow_index_creation && self.auto_index_creation {
+                                self.check_index_quota(&uid, &quota).await?;
+                            }
+                            Default::default()
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
                 let (content_uuid, mut update_file) = self.update_file_store.new_update()?;
-                let documents_count = tokio::task::spawn_blocking(move || -> Result<_> {
-                    // check if the payload is empty, and return an error
-                    if buffer.is_empty() {
-                        return Err(IndexControllerError::MissingPayload(format));
+                let (sender, receiver) = mpsc::channel(PAYLOAD_CHANNEL_CAPACITY);
+                let reading = tokio::task::spawn_blocking(move || -> Result<_> {
+                    let mut reader = PayloadReader::new(receiver);
+                    let count = read_payload(
+                        &mut reader,
+                        format,
+                        &ingestion_pipeline,
+                        &attachments,
+                        &mut update_file,
+                    );
+                    // the error of the payload stream prevails over the parsing error it caused
+                    if let Some(error) = reader.error.take() {
+                        return Err(error.into());
                     }
-
-                    let reader = Cursor::new(buffer);
-                    let count = match format {
-                        DocumentAdditionFormat::Json => read_json(reader, &mut *update_file)?,
-                        DocumentAdditionFormat::Csv => read_csv(reader, &mut *update_file)?,
-                        DocumentAdditionFormat::Ndjson => read_ndjson(reader, &mut *update_file)?,
-                    };
+                    let count = count?;
 
                     update_file.persist()?;
 
                     Ok(count)
-                })
-                .await??;
+                });
+
+                while let Some(bytes) = payload.next().await {
+                    let is_err = bytes.is_err();
+                    // the reader stops receiving the payload as soon as it's found malformed, its
+                    // error is returned below without waiting for the rest of the payload
+                    if sender.send(bytes).await.is_err() || is_err {
+                        break;
+                    }
+                }
+                drop(sender);
+                let documents_count = reading.await??;
+
+                // the payload is counted as new documents, even though some of them may replace
+                // stored ones. The additions enqueued before are counted when the task is
+                // processed.
+                if let Some(max_documents) = quota.max_documents_per_index {
+                    if stored_documents + documents_count as u64 > max_documents {
+                        self.delete_content_files(vec![content_uuid]).await;
+                        return Err(IndexError::DocumentQuotaExceeded(max_documents).into());
+                    }
+                }
 
                 TaskContent::DocumentAddition {
                     content_uuid,
                     merge_strategy: method,
                     primary_key,
                     documents_count,
-                    allow_index_creation,
+                    allow_index_creation: allow_index_creation && self.auto_index_creation,
+                    quota,
+                    auto_generate_primary_key,
+                    merge_nested,
+                    on_duplicate,
                     index_uid,
                 }
             }
             Update::DeleteIndex => TaskContent::IndexDeletion { index_uid },
-            Update::CreateIndex { primary_key } => TaskContent::IndexCreation {
+            Update::CreateIndex { primary_key, quota } => {
+                self.check_index_quota(&uid, &quota).await?;
+                TaskContent::IndexCreation {
+                    primary_key,
+                    quota,
+                    index_uid,
+                }
+            }
+            Update::UpdateIndex {
                 primary_key,
-                index_uid,
-            },
-            Update::UpdateIndex { primary_key } => TaskContent::IndexUpdate {
+                max_size,
+                frozen,
+            } => TaskContent::IndexUpdate {
                 primary_key,
+                max_size,
+                frozen,
                 index_uid,
             },
         };
 
+        Ok(content)
+    }
+
+    pub async fn register_dump_task(&self) -> Result<Task> {
+        self.ensure_not_draining()?;
+        let uid = dump::generate_uid();
+        let content = TaskContent::Dump { uid };
         let task = self.task_store.register(content).await?;
         self.scheduler.read().await.notify();
+        Ok(task)
+    }
 
+    /// Registers a barrier task. The tasks enqueued after it on `index_uids`, or on every index
+    /// when `None`, are only processed once all the tasks enqueued before it are processed.
+    pub async fn register_barrier_task(&self, index_uids: Option<Vec<String>>) -> Result<Task> {
+        self.ensure_not_draining()?;
+        let index_uids = match index_uids {
+            Some(uids) => Some(
+                uids.iter()
+                    .map(|uid| IndexUid::from_str(uid).map_err(IndexResolverError::from))
+                    .collect::<std::result::Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+        let content = TaskContent::Barrier { index_uids };
+        let task = self.task_store.register(content).await?;
+        self.scheduler.read().await.notify();
         Ok(task)
     }
 
-    pub async fn register_dump_task(&self) -> Result<Task> {
-        let uid = dump::generate_uid();
-        let content = TaskContent::Dump { uid };
+    /// Registers a task swapping the indexes of each pair of uids, all at once. An index can only
+    /// be part of one pair.
+    pub async fn register_index_swap_task(&self, swaps: Vec<(String, String)>) -> Result<Task> {
+        self.ensure_not_draining()?;
+        let mut swapped = HashSet::new();
+        let mut index_uid = |uid: String| {
+            if !swapped.insert(uid.clone()) {
+                return Err(IndexControllerError::DuplicateIndexInSwap(uid));
+            }
+            Ok(IndexUid::from_str(&uid).map_err(IndexResolverError::from)?)
+        };
+        let swaps = swaps
+            .into_iter()
+            .map(|(lhs, rhs)| Ok((index_uid(lhs)?, index_uid(rhs)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let content = TaskContent::IndexSwap { swaps };
+        let task = self.task_store.register(content).await?;
+        self.scheduler.read().await.notify();
+        Ok(task)
+    }
+
+    /// Registers a task creating the index `index_uid` as a copy of the index `source_uid`.
+    pub async fn register_index_clone_task(
+        &self,
+        source_uid: String,
+        index_uid: String,
+        quota: Quota,
+    ) -> Result<Task> {
+        self.ensure_not_draining()?;
+        let source_uid = IndexUid::from_str(&source_uid).map_err(IndexResolverError::from)?;
+        let index_uid = IndexUid::from_str(&index_uid).map_err(IndexResolverError::from)?;
+        self.check_index_quota(index_uid.as_str(), &quota).await?;
+        let content = TaskContent::IndexClone {
+            index_uid,
+            source_uid,
+            quota,
+        };
+        let task = self.task_store.register(content).await?;
+        self.scheduler.read().await.notify();
+        Ok(task)
+    }
+
+    /// Registers a task compacting the environment of the index, once its previously enqueued
+    /// tasks are processed.
+    pub async fn register_index_compaction_task(&self, uid: String) -> Result<Task> {
+        self.ensure_not_draining()?;
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        let index_uid = IndexUid::from_str(&uid).map_err(IndexResolverError::from)?;
+        let content = TaskContent::IndexCompaction { index_uid };
+        let task = self.task_store.register(content).await?;
+        self.scheduler.read().await.notify();
+        Ok(task)
+    }
+
+    /// Registers a task verifying the consistency of the databases of the index, once its
+    /// previously enqueued tasks are processed.
+    pub async fn register_index_integrity_check_task(&self, uid: String) -> Result<Task> {
+        self.ensure_not_draining()?;
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        let index_uid = IndexUid::from_str(&uid).map_err(IndexResolverError::from)?;
+        let content = TaskContent::IndexIntegrityCheck { index_uid };
         let task = self.task_store.register(content).await?;
         self.scheduler.read().await.notify();
         Ok(task)
     }
 
+    /// Registers a task loading the databases used by the searches of the index in memory, once
+    /// its previously enqueued tasks are processed.
+    pub async fn register_index_warmup_task(&self, uid: String) -> Result<Task> {
+        self.ensure_not_draining()?;
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        let index_uid = IndexUid::from_str(&uid).map_err(IndexResolverError::from)?;
+        let content = TaskContent::IndexWarmup { index_uid };
+        let task = self.task_store.register(content).await?;
+        self.scheduler.read().await.notify();
+        Ok(task)
+    }
+
+    /// Registers a task restoring the deleted index `uid`, once its previously enqueued tasks
+    /// are processed.
+    pub async fn register_index_restoration_task(&self, uid: String, quota: Quota) -> Result<Task> {
+        self.ensure_not_draining()?;
+        let index_uid = IndexUid::from_str(&uid).map_err(IndexResolverError::from)?;
+        self.check_index_quota(index_uid.as_str(), &quota).await?;
+        let content = TaskContent::IndexRestoration { index_uid, quota };
+        let task = self.task_store.register(content).await?;
+        self.scheduler.read().await.notify();
+        Ok(task)
+    }
+
+    pub fn drain_status(&self) -> DrainStatus {
+        *self.drain_status.borrow()
+    }
+
+    fn ensure_not_draining(&self) -> Result<()> {
+        match self.drain_status() {
+            DrainStatus::Running => Ok(()),
+            DrainStatus::Draining | DrainStatus::Drained => Err(IndexControllerError::Draining),
+        }
+    }
+
+    /// Gracefully drains the instance: new tasks are refused, the batch being processed is
+    /// finished, and the task store is flushed to disk. No other batch is processed afterward.
+    pub async fn drain(&self) -> Result<()> {
+        if self.drain_status.send_replace(DrainStatus::Draining) != DrainStatus::Running {
+            // A drain is already in progress.
+            return Ok(());
+        }
+
+        self.scheduler.write().await.drain();
+        while self.scheduler.read().await.is_processing() {
+            sleep(DRAIN_POLL_INTERVAL).await;
+        }
+
+        self.task_store.flush().await?;
+        self.drain_status.send_replace(DrainStatus::Drained);
+
+        Ok(())
+    }
+
+    /// Waits until the instance is drained.
+    pub async fn wait_drained(&self) {
+        let mut receiver = self.drain_status.subscribe();
+        while *receiver.borrow() != DrainStatus::Drained {
+            if receiver.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+
     pub async fn get_task(&self, id: TaskId, filter: Option<TaskFilter>) -> Result<Task> {
         let task = self.scheduler.read().await.get_task(id, filter).await?;
         Ok(task)
     }
 
+    /// Aborts the processing of the task `id`, along with the other tasks of its batch. Their
+    /// uncommitted writes are rolled back, and they are marked as failed once the processing of
+    /// the batch stops, or on the next start if the process stops first.
+    pub async fn abort_task(&self, id: TaskId) -> Result<Task> {
+        let scheduler = self.scheduler.read().await;
+        let task = scheduler.get_task(id, None).await?;
+        scheduler.abort(id).await?;
+        Ok(task)
+    }
+
     pub async fn get_index_task(&self, index_uid: String, task_id: TaskId) -> Result<Task> {
         let creation_task_id = self
             .index_resolver
@@ -486,6 +1429,23 @@ where
         Ok(tasks)
     }
 
+    /// Same as `list_tasks`, also counting all the tasks matching the filter.
+    pub async fn list_tasks_with_counts(
+        &self,
+        filter: Option<TaskFilter>,
+        limit: Option<usize>,
+        offset: Option<TaskId>,
+    ) -> Result<(Vec<Task>, TaskCounts)> {
+        let result = self
+            .scheduler
+            .read()
+            .await
+            .list_tasks_with_counts(offset, filter, limit)
+            .await?;
+
+        Ok(result)
+    }
+
     pub async fn list_index_task(
         &self,
         index_uid: String,
@@ -514,39 +1474,204 @@ where
         Ok(tasks)
     }
 
-    pub async fn list_indexes(&self) -> Result<Vec<IndexMetadata>> {
-        let indexes = self.index_resolver.list().await?;
+    /// Returns the number of indexes whose uid is accepted by `filter`, along with the metadata
+    /// of `limit` of them from `offset`, in the order of their uids. Only the indexes of the page
+    /// are opened.
+    pub async fn list_indexes(
+        &self,
+        filter: impl Fn(&str) -> bool,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(usize, Vec<IndexMetadata>)> {
+        let uids: Vec<_> = self
+            .index_resolver
+            .list_uids()
+            .await?
+            .into_iter()
+            .filter(|uid| filter(uid))
+            .collect();
+
         let mut ret = Vec::new();
-        for (uid, index) in indexes {
-            let meta = index.meta()?;
-            let meta = IndexMetadata {
-                uuid: index.uuid(),
-                uid,
-                meta,
-            };
-            ret.push(meta);
+        for uid in uids.iter().skip(offset).take(limit) {
+            match self.get_index(uid.clone()).await {
+                Ok(meta) => ret.push(meta),
+                // the index was deleted since its uid was listed
+                Err(IndexControllerError::IndexResolver(IndexResolverError::UnexistingIndex(
+                    _,
+                ))) => continue,
+                Err(e) => return Err(e),
+            }
         }
 
-        Ok(ret)
+        Ok((uids.len(), ret))
+    }
+
+    pub async fn list_aliases(&self) -> Result<Vec<IndexAlias>> {
+        let aliases = self.index_resolver.list_aliases().await?;
+        Ok(aliases
+            .into_iter()
+            .map(|(alias, index_uid)| IndexAlias { alias, index_uid })
+            .collect())
+    }
+
+    pub async fn get_alias(&self, alias: String) -> Result<IndexAlias> {
+        let index_uid = self.index_resolver.get_alias(alias.clone()).await?;
+        Ok(IndexAlias { alias, index_uid })
+    }
+
+    /// Creates the alias, or atomically retargets it to another index if it exists.
+    pub async fn set_alias(&self, alias: String, index_uid: String) -> Result<IndexAlias> {
+        IndexUid::from_str(&alias).map_err(IndexResolverError::from)?;
+        IndexUid::from_str(&index_uid).map_err(IndexResolverError::from)?;
+        self.index_resolver
+            .set_alias(alias.clone(), index_uid.clone())
+            .await?;
+        Ok(IndexAlias { alias, index_uid })
+    }
+
+    pub async fn delete_alias(&self, alias: String) -> Result<()> {
+        Ok(self.index_resolver.delete_alias(alias).await?)
+    }
+
+    pub async fn list_templates(&self) -> Result<Vec<IndexTemplate>> {
+        let templates = self.index_resolver.list_templates().await?;
+        Ok(templates
+            .into_iter()
+            .map(|(pattern, settings)| IndexTemplate { pattern, settings })
+            .collect())
+    }
+
+    pub async fn get_template(&self, pattern: String) -> Result<IndexTemplate> {
+        let settings = self.index_resolver.get_template(pattern.clone()).await?;
+        Ok(IndexTemplate { pattern, settings })
+    }
+
+    /// Creates or replaces the template, applied to the indexes created afterward, explicitly or
+    /// by a task, with a uid matching the pattern. When several templates match, the one with the
+    /// longest pattern is applied.
+    pub async fn set_template(
+        &self,
+        pattern: String,
+        settings: Settings<Unchecked>,
+    ) -> Result<IndexTemplate> {
+        self.index_resolver
+            .set_template(pattern.clone(), settings.clone())
+            .await?;
+        Ok(IndexTemplate { pattern, settings })
+    }
+
+    pub async fn delete_template(&self, pattern: String) -> Result<()> {
+        Ok(self.index_resolver.delete_template(pattern).await?)
+    }
+
+    /// Returns the index `uid`, or the index targeted by the alias `uid`. Only the documents,
+    /// search and settings routes accept an alias in place of an index uid.
+    async fn get_index_or_alias(&self, uid: String) -> Result<Index> {
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        Ok(self.index_resolver.get_index(uid).await?)
     }
 
     pub async fn settings(&self, uid: String) -> Result<Settings<Checked>> {
-        let index = self.index_resolver.get_index(uid).await?;
+        let index = self.get_index_or_alias(uid).await?;
         let settings = spawn_blocking(move || index.settings()).await??;
         Ok(settings)
     }
 
-    /// Return the total number of documents contained in the index + the selected documents.
+    /// Returns the last versions of the settings of the index, from the oldest to the newest.
+    pub async fn settings_history(&self, uid: String) -> Result<Vec<SettingsVersion>> {
+        let index = self.get_index_or_alias(uid).await?;
+        let history = spawn_blocking(move || index.settings_history()).await??;
+        Ok(history)
+    }
+
+    /// Compares the settings of the index with the ones they would be after the update of
+    /// `settings`, which isn't registered.
+    pub async fn settings_diff(
+        &self,
+        uid: String,
+        settings: Settings<Unchecked>,
+    ) -> Result<SettingsDiff> {
+        let index = self.get_index_or_alias(uid).await?;
+        let diff = spawn_blocking(move || index.settings_diff(settings.check())).await??;
+        Ok(diff)
+    }
+
+    /// Registers a settings update replacing the settings of the index with their version
+    /// `version`, which must still be in the history.
+    pub async fn register_settings_rollback(
+        &self,
+        uid: String,
+        version: u64,
+        idempotency_key: Option<String>,
+    ) -> Result<Task> {
+        let index = self.get_index_or_alias(uid.clone()).await?;
+        let settings = spawn_blocking(move || index.settings_version(version))
+            .await??
+            .ok_or(IndexError::SettingsVersionNotFound(version))?
+            .settings;
+        let update = Update::Settings {
+            settings,
+            is_deletion: false,
+            allow_index_creation: false,
+            quota: Quota::default(),
+        };
+        self.register_idempotent_update(uid, update, idempotency_key)
+            .await
+    }
+
+    /// Streams a page of the documents of the index as a JSON object, see
+    /// `Index::write_documents`. The errors preventing the page from being written, like an
+    /// invalid filter, are returned right away, the ones occurring later end the stream.
     pub async fn documents(
         &self,
         uid: String,
         offset: usize,
         limit: usize,
         attributes_to_retrieve: Option<Vec<String>>,
-    ) -> Result<(u64, Vec<Document>)> {
-        let index = self.index_resolver.get_index(uid).await?;
+        filter: Option<serde_json::Value>,
+        sort: Option<Vec<String>>,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let index = self.get_index_or_alias(uid).await?;
+        stream_written(move |writer| {
+            index.write_documents(
+                offset,
+                limit,
+                attributes_to_retrieve,
+                filter.as_ref(),
+                sort.as_deref(),
+                writer,
+            )
+        })
+        .await
+    }
+
+    /// Streams the documents of the index matching the filter, or every document, as NDJSON.
+    /// The errors preventing the export from starting, like an invalid filter, are returned
+    /// right away, the ones occurring later end the stream.
+    pub async fn export_documents(
+        &self,
+        uid: String,
+        attributes_to_retrieve: Option<Vec<String>>,
+        filter: Option<serde_json::Value>,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let index = self.get_index_or_alias(uid).await?;
+        stream_written(move |writer| {
+            index.export_documents(attributes_to_retrieve, filter.as_ref(), writer)
+        })
+        .await
+    }
+
+    /// Returns the documents with the given ids, in the order of the ids, along with the ids
+    /// matching no document.
+    pub async fn documents_by_ids(
+        &self,
+        uid: String,
+        ids: Vec<String>,
+        attributes_to_retrieve: Option<Vec<String>>,
+    ) -> Result<(Vec<Document>, Vec<String>)> {
+        let index = self.get_index_or_alias(uid).await?;
         let result =
-            spawn_blocking(move || index.retrieve_documents(offset, limit, attributes_to_retrieve))
+            spawn_blocking(move || index.retrieve_documents_by_ids(&ids, attributes_to_retrieve))
                 .await??;
         Ok(result)
     }
@@ -557,19 +1682,140 @@ where
         doc_id: String,
         attributes_to_retrieve: Option<Vec<String>>,
     ) -> Result<Document> {
-        let index = self.index_resolver.get_index(uid).await?;
+        let index = self.get_index_or_alias(uid).await?;
         let document =
             spawn_blocking(move || index.retrieve_document(doc_id, attributes_to_retrieve))
                 .await??;
         Ok(document)
     }
 
+    pub fn is_search_log_enabled(&self) -> bool {
+        self.search_log.is_some()
+    }
+
+    /// Records a search in the search log, if it's enabled.
+    pub fn record_search(&self, search: LoggedSearch) {
+        if let Some(ref search_log) = self.search_log {
+            search_log.record(search);
+        }
+    }
+
+    pub fn search_log(&self) -> Result<&SearchLog> {
+        self.search_log
+            .as_ref()
+            .ok_or(IndexControllerError::SearchLogDisabled)
+    }
+
     pub async fn search(&self, uid: String, query: SearchQuery) -> Result<SearchResult> {
-        let index = self.index_resolver.get_index(uid).await?;
+        // the results are cached under the index, whose tasks invalidate them
+        let uid = self.index_resolver.resolve_alias(uid).await?;
+        let cached = self.search_cache.as_ref().map(|cache| {
+            let key = cache_key(&query);
+            let generation = cache.generation(&uid);
+            (cache, key, generation)
+        });
+        if let Some((cache, key, _)) = &cached {
+            if let Some(result) = cache.get(&uid, key) {
+                return Ok(result);
+            }
+        }
+
+        let _permit = self.acquire_search_permit().await?;
+        let index = self.index_resolver.get_index(uid.clone()).await?;
+        let keyless = query.experiment_key.is_none();
         let result = spawn_blocking(move || index.perform_search(query)).await??;
+
+        // A degraded result depends on the load of the instance, it's not worth serving again.
+        // Neither is a result served by a variant of a ranking experiment picked at random, since
+        // the key of the search doesn't tell the variant: the key holds the experiment key of the
+        // search, which gets the same variant as long as the experiment doesn't change.
+        if let Some((cache, key, generation)) = cached {
+            let random_variant = keyless && result.ranking_variant.is_some();
+            if !result.degraded && !random_variant {
+                cache.insert(&uid, &key, generation, result.clone());
+            }
+        }
+
         Ok(result)
     }
 
+    pub async fn facet_search(
+        &self,
+        uid: String,
+        query: FacetSearchQuery,
+    ) -> Result<FacetSearchResult> {
+        let _permit = self.acquire_search_permit().await?;
+        let index = self.get_index_or_alias(uid).await?;
+        let result = spawn_blocking(move || index.perform_facet_search(query)).await??;
+        Ok(result)
+    }
+
+    pub async fn similar_documents(
+        &self,
+        uid: String,
+        query: SimilarQuery,
+    ) -> Result<SimilarResult> {
+        let _permit = self.acquire_search_permit().await?;
+        let index = self.get_index_or_alias(uid).await?;
+        let result = spawn_blocking(move || index.perform_similar(query)).await??;
+        Ok(result)
+    }
+
+    /// Suggests completions of a query from the words of the index and, when the search log is
+    /// enabled, from the queries searched on the index.
+    pub async fn suggest(&self, uid: String, query: SuggestQuery) -> Result<SuggestResult> {
+        let _permit = self.acquire_search_permit().await?;
+        let popular_queries = match self.search_log {
+            Some(ref search_log) => {
+                let filter = SearchLogFilter {
+                    index_uids: Some(vec![uid.clone()]),
+                    ..Default::default()
+                };
+                search_log.top_searches(&filter, usize::MAX)
+            }
+            None => Vec::new(),
+        };
+        let index = self.get_index_or_alias(uid).await?;
+        let result =
+            spawn_blocking(move || index.perform_suggest(query, popular_queries)).await??;
+        Ok(result)
+    }
+
+    /// Waits for a slot to execute a search when the concurrent searches are limited.
+    async fn acquire_search_permit(&self) -> Result<Option<SearchPermit>> {
+        match self.search_limiter {
+            Some(ref limiter) => match limiter.acquire().await {
+                Some(permit) => Ok(Some(permit)),
+                None => Err(IndexControllerError::TooManySearchRequests),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Runs the queries concurrently, and merges their hits into a single list ordered by their
+    /// weighted ranking score. The offset and limit of the queries are ignored, in favor of the
+    /// ones of the federation.
+    pub async fn federated_search(
+        &self,
+        queries: Vec<FederatedQuery>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<FederatedSearchResult> {
+        let searches = queries.into_iter().map(|federated| async move {
+            let mut query = federated.query.clone();
+            query.offset = None;
+            query.limit = offset.saturating_add(limit);
+            query.page = None;
+            query.hits_per_page = None;
+            query.ranking_score = true;
+            let result = self.search(federated.index_uid.clone(), query).await?;
+            Ok::<_, IndexControllerError>((federated, result))
+        });
+        let results = futures::future::try_join_all(searches).await?;
+
+        Ok(merge_federated_results(results, offset, limit))
+    }
+
     pub async fn get_index(&self, uid: String) -> Result<IndexMetadata> {
         let index = self.index_resolver.get_index(uid.clone()).await?;
         let uuid = index.uuid();
@@ -578,17 +1824,49 @@ where
         Ok(meta)
     }
 
-    pub async fn get_index_stats(&self, uid: String) -> Result<IndexStats> {
-        let processing_tasks = self.scheduler.read().await.get_processing_tasks().await?;
+    /// Returns the stats of the index, with its field distribution including the nested paths of
+    /// the fields holding objects, which requires reading every document.
+    pub async fn get_index_stats(&self, uid: String) -> Result<IndexDetailedStats> {
+        let scheduler = self.scheduler.read().await;
+        let processing_tasks = scheduler.get_processing_tasks().await?;
         // Check if the currently indexing update is from our index.
         let is_indexing = processing_tasks
             .first()
             .map_or(false, |task| task.index_uid().map_or(false, |u| u == uid));
 
+        let mut filter = TaskFilter::default();
+        filter.filter_index(uid.clone());
+        let (_, counts) = scheduler
+            .list_tasks_with_counts(None, Some(filter), Some(0))
+            .await?;
+        drop(scheduler);
+
         let index = self.index_resolver.get_index(uid).await?;
-        let mut stats = spawn_blocking(move || index.stats()).await??;
+        let max_size = index.map_size();
+        let (mut stats, meta) = spawn_blocking(move || -> Result<_> {
+            let mut stats = index.stats()?;
+            stats
+                .field_distribution
+                .extend(index.nested_field_distribution()?);
+            Ok((stats, index.meta()?))
+        })
+        .await??;
         stats.is_indexing = Some(is_indexing);
 
+        Ok(IndexDetailedStats {
+            database_size: stats.size,
+            max_size,
+            number_of_pending_tasks: counts.enqueued,
+            last_update: meta.updated_at,
+            stats,
+        })
+    }
+
+    /// Returns the number of documents of the index, and how many documents have each field and
+    /// how many distinct values it takes.
+    pub async fn document_stats(&self, uid: String) -> Result<DocumentStats> {
+        let index = self.get_index_or_alias(uid).await?;
+        let stats = spawn_blocking(move || index.document_stats()).await??;
         Ok(stats)
     }
 
@@ -654,7 +1932,7 @@ mod test {
     use crate::index::error::Result as IndexResult;
     use crate::index::Index;
     use crate::index::{
-        DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
+        HitsInfo, DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
     };
     use crate::index_resolver::index_store::MockIndexStore;
     use crate::index_resolver::meta_store::MockIndexMetaStore;
@@ -673,7 +1951,13 @@ mod test {
                 index_resolver,
                 task_store,
                 update_file_store,
+                upload_store: UploadStore::new(std::env::temp_dir()),
                 scheduler,
+                drain_status: Arc::new(watch::channel(DrainStatus::Running).0),
+                search_log: None,
+                search_limiter: None,
+                search_cache: None,
+                auto_index_creation: true,
             }
         }
     }
@@ -686,6 +1970,8 @@ mod test {
             q: Some(String::from("hello world")),
             offset: Some(10),
             limit: 0,
+            page: None,
+            hits_per_page: None,
             attributes_to_retrieve: Some(vec!["string".to_owned()].into_iter().collect()),
             attributes_to_crop: None,
             crop_length: 18,
@@ -694,19 +1980,41 @@ mod test {
             filter: None,
             sort: None,
             facets: None,
+            exhaustive_facet_count: false,
+            attributes_to_search_on: None,
+            attribute_weights: None,
+            typo_tolerance: None,
+            distinct: None,
+            boost_documents: None,
+            bury_documents: None,
+            matching_strategy: Default::default(),
+            vector: None,
+            hybrid: None,
             highlight_pre_tag: DEFAULT_HIGHLIGHT_PRE_TAG(),
             highlight_post_tag: DEFAULT_HIGHLIGHT_POST_TAG(),
             crop_marker: DEFAULT_CROP_MARKER(),
+            show_suggested_query: false,
+            debug: false,
+            experiment_key: None,
+            ranking_score: false,
         };
 
         let result = SearchResult {
             hits: vec![],
-            estimated_total_hits: 29,
             query: "hello world".to_string(),
-            limit: 24,
-            offset: 0,
             processing_time_ms: 50,
+            hits_info: HitsInfo::OffsetLimit {
+                limit: 24,
+                offset: 0,
+                estimated_total_hits: 29,
+            },
             facet_distribution: None,
+            facet_stats: None,
+            degraded: false,
+            ranking_variant: None,
+            suggested_query: None,
+            applied_rules: Vec::new(),
+            debug: None,
         };
 
         let mut uuid_store = MockIndexMetaStore::new();
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use meilisearch_types::error::{Code, ErrorCode};
+use meilisearch_types::error::{Code, ErrorCode, ErrorDetails};
 use meilisearch_types::index_uid::IndexUidFormatError;
 use meilisearch_types::internal_error;
 use tokio::sync::mpsc::error::SendError as MpscSendError;
@@ -19,6 +19,16 @@ pub enum IndexResolverError {
     IndexAlreadyExists(String),
     #[error("Index `{0}` not found.")]
     UnexistingIndex(String),
+    #[error("Index `{0}` not found in the deleted indexes.")]
+    UnexistingTrashedIndex(String),
+    #[error("Alias `{0}` not found.")]
+    UnexistingAlias(String),
+    #[error("An index is named `{0}`, an alias can't be named after an index.")]
+    AliasNamedAfterIndex(String),
+    #[error("Index template `{0}` not found.")]
+    UnexistingTemplate(String),
+    #[error("`{0}` is not a valid index template pattern. A pattern is made of alphanumeric characters, hyphens (-), underscores (_) and wildcards (*).")]
+    InvalidTemplatePattern(String),
     #[error("A primary key is already present. It's impossible to update it")]
     ExistingPrimaryKey,
     #[error("An internal error has occurred. `{0}`.")]
@@ -29,6 +39,16 @@ pub enum IndexResolverError {
     Milli(#[from] milli::Error),
     #[error("{0}")]
     BadlyFormatted(#[from] IndexUidFormatError),
+    #[error("The update file `{0}` is missing or corrupted.")]
+    UpdateFileCorrupted(Uuid),
+    #[error("Index `{0}` reached its maximum size of {1} bytes. The `maxSize` of the index can be raised with a `PATCH` request on the index.")]
+    IndexFull(String, usize),
+    #[error(
+        "Index `{0}` is frozen, its documents and settings can't be modified until it's unfrozen."
+    )]
+    IndexFrozen(String),
+    #[error("No more than {0} indexes can be created.")]
+    IndexQuotaExceeded(u64),
 }
 
 impl<T> From<MpscSendError<T>> for IndexResolverError
@@ -61,11 +81,28 @@ impl ErrorCode for IndexResolverError {
             IndexResolverError::IndexError(e) => e.error_code(),
             IndexResolverError::IndexAlreadyExists(_) => Code::IndexAlreadyExists,
             IndexResolverError::UnexistingIndex(_) => Code::IndexNotFound,
+            IndexResolverError::UnexistingTrashedIndex(_) => Code::IndexNotFound,
+            IndexResolverError::UnexistingAlias(_) => Code::AliasNotFound,
+            IndexResolverError::AliasNamedAfterIndex(_) => Code::InvalidAlias,
+            IndexResolverError::UnexistingTemplate(_) => Code::IndexTemplateNotFound,
+            IndexResolverError::InvalidTemplatePattern(_) => Code::InvalidIndexTemplate,
             IndexResolverError::ExistingPrimaryKey => Code::PrimaryKeyAlreadyPresent,
             IndexResolverError::Internal(_) => Code::Internal,
             IndexResolverError::UuidAlreadyExists(_) => Code::CreateIndex,
             IndexResolverError::Milli(e) => MilliError(e).error_code(),
             IndexResolverError::BadlyFormatted(_) => Code::InvalidIndexUid,
+            IndexResolverError::UpdateFileCorrupted(..) => Code::UpdateFileCorrupted,
+            IndexResolverError::IndexFull(..) => Code::IndexFull,
+            IndexResolverError::IndexFrozen(_) => Code::IndexFrozen,
+            IndexResolverError::IndexQuotaExceeded(_) => Code::IndexQuotaExceeded,
+        }
+    }
+
+    fn error_details(&self) -> Option<ErrorDetails> {
+        match self {
+            IndexResolverError::IndexError(e) => e.error_details(),
+            IndexResolverError::Milli(e) => MilliError(e).error_details(),
+            _ => None,
         }
     }
 }
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+use milli::heed::EnvClosingEvent;
 use milli::update::IndexerConfig;
+use parking_lot::Mutex;
 use tokio::fs;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex as AsyncMutex, RwLock};
 use tokio::task::spawn_blocking;
 use uuid::Uuid;
 
@@ -15,18 +18,43 @@ use crate::options::IndexerOpts;
 
 type AsyncMap<K, V> = Arc<RwLock<HashMap<K, V>>>;
 
+/// An open index, along with the last time it was used.
+struct OpenIndex {
+    index: Index,
+    last_use: AtomicU64,
+}
+
 #[async_trait::async_trait]
 #[cfg_attr(test, mockall::automock)]
 pub trait IndexStore {
     async fn create(&self, uuid: Uuid) -> Result<Index>;
     async fn get(&self, uuid: Uuid) -> Result<Option<Index>>;
+    /// Returns the index `uuid` if it's open, without opening it nor marking it as used.
+    async fn get_if_open(&self, uuid: Uuid) -> Option<Index>;
     async fn delete(&self, uuid: Uuid) -> Result<Option<Index>>;
+    /// Closes the index `uuid`, keeping its files. It's opened again by the next `get`.
+    async fn close(&self, uuid: Uuid) -> Result<()>;
+    /// Creates the index `uuid` as a copy of `index`.
+    async fn copy(&self, index: Index, uuid: Uuid) -> Result<Index>;
+    /// Reopens the index `uuid` with a map of `size` bytes. The index is closed first, the
+    /// caller must not hold an instance of it.
+    async fn resize(&self, uuid: Uuid, size: usize) -> Result<Index>;
 }
 
 pub struct MapIndexStore {
-    index_store: AsyncMap<Uuid, Index>,
+    index_store: AsyncMap<Uuid, OpenIndex>,
     path: PathBuf,
     index_size: usize,
+    /// The number of indexes kept open, the least recently used ones are closed past it.
+    max_open_indexes: Option<usize>,
+    /// Counts the uses of the indexes, to order them.
+    uses: AtomicU64,
+    /// The events signaled once the closed indexes are actually closed. A closed index can't be
+    /// opened again before.
+    closing: Mutex<HashMap<Uuid, Arc<EnvClosingEvent>>>,
+    /// The lock held while an index is opened, copied or resized, so that it's not opened twice.
+    /// These are done without holding the lock of all the open indexes.
+    opening: Mutex<HashMap<Uuid, Arc<AsyncMutex<()>>>>,
     indexer_config: Arc<IndexerConfig>,
 }
 
@@ -34,6 +62,7 @@ impl MapIndexStore {
     pub fn new(
         path: impl AsRef<Path>,
         index_size: usize,
+        max_open_indexes: Option<usize>,
         indexer_opts: &IndexerOpts,
     ) -> anyhow::Result<Self> {
         let indexer_config = Arc::new(IndexerConfig::try_from(indexer_opts)?);
@@ -43,9 +72,75 @@ impl MapIndexStore {
             index_store,
             path,
             index_size,
+            max_open_indexes,
+            uses: AtomicU64::new(0),
+            closing: Mutex::new(HashMap::new()),
+            opening: Mutex::new(HashMap::new()),
             indexer_config,
         })
     }
+
+    /// Returns the open index, marking it as the most recently used.
+    fn touch(&self, open: &OpenIndex) -> Index {
+        let last_use = self.uses.fetch_add(1, Ordering::Relaxed);
+        open.last_use.store(last_use, Ordering::Relaxed);
+        open.index.clone()
+    }
+
+    /// Returns the lock held while the index `uuid` is opened.
+    fn opening_lock(&self, uuid: Uuid) -> Arc<AsyncMutex<()>> {
+        self.opening.lock().entry(uuid).or_default().clone()
+    }
+
+    /// Closes the index once the searches and tasks still using it drop it.
+    fn close_index(&self, uuid: Uuid, index: Index) {
+        let closing = index.prepare_for_closing();
+        self.closing.lock().insert(uuid, Arc::new(closing));
+    }
+
+    /// Waits for the index `uuid` to be actually closed, if it's being closed, so it can be
+    /// opened again.
+    async fn wait_for_closing(&self, uuid: Uuid) -> Result<()> {
+        let closing = self.closing.lock().get(&uuid).cloned();
+        if let Some(closing) = closing {
+            let event = closing.clone();
+            spawn_blocking(move || event.wait()).await?;
+            let mut lock = self.closing.lock();
+            // the index may have been opened and closed again in the meantime
+            if lock
+                .get(&uuid)
+                .map_or(false, |event| Arc::ptr_eq(event, &closing))
+            {
+                lock.remove(&uuid);
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps the index open, and evicts the least recently used indexes past the maximum. The
+    /// indexes still used by a search or a task aren't evicted, since the next `get` would have
+    /// to wait for them to be dropped before opening them again: more indexes than the maximum
+    /// are then kept open until they're not used anymore.
+    fn insert(&self, open_indexes: &mut HashMap<Uuid, OpenIndex>, uuid: Uuid, index: Index) {
+        let last_use = AtomicU64::new(self.uses.fetch_add(1, Ordering::Relaxed));
+        open_indexes.insert(uuid, OpenIndex { index, last_use });
+
+        let max_open_indexes = match self.max_open_indexes {
+            Some(max) => max,
+            None => return,
+        };
+        while open_indexes.len() > max_open_indexes {
+            let least_recently_used = open_indexes
+                .iter()
+                .filter(|(open_uuid, open)| **open_uuid != uuid && !open.index.is_shared())
+                .min_by_key(|(_, open)| open.last_use.load(Ordering::Relaxed))
+                .map(|(open_uuid, _)| *open_uuid);
+            match least_recently_used.and_then(|evicted| open_indexes.remove_entry(&evicted)) {
+                Some((evicted, open)) => self.close_index(evicted, open.index),
+                None => break,
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -55,8 +150,8 @@ impl IndexStore for MapIndexStore {
         // ensure that another db is not created at the same time.
         let mut lock = self.index_store.write().await;
 
-        if let Some(index) = lock.get(&uuid) {
-            return Ok(index.clone());
+        if let Some(open) = lock.get(&uuid) {
+            return Ok(self.touch(open));
         }
         let path = self.path.join(format!("{}", uuid));
         if path.exists() {
@@ -71,15 +166,22 @@ impl IndexStore for MapIndexStore {
         })
         .await??;
 
-        lock.insert(uuid, index.clone());
+        self.insert(&mut lock, uuid, index.clone());
 
         Ok(index)
     }
 
     async fn get(&self, uuid: Uuid) -> Result<Option<Index>> {
+        if let Some(open) = self.index_store.read().await.get(&uuid) {
+            return Ok(Some(self.touch(open)));
+        }
+
+        let opening = self.opening_lock(uuid);
+        let _opening = opening.lock().await;
         let guard = self.index_store.read().await;
         match guard.get(&uuid) {
-            Some(index) => Ok(Some(index.clone())),
+            // the index was opened while waiting for the lock
+            Some(open) => Ok(Some(self.touch(open))),
             None => {
                 // drop the guard here so we can perform the write after without deadlocking;
                 drop(guard);
@@ -88,21 +190,143 @@ impl IndexStore for MapIndexStore {
                     return Ok(None);
                 }
 
+                self.wait_for_closing(uuid).await?;
                 let index_size = self.index_size;
                 let update_handler = self.indexer_config.clone();
                 let index =
                     spawn_blocking(move || Index::open(path, index_size, uuid, update_handler))
                         .await??;
-                self.index_store.write().await.insert(uuid, index.clone());
+                let mut lock = self.index_store.write().await;
+                self.insert(&mut lock, uuid, index.clone());
                 Ok(Some(index))
             }
         }
     }
 
+    async fn get_if_open(&self, uuid: Uuid) -> Option<Index> {
+        let guard = self.index_store.read().await;
+        guard.get(&uuid).map(|open| open.index.clone())
+    }
+
     async fn delete(&self, uuid: Uuid) -> Result<Option<Index>> {
         let db_path = self.path.join(format!("{}", uuid));
         fs::remove_dir_all(db_path).await?;
+        self.closing.lock().remove(&uuid);
+        self.opening.lock().remove(&uuid);
         let index = self.index_store.write().await.remove(&uuid);
+        Ok(index.map(|open| open.index))
+    }
+
+    async fn close(&self, uuid: Uuid) -> Result<()> {
+        if let Some(open) = self.index_store.write().await.remove(&uuid) {
+            self.close_index(uuid, open.index);
+        }
+        Ok(())
+    }
+
+    async fn copy(&self, index: Index, uuid: Uuid) -> Result<Index> {
+        // The copy is made without holding the lock of all the open indexes, which would block
+        // every search and task on the other indexes until it's done.
+        let opening = self.opening_lock(uuid);
+        let _opening = opening.lock().await;
+
+        let path = self.path.join(format!("{}", uuid));
+        if self.index_store.read().await.contains_key(&uuid) || path.exists() {
+            return Err(IndexResolverError::UuidAlreadyExists(uuid));
+        }
+
+        let index_size = self.index_size;
+        let update_handler = self.indexer_config.clone();
+        let copy = spawn_blocking(move || -> Result<Index> {
+            index.copy_to(&path)?;
+            let copy = Index::open(path, index_size, uuid, update_handler)?;
+            Ok(copy)
+        })
+        .await??;
+
+        let mut lock = self.index_store.write().await;
+        self.insert(&mut lock, uuid, copy.clone());
+
+        Ok(copy)
+    }
+
+    async fn resize(&self, uuid: Uuid, size: usize) -> Result<Index> {
+        // The lock of the index is held until it's reopened, so that it's not opened again with
+        // its previous size in the meantime. The other indexes are still used while it's closed.
+        let opening = self.opening_lock(uuid);
+        let _opening = opening.lock().await;
+
+        let path = self.path.join(format!("{}", uuid));
+        if !path.exists() {
+            return Err(IndexResolverError::Internal(
+                format!("Index `{}` not found.", uuid).into(),
+            ));
+        }
+
+        let index = self
+            .index_store
+            .write()
+            .await
+            .remove(&uuid)
+            .map(|open| open.index);
+        self.wait_for_closing(uuid).await?;
+        let index_size = self.index_size;
+        let update_handler = self.indexer_config.clone();
+        let index = spawn_blocking(move || -> Result<Index> {
+            if let Some(index) = index {
+                index.close_and_wait();
+            }
+            Index::write_map_size(&path, size)?;
+            let index = Index::open(path, index_size, uuid, update_handler)?;
+            Ok(index)
+        })
+        .await??;
+
+        let mut lock = self.index_store.write().await;
+        self.insert(&mut lock, uuid, index.clone());
+
         Ok(index)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::time::timeout;
+
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_used_index_not_evicted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store =
+            MapIndexStore::new(tmp.path(), 4096 * 100_000, Some(1), &IndexerOpts::default())
+                .unwrap();
+
+        let evicted = Uuid::new_v4();
+        // the instance used by a search for example
+        let used = store.create(evicted).await.unwrap();
+        store.create(Uuid::new_v4()).await.unwrap();
+
+        // the index is kept open while it's used
+        assert!(store.closing.lock().is_empty());
+        assert_eq!(store.index_store.read().await.len(), 2);
+        let index = timeout(Duration::from_secs(5), store.get(evicted))
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(index.stats().is_ok());
+
+        // and evicted by the next index opened once it's not used anymore
+        drop(used);
+        drop(index);
+        store.create(Uuid::new_v4()).await.unwrap();
+        let closing = store.closing.lock().get(&evicted).cloned().unwrap();
+        assert!(closing.wait_timeout(Duration::from_secs(5)));
+        let index = store.get(evicted).await.unwrap().unwrap();
+        assert!(index.stats().is_ok());
+        assert!(store.closing.lock().is_empty());
+    }
+}
@@ -4,12 +4,14 @@ use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use milli::heed::types::{SerdeBincode, Str};
+use milli::heed::types::{SerdeBincode, SerdeJson, Str};
 use milli::heed::{CompactionOption, Database, Env};
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use super::error::{IndexResolverError, Result};
+use crate::index::{Settings, Unchecked};
 use crate::tasks::task::TaskId;
 use crate::EnvSizer;
 
@@ -19,7 +21,21 @@ pub struct DumpEntry {
     pub index_meta: IndexMeta,
 }
 
+#[derive(Serialize, Deserialize)]
+struct AliasDumpEntry {
+    alias: String,
+    index_uid: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TemplateDumpEntry {
+    pattern: String,
+    settings: Settings<Unchecked>,
+}
+
 const UUIDS_DB_PATH: &str = "index_uuids";
+const ALIASES_DUMP_FILE: &str = "aliases.jsonl";
+const TEMPLATES_DUMP_FILE: &str = "templates.jsonl";
 
 #[async_trait::async_trait]
 #[cfg_attr(test, mockall::automock)]
@@ -30,6 +46,24 @@ pub trait IndexMetaStore: Sized {
     async fn delete(&self, uid: String) -> Result<Option<IndexMeta>>;
     async fn list(&self) -> Result<Vec<(String, IndexMeta)>>;
     async fn insert(&self, name: String, meta: IndexMeta) -> Result<()>;
+    async fn swap(&self, swaps: Vec<(String, String)>) -> Result<()>;
+    /// Points the index `uid` to the environment `uuid`, returning the previous one.
+    async fn update_uuid(&self, uid: String, uuid: Uuid) -> Result<Uuid>;
+    async fn get_alias(&self, alias: String) -> Result<Option<String>>;
+    async fn list_aliases(&self) -> Result<Vec<(String, String)>>;
+    async fn set_alias(&self, alias: String, index_uid: String) -> Result<()>;
+    async fn delete_alias(&self, alias: String) -> Result<Option<String>>;
+    async fn get_template(&self, pattern: String) -> Result<Option<Settings<Unchecked>>>;
+    async fn list_templates(&self) -> Result<Vec<(String, Settings<Unchecked>)>>;
+    async fn set_template(&self, pattern: String, settings: Settings<Unchecked>) -> Result<()>;
+    async fn delete_template(&self, pattern: String) -> Result<Option<Settings<Unchecked>>>;
+    /// Moves the index `uid` to the trash. Returns its meta, and the uuid of the index trashed
+    /// before under the same uid, which is replaced.
+    async fn trash(&self, uid: String) -> Result<Option<(IndexMeta, Option<Uuid>)>>;
+    /// Moves the index `uid` back from the trash, returning its meta.
+    async fn restore(&self, uid: String) -> Result<Option<IndexMeta>>;
+    /// Removes the indexes trashed before `before` from the trash, returning their uuids.
+    async fn purge_trash(&self, before: OffsetDateTime) -> Result<Vec<Uuid>>;
     async fn snapshot(&self, path: PathBuf) -> Result<HashSet<Uuid>>;
     async fn get_size(&self) -> Result<u64>;
     async fn dump(&self, path: PathBuf) -> Result<()>;
@@ -41,10 +75,25 @@ pub struct IndexMeta {
     pub creation_task_id: TaskId,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashedIndex {
+    pub uuid: Uuid,
+    pub creation_task_id: TaskId,
+    #[serde(with = "time::serde::rfc3339")]
+    pub deleted_at: OffsetDateTime,
+}
+
 #[derive(Clone)]
 pub struct HeedMetaStore {
     env: Arc<Env>,
     db: Database<Str, SerdeBincode<IndexMeta>>,
+    /// Maps the aliases to the uid of the index they target.
+    aliases: Database<Str, Str>,
+    /// Maps the patterns of the templates to the settings of the indexes created with a uid
+    /// matching them.
+    templates: Database<Str, SerdeJson<Settings<Unchecked>>>,
+    /// The deleted indexes, which can be restored until they're purged.
+    trash: Database<Str, SerdeJson<TrashedIndex>>,
 }
 
 impl Drop for HeedMetaStore {
@@ -58,17 +107,23 @@ impl Drop for HeedMetaStore {
 impl HeedMetaStore {
     pub fn new(env: Arc<milli::heed::Env>) -> Result<Self> {
         let db = env.create_database(Some("uuids"))?;
-        Ok(Self { env, db })
+        let aliases = env.create_database(Some("aliases"))?;
+        let templates = env.create_database(Some("templates"))?;
+        let trash = env.create_database(Some("trash"))?;
+        Ok(Self {
+            env,
+            db,
+            aliases,
+            templates,
+            trash,
+        })
     }
 
     fn get(&self, name: &str) -> Result<Option<IndexMeta>> {
         let env = self.env.clone();
         let db = self.db;
         let txn = env.read_txn()?;
-        match db.get(&txn, name)? {
-            Some(meta) => Ok(Some(meta)),
-            None => Ok(None),
-        }
+        Ok(db.get(&txn, name)?)
     }
 
     fn delete(&self, uid: String) -> Result<Option<IndexMeta>> {
@@ -102,7 +157,7 @@ impl HeedMetaStore {
         let db = self.db;
         let mut txn = env.write_txn()?;
 
-        if db.get(&txn, &name)?.is_some() {
+        if db.get(&txn, &name)?.is_some() || self.aliases.get(&txn, &name)?.is_some() {
             return Err(IndexResolverError::IndexAlreadyExists(name));
         }
 
@@ -111,6 +166,178 @@ impl HeedMetaStore {
         Ok(())
     }
 
+    /// Swaps the indexes of each pair of uids in a single transaction, so either all the pairs
+    /// or none of them are swapped.
+    fn swap(&self, swaps: Vec<(String, String)>) -> Result<()> {
+        let env = self.env.clone();
+        let db = self.db;
+        let mut txn = env.write_txn()?;
+
+        for (lhs, rhs) in swaps {
+            let lhs_meta = db
+                .get(&txn, &lhs)?
+                .ok_or_else(|| IndexResolverError::UnexistingIndex(lhs.clone()))?;
+            let rhs_meta = db
+                .get(&txn, &rhs)?
+                .ok_or_else(|| IndexResolverError::UnexistingIndex(rhs.clone()))?;
+            db.put(&mut txn, &lhs, &rhs_meta)?;
+            db.put(&mut txn, &rhs, &lhs_meta)?;
+        }
+
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn update_uuid(&self, uid: String, uuid: Uuid) -> Result<Uuid> {
+        let env = self.env.clone();
+        let db = self.db;
+        let mut txn = env.write_txn()?;
+
+        let mut meta = db
+            .get(&txn, &uid)?
+            .ok_or_else(|| IndexResolverError::UnexistingIndex(uid.clone()))?;
+        let previous_uuid = std::mem::replace(&mut meta.uuid, uuid);
+        db.put(&mut txn, &uid, &meta)?;
+
+        txn.commit()?;
+        Ok(previous_uuid)
+    }
+
+    fn get_alias(&self, alias: &str) -> Result<Option<String>> {
+        let txn = self.env.read_txn()?;
+        Ok(self.aliases.get(&txn, alias)?.map(String::from))
+    }
+
+    fn list_aliases(&self) -> Result<Vec<(String, String)>> {
+        let txn = self.env.read_txn()?;
+        let mut aliases = Vec::new();
+        for entry in self.aliases.iter(&txn)? {
+            let (alias, index_uid) = entry?;
+            aliases.push((alias.to_string(), index_uid.to_string()));
+        }
+        Ok(aliases)
+    }
+
+    /// Creates the alias, or retargets it if it exists. An alias can't be named after an index,
+    /// and must target an index rather than another alias.
+    fn set_alias(&self, alias: String, index_uid: String) -> Result<()> {
+        let mut txn = self.env.write_txn()?;
+
+        if self.db.get(&txn, &alias)?.is_some() {
+            return Err(IndexResolverError::AliasNamedAfterIndex(alias));
+        }
+        if self.db.get(&txn, &index_uid)?.is_none() {
+            return Err(IndexResolverError::UnexistingIndex(index_uid));
+        }
+
+        self.aliases.put(&mut txn, &alias, &index_uid)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn delete_alias(&self, alias: String) -> Result<Option<String>> {
+        let mut txn = self.env.write_txn()?;
+        let index_uid = self.aliases.get(&txn, &alias)?.map(String::from);
+        if index_uid.is_some() {
+            self.aliases.delete(&mut txn, &alias)?;
+            txn.commit()?;
+        }
+        Ok(index_uid)
+    }
+
+    fn get_template(&self, pattern: &str) -> Result<Option<Settings<Unchecked>>> {
+        let txn = self.env.read_txn()?;
+        Ok(self.templates.get(&txn, pattern)?)
+    }
+
+    fn list_templates(&self) -> Result<Vec<(String, Settings<Unchecked>)>> {
+        let txn = self.env.read_txn()?;
+        let mut templates = Vec::new();
+        for entry in self.templates.iter(&txn)? {
+            let (pattern, settings) = entry?;
+            templates.push((pattern.to_string(), settings));
+        }
+        Ok(templates)
+    }
+
+    fn set_template(&self, pattern: String, settings: Settings<Unchecked>) -> Result<()> {
+        let mut txn = self.env.write_txn()?;
+        self.templates.put(&mut txn, &pattern, &settings)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn delete_template(&self, pattern: String) -> Result<Option<Settings<Unchecked>>> {
+        let mut txn = self.env.write_txn()?;
+        let settings = self.templates.get(&txn, &pattern)?;
+        if settings.is_some() {
+            self.templates.delete(&mut txn, &pattern)?;
+            txn.commit()?;
+        }
+        Ok(settings)
+    }
+
+    fn trash(&self, uid: String) -> Result<Option<(IndexMeta, Option<Uuid>)>> {
+        let mut txn = self.env.write_txn()?;
+        let meta = match self.db.get(&txn, &uid)? {
+            Some(meta) => meta,
+            None => return Ok(None),
+        };
+        let previous = self.trash.get(&txn, &uid)?.map(|trashed| trashed.uuid);
+
+        let trashed = TrashedIndex {
+            uuid: meta.uuid,
+            creation_task_id: meta.creation_task_id,
+            deleted_at: OffsetDateTime::now_utc(),
+        };
+        self.db.delete(&mut txn, &uid)?;
+        self.trash.put(&mut txn, &uid, &trashed)?;
+        txn.commit()?;
+
+        Ok(Some((meta, previous)))
+    }
+
+    /// Moves the index `uid` back from the trash, unless an index or an alias was named `uid`
+    /// since it was deleted.
+    fn restore(&self, uid: String) -> Result<Option<IndexMeta>> {
+        let mut txn = self.env.write_txn()?;
+        let trashed = match self.trash.get(&txn, &uid)? {
+            Some(trashed) => trashed,
+            None => return Ok(None),
+        };
+        if self.db.get(&txn, &uid)?.is_some() || self.aliases.get(&txn, &uid)?.is_some() {
+            return Err(IndexResolverError::IndexAlreadyExists(uid));
+        }
+
+        let meta = IndexMeta {
+            uuid: trashed.uuid,
+            creation_task_id: trashed.creation_task_id,
+        };
+        self.trash.delete(&mut txn, &uid)?;
+        self.db.put(&mut txn, &uid, &meta)?;
+        txn.commit()?;
+
+        Ok(Some(meta))
+    }
+
+    fn purge_trash(&self, before: OffsetDateTime) -> Result<Vec<Uuid>> {
+        let mut txn = self.env.write_txn()?;
+        let mut purged = Vec::new();
+        for entry in self.trash.iter(&txn)? {
+            let (uid, trashed) = entry?;
+            if trashed.deleted_at < before {
+                purged.push((uid.to_string(), trashed.uuid));
+            }
+        }
+
+        for (uid, _) in &purged {
+            self.trash.delete(&mut txn, uid)?;
+        }
+        txn.commit()?;
+
+        Ok(purged.into_iter().map(|(_, uuid)| uuid).collect())
+    }
+
     fn snapshot(&self, mut path: PathBuf) -> Result<HashSet<Uuid>> {
         // Write transaction to acquire a lock on the database.
         let txn = self.env.write_txn()?;
@@ -119,6 +346,11 @@ impl HeedMetaStore {
             let (_, IndexMeta { uuid, .. }) = entry?;
             entries.insert(uuid);
         }
+        // the trashed indexes can still be restored from the snapshot
+        for entry in self.trash.iter(&txn)? {
+            let (_, TrashedIndex { uuid, .. }) = entry?;
+            entries.insert(uuid);
+        }
 
         // only perform snapshot if there are indexes
         if !entries.is_empty() {
@@ -150,6 +382,28 @@ impl HeedMetaStore {
             dump_file.write_all(b"\n").unwrap();
         }
 
+        let mut aliases_file = File::create(dump_path.join(ALIASES_DUMP_FILE))?;
+        for entry in self.aliases.iter(&txn)? {
+            let (alias, index_uid) = entry?;
+            let entry = AliasDumpEntry {
+                alias: alias.to_string(),
+                index_uid: index_uid.to_string(),
+            };
+            serde_json::to_writer(&mut aliases_file, &entry)?;
+            aliases_file.write_all(b"\n")?;
+        }
+
+        let mut templates_file = File::create(dump_path.join(TEMPLATES_DUMP_FILE))?;
+        for entry in self.templates.iter(&txn)? {
+            let (pattern, settings) = entry?;
+            let entry = TemplateDumpEntry {
+                pattern: pattern.to_string(),
+                settings,
+            };
+            serde_json::to_writer(&mut templates_file, &entry)?;
+            templates_file.write_all(b"\n")?;
+        }
+
         Ok(())
     }
 
@@ -174,6 +428,23 @@ impl HeedMetaStore {
 
             line.clear();
         }
+
+        // the dumps created before the aliases don't have any
+        let src_aliases = src.as_ref().join(UUIDS_DB_PATH).join(ALIASES_DUMP_FILE);
+        if src_aliases.exists() {
+            for line in BufReader::new(File::open(&src_aliases)?).lines() {
+                let AliasDumpEntry { alias, index_uid } = serde_json::from_str(&line?)?;
+                db.aliases.put(&mut txn, &alias, &index_uid)?;
+            }
+        }
+
+        let src_templates = src.as_ref().join(UUIDS_DB_PATH).join(TEMPLATES_DUMP_FILE);
+        if src_templates.exists() {
+            for line in BufReader::new(File::open(&src_templates)?).lines() {
+                let TemplateDumpEntry { pattern, settings } = serde_json::from_str(&line?)?;
+                db.templates.put(&mut txn, &pattern, &settings)?;
+            }
+        }
         txn.commit()?;
 
         Ok(())
@@ -202,6 +473,71 @@ impl IndexMetaStore for HeedMetaStore {
         tokio::task::spawn_blocking(move || this.insert(name, meta)).await?
     }
 
+    async fn swap(&self, swaps: Vec<(String, String)>) -> Result<()> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.swap(swaps)).await?
+    }
+
+    async fn update_uuid(&self, uid: String, uuid: Uuid) -> Result<Uuid> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.update_uuid(uid, uuid)).await?
+    }
+
+    async fn get_alias(&self, alias: String) -> Result<Option<String>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_alias(&alias)).await?
+    }
+
+    async fn list_aliases(&self) -> Result<Vec<(String, String)>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.list_aliases()).await?
+    }
+
+    async fn set_alias(&self, alias: String, index_uid: String) -> Result<()> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.set_alias(alias, index_uid)).await?
+    }
+
+    async fn delete_alias(&self, alias: String) -> Result<Option<String>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.delete_alias(alias)).await?
+    }
+
+    async fn get_template(&self, pattern: String) -> Result<Option<Settings<Unchecked>>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_template(&pattern)).await?
+    }
+
+    async fn list_templates(&self) -> Result<Vec<(String, Settings<Unchecked>)>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.list_templates()).await?
+    }
+
+    async fn set_template(&self, pattern: String, settings: Settings<Unchecked>) -> Result<()> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.set_template(pattern, settings)).await?
+    }
+
+    async fn delete_template(&self, pattern: String) -> Result<Option<Settings<Unchecked>>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.delete_template(pattern)).await?
+    }
+
+    async fn trash(&self, uid: String) -> Result<Option<(IndexMeta, Option<Uuid>)>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.trash(uid)).await?
+    }
+
+    async fn restore(&self, uid: String) -> Result<Option<IndexMeta>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.restore(uid)).await?
+    }
+
+    async fn purge_trash(&self, before: OffsetDateTime) -> Result<Vec<Uuid>> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.purge_trash(before)).await?
+    }
+
     async fn snapshot(&self, path: PathBuf) -> Result<HashSet<Uuid>> {
         let this = self.clone();
         tokio::task::spawn_blocking(move || this.snapshot(path)).await?
@@ -1,14 +1,17 @@
 pub mod error;
 pub mod index_store;
 pub mod meta_store;
+pub mod template;
 
 use std::convert::TryFrom;
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 use error::{IndexResolverError, Result};
 use index_store::{IndexStore, MapIndexStore};
-use meilisearch_types::error::ResponseError;
+use meilisearch_auth::Quota;
+use meilisearch_types::error::{Code, ErrorCode, ResponseError};
 use meilisearch_types::index_uid::IndexUid;
 use meta_store::{HeedMetaStore, IndexMetaStore};
 use milli::heed::Env;
@@ -17,7 +20,10 @@ use time::OffsetDateTime;
 use tokio::task::spawn_blocking;
 use uuid::Uuid;
 
-use crate::index::{error::Result as IndexResult, Index};
+use crate::index::{
+    error::Result as IndexResult, DocumentEditionResult, DocumentPayload, Index, Settings,
+    Unchecked,
+};
 use crate::options::IndexerOpts;
 use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskResult};
 use crate::update_file_store::UpdateFileStore;
@@ -32,15 +38,42 @@ pub use real::IndexResolver;
 #[cfg(test)]
 pub use test::MockIndexResolver as IndexResolver;
 
+/// Replaces the error of a write that didn't fit in the map of the index `uid` with an error
+/// telling the size of the map.
+fn index_full_error(uid: &str, map_size: usize, error: IndexResolverError) -> IndexResolverError {
+    match error.error_code() {
+        Code::DatabaseSizeLimitReached => IndexResolverError::IndexFull(uid.to_string(), map_size),
+        _ => error,
+    }
+}
+
+/// Returns the strictest of two limits, a missing limit being no limit.
+fn strictest_limit(lhs: Option<u64>, rhs: Option<u64>) -> Option<u64> {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Some(lhs.min(rhs)),
+        (lhs, rhs) => lhs.or(rhs),
+    }
+}
+
+/// Returns the index `uid`, unless it's frozen and can't be modified. Its writes are rolled back
+/// once `aborted` is set.
+fn writable(uid: &str, index: Index, aborted: &Arc<AtomicBool>) -> Result<Index> {
+    if index.is_frozen()? {
+        return Err(IndexResolverError::IndexFrozen(uid.to_string()));
+    }
+    Ok(index.abortable(aborted.clone()))
+}
+
 pub fn create_index_resolver(
     path: impl AsRef<Path>,
     index_size: usize,
+    max_open_indexes: Option<usize>,
     indexer_opts: &IndexerOpts,
     meta_env: Arc<milli::heed::Env>,
     file_store: UpdateFileStore,
 ) -> anyhow::Result<HardStateIndexResolver> {
     let uuid_store = HeedMetaStore::new(meta_env)?;
-    let index_store = MapIndexStore::new(&path, index_size, indexer_opts)?;
+    let index_store = MapIndexStore::new(&path, index_size, max_open_indexes, indexer_opts)?;
     Ok(IndexResolver::new(uuid_store, index_store, file_store))
 }
 
@@ -51,6 +84,10 @@ mod real {
         pub(super) index_uuid_store: U,
         pub(super) index_store: I,
         pub(super) file_store: UpdateFileStore,
+        /// Whether the deleted indexes are moved to the trash, rather than deleted for good.
+        pub(super) trash_deleted_indexes: bool,
+        /// The limits of the instance, applied to every task.
+        pub(super) quota: Quota,
     }
 
     impl IndexResolver<HeedMetaStore, MapIndexStore> {
@@ -83,21 +120,74 @@ mod real {
                 index_uuid_store,
                 index_store,
                 file_store,
+                trash_deleted_indexes: false,
+                quota: Quota::default(),
             }
         }
 
-        pub async fn process_document_addition_batch(&self, tasks: &mut [Task]) {
-            fn get_content_uuid(task: &Task) -> Uuid {
+        pub fn set_trash_deleted_indexes(&mut self, trash_deleted_indexes: bool) {
+            self.trash_deleted_indexes = trash_deleted_indexes;
+        }
+
+        pub fn set_quota(&mut self, quota: Quota) {
+            self.quota = quota;
+        }
+
+        pub async fn process_document_addition_batch(
+            &self,
+            tasks: &mut [Task],
+            aborted: &Arc<AtomicBool>,
+        ) {
+            fn get_payload(task: &Task) -> DocumentPayload {
                 match task {
                     Task {
-                        content: TaskContent::DocumentAddition { content_uuid, .. },
+                        content:
+                            TaskContent::DocumentAddition {
+                                content_uuid,
+                                auto_generate_primary_key,
+                                merge_nested,
+                                on_duplicate,
+                                ..
+                            },
                         ..
-                    } => *content_uuid,
+                    } => DocumentPayload {
+                        content_uuid: *content_uuid,
+                        auto_generate_primary_key: *auto_generate_primary_key,
+                        merge_nested: *merge_nested,
+                        on_duplicate: *on_duplicate,
+                    },
                     _ => panic!("unexpected task in the document addition batch"),
                 }
             }
 
-            let content_uuids = tasks.iter().map(get_content_uuid).collect::<Vec<_>>();
+            // A missing or corrupted update file must not take the rest of the batch down with it,
+            // so the faulty tasks are failed right away and left out of the indexing.
+            let now = OffsetDateTime::now_utc();
+            let mut contents = Vec::with_capacity(tasks.len());
+            for task in tasks.iter_mut() {
+                let payload = get_payload(task);
+                let content_uuid = payload.content_uuid;
+                match self.file_store.check_update(content_uuid) {
+                    Ok(()) => contents.push(payload),
+                    Err(e) => {
+                        log::error!("Error while reading update file {}: {}", content_uuid, e);
+                        let error = IndexResolverError::UpdateFileCorrupted(content_uuid);
+                        task.events.push(TaskEvent::Failed {
+                            error: error.into(),
+                            timestamp: now,
+                        });
+                    }
+                }
+            }
+
+            if contents.is_empty() {
+                return;
+            }
+
+            let mut tasks = tasks
+                .iter_mut()
+                .filter(|task| !task.is_finished())
+                .collect::<Vec<_>>();
 
             match tasks.first() {
                 Some(Task {
@@ -107,6 +197,7 @@ mod real {
                             merge_strategy,
                             primary_key,
                             allow_index_creation,
+                            quota,
                             index_uid,
                             ..
                         },
@@ -116,10 +207,13 @@ mod real {
                     let method = *merge_strategy;
 
                     let index = if *allow_index_creation {
-                        self.get_or_create_index(index_uid.clone(), *id).await
+                        self.get_or_create_index(index_uid.clone(), *id, quota)
+                            .await
                     } else {
                         self.get_index(index_uid.as_str().to_string()).await
                     };
+                    let index =
+                        index.and_then(|index| writable(index_uid.as_str(), index, aborted));
 
                     // If the index doesn't exist and we are not allowed to create it with the first
                     // task, we must fails the whole batch.
@@ -139,48 +233,85 @@ mod real {
                         }
                     };
 
+                    let map_size = index.map_size();
+                    // the tasks of the batch are indexed together, so the batch is limited by the
+                    // strictest limit of the keys registering them
+                    let max_documents = tasks.iter().fold(
+                        self.quota.max_documents_per_index,
+                        |max_documents, task| match &task.content {
+                            TaskContent::DocumentAddition { quota, .. } => {
+                                strictest_limit(max_documents, quota.max_documents_per_index)
+                            }
+                            _ => max_documents,
+                        },
+                    );
                     let file_store = self.file_store.clone();
                     let result = spawn_blocking(move || {
                         index.update_documents(
                             method,
                             primary_key,
+                            max_documents,
                             file_store,
-                            content_uuids.into_iter(),
+                            contents.into_iter(),
                         )
                     })
                     .await;
 
-                    let event = match result {
-                        Ok(Ok(result)) => TaskEvent::Succeeded {
-                            timestamp: OffsetDateTime::now_utc(),
-                            result: TaskResult::DocumentAddition {
-                                indexed_documents: result.indexed_documents,
-                            },
-                        },
-                        Ok(Err(e)) => TaskEvent::Failed {
-                            timestamp: OffsetDateTime::now_utc(),
-                            error: e.into(),
-                        },
-                        Err(e) => TaskEvent::Failed {
-                            timestamp: OffsetDateTime::now_utc(),
-                            error: IndexResolverError::from(e).into(),
-                        },
-                    };
-
-                    for task in tasks.iter_mut() {
-                        task.events.push(event.clone());
+                    let now = OffsetDateTime::now_utc();
+                    match result {
+                        // The tasks of the batch are in the order of their payloads.
+                        Ok(Ok(outcome)) => {
+                            for (task, failed_documents) in
+                                tasks.iter_mut().zip(outcome.failed_documents)
+                            {
+                                task.events.push(TaskEvent::Succeeded {
+                                    timestamp: now,
+                                    result: TaskResult::DocumentAddition {
+                                        indexed_documents: outcome.indexed_documents,
+                                        failed_documents,
+                                    },
+                                });
+                            }
+                        }
+                        Ok(Err(e)) => {
+                            let error = index_full_error(index_uid.as_str(), map_size, e.into());
+                            let error = ResponseError::from(error);
+                            for task in tasks.iter_mut() {
+                                task.events.push(TaskEvent::Failed {
+                                    timestamp: now,
+                                    error: error.clone(),
+                                });
+                            }
+                        }
+                        Err(e) => {
+                            let error = ResponseError::from(IndexResolverError::from(e));
+                            for task in tasks.iter_mut() {
+                                task.events.push(TaskEvent::Failed {
+                                    timestamp: now,
+                                    error: error.clone(),
+                                });
+                            }
+                        }
                     }
                 }
                 _ => panic!("invalid batch!"),
             }
         }
 
+        /// Deletes the update file of a processed task, unless the update files are retained
+        /// after their task is processed.
         pub async fn delete_content_file(&self, content_uuid: Uuid) -> Result<()> {
-            self.file_store.delete(content_uuid).await?;
+            if !self.file_store.retains_processed() {
+                self.file_store.delete(content_uuid).await?;
+            }
             Ok(())
         }
 
-        async fn process_task_inner(&self, task: &Task) -> Result<TaskResult> {
+        async fn process_task_inner(
+            &self,
+            task: &Task,
+            aborted: &Arc<AtomicBool>,
+        ) -> Result<TaskResult> {
             match &task.content {
                 TaskContent::DocumentAddition { .. } => {
                     panic!("updates should be handled by batch")
@@ -190,7 +321,9 @@ mod real {
                     index_uid,
                 } => {
                     let ids = ids.clone();
-                    let index = self.get_index(index_uid.clone().into_inner()).await?;
+                    let index = self
+                        .get_writable_index(index_uid.clone().into_inner(), aborted)
+                        .await?;
 
                     let DocumentDeletionResult {
                         deleted_documents, ..
@@ -198,11 +331,49 @@ mod real {
 
                     Ok(TaskResult::DocumentDeletion { deleted_documents })
                 }
+                TaskContent::DocumentDeletionByFilter { index_uid, filter } => {
+                    let filter = filter.clone();
+                    let index = self
+                        .get_writable_index(index_uid.clone().into_inner(), aborted)
+                        .await?;
+
+                    let DocumentDeletionResult {
+                        deleted_documents, ..
+                    } = spawn_blocking(move || index.delete_documents_by_filter(&filter)).await??;
+
+                    Ok(TaskResult::DocumentDeletion { deleted_documents })
+                }
+                TaskContent::DocumentEdition {
+                    index_uid,
+                    function,
+                    filter,
+                } => {
+                    let function = function.clone();
+                    let filter = filter.clone();
+                    let index = self
+                        .get_writable_index(index_uid.clone().into_inner(), aborted)
+                        .await?;
+
+                    let DocumentEditionResult {
+                        edited_documents,
+                        deleted_documents,
+                    } = spawn_blocking(move || {
+                        index.edit_documents_by_function(filter.as_ref(), &function)
+                    })
+                    .await??;
+
+                    Ok(TaskResult::DocumentEdition {
+                        edited_documents,
+                        deleted_documents,
+                    })
+                }
                 TaskContent::DocumentDeletion {
                     deletion: DocumentDeletion::Clear,
                     index_uid,
                 } => {
-                    let index = self.get_index(index_uid.clone().into_inner()).await?;
+                    let index = self
+                        .get_writable_index(index_uid.clone().into_inner(), aborted)
+                        .await?;
                     let deleted_documents = spawn_blocking(move || -> IndexResult<u64> {
                         let number_documents = index.stats()?.number_of_documents;
                         index.clear_documents()?;
@@ -216,13 +387,16 @@ mod real {
                     settings,
                     is_deletion,
                     allow_index_creation,
+                    quota,
                     index_uid,
                 } => {
                     let index = if *is_deletion || !*allow_index_creation {
                         self.get_index(index_uid.clone().into_inner()).await?
                     } else {
-                        self.get_or_create_index(index_uid.clone(), task.id).await?
+                        self.get_or_create_index(index_uid.clone(), task.id, quota)
+                            .await?
                     };
+                    let index = writable(index_uid.as_str(), index, aborted)?;
 
                     let settings = settings.clone();
                     spawn_blocking(move || index.update_settings(&settings.check())).await??;
@@ -230,6 +404,8 @@ mod real {
                     Ok(TaskResult::Other)
                 }
                 TaskContent::IndexDeletion { index_uid } => {
+                    self.get_writable_index(index_uid.clone().into_inner(), aborted)
+                        .await?;
                     let index = self.delete_index(index_uid.clone().into_inner()).await?;
 
                     let deleted_documents = spawn_blocking(move || -> IndexResult<u64> {
@@ -241,9 +417,10 @@ mod real {
                 }
                 TaskContent::IndexCreation {
                     primary_key,
+                    quota,
                     index_uid,
                 } => {
-                    let index = self.create_index(index_uid.clone(), task.id).await?;
+                    let index = self.create_index(index_uid.clone(), task.id, quota).await?;
 
                     if let Some(primary_key) = primary_key {
                         let primary_key = primary_key.clone();
@@ -254,25 +431,101 @@ mod real {
                 }
                 TaskContent::IndexUpdate {
                     primary_key,
+                    max_size,
+                    frozen,
                     index_uid,
                 } => {
                     let index = self.get_index(index_uid.clone().into_inner()).await?;
+                    let uuid = index.uuid();
+                    let set_frozen = |frozen: bool| {
+                        let index = index.clone();
+                        spawn_blocking(move || index.set_frozen(frozen))
+                    };
 
+                    // the index is unfrozen before the other changes of the task, and frozen
+                    // after them
+                    if *frozen == Some(false) {
+                        set_frozen(false).await??;
+                    }
                     if let Some(primary_key) = primary_key {
+                        let index = writable(index_uid.as_str(), index.clone(), aborted)?;
                         let primary_key = primary_key.clone();
                         spawn_blocking(move || index.update_primary_key(primary_key)).await??;
                     }
+                    if *frozen == Some(true) {
+                        set_frozen(true).await??;
+                    }
+
+                    // the index can't be resized while an instance of it is held
+                    drop(index);
+                    if let Some(max_size) = max_size {
+                        self.index_store.resize(uuid, *max_size).await?;
+                    }
 
                     Ok(TaskResult::Other)
                 }
+                TaskContent::IndexClone {
+                    index_uid,
+                    source_uid,
+                    quota,
+                } => {
+                    let source = self.get_index(source_uid.clone().into_inner()).await?;
+                    self.create_index_from(index_uid.clone(), task.id, Some(source), quota)
+                        .await?;
+
+                    Ok(TaskResult::Other)
+                }
+                TaskContent::IndexSwap { swaps } => {
+                    let swaps = swaps
+                        .iter()
+                        .map(|(lhs, rhs)| (lhs.as_str().to_string(), rhs.as_str().to_string()))
+                        .collect();
+                    self.index_uuid_store.swap(swaps).await?;
+
+                    Ok(TaskResult::Other)
+                }
+                TaskContent::IndexCompaction { index_uid } => {
+                    self.compact_index(index_uid.clone().into_inner()).await?;
+
+                    Ok(TaskResult::Other)
+                }
+                TaskContent::IndexRestoration { index_uid, quota } => {
+                    self.restore_index(index_uid.clone().into_inner(), quota)
+                        .await?;
+
+                    Ok(TaskResult::Other)
+                }
+                TaskContent::IndexIntegrityCheck { index_uid } => {
+                    let index = self.get_index(index_uid.clone().into_inner()).await?;
+                    let anomalies = spawn_blocking(move || index.check_integrity()).await??;
+
+                    Ok(TaskResult::IntegrityCheck { anomalies })
+                }
+                TaskContent::IndexWarmup { index_uid } => {
+                    let index = self.get_index(index_uid.clone().into_inner()).await?;
+                    let warmed_bytes = spawn_blocking(move || index.warmup()).await??;
+
+                    Ok(TaskResult::Warmup { warmed_bytes })
+                }
                 _ => unreachable!("Invalid task for index resolver"),
             }
         }
 
-        pub async fn process_task(&self, task: &mut Task) {
-            match self.process_task_inner(task).await {
+        pub async fn process_task(&self, task: &mut Task, aborted: &Arc<AtomicBool>) {
+            match self.process_task_inner(task, aborted).await {
                 Ok(res) => task.events.push(TaskEvent::succeeded(res)),
-                Err(e) => task.events.push(TaskEvent::failed(e)),
+                Err(e) => {
+                    let e = match task.index_uid() {
+                        Some(uid) if matches!(e.error_code(), Code::DatabaseSizeLimitReached) => {
+                            match self.get_index(uid.to_string()).await {
+                                Ok(index) => index_full_error(uid, index.map_size(), e),
+                                Err(_) => e,
+                            }
+                        }
+                        _ => e,
+                    };
+                    task.events.push(TaskEvent::failed(e))
+                }
             }
         }
 
@@ -284,23 +537,49 @@ mod real {
             Ok(())
         }
 
-        async fn create_index(&self, uid: IndexUid, creation_task_id: TaskId) -> Result<Index> {
+        async fn create_index(
+            &self,
+            uid: IndexUid,
+            creation_task_id: TaskId,
+            key_quota: &Quota,
+        ) -> Result<Index> {
+            self.create_index_from(uid, creation_task_id, None, key_quota)
+                .await
+        }
+
+        /// Creates the index `uid`, as a copy of the `source` index if any. The number of
+        /// indexes is limited by the strictest of the limits of the instance and of the key
+        /// registering the task.
+        async fn create_index_from(
+            &self,
+            uid: IndexUid,
+            creation_task_id: TaskId,
+            source: Option<Index>,
+            key_quota: &Quota,
+        ) -> Result<Index> {
             match self.index_uuid_store.get(uid.into_inner()).await? {
                 (uid, Some(_)) => Err(IndexResolverError::IndexAlreadyExists(uid)),
                 (uid, None) => {
+                    self.check_index_quota(key_quota).await?;
+
                     let uuid = Uuid::new_v4();
-                    let index = self.index_store.create(uuid).await?;
-                    match self
-                        .index_uuid_store
-                        .insert(
-                            uid,
-                            IndexMeta {
-                                uuid,
-                                creation_task_id,
-                            },
-                        )
-                        .await
-                    {
+                    let is_copy = source.is_some();
+                    let index = match source {
+                        Some(source) => self.index_store.copy(source, uuid).await?,
+                        None => self.index_store.create(uuid).await?,
+                    };
+                    let meta = IndexMeta {
+                        uuid,
+                        creation_task_id,
+                    };
+                    let result = async {
+                        // a copy keeps the settings of its source
+                        if !is_copy {
+                            self.apply_template(&uid, index.clone()).await?;
+                        }
+                        self.index_uuid_store.insert(uid, meta).await
+                    };
+                    match result.await {
                         Err(e) => {
                             match self.index_store.delete(uuid).await {
                                 Ok(Some(index)) => {
@@ -317,9 +596,45 @@ mod real {
             }
         }
 
+        /// Applies the settings of the template matching the uid to the index being created.
+        async fn apply_template(&self, uid: &str, index: Index) -> Result<()> {
+            let templates = self.index_uuid_store.list_templates().await?;
+            if let Some(settings) = template::find_template(templates, uid) {
+                spawn_blocking(move || index.update_settings(&settings.check())).await??;
+            }
+            Ok(())
+        }
+
+        /// Replaces the index `uid` with a compacted copy of its environment. The searches still
+        /// holding the previous environment keep reading it until they're done, it's closed once
+        /// the last of them drops it.
+        async fn compact_index(&self, uid: String) -> Result<()> {
+            let index = self.get_index(uid.clone()).await?;
+            let uuid = Uuid::new_v4();
+            self.index_store.copy(index, uuid).await?;
+
+            match self.index_uuid_store.update_uuid(uid, uuid).await {
+                Ok(previous_uuid) => {
+                    self.index_store.delete(previous_uuid).await?;
+                    Ok(())
+                }
+                Err(e) => {
+                    if let Err(e) = self.index_store.delete(uuid).await {
+                        log::error!("Error while deleting index: {:?}", e);
+                    }
+                    Err(e)
+                }
+            }
+        }
+
         /// Get or create an index with name `uid`.
-        pub async fn get_or_create_index(&self, uid: IndexUid, task_id: TaskId) -> Result<Index> {
-            match self.create_index(uid, task_id).await {
+        pub async fn get_or_create_index(
+            &self,
+            uid: IndexUid,
+            task_id: TaskId,
+            key_quota: &Quota,
+        ) -> Result<Index> {
+            match self.create_index(uid, task_id, key_quota).await {
                 Ok(index) => Ok(index),
                 Err(IndexResolverError::IndexAlreadyExists(uid)) => self.get_index(uid).await,
                 Err(e) => Err(e),
@@ -342,7 +657,31 @@ mod real {
             Ok(indexes)
         }
 
+        /// Returns the uids of the indexes in order, without opening them.
+        pub async fn list_uids(&self) -> Result<Vec<String>> {
+            let uuids = self.index_uuid_store.list().await?;
+            Ok(uuids.into_iter().map(|(uid, _)| uid).collect())
+        }
+
+        /// Returns the indexes that are open, without opening the others nor changing the order
+        /// in which the open indexes are closed.
+        pub async fn list_open(&self) -> Result<Vec<(String, Index)>> {
+            let uuids = self.index_uuid_store.list().await?;
+            let mut indexes = Vec::new();
+            for (name, IndexMeta { uuid, .. }) in uuids {
+                if let Some(index) = self.index_store.get_if_open(uuid).await {
+                    indexes.push((name, index));
+                }
+            }
+
+            Ok(indexes)
+        }
+
         pub async fn delete_index(&self, uid: String) -> Result<Index> {
+            if self.trash_deleted_indexes {
+                return self.trash_index(uid).await;
+            }
+
             match self.index_uuid_store.delete(uid.clone()).await? {
                 Some(IndexMeta { uuid, .. }) => match self.index_store.delete(uuid).await? {
                     Some(index) => {
@@ -372,6 +711,65 @@ mod real {
             }
         }
 
+        /// Moves the index `uid` to the trash, from which it can be restored until it's purged.
+        async fn trash_index(&self, uid: String) -> Result<Index> {
+            let index = self.get_index(uid.clone()).await?;
+            match self.index_uuid_store.trash(uid.clone()).await? {
+                Some((IndexMeta { uuid, .. }, previous)) => {
+                    // the index trashed before under the same uid can't be restored anymore
+                    if let Some(previous) = previous {
+                        self.index_store.delete(previous).await?;
+                    }
+                    self.index_store.close(uuid).await?;
+                    Ok(index)
+                }
+                None => Err(IndexResolverError::UnexistingIndex(uid)),
+            }
+        }
+
+        /// Fails when the instance holds the number of indexes allowed by the strictest of the
+        /// limits of the instance and of the key registering the task.
+        async fn check_index_quota(&self, key_quota: &Quota) -> Result<()> {
+            let max_indexes = strictest_limit(self.quota.max_indexes, key_quota.max_indexes);
+            if let Some(max_indexes) = max_indexes {
+                let indexes = self.index_uuid_store.list().await?.len() as u64;
+                if indexes >= max_indexes {
+                    return Err(IndexResolverError::IndexQuotaExceeded(max_indexes));
+                }
+            }
+            Ok(())
+        }
+
+        /// Moves the index `uid` back from the trash. Like a creation, it's limited by the
+        /// number of indexes allowed.
+        async fn restore_index(&self, uid: String, key_quota: &Quota) -> Result<()> {
+            self.check_index_quota(key_quota).await?;
+            match self.index_uuid_store.restore(uid.clone()).await? {
+                // the index is opened to check its files are still there
+                Some(_) => self.get_index(uid).await.map(|_| ()),
+                None => Err(IndexResolverError::UnexistingTrashedIndex(uid)),
+            }
+        }
+
+        /// Deletes for good the indexes trashed before `before`, and returns their number.
+        pub async fn purge_trash(&self, before: OffsetDateTime) -> Result<usize> {
+            let uuids = self.index_uuid_store.purge_trash(before).await?;
+            for uuid in &uuids {
+                self.index_store.delete(*uuid).await?;
+            }
+            Ok(uuids.len())
+        }
+
+        /// Returns the index `uid`, unless it's frozen and can't be modified.
+        async fn get_writable_index(
+            &self,
+            uid: String,
+            aborted: &Arc<AtomicBool>,
+        ) -> Result<Index> {
+            let index = self.get_index(uid.clone()).await?;
+            writable(&uid, index, aborted)
+        }
+
         pub async fn get_index_creation_task_id(&self, index_uid: String) -> Result<TaskId> {
             let (uid, meta) = self.index_uuid_store.get(index_uid).await?;
             meta.map(
@@ -381,18 +779,85 @@ mod real {
             )
             .ok_or(IndexResolverError::UnexistingIndex(uid))
         }
+
+        /// Returns the uid of the index targeted by the alias `uid`, or `uid` when it's not an
+        /// alias.
+        pub async fn resolve_alias(&self, uid: String) -> Result<String> {
+            Ok(self
+                .index_uuid_store
+                .get_alias(uid.clone())
+                .await?
+                .unwrap_or(uid))
+        }
+
+        pub async fn get_alias(&self, alias: String) -> Result<String> {
+            self.index_uuid_store
+                .get_alias(alias.clone())
+                .await?
+                .ok_or(IndexResolverError::UnexistingAlias(alias))
+        }
+
+        pub async fn list_aliases(&self) -> Result<Vec<(String, String)>> {
+            self.index_uuid_store.list_aliases().await
+        }
+
+        pub async fn set_alias(&self, alias: String, index_uid: String) -> Result<()> {
+            self.index_uuid_store.set_alias(alias, index_uid).await
+        }
+
+        pub async fn delete_alias(&self, alias: String) -> Result<()> {
+            match self.index_uuid_store.delete_alias(alias.clone()).await? {
+                Some(_) => Ok(()),
+                None => Err(IndexResolverError::UnexistingAlias(alias)),
+            }
+        }
+
+        pub async fn get_template(&self, pattern: String) -> Result<Settings<Unchecked>> {
+            self.index_uuid_store
+                .get_template(pattern.clone())
+                .await?
+                .ok_or(IndexResolverError::UnexistingTemplate(pattern))
+        }
+
+        pub async fn list_templates(&self) -> Result<Vec<(String, Settings<Unchecked>)>> {
+            self.index_uuid_store.list_templates().await
+        }
+
+        /// Registers the settings applied to the indexes created afterward with a uid matching
+        /// the pattern. The existing indexes are left as is.
+        pub async fn set_template(
+            &self,
+            pattern: String,
+            settings: Settings<Unchecked>,
+        ) -> Result<()> {
+            template::check_pattern(&pattern)?;
+            self.index_uuid_store.set_template(pattern, settings).await
+        }
+
+        pub async fn delete_template(&self, pattern: String) -> Result<()> {
+            match self
+                .index_uuid_store
+                .delete_template(pattern.clone())
+                .await?
+            {
+                Some(_) => Ok(()),
+                None => Err(IndexResolverError::UnexistingTemplate(pattern)),
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::index::IndexStats;
+    use crate::index::{IndexStats, OnDuplicate};
+    use crate::update_file_store::Result as FileStoreResult;
 
     use super::index_store::MockIndexStore;
     use super::meta_store::MockIndexMetaStore;
     use super::*;
 
     use futures::future::ok;
+    use milli::update::IndexDocumentsMethod;
     use milli::FieldDistribution;
     use nelson::Mocker;
 
@@ -423,6 +888,8 @@ mod test {
                 index_uuid_store,
                 index_store,
                 file_store,
+                trash_deleted_indexes: false,
+                quota: Quota::default(),
             })
         }
 
@@ -430,18 +897,22 @@ mod test {
             Self::Mock(mocker)
         }
 
-        pub async fn process_document_addition_batch(&self, tasks: &mut [Task]) {
+        pub async fn process_document_addition_batch(
+            &self,
+            tasks: &mut [Task],
+            aborted: &Arc<AtomicBool>,
+        ) {
             match self {
-                IndexResolver::Real(r) => r.process_document_addition_batch(tasks).await,
+                IndexResolver::Real(r) => r.process_document_addition_batch(tasks, aborted).await,
                 IndexResolver::Mock(m) => unsafe {
                     m.get("process_document_addition_batch").call(tasks)
                 },
             }
         }
 
-        pub async fn process_task(&self, task: &mut Task) {
+        pub async fn process_task(&self, task: &mut Task, aborted: &Arc<AtomicBool>) {
             match self {
-                IndexResolver::Real(r) => r.process_task(task).await,
+                IndexResolver::Real(r) => r.process_task(task, aborted).await,
                 IndexResolver::Mock(m) => unsafe { m.get("process_task").call(task) },
             }
         }
@@ -454,9 +925,14 @@ mod test {
         }
 
         /// Get or create an index with name `uid`.
-        pub async fn get_or_create_index(&self, uid: IndexUid, task_id: TaskId) -> Result<Index> {
+        pub async fn get_or_create_index(
+            &self,
+            uid: IndexUid,
+            task_id: TaskId,
+            key_quota: &Quota,
+        ) -> Result<Index> {
             match self {
-                IndexResolver::Real(r) => r.get_or_create_index(uid, task_id).await,
+                IndexResolver::Real(r) => r.get_or_create_index(uid, task_id, key_quota).await,
                 IndexResolver::Mock(_) => todo!(),
             }
         }
@@ -468,6 +944,20 @@ mod test {
             }
         }
 
+        pub async fn list_uids(&self) -> Result<Vec<String>> {
+            match self {
+                IndexResolver::Real(r) => r.list_uids().await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn list_open(&self) -> Result<Vec<(String, Index)>> {
+            match self {
+                IndexResolver::Real(r) => r.list_open().await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
         pub async fn delete_index(&self, uid: String) -> Result<Index> {
             match self {
                 IndexResolver::Real(r) => r.delete_index(uid).await,
@@ -475,6 +965,27 @@ mod test {
             }
         }
 
+        pub fn set_trash_deleted_indexes(&mut self, trash_deleted_indexes: bool) {
+            match self {
+                IndexResolver::Real(r) => r.set_trash_deleted_indexes(trash_deleted_indexes),
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn set_quota(&mut self, quota: Quota) {
+            match self {
+                IndexResolver::Real(r) => r.set_quota(quota),
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn purge_trash(&self, before: OffsetDateTime) -> Result<usize> {
+            match self {
+                IndexResolver::Real(r) => r.purge_trash(before).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
         pub async fn get_index(&self, uid: String) -> Result<Index> {
             match self {
                 IndexResolver::Real(r) => r.get_index(uid).await,
@@ -489,6 +1000,73 @@ mod test {
             }
         }
 
+        pub async fn resolve_alias(&self, uid: String) -> Result<String> {
+            match self {
+                IndexResolver::Real(r) => r.resolve_alias(uid).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn get_alias(&self, alias: String) -> Result<String> {
+            match self {
+                IndexResolver::Real(r) => r.get_alias(alias).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn list_aliases(&self) -> Result<Vec<(String, String)>> {
+            match self {
+                IndexResolver::Real(r) => r.list_aliases().await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn set_alias(&self, alias: String, index_uid: String) -> Result<()> {
+            match self {
+                IndexResolver::Real(r) => r.set_alias(alias, index_uid).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn delete_alias(&self, alias: String) -> Result<()> {
+            match self {
+                IndexResolver::Real(r) => r.delete_alias(alias).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn get_template(&self, pattern: String) -> Result<Settings<Unchecked>> {
+            match self {
+                IndexResolver::Real(r) => r.get_template(pattern).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn list_templates(&self) -> Result<Vec<(String, Settings<Unchecked>)>> {
+            match self {
+                IndexResolver::Real(r) => r.list_templates().await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn set_template(
+            &self,
+            pattern: String,
+            settings: Settings<Unchecked>,
+        ) -> Result<()> {
+            match self {
+                IndexResolver::Real(r) => r.set_template(pattern, settings).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
+        pub async fn delete_template(&self, pattern: String) -> Result<()> {
+            match self {
+                IndexResolver::Real(r) => r.delete_template(pattern).await,
+                IndexResolver::Mock(_) => todo!(),
+            }
+        }
+
         pub async fn delete_content_file(&self, content_uuid: Uuid) -> Result<()> {
             match self {
                 IndexResolver::Real(r) => r.delete_content_file(content_uuid).await,
@@ -503,9 +1081,9 @@ mod test {
     async fn test_remove_unknown_index() {
         let mut meta_store = MockIndexMetaStore::new();
         meta_store
-            .expect_delete()
+            .expect_get()
             .once()
-            .returning(|_| Box::pin(ok(None)));
+            .returning(|uid| Box::pin(ok((uid, None))));
 
         let index_store = MockIndexStore::new();
 
@@ -522,7 +1100,9 @@ mod test {
             events: Vec::new(),
         };
 
-        index_resolver.process_task(&mut task).await;
+        index_resolver
+            .process_task(&mut task, &Arc::default())
+            .await;
 
         assert!(matches!(task.events[0], TaskEvent::Failed { .. }));
     }
@@ -530,6 +1110,15 @@ mod test {
     #[actix_rt::test]
     async fn test_remove_index() {
         let mut meta_store = MockIndexMetaStore::new();
+        meta_store.expect_get().once().returning(|uid| {
+            Box::pin(ok((
+                uid,
+                Some(IndexMeta {
+                    uuid: Uuid::new_v4(),
+                    creation_task_id: 1,
+                }),
+            )))
+        });
         meta_store.expect_delete().once().returning(|_| {
             Box::pin(ok(Some(IndexMeta {
                 uuid: Uuid::new_v4(),
@@ -538,6 +1127,13 @@ mod test {
         });
 
         let mut index_store = MockIndexStore::new();
+        index_store.expect_get().once().returning(|_| {
+            let mocker = Mocker::default();
+            mocker
+                .when::<(), IndexResult<bool>>("is_frozen")
+                .then(|_| Ok(false));
+            Box::pin(ok(Some(Index::mock(mocker))))
+        });
         index_store.expect_delete().once().returning(|_| {
             let mocker = Mocker::default();
             mocker.when::<(), ()>("close").then(|_| ());
@@ -567,7 +1163,9 @@ mod test {
             events: Vec::new(),
         };
 
-        index_resolver.process_task(&mut task).await;
+        index_resolver
+            .process_task(&mut task, &Arc::default())
+            .await;
 
         assert!(matches!(task.events[0], TaskEvent::Succeeded { .. }));
     }
@@ -588,6 +1186,9 @@ mod test {
         let mut index_store = MockIndexStore::new();
         index_store.expect_get().once().returning(|_| {
             let mocker = Mocker::default();
+            mocker
+                .when::<(), IndexResult<bool>>("is_frozen")
+                .then(|_| Ok(false));
             mocker
                 .when::<(), IndexResult<()>>("clear_documents")
                 .once()
@@ -620,7 +1221,9 @@ mod test {
             events: Vec::new(),
         };
 
-        index_resolver.process_task(&mut task).await;
+        index_resolver
+            .process_task(&mut task, &Arc::default())
+            .await;
 
         assert!(matches!(task.events[0], TaskEvent::Succeeded { .. }));
     }
@@ -642,6 +1245,10 @@ mod test {
         index_store.expect_get().once().returning(|_| {
             let mocker = Mocker::default();
 
+            mocker.when::<(), Uuid>("uuid").then(|_| Uuid::new_v4());
+            mocker
+                .when::<(), IndexResult<bool>>("is_frozen")
+                .then(|_| Ok(false));
             mocker
                 .when::<String, IndexResult<crate::index::IndexMeta>>("update_primary_key")
                 .once()
@@ -650,6 +1257,7 @@ mod test {
                         created_at: OffsetDateTime::now_utc(),
                         updated_at: OffsetDateTime::now_utc(),
                         primary_key: Some("key".to_string()),
+                        frozen: false,
                     })
                 });
             Box::pin(ok(Some(Index::mock(mocker))))
@@ -664,13 +1272,65 @@ mod test {
             id: 1,
             content: TaskContent::IndexUpdate {
                 primary_key: Some("key".to_string()),
+                max_size: None,
+                frozen: None,
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
         };
 
-        index_resolver.process_task(&mut task).await;
+        index_resolver
+            .process_task(&mut task, &Arc::default())
+            .await;
 
         assert!(matches!(task.events[0], TaskEvent::Succeeded { .. }));
     }
+
+    #[actix_rt::test]
+    async fn test_document_addition_with_corrupted_update_files() {
+        // The index must never be touched since none of the update files can be read.
+        let meta_store = MockIndexMetaStore::new();
+        let index_store = MockIndexStore::new();
+
+        let mocker = Mocker::default();
+        mocker
+            .when::<Uuid, FileStoreResult<()>>("check_update")
+            .times(2)
+            .then(|_| Err(std::io::Error::from(std::io::ErrorKind::NotFound).into()));
+        let file_store = UpdateFileStore::mock(mocker);
+
+        let index_resolver = IndexResolver::new(meta_store, index_store, file_store);
+
+        let mut tasks = (0..2)
+            .map(|id| Task {
+                id,
+                content: TaskContent::DocumentAddition {
+                    content_uuid: Uuid::new_v4(),
+                    merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                    primary_key: None,
+                    documents_count: 10,
+                    allow_index_creation: true,
+                    quota: Quota::default(),
+                    auto_generate_primary_key: false,
+                    merge_nested: false,
+                    on_duplicate: OnDuplicate::Replace,
+                    index_uid: IndexUid::new_unchecked("test"),
+                },
+                events: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        index_resolver
+            .process_document_addition_batch(&mut tasks, &Arc::default())
+            .await;
+
+        for task in tasks {
+            match &task.events[..] {
+                [TaskEvent::Failed { error, .. }] => {
+                    assert_eq!(error.error_code(), "update_file_corrupted")
+                }
+                events => panic!("unexpected events: {:?}", events),
+            }
+        }
+    }
 }
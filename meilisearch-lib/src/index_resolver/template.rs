@@ -0,0 +1,89 @@
+use super::error::{IndexResolverError, Result};
+
+/// Checks that the pattern of a template is made of the characters of an index uid, and of
+/// wildcards matching any sequence of these characters.
+pub fn check_pattern(pattern: &str) -> Result<()> {
+    let is_valid = !pattern.is_empty()
+        && pattern.len() <= 400
+        && pattern
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '/' | '*'));
+    if is_valid {
+        Ok(())
+    } else {
+        Err(IndexResolverError::InvalidTemplatePattern(
+            pattern.to_string(),
+        ))
+    }
+}
+
+/// Whether the index `uid` matches the pattern, in which each `*` stands for any sequence of
+/// characters, including an empty one.
+pub fn pattern_matches(pattern: &str, uid: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // the pattern always has a first part, the one before its first wildcard
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match uid.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let mut parts: Vec<_> = parts.collect();
+    // the part after the last wildcard must end the uid
+    let last = match parts.pop() {
+        Some(last) => last,
+        None => return rest.is_empty(),
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Returns the settings of the most specific template matching the index `uid`, the one with the
+/// longest pattern.
+pub fn find_template<T>(templates: Vec<(String, T)>, uid: &str) -> Option<T> {
+    templates
+        .into_iter()
+        .filter(|(pattern, _)| pattern_matches(pattern, uid))
+        .max_by_key(|(pattern, _)| pattern.len())
+        .map(|(_, settings)| settings)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pattern_matches() {
+        assert!(pattern_matches("logs-*", "logs-2022"));
+        assert!(pattern_matches("logs-*", "logs-"));
+        assert!(!pattern_matches("logs-*", "log"));
+        assert!(pattern_matches("logs", "logs"));
+        assert!(!pattern_matches("logs", "logs-2022"));
+        assert!(pattern_matches("*-staging", "products-staging"));
+        assert!(pattern_matches("logs-*-eu*", "logs-2022-eu-west"));
+        assert!(!pattern_matches("logs-*-eu", "logs-2022-us"));
+        assert!(!pattern_matches("a*a", "a"));
+        assert!(pattern_matches("*", "products"));
+    }
+
+    #[test]
+    fn test_find_template() {
+        let templates = vec![
+            ("*".to_string(), 1),
+            ("logs-*".to_string(), 2),
+            ("logs-eu-*".to_string(), 3),
+        ];
+        assert_eq!(find_template(templates.clone(), "products"), Some(1));
+        assert_eq!(find_template(templates.clone(), "logs-us-1"), Some(2));
+        assert_eq!(find_template(templates, "logs-eu-1"), Some(3));
+        assert_eq!(
+            find_template(vec![("logs-*".to_string(), 2)], "products"),
+            None
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{error, info};
+use time::OffsetDateTime;
+use tokio::time::sleep;
+
+use crate::index_resolver::HardStateIndexResolver;
+
+/// How often, at most, the trash is checked for indexes to delete.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Periodically deletes for good the indexes that were moved to the trash more than `retention`
+/// ago.
+pub struct IndexTrashService {
+    pub(crate) index_resolver: Arc<HardStateIndexResolver>,
+    pub(crate) retention: Duration,
+}
+
+impl IndexTrashService {
+    pub async fn run(self) {
+        info!(
+            "Deleted indexes will be purged {}s after their deletion.",
+            self.retention.as_secs()
+        );
+        loop {
+            let deleted_before = OffsetDateTime::now_utc() - self.retention;
+            match self.index_resolver.purge_trash(deleted_before).await {
+                Ok(0) => (),
+                Ok(count) => info!("Purged {} deleted indexes.", count),
+                Err(e) => error!("Error while purging the deleted indexes: {}", e),
+            }
+            sleep(self.retention.min(PURGE_INTERVAL)).await;
+        }
+    }
+}
@@ -3,13 +3,19 @@ pub mod error;
 pub mod options;
 
 mod analytics;
+mod document_expiration;
 mod dump;
 pub mod index;
 pub mod index_controller;
 mod index_resolver;
+mod index_trash;
+pub mod search_cache;
+pub mod search_limiter;
+pub mod search_log;
 mod snapshot;
 pub mod tasks;
 mod update_file_store;
+pub mod upload_store;
 
 use std::path::Path;
 
@@ -62,6 +62,15 @@ pub struct SchedulerConfig {
     /// starting to process a batch of updates.
     #[clap(long, requires = "enable-auto-batching", hide = true)]
     pub debounce_duration_sec: Option<u64>,
+
+    /// The maximum duration in seconds a batch of tasks can stay in the processing state.
+    ///
+    /// A batch exceeding this duration is considered stuck: the writes of its tasks that aren't
+    /// committed yet are rolled back and these tasks are marked as failed, then the scheduler moves
+    /// on to the next batch once the indexing work in progress has returned, or after a minute at
+    /// most. If unspecified, a batch can be processed for as long as it takes.
+    #[clap(long, env = "MEILI_TASK_WATCHDOG_TIMEOUT_SEC")]
+    pub task_watchdog_timeout_sec: Option<u64>,
 }
 
 impl TryFrom<&IndexerOpts> for IndexerConfig {
@@ -0,0 +1,173 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::index::{SearchQuery, SearchResult};
+
+/// Keeps the results of the last searches, up to a capacity, so the searches repeated on an
+/// index are served without being performed again. The results of an index are dropped as soon
+/// as a task of the index finishes.
+#[derive(Debug, Clone)]
+pub struct SearchCache {
+    capacity: usize,
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// The cached results, along with the tick of their last use.
+    entries: HashMap<(String, String), (u64, SearchResult)>,
+    /// The keys of the entries by tick of their last use, the least recently used first.
+    recency: BTreeMap<u64, (String, String)>,
+    /// Bumped every time the results of an index are invalidated, so a search performed before
+    /// the invalidation doesn't get cached after it.
+    generations: HashMap<String, u64>,
+    tick: u64,
+}
+
+impl Inner {
+    fn generation(&self, index_uid: &str) -> u64 {
+        self.generations.get(index_uid).copied().unwrap_or_default()
+    }
+}
+
+impl SearchCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Arc::default(),
+        }
+    }
+
+    /// The generation of the results of the index, to be passed to `insert` once the search is
+    /// performed.
+    pub fn generation(&self, index_uid: &str) -> u64 {
+        self.inner.lock().generation(index_uid)
+    }
+
+    pub fn get(&self, index_uid: &str, key: &str) -> Option<SearchResult> {
+        let mut inner = self.inner.lock();
+        let inner = &mut *inner;
+        let key = (index_uid.to_string(), key.to_string());
+        let (used_at, result) = inner.entries.get_mut(&key)?;
+
+        inner.tick += 1;
+        inner.recency.remove(used_at);
+        *used_at = inner.tick;
+        inner.recency.insert(inner.tick, key);
+
+        Some(result.clone())
+    }
+
+    /// Caches the result, unless the results of the index were invalidated since `generation`
+    /// was read. The least recently used result is evicted when the cache is full.
+    pub fn insert(&self, index_uid: &str, key: &str, generation: u64, result: SearchResult) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock();
+        let inner = &mut *inner;
+        if inner.generation(index_uid) != generation {
+            return;
+        }
+
+        let key = (index_uid.to_string(), key.to_string());
+        if let Some((used_at, _)) = inner.entries.remove(&key) {
+            inner.recency.remove(&used_at);
+        }
+        while inner.entries.len() >= self.capacity {
+            let oldest = match inner.recency.keys().next() {
+                Some(used_at) => *used_at,
+                None => break,
+            };
+            if let Some(oldest) = inner.recency.remove(&oldest) {
+                inner.entries.remove(&oldest);
+            }
+        }
+
+        inner.tick += 1;
+        inner.recency.insert(inner.tick, key.clone());
+        inner.entries.insert(key, (inner.tick, result));
+    }
+
+    /// Drops the cached results of the index.
+    pub fn invalidate(&self, index_uid: &str) {
+        let mut inner = self.inner.lock();
+        let inner = &mut *inner;
+        *inner.generations.entry(index_uid.to_string()).or_default() += 1;
+
+        let Inner {
+            entries, recency, ..
+        } = inner;
+        entries.retain(|(uid, _), (used_at, _)| {
+            let keep = uid != index_uid;
+            if !keep {
+                recency.remove(used_at);
+            }
+            keep
+        });
+    }
+}
+
+/// The key of the query in the cache. The queries only differing by the order of their
+/// attributes to highlight share a key.
+pub fn cache_key(query: &SearchQuery) -> String {
+    let mut query = query.clone();
+    let attributes_to_highlight = query.attributes_to_highlight.take().map(|attributes| {
+        let mut attributes: Vec<_> = attributes.into_iter().collect();
+        attributes.sort_unstable();
+        attributes
+    });
+    format!("{:?}|{:?}", query, attributes_to_highlight)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::index::HitsInfo;
+
+    use super::*;
+
+    fn result(query: &str) -> SearchResult {
+        SearchResult {
+            hits: Vec::new(),
+            query: query.to_string(),
+            processing_time_ms: 0,
+            hits_info: HitsInfo::OffsetLimit {
+                limit: 20,
+                offset: 0,
+                estimated_total_hits: 0,
+            },
+            facet_distribution: None,
+            facet_stats: None,
+            degraded: false,
+            ranking_variant: None,
+            suggested_query: None,
+            applied_rules: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_search_cache() {
+        let cache = SearchCache::new(2);
+        cache.insert("movies", "a", 0, result("a"));
+        cache.insert("movies", "b", 0, result("b"));
+        cache.insert("books", "a", 0, result("a"));
+
+        // The least recently used result was evicted.
+        assert!(cache.get("movies", "a").is_none());
+        assert_eq!(cache.get("movies", "b").unwrap().query, "b");
+
+        cache.invalidate("movies");
+        assert!(cache.get("movies", "b").is_none());
+        assert_eq!(cache.get("books", "a").unwrap().query, "a");
+
+        // A search performed before the invalidation isn't cached.
+        cache.insert("movies", "c", 0, result("c"));
+        assert!(cache.get("movies", "c").is_none());
+        let generation = cache.generation("movies");
+        cache.insert("movies", "c", generation, result("c"));
+        assert_eq!(cache.get("movies", "c").unwrap().query, "c");
+    }
+}
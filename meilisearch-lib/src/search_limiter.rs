@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Caps the number of searches executed at the same time. The searches above the cap wait for
+/// a slot in a bounded queue, the ones that don't fit in the queue are rejected right away.
+#[derive(Debug, Clone)]
+pub struct SearchLimiter {
+    /// One permit per search executing or waiting in the queue.
+    admitted: Arc<Semaphore>,
+    /// One permit per search executing.
+    running: Arc<Semaphore>,
+}
+
+/// Holds the slot of a search until it's dropped.
+#[derive(Debug)]
+pub struct SearchPermit {
+    _admitted: OwnedSemaphorePermit,
+    _running: OwnedSemaphorePermit,
+}
+
+impl SearchLimiter {
+    pub fn new(max_concurrent_searches: usize, queue_size: usize) -> Self {
+        Self {
+            admitted: Arc::new(Semaphore::new(
+                max_concurrent_searches.saturating_add(queue_size),
+            )),
+            running: Arc::new(Semaphore::new(max_concurrent_searches)),
+        }
+    }
+
+    /// Waits for a search to be allowed to execute. Returns `None` when the queue is full.
+    pub async fn acquire(&self) -> Option<SearchPermit> {
+        let admitted = self.admitted.clone().try_acquire_owned().ok()?;
+        // The semaphores are never closed.
+        let running = self.running.clone().acquire_owned().await.ok()?;
+        Some(SearchPermit {
+            _admitted: admitted,
+            _running: running,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_search_limiter() {
+        let limiter = SearchLimiter::new(1, 1);
+
+        let running = limiter.acquire().await.unwrap();
+
+        // The second search waits in the queue, the third one doesn't fit.
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire().await.is_some() }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(limiter.acquire().await.is_none());
+
+        drop(running);
+        assert!(queued.await.unwrap());
+        assert!(limiter.acquire().await.is_some());
+    }
+}
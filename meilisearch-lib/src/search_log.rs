@@ -0,0 +1,222 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use crate::index::{SearchQuery, SearchResult};
+
+/// A search recorded by the [SearchLog].
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggedSearch {
+    pub index_uid: String,
+    pub q: Option<String>,
+    pub filter: Option<Value>,
+    pub nb_hits: u64,
+    pub processing_time_ms: u128,
+    /// The tag given by the client to identify its users, if any.
+    pub user_tag: Option<String>,
+    /// The variant of the ranking experiment of the index that served the search, if any.
+    pub ranking_variant: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub searched_at: OffsetDateTime,
+}
+
+impl LoggedSearch {
+    /// Creates the entry of a search about to be performed, it's completed by
+    /// [LoggedSearch::with_result].
+    pub fn new(index_uid: String, query: &SearchQuery, user_tag: Option<String>) -> Self {
+        Self {
+            index_uid,
+            q: query.q.clone(),
+            filter: query.filter.clone(),
+            nb_hits: 0,
+            processing_time_ms: 0,
+            user_tag,
+            ranking_variant: None,
+            searched_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    pub fn with_result(mut self, result: &SearchResult) -> Self {
+        self.nb_hits = result.hits_info.total_hits();
+        self.processing_time_ms = result.processing_time_ms;
+        self.ranking_variant = result.ranking_variant.clone();
+        self
+    }
+}
+
+/// Restricts the searches of the [SearchLog] an aggregation is computed on.
+#[derive(Debug, Clone, Default)]
+pub struct SearchLogFilter {
+    /// The searches performed before this date are ignored.
+    pub from: Option<OffsetDateTime>,
+    /// The searches performed after this date are ignored.
+    pub to: Option<OffsetDateTime>,
+    /// Only the searches on these indexes are kept, all the indexes if unset.
+    pub index_uids: Option<Vec<String>>,
+}
+
+impl SearchLogFilter {
+    fn matches(&self, search: &LoggedSearch) -> bool {
+        self.from.map_or(true, |from| search.searched_at >= from)
+            && self.to.map_or(true, |to| search.searched_at <= to)
+            && self
+                .index_uids
+                .as_ref()
+                .map_or(true, |uids| uids.contains(&search.index_uid))
+    }
+}
+
+/// The number of times a query was searched.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryCount {
+    pub q: String,
+    pub count: u64,
+}
+
+/// Keeps the last searches performed on the instance in memory. Once the capacity is reached, the
+/// oldest search is dropped each time a new one is recorded.
+#[derive(Debug, Clone)]
+pub struct SearchLog {
+    capacity: usize,
+    searches: Arc<Mutex<VecDeque<LoggedSearch>>>,
+}
+
+impl SearchLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            searches: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    pub fn record(&self, search: LoggedSearch) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut searches = self.searches.lock();
+        if searches.len() == self.capacity {
+            searches.pop_front();
+        }
+        searches.push_back(search);
+    }
+
+    /// Returns the recorded searches, the most recent first.
+    pub fn searches(&self) -> Vec<LoggedSearch> {
+        self.searches.lock().iter().rev().cloned().collect()
+    }
+
+    /// Returns the most searched queries, the placeholder searches excluded.
+    pub fn top_searches(&self, filter: &SearchLogFilter, limit: usize) -> Vec<QueryCount> {
+        self.count_queries(filter, limit, |search| {
+            search.q.as_ref().map_or(false, |q| !q.trim().is_empty())
+        })
+    }
+
+    /// Returns the most searched queries among the searches that returned no hits.
+    pub fn no_result_searches(&self, filter: &SearchLogFilter, limit: usize) -> Vec<QueryCount> {
+        self.count_queries(filter, limit, |search| search.nb_hits == 0)
+    }
+
+    /// Counts the searches of each query, the queries only differing by their case or their
+    /// surrounding whitespaces are counted together. The most searched queries come first.
+    fn count_queries(
+        &self,
+        filter: &SearchLogFilter,
+        limit: usize,
+        keep: impl Fn(&LoggedSearch) -> bool,
+    ) -> Vec<QueryCount> {
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        for search in self.searches.lock().iter() {
+            if filter.matches(search) && keep(search) {
+                let q = search
+                    .q
+                    .as_deref()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_lowercase();
+                *counts.entry(q).or_default() += 1;
+            }
+        }
+
+        let mut counts: Vec<_> = counts
+            .into_iter()
+            .map(|(q, count)| QueryCount { q, count })
+            .collect();
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.q.cmp(&b.q)));
+        counts.truncate(limit);
+        counts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn logged_search(q: &str) -> LoggedSearch {
+        let query = serde_json::from_value(serde_json::json!({ "q": q })).unwrap();
+        LoggedSearch::new("movies".to_string(), &query, None)
+    }
+
+    #[test]
+    fn test_count_queries() {
+        let log = SearchLog::new(10);
+        for (index_uid, q, nb_hits) in [
+            ("movies", "Shoes", 3),
+            ("movies", " shoes", 3),
+            ("movies", "jacket", 0),
+            ("books", "jacket", 0),
+            ("movies", "", 12),
+        ] {
+            let mut search = logged_search(q);
+            search.index_uid = index_uid.to_string();
+            search.nb_hits = nb_hits;
+            log.record(search);
+        }
+
+        let count = |q: &str, n| QueryCount {
+            q: q.to_string(),
+            count: n,
+        };
+        let all = SearchLogFilter::default();
+        assert_eq!(
+            log.top_searches(&all, 10),
+            [count("jacket", 2), count("shoes", 2)]
+        );
+        assert_eq!(log.top_searches(&all, 1), [count("jacket", 2)]);
+        assert_eq!(log.no_result_searches(&all, 10), [count("jacket", 2)]);
+
+        let books = SearchLogFilter {
+            index_uids: Some(vec!["books".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(log.top_searches(&books, 10), [count("jacket", 1)]);
+
+        let future = SearchLogFilter {
+            from: Some(OffsetDateTime::now_utc() + time::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(log.top_searches(&future, 10).is_empty());
+    }
+
+    #[test]
+    fn test_search_log_rotation() {
+        let log = SearchLog::new(2);
+        log.record(logged_search("a"));
+        log.record(logged_search("b"));
+        log.record(logged_search("c"));
+
+        let queries: Vec<_> = log.searches().into_iter().map(|s| s.q.unwrap()).collect();
+        assert_eq!(queries, ["c", "b"]);
+
+        let log = SearchLog::new(0);
+        log.record(logged_search("a"));
+        assert!(log.searches().is_empty());
+    }
+}
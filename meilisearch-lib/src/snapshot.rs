@@ -16,12 +16,18 @@ use crate::index_controller::open_meta_env;
 use crate::index_controller::versioning::VERSION_FILE_NAME;
 use crate::tasks::Scheduler;
 
+/// The directory of a snapshot containing the task store, when it was taken from its own
+/// environment.
+pub const TASKS_SNAPSHOT_DIR: &str = "tasks";
+
 pub struct SnapshotService {
     pub(crate) db_path: PathBuf,
     pub(crate) snapshot_period: Duration,
     pub(crate) snapshot_path: PathBuf,
     pub(crate) index_size: usize,
     pub(crate) meta_env_size: usize,
+    pub(crate) task_db_path: Option<PathBuf>,
+    pub(crate) task_db_size: usize,
     pub(crate) scheduler: Arc<RwLock<Scheduler>>,
 }
 
@@ -37,6 +43,8 @@ impl SnapshotService {
                 src_path: self.db_path.clone(),
                 meta_env_size: self.meta_env_size,
                 index_size: self.index_size,
+                task_db_path: self.task_db_path.clone(),
+                task_db_size: self.task_db_size,
             };
             self.scheduler.write().await.schedule_snapshot(snapshot_job);
             sleep(self.snapshot_period).await;
@@ -77,13 +85,15 @@ pub fn load_snapshot(
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SnapshotJob {
     dest_path: PathBuf,
     src_path: PathBuf,
 
     meta_env_size: usize,
     index_size: usize,
+    task_db_path: Option<PathBuf>,
+    task_db_size: usize,
 }
 
 impl SnapshotJob {
@@ -103,6 +113,7 @@ impl SnapshotJob {
 
         self.snapshot_version_file(temp_snapshot_path)?;
         self.snapshot_meta_env(temp_snapshot_path)?;
+        self.snapshot_task_env(temp_snapshot_path)?;
         self.snapshot_file_store(temp_snapshot_path)?;
         self.snapshot_indexes(temp_snapshot_path)?;
         self.snapshot_auth(temp_snapshot_path)?;
@@ -152,6 +163,20 @@ impl SnapshotJob {
         Ok(())
     }
 
+    /// When the task store lives in its own environment, it is copied into the `tasks` directory
+    /// of the snapshot, and moved back to the task database when the snapshot is imported.
+    fn snapshot_task_env(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(ref task_db_path) = self.task_db_path {
+            let env = open_meta_env(task_db_path, self.task_db_size)?;
+
+            let dst = path.join(TASKS_SNAPSHOT_DIR);
+            fs::create_dir_all(&dst)?;
+            env.copy_to_path(dst.join("data.mdb"), milli::heed::CompactionOption::Enabled)?;
+        }
+
+        Ok(())
+    }
+
     fn snapshot_file_store(&self, path: &Path) -> anyhow::Result<()> {
         // for now we simply copy the updates/updates_files
         // FIXME(marin): We may copy more files than necessary, if new files are added while we are
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use log::{error, info};
+use time::OffsetDateTime;
+use tokio::time::sleep;
+
+use super::update_file_retention::delete_update_files;
+use super::TaskStore;
+use crate::update_file_store::UpdateFileStore;
+
+/// How often the task store is checked for tasks to archive.
+const ARCHIVE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Periodically moves the tasks that finished long ago out of the task store, into NDJSON files
+/// in `archive_dir`. The update files retained after their task is processed are deleted when
+/// their task is archived, at the latest.
+pub struct TaskArchiveService {
+    pub(crate) task_store: TaskStore,
+    pub(crate) update_file_store: UpdateFileStore,
+    pub(crate) archive_dir: PathBuf,
+    pub(crate) archive_after: Duration,
+}
+
+impl TaskArchiveService {
+    pub async fn run(self) {
+        info!(
+            "Tasks finished for more than {}s will be archived in {:?}.",
+            self.archive_after.as_secs(),
+            self.archive_dir
+        );
+        loop {
+            match self.archive_tasks().await {
+                Ok(0) => (),
+                Ok(count) => info!("Archived {} tasks in {:?}.", count, self.archive_dir),
+                Err(e) => error!("Error while archiving tasks: {}", e),
+            }
+            sleep(ARCHIVE_INTERVAL).await;
+        }
+    }
+
+    async fn archive_tasks(&self) -> anyhow::Result<usize> {
+        let finished_before = OffsetDateTime::now_utc() - self.archive_after;
+        // The update files of the archived tasks wouldn't be found by the retention service
+        // anymore.
+        if self.update_file_store.retains_processed() {
+            delete_update_files(&self.task_store, &self.update_file_store, finished_before).await?;
+        }
+
+        Ok(self
+            .task_store
+            .archive(self.archive_dir.clone(), finished_before)
+            .await?)
+    }
+}
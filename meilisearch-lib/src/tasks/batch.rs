@@ -1,16 +1,21 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
 use time::OffsetDateTime;
 
 use crate::snapshot::SnapshotJob;
 
-use super::task::{Task, TaskEvent};
+use super::task::{Task, TaskEvent, TaskId};
 
 pub type BatchId = u32;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum BatchContent {
     DocumentsAdditionBatch(Vec<Task>),
     IndexUpdate(Task),
     Dump(Task),
+    Barrier(Task),
+    IndexSwap(Task),
     Snapshot(SnapshotJob),
     // Symbolizes a empty batch. This can occur when we were woken, but there wasn't any work to do.
     Empty,
@@ -20,29 +25,50 @@ impl BatchContent {
     pub fn first(&self) -> Option<&Task> {
         match self {
             BatchContent::DocumentsAdditionBatch(ts) => ts.first(),
-            BatchContent::Dump(t) | BatchContent::IndexUpdate(t) => Some(t),
+            BatchContent::Dump(t)
+            | BatchContent::IndexUpdate(t)
+            | BatchContent::Barrier(t)
+            | BatchContent::IndexSwap(t) => Some(t),
             BatchContent::Snapshot(_) | BatchContent::Empty => None,
         }
     }
 
+    /// Returns whether the task `id` is part of this batch.
+    pub fn contains(&self, id: TaskId) -> bool {
+        match self {
+            BatchContent::DocumentsAdditionBatch(ts) => ts.iter().any(|t| t.id == id),
+            BatchContent::Dump(t)
+            | BatchContent::IndexUpdate(t)
+            | BatchContent::Barrier(t)
+            | BatchContent::IndexSwap(t) => t.id == id,
+            BatchContent::Snapshot(_) | BatchContent::Empty => false,
+        }
+    }
+
     pub fn push_event(&mut self, event: TaskEvent) {
         match self {
             BatchContent::DocumentsAdditionBatch(ts) => {
                 ts.iter_mut().for_each(|t| t.events.push(event.clone()))
             }
-            BatchContent::IndexUpdate(t) | BatchContent::Dump(t) => t.events.push(event),
+            BatchContent::IndexUpdate(t)
+            | BatchContent::Dump(t)
+            | BatchContent::Barrier(t)
+            | BatchContent::IndexSwap(t) => t.events.push(event),
             BatchContent::Snapshot(_) | BatchContent::Empty => (),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Batch {
     // Only batches that contains a persistent tasks are given an id. Snapshot batches don't have
     // an id.
     pub id: Option<BatchId>,
     pub created_at: OffsetDateTime,
     pub content: BatchContent,
+    /// Set when the processing of the batch is aborted, the writes of its tasks are then rolled
+    /// back rather than committed.
+    pub aborted: Arc<AtomicBool>,
 }
 
 impl Batch {
@@ -51,12 +77,17 @@ impl Batch {
             id,
             created_at: OffsetDateTime::now_utc(),
             content,
+            aborted: Arc::default(),
         }
     }
     pub fn len(&self) -> usize {
         match self.content {
             BatchContent::DocumentsAdditionBatch(ref ts) => ts.len(),
-            BatchContent::IndexUpdate(_) | BatchContent::Dump(_) | BatchContent::Snapshot(_) => 1,
+            BatchContent::IndexUpdate(_)
+            | BatchContent::Dump(_)
+            | BatchContent::Barrier(_)
+            | BatchContent::IndexSwap(_)
+            | BatchContent::Snapshot(_) => 1,
             BatchContent::Empty => 0,
         }
     }
@@ -70,6 +101,7 @@ impl Batch {
             id: None,
             created_at: OffsetDateTime::now_utc(),
             content: BatchContent::Empty,
+            aborted: Arc::default(),
         }
     }
 }
@@ -12,6 +12,14 @@ pub type Result<T> = std::result::Result<T, TaskError>;
 pub enum TaskError {
     #[error("Task `{0}` not found.")]
     UnexistingTask(TaskId),
+    #[error("The processing of the task was aborted after {0} seconds without completing.")]
+    ProcessingTimeout(u64),
+    #[error("The processing of the task panicked: {0}")]
+    ProcessingPanicked(String),
+    #[error("The processing of the task was aborted on request, while processing task `{0}`.")]
+    ProcessingAborted(TaskId),
+    #[error("Task `{0}` is not being processed.")]
+    TaskNotProcessing(TaskId),
     #[error("Internal error: {0}")]
     Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
 }
@@ -28,6 +36,10 @@ impl ErrorCode for TaskError {
     fn error_code(&self) -> Code {
         match self {
             TaskError::UnexistingTask(_) => Code::TaskNotFound,
+            TaskError::ProcessingTimeout(_)
+            | TaskError::ProcessingPanicked(_)
+            | TaskError::ProcessingAborted(_) => Code::TaskProcessingAborted,
+            TaskError::TaskNotProcessing(_) => Code::TaskNotProcessing,
             TaskError::Internal(_) => Code::Internal,
         }
     }
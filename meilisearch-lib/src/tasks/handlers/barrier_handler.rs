@@ -0,0 +1,27 @@
+use crate::tasks::batch::{Batch, BatchContent};
+use crate::tasks::task::{TaskEvent, TaskResult};
+use crate::tasks::BatchHandler;
+
+/// Handles the barrier tasks. The scheduler only hands a barrier over once all the tasks it was
+/// waiting for are processed, so there is nothing left to do but mark it as succeeded.
+pub struct BarrierHandler;
+
+#[async_trait::async_trait]
+impl BatchHandler for BarrierHandler {
+    fn accept(&self, batch: &Batch) -> bool {
+        matches!(batch.content, BatchContent::Barrier(_))
+    }
+
+    async fn process_batch(&self, mut batch: Batch) -> Batch {
+        match batch.content {
+            BatchContent::Barrier(ref mut task) => {
+                task.events.push(TaskEvent::succeeded(TaskResult::Other));
+            }
+            _ => unreachable!("invalid batch content for barrier"),
+        }
+
+        batch
+    }
+
+    async fn finish(&self, _: &Batch) {}
+}
@@ -12,17 +12,20 @@ where
     fn accept(&self, batch: &Batch) -> bool {
         matches!(
             batch.content,
-            BatchContent::DocumentsAdditionBatch(_) | BatchContent::IndexUpdate(_)
+            BatchContent::DocumentsAdditionBatch(_)
+                | BatchContent::IndexUpdate(_)
+                | BatchContent::IndexSwap(_)
         )
     }
 
     async fn process_batch(&self, mut batch: Batch) -> Batch {
         match batch.content {
             BatchContent::DocumentsAdditionBatch(ref mut tasks) => {
-                self.process_document_addition_batch(tasks).await;
+                self.process_document_addition_batch(tasks, &batch.aborted)
+                    .await;
             }
-            BatchContent::IndexUpdate(ref mut task) => {
-                self.process_task(task).await;
+            BatchContent::IndexUpdate(ref mut task) | BatchContent::IndexSwap(ref mut task) => {
+                self.process_task(task, &batch.aborted).await;
             }
             _ => unreachable!(),
         }
@@ -45,20 +48,27 @@ where
 
 #[cfg(test)]
 mod test {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use meilisearch_auth::Quota;
+
+    use crate::index::{Index, OnDuplicate};
     use crate::index_resolver::index_store::MapIndexStore;
-    use crate::index_resolver::meta_store::HeedMetaStore;
+    use crate::index_resolver::meta_store::{HeedMetaStore, IndexMeta};
     use crate::index_resolver::{
         error::Result as IndexResult, index_store::MockIndexStore, meta_store::MockIndexMetaStore,
     };
     use crate::tasks::{
         handlers::test::task_to_batch,
-        task::{Task, TaskContent},
+        task::{Task, TaskContent, TaskEvent},
     };
     use crate::update_file_store::{Result as FileStoreResult, UpdateFileStore};
 
     use super::*;
+    use futures::future::ok;
     use meilisearch_types::index_uid::IndexUid;
-    use milli::update::IndexDocumentsMethod;
+    use milli::update::{IndexDocumentsMethod, IndexerConfig};
     use nelson::Mocker;
     use proptest::prelude::*;
     use uuid::Uuid;
@@ -78,8 +88,10 @@ mod test {
 
             match batch.content {
                 BatchContent::DocumentsAdditionBatch(_)
-                    | BatchContent::IndexUpdate(_) => assert!(index_resolver.accept(&batch)),
+                    | BatchContent::IndexUpdate(_)
+                    | BatchContent::IndexSwap(_) => assert!(index_resolver.accept(&batch)),
                 BatchContent::Dump(_)
+                    | BatchContent::Barrier(_)
                     | BatchContent::Snapshot(_)
                     | BatchContent::Empty => assert!(!index_resolver.accept(&batch)),
             }
@@ -110,6 +122,10 @@ mod test {
                 primary_key: None,
                 documents_count: 100,
                 allow_index_creation: true,
+                quota: Quota::default(),
+                auto_generate_primary_key: false,
+                merge_nested: false,
+                on_duplicate: OnDuplicate::Replace,
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
@@ -120,6 +136,88 @@ mod test {
         index_resolver.finish(&batch).await;
     }
 
+    #[actix_rt::test]
+    async fn aborted_batch_rolls_back_its_writes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let update_file_store = UpdateFileStore::new(tmp.path()).unwrap();
+        let index = Index::open(
+            tmp.path().join("index"),
+            100 * 1024 * 1024,
+            Uuid::new_v4(),
+            Arc::new(IndexerConfig::default()),
+        )
+        .unwrap();
+
+        let mut meta_store = MockIndexMetaStore::new();
+        meta_store.expect_get().returning(|uid| {
+            Box::pin(ok((
+                uid,
+                Some(IndexMeta {
+                    uuid: Uuid::new_v4(),
+                    creation_task_id: 0,
+                }),
+            )))
+        });
+        let mut index_store = MockIndexStore::new();
+        let cloned = index.clone();
+        index_store
+            .expect_get()
+            .returning(move |_| Box::pin(ok(Some(cloned.clone()))));
+
+        let new_batch = || {
+            let (content_uuid, mut file) = update_file_store.new_update().unwrap();
+            let documents = r#"[{ "id": 1, "title": "Carol" }, { "id": 2, "title": "Hey" }]"#;
+            crate::document_formats::read_json(documents.as_bytes(), &mut *file).unwrap();
+            file.persist().unwrap();
+
+            let task = Task {
+                id: 1,
+                content: TaskContent::DocumentAddition {
+                    content_uuid,
+                    merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                    primary_key: Some(String::from("id")),
+                    documents_count: 2,
+                    allow_index_creation: false,
+                    quota: Quota::default(),
+                    auto_generate_primary_key: false,
+                    merge_nested: false,
+                    on_duplicate: OnDuplicate::Replace,
+                    index_uid: IndexUid::new_unchecked("test"),
+                },
+                events: Vec::new(),
+            };
+            task_to_batch(task)
+        };
+
+        let aborted_batch = new_batch();
+        let batch = new_batch();
+        let index_resolver = IndexResolver::new(meta_store, index_store, update_file_store);
+
+        // the abort is requested while the batch is being processed
+        aborted_batch.aborted.store(true, Ordering::Relaxed);
+        let aborted_batch = index_resolver.process_batch(aborted_batch).await;
+        match aborted_batch.content {
+            BatchContent::DocumentsAdditionBatch(ref tasks) => match tasks[0].events.last() {
+                Some(TaskEvent::Failed { error, .. }) => {
+                    assert_eq!(error.error_code(), "task_processing_aborted")
+                }
+                event => panic!("unexpected last event: {:?}", event),
+            },
+            _ => unreachable!(),
+        }
+        assert_eq!(index.stats().unwrap().number_of_documents, 0);
+
+        let batch = index_resolver.process_batch(batch).await;
+        match batch.content {
+            BatchContent::DocumentsAdditionBatch(ref tasks) => assert!(matches!(
+                tasks[0].events.last(),
+                Some(TaskEvent::Succeeded { .. })
+            )),
+            _ => unreachable!(),
+        }
+        assert_eq!(index.stats().unwrap().number_of_documents, 2);
+    }
+
     #[actix_rt::test]
     #[should_panic]
     async fn panic_when_passed_unsupported_batch() {
@@ -174,7 +272,7 @@ mod test {
                     TaskContent::DocumentAddition { .. } => {
                         mocker.when::<&mut [Task], ()>("process_document_addition_batch").then(|_| ());
                     }
-                    TaskContent::Dump { .. } => (),
+                    TaskContent::Dump { .. } | TaskContent::Barrier { .. } => (),
                     _ => {
                         mocker.when::<&mut Task, ()>("process_task").then(|_| ());
                     }
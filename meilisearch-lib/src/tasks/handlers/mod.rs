@@ -1,12 +1,12 @@
+pub mod barrier_handler;
 pub mod dump_handler;
 pub mod empty_handler;
 mod index_resolver_handler;
+pub mod search_cache_handler;
 pub mod snapshot_handler;
 
 #[cfg(test)]
 mod test {
-    use time::OffsetDateTime;
-
     use crate::tasks::{
         batch::{Batch, BatchContent},
         task::{Task, TaskContent},
@@ -18,17 +18,22 @@ mod test {
                 BatchContent::DocumentsAdditionBatch(vec![task])
             }
             TaskContent::DocumentDeletion { .. }
+            | TaskContent::DocumentDeletionByFilter { .. }
+            | TaskContent::DocumentEdition { .. }
             | TaskContent::SettingsUpdate { .. }
             | TaskContent::IndexDeletion { .. }
             | TaskContent::IndexCreation { .. }
-            | TaskContent::IndexUpdate { .. } => BatchContent::IndexUpdate(task),
+            | TaskContent::IndexUpdate { .. }
+            | TaskContent::IndexClone { .. }
+            | TaskContent::IndexCompaction { .. }
+            | TaskContent::IndexRestoration { .. }
+            | TaskContent::IndexIntegrityCheck { .. }
+            | TaskContent::IndexWarmup { .. } => BatchContent::IndexUpdate(task),
             TaskContent::Dump { .. } => BatchContent::Dump(task),
+            TaskContent::Barrier { .. } => BatchContent::Barrier(task),
+            TaskContent::IndexSwap { .. } => BatchContent::IndexSwap(task),
         };
 
-        Batch {
-            id: Some(1),
-            created_at: OffsetDateTime::now_utc(),
-            content,
-        }
+        Batch::new(Some(1), content)
     }
 }
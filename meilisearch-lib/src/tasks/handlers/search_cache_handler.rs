@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use crate::search_cache::SearchCache;
+use crate::tasks::batch::{Batch, BatchContent};
+use crate::tasks::task::TaskContent;
+use crate::tasks::BatchHandler;
+
+/// Wraps the handler processing the tasks of the indexes, and drops the cached search results of
+/// an index once one of its tasks has been processed.
+pub struct SearchCacheHandler<H> {
+    handler: Arc<H>,
+    cache: SearchCache,
+}
+
+impl<H> SearchCacheHandler<H> {
+    pub fn new(handler: Arc<H>, cache: SearchCache) -> Self {
+        Self { handler, cache }
+    }
+}
+
+#[async_trait::async_trait]
+impl<H: BatchHandler> BatchHandler for SearchCacheHandler<H> {
+    fn accept(&self, batch: &Batch) -> bool {
+        self.handler.accept(batch)
+    }
+
+    async fn process_batch(&self, batch: Batch) -> Batch {
+        self.handler.process_batch(batch).await
+    }
+
+    async fn finish(&self, batch: &Batch) {
+        if let BatchContent::IndexSwap(ref task) = batch.content {
+            if let TaskContent::IndexSwap { ref swaps } = task.content {
+                for (lhs, rhs) in swaps {
+                    self.cache.invalidate(lhs.as_str());
+                    self.cache.invalidate(rhs.as_str());
+                }
+            }
+        }
+
+        let tasks = match batch.content {
+            BatchContent::DocumentsAdditionBatch(ref tasks) => tasks.as_slice(),
+            BatchContent::IndexUpdate(ref task) => std::slice::from_ref(task),
+            _ => &[],
+        };
+        for index_uid in tasks.iter().filter_map(|task| task.index_uid()) {
+            self.cache.invalidate(index_uid);
+        }
+
+        self.handler.finish(batch).await
+    }
+}
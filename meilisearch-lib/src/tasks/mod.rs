@@ -1,9 +1,11 @@
 use async_trait::async_trait;
 
+pub use handlers::barrier_handler::BarrierHandler;
 pub use handlers::empty_handler::EmptyBatchHandler;
+pub use handlers::search_cache_handler::SearchCacheHandler;
 pub use handlers::snapshot_handler::SnapshotHandler;
 pub use scheduler::Scheduler;
-pub use task_store::TaskFilter;
+pub use task_store::{idempotency_key_on_index, DetailsCondition, TaskCounts, TaskFilter};
 
 #[cfg(test)]
 pub use task_store::test::MockTaskStore as TaskStore;
@@ -13,12 +15,14 @@ pub use task_store::TaskStore;
 use batch::Batch;
 use error::Result;
 
+pub mod archive;
 pub mod batch;
 pub mod error;
 mod handlers;
 mod scheduler;
 pub mod task;
 mod task_store;
+pub mod update_file_retention;
 pub mod update_loop;
 
 #[cfg_attr(test, mockall::automock(type Error=test::DebugError;))]
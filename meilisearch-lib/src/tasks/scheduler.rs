@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque};
+use std::collections::{hash_map::Entry, BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
 use std::ops::{Deref, DerefMut};
 use std::slice;
 use std::sync::Arc;
@@ -14,10 +14,10 @@ use crate::options::SchedulerConfig;
 use crate::snapshot::SnapshotJob;
 
 use super::batch::{Batch, BatchContent};
-use super::error::Result;
+use super::error::{Result, TaskError};
 use super::task::{Task, TaskContent, TaskEvent, TaskId};
 use super::update_loop::UpdateLoop;
-use super::{BatchHandler, TaskFilter, TaskStore};
+use super::{BatchHandler, TaskCounts, TaskFilter, TaskStore};
 
 #[derive(Eq, Debug, Clone, Copy)]
 enum TaskType {
@@ -25,6 +25,8 @@ enum TaskType {
     DocumentUpdate { number: usize },
     IndexUpdate,
     Dump,
+    Barrier,
+    IndexSwap,
 }
 
 /// Two tasks are equal if they have the same type.
@@ -66,6 +68,8 @@ impl Ord for PendingTask {
 struct TaskList {
     id: TaskListIdentifier,
     tasks: BinaryHeap<PendingTask>,
+    /// The turn at which this list was last scheduled, 0 if it was never scheduled.
+    last_turn: u64,
 }
 
 impl Deref for TaskList {
@@ -87,6 +91,7 @@ impl TaskList {
         Self {
             id,
             tasks: Default::default(),
+            last_turn: 0,
         }
     }
 }
@@ -102,16 +107,27 @@ impl Eq for TaskList {}
 impl Ord for TaskList {
     fn cmp(&self, other: &Self) -> Ordering {
         match (&self.id, &other.id) {
-            (TaskListIdentifier::Index(_), TaskListIdentifier::Index(_)) => {
+            // The lists are scheduled in a round-robin fashion: the list that waited the longest
+            // since its last turn comes first, and ties are broken by the lowest pending task id.
+            (
+                TaskListIdentifier::Index(_) | TaskListIdentifier::Barrier,
+                TaskListIdentifier::Index(_) | TaskListIdentifier::Barrier,
+            ) => other.last_turn.cmp(&self.last_turn).then_with(|| {
                 match (self.peek(), other.peek()) {
                     (None, None) => Ordering::Equal,
                     (None, Some(_)) => Ordering::Less,
                     (Some(_), None) => Ordering::Greater,
                     (Some(lhs), Some(rhs)) => lhs.cmp(rhs),
                 }
-            }
-            (TaskListIdentifier::Index(_), TaskListIdentifier::Dump) => Ordering::Less,
-            (TaskListIdentifier::Dump, TaskListIdentifier::Index(_)) => Ordering::Greater,
+            }),
+            (
+                TaskListIdentifier::Index(_) | TaskListIdentifier::Barrier,
+                TaskListIdentifier::Dump,
+            ) => Ordering::Less,
+            (
+                TaskListIdentifier::Dump,
+                TaskListIdentifier::Index(_) | TaskListIdentifier::Barrier,
+            ) => Ordering::Greater,
             (TaskListIdentifier::Dump, TaskListIdentifier::Dump) => {
                 unreachable!("There should be only one Dump task list")
             }
@@ -129,6 +145,7 @@ impl PartialOrd for TaskList {
 enum TaskListIdentifier {
     Index(String),
     Dump,
+    Barrier,
 }
 
 impl From<&Task> for TaskListIdentifier {
@@ -136,23 +153,43 @@ impl From<&Task> for TaskListIdentifier {
         match &task.content {
             TaskContent::DocumentAddition { index_uid, .. }
             | TaskContent::DocumentDeletion { index_uid, .. }
+            | TaskContent::DocumentDeletionByFilter { index_uid, .. }
+            | TaskContent::DocumentEdition { index_uid, .. }
             | TaskContent::SettingsUpdate { index_uid, .. }
             | TaskContent::IndexDeletion { index_uid }
             | TaskContent::IndexCreation { index_uid, .. }
-            | TaskContent::IndexUpdate { index_uid, .. } => {
+            | TaskContent::IndexUpdate { index_uid, .. }
+            | TaskContent::IndexCompaction { index_uid }
+            | TaskContent::IndexRestoration { index_uid, .. }
+            | TaskContent::IndexIntegrityCheck { index_uid }
+            | TaskContent::IndexWarmup { index_uid } => {
                 TaskListIdentifier::Index(index_uid.as_str().to_string())
             }
             TaskContent::Dump { .. } => TaskListIdentifier::Dump,
+            // A swap or a clone is processed like a barrier on the indexes it reads and writes.
+            TaskContent::Barrier { .. }
+            | TaskContent::IndexSwap { .. }
+            | TaskContent::IndexClone { .. } => TaskListIdentifier::Barrier,
         }
     }
 }
 
+fn barrier_covers(index_uids: &Option<HashSet<String>>, index_uid: &str) -> bool {
+    index_uids
+        .as_ref()
+        .map_or(true, |index_uids| index_uids.contains(index_uid))
+}
+
 #[derive(Default)]
 struct TaskQueue {
     /// Maps index uids to their TaskList, for quick access
     index_tasks: HashMap<TaskListIdentifier, Arc<AtomicRefCell<TaskList>>>,
     /// A queue that orders TaskList by the priority of their fist update
     queue: BinaryHeap<Arc<AtomicRefCell<TaskList>>>,
+    /// The pending barriers, along with the indexes they are restricted to, if any.
+    barriers: BTreeMap<TaskId, Option<HashSet<String>>>,
+    /// Incremented every time a task list is scheduled.
+    turn: u64,
 }
 
 impl TaskQueue {
@@ -176,11 +213,45 @@ impl TaskQueue {
                 number: documents_count,
             },
             TaskContent::Dump { .. } => TaskType::Dump,
+            TaskContent::Barrier { ref index_uids } => {
+                let index_uids = index_uids
+                    .as_ref()
+                    .map(|uids| uids.iter().map(|uid| uid.as_str().to_string()).collect());
+                self.barriers.insert(id, index_uids);
+                TaskType::Barrier
+            }
+            TaskContent::IndexSwap { ref swaps } => {
+                let index_uids = swaps
+                    .iter()
+                    .flat_map(|(lhs, rhs)| [lhs, rhs])
+                    .map(|uid| uid.as_str().to_string())
+                    .collect();
+                self.barriers.insert(id, Some(index_uids));
+                TaskType::IndexSwap
+            }
+            TaskContent::IndexClone {
+                ref index_uid,
+                ref source_uid,
+                ..
+            } => {
+                let index_uids = [index_uid, source_uid]
+                    .iter()
+                    .map(|uid| uid.as_str().to_string())
+                    .collect();
+                self.barriers.insert(id, Some(index_uids));
+                TaskType::IndexUpdate
+            }
             TaskContent::DocumentDeletion { .. }
+            | TaskContent::DocumentDeletionByFilter { .. }
+            | TaskContent::DocumentEdition { .. }
             | TaskContent::SettingsUpdate { .. }
             | TaskContent::IndexDeletion { .. }
             | TaskContent::IndexCreation { .. }
-            | TaskContent::IndexUpdate { .. } => TaskType::IndexUpdate,
+            | TaskContent::IndexUpdate { .. }
+            | TaskContent::IndexCompaction { .. }
+            | TaskContent::IndexRestoration { .. }
+            | TaskContent::IndexIntegrityCheck { .. }
+            | TaskContent::IndexWarmup { .. } => TaskType::IndexUpdate,
             _ => unreachable!("unhandled task type"),
         };
         let task = PendingTask { kind, id };
@@ -208,13 +279,19 @@ impl TaskQueue {
         }
     }
 
-    /// Passes a context with a view to the task list of the next index to schedule. It is
-    /// guaranteed that the first id from task list will be the lowest pending task id.
-    fn head_mut<R>(&mut self, mut f: impl FnMut(&mut TaskList) -> R) -> Option<R> {
-        let head = self.queue.pop()?;
+    /// Passes a context with a view to the task list of the next index to schedule, along with
+    /// the id of the first pending barrier its tasks must not be processed past, if any.
+    ///
+    /// The task lists take turns, so that the backlog of an index doesn't delay the tasks of the
+    /// other indexes. Within a task list, the first id is always the lowest pending task id.
+    fn head_mut<R>(&mut self, mut f: impl FnMut(&mut TaskList, Option<TaskId>) -> R) -> Option<R> {
+        let head = self.pop_schedulable()?;
+        let next_barrier = self.next_barrier_for(&head.borrow());
+        self.turn += 1;
         let result = {
             let mut ref_head = head.borrow_mut();
-            f(&mut *ref_head)
+            ref_head.last_turn = self.turn;
+            f(&mut *ref_head, next_barrier)
         };
         if !head.borrow().tasks.is_empty() {
             // After being mutated, the head is reinserted to the correct position.
@@ -226,6 +303,64 @@ impl TaskQueue {
         Some(result)
     }
 
+    /// Pops the next task list to schedule, skipping the lists held back by a pending barrier.
+    ///
+    /// There is always a list that can be scheduled: the one containing the lowest pending task
+    /// id, since a barrier only waits for and holds back tasks enqueued before and after it.
+    fn pop_schedulable(&mut self) -> Option<Arc<AtomicRefCell<TaskList>>> {
+        let mut skipped = Vec::new();
+        let head = loop {
+            match self.queue.pop() {
+                Some(list) if self.is_held_back(&list.borrow()) => skipped.push(list),
+                otherwise => break otherwise,
+            }
+        };
+        self.queue.extend(skipped);
+
+        head
+    }
+
+    fn is_held_back(&self, list: &TaskList) -> bool {
+        let head = match list.peek() {
+            Some(head) => head.id,
+            None => return false,
+        };
+
+        match list.id {
+            // An index task can't be processed before a previously enqueued barrier on its index.
+            TaskListIdentifier::Index(ref index_uid) => self
+                .barriers
+                .range(..head)
+                .any(|(_, index_uids)| barrier_covers(index_uids, index_uid)),
+            // A barrier waits for all the previously enqueued tasks on the indexes it covers.
+            TaskListIdentifier::Barrier => {
+                let index_uids = self.barriers.get(&head).and_then(Option::as_ref);
+                self.index_tasks.iter().any(|(id, list)| match id {
+                    TaskListIdentifier::Index(index_uid)
+                        if index_uids.map_or(true, |uids| uids.contains(index_uid)) =>
+                    {
+                        list.borrow().peek().map_or(false, |task| task.id < head)
+                    }
+                    _ => false,
+                })
+            }
+            TaskListIdentifier::Dump => false,
+        }
+    }
+
+    /// Returns the id of the first pending barrier that the tasks of `list` must wait for, if any.
+    fn next_barrier_for(&self, list: &TaskList) -> Option<TaskId> {
+        let index_uid = match list.id {
+            TaskListIdentifier::Index(ref index_uid) => index_uid,
+            TaskListIdentifier::Dump | TaskListIdentifier::Barrier => return None,
+        };
+
+        self.barriers
+            .iter()
+            .find(|(_, index_uids)| barrier_covers(index_uids, index_uid))
+            .map(|(id, _)| *id)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.queue.is_empty() && self.index_tasks.is_empty()
     }
@@ -242,6 +377,10 @@ pub struct Scheduler {
     config: SchedulerConfig,
     /// Notifies the update loop that a new task was received
     notifier: watch::Sender<()>,
+    /// When set, no new batch is prepared.
+    draining: bool,
+    /// Asks the update loop to abort the batch containing the given task.
+    abort_notifier: watch::Sender<Option<TaskId>>,
 }
 
 impl Scheduler {
@@ -251,8 +390,10 @@ impl Scheduler {
         mut config: SchedulerConfig,
     ) -> Result<Arc<RwLock<Self>>> {
         let (notifier, rcv) = watch::channel(());
+        let (abort_notifier, abort_rcv) = watch::channel(None);
 
         let debounce_time = config.debounce_duration_sec;
+        let watchdog_timeout = config.task_watchdog_timeout_sec;
 
         // Disable autobatching
         if !config.enable_auto_batching {
@@ -268,6 +409,8 @@ impl Scheduler {
             next_fetched_task_id: 0,
             config,
             notifier,
+            draining: false,
+            abort_notifier,
         };
 
         // Notify update loop to start processing pending updates immediately after startup.
@@ -279,7 +422,9 @@ impl Scheduler {
             this.clone(),
             performers,
             debounce_time.filter(|&v| v > 0).map(Duration::from_secs),
+            watchdog_timeout.filter(|&v| v > 0).map(Duration::from_secs),
             rcv,
+            abort_rcv,
         );
 
         tokio::task::spawn_local(update_loop.run());
@@ -297,6 +442,35 @@ impl Scheduler {
         self.processing = Processing::Nothing;
     }
 
+    /// Stops preparing new batches. The batch being processed, if any, is processed normally.
+    pub fn drain(&mut self) {
+        self.draining = true;
+    }
+
+    /// Aborts the processing of the batch containing the task `id`: the writes of the batch that
+    /// are not committed yet are rolled back, and once its performer returns, the tasks that didn't
+    /// complete are marked as failed and the scheduling resumes with the next batch.
+    ///
+    /// The abort is persisted first, so that if the process stops before the batch is reported,
+    /// its unfinished tasks are marked as failed instead of being processed again on restart.
+    pub async fn abort(&self, id: TaskId) -> Result<()> {
+        if !self.processing.ids().any(|processing| processing == id) {
+            return Err(TaskError::TaskNotProcessing(id));
+        }
+
+        self.store
+            .abort_tasks(self.processing.ids().collect())
+            .await?;
+        let _ = self.abort_notifier.send(Some(id));
+
+        Ok(())
+    }
+
+    /// Returns whether a batch of tasks is being processed.
+    pub fn is_processing(&self) -> bool {
+        !self.processing.is_nothing()
+    }
+
     pub fn notify(&self) {
         let _ = self.notifier.send(());
     }
@@ -321,6 +495,14 @@ impl Scheduler {
                 let mut tasks = self.store.update_tasks(vec![t]).await?;
                 Ok(BatchContent::Dump(tasks.remove(0)))
             }
+            BatchContent::Barrier(t) => {
+                let mut tasks = self.store.update_tasks(vec![t]).await?;
+                Ok(BatchContent::Barrier(tasks.remove(0)))
+            }
+            BatchContent::IndexSwap(t) => {
+                let mut tasks = self.store.update_tasks(vec![t]).await?;
+                Ok(BatchContent::IndexSwap(tasks.remove(0)))
+            }
             other => Ok(other),
         }
     }
@@ -338,6 +520,17 @@ impl Scheduler {
         self.store.list_tasks(offset, filter, limit).await
     }
 
+    pub async fn list_tasks_with_counts(
+        &self,
+        offset: Option<TaskId>,
+        filter: Option<TaskFilter>,
+        limit: Option<usize>,
+    ) -> Result<(Vec<Task>, TaskCounts)> {
+        self.store
+            .list_tasks_with_counts(offset, filter, limit)
+            .await
+    }
+
     pub async fn get_processing_tasks(&self) -> Result<Vec<Task>> {
         let mut tasks = Vec::new();
 
@@ -355,20 +548,40 @@ impl Scheduler {
     }
 
     async fn fetch_pending_tasks(&mut self) -> Result<()> {
-        self.store
+        let tasks = self
+            .store
             .fetch_unfinished_tasks(Some(self.next_fetched_task_id))
-            .await?
-            .into_iter()
-            .for_each(|t| {
-                self.next_fetched_task_id = t.id + 1;
-                self.register_task(t);
-            });
+            .await?;
+        if tasks.is_empty() {
+            return Ok(());
+        }
+
+        // The tasks aborted before a crash are failed rather than processed again.
+        let aborted_ids = self.store.aborted_task_ids().await?;
+        let mut aborted = Vec::new();
+        for mut task in tasks {
+            self.next_fetched_task_id = task.id + 1;
+            if aborted_ids.contains(task.id) {
+                task.events
+                    .push(TaskEvent::failed(TaskError::ProcessingAborted(task.id)));
+                aborted.push(task);
+            } else {
+                self.register_task(task);
+            }
+        }
+        if !aborted.is_empty() {
+            self.store.update_tasks(aborted).await?;
+        }
 
         Ok(())
     }
 
     /// Prepare the next batch, and set `processing` to the ids in that batch.
     pub async fn prepare(&mut self) -> Result<Batch> {
+        if self.draining {
+            return Ok(Batch::empty());
+        }
+
         // If there is a job to process, do it first.
         if let Some(job) = self.snapshots.pop_front() {
             // There is more work to do, notify the update loop
@@ -421,6 +634,8 @@ pub enum Processing {
     DocumentAdditions(Vec<TaskId>),
     IndexUpdate(TaskId),
     Dump(TaskId),
+    Barrier(TaskId),
+    IndexSwap(TaskId),
     /// Variant used when there is nothing to process.
     Nothing,
 }
@@ -455,7 +670,10 @@ impl Processing {
     pub fn ids(&self) -> impl Iterator<Item = TaskId> + '_ {
         match self {
             Processing::DocumentAdditions(v) => ProcessingIter::Many(v.iter()),
-            Processing::IndexUpdate(id) | Processing::Dump(id) => ProcessingIter::Single(Some(*id)),
+            Processing::IndexUpdate(id)
+            | Processing::Dump(id)
+            | Processing::Barrier(id)
+            | Processing::IndexSwap(id) => ProcessingIter::Single(Some(*id)),
             Processing::Nothing => ProcessingIter::Single(None),
         }
     }
@@ -463,7 +681,10 @@ impl Processing {
     pub fn len(&self) -> usize {
         match self {
             Processing::DocumentAdditions(v) => v.len(),
-            Processing::IndexUpdate(_) | Processing::Dump(_) => 1,
+            Processing::IndexUpdate(_)
+            | Processing::Dump(_)
+            | Processing::Barrier(_)
+            | Processing::IndexSwap(_) => 1,
             Processing::Nothing => 0,
         }
     }
@@ -475,8 +696,8 @@ impl Processing {
 
 fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
     let mut doc_count = 0;
-    tasks
-        .head_mut(|list| match list.peek().copied() {
+    let processing = tasks
+        .head_mut(|list, next_barrier| match list.peek().copied() {
             Some(PendingTask {
                 kind: TaskType::IndexUpdate,
                 id,
@@ -491,11 +712,30 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
                 list.pop();
                 Processing::Dump(id)
             }
+            Some(PendingTask {
+                kind: TaskType::Barrier,
+                id,
+            }) => {
+                list.pop();
+                Processing::Barrier(id)
+            }
+            Some(PendingTask {
+                kind: TaskType::IndexSwap,
+                id,
+            }) => {
+                list.pop();
+                Processing::IndexSwap(id)
+            }
             Some(PendingTask { kind, .. }) => {
                 let mut task_list = Vec::new();
                 loop {
                     match list.peek() {
-                        Some(pending) if pending.kind == kind => {
+                        // Tasks enqueued after a barrier can't be batched with the tasks enqueued
+                        // before it.
+                        Some(pending)
+                            if pending.kind == kind
+                                && next_barrier.map_or(true, |barrier| pending.id < barrier) =>
+                        {
                             // We always need to process at least one task for the scheduler to make progress.
                             if task_list.len() >= config.max_batch_size.unwrap_or(usize::MAX).max(1)
                             {
@@ -529,15 +769,23 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
             }
             None => Processing::Nothing,
         })
-        .unwrap_or(Processing::Nothing)
+        .unwrap_or(Processing::Nothing);
+
+    for id in processing.ids() {
+        tasks.barriers.remove(&id);
+    }
+
+    processing
 }
 
 #[cfg(test)]
 mod test {
+    use meilisearch_auth::Quota;
     use meilisearch_types::index_uid::IndexUid;
     use milli::update::IndexDocumentsMethod;
     use uuid::Uuid;
 
+    use crate::index::OnDuplicate;
     use crate::tasks::task::TaskContent;
 
     use super::*;
@@ -563,13 +811,13 @@ mod test {
         queue.insert(gen_task(6, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
 
         let test1_tasks = queue
-            .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
+            .head_mut(|tasks, _| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
             .unwrap();
 
         assert_eq!(test1_tasks, &[0, 4, 5]);
 
         let test2_tasks = queue
-            .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
+            .head_mut(|tasks, _| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
             .unwrap();
 
         assert_eq!(test2_tasks, &[1, 2, 3, 6]);
@@ -585,6 +833,10 @@ mod test {
             primary_key: Some("test".to_string()),
             documents_count: 0,
             allow_index_creation: true,
+            quota: Quota::default(),
+            auto_generate_primary_key: false,
+            merge_nested: false,
+            on_duplicate: OnDuplicate::Replace,
             index_uid: IndexUid::new_unchecked(index_uid),
         }
     }
@@ -609,24 +861,162 @@ mod test {
         let batch = make_batch(&mut queue, &config);
         assert_eq!(batch, Processing::Dump(8));
 
+        // Then the indexes take turns.
         let batch = make_batch(&mut queue, &config);
         assert_eq!(batch, Processing::DocumentAdditions(vec![0, 4]));
 
         let batch = make_batch(&mut queue, &config);
         assert_eq!(batch, Processing::DocumentAdditions(vec![1]));
 
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(5));
+
         let batch = make_batch(&mut queue, &config);
         assert_eq!(batch, Processing::IndexUpdate(2));
 
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![7]));
+
         let batch = make_batch(&mut queue, &config);
         assert_eq!(batch, Processing::DocumentAdditions(vec![3, 6]));
 
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_make_batch_round_robin() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(1, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(2, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(3, gen_doc_addition_task_content("test2")));
+        queue.insert(gen_task(4, gen_doc_addition_task_content("test3")));
+
+        let config = SchedulerConfig {
+            max_batch_size: Some(1),
+            ..Default::default()
+        };
+
+        // The backlog of test1 doesn't delay test2 and test3.
         let batch = make_batch(&mut queue, &config);
-        assert_eq!(batch, Processing::IndexUpdate(5));
+        assert_eq!(batch, Processing::DocumentAdditions(vec![0]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![3]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![4]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![1]));
+
+        // A new index gets its turn before the indexes that were already scheduled.
+        queue.insert(gen_task(5, gen_doc_addition_task_content("test4")));
 
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![5]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![2]));
+
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_make_batch_with_barriers() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(1, gen_doc_addition_task_content("test2")));
+        queue.insert(gen_task(2, TaskContent::Barrier { index_uids: Some(vec![IndexUid::new_unchecked("test1")]) }));
+        queue.insert(gen_task(3, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(4, gen_doc_addition_task_content("test2")));
+        queue.insert(gen_task(5, TaskContent::Barrier { index_uids: None }));
+        queue.insert(gen_task(6, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(7, gen_doc_addition_task_content("test2")));
+
+        let config = SchedulerConfig::default();
+
+        // The barrier on test1 prevents task 3 from being batched with task 0...
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![0]));
+
+        // ...but doesn't concern test2.
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![1, 4]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::Barrier(2));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![3]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::Barrier(5));
+
+        // test2 took its turn before test1, but couldn't go past the barrier.
         let batch = make_batch(&mut queue, &config);
         assert_eq!(batch, Processing::DocumentAdditions(vec![7]));
 
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![6]));
+
+        assert!(queue.is_empty());
+        assert!(queue.barriers.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_make_batch_with_index_swap() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("test2")));
+        queue.insert(gen_task(1, gen_doc_addition_task_content("test1_new")));
+        queue.insert(gen_task(2, TaskContent::IndexSwap { swaps: vec![(IndexUid::new_unchecked("test1"), IndexUid::new_unchecked("test1_new"))] }));
+        queue.insert(gen_task(3, gen_doc_addition_task_content("test1_new")));
+        queue.insert(gen_task(4, gen_doc_addition_task_content("test2")));
+
+        let config = SchedulerConfig::default();
+
+        // The swap doesn't concern test2...
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![0, 4]));
+
+        // ...but prevents task 3 from being batched with task 1.
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![1]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexSwap(2));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![3]));
+
+        assert!(queue.is_empty());
+        assert!(queue.barriers.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_make_batch_with_index_clone() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(1, TaskContent::IndexClone { index_uid: IndexUid::new_unchecked("test2"), source_uid: IndexUid::new_unchecked("test1"), quota: Quota::default() }));
+        queue.insert(gen_task(2, gen_doc_addition_task_content("test1")));
+
+        let config = SchedulerConfig::default();
+
+        // The clone copies the documents added before it, but not the ones added after it.
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![0]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(1));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![2]));
+
         assert!(queue.is_empty());
+        assert!(queue.barriers.is_empty());
     }
 }
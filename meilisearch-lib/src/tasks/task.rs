@@ -1,21 +1,42 @@
+use meilisearch_auth::Quota;
 use meilisearch_types::error::ResponseError;
 use meilisearch_types::index_uid::IndexUid;
 use milli::update::{DocumentAdditionResult, IndexDocumentsMethod};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
 use super::batch::BatchId;
-use crate::index::{Settings, Unchecked};
+use crate::index::{DocumentError, OnDuplicate, Settings, Unchecked};
 
 pub type TaskId = u32;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(test, derive(proptest_derive::Arbitrary))]
 pub enum TaskResult {
-    DocumentAddition { indexed_documents: u64 },
-    DocumentDeletion { deleted_documents: u64 },
-    ClearAll { deleted_documents: u64 },
+    DocumentAddition {
+        indexed_documents: u64,
+        /// The documents of the payload left out of the addition, up to `MAX_DOCUMENT_ERRORS`.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        failed_documents: Vec<DocumentError>,
+    },
+    DocumentDeletion {
+        deleted_documents: u64,
+    },
+    DocumentEdition {
+        edited_documents: u64,
+        deleted_documents: u64,
+    },
+    ClearAll {
+        deleted_documents: u64,
+    },
+    IntegrityCheck {
+        anomalies: Vec<String>,
+    },
+    Warmup {
+        warmed_bytes: u64,
+    },
     Other,
 }
 
@@ -23,6 +44,7 @@ impl From<DocumentAdditionResult> for TaskResult {
     fn from(other: DocumentAdditionResult) -> Self {
         Self::DocumentAddition {
             indexed_documents: other.indexed_documents,
+            failed_documents: Vec::new(),
         }
     }
 }
@@ -104,6 +126,16 @@ impl Task {
         })
     }
 
+    /// Returns the date at which the task finished, if it is finished.
+    pub fn finished_at(&self) -> Option<OffsetDateTime> {
+        match self.events.last() {
+            Some(TaskEvent::Succeeded { timestamp, .. } | TaskEvent::Failed { timestamp, .. }) => {
+                Some(*timestamp)
+            }
+            _ => None,
+        }
+    }
+
     /// Return the content_uuid of the `Task` if there is one.
     pub fn get_content_uuid(&self) -> Option<Uuid> {
         match self {
@@ -119,13 +151,133 @@ impl Task {
         match &self.content {
             TaskContent::DocumentAddition { index_uid, .. }
             | TaskContent::DocumentDeletion { index_uid, .. }
+            | TaskContent::DocumentDeletionByFilter { index_uid, .. }
+            | TaskContent::DocumentEdition { index_uid, .. }
             | TaskContent::SettingsUpdate { index_uid, .. }
             | TaskContent::IndexDeletion { index_uid }
             | TaskContent::IndexCreation { index_uid, .. }
-            | TaskContent::IndexUpdate { index_uid, .. } => Some(index_uid.as_str()),
-            TaskContent::Dump { .. } => None,
+            | TaskContent::IndexUpdate { index_uid, .. }
+            | TaskContent::IndexClone { index_uid, .. }
+            | TaskContent::IndexCompaction { index_uid }
+            | TaskContent::IndexRestoration { index_uid, .. }
+            | TaskContent::IndexIntegrityCheck { index_uid }
+            | TaskContent::IndexWarmup { index_uid } => Some(index_uid.as_str()),
+            TaskContent::Dump { .. }
+            | TaskContent::Barrier { .. }
+            | TaskContent::IndexSwap { .. } => None,
         }
     }
+
+    /// Returns a field of the details of the task, named as in the `details` of the tasks API,
+    /// e.g. `receivedDocuments`. The fields filled by the result of the task are `null` until the
+    /// task succeeds. Returns `None` when the details of the task don't have this field.
+    pub fn detail(&self, field: &str) -> Option<Value> {
+        let result = match self.events.last() {
+            Some(TaskEvent::Succeeded { result, .. }) => Some(result),
+            _ => None,
+        };
+
+        let value = match (&self.content, field) {
+            (
+                TaskContent::DocumentAddition {
+                    documents_count, ..
+                },
+                "receivedDocuments",
+            ) => {
+                json!(documents_count)
+            }
+            (TaskContent::DocumentAddition { .. }, "indexedDocuments") => match result {
+                Some(TaskResult::DocumentAddition {
+                    indexed_documents, ..
+                }) => json!(indexed_documents),
+                _ => Value::Null,
+            },
+            (TaskContent::DocumentAddition { .. }, "failedDocuments") => match result {
+                Some(TaskResult::DocumentAddition {
+                    failed_documents, ..
+                }) if !failed_documents.is_empty() => json!(failed_documents),
+                _ => return None,
+            },
+            (
+                TaskContent::DocumentDeletion {
+                    deletion: DocumentDeletion::Ids(ids),
+                    ..
+                },
+                "receivedDocumentIds",
+            ) => json!(ids.len()),
+            (
+                TaskContent::DocumentDeletion {
+                    deletion: DocumentDeletion::Ids(_),
+                    ..
+                }
+                | TaskContent::DocumentDeletionByFilter { .. },
+                "deletedDocuments",
+            ) => match result {
+                Some(TaskResult::DocumentDeletion { deleted_documents }) => {
+                    json!(deleted_documents)
+                }
+                _ => Value::Null,
+            },
+            (
+                TaskContent::DocumentDeletion {
+                    deletion: DocumentDeletion::Clear,
+                    ..
+                }
+                | TaskContent::IndexDeletion { .. },
+                "deletedDocuments",
+            ) => match result {
+                Some(TaskResult::ClearAll { deleted_documents }) => json!(deleted_documents),
+                _ => Value::Null,
+            },
+            (TaskContent::DocumentDeletionByFilter { filter, .. }, "originalFilter") => {
+                filter.clone()
+            }
+            (TaskContent::DocumentEdition { function, .. }, "function") => json!(function),
+            (TaskContent::DocumentEdition { filter, .. }, "originalFilter") => json!(filter),
+            (TaskContent::DocumentEdition { .. }, "editedDocuments") => match result {
+                Some(TaskResult::DocumentEdition {
+                    edited_documents, ..
+                }) => json!(edited_documents),
+                _ => Value::Null,
+            },
+            (TaskContent::DocumentEdition { .. }, "deletedDocuments") => match result {
+                Some(TaskResult::DocumentEdition {
+                    deleted_documents, ..
+                }) => json!(deleted_documents),
+                _ => Value::Null,
+            },
+            (TaskContent::SettingsUpdate { settings, .. }, field) => {
+                serde_json::to_value(settings).ok()?.get(field)?.clone()
+            }
+            (
+                TaskContent::IndexCreation { primary_key, .. }
+                | TaskContent::IndexUpdate { primary_key, .. },
+                "primaryKey",
+            ) => json!(primary_key),
+            (TaskContent::IndexUpdate { max_size, .. }, "maxSize") => json!((*max_size)?),
+            (TaskContent::IndexUpdate { frozen, .. }, "frozen") => json!((*frozen)?),
+            (TaskContent::Dump { uid }, "dumpUid") => json!(uid),
+            (TaskContent::Barrier { index_uids }, "indexUids") => json!(index_uids),
+            (TaskContent::IndexSwap { swaps }, "swaps") => Value::Array(
+                swaps
+                    .iter()
+                    .map(|(lhs, rhs)| json!({ "indexes": [lhs, rhs] }))
+                    .collect(),
+            ),
+            (TaskContent::IndexClone { source_uid, .. }, "sourceUid") => json!(source_uid),
+            (TaskContent::IndexIntegrityCheck { .. }, "anomalies") => match result {
+                Some(TaskResult::IntegrityCheck { anomalies }) => json!(anomalies),
+                _ => Value::Null,
+            },
+            (TaskContent::IndexWarmup { .. }, "warmedBytes") => match result {
+                Some(TaskResult::Warmup { warmed_bytes }) => json!(warmed_bytes),
+                _ => Value::Null,
+            },
+            _ => return None,
+        };
+
+        Some(value)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -148,17 +300,49 @@ pub enum TaskContent {
         primary_key: Option<String>,
         documents_count: usize,
         allow_index_creation: bool,
+        /// The limits of the key registering the task, on top of the ones of the instance.
+        #[serde(default)]
+        #[cfg_attr(test, proptest(value = "Quota::default()"))]
+        quota: Quota,
+        /// Whether the documents missing the primary key are given a generated one instead of
+        /// failing.
+        #[serde(default)]
+        auto_generate_primary_key: bool,
+        /// Whether the nested objects of the documents are merged into the ones of the stored
+        /// documents, rather than replacing them.
+        #[serde(default)]
+        merge_nested: bool,
+        /// What happens to the documents whose id is used by a stored document.
+        #[serde(default)]
+        on_duplicate: OnDuplicate,
     },
     DocumentDeletion {
         index_uid: IndexUid,
         deletion: DocumentDeletion,
     },
+    /// Deletes all the documents matching the filter, expressed like the `filter` of a search.
+    DocumentDeletionByFilter {
+        index_uid: IndexUid,
+        #[cfg_attr(test, proptest(strategy = "test::filter_strategy()"))]
+        filter: serde_json::Value,
+    },
+    /// Runs a function on the documents matching the filter, or on every document when there's
+    /// no filter.
+    DocumentEdition {
+        index_uid: IndexUid,
+        function: String,
+        #[cfg_attr(test, proptest(strategy = "test::optional_filter_strategy()"))]
+        filter: Option<serde_json::Value>,
+    },
     SettingsUpdate {
         index_uid: IndexUid,
         settings: Settings<Unchecked>,
         /// Indicates whether the task was a deletion
         is_deletion: bool,
         allow_index_creation: bool,
+        #[serde(default)]
+        #[cfg_attr(test, proptest(value = "Quota::default()"))]
+        quota: Quota,
     },
     IndexDeletion {
         index_uid: IndexUid,
@@ -166,14 +350,60 @@ pub enum TaskContent {
     IndexCreation {
         index_uid: IndexUid,
         primary_key: Option<String>,
+        #[serde(default)]
+        #[cfg_attr(test, proptest(value = "Quota::default()"))]
+        quota: Quota,
     },
     IndexUpdate {
         index_uid: IndexUid,
         primary_key: Option<String>,
+        /// The size of the map of the index, in bytes.
+        #[serde(default)]
+        max_size: Option<usize>,
+        #[serde(default)]
+        frozen: Option<bool>,
     },
     Dump {
         uid: String,
     },
+    /// Waits for all the previously enqueued tasks to be processed before letting the next ones
+    /// be processed. When `index_uids` is set, only the tasks of these indexes are concerned.
+    Barrier {
+        index_uids: Option<Vec<IndexUid>>,
+    },
+    /// Swaps the indexes of each pair of uids, all at once. Like a barrier on these indexes, it
+    /// waits for their previously enqueued tasks, and the next ones apply to the swapped indexes.
+    IndexSwap {
+        swaps: Vec<(IndexUid, IndexUid)>,
+    },
+    /// Creates the index `index_uid` as a copy of the documents and settings of `source_uid`.
+    IndexClone {
+        index_uid: IndexUid,
+        source_uid: IndexUid,
+        #[serde(default)]
+        #[cfg_attr(test, proptest(value = "Quota::default()"))]
+        quota: Quota,
+    },
+    /// Rewrites the environment of the index without its free pages, reclaiming the space left
+    /// by the deleted documents.
+    IndexCompaction {
+        index_uid: IndexUid,
+    },
+    /// Moves a deleted index back from the trash.
+    IndexRestoration {
+        index_uid: IndexUid,
+        #[serde(default)]
+        #[cfg_attr(test, proptest(value = "Quota::default()"))]
+        quota: Quota,
+    },
+    /// Verifies the consistency of the databases of the index, without modifying it.
+    IndexIntegrityCheck {
+        index_uid: IndexUid,
+    },
+    /// Loads the databases used by the searches of the index in memory.
+    IndexWarmup {
+        index_uid: IndexUid,
+    },
 }
 
 #[cfg(test)]
@@ -189,6 +419,14 @@ mod test {
         ]
     }
 
+    pub(super) fn filter_strategy() -> impl Strategy<Value = serde_json::Value> {
+        Just(serde_json::json!("id = 1"))
+    }
+
+    pub(super) fn optional_filter_strategy() -> impl Strategy<Value = Option<serde_json::Value>> {
+        prop_oneof![Just(None), filter_strategy().prop_map(Some)]
+    }
+
     pub(super) fn datetime_strategy() -> impl Strategy<Value = OffsetDateTime> {
         Just(OffsetDateTime::now_utc())
     }
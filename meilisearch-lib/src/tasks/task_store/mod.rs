@@ -2,12 +2,16 @@ mod store;
 
 use std::collections::HashSet;
 use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use log::debug;
+use meilisearch_types::index_uid::matches_pattern;
 use milli::heed::{Env, RwTxn};
+use roaring::RoaringBitmap;
+use serde_json::Value;
 use time::OffsetDateTime;
+use uuid::Uuid;
 
 use super::batch::BatchContent;
 use super::error::TaskError;
@@ -22,40 +26,172 @@ pub use store::test::MockStore as Store;
 #[cfg(not(test))]
 pub use store::Store;
 
+/// The number of tasks archived at once, each chunk in its own file, to bound the memory used by
+/// the archival and the time it holds the write transaction.
+const ARCHIVE_CHUNK_SIZE: usize = 1000;
+
+/// Scopes the idempotency key to the index, so the same key used on two indexes registers two
+/// tasks.
+pub fn idempotency_key_on_index(index_uid: &str, key: &str) -> String {
+    // the uid of an index can contain a `/` separating its namespace, but never a NUL byte, so the
+    // keys scoped to two different indexes can't collide
+    format!("{}\0{}", index_uid, key)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ComparisonOperator {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Lower,
+    LowerOrEqual,
+}
+
+/// A condition on a field of the details of a task, e.g. `receivedDocuments>10000` or
+/// `primaryKey=sku`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetailsCondition {
+    field: String,
+    operator: ComparisonOperator,
+    value: Value,
+}
+
+impl DetailsCondition {
+    /// Parses a `<field><operator><value>` condition. The value is a number, `null`, or a
+    /// string, and only numbers can be compared with `>`, `>=`, `<` and `<=`.
+    pub fn parse(s: &str) -> Option<Self> {
+        use ComparisonOperator::*;
+
+        let start = s.find(|c| matches!(c, '=' | '!' | '<' | '>'))?;
+        let (field, rest) = s.split_at(start);
+        if field.is_empty() || !field.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+
+        let (operator, value) = [
+            (">=", GreaterOrEqual),
+            ("<=", LowerOrEqual),
+            ("!=", NotEqual),
+            (">", Greater),
+            ("<", Lower),
+            ("=", Equal),
+        ]
+        .into_iter()
+        .find_map(|(token, operator)| rest.strip_prefix(token).map(|value| (operator, value)))?;
+
+        let value = match value {
+            "" => return None,
+            "null" => Value::Null,
+            value => match serde_json::from_str::<serde_json::Number>(value) {
+                Ok(number) => Value::Number(number),
+                Err(_) => Value::String(value.to_string()),
+            },
+        };
+
+        match (operator, &value) {
+            (Equal | NotEqual, _) | (_, Value::Number(_)) => Some(Self {
+                field: field.to_string(),
+                operator,
+                value,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the details of the task match the condition. A task that doesn't have the
+    /// field in its details never matches.
+    fn matches(&self, task: &Task) -> bool {
+        use ComparisonOperator::*;
+
+        let field = match task.detail(&self.field) {
+            Some(field) => field,
+            None => return false,
+        };
+
+        match (self.operator, &field, &self.value) {
+            (Equal, Value::Number(l), Value::Number(r)) => l.as_f64() == r.as_f64(),
+            (NotEqual, Value::Number(l), Value::Number(r)) => l.as_f64() != r.as_f64(),
+            (Equal, l, r) => l == r,
+            (NotEqual, l, r) => l != r,
+            (operator, Value::Number(l), Value::Number(r)) => {
+                match l.as_f64().partial_cmp(&r.as_f64()) {
+                    Some(ordering) => match operator {
+                        Greater => ordering.is_gt(),
+                        GreaterOrEqual => ordering.is_ge(),
+                        Lower => ordering.is_lt(),
+                        LowerOrEqual => ordering.is_le(),
+                        Equal | NotEqual => unreachable!(),
+                    },
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
 /// Defines constraints to be applied when querying for Tasks from the store.
 #[derive(Default)]
 pub struct TaskFilter {
     indexes: Option<HashSet<String>>,
+    details: Vec<DetailsCondition>,
     filter_fn: Option<Box<dyn Fn(&Task) -> bool + Sync + Send + 'static>>,
 }
 
 impl TaskFilter {
     fn pass(&self, task: &Task) -> bool {
         match task.index_uid() {
-            Some(index_uid) => self
-                .indexes
-                .as_ref()
-                .map_or(true, |indexes| indexes.contains(index_uid)),
+            Some(index_uid) => self.indexes.as_ref().map_or(true, |indexes| {
+                indexes
+                    .iter()
+                    .any(|pattern| matches_pattern(pattern, index_uid))
+            }),
             None => false,
         }
     }
 
+    /// Whether the filter needs the tasks to be decoded, rather than only their ids.
+    fn filters_tasks(&self) -> bool {
+        self.filter_fn.is_some() || !self.details.is_empty()
+    }
+
+    /// Returns whether the task matches the details conditions and the filter function.
+    fn matches_task(&self, task: &Task) -> bool {
+        self.details.iter().all(|condition| condition.matches(task))
+            && self.filter_fn.as_ref().map_or(true, |f| f(task))
+    }
+
     fn filtered_indexes(&self) -> Option<&HashSet<String>> {
         self.indexes.as_ref()
     }
 
-    /// Adds an index to the filter, so the filter must match this index.
+    /// Adds an index, or a `<namespace>/*` pattern matching every index of a namespace, to the
+    /// filter, so the filter must match this index.
     pub fn filter_index(&mut self, index: String) {
         self.indexes
             .get_or_insert_with(Default::default)
             .insert(index);
     }
 
+    /// Adds a condition on the details of the tasks, which must match along with the other ones.
+    pub fn filter_details(&mut self, condition: DetailsCondition) {
+        self.details.push(condition);
+    }
+
     pub fn filter_fn(&mut self, f: impl Fn(&Task) -> bool + Sync + Send + 'static) {
         self.filter_fn.replace(Box::new(f));
     }
 }
 
+/// The number of tasks matching a filter.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TaskCounts {
+    pub total: usize,
+    /// The matching tasks that are still waiting to be processed.
+    pub enqueued: usize,
+}
+
 pub struct TaskStore {
     store: Arc<Store>,
 }
@@ -97,6 +233,86 @@ impl TaskStore {
         Ok(task)
     }
 
+    /// Registers the content as a task along with the idempotency key, unless a task was already
+    /// registered with the key. Returns the task registered with the key, and whether it's a new
+    /// one.
+    pub async fn register_idempotent(
+        &self,
+        content: TaskContent,
+        idempotency_key: String,
+    ) -> Result<(Task, bool)> {
+        debug!(
+            "registering update with idempotency key {:?}: {:?}",
+            idempotency_key, content
+        );
+        let store = self.store.clone();
+        let result = tokio::task::spawn_blocking(move || -> Result<(Task, bool)> {
+            let mut txn = store.wtxn()?;
+            if let Some(task) = store.get_idempotent_task(&txn, &idempotency_key)? {
+                return Ok((task, false));
+            }
+
+            let next_task_id = store.next_task_id(&mut txn)?;
+            let created_at = TaskEvent::Created(OffsetDateTime::now_utc());
+            let task = Task {
+                id: next_task_id,
+                content,
+                events: vec![created_at],
+            };
+
+            store.put(&mut txn, &task)?;
+            store.put_idempotency_key(&mut txn, &idempotency_key, task.id)?;
+            txn.commit()?;
+
+            Ok((task, true))
+        })
+        .await??;
+
+        Ok(result)
+    }
+
+    /// Returns the task registered with the idempotency key, if any.
+    pub async fn get_idempotent_task(&self, idempotency_key: String) -> Result<Option<Task>> {
+        let store = self.store.clone();
+        let task = tokio::task::spawn_blocking(move || -> Result<_> {
+            let txn = store.rtxn()?;
+            store.get_idempotent_task(&txn, &idempotency_key)
+        })
+        .await??;
+
+        Ok(task)
+    }
+
+    /// Registers all the contents as tasks in a single write transaction. Either all the tasks
+    /// are registered, with contiguous ids and in the same order as `contents`, or none are.
+    pub async fn register_batch(&self, contents: Vec<TaskContent>) -> Result<Vec<Task>> {
+        debug!("registering {} updates: {:?}", contents.len(), contents);
+        let store = self.store.clone();
+        let tasks = tokio::task::spawn_blocking(move || -> Result<Vec<Task>> {
+            let mut txn = store.wtxn()?;
+            let first_task_id = store.next_task_id(&mut txn)?;
+            let created_at = TaskEvent::Created(OffsetDateTime::now_utc());
+
+            let mut tasks = Vec::with_capacity(contents.len());
+            for (id, content) in (first_task_id..).zip(contents) {
+                let task = Task {
+                    id,
+                    content,
+                    events: vec![created_at.clone()],
+                };
+                store.put(&mut txn, &task)?;
+                tasks.push(task);
+            }
+
+            txn.commit()?;
+
+            Ok(tasks)
+        })
+        .await??;
+
+        Ok(tasks)
+    }
+
     pub fn register_raw_update(&self, wtxn: &mut RwTxn, task: &Task) -> Result<()> {
         self.store.put(wtxn, task)?;
         Ok(())
@@ -155,6 +371,16 @@ impl TaskStore {
                     debug_assert!(matches!(task.content, TaskContent::Dump { .. }));
                     BatchContent::Dump(task)
                 }
+                Processing::Barrier(id) => {
+                    let task = store.get(&txn, id)?.ok_or(TaskError::UnexistingTask(id))?;
+                    debug_assert!(matches!(task.content, TaskContent::Barrier { .. }));
+                    BatchContent::Barrier(task)
+                }
+                Processing::IndexSwap(id) => {
+                    let task = store.get(&txn, id)?.ok_or(TaskError::UnexistingTask(id))?;
+                    debug_assert!(matches!(task.content, TaskContent::IndexSwap { .. }));
+                    BatchContent::IndexSwap(task)
+                }
                 Processing::Nothing => BatchContent::Empty,
             };
 
@@ -184,6 +410,31 @@ impl TaskStore {
         Ok(tasks)
     }
 
+    /// Marks the tasks as aborted, so that they're failed instead of processed again if the
+    /// processing of their batch is interrupted, by a crash, before they're finished.
+    pub async fn abort_tasks(&self, ids: RoaringBitmap) -> Result<()> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            let mut txn = store.wtxn()?;
+            store.abort(&mut txn, &ids)?;
+            txn.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Returns the ids of the aborted tasks that aren't finished yet.
+    pub async fn aborted_task_ids(&self) -> Result<RoaringBitmap> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            store.aborted(&txn)
+        })
+        .await?
+    }
+
     pub async fn fetch_unfinished_tasks(&self, offset: Option<TaskId>) -> Result<Vec<Task>> {
         let store = self.store.clone();
 
@@ -211,6 +462,102 @@ impl TaskStore {
         .await?
     }
 
+    /// Same as `list_tasks`, also counting all the tasks matching the filter, whatever the offset
+    /// and the limit.
+    pub async fn list_tasks_with_counts(
+        &self,
+        offset: Option<TaskId>,
+        filter: Option<TaskFilter>,
+        limit: Option<usize>,
+    ) -> Result<(Vec<Task>, TaskCounts)> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            let counts = store.count_tasks(&txn, filter.as_ref())?;
+            let tasks = store.list_tasks(&txn, offset, filter, limit)?;
+            Ok((tasks, counts))
+        })
+        .await?
+    }
+
+    /// Makes sure that all the registered tasks and their updates are persisted on disk.
+    pub async fn flush(&self) -> Result<()> {
+        let store = self.store.clone();
+        tokio::task::spawn_blocking(move || store.sync()).await?
+    }
+
+    /// Returns the uuids of the update files of the tasks that finished before `finished_before`.
+    pub async fn content_uuids_finished_before(
+        &self,
+        finished_before: OffsetDateTime,
+    ) -> Result<Vec<Uuid>> {
+        let store = self.store.clone();
+        tokio::task::spawn_blocking(move || {
+            let rtxn = store.rtxn()?;
+            store.content_uuids_finished_before(&rtxn, finished_before)
+        })
+        .await?
+    }
+
+    /// Moves the tasks that finished before `finished_before` out of the store, into new NDJSON
+    /// files in `dir`. Returns the number of archived tasks.
+    pub async fn archive(&self, dir: PathBuf, finished_before: OffsetDateTime) -> Result<usize> {
+        self.archive_in_chunks(dir, finished_before, ARCHIVE_CHUNK_SIZE)
+            .await
+    }
+
+    /// Archives the tasks `chunk_size` at a time, in a file named after the ids of the first and
+    /// last tasks of the chunk.
+    async fn archive_in_chunks(
+        &self,
+        dir: PathBuf,
+        finished_before: OffsetDateTime,
+        chunk_size: usize,
+    ) -> Result<usize> {
+        let store = self.store.clone();
+        tokio::task::spawn_blocking(move || -> Result<usize> {
+            let mut archived = 0;
+            let mut from = 0;
+            loop {
+                let rtxn = store.rtxn()?;
+                let tasks =
+                    store.finished_tasks_before(&rtxn, finished_before, from, chunk_size)?;
+                drop(rtxn);
+
+                let (first, last) = match (tasks.first(), tasks.last()) {
+                    (Some(first), Some(last)) => (first.id, last.id),
+                    _ => return Ok(archived),
+                };
+
+                // The archive must be safely on disk before the tasks are removed from the store.
+                std::fs::create_dir_all(&dir)?;
+                let mut archive = tempfile::NamedTempFile::new_in(&dir)?;
+                let mut writer = BufWriter::new(archive.as_file_mut());
+                for task in &tasks {
+                    serde_json::to_writer(&mut writer, task)?;
+                    writer.write_all(b"\n")?;
+                }
+                writer.flush()?;
+                drop(writer);
+                archive.as_file().sync_all()?;
+                archive
+                    .persist(dir.join(format!("tasks-{}-{}.ndjson", first, last)))
+                    .map_err(|e| e.error)?;
+
+                let mut wtxn = store.wtxn()?;
+                for task in &tasks {
+                    store.delete(&mut wtxn, task)?;
+                }
+                wtxn.commit()?;
+
+                archived += tasks.len();
+                from = last + 1;
+            }
+        })
+        .await?
+    }
+
     pub async fn dump(
         env: Arc<Env>,
         dir_path: impl AsRef<Path>,
@@ -263,14 +610,53 @@ impl TaskStore {
 
         Ok(())
     }
+
+    /// Moves all the tasks from the `src` environment to the `dst` environment, keeping their ids.
+    /// Used when the task store is moved to its own environment. The tasks already in `dst` must
+    /// be identical to the ones of `src` with the same id: they were copied by a previous migration
+    /// interrupted before it removed them from `src`, which is then completed.
+    pub fn migrate(src: Arc<Env>, dst: Arc<Env>) -> anyhow::Result<()> {
+        let src_store = Self::new(src)?;
+        let rtxn = src_store.store.rtxn()?;
+        let tasks = src_store.store.list_tasks(&rtxn, None, None, None)?;
+        drop(rtxn);
+
+        if tasks.is_empty() {
+            return Ok(());
+        }
+
+        let dst_store = Self::new(dst)?;
+        let mut wtxn = dst_store.store.wtxn()?;
+        for task in tasks.iter() {
+            match dst_store.store.get(&wtxn, task.id)? {
+                Some(copied) if copied == *task => (),
+                Some(_) => anyhow::bail!(
+                    "Can't move the task `{}` to a task database that already contains another task with the same id.",
+                    task.id
+                ),
+                None => dst_store.register_raw_update(&mut wtxn, task)?,
+            }
+        }
+        wtxn.commit()?;
+
+        let mut wtxn = src_store.store.wtxn()?;
+        src_store.store.clear(&mut wtxn)?;
+        wtxn.commit()?;
+
+        log::info!("Moved {} tasks to the task database.", tasks.len());
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 pub mod test {
+    use crate::tasks::task::TaskResult;
     use crate::tasks::{scheduler::Processing, task_store::store::test::tmp_env};
 
     use super::*;
 
+    use meilisearch_auth::Quota;
     use meilisearch_types::index_uid::IndexUid;
     use nelson::Mocker;
     use proptest::{
@@ -335,6 +721,20 @@ pub mod test {
             }
         }
 
+        pub async fn abort_tasks(&self, ids: RoaringBitmap) -> Result<()> {
+            match self {
+                Self::Real(s) => s.abort_tasks(ids).await,
+                Self::Mock(m) => unsafe { m.get("abort_tasks").call(ids) },
+            }
+        }
+
+        pub async fn aborted_task_ids(&self) -> Result<RoaringBitmap> {
+            match self {
+                Self::Real(s) => s.aborted_task_ids().await,
+                Self::Mock(m) => unsafe { m.get("aborted_task_ids").call(()) },
+            }
+        }
+
         pub async fn fetch_unfinished_tasks(&self, from: Option<TaskId>) -> Result<Vec<Task>> {
             match self {
                 Self::Real(s) => s.fetch_unfinished_tasks(from).await,
@@ -354,6 +754,50 @@ pub mod test {
             }
         }
 
+        pub async fn list_tasks_with_counts(
+            &self,
+            from: Option<TaskId>,
+            filter: Option<TaskFilter>,
+            limit: Option<usize>,
+        ) -> Result<(Vec<Task>, TaskCounts)> {
+            match self {
+                Self::Real(s) => s.list_tasks_with_counts(from, filter, limit).await,
+                Self::Mock(m) => unsafe {
+                    m.get("list_tasks_with_counts").call((from, filter, limit))
+                },
+            }
+        }
+
+        pub async fn content_uuids_finished_before(
+            &self,
+            finished_before: OffsetDateTime,
+        ) -> Result<Vec<Uuid>> {
+            match self {
+                Self::Real(s) => s.content_uuids_finished_before(finished_before).await,
+                Self::Mock(m) => unsafe {
+                    m.get("content_uuids_finished_before").call(finished_before)
+                },
+            }
+        }
+
+        pub async fn archive(
+            &self,
+            dir: PathBuf,
+            finished_before: OffsetDateTime,
+        ) -> Result<usize> {
+            match self {
+                Self::Real(s) => s.archive(dir, finished_before).await,
+                Self::Mock(m) => unsafe { m.get("archive").call((dir, finished_before)) },
+            }
+        }
+
+        pub async fn flush(&self) -> Result<()> {
+            match self {
+                Self::Real(s) => s.flush().await,
+                Self::Mock(m) => unsafe { m.get::<_, Result<()>>("flush").call(()) },
+            }
+        }
+
         pub async fn register(&self, content: TaskContent) -> Result<Task> {
             match self {
                 Self::Real(s) => s.register(content).await,
@@ -361,6 +805,31 @@ pub mod test {
             }
         }
 
+        pub async fn register_idempotent(
+            &self,
+            content: TaskContent,
+            idempotency_key: String,
+        ) -> Result<(Task, bool)> {
+            match self {
+                Self::Real(s) => s.register_idempotent(content, idempotency_key).await,
+                Self::Mock(_m) => todo!(),
+            }
+        }
+
+        pub async fn get_idempotent_task(&self, idempotency_key: String) -> Result<Option<Task>> {
+            match self {
+                Self::Real(s) => s.get_idempotent_task(idempotency_key).await,
+                Self::Mock(m) => unsafe { m.get("get_idempotent_task").call(idempotency_key) },
+            }
+        }
+
+        pub async fn register_batch(&self, contents: Vec<TaskContent>) -> Result<Vec<Task>> {
+            match self {
+                Self::Real(s) => s.register_batch(contents).await,
+                Self::Mock(_m) => todo!(),
+            }
+        }
+
         pub fn register_raw_update(&self, wtxn: &mut RwTxn, task: &Task) -> Result<()> {
             match self {
                 Self::Real(s) => s.register_raw_update(wtxn, task),
@@ -371,6 +840,10 @@ pub mod test {
         pub fn load_dump(path: impl AsRef<Path>, env: Arc<Env>) -> anyhow::Result<()> {
             TaskStore::load_dump(path, env)
         }
+
+        pub fn migrate(src: Arc<Env>, dst: Arc<Env>) -> anyhow::Result<()> {
+            TaskStore::migrate(src, dst)
+        }
     }
 
     #[test]
@@ -386,6 +859,7 @@ pub mod test {
             id,
             content: TaskContent::IndexCreation {
                 primary_key: None,
+                quota: Quota::default(),
                 index_uid: IndexUid::new_unchecked("test"),
             },
             events: Vec::new(),
@@ -415,4 +889,285 @@ pub mod test {
             })
             .unwrap();
     }
+
+    #[actix_rt::test]
+    async fn test_register_batch_contiguous_ids() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let task = store
+            .register(TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("other"),
+            })
+            .await
+            .unwrap();
+        assert_eq!(task.id, 0);
+
+        let contents = vec![
+            TaskContent::IndexCreation {
+                primary_key: None,
+                quota: Quota::default(),
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            TaskContent::IndexUpdate {
+                primary_key: Some(String::from("id")),
+                max_size: None,
+                frozen: None,
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+        ];
+
+        let tasks = store.register_batch(contents.clone()).await.unwrap();
+        assert_eq!(
+            tasks.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        for (task, content) in tasks.iter().zip(contents) {
+            let stored = store.get_task(task.id, None).await.unwrap();
+            assert_eq!(stored.content, content);
+            assert!(matches!(stored.events[..], [TaskEvent::Created(_)]));
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_register_idempotent() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+        let content = |uid| TaskContent::IndexDeletion {
+            index_uid: IndexUid::new_unchecked(uid),
+        };
+
+        let (task, registered) = store
+            .register_idempotent(content("test"), "test/key".to_string())
+            .await
+            .unwrap();
+        assert!(registered);
+
+        // the task registered with the key is returned, and nothing is registered
+        let (same, registered) = store
+            .register_idempotent(content("other"), "test/key".to_string())
+            .await
+            .unwrap();
+        assert!(!registered);
+        assert_eq!(same.id, task.id);
+        assert_eq!(same.content, content("test"));
+        let tasks = store.list_tasks(None, None, None).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+
+        let stored = store
+            .get_idempotent_task("test/key".to_string())
+            .await
+            .unwrap();
+        assert_eq!(stored.map(|t| t.id), Some(task.id));
+        let stored = store
+            .get_idempotent_task("test/other-key".to_string())
+            .await
+            .unwrap();
+        assert!(stored.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_idempotency_keys_of_namespaced_indexes() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+        let content = |uid| TaskContent::IndexDeletion {
+            index_uid: IndexUid::new_unchecked(uid),
+        };
+
+        // `tenant-a` with the key `products/k` and `tenant-a/products` with the key `k`
+        let (first, registered) = store
+            .register_idempotent(
+                content("tenant-a"),
+                idempotency_key_on_index("tenant-a", "products/k"),
+            )
+            .await
+            .unwrap();
+        assert!(registered);
+        let (second, registered) = store
+            .register_idempotent(
+                content("tenant-a/products"),
+                idempotency_key_on_index("tenant-a/products", "k"),
+            )
+            .await
+            .unwrap();
+        assert!(registered);
+        assert_ne!(first.id, second.id);
+    }
+
+    #[actix_rt::test]
+    async fn test_migrate_keeps_task_ids() {
+        let src = tmp_env();
+        let dst = tmp_env();
+        let store = TaskStore::new(src.env()).unwrap();
+
+        for _ in 0..3 {
+            store
+                .register(TaskContent::IndexDeletion {
+                    index_uid: IndexUid::new_unchecked("test"),
+                })
+                .await
+                .unwrap();
+        }
+
+        TaskStore::migrate(src.env(), dst.env()).unwrap();
+
+        let tasks = store.list_tasks(None, None, None).await.unwrap();
+        assert!(tasks.is_empty());
+
+        let store = TaskStore::new(dst.env()).unwrap();
+        let tasks = store.list_tasks(None, None, None).await.unwrap();
+        assert_eq!(
+            tasks.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![2, 1, 0]
+        );
+
+        let task = store
+            .register(TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            })
+            .await
+            .unwrap();
+        assert_eq!(task.id, 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_migrate_completes_interrupted_migration() {
+        let src = tmp_env();
+        let dst = tmp_env();
+        let store = TaskStore::new(src.env()).unwrap();
+
+        for _ in 0..3 {
+            store
+                .register(TaskContent::IndexDeletion {
+                    index_uid: IndexUid::new_unchecked("test"),
+                })
+                .await
+                .unwrap();
+        }
+
+        // the tasks were copied, but the migration stopped before removing them from `src`
+        let tasks = store.list_tasks(None, None, None).await.unwrap();
+        let dst_store = TaskStore::new(dst.env()).unwrap();
+        let mut wtxn = dst.env().write_txn().unwrap();
+        for task in tasks.iter().skip(1) {
+            dst_store.register_raw_update(&mut wtxn, task).unwrap();
+        }
+        wtxn.commit().unwrap();
+
+        TaskStore::migrate(src.env(), dst.env()).unwrap();
+
+        assert!(store.list_tasks(None, None, None).await.unwrap().is_empty());
+        assert_eq!(dst_store.list_tasks(None, None, None).await.unwrap(), tasks);
+
+        // a different task with the same id isn't overwritten
+        store
+            .register(TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("other"),
+            })
+            .await
+            .unwrap();
+        assert!(TaskStore::migrate(src.env(), dst.env()).is_err());
+        assert_eq!(dst_store.list_tasks(None, None, None).await.unwrap(), tasks);
+    }
+
+    #[actix_rt::test]
+    async fn test_archive_finished_tasks() {
+        let tmp = tmp_env();
+        let archive_dir = tempfile::tempdir().unwrap();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let mut tasks = Vec::new();
+        for _ in 0..3 {
+            let mut task = store
+                .register(TaskContent::IndexDeletion {
+                    index_uid: IndexUid::new_unchecked("test"),
+                })
+                .await
+                .unwrap();
+            task.events
+                .push(TaskEvent::Processing(OffsetDateTime::now_utc()));
+            task.events.push(TaskEvent::succeeded(TaskResult::Other));
+            tasks.push(task);
+        }
+        store.update_tasks(tasks).await.unwrap();
+
+        let archived = store
+            .archive(
+                archive_dir.path().to_owned(),
+                OffsetDateTime::now_utc() + time::Duration::SECOND,
+            )
+            .await
+            .unwrap();
+        // The last task is kept to preserve the task ids.
+        assert_eq!(archived, 2);
+
+        let content = std::fs::read_to_string(archive_dir.path().join("tasks-0-1.ndjson")).unwrap();
+        let archived_ids: Vec<TaskId> = content
+            .lines()
+            .map(|line| serde_json::from_str::<Task>(line).unwrap().id)
+            .collect();
+        assert_eq!(archived_ids, vec![0, 1]);
+
+        let remaining = store.list_tasks(None, None, None).await.unwrap();
+        assert_eq!(remaining.iter().map(|t| t.id).collect::<Vec<_>>(), vec![2]);
+
+        let task = store
+            .register(TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            })
+            .await
+            .unwrap();
+        assert_eq!(task.id, 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_archive_finished_tasks_in_chunks() {
+        let tmp = tmp_env();
+        let archive_dir = tempfile::tempdir().unwrap();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let mut tasks = Vec::new();
+        for _ in 0..6 {
+            let mut task = store
+                .register(TaskContent::IndexDeletion {
+                    index_uid: IndexUid::new_unchecked("test"),
+                })
+                .await
+                .unwrap();
+            task.events
+                .push(TaskEvent::Processing(OffsetDateTime::now_utc()));
+            task.events.push(TaskEvent::succeeded(TaskResult::Other));
+            tasks.push(task);
+        }
+        // an unfinished task isn't archived, and doesn't end the archival
+        tasks.remove(2);
+        store.update_tasks(tasks).await.unwrap();
+
+        let archived = store
+            .archive_in_chunks(
+                archive_dir.path().to_owned(),
+                OffsetDateTime::now_utc() + time::Duration::SECOND,
+                2,
+            )
+            .await
+            .unwrap();
+        assert_eq!(archived, 4);
+
+        let mut files: Vec<_> = std::fs::read_dir(archive_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["tasks-0-1.ndjson", "tasks-3-4.ndjson"]);
+
+        let remaining = store.list_tasks(None, None, None).await.unwrap();
+        assert_eq!(
+            remaining.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![5, 2]
+        );
+    }
 }
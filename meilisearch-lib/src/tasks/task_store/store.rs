@@ -4,6 +4,18 @@ type BEU32 = milli::heed::zerocopy::U32<milli::heed::byteorder::BE>;
 
 const INDEX_UIDS_TASK_IDS: &str = "index-uids-task-ids";
 const TASKS: &str = "tasks";
+const IDEMPOTENCY_KEYS: &str = "index-idempotency-keys";
+/// Held the idempotency keys scoped to their index with a `/`, before the `/` could separate the
+/// namespace of an index uid.
+const LEGACY_IDEMPOTENCY_KEYS: &str = "idempotency-keys";
+const TASK_IDEMPOTENCY_KEYS: &str = "task-idempotency-keys";
+const ENQUEUED_TASK_IDS: &str = "enqueued-task-ids";
+const ABORTED_TASK_IDS: &str = "aborted-task-ids";
+
+/// The key of the set of the enqueued tasks ids in the `enqueued_task_ids` database.
+const ENQUEUED_KEY: &str = "enqueued";
+/// The key of the set of the aborted tasks ids in the `aborted_task_ids` database.
+const ABORTED_KEY: &str = "aborted";
 
 use std::collections::HashSet;
 use std::ops::Bound::{Excluded, Unbounded};
@@ -14,17 +26,30 @@ use milli::heed::types::{OwnedType, SerdeJson, Str};
 use milli::heed::{Database, Env, RoTxn, RwTxn};
 use milli::heed_codec::RoaringBitmapCodec;
 use roaring::RoaringBitmap;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
-use crate::tasks::task::{Task, TaskId};
+use crate::tasks::task::{Task, TaskEvent, TaskId};
 
 use super::super::Result;
-use super::TaskFilter;
+use super::{idempotency_key_on_index, TaskCounts, TaskFilter};
 
 pub struct Store {
     env: Arc<Env>,
     /// Maps an index uid to the set of tasks ids associated to it.
     index_uid_task_ids: Database<Str, RoaringBitmapCodec>,
     tasks: Database<OwnedType<BEU32>, SerdeJson<Task>>,
+    /// Maps an idempotency key to the task registered with it.
+    idempotency_keys: Database<Str, OwnedType<BEU32>>,
+    /// Maps a task to the idempotency key it was registered with, so the key is deleted along
+    /// with the task.
+    task_idempotency_keys: Database<OwnedType<BEU32>, Str>,
+    /// Holds the set of the ids of the tasks waiting to be processed, so they can be counted
+    /// without decoding every task.
+    enqueued_task_ids: Database<Str, RoaringBitmapCodec>,
+    /// Holds the set of the ids of the tasks whose processing was aborted but that aren't
+    /// finished yet, so they aren't processed again after a crash.
+    aborted_task_ids: Database<Str, RoaringBitmapCodec>,
 }
 
 impl Drop for Store {
@@ -44,11 +69,107 @@ impl Store {
     pub fn new(env: Arc<milli::heed::Env>) -> Result<Self> {
         let index_uid_task_ids = env.create_database(Some(INDEX_UIDS_TASK_IDS))?;
         let tasks = env.create_database(Some(TASKS))?;
+        let aborted_task_ids = env.create_database(Some(ABORTED_TASK_IDS))?;
+        let task_idempotency_keys: Option<Database<OwnedType<BEU32>, Str>> =
+            env.open_database(Some(TASK_IDEMPOTENCY_KEYS))?;
+
+        // The keys scoped to their index with a `/` are scoped again once, by stripping the uid
+        // of the index of their task. The ones whose task was deleted, or was registered on
+        // another index than the one of the key, are dropped.
+        let idempotency_keys = match env.open_database(Some(IDEMPOTENCY_KEYS))? {
+            Some(idempotency_keys) => idempotency_keys,
+            None => {
+                let legacy_idempotency_keys: Option<Database<Str, OwnedType<BEU32>>> =
+                    env.open_database(Some(LEGACY_IDEMPOTENCY_KEYS))?;
+                let mut txn = env.write_txn()?;
+                let idempotency_keys: Database<Str, OwnedType<BEU32>> =
+                    env.create_database_with_txn(Some(IDEMPOTENCY_KEYS), &mut txn)?;
+                if let Some(legacy_idempotency_keys) = legacy_idempotency_keys {
+                    let mut keys = Vec::new();
+                    for result in legacy_idempotency_keys.iter(&txn)? {
+                        let (key, id) = result?;
+                        keys.push((key.to_string(), id));
+                    }
+                    for (key, id) in keys {
+                        let task = tasks.get(&txn, &id)?;
+                        let scoped_key = task.as_ref().and_then(|task| {
+                            let index_uid = task.index_uid()?;
+                            let key = key.strip_prefix(index_uid)?.strip_prefix('/')?;
+                            Some(idempotency_key_on_index(index_uid, key))
+                        });
+                        match (scoped_key, task_idempotency_keys) {
+                            (Some(scoped_key), task_idempotency_keys) => {
+                                idempotency_keys.put(&mut txn, &scoped_key, &id)?;
+                                if let Some(task_idempotency_keys) = task_idempotency_keys {
+                                    task_idempotency_keys.put(&mut txn, &id, &scoped_key)?;
+                                }
+                            }
+                            (None, Some(task_idempotency_keys)) => {
+                                task_idempotency_keys.delete(&mut txn, &id)?;
+                            }
+                            (None, None) => (),
+                        }
+                    }
+                    legacy_idempotency_keys.clear(&mut txn)?;
+                }
+                txn.commit()?;
+                idempotency_keys
+            }
+        };
+
+        // The stores created before the enqueued tasks were tracked get their set computed once.
+        let enqueued_task_ids = match env.open_database(Some(ENQUEUED_TASK_IDS))? {
+            Some(enqueued_task_ids) => enqueued_task_ids,
+            None => {
+                let mut txn = env.write_txn()?;
+                let enqueued_task_ids: Database<Str, RoaringBitmapCodec> =
+                    env.create_database_with_txn(Some(ENQUEUED_TASK_IDS), &mut txn)?;
+                let mut enqueued = RoaringBitmap::new();
+                for result in tasks.iter(&txn)? {
+                    let (id, task) = result?;
+                    if is_enqueued(&task) {
+                        enqueued.insert(id.get());
+                    }
+                }
+                enqueued_task_ids.put(&mut txn, ENQUEUED_KEY, &enqueued)?;
+                txn.commit()?;
+                enqueued_task_ids
+            }
+        };
+
+        // The keys registered before they were deleted along with their task are linked to their
+        // task once, and the ones whose task was deleted since are dropped.
+        let task_idempotency_keys = match task_idempotency_keys {
+            Some(task_idempotency_keys) => task_idempotency_keys,
+            None => {
+                let mut txn = env.write_txn()?;
+                let task_idempotency_keys: Database<OwnedType<BEU32>, Str> =
+                    env.create_database_with_txn(Some(TASK_IDEMPOTENCY_KEYS), &mut txn)?;
+                let mut links = Vec::new();
+                for result in idempotency_keys.iter(&txn)? {
+                    let (key, id) = result?;
+                    links.push((key.to_string(), id));
+                }
+                for (key, id) in links {
+                    if tasks.lazily_decode_data().get(&txn, &id)?.is_some() {
+                        task_idempotency_keys.put(&mut txn, &id, &key)?;
+                    } else {
+                        idempotency_keys.delete(&mut txn, &key)?;
+                    }
+                }
+                txn.commit()?;
+                task_idempotency_keys
+            }
+        };
 
         Ok(Self {
             env,
             index_uid_task_ids,
             tasks,
+            idempotency_keys,
+            task_idempotency_keys,
+            enqueued_task_ids,
+            aborted_task_ids,
         })
     }
 
@@ -60,6 +181,11 @@ impl Store {
         Ok(self.env.read_txn()?)
     }
 
+    /// Flushes the data buffers of the environment to disk.
+    pub fn sync(&self) -> Result<()> {
+        Ok(self.env.force_sync()?)
+    }
+
     /// Returns the id for the next task.
     ///
     /// The required `mut txn` acts as a reservation system. It guarantees that as long as you commit
@@ -88,6 +214,93 @@ impl Store {
             self.index_uid_task_ids.put(txn, index_uid, &tasks_set)?;
         }
 
+        let mut enqueued = self.enqueued(txn)?;
+        let changed = if is_enqueued(task) {
+            enqueued.insert(task.id)
+        } else {
+            enqueued.remove(task.id)
+        };
+        if changed {
+            self.enqueued_task_ids.put(txn, ENQUEUED_KEY, &enqueued)?;
+        }
+
+        if task.is_finished() {
+            self.forget_aborted(txn, task.id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the ids of the tasks waiting to be processed.
+    fn enqueued(&self, txn: &RoTxn) -> Result<RoaringBitmap> {
+        Ok(self
+            .enqueued_task_ids
+            .get(txn, ENQUEUED_KEY)?
+            .unwrap_or_default())
+    }
+
+    /// Marks the tasks as aborted until they're finished.
+    pub fn abort(&self, txn: &mut RwTxn, ids: &RoaringBitmap) -> Result<()> {
+        let aborted = self.aborted(txn)? | ids;
+        self.aborted_task_ids.put(txn, ABORTED_KEY, &aborted)?;
+        Ok(())
+    }
+
+    /// Returns the ids of the aborted tasks that aren't finished yet.
+    pub fn aborted(&self, txn: &RoTxn) -> Result<RoaringBitmap> {
+        Ok(self
+            .aborted_task_ids
+            .get(txn, ABORTED_KEY)?
+            .unwrap_or_default())
+    }
+
+    fn forget_aborted(&self, txn: &mut RwTxn, id: TaskId) -> Result<()> {
+        let mut aborted = self.aborted(txn)?;
+        if aborted.remove(id) {
+            self.aborted_task_ids.put(txn, ABORTED_KEY, &aborted)?;
+        }
+        Ok(())
+    }
+
+    /// Removes all the tasks from the store.
+    pub fn clear(&self, txn: &mut RwTxn) -> Result<()> {
+        self.tasks.clear(txn)?;
+        self.index_uid_task_ids.clear(txn)?;
+        self.enqueued_task_ids.clear(txn)?;
+        self.aborted_task_ids.clear(txn)?;
+        // the ids of the tasks are reused once the store is cleared
+        self.idempotency_keys.clear(txn)?;
+        self.task_idempotency_keys.clear(txn)?;
+        Ok(())
+    }
+
+    /// Removes a task from the store.
+    pub fn delete(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+        self.tasks.delete(txn, &BEU32::new(task.id))?;
+        if let Some(index_uid) = task.index_uid() {
+            if let Some(mut tasks_set) = self.index_uid_task_ids.get(txn, index_uid)? {
+                tasks_set.remove(task.id);
+                if tasks_set.is_empty() {
+                    self.index_uid_task_ids.delete(txn, index_uid)?;
+                } else {
+                    self.index_uid_task_ids.put(txn, index_uid, &tasks_set)?;
+                }
+            }
+        }
+
+        let mut enqueued = self.enqueued(txn)?;
+        if enqueued.remove(task.id) {
+            self.enqueued_task_ids.put(txn, ENQUEUED_KEY, &enqueued)?;
+        }
+        self.forget_aborted(txn, task.id)?;
+
+        let id = BEU32::new(task.id);
+        let key = self.task_idempotency_keys.get(txn, &id)?.map(String::from);
+        if let Some(key) = key {
+            self.idempotency_keys.delete(txn, &key)?;
+            self.task_idempotency_keys.delete(txn, &id)?;
+        }
+
         Ok(())
     }
 
@@ -96,6 +309,21 @@ impl Store {
         Ok(task)
     }
 
+    /// Returns the task registered with the idempotency key. The key is deleted along with its
+    /// task.
+    pub fn get_idempotent_task(&self, txn: &RoTxn, key: &str) -> Result<Option<Task>> {
+        match self.idempotency_keys.get(txn, key)? {
+            Some(id) => self.get(txn, id.get()),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put_idempotency_key(&self, txn: &mut RwTxn, key: &str, id: TaskId) -> Result<()> {
+        self.idempotency_keys.put(txn, key, &BEU32::new(id))?;
+        self.task_idempotency_keys.put(txn, &BEU32::new(id), key)?;
+        Ok(())
+    }
+
     /// Returns the unfinished tasks starting from the given taskId in ascending order.
     pub fn fetch_unfinished_tasks(&self, txn: &RoTxn, from: Option<TaskId>) -> Result<Vec<Task>> {
         // We must NEVER re-enqueue an already processed task! It's content uuid would point to an unexisting file.
@@ -113,6 +341,55 @@ impl Store {
         result.map_err(Into::into)
     }
 
+    /// Returns at most `limit` tasks that finished before the given date, starting from the task
+    /// `from` and in ascending order.
+    ///
+    /// The last task is never returned, even if it is finished, since the id of the next task is
+    /// derived from it.
+    pub fn finished_tasks_before(
+        &self,
+        txn: &RoTxn,
+        before: OffsetDateTime,
+        from: TaskId,
+        limit: usize,
+    ) -> Result<Vec<Task>> {
+        let last_id = match self.tasks.lazily_decode_data().last(txn)? {
+            Some((id, _)) => id.get(),
+            None => return Ok(Vec::new()),
+        };
+
+        let result: StdResult<Vec<_>, milli::heed::Error> = self
+            .tasks
+            .range(txn, &(BEU32::new(from)..BEU32::new(last_id)))?
+            .map(|r| r.map(|(_, t)| t))
+            .filter(|result| {
+                result
+                    .as_ref()
+                    .map_or(true, |t| t.finished_at().map_or(false, |at| at < before))
+            })
+            .take(limit)
+            .collect();
+
+        result.map_err(Into::into)
+    }
+
+    /// Returns the uuids of the update files of the tasks that finished before the given date.
+    pub fn content_uuids_finished_before(
+        &self,
+        txn: &RoTxn,
+        before: OffsetDateTime,
+    ) -> Result<Vec<Uuid>> {
+        let mut uuids = Vec::new();
+        for result in self.tasks.iter(txn)? {
+            let (_, task) = result?;
+            if task.finished_at().map_or(false, |at| at < before) {
+                uuids.extend(task.get_content_uuid());
+            }
+        }
+
+        Ok(uuids)
+    }
+
     /// Returns all the tasks starting from the given taskId and going in descending order.
     pub fn list_tasks(
         &self,
@@ -121,34 +398,76 @@ impl Store {
         filter: Option<TaskFilter>,
         limit: Option<usize>,
     ) -> Result<Vec<Task>> {
-        let from = match from {
-            Some(from) => from,
-            None => self.tasks.last(txn)?.map_or(0, |(id, _)| id.get()),
-        };
+        self.filtered_tasks(txn, from, filter.as_ref())?
+            .take(limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    /// Counts all the tasks matching the filter, and how many of them are still enqueued.
+    ///
+    /// Only a filter function or details conditions require decoding the tasks, the other filters
+    /// are answered with the sets of task ids.
+    pub fn count_tasks(&self, txn: &RoTxn, filter: Option<&TaskFilter>) -> Result<TaskCounts> {
+        if filter.map_or(false, |f| f.filters_tasks()) {
+            return self.filtered_tasks(txn, None, filter)?.try_fold(
+                TaskCounts::default(),
+                |mut counts, task| {
+                    let task = task?;
+                    counts.total += 1;
+                    if is_enqueued(&task) {
+                        counts.enqueued += 1;
+                    }
+                    Ok(counts)
+                },
+            );
+        }
 
-        let filter_fn = |task: &Task| {
-            filter
-                .as_ref()
-                .and_then(|f| f.filter_fn.as_ref())
-                .map_or(true, |f| f(task))
+        let enqueued = self.enqueued(txn)?;
+        let counts = match filter.and_then(|f| f.filtered_indexes()) {
+            Some(indexes) => {
+                let candidates = self.indexes_task_ids(txn, indexes)?;
+                TaskCounts {
+                    total: candidates.len() as usize,
+                    enqueued: candidates.intersection_len(&enqueued) as usize,
+                }
+            }
+            None => TaskCounts {
+                total: self.tasks.len(txn)? as usize,
+                enqueued: enqueued.len() as usize,
+            },
         };
 
-        let result: Result<Vec<_>> = match filter.as_ref().and_then(|f| f.filtered_indexes()) {
-            Some(indexes) => self
-                .compute_candidates(txn, indexes, from)?
-                .filter(|result| result.as_ref().map_or(true, filter_fn))
-                .take(limit.unwrap_or(usize::MAX))
-                .collect(),
-            None => self
-                .tasks
-                .rev_range(txn, &(..=BEU32::new(from)))?
-                .map(|r| r.map(|(_, t)| t).map_err(Into::into))
-                .filter(|result| result.as_ref().map_or(true, filter_fn))
-                .take(limit.unwrap_or(usize::MAX))
-                .collect(),
+        Ok(counts)
+    }
+
+    /// Iterates over the tasks matching the filter, starting from the given taskId and going in
+    /// descending order.
+    fn filtered_tasks<'a>(
+        &'a self,
+        txn: &'a RoTxn,
+        from: Option<TaskId>,
+        filter: Option<&'a TaskFilter>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Task>> + 'a>> {
+        let from = match from {
+            Some(from) => from,
+            None => self.tasks.last(txn)?.map_or(0, |(id, _)| id.get()),
         };
 
-        result.map_err(Into::into)
+        let filter_fn = move |task: &Task| filter.map_or(true, |f| f.matches_task(task));
+
+        let iter: Box<dyn Iterator<Item = Result<Task>> + 'a> =
+            match filter.and_then(|f| f.filtered_indexes()) {
+                Some(indexes) => Box::new(self.compute_candidates(txn, indexes, from)?),
+                None => Box::new(
+                    self.tasks
+                        .rev_range(txn, &(..=BEU32::new(from)))?
+                        .map(|r| r.map(|(_, t)| t).map_err(Into::into)),
+                ),
+            };
+
+        Ok(Box::new(iter.filter(move |result| {
+            result.as_ref().map_or(true, filter_fn)
+        })))
     }
 
     fn compute_candidates<'a>(
@@ -157,14 +476,7 @@ impl Store {
         indexes: &HashSet<String>,
         from: TaskId,
     ) -> Result<impl Iterator<Item = Result<Task>> + 'a> {
-        let mut candidates = RoaringBitmap::new();
-
-        for index_uid in indexes {
-            if let Some(tasks_set) = self.index_uid_task_ids.get(txn, index_uid)? {
-                candidates |= tasks_set;
-            }
-        }
-
+        let mut candidates = self.indexes_task_ids(txn, indexes)?;
         candidates.remove_range((Excluded(from), Unbounded));
 
         let iter = candidates
@@ -174,6 +486,40 @@ impl Store {
 
         Ok(iter)
     }
+
+    /// Returns the ids of the tasks of the indexes, or of the namespaces of the `<namespace>/*`
+    /// patterns.
+    fn indexes_task_ids(&self, txn: &RoTxn, indexes: &HashSet<String>) -> Result<RoaringBitmap> {
+        let mut candidates = RoaringBitmap::new();
+
+        for index_uid in indexes {
+            match index_uid.strip_suffix("/*") {
+                // the pattern targets every index of the namespace
+                Some(namespace) => {
+                    let prefix = format!("{}/", namespace);
+                    for result in self.index_uid_task_ids.prefix_iter(txn, &prefix)? {
+                        let (_, tasks_set) = result?;
+                        candidates |= tasks_set;
+                    }
+                }
+                None => {
+                    if let Some(tasks_set) = self.index_uid_task_ids.get(txn, index_uid)? {
+                        candidates |= tasks_set;
+                    }
+                }
+            }
+        }
+
+        Ok(candidates)
+    }
+}
+
+/// Returns whether the task is waiting to be processed.
+fn is_enqueued(task: &Task) -> bool {
+    matches!(
+        task.events.last(),
+        Some(TaskEvent::Created(_) | TaskEvent::Batched { .. })
+    )
 }
 
 #[cfg(test)]
@@ -184,7 +530,8 @@ pub mod test {
     use nelson::Mocker;
     use tempfile::TempDir;
 
-    use crate::tasks::task::TaskContent;
+    use crate::tasks::task::{TaskContent, TaskResult};
+    use crate::tasks::task_store::DetailsCondition;
 
     use super::*;
 
@@ -233,6 +580,13 @@ pub mod test {
             }
         }
 
+        pub fn sync(&self) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.sync(),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
         pub fn next_task_id(&self, txn: &mut RwTxn) -> Result<TaskId> {
             match self {
                 MockStore::Real(index) => index.next_task_id(txn),
@@ -254,6 +608,34 @@ pub mod test {
             }
         }
 
+        pub fn abort(&self, txn: &mut RwTxn, ids: &RoaringBitmap) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.abort(txn, ids),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn aborted(&self, txn: &RoTxn) -> Result<RoaringBitmap> {
+            match self {
+                MockStore::Real(index) => index.aborted(txn),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn get_idempotent_task(&self, txn: &RoTxn, key: &str) -> Result<Option<Task>> {
+            match self {
+                MockStore::Real(index) => index.get_idempotent_task(txn, key),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn put_idempotency_key(&self, txn: &mut RwTxn, key: &str, id: TaskId) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.put_idempotency_key(txn, key, id),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
         pub fn fetch_unfinished_tasks(
             &self,
             txn: &RoTxn,
@@ -277,6 +659,51 @@ pub mod test {
                 MockStore::Fake(_) => todo!(),
             }
         }
+
+        pub fn count_tasks(&self, txn: &RoTxn, filter: Option<&TaskFilter>) -> Result<TaskCounts> {
+            match self {
+                MockStore::Real(index) => index.count_tasks(txn, filter),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn clear(&self, txn: &mut RwTxn) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.clear(txn),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn delete(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.delete(txn, task),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn finished_tasks_before(
+            &self,
+            txn: &RoTxn,
+            before: OffsetDateTime,
+            from: TaskId,
+            limit: usize,
+        ) -> Result<Vec<Task>> {
+            match self {
+                MockStore::Real(index) => index.finished_tasks_before(txn, before, from, limit),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn content_uuids_finished_before(
+            &self,
+            txn: &RoTxn,
+            before: OffsetDateTime,
+        ) -> Result<Vec<Uuid>> {
+            match self {
+                MockStore::Real(index) => index.content_uuids_finished_before(txn, before),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
     }
 
     #[test]
@@ -377,4 +804,227 @@ pub mod test {
             "test"
         );
     }
+
+    #[test]
+    fn test_idempotency_key_deleted_with_its_task() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let mut txn = store.wtxn().unwrap();
+        let task = Task {
+            id: 0,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+        };
+        store.put(&mut txn, &task).unwrap();
+        store.put_idempotency_key(&mut txn, "key", task.id).unwrap();
+        assert!(store.get_idempotent_task(&txn, "key").unwrap().is_some());
+
+        store.delete(&mut txn, &task).unwrap();
+        assert!(store.idempotency_keys.get(&txn, "key").unwrap().is_none());
+        assert_eq!(store.task_idempotency_keys.len(&txn).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_aborted_task_forgotten_once_finished() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let mut txn = store.wtxn().unwrap();
+        let mut task = Task {
+            id: 0,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+        };
+        store.put(&mut txn, &task).unwrap();
+        store
+            .abort(&mut txn, &[0, 1].into_iter().collect())
+            .unwrap();
+        assert_eq!(
+            store.aborted(&txn).unwrap().iter().collect::<Vec<_>>(),
+            [0, 1]
+        );
+
+        task.events.push(TaskEvent::succeeded(TaskResult::Other));
+        store.put(&mut txn, &task).unwrap();
+        assert_eq!(store.aborted(&txn).unwrap().iter().collect::<Vec<_>>(), [1]);
+    }
+
+    #[test]
+    fn test_migrate_legacy_idempotency_keys() {
+        let tmp = tmp_env();
+        let env = tmp.env();
+
+        let mut txn = env.write_txn().unwrap();
+        let tasks: Database<OwnedType<BEU32>, SerdeJson<Task>> =
+            env.create_database_with_txn(Some(TASKS), &mut txn).unwrap();
+        let legacy: Database<Str, OwnedType<BEU32>> = env
+            .create_database_with_txn(Some(LEGACY_IDEMPOTENCY_KEYS), &mut txn)
+            .unwrap();
+        for (id, index_uid) in ["test", "tenant-a/products"].into_iter().enumerate() {
+            let task = Task {
+                id: id as TaskId,
+                content: TaskContent::IndexDeletion {
+                    index_uid: IndexUid::new_unchecked(index_uid),
+                },
+                events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+            };
+            tasks.put(&mut txn, &BEU32::new(task.id), &task).unwrap();
+        }
+        legacy.put(&mut txn, "test/key", &BEU32::new(0)).unwrap();
+        legacy
+            .put(&mut txn, "tenant-a/products/k", &BEU32::new(1))
+            .unwrap();
+        // the task of this key was deleted
+        legacy.put(&mut txn, "gone/key", &BEU32::new(2)).unwrap();
+        txn.commit().unwrap();
+
+        let store = Store::new(env).unwrap();
+        let txn = store.rtxn().unwrap();
+        let task_id = |index_uid, key| {
+            store
+                .get_idempotent_task(&txn, &idempotency_key_on_index(index_uid, key))
+                .unwrap()
+                .map(|task| task.id)
+        };
+        assert_eq!(task_id("test", "key"), Some(0));
+        assert_eq!(task_id("tenant-a/products", "k"), Some(1));
+        assert_eq!(task_id("tenant-a", "products/k"), None);
+        assert_eq!(store.idempotency_keys.len(&txn).unwrap(), 2);
+        assert_eq!(
+            store
+                .task_idempotency_keys
+                .get(&txn, &BEU32::new(1))
+                .unwrap(),
+            Some(idempotency_key_on_index("tenant-a/products", "k").as_str())
+        );
+        assert_eq!(legacy.len(&txn).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_count_tasks() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let mut txn = store.wtxn().unwrap();
+        for (id, index_uid) in ["test", "test", "other"].into_iter().enumerate() {
+            let mut events = vec![TaskEvent::Created(OffsetDateTime::now_utc())];
+            if id == 0 {
+                events.push(TaskEvent::Processing(OffsetDateTime::now_utc()));
+            }
+            let task = Task {
+                id: id as TaskId,
+                content: TaskContent::IndexDeletion {
+                    index_uid: IndexUid::new_unchecked(index_uid),
+                },
+                events,
+            };
+            store.put(&mut txn, &task).unwrap();
+        }
+
+        let counts = store.count_tasks(&txn, None).unwrap();
+        assert_eq!(
+            counts,
+            TaskCounts {
+                total: 3,
+                enqueued: 2
+            }
+        );
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".into());
+        let counts = store.count_tasks(&txn, Some(&filter)).unwrap();
+        assert_eq!(
+            counts,
+            TaskCounts {
+                total: 2,
+                enqueued: 1
+            }
+        );
+
+        // The counts don't depend on the page of tasks being listed.
+        let tasks = store.list_tasks(&txn, Some(0), Some(filter), None).unwrap();
+        assert_eq!(tasks.len(), 1);
+
+        // The set of the enqueued tasks follows the updates and the deletions of the tasks.
+        let mut task = store.get(&txn, 1).unwrap().unwrap();
+        task.events.push(TaskEvent::Succeeded {
+            result: TaskResult::Other,
+            timestamp: OffsetDateTime::now_utc(),
+        });
+        store.put(&mut txn, &task).unwrap();
+        let task = store.get(&txn, 2).unwrap().unwrap();
+        store.delete(&mut txn, &task).unwrap();
+
+        let counts = store.count_tasks(&txn, None).unwrap();
+        assert_eq!(
+            counts,
+            TaskCounts {
+                total: 2,
+                enqueued: 0
+            }
+        );
+
+        let mut filter = TaskFilter::default();
+        filter.filter_fn(|task| task.id != 1);
+        let counts = store.count_tasks(&txn, Some(&filter)).unwrap();
+        assert_eq!(
+            counts,
+            TaskCounts {
+                total: 1,
+                enqueued: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_filter_details() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let mut txn = store.wtxn().unwrap();
+        for (id, deleted_documents) in [Some(12), Some(3), None].into_iter().enumerate() {
+            let mut events = vec![TaskEvent::Created(OffsetDateTime::now_utc())];
+            if let Some(deleted_documents) = deleted_documents {
+                events.push(TaskEvent::Succeeded {
+                    result: TaskResult::ClearAll { deleted_documents },
+                    timestamp: OffsetDateTime::now_utc(),
+                });
+            }
+            let task = Task {
+                id: id as TaskId,
+                content: TaskContent::IndexDeletion {
+                    index_uid: IndexUid::new_unchecked("test"),
+                },
+                events,
+            };
+            store.put(&mut txn, &task).unwrap();
+        }
+
+        let ids = |conditions: &[&str]| {
+            let mut filter = TaskFilter::default();
+            for condition in conditions {
+                filter.filter_details(DetailsCondition::parse(condition).unwrap());
+            }
+            store
+                .list_tasks(&txn, None, Some(filter), None)
+                .unwrap()
+                .into_iter()
+                .map(|task| task.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(&["deletedDocuments>10"]), vec![0]);
+        assert_eq!(
+            ids(&["deletedDocuments<=12", "deletedDocuments!=12"]),
+            vec![1]
+        );
+        // the result of an enqueued task is still unknown
+        assert_eq!(ids(&["deletedDocuments=null"]), vec![2]);
+        assert_eq!(ids(&["receivedDocuments>0"]), Vec::<TaskId>::new());
+    }
 }
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use log::{error, info};
+use time::OffsetDateTime;
+use tokio::time::sleep;
+
+use super::TaskStore;
+use crate::update_file_store::UpdateFileStore;
+
+/// How often, at most, the update files are checked for deletion.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// The shortest time between two checks of the update files. Every check reads the whole task
+/// store, so a short retention doesn't make the checks more frequent than this.
+const MIN_RETENTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Periodically deletes the update files of the tasks that finished more than `retention` ago,
+/// when the update files are kept after their task is processed.
+pub struct UpdateFileRetentionService {
+    pub(crate) task_store: TaskStore,
+    pub(crate) update_file_store: UpdateFileStore,
+    pub(crate) retention: Duration,
+}
+
+impl UpdateFileRetentionService {
+    pub async fn run(self) {
+        info!(
+            "Update files will be deleted {}s after their task is processed.",
+            self.retention.as_secs()
+        );
+        loop {
+            let finished_before = OffsetDateTime::now_utc() - self.retention;
+            match delete_update_files(&self.task_store, &self.update_file_store, finished_before)
+                .await
+            {
+                Ok(0) => (),
+                Ok(count) => info!("Deleted {} update files.", count),
+                Err(e) => error!("Error while deleting update files: {}", e),
+            }
+            sleep(
+                self.retention
+                    .clamp(MIN_RETENTION_INTERVAL, RETENTION_INTERVAL),
+            )
+            .await;
+        }
+    }
+}
+
+/// Deletes the update files of the tasks that finished before `finished_before`, and returns the
+/// number of deleted update files.
+pub(crate) async fn delete_update_files(
+    task_store: &TaskStore,
+    update_file_store: &UpdateFileStore,
+    finished_before: OffsetDateTime,
+) -> anyhow::Result<usize> {
+    let mut count = 0;
+    for content_uuid in task_store
+        .content_uuids_finished_before(finished_before)
+        .await?
+    {
+        if update_file_store.exists(content_uuid) {
+            update_file_store.delete(content_uuid).await?;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
@@ -1,14 +1,21 @@
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 
+use meilisearch_types::error::ResponseError;
 use time::OffsetDateTime;
 use tokio::sync::{watch, RwLock};
+use tokio::task::JoinError;
 use tokio::time::interval_at;
 
-use super::batch::Batch;
-use super::error::Result;
+use super::batch::{Batch, BatchContent};
+use super::error::{Result, TaskError};
 use super::{BatchHandler, Scheduler};
-use crate::tasks::task::TaskEvent;
+use crate::tasks::task::{TaskEvent, TaskId};
+
+/// How long the performer of an aborted batch is waited for before its tasks are failed without its
+/// result.
+const ABORT_GRACE_PERIOD: Duration = Duration::from_secs(60);
 
 /// The update loop sequentially performs batches of updates by asking the scheduler for a batch,
 /// and handing it to the `TaskPerformer`.
@@ -18,6 +25,10 @@ pub struct UpdateLoop {
 
     notifier: Option<watch::Receiver<()>>,
     debounce_duration: Option<Duration>,
+    /// How long a batch can be processed before it's considered stuck.
+    watchdog_timeout: Option<Duration>,
+    /// Receives the id of a task whose batch must be aborted.
+    abort: watch::Receiver<Option<TaskId>>,
 }
 
 impl UpdateLoop {
@@ -25,13 +36,17 @@ impl UpdateLoop {
         scheduler: Arc<RwLock<Scheduler>>,
         performers: Vec<Arc<dyn BatchHandler + Send + Sync + 'static>>,
         debuf_duration: Option<Duration>,
+        watchdog_timeout: Option<Duration>,
         notifier: watch::Receiver<()>,
+        abort: watch::Receiver<Option<TaskId>>,
     ) -> Self {
         Self {
             scheduler,
             performers,
             debounce_duration: debuf_duration,
+            watchdog_timeout,
             notifier: Some(notifier),
+            abort,
         }
     }
 
@@ -75,7 +90,14 @@ impl UpdateLoop {
                 .await?
         };
 
-        let batch = performer.process_batch(batch).await;
+        let batch = watch_batch(
+            batch,
+            performer.clone(),
+            self.watchdog_timeout,
+            ABORT_GRACE_PERIOD,
+            self.abort.clone(),
+        )
+        .await;
 
         self.handle_batch_result(batch, performer).await?;
 
@@ -101,3 +123,302 @@ impl UpdateLoop {
         Ok(())
     }
 }
+
+/// Processes the batch on its own task, so that a panicking or stuck performer doesn't take the
+/// update loop down with it. When the processing exceeds the watchdog timeout, or when one of its
+/// tasks is aborted on request, the batch is aborted: the writes of its tasks that aren't committed
+/// yet are rolled back, and these tasks are marked as failed.
+///
+/// The blocking indexing work can't be interrupted, so an aborted batch is reported once its
+/// performer has returned. A performer that doesn't return within `grace_period` is left running
+/// in the background, its uncommitted writes are still rolled back when it returns, and all the
+/// tasks of the batch are marked as failed, including the ones whose writes were committed right
+/// before the abort.
+async fn watch_batch(
+    batch: Batch,
+    performer: Arc<dyn BatchHandler + Sync + Send + 'static>,
+    watchdog_timeout: Option<Duration>,
+    grace_period: Duration,
+    mut abort: watch::Receiver<Option<TaskId>>,
+) -> Batch {
+    let mut fallback = batch.clone();
+    let mut handle = tokio::task::spawn_local(async move { performer.process_batch(batch).await });
+
+    let timeout = async {
+        match watchdog_timeout {
+            Some(timeout) => {
+                tokio::time::sleep(timeout).await;
+                TaskError::ProcessingTimeout(timeout.as_secs())
+            }
+            None => std::future::pending().await,
+        }
+    };
+
+    // The abort request may have been sent before the processing of the batch started, and
+    // requests targeting previous batches must be ignored.
+    let aborted = async {
+        loop {
+            if let Some(id) = *abort.borrow_and_update() {
+                if fallback.content.contains(id) {
+                    return id;
+                }
+            }
+            if abort.changed().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    let (error, running) = tokio::select! {
+        result = &mut handle => match result {
+            Ok(batch) => return batch,
+            Err(e) => (panic_error(e), false),
+        },
+        error = timeout => (error, true),
+        id = aborted => (TaskError::ProcessingAborted(id), true),
+    };
+
+    if !running {
+        log::error!(
+            "the processing of batch {:?} panicked: {}",
+            fallback.id,
+            error
+        );
+        fallback.content.push_event(TaskEvent::failed(error));
+        return fallback;
+    }
+
+    log::error!(
+        "aborting the processing of batch {:?}: {}",
+        fallback.id,
+        error
+    );
+    fallback.aborted.store(true, Ordering::Relaxed);
+
+    match tokio::time::timeout(grace_period, handle).await {
+        Ok(Ok(mut batch)) => {
+            explain_abort(&mut batch.content, error.into());
+            batch
+        }
+        Ok(Err(e)) => {
+            fallback
+                .content
+                .push_event(TaskEvent::failed(panic_error(e)));
+            fallback
+        }
+        Err(_) => {
+            log::error!(
+                "the processing of batch {:?} didn't stop {} seconds after its abort, its tasks are failed",
+                fallback.id,
+                grace_period.as_secs()
+            );
+            fallback.content.push_event(TaskEvent::failed(error));
+            fallback
+        }
+    }
+}
+
+/// Replaces the errors of the tasks whose writes were rolled back by the abort of their batch with
+/// `error`, telling why the batch was aborted. The tasks whose writes were committed before the
+/// abort keep their result.
+fn explain_abort(content: &mut BatchContent, error: ResponseError) {
+    let tasks = match content {
+        BatchContent::DocumentsAdditionBatch(tasks) => tasks.as_mut_slice(),
+        BatchContent::IndexUpdate(task)
+        | BatchContent::Dump(task)
+        | BatchContent::Barrier(task)
+        | BatchContent::IndexSwap(task) => std::slice::from_mut(task),
+        BatchContent::Snapshot(_) | BatchContent::Empty => &mut [],
+    };
+    for task in tasks {
+        if let Some(TaskEvent::Failed {
+            error: task_error, ..
+        }) = task.events.last_mut()
+        {
+            if task_error.error_code() == error.error_code() {
+                *task_error = error.clone();
+            }
+        }
+    }
+}
+
+fn panic_error(error: JoinError) -> TaskError {
+    match error.try_into_panic() {
+        Ok(panic) => {
+            let message = match panic.downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => match panic.downcast_ref::<String>() {
+                    Some(message) => message.clone(),
+                    None => "unknown panic payload".to_string(),
+                },
+            };
+            TaskError::ProcessingPanicked(message)
+        }
+        Err(e) => TaskError::from(e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::AtomicBool;
+
+    use meilisearch_types::index_uid::IndexUid;
+
+    use super::*;
+    use crate::index::error::IndexError;
+    use crate::tasks::task::{Task, TaskContent, TaskResult};
+
+    /// Sleeps for `duration`, then rolls its write back if the batch was aborted by then, unless
+    /// it ignores the abort, like a write committed right before it.
+    struct SleepingHandler {
+        duration: Duration,
+        ignores_abort: bool,
+        returned: AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl BatchHandler for SleepingHandler {
+        fn accept(&self, _: &Batch) -> bool {
+            true
+        }
+
+        async fn process_batch(&self, mut batch: Batch) -> Batch {
+            tokio::time::sleep(self.duration).await;
+            let event = if batch.aborted.load(Ordering::Relaxed) && !self.ignores_abort {
+                TaskEvent::failed(IndexError::WriteAborted)
+            } else {
+                TaskEvent::succeeded(TaskResult::Other)
+            };
+            batch.content.push_event(event);
+            self.returned.store(true, Ordering::Relaxed);
+            batch
+        }
+
+        async fn finish(&self, _: &Batch) {}
+    }
+
+    struct PanickingHandler;
+
+    #[async_trait::async_trait]
+    impl BatchHandler for PanickingHandler {
+        fn accept(&self, _: &Batch) -> bool {
+            true
+        }
+
+        async fn process_batch(&self, _: Batch) -> Batch {
+            panic!("the performer panicked");
+        }
+
+        async fn finish(&self, _: &Batch) {}
+    }
+
+    fn batch() -> Batch {
+        let task = Task {
+            id: 1,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+            },
+            events: Vec::new(),
+        };
+        Batch::new(Some(1), BatchContent::IndexUpdate(task))
+    }
+
+    fn last_event(batch: &Batch) -> TaskEvent {
+        batch
+            .content
+            .first()
+            .unwrap()
+            .events
+            .last()
+            .unwrap()
+            .clone()
+    }
+
+    #[actix_rt::test]
+    async fn timeout_waits_for_the_performer() {
+        let (_sender, abort) = watch::channel(None);
+        let handler = Arc::new(SleepingHandler {
+            duration: Duration::from_millis(300),
+            ignores_abort: false,
+            returned: AtomicBool::new(false),
+        });
+
+        let timeout = Some(Duration::from_millis(50));
+        let grace_period = Duration::from_secs(1);
+        let batch = watch_batch(batch(), handler.clone(), timeout, grace_period, abort).await;
+
+        assert!(handler.returned.load(Ordering::Relaxed));
+        match last_event(&batch) {
+            TaskEvent::Failed { error, .. } => {
+                assert_eq!(error.error_code(), "task_processing_aborted");
+                assert!(error.to_string().contains("after 0 seconds"), "{}", error);
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn timeout_keeps_committed_writes() {
+        let (_sender, abort) = watch::channel(None);
+        let handler = Arc::new(SleepingHandler {
+            duration: Duration::from_millis(300),
+            ignores_abort: true,
+            returned: AtomicBool::new(false),
+        });
+
+        let timeout = Some(Duration::from_millis(50));
+        let grace_period = Duration::from_secs(1);
+        let batch = watch_batch(batch(), handler, timeout, grace_period, abort).await;
+
+        assert!(matches!(last_event(&batch), TaskEvent::Succeeded { .. }));
+    }
+
+    #[actix_rt::test]
+    async fn timeout_gives_up_on_a_stuck_performer() {
+        let (_sender, abort) = watch::channel(None);
+        let handler = Arc::new(SleepingHandler {
+            duration: Duration::from_secs(5),
+            ignores_abort: false,
+            returned: AtomicBool::new(false),
+        });
+
+        let timeout = Some(Duration::from_millis(50));
+        let grace_period = Duration::from_millis(50);
+        let batch = watch_batch(batch(), handler.clone(), timeout, grace_period, abort).await;
+
+        assert!(!handler.returned.load(Ordering::Relaxed));
+        match last_event(&batch) {
+            TaskEvent::Failed { error, .. } => {
+                assert_eq!(error.error_code(), "task_processing_aborted");
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn panicking_performer() {
+        let (_sender, abort) = watch::channel(None);
+
+        let grace_period = Duration::from_secs(1);
+        let batch = watch_batch(
+            batch(),
+            Arc::new(PanickingHandler),
+            None,
+            grace_period,
+            abort,
+        )
+        .await;
+
+        match last_event(&batch) {
+            TaskEvent::Failed { error, .. } => {
+                assert_eq!(error.error_code(), "task_processing_aborted");
+                assert!(
+                    error.to_string().contains("the performer panicked"),
+                    "{}",
+                    error
+                );
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+}
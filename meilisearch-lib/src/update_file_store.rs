@@ -1,10 +1,15 @@
+use std::collections::{BTreeSet, HashMap};
 use std::fs::{create_dir_all, File};
-use std::io::{self, BufReader, BufWriter, Write};
+use std::io::{self, BufReader, BufWriter, Seek, SeekFrom, Write};
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use log::debug;
 use milli::documents::DocumentBatchReader;
+use parking_lot::Mutex;
 use serde_json::Map;
+use sha2::{Digest, Sha256};
 use tempfile::{NamedTempFile, PersistError};
 use uuid::Uuid;
 
@@ -18,8 +23,44 @@ const UPDATE_FILES_PATH: &str = "updates/updates_files";
 use crate::document_formats::read_ndjson;
 
 pub struct UpdateFile {
+    uuid: Uuid,
     path: PathBuf,
     file: NamedTempFile,
+    contents: Arc<Mutex<UpdateFileContents>>,
+}
+
+type ContentHash = [u8; 32];
+
+/// The hashes of the contents of the update files persisted since the store was opened. The
+/// update files with the same content are hard links to a single file, which the file system
+/// removes along with its last link.
+#[derive(Debug, Default)]
+struct UpdateFileContents {
+    uuids: HashMap<ContentHash, BTreeSet<Uuid>>,
+    hashes: HashMap<Uuid, ContentHash>,
+}
+
+impl UpdateFileContents {
+    /// Returns an update file with the content, if any.
+    fn get(&self, hash: &ContentHash) -> Option<Uuid> {
+        self.uuids.get(hash)?.iter().next().copied()
+    }
+
+    fn insert(&mut self, hash: ContentHash, uuid: Uuid) {
+        self.uuids.entry(hash).or_default().insert(uuid);
+        self.hashes.insert(uuid, hash);
+    }
+
+    fn remove(&mut self, uuid: Uuid) {
+        if let Some(hash) = self.hashes.remove(&uuid) {
+            if let Some(uuids) = self.uuids.get_mut(&hash) {
+                uuids.remove(&uuid);
+                if uuids.is_empty() {
+                    self.uuids.remove(&hash);
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -48,12 +89,44 @@ into_update_store_error!(
 );
 
 impl UpdateFile {
-    pub fn persist(self) -> Result<()> {
+    /// Persists the update file. When another update file has the same content, the update file
+    /// is made a hard link to it rather than a copy of the content.
+    pub fn persist(mut self) -> Result<()> {
+        let hash = content_hash(self.file.as_file_mut())?;
+
+        // the lock is held until the update file is persisted, so the update file it's linked to
+        // isn't deleted in the meantime
+        let mut contents = self.contents.lock();
+        if let Some(uuid) = contents.get(&hash) {
+            let src = self.path.with_file_name(uuid.to_string());
+            match std::fs::hard_link(&src, &self.path) {
+                Ok(()) => {
+                    debug!("update file {} shares its content with {}", self.uuid, uuid);
+                    contents.insert(hash, self.uuid);
+                    return Ok(());
+                }
+                // the content is written to the disk again
+                Err(e) => debug!(
+                    "can't link the update file {} to {}: {}",
+                    self.uuid, uuid, e
+                ),
+            }
+        }
+
         self.file.persist(&self.path)?;
+        contents.insert(hash, self.uuid);
+
         Ok(())
     }
 }
 
+fn content_hash(file: &mut File) -> Result<ContentHash> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut hasher = Sha256::new();
+    io::copy(file, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
 impl Deref for UpdateFile {
     type Target = NamedTempFile;
 
@@ -74,6 +147,8 @@ mod store {
     #[derive(Clone, Debug)]
     pub struct UpdateFileStore {
         path: PathBuf,
+        contents: Arc<Mutex<UpdateFileContents>>,
+        retain_processed: bool,
     }
 
     impl UpdateFileStore {
@@ -108,7 +183,21 @@ mod store {
         pub fn new(path: impl AsRef<Path>) -> Result<Self> {
             let path = path.as_ref().join(UPDATE_FILES_PATH);
             std::fs::create_dir_all(&path)?;
-            Ok(Self { path })
+            Ok(Self {
+                path,
+                contents: Arc::default(),
+                retain_processed: false,
+            })
+        }
+
+        /// Keeps the update files once their task is processed, until they're deleted by the
+        /// update file retention service.
+        pub fn set_retain_processed(&mut self, retain_processed: bool) {
+            self.retain_processed = retain_processed;
+        }
+
+        pub fn retains_processed(&self) -> bool {
+            self.retain_processed
         }
 
         /// Creates a new temporary update file.
@@ -117,7 +206,12 @@ mod store {
             let file = NamedTempFile::new_in(&self.path)?;
             let uuid = Uuid::new_v4();
             let path = self.path.join(uuid.to_string());
-            let update_file = UpdateFile { file, path };
+            let update_file = UpdateFile {
+                uuid,
+                path,
+                file,
+                contents: self.contents.clone(),
+            };
 
             Ok((uuid, update_file))
         }
@@ -129,6 +223,14 @@ mod store {
             Ok(file)
         }
 
+        /// Makes sure the update file corresponding to `uuid` exists and can be read as a
+        /// document batch.
+        pub fn check_update(&self, uuid: Uuid) -> Result<()> {
+            let file = self.get_update(uuid)?;
+            DocumentBatchReader::from_reader(file)?;
+            Ok(())
+        }
+
         /// Copies the content of the update file pointed to by `uuid` to the `dst` directory.
         pub fn snapshot(&self, uuid: Uuid, dst: impl AsRef<Path>) -> Result<()> {
             let src = self.path.join(uuid.to_string());
@@ -176,7 +278,14 @@ mod store {
             Ok(self.get_update(uuid)?.metadata()?.len())
         }
 
+        pub fn exists(&self, uuid: Uuid) -> bool {
+            self.path.join(uuid.to_string()).exists()
+        }
+
+        /// Deletes the update file. Its content stays on the disk as long as other update files
+        /// share it.
         pub async fn delete(&self, uuid: Uuid) -> Result<()> {
+            self.contents.lock().remove(uuid);
             let path = self.path.join(uuid.to_string());
             tokio::fs::remove_file(path).await?;
             Ok(())
@@ -211,6 +320,20 @@ mod test {
             store::UpdateFileStore::new(path).map(Self::Real)
         }
 
+        pub fn set_retain_processed(&mut self, retain_processed: bool) {
+            match self {
+                MockUpdateFileStore::Real(s) => s.set_retain_processed(retain_processed),
+                MockUpdateFileStore::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn retains_processed(&self) -> bool {
+            match self {
+                MockUpdateFileStore::Real(s) => s.retains_processed(),
+                MockUpdateFileStore::Mock(_) => false,
+            }
+        }
+
         pub fn new_update(&self) -> Result<(Uuid, UpdateFile)> {
             match self {
                 MockUpdateFileStore::Real(s) => s.new_update(),
@@ -225,6 +348,15 @@ mod test {
             }
         }
 
+        pub fn check_update(&self, uuid: Uuid) -> Result<()> {
+            match self {
+                MockUpdateFileStore::Real(s) => s.check_update(uuid),
+                MockUpdateFileStore::Mock(mocker) => unsafe {
+                    mocker.get("check_update").call(uuid)
+                },
+            }
+        }
+
         pub fn snapshot(&self, uuid: Uuid, dst: impl AsRef<Path>) -> Result<()> {
             match self {
                 MockUpdateFileStore::Real(s) => s.snapshot(uuid, dst),
@@ -246,6 +378,13 @@ mod test {
             }
         }
 
+        pub fn exists(&self, uuid: Uuid) -> bool {
+            match self {
+                MockUpdateFileStore::Real(s) => s.exists(uuid),
+                MockUpdateFileStore::Mock(mocker) => unsafe { mocker.get("exists").call(uuid) },
+            }
+        }
+
         pub async fn delete(&self, uuid: Uuid) -> Result<()> {
             match self {
                 MockUpdateFileStore::Real(s) => s.delete(uuid).await,
@@ -253,4 +392,46 @@ mod test {
             }
         }
     }
+
+    fn write_update(store: &store::UpdateFileStore, content: &[u8]) -> Uuid {
+        let (uuid, mut file) = store.new_update().unwrap();
+        file.write_all(content).unwrap();
+        file.persist().unwrap();
+        uuid
+    }
+
+    fn read_update(store: &store::UpdateFileStore, uuid: Uuid) -> Vec<u8> {
+        let mut content = Vec::new();
+        io::Read::read_to_end(&mut store.get_update(uuid).unwrap(), &mut content).unwrap();
+        content
+    }
+
+    #[actix_rt::test]
+    async fn test_deduplicate_update_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store::UpdateFileStore::new(dir.path()).unwrap();
+
+        let first = write_update(&store, b"hello");
+        let second = write_update(&store, b"hello");
+        let third = write_update(&store, b"world");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            let inode = |uuid: Uuid| store.get_update(uuid).unwrap().metadata().unwrap().ino();
+            assert_eq!(inode(first), inode(second));
+            assert_ne!(inode(first), inode(third));
+        }
+
+        // the content is kept as long as an update file refers to it
+        store.delete(first).await.unwrap();
+        assert_eq!(read_update(&store, second), b"hello");
+        assert!(store.get_update(first).is_err());
+
+        let fourth = write_update(&store, b"hello");
+        store.delete(second).await.unwrap();
+        assert_eq!(read_update(&store, fourth), b"hello");
+        assert_eq!(read_update(&store, third), b"world");
+    }
 }
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::error::PayloadError;
+use bytes::Bytes;
+use futures::StreamExt;
+use log::{error, info};
+use meilisearch_types::error::{Code, ErrorCode};
+use meilisearch_types::index_uid::IndexUid;
+use meilisearch_types::internal_error;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tokio::time::sleep;
+use uuid::Uuid;
+
+use crate::index_controller::Payload;
+
+const UPLOADS_PATH: &str = "updates/uploads";
+const UPLOAD_META_FILE: &str = "meta.json";
+const UPLOAD_PAYLOAD_FILE: &str = "payload";
+
+/// The size of the chunks an assembled payload is read in when its upload is committed.
+const UPLOAD_READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// How often, at most, the uploads are checked for deletion.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub type Result<T> = std::result::Result<T, UploadStoreError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum UploadStoreError {
+    #[error("Upload `{0}` not found.")]
+    UploadNotFound(Uuid),
+    #[error("The chunk starts at offset {offset}, but {size} bytes of the payload have been uploaded. The next chunk must start at offset {size}.")]
+    InvalidOffset { offset: u64, size: u64 },
+    #[error("The provided payload reached the size limit.")]
+    PayloadTooLarge,
+    #[error("An internal error has occurred. `{0}`.")]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+internal_error!(UploadStoreError: std::io::Error, serde_json::Error);
+
+impl From<PayloadError> for UploadStoreError {
+    fn from(other: PayloadError) -> Self {
+        match other {
+            PayloadError::Overflow => Self::PayloadTooLarge,
+            _ => Self::Internal(Box::new(other)),
+        }
+    }
+}
+
+impl ErrorCode for UploadStoreError {
+    fn error_code(&self) -> Code {
+        match self {
+            UploadStoreError::UploadNotFound(_) => Code::UploadNotFound,
+            UploadStoreError::InvalidOffset { .. } => Code::InvalidUploadOffset,
+            UploadStoreError::PayloadTooLarge => Code::PayloadTooLarge,
+            UploadStoreError::Internal(_) => Code::Internal,
+        }
+    }
+}
+
+/// A payload of a document addition uploaded in several chunks, so an interrupted upload is
+/// resumed from the size already received.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Upload {
+    pub upload_id: Uuid,
+    pub index_uid: String,
+    /// The number of bytes of the payload received so far, which is the offset of the next chunk.
+    pub size: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadMeta {
+    index_uid: String,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+}
+
+/// Stores the uploads under `updates/uploads`, each in a directory holding its payload and the
+/// index it's uploaded to.
+#[derive(Clone, Debug)]
+pub struct UploadStore {
+    path: PathBuf,
+    /// Held while a chunk is written to an upload or its payload is read, so the chunks of an
+    /// upload are appended one at a time.
+    locks: Arc<parking_lot::Mutex<HashMap<Uuid, Arc<Mutex<()>>>>>,
+    /// The size the payload of an upload can't outgrow.
+    max_size: Option<u64>,
+}
+
+impl UploadStore {
+    /// The directory of the uploads is created along with the first upload.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().join(UPLOADS_PATH),
+            locks: Default::default(),
+            max_size: None,
+        }
+    }
+
+    pub fn set_max_size(&mut self, max_size: u64) {
+        self.max_size = Some(max_size);
+    }
+
+    pub async fn create(&self, index_uid: IndexUid) -> Result<Upload> {
+        let upload_id = Uuid::new_v4();
+        let path = self.path.join(upload_id.to_string());
+        fs::create_dir_all(&path).await?;
+
+        let meta = UploadMeta {
+            index_uid: index_uid.into_inner(),
+            created_at: OffsetDateTime::now_utc(),
+        };
+        fs::write(path.join(UPLOAD_META_FILE), serde_json::to_vec(&meta)?).await?;
+        fs::File::create(path.join(UPLOAD_PAYLOAD_FILE)).await?;
+
+        Ok(Upload {
+            upload_id,
+            index_uid: meta.index_uid,
+            size: 0,
+            created_at: meta.created_at,
+        })
+    }
+
+    /// Returns the upload, if it was created for the index.
+    pub async fn get(&self, index_uid: &str, upload_id: Uuid) -> Result<Upload> {
+        let path = self.path.join(upload_id.to_string());
+        let meta = match fs::read(path.join(UPLOAD_META_FILE)).await {
+            Ok(meta) => serde_json::from_slice::<UploadMeta>(&meta)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(UploadStoreError::UploadNotFound(upload_id))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if meta.index_uid != index_uid {
+            return Err(UploadStoreError::UploadNotFound(upload_id));
+        }
+        let size = fs::metadata(path.join(UPLOAD_PAYLOAD_FILE)).await?.len();
+
+        Ok(Upload {
+            upload_id,
+            index_uid: meta.index_uid,
+            size,
+            created_at: meta.created_at,
+        })
+    }
+
+    /// Appends the chunk to the payload of the upload. The chunk must start where the payload
+    /// ends; the bytes received before the chunk is interrupted are kept, so the upload resumes
+    /// from the size of the payload. The bytes that would make the payload outgrow the maximum
+    /// size are refused.
+    pub async fn append(
+        &self,
+        index_uid: &str,
+        upload_id: Uuid,
+        offset: u64,
+        mut chunk: Payload,
+    ) -> Result<Upload> {
+        let _guard = self.lock(upload_id).await;
+        let mut upload = self.get(index_uid, upload_id).await?;
+        if offset != upload.size {
+            return Err(UploadStoreError::InvalidOffset {
+                offset,
+                size: upload.size,
+            });
+        }
+
+        let path = self.path.join(upload_id.to_string());
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .open(path.join(UPLOAD_PAYLOAD_FILE))
+            .await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+
+        let mut result = Ok(());
+        while let Some(bytes) = chunk.next().await {
+            match bytes {
+                Ok(bytes) if self.exceeds_max_size(upload.size + bytes.len() as u64) => {
+                    result = Err(UploadStoreError::PayloadTooLarge);
+                    break;
+                }
+                Ok(bytes) => {
+                    file.write_all(&bytes).await?;
+                    upload.size += bytes.len() as u64;
+                }
+                Err(e) => {
+                    result = Err(e.into());
+                    break;
+                }
+            }
+        }
+        file.flush().await?;
+        file.sync_all().await?;
+
+        result.map(|()| upload)
+    }
+
+    fn exceeds_max_size(&self, size: u64) -> bool {
+        self.max_size.map_or(false, |max| size > max)
+    }
+
+    /// Returns the assembled payload of the upload. No chunk is appended to the upload until the
+    /// payload is read.
+    pub async fn payload(&self, index_uid: &str, upload_id: Uuid) -> Result<Payload> {
+        let guard = self.lock(upload_id).await;
+        self.get(index_uid, upload_id).await?;
+
+        let path = self.path.join(upload_id.to_string());
+        let file = fs::File::open(path.join(UPLOAD_PAYLOAD_FILE)).await?;
+        let stream = futures::stream::unfold(Some((file, guard)), |state| async move {
+            let (mut file, guard) = state?;
+            let mut buffer = vec![0; UPLOAD_READ_CHUNK_SIZE];
+            match file.read(&mut buffer).await {
+                Ok(0) => None,
+                Ok(len) => {
+                    buffer.truncate(len);
+                    Some((Ok(Bytes::from(buffer)), Some((file, guard))))
+                }
+                Err(e) => Some((Err(PayloadError::Io(e)), None)),
+            }
+        });
+
+        Ok(Box::new(Box::pin(stream)))
+    }
+
+    pub async fn delete(&self, index_uid: &str, upload_id: Uuid) -> Result<()> {
+        let _guard = self.lock(upload_id).await;
+        self.get(index_uid, upload_id).await?;
+        fs::remove_dir_all(self.path.join(upload_id.to_string())).await?;
+        self.locks.lock().remove(&upload_id);
+
+        Ok(())
+    }
+
+    /// Deletes the uploads that received no chunk since `before`, and returns the number of
+    /// deleted uploads.
+    pub async fn delete_abandoned(&self, before: OffsetDateTime) -> Result<usize> {
+        let mut entries = match fs::read_dir(&self.path).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut count = 0;
+        while let Some(entry) = entries.next_entry().await? {
+            let upload_id = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+                Some(upload_id) => upload_id,
+                None => continue,
+            };
+
+            let _guard = self.lock(upload_id).await;
+            let path = entry.path();
+            let modified = match fs::metadata(path.join(UPLOAD_PAYLOAD_FILE)).await {
+                Ok(metadata) => OffsetDateTime::from(metadata.modified()?),
+                // the upload was deleted in the meantime
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if modified < before {
+                fs::remove_dir_all(&path).await?;
+                self.locks.lock().remove(&upload_id);
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    async fn lock(&self, upload_id: Uuid) -> OwnedMutexGuard<()> {
+        let lock = self.locks.lock().entry(upload_id).or_default().clone();
+        lock.lock_owned().await
+    }
+}
+
+/// Periodically deletes the uploads that received no chunk for more than `retention`.
+pub struct UploadRetentionService {
+    pub(crate) upload_store: UploadStore,
+    pub(crate) retention: Duration,
+}
+
+impl UploadRetentionService {
+    pub async fn run(self) {
+        info!(
+            "Uploads will be deleted {}s after their last chunk.",
+            self.retention.as_secs()
+        );
+        loop {
+            let before = OffsetDateTime::now_utc() - self.retention;
+            match self.upload_store.delete_abandoned(before).await {
+                Ok(0) => (),
+                Ok(count) => info!("Deleted {} abandoned uploads.", count),
+                Err(e) => error!("Error while deleting the abandoned uploads: {}", e),
+            }
+            sleep(self.retention.min(RETENTION_INTERVAL)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures::stream;
+
+    use super::*;
+
+    fn chunk(bytes: &'static [u8]) -> Payload {
+        Box::new(stream::iter(vec![Ok(Bytes::from_static(bytes))]))
+    }
+
+    #[actix_rt::test]
+    async fn test_resume_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = UploadStore::new(dir.path());
+        let upload = store.create(IndexUid::new_unchecked("test")).await.unwrap();
+        let id = upload.upload_id;
+
+        // the connection is lost after the first bytes of the chunk
+        let interrupted: Payload = Box::new(stream::iter(vec![
+            Ok(Bytes::from_static(b"[{\"id\": 1},")),
+            Err(PayloadError::Incomplete(None)),
+        ]));
+        assert!(store.append("test", id, 0, interrupted).await.is_err());
+        assert_eq!(store.get("test", id).await.unwrap().size, 11);
+
+        assert!(matches!(
+            store.append("test", id, 0, chunk(b"")).await,
+            Err(UploadStoreError::InvalidOffset {
+                offset: 0,
+                size: 11
+            })
+        ));
+        let upload = store
+            .append("test", id, 11, chunk(b" {\"id\": 2}]"))
+            .await
+            .unwrap();
+        assert_eq!(upload.size, 22);
+
+        assert!(matches!(
+            store.get("other", id).await,
+            Err(UploadStoreError::UploadNotFound(_))
+        ));
+
+        let payload: Vec<_> = store.payload("test", id).await.unwrap().collect().await;
+        let payload: Vec<u8> = payload
+            .into_iter()
+            .flat_map(|bytes| bytes.unwrap().to_vec())
+            .collect();
+        assert_eq!(payload, b"[{\"id\": 1}, {\"id\": 2}]");
+
+        store.delete("test", id).await.unwrap();
+        assert!(matches!(
+            store.get("test", id).await,
+            Err(UploadStoreError::UploadNotFound(_))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_upload_max_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = UploadStore::new(dir.path());
+        store.set_max_size(16);
+        let upload = store.create(IndexUid::new_unchecked("test")).await.unwrap();
+        let id = upload.upload_id;
+
+        store
+            .append("test", id, 0, chunk(b"[{\"id\": 1},"))
+            .await
+            .unwrap();
+        // the chunks of the upload add up to more than the maximum size
+        assert!(matches!(
+            store.append("test", id, 11, chunk(b" {\"id\": 2}]")).await,
+            Err(UploadStoreError::PayloadTooLarge)
+        ));
+        assert_eq!(store.get("test", id).await.unwrap().size, 11);
+    }
+
+    #[actix_rt::test]
+    async fn test_delete_abandoned_uploads() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = UploadStore::new(dir.path());
+        assert_eq!(
+            store
+                .delete_abandoned(OffsetDateTime::now_utc())
+                .await
+                .unwrap(),
+            0
+        );
+
+        let upload = store.create(IndexUid::new_unchecked("test")).await.unwrap();
+        let id = upload.upload_id;
+
+        // the upload received a chunk since an hour ago
+        let an_hour_ago = OffsetDateTime::now_utc() - time::Duration::hours(1);
+        assert_eq!(store.delete_abandoned(an_hour_ago).await.unwrap(), 0);
+        assert!(store.get("test", id).await.is_ok());
+
+        let later = OffsetDateTime::now_utc() + time::Duration::seconds(1);
+        assert_eq!(store.delete_abandoned(later).await.unwrap(), 1);
+        assert!(matches!(
+            store.get("test", id).await,
+            Err(UploadStoreError::UploadNotFound(_))
+        ));
+    }
+}
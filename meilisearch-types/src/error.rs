@@ -20,6 +20,22 @@ pub struct ResponseError {
     error_type: String,
     #[serde(rename = "link")]
     error_link: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    details: Option<ErrorDetails>,
+}
+
+/// Points to the part of the request causing the error.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "test-traits", derive(proptest_derive::Arbitrary))]
+pub struct ErrorDetails {
+    /// The offset, in characters, of the first character of the erroneous part.
+    pub start: usize,
+    /// The offset, in characters, following the last character of the erroneous part.
+    pub end: usize,
+    /// A replacement for the erroneous part, e.g. the closest filterable attribute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
 }
 
 impl ResponseError {
@@ -30,8 +46,14 @@ impl ResponseError {
             error_code: code.err_code().error_name.to_string(),
             error_type: code.type_(),
             error_link: code.url(),
+            details: None,
         }
     }
+
+    /// Returns the name of the error code, e.g. `index_not_found`.
+    pub fn error_code(&self) -> &str {
+        &self.error_code
+    }
 }
 
 impl fmt::Display for ResponseError {
@@ -53,6 +75,7 @@ where
             error_code: other.error_name(),
             error_type: other.error_type(),
             error_link: other.error_url(),
+            details: other.error_details(),
         }
     }
 }
@@ -92,6 +115,11 @@ pub trait ErrorCode: std::error::Error {
     fn error_type(&self) -> String {
         self.error_code().type_()
     }
+
+    /// returns the part of the request causing the error, when it's known
+    fn error_details(&self) -> Option<ErrorDetails> {
+        None
+    }
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -140,6 +168,7 @@ pub enum Code {
     DocumentNotFound,
     Internal,
     InvalidGeoField,
+    InvalidDocumentAcl,
     InvalidRankingRule,
     InvalidStore,
     InvalidToken,
@@ -168,8 +197,66 @@ pub enum Code {
     InvalidApiKeyDescription,
     InvalidApiKeyName,
     InvalidApiKeyUid,
+    InvalidApiKeyQuota,
     ImmutableField,
     ApiKeyAlreadyExists,
+
+    InstanceDraining,
+
+    UpdateFileCorrupted,
+    TaskProcessingAborted,
+    TaskNotProcessing,
+
+    InvalidFacetSearchFacetName,
+    InvalidSearchVector,
+    InvalidSearchSemanticRatio,
+    InvalidSearchAttributesToSearchOn,
+    InvalidSearchAttributeWeights,
+    InvalidSearchDistinct,
+
+    InvalidRankingExperiment,
+    InvalidRules,
+    InvalidDocumentSchema,
+    InvalidIngestionPipeline,
+    InvalidNestedFlattening,
+    InvalidAttachments,
+    AttachmentExtractionFailed,
+    InvalidStopWordsPreset,
+
+    InvalidDocumentEditionFunction,
+    DocumentEditionFailed,
+    DocumentEditionDisabled,
+
+    InvalidDocumentCsvDelimiter,
+    InvalidDocumentXmlRecordPath,
+    InvalidDocumentMergeNested,
+    InvalidDocumentOperator,
+    DocumentSchemaViolation,
+    DocumentAlreadyExists,
+
+    UploadNotFound,
+    InvalidUploadOffset,
+
+    InvalidIdempotencyKey,
+
+    SearchLogDisabled,
+
+    TooManySearchRequests,
+
+    InvalidSwapIndexes,
+
+    AliasNotFound,
+    InvalidAlias,
+
+    IndexTemplateNotFound,
+    InvalidIndexTemplate,
+    IndexFull,
+    IndexFrozen,
+
+    SettingsVersionNotFound,
+
+    IndexQuotaExceeded,
+    DocumentQuotaExceeded,
 }
 
 impl Code {
@@ -227,6 +314,8 @@ impl Code {
             DocumentNotFound => ErrCode::invalid("document_not_found", StatusCode::NOT_FOUND),
             Internal => ErrCode::internal("internal", StatusCode::INTERNAL_SERVER_ERROR),
             InvalidGeoField => ErrCode::invalid("invalid_geo_field", StatusCode::BAD_REQUEST),
+            // thrown when the `_acl` field of a document isn't an array of strings
+            InvalidDocumentAcl => ErrCode::invalid("invalid_document_acl", StatusCode::BAD_REQUEST),
             InvalidToken => ErrCode::authentication("invalid_api_key", StatusCode::FORBIDDEN),
             MissingAuthorizationHeader => {
                 ErrCode::authentication("missing_authorization_header", StatusCode::UNAUTHORIZED)
@@ -278,11 +367,180 @@ impl Code {
             }
             InvalidApiKeyName => ErrCode::invalid("invalid_api_key_name", StatusCode::BAD_REQUEST),
             InvalidApiKeyUid => ErrCode::invalid("invalid_api_key_uid", StatusCode::BAD_REQUEST),
+            InvalidApiKeyQuota => {
+                ErrCode::invalid("invalid_api_key_quota", StatusCode::BAD_REQUEST)
+            }
             ApiKeyAlreadyExists => ErrCode::invalid("api_key_already_exists", StatusCode::CONFLICT),
             ImmutableField => ErrCode::invalid("immutable_field", StatusCode::BAD_REQUEST),
             InvalidMinWordLengthForTypo => {
                 ErrCode::invalid("invalid_min_word_length_for_typo", StatusCode::BAD_REQUEST)
             }
+
+            // thrown when a task is registered while the instance is draining
+            InstanceDraining => {
+                ErrCode::internal("instance_draining", StatusCode::SERVICE_UNAVAILABLE)
+            }
+
+            // thrown when the content of a document addition can't be read back from disk
+            UpdateFileCorrupted => {
+                ErrCode::internal("update_file_corrupted", StatusCode::INTERNAL_SERVER_ERROR)
+            }
+            // thrown when the watchdog gives up on a task that panicked or got stuck, or when the
+            // processing of a task is aborted through the abort route
+            TaskProcessingAborted => {
+                ErrCode::internal("task_processing_aborted", StatusCode::INTERNAL_SERVER_ERROR)
+            }
+            // thrown when aborting a task that isn't being processed
+            TaskNotProcessing => ErrCode::invalid("task_not_processing", StatusCode::CONFLICT),
+
+            // thrown when searching the values of a facet that isn't filterable
+            InvalidFacetSearchFacetName => {
+                ErrCode::invalid("invalid_facet_search_facet_name", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the vector of a search is empty or can't be used with the other
+            // parameters
+            InvalidSearchVector => {
+                ErrCode::invalid("invalid_search_vector", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the semantic ratio of a hybrid search isn't between 0 and 1
+            InvalidSearchSemanticRatio => {
+                ErrCode::invalid("invalid_search_semantic_ratio", StatusCode::BAD_REQUEST)
+            }
+            // thrown when a search is restricted to an attribute that isn't searchable
+            InvalidSearchAttributesToSearchOn => ErrCode::invalid(
+                "invalid_search_attributes_to_search_on",
+                StatusCode::BAD_REQUEST,
+            ),
+            // thrown when the weight of an attribute of a search isn't a positive number
+            InvalidSearchAttributeWeights => {
+                ErrCode::invalid("invalid_search_attribute_weights", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the distinct parameter of a search keeps no hit per group
+            InvalidSearchDistinct => {
+                ErrCode::invalid("invalid_search_distinct", StatusCode::BAD_REQUEST)
+            }
+
+            // thrown when no variant of the ranking experiment of an index would serve a search
+            InvalidRankingExperiment => {
+                ErrCode::invalid("invalid_ranking_experiment", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the rules of an index have an empty or duplicated id, or an invalid
+            // boost filter
+            InvalidRules => ErrCode::invalid("invalid_rules", StatusCode::BAD_REQUEST),
+            // thrown when the document schema of an index isn't a valid JSON Schema
+            InvalidDocumentSchema => {
+                ErrCode::invalid("invalid_document_schema", StatusCode::BAD_REQUEST)
+            }
+            // thrown when a step of the ingestion pipeline of an index has an empty field or
+            // separator
+            InvalidIngestionPipeline => {
+                ErrCode::invalid("invalid_ingestion_pipeline", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the nested flattening of an index has a `maxDepth` of 0
+            InvalidNestedFlattening => {
+                ErrCode::invalid("invalid_nested_flattening", StatusCode::BAD_REQUEST)
+            }
+            // thrown when an attachment field of an index, or the field storing its text, is empty
+            // or is another attachment field
+            InvalidAttachments => ErrCode::invalid("invalid_attachments", StatusCode::BAD_REQUEST),
+            // thrown when the text of an attachment of an added document can't be extracted
+            AttachmentExtractionFailed => {
+                ErrCode::invalid("attachment_extraction_failed", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the stop words of an index refer to a preset that doesn't exist
+            InvalidStopWordsPreset => {
+                ErrCode::invalid("invalid_stop_words_preset", StatusCode::BAD_REQUEST)
+            }
+
+            // thrown when the function of a document edition doesn't compile
+            InvalidDocumentEditionFunction => {
+                ErrCode::invalid("invalid_document_edition_function", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the function of a document edition fails on a document, or changes its
+            // primary key
+            DocumentEditionFailed => {
+                ErrCode::invalid("document_edition_failed", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the document edition isn't compiled in
+            DocumentEditionDisabled => {
+                ErrCode::invalid("document_edition_disabled", StatusCode::BAD_REQUEST)
+            }
+
+            // thrown when the csv delimiter of a document addition isn't a single ASCII character,
+            // or the payload isn't csv
+            InvalidDocumentCsvDelimiter => {
+                ErrCode::invalid("invalid_document_csv_delimiter", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the record path of an xml document addition is missing or invalid, or
+            // the payload isn't xml
+            InvalidDocumentXmlRecordPath => {
+                ErrCode::invalid("invalid_document_xml_record_path", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the nested objects merge is requested on a document addition that
+            // replaces the documents
+            InvalidDocumentMergeNested => {
+                ErrCode::invalid("invalid_document_merge_nested", StatusCode::BAD_REQUEST)
+            }
+            // thrown when the `_inc` or `_dec` operator of a document update isn't applied to
+            // numbers
+            InvalidDocumentOperator => {
+                ErrCode::invalid("invalid_document_operator", StatusCode::BAD_REQUEST)
+            }
+            // thrown when a document of an addition doesn't match the document schema of the index
+            DocumentSchemaViolation => {
+                ErrCode::invalid("document_schema_violation", StatusCode::BAD_REQUEST)
+            }
+            // thrown when a document of an addition failing on duplicates has the id of a stored
+            // document
+            DocumentAlreadyExists => {
+                ErrCode::invalid("document_already_exists", StatusCode::CONFLICT)
+            }
+
+            // thrown when an upload doesn't exist, or was created for another index
+            UploadNotFound => ErrCode::invalid("upload_not_found", StatusCode::NOT_FOUND),
+            // thrown when a chunk of an upload doesn't start where the uploaded payload ends
+            InvalidUploadOffset => ErrCode::invalid("invalid_upload_offset", StatusCode::CONFLICT),
+
+            // thrown when the `Idempotency-Key` header of a write request is empty, too long, or
+            // not visible ASCII
+            InvalidIdempotencyKey => {
+                ErrCode::invalid("invalid_idempotency_key", StatusCode::BAD_REQUEST)
+            }
+
+            // thrown when reading the search log of an instance where it isn't enabled
+            SearchLogDisabled => ErrCode::invalid("search_log_disabled", StatusCode::BAD_REQUEST),
+            // thrown when a search is received while the search queue is full
+            TooManySearchRequests => {
+                ErrCode::internal("too_many_search_requests", StatusCode::SERVICE_UNAVAILABLE)
+            }
+            // thrown when an index is part of several pairs of a swap
+            InvalidSwapIndexes => ErrCode::invalid("invalid_swap_indexes", StatusCode::BAD_REQUEST),
+            // thrown when the alias is unknown
+            AliasNotFound => ErrCode::invalid("alias_not_found", StatusCode::NOT_FOUND),
+            // thrown when an alias is named after an index
+            InvalidAlias => ErrCode::invalid("invalid_alias", StatusCode::BAD_REQUEST),
+            // thrown when no template has the pattern
+            IndexTemplateNotFound => {
+                ErrCode::invalid("index_template_not_found", StatusCode::NOT_FOUND)
+            }
+            // thrown when the pattern of a template is malformed
+            InvalidIndexTemplate => {
+                ErrCode::invalid("invalid_index_template", StatusCode::BAD_REQUEST)
+            }
+            // thrown when a write doesn't fit in the map of the index
+            IndexFull => ErrCode::internal("index_full", StatusCode::INTERNAL_SERVER_ERROR),
+            // thrown when a task modifies a frozen index
+            IndexFrozen => ErrCode::invalid("index_frozen", StatusCode::BAD_REQUEST),
+            // thrown when a rollback targets a version missing from the settings history
+            SettingsVersionNotFound => {
+                ErrCode::invalid("settings_version_not_found", StatusCode::NOT_FOUND)
+            }
+            // thrown when an index is created past the limit of the instance or of the key
+            IndexQuotaExceeded => ErrCode::invalid("index_quota_exceeded", StatusCode::FORBIDDEN),
+            // thrown when documents are added past the limit of the instance or of the key
+            DocumentQuotaExceeded => {
+                ErrCode::invalid("document_quota_exceeded", StatusCode::FORBIDDEN)
+            }
         }
     }
 
@@ -292,7 +550,7 @@ impl Code {
     }
 
     /// return error name, used as error code
-    fn name(&self) -> String {
+    pub fn name(&self) -> String {
         self.err_code().error_name.to_string()
     }
 
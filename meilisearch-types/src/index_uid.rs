@@ -3,8 +3,11 @@ use std::error::Error;
 use std::fmt;
 use std::str::FromStr;
 
+/// Separates the namespace of an index uid from the name of the index, as in `tenant-a/products`.
+pub const NAMESPACE_SEPARATOR: char = '/';
+
 /// An index uid is composed of only ascii alphanumeric characters, - and _, between 1 and 400
-/// bytes long
+/// bytes long. It can be prefixed by a namespace, made of the same characters, and a `/`.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "test-traits", derive(proptest_derive::Arbitrary))]
 pub struct IndexUid(
@@ -24,6 +27,33 @@ impl IndexUid {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Returns the namespace of the index, if it belongs to one.
+    pub fn namespace(&self) -> Option<&str> {
+        namespace(&self.0)
+    }
+}
+
+/// Returns the namespace of the index `uid`, if it belongs to one.
+pub fn namespace(uid: &str) -> Option<&str> {
+    uid.split_once(NAMESPACE_SEPARATOR)
+        .map(|(namespace, _)| namespace)
+}
+
+/// Whether the index `uid` is targeted by `pattern`, which is either an index uid or a
+/// `<namespace>/*` pattern targeting every index of a namespace.
+pub fn matches_pattern(pattern: &str, uid: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(pattern_namespace) => namespace(uid) == Some(pattern_namespace),
+        None => pattern == uid,
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_')
 }
 
 impl std::ops::Deref for IndexUid {
@@ -38,12 +68,11 @@ impl TryFrom<String> for IndexUid {
     type Error = IndexUidFormatError;
 
     fn try_from(uid: String) -> Result<Self, Self::Error> {
-        if !uid
-            .chars()
-            .all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_')
-            || uid.is_empty()
-            || uid.len() > 400
-        {
+        let is_valid = match uid.split_once(NAMESPACE_SEPARATOR) {
+            Some((namespace, name)) => is_valid_name(namespace) && is_valid_name(name),
+            None => is_valid_name(&uid),
+        };
+        if !is_valid || uid.len() > 400 {
             Err(IndexUidFormatError { invalid_uid: uid })
         } else {
             Ok(IndexUid(uid))
@@ -76,10 +105,86 @@ impl fmt::Display for IndexUidFormatError {
             f,
             "invalid index uid `{}`, the uid must be an integer \
             or a string containing only alphanumeric characters \
-            a-z A-Z 0-9, hyphens - and underscores _.",
+            a-z A-Z 0-9, hyphens - and underscores _, optionally \
+            prefixed by a namespace and a slash /.",
             self.invalid_uid,
         )
     }
 }
 
 impl Error for IndexUidFormatError {}
+
+/// Either an index uid, or a `<namespace>/*` pattern targeting every index of a namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexUidPattern(String);
+
+impl IndexUidPattern {
+    /// Returns the namespace targeted by the pattern, unless it's a single index uid.
+    pub fn namespace(&self) -> Option<&str> {
+        self.0.strip_suffix("/*")
+    }
+
+    /// Whether the index `uid` is targeted by the pattern.
+    pub fn matches(&self, uid: &str) -> bool {
+        matches_pattern(&self.0, uid)
+    }
+}
+
+impl std::ops::Deref for IndexUidPattern {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromStr for IndexUidPattern {
+    type Err = IndexUidFormatError;
+
+    fn from_str(pattern: &str) -> Result<IndexUidPattern, IndexUidFormatError> {
+        match pattern.strip_suffix("/*") {
+            Some(namespace) if is_valid_name(namespace) => Ok(IndexUidPattern(pattern.to_string())),
+            _ => IndexUid::from_str(pattern).map(|uid| IndexUidPattern(uid.into_inner())),
+        }
+    }
+}
+
+impl From<IndexUidPattern> for String {
+    fn from(pattern: IndexUidPattern) -> Self {
+        pattern.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn namespaced_index_uid() {
+        let uid = IndexUid::from_str("tenant-a/products").unwrap();
+        assert_eq!(uid.namespace(), Some("tenant-a"));
+        assert_eq!(IndexUid::from_str("products").unwrap().namespace(), None);
+
+        assert!(IndexUid::from_str("tenant-a/").is_err());
+        assert!(IndexUid::from_str("/products").is_err());
+        assert!(IndexUid::from_str("tenant-a/b/products").is_err());
+        assert!(IndexUid::from_str("tenant-a/*").is_err());
+    }
+
+    #[test]
+    fn index_uid_pattern() {
+        let pattern = IndexUidPattern::from_str("tenant-a/*").unwrap();
+        assert_eq!(pattern.namespace(), Some("tenant-a"));
+        assert!(pattern.matches("tenant-a/products"));
+        assert!(!pattern.matches("tenant-b/products"));
+        assert!(!pattern.matches("tenant-a"));
+
+        let pattern = IndexUidPattern::from_str("tenant-a/products").unwrap();
+        assert_eq!(pattern.namespace(), None);
+        assert!(pattern.matches("tenant-a/products"));
+        assert!(!pattern.matches("tenant-a/movies"));
+
+        assert!(IndexUidPattern::from_str("*/*").is_err());
+        assert!(IndexUidPattern::from_str("tenant-a/b/*").is_err());
+    }
+}